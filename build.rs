@@ -1,9 +1,49 @@
 fn main() {
+    build_info();
+    // The host-test build links a normal host binary
+    if std::env::var("CARGO_CFG_TARGET_ARCH").as_deref() != Ok("xtensa") {
+        return;
+    }
     linker_be_nice();
+    if std::env::var_os("CARGO_FEATURE_DEFMT").is_some() {
+        println!("cargo:rustc-link-arg=-Tdefmt.x");
+    }
     // make sure linkall.x is the last linker script (otherwise might cause problems with flip-link)
     println!("cargo:rustc-link-arg=-Tlinkall.x");
 }
 
+// Exposed to the firmware through env!() for DeviceInfo
+fn build_info() {
+    let git_hash = std::process::Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let build_timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    // Resolved version, Cargo.toml only carries the requirement
+    let esp_hal_version = std::fs::read_to_string("Cargo.lock")
+        .ok()
+        .and_then(|lock| {
+            let package = lock.split("[[package]]").find(|package| package.contains("name = \"esp-hal\"\n"))?;
+            let version = package.lines().find_map(|line| line.strip_prefix("version = "))?;
+            Some(version.trim_matches('"').to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=GIT_HASH={}", git_hash);
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={}", build_timestamp);
+    println!("cargo:rustc-env=ESP_HAL_VERSION={}", esp_hal_version);
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+    println!("cargo:rerun-if-changed=Cargo.lock");
+}
+
 fn linker_be_nice() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() > 1 {
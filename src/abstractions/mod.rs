@@ -1,5 +1,8 @@
 pub mod factory;
 pub mod pipeline;
+pub mod queue;
 pub mod sensor;
 pub mod service;
+pub mod store;
+pub mod transport;
 pub mod utility;
\ No newline at end of file
@@ -4,6 +4,7 @@ use alloc::boxed::Box;
 use core::error::Error;
 
 use crate::abstractions::sensor::ISensor;
+use crate::dtos::pipeline::output::PipelineOutputDTO;
 use crate::enums::value::Value;
 
 pub trait IPipeline<T> {
@@ -11,4 +12,11 @@ pub trait IPipeline<T> {
     fn device_urn(&self) -> String;
     fn location_urn(&self) -> String;
     fn run(&self, sensor: &dyn ISensor<T>) -> Result<BTreeMap<String, Value>, Box<dyn Error + Send + Sync>>;
-}
\ No newline at end of file
+}
+
+// One step of a chain built by `PipelineFactory`. Stages keep state between
+// readings (windows, history), hence `&mut self`
+pub trait IPipelineStage {
+    fn name(&self) -> String;
+    fn process(&mut self, output: &mut PipelineOutputDTO);
+}
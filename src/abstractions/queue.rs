@@ -0,0 +1,25 @@
+use alloc::string::String;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::error::Error;
+
+use crate::dtos::configurations::retention::RetentionConfigDTO;
+
+pub trait IQueue {
+    fn urn(&self) -> String;
+    fn device_urn(&self) -> String;
+    fn location_urn(&self) -> String;
+    fn push(&mut self, payload: &[u8]) -> Result<(), Box<dyn Error + Send + Sync>>;
+    fn peek(&mut self) -> Result<Option<Vec<u8>>, Box<dyn Error + Send + Sync>>;
+    fn pop(&mut self) -> Result<(), Box<dyn Error + Send + Sync>>;
+    // Up to `max_records` records after the first `skip`, oldest first,
+    // stopping before the one that would take the total past `max_bytes`
+    // unless it is the first
+    fn peek_many(&mut self, skip: usize, max_records: usize, max_bytes: usize) -> Result<Vec<Vec<u8>>, Box<dyn Error + Send + Sync>>;
+    fn pop_many(&mut self, count: usize) -> Result<(), Box<dyn Error + Send + Sync>>;
+    fn len(&self) -> usize;
+    fn flush(&mut self) -> Result<(), Box<dyn Error + Send + Sync>>;
+    // Drops the oldest records past any limit of `retention`, `now` in Unix
+    // seconds. Returns the records dropped
+    fn compact(&mut self, retention: &RetentionConfigDTO, now: u64) -> Result<usize, Box<dyn Error + Send + Sync>>;
+}
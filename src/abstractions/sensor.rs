@@ -1,10 +1,23 @@
+use alloc::boxed::Box;
 use alloc::string::String;
-use alloc::fmt::Error;
+use core::future::Future;
+use core::pin::Pin;
+
+use crate::enums::sensor_error::SensorError;
 
 pub trait ISensor<T> {
     fn urn(&self) -> String;
     fn device_urn(&self) -> String;
     fn location_urn(&self) -> String;
     fn name(&self) -> String;
-    fn read(&self) -> Result<T, Error>;
+    fn read(&self) -> Result<T, SensorError>;
+
+    // Boxed so sensors stay usable as trait objects. Sensors that can await
+    // their conversion or bus override this, which lets reads on other buses
+    // run meanwhile and a slow device be timed out. The default runs `read`
+    // to completion: the other buses wait and the timeout only takes effect
+    // once it has returned
+    fn read_async(&self) -> Pin<Box<dyn Future<Output = Result<T, SensorError>> + '_>> {
+        Box::pin(async move { self.read() })
+    }
 }
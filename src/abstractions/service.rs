@@ -1,11 +1,17 @@
+use alloc::boxed::Box;
 use alloc::string::String;
-use alloc::fmt::Error;
+use core::error::Error;
+use core::future::Future;
 
-use crate::dtos::response::base::BaseResponseDTO;
+pub trait IService {
+    // What one run produces, e.g. the readings of a sensing cycle
+    type Response;
 
-pub trait IService<T> {
     fn urn(&self) -> String;
     fn device_urn(&self) -> String;
     fn location_urn(&self) -> String;
-    fn run(&self) -> Result<BaseResponseDTO, Error>;
-}
\ No newline at end of file
+    // Spelled out so the bounds are explicit: not `Send`, services hold
+    // RefCells and stay on the executor of the main core. Implementations
+    // may still write `async fn run`
+    fn run(&mut self) -> impl Future<Output = Result<Self::Response, Box<dyn Error + Send + Sync>>>;
+}
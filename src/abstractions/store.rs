@@ -0,0 +1,22 @@
+use alloc::string::String;
+use alloc::boxed::Box;
+use core::error::Error;
+
+use crate::dtos::configurations::retention::RetentionConfigDTO;
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+
+pub trait IStore {
+    fn urn(&self) -> String;
+    fn device_urn(&self) -> String;
+    fn location_urn(&self) -> String;
+    fn append(&mut self, envelope: &MeasurementEnvelopeDTO) -> Result<(), Box<dyn Error + Send + Sync>>;
+    fn export_csv(
+        &mut self,
+        from: u64,
+        to: u64,
+        sink: &mut dyn FnMut(&str) -> Result<(), Box<dyn Error + Send + Sync>>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>>;
+    // Deletes the oldest data past any limit of `retention`, `now` in Unix
+    // seconds. Returns the bytes freed
+    fn compact(&mut self, retention: &RetentionConfigDTO, now: u64) -> Result<u64, Box<dyn Error + Send + Sync>>;
+}
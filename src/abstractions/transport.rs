@@ -0,0 +1,21 @@
+use alloc::string::String;
+use core::future::Future;
+
+use crate::dtos::transport::ack::TransportAck;
+use crate::enums::transport_error::TransportError;
+
+pub trait ITransport {
+    fn urn(&self) -> String;
+    fn device_urn(&self) -> String;
+    fn location_urn(&self) -> String;
+    // Spelled out so the bounds are explicit: not `Send`, transports hold
+    // sockets and RefCells and stay on the executor of the main core.
+    // Implementations may still write `async fn send`
+    fn send(&mut self, payload: &[u8]) -> impl Future<Output = Result<TransportAck, TransportError>>;
+
+    // Transports without a separate event endpoint send events inline, the
+    // "event" field tells them apart from measurements
+    fn send_event(&mut self, payload: &[u8]) -> impl Future<Output = Result<TransportAck, TransportError>> {
+        self.send(payload)
+    }
+}
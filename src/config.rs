@@ -1,4 +1,56 @@
 use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::constants::aqi::AqiConstant;
+use crate::constants::baseline::BaselineConstant;
+use crate::constants::ble::BleConstant;
+use crate::constants::can::CanConstant;
+use crate::constants::cellular::CellularConstant;
+use crate::constants::clock::ClockConstant;
+use crate::constants::lora::LoRaConstant;
+use crate::constants::mesh::MeshConstant;
+use crate::constants::metrics::MetricsConstant;
+use crate::constants::modbus::ModbusConstant;
+use crate::constants::people_counter::PeopleCounterConstant;
+use crate::constants::profile::ProfileConstant;
+use crate::constants::retention::RetentionConstant;
+use crate::constants::rfid::RfidConstant;
+use crate::constants::sensor::SensorConstant;
+use crate::constants::storage::StorageConstant;
+use crate::constants::run_hours::RunHoursConstant;
+use crate::constants::tamper::TamperConstant;
+use crate::constants::thermal::ThermalConstant;
+use crate::constants::upload::UploadConstant;
+use crate::constants::wifi::WifiConstant;
+use crate::dtos::configurations::aqi::AqiConfigDTO;
+use crate::dtos::configurations::bh1750::Bh1750ConfigDTO;
+use crate::dtos::configurations::bme280::Bme280ConfigDTO;
+use crate::dtos::configurations::can::CanConfigDTO;
+use crate::dtos::configurations::inputs::InputsConfigDTO;
+use crate::dtos::configurations::ir::IrConfigDTO;
+use crate::dtos::configurations::modbus::ModbusConfigDTO;
+use crate::dtos::configurations::network::{StaticIpv4ConfigDTO, StaticIpv6ConfigDTO};
+use crate::dtos::configurations::retention::RetentionConfigDTO;
+use crate::dtos::configurations::schedule::ScheduleWindowDTO;
+use crate::dtos::configurations::sdi12::Sdi12ConfigDTO;
+use crate::dtos::configurations::tamper::TamperConfigDTO;
+use crate::dtos::configurations::timezone::TimeZoneDTO;
+use crate::dtos::configurations::wifi::{WifiEnterpriseDTO, WifiProfileDTO};
+use crate::dtos::response::server::config::ServerConfigResponseDTO;
+use crate::enums::bh1750_mode::Bh1750Mode;
+use crate::enums::bh1750_resolution::Bh1750Resolution;
+use crate::enums::bme280_mode::Bme280Mode;
+use crate::enums::compression::Compression;
+use crate::enums::log_format::LogFormat;
+use crate::enums::network_interface::NetworkInterface;
+use crate::enums::eap_method::EapMethod;
+use crate::enums::event_kind::EventKind;
+use crate::enums::node_mode::NodeMode;
+use crate::enums::sensing_profile::SensingProfile;
+use crate::enums::store_mode::StoreMode;
+use crate::enums::uplink::Uplink;
+use crate::services::{board_identity, hardware_profile};
+use crate::utilities::{aqi, can, hex, ipv4, ipv6, ir, modbus, retention, schedule, sdi12, thresholds, timezone, url};
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -6,18 +58,541 @@ pub struct Config {
     pub location_urn: String,
     pub wifi_ssid: String,
     pub wifi_password: String,
+    pub wifi_profiles: Vec<WifiProfileDTO>,
+    pub wifi_roam_rssi_dbm: i8,
     pub server_base_url: String,
+    pub sd_log_format: LogFormat,
+    pub sd_store_mode: StoreMode,
+    pub node_mode: NodeMode,
+    pub espnow_channel: u8,
+    pub espnow_gateway: [u8; 6],
+    pub espnow_peers: Vec<[u8; 6]>,
+    pub espnow_pmk: Option<[u8; 16]>,
+    pub espnow_lmk: Option<[u8; 16]>,
+    pub uplinks: Vec<Uplink>,
+    pub lora_frequency_hz: u32,
+    pub lora_spreading_factor: u8,
+    pub lora_tx_power_dbm: u8,
+    pub lora_duty_cycle_percent: u8,
+    pub cellular_apn: String,
+    pub cellular_user: String,
+    pub cellular_password: String,
+    pub network_interfaces: Vec<NetworkInterface>,
+    pub static_ipv6: Option<StaticIpv6ConfigDTO>,
+    pub static_ipv4: Option<StaticIpv4ConfigDTO>,
+    pub mqtt_broker_url: Option<String>,
+    pub mqtt_username: Option<String>,
+    pub mqtt_password: Option<String>,
+    pub upload_targets: Vec<String>,
+    pub udp_collector: Option<String>,
+    pub udp_ack: bool,
+    pub http_compression: Compression,
+    pub cellular_compression: Compression,
+    pub lora_compression: Compression,
+    pub batch_max_records: usize,
+    pub batch_max_age_s: u64,
+    // OTA is disabled without a signing key
+    pub ota_public_key: Option<[u8; 32]>,
+    pub ota_security_version: u32,
+    pub ota_pinned_version: Option<u32>,
+    pub sensor_interval_ms: u64,
+    // How long a remotely applied config has to produce a successful upload
+    pub config_grace_period_s: u64,
+    // Empty means sensing and uploads are never restricted
+    pub schedule: Vec<ScheduleWindowDTO>,
+    // Local time for schedules, SD file names and display; uploaded
+    // timestamps stay UTC
+    pub timezone: TimeZoneDTO,
+    // Disciplines the system clock and the DS3231 while online
+    pub sntp_server: String,
+    // Deep sleep between cycles, woken by the DS3231 alarm; None keeps the
+    // device awake at `sensor_interval_ms`
+    pub deep_sleep_interval_s: Option<u64>,
+    pub bh1750: Bh1750ConfigDTO,
+    pub bme280: Bme280ConfigDTO,
+    // Breakpoints and categories of the on-device air quality index
+    pub aqi: AqiConfigDTO,
+    // XSHUT GPIOs of a VL53L0X array in unit order; empty for a single
+    // sensor left at the default address
+    pub vl53l0x_xshut_gpios: Vec<u8>,
+    // Distance below which a people counter zone counts as occupied
+    pub people_counter_presence_mm: u16,
+    // Shock, tilt and light limits of `TamperService`
+    pub tamper: TamperConfigDTO,
+    // Vibration RMS above which the monitored machine counts as running
+    pub run_hours_rms_ms2: f32,
+    // Beacon MACs `BleScannerService` tracks; empty leaves BLE off
+    pub ble_beacons: Vec<[u8; 6]>,
+    pub ble_absent_after_s: u64,
+    // Broadcast the latest readings as BTHome, see `BleAdvertiserService`.
+    // It takes the BLE controller, so not together with beacon scanning
+    pub ble_advertise: bool,
+    // RS-485 bus and the meter registers `ModbusService` polls
+    pub modbus: ModbusConfigDTO,
+    // Probes on the SDI-12 bus, each read as its own virtual sensor
+    pub sdi12: Sdi12ConfigDTO,
+    // TWAI bitrate, filter and the signals `CanListenerService` decodes
+    pub can: CanConfigDTO,
+    // Pins and named codes of `IrService`
+    pub ir: IrConfigDTO,
+    // Buttons and rotary encoders feeding `inputs`
+    pub inputs: InputsConfigDTO,
+    // Badge UIDs that unlock the local endpoints, and for how long
+    pub rfid_authorized_uids: Vec<Vec<u8>>,
+    pub rfid_unlock_s: u64,
+    // Hottest pixel that makes `ThermalFrameService` upload a full frame,
+    // and how often one is sent regardless; 0 only on alerts
+    pub thermal_alert_c: f32,
+    pub thermal_frame_interval_s: u64,
+    // Sounds `AudioEventService` listens for; empty leaves the microphone off
+    pub audio_events: Vec<EventKind>,
+    // Lux from which `ScheduleBaselineService` counts a room as lit
+    pub baseline_lit_lux: f32,
+    // Cadence of the device health envelope from `MetricsService`
+    pub metrics_interval_s: u64,
+    // Limits on the persistent upload queue and the SD card log, enforced
+    // by `RetentionService`
+    pub queue_retention: RetentionConfigDTO,
+    pub sd_retention: RetentionConfigDTO,
+    // Profile selected at boot, see `ProfileService`
+    pub sensing_profile: SensingProfile,
+    // Battery charge below which the eco profile is forced, 0 disables
+    pub eco_battery_percent: u8,
+    // Blinks the boot stage, see `status_led::run`
+    pub status_led_gpio: Option<u8>,
+    // Chip select, interrupt and reset of a W5500 on the shared SPI bus;
+    // the Ethernet interface is only brought up with all three set
+    pub ethernet_cs_gpio: Option<u8>,
+    pub ethernet_int_gpio: Option<u8>,
+    pub ethernet_reset_gpio: Option<u8>,
+    // Chip select of the SD card on the shared SPI bus, no card without it
+    pub sd_cs_gpio: Option<u8>,
 }
 
 impl Config {
 
     pub fn new() -> Self {
+        // Factory calibration from the board EEPROM, see `board_identity`
+        let factory = board_identity::identity();
         Self {
             device_urn: option_env!("DEVICE_URN").expect("DEVICE_URN must be set").to_string(),
             location_urn: option_env!("LOCATION_URN").expect("LOCATION_URN must be set").to_string(),
             wifi_ssid: option_env!("WIFI_SSID").expect("WIFI_SSID must be set").to_string(),
             wifi_password: option_env!("WIFI_PASSWORD").expect("WIFI_PASSWORD must be set").to_string(),
-            server_base_url: option_env!("SEVER_BASE_URL").expect("SEVER_BASE_URL must be set").to_string()
+            wifi_profiles: wifi_profiles(),
+            wifi_roam_rssi_dbm: option_env!("WIFI_ROAM_RSSI_DBM")
+                .map(|value| value.parse().expect("WIFI_ROAM_RSSI_DBM must be a negative integer"))
+                .unwrap_or(WifiConstant::DEFAULT_ROAM_RSSI_DBM),
+            server_base_url: option_env!("SEVER_BASE_URL").expect("SEVER_BASE_URL must be set").to_string(),
+            sd_log_format: LogFormat::parse(option_env!("SD_LOG_FORMAT").unwrap_or(StorageConstant::DEFAULT_LOG_FORMAT))
+                .expect("SD_LOG_FORMAT must be csv or jsonl"),
+            sd_store_mode: StoreMode::parse(option_env!("SD_STORE_MODE").unwrap_or(StorageConstant::DEFAULT_STORE_MODE))
+                .expect("SD_STORE_MODE must be primary, backup or disabled"),
+            node_mode: NodeMode::parse(option_env!("NODE_MODE").unwrap_or(MeshConstant::DEFAULT_NODE_MODE))
+                .expect("NODE_MODE must be standalone, leaf or gateway"),
+            espnow_channel: option_env!("ESPNOW_CHANNEL")
+                .map(|value| value.parse().expect("ESPNOW_CHANNEL must be 1-14"))
+                .unwrap_or(MeshConstant::DEFAULT_CHANNEL),
+            // Leaves broadcast unencrypted unless they know their gateway
+            espnow_gateway: option_env!("ESPNOW_GATEWAY")
+                .map(|value| hex::parse_mac(value).expect("ESPNOW_GATEWAY must be a MAC address"))
+                .unwrap_or(MeshConstant::BROADCAST_ADDRESS),
+            espnow_peers: option_env!("ESPNOW_PEERS")
+                .unwrap_or("")
+                .split(',')
+                .filter(|value| !value.trim().is_empty())
+                .map(|value| hex::parse_mac(value.trim()).expect("ESPNOW_PEERS must be comma separated MAC addresses"))
+                .collect(),
+            espnow_pmk: option_env!("ESPNOW_PMK")
+                .map(|value| hex::parse_key(value).expect("ESPNOW_PMK must be 32 hex characters")),
+            espnow_lmk: option_env!("ESPNOW_LMK")
+                .map(|value| hex::parse_key(value).expect("ESPNOW_LMK must be 32 hex characters")),
+            // Failover order, e.g. "mqtt,wifi,cellular"
+            uplinks: option_env!("UPLINKS")
+                .unwrap_or("wifi")
+                .split(',')
+                .map(|value| Uplink::parse(value).expect("UPLINKS must list wifi, mqtt, espnow, udp, lora or cellular"))
+                .collect(),
+            lora_frequency_hz: option_env!("LORA_FREQUENCY_HZ")
+                .map(|value| value.parse().expect("LORA_FREQUENCY_HZ must be an integer"))
+                .unwrap_or(LoRaConstant::DEFAULT_FREQUENCY_HZ),
+            lora_spreading_factor: option_env!("LORA_SPREADING_FACTOR")
+                .map(|value| value.parse().expect("LORA_SPREADING_FACTOR must be 7-12"))
+                .unwrap_or(LoRaConstant::DEFAULT_SPREADING_FACTOR),
+            lora_tx_power_dbm: option_env!("LORA_TX_POWER_DBM")
+                .map(|value| value.parse().expect("LORA_TX_POWER_DBM must be 2-20"))
+                .unwrap_or(LoRaConstant::DEFAULT_TX_POWER_DBM),
+            // Share of time on air the region allows, 100 where unlimited
+            lora_duty_cycle_percent: option_env!("LORA_DUTY_CYCLE_PERCENT")
+                .map(|value| value.parse().expect("LORA_DUTY_CYCLE_PERCENT must be 1-100"))
+                .unwrap_or(LoRaConstant::DEFAULT_DUTY_CYCLE_PERCENT),
+            cellular_apn: option_env!("CELLULAR_APN").unwrap_or(CellularConstant::DEFAULT_APN).to_string(),
+            cellular_user: option_env!("CELLULAR_USER").unwrap_or("").to_string(),
+            cellular_password: option_env!("CELLULAR_PASSWORD").unwrap_or("").to_string(),
+            // Priority order, e.g. "ethernet" alone where Wi-Fi is forbidden
+            network_interfaces: option_env!("NETWORK_INTERFACES")
+                .unwrap_or("ethernet,wifi")
+                .split(',')
+                .map(|value| NetworkInterface::parse(value).expect("NETWORK_INTERFACES must list ethernet and/or wifi"))
+                .collect(),
+            // IPv4 only unless STATIC_IPV6 is set, e.g. STATIC_IPV6=2001:db8::20/64;
+            // embassy-net 0.7 has no SLAAC. IPv6 is preferred once configured
+            static_ipv6: option_env!("STATIC_IPV6").map(|address| {
+                ipv6::parse_static(address, option_env!("STATIC_IPV6_GATEWAY"))
+                    .unwrap_or_else(|error| panic!("Invalid static IPv6 configuration: {}", error))
+            }),
+            // DHCP unless STATIC_IP is set, e.g. STATIC_IP=10.0.4.20/24
+            static_ipv4: option_env!("STATIC_IP").map(|address| {
+                ipv4::parse_static(
+                    address,
+                    option_env!("STATIC_NETMASK"),
+                    option_env!("STATIC_GATEWAY").expect("STATIC_GATEWAY must be set with STATIC_IP"),
+                    option_env!("STATIC_DNS").unwrap_or(""),
+                )
+                .unwrap_or_else(|error| panic!("Invalid static IP configuration: {}", error))
+            }),
+            mqtt_broker_url: option_env!("MQTT_BROKER_URL").map(|value| value.to_string()),
+            mqtt_username: option_env!("MQTT_USERNAME").map(|value| value.to_string()),
+            mqtt_password: option_env!("MQTT_PASSWORD").map(|value| value.to_string()),
+            // Every envelope goes to each of these, e.g. cloud plus a local Node-RED
+            upload_targets: option_env!("UPLOAD_TARGETS")
+                .or(option_env!("SEVER_BASE_URL"))
+                .unwrap_or("")
+                .split(',')
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+                .map(|value| value.to_string())
+                .collect(),
+            // "host:port" of the LAN collector for sensors listed in udp_stream
+            udp_collector: option_env!("UDP_COLLECTOR").map(|value| value.to_string()),
+            udp_ack: option_env!("UDP_ACK").map_or(false, |value| value == "true"),
+            http_compression: Compression::parse(option_env!("HTTP_COMPRESSION").unwrap_or("none"))
+                .expect("HTTP_COMPRESSION must be none or deflate"),
+            // Airtime is what costs money on these links, so compress by default
+            cellular_compression: Compression::parse(option_env!("CELLULAR_COMPRESSION").unwrap_or("deflate"))
+                .expect("CELLULAR_COMPRESSION must be none or deflate"),
+            lora_compression: Compression::parse(option_env!("LORA_COMPRESSION").unwrap_or("deflate"))
+                .expect("LORA_COMPRESSION must be none or deflate"),
+            batch_max_records: option_env!("BATCH_MAX_RECORDS")
+                .map(|value| value.parse().expect("BATCH_MAX_RECORDS must be an integer"))
+                .unwrap_or(UploadConstant::DEFAULT_BATCH_MAX_RECORDS),
+            batch_max_age_s: option_env!("BATCH_MAX_AGE_S")
+                .map(|value| value.parse().expect("BATCH_MAX_AGE_S must be an integer"))
+                .unwrap_or(UploadConstant::DEFAULT_BATCH_MAX_AGE_S),
+            ota_public_key: option_env!("OTA_PUBLIC_KEY").map(|value| {
+                hex::decode(value)
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .expect("OTA_PUBLIC_KEY must be 64 hex characters")
+            }),
+            ota_security_version: option_env!("FIRMWARE_SECURITY_VERSION")
+                .map(|value| value.parse().expect("FIRMWARE_SECURITY_VERSION must be an integer"))
+                .unwrap_or(0),
+            ota_pinned_version: option_env!("OTA_PIN_VERSION")
+                .map(|value| value.parse().expect("OTA_PIN_VERSION must be an integer")),
+            sensor_interval_ms: option_env!("SENSOR_INTERVAL")
+                .map(|value| value.parse().expect("SENSOR_INTERVAL must be an integer"))
+                .unwrap_or(SensorConstant::DEFAULT_INTERVAL_MS),
+            config_grace_period_s: option_env!("CONFIG_GRACE_PERIOD_S")
+                .map(|value| value.parse().expect("CONFIG_GRACE_PERIOD_S must be an integer"))
+                .unwrap_or(StorageConstant::DEFAULT_CONFIG_GRACE_PERIOD_S),
+            // e.g. "mon-fri 07:00-19:00 every 15m; sat 09:00-13:00"
+            schedule: schedule::parse(option_env!("SCHEDULE").unwrap_or(""))
+                .expect("SCHEDULE must be ';' separated \"days HH:MM-HH:MM [every N(s|m|h)]\" rules"),
+            // POSIX TZ, e.g. "CET-1CEST,M3.5.0,M10.5.0/3"
+            timezone: timezone::parse(option_env!("TZ").unwrap_or("UTC0"))
+                .expect("TZ must be a POSIX TZ string such as CET-1CEST,M3.5.0,M10.5.0/3"),
+            sntp_server: option_env!("SNTP_SERVER").unwrap_or(ClockConstant::DEFAULT_SNTP_SERVER).to_string(),
+            deep_sleep_interval_s: option_env!("DEEP_SLEEP_INTERVAL_S")
+                .map(|value| value.parse::<u64>().expect("DEEP_SLEEP_INTERVAL_S must be a number of seconds"))
+                .filter(|&seconds| seconds > 0),
+            bh1750: Bh1750ConfigDTO {
+                mode: Bh1750Mode::parse(option_env!("BH1750_MODE").unwrap_or(SensorConstant::BH1750_DEFAULT_MODE))
+                    .expect("BH1750_MODE must be continuous or one_time"),
+                resolution: Bh1750Resolution::parse(
+                    option_env!("BH1750_RESOLUTION").unwrap_or(SensorConstant::BH1750_DEFAULT_RESOLUTION),
+                )
+                .expect("BH1750_RESOLUTION must be h, h2 or l"),
+                mtreg: option_env!("BH1750_MTREG")
+                    .map(|value| value.parse().expect("BH1750_MTREG must be 31-254"))
+                    .unwrap_or(SensorConstant::BH1750_DEFAULT_MTREG)
+                    .clamp(SensorConstant::BH1750_MIN_MTREG, SensorConstant::BH1750_MAX_MTREG),
+            },
+            bme280: Bme280ConfigDTO {
+                mode: Bme280Mode::parse(option_env!("BME280_MODE").unwrap_or(SensorConstant::BME280_DEFAULT_MODE))
+                    .expect("BME280_MODE must be forced or normal"),
+                temperature_oversampling: ratio(
+                    option_env!("BME280_OSRS_T"),
+                    SensorConstant::BME280_DEFAULT_TEMPERATURE_OVERSAMPLING,
+                    &[1, 2, 4, 8, 16],
+                )
+                .expect("BME280_OSRS_T must be 1, 2, 4, 8 or 16"),
+                pressure_oversampling: ratio(
+                    option_env!("BME280_OSRS_P"),
+                    SensorConstant::BME280_DEFAULT_PRESSURE_OVERSAMPLING,
+                    &[1, 2, 4, 8, 16],
+                )
+                .expect("BME280_OSRS_P must be 1, 2, 4, 8 or 16"),
+                humidity_oversampling: ratio(
+                    option_env!("BME280_OSRS_H"),
+                    SensorConstant::BME280_DEFAULT_HUMIDITY_OVERSAMPLING,
+                    &[1, 2, 4, 8, 16],
+                )
+                .expect("BME280_OSRS_H must be 1, 2, 4, 8 or 16"),
+                filter: ratio(option_env!("BME280_FILTER"), SensorConstant::BME280_DEFAULT_FILTER, &[0, 2, 4, 8, 16])
+                    .expect("BME280_FILTER must be 0, 2, 4, 8 or 16"),
+                standby_ms: option_env!("BME280_STANDBY_MS")
+                    .map(|value| value.parse().expect("BME280_STANDBY_MS must be a number"))
+                    .unwrap_or(SensorConstant::BME280_DEFAULT_STANDBY_MS),
+                temperature_offset_c: option_env!("BME280_TEMPERATURE_OFFSET_C")
+                    .map(|value| value.parse().expect("BME280_TEMPERATURE_OFFSET_C must be a number"))
+                    .or(factory.as_ref().map(|identity| identity.temperature_offset_c))
+                    .unwrap_or(0.0),
+                cpu_heating_c: option_env!("BME280_CPU_HEATING_C")
+                    .map(|value| value.parse().expect("BME280_CPU_HEATING_C must be a number"))
+                    .or(factory.as_ref().map(|identity| identity.cpu_heating_c))
+                    .unwrap_or(0.0),
+                radio_heating_c: option_env!("BME280_RADIO_HEATING_C")
+                    .map(|value| value.parse().expect("BME280_RADIO_HEATING_C must be a number"))
+                    .or(factory.as_ref().map(|identity| identity.radio_heating_c))
+                    .unwrap_or(0.0),
+            },
+            aqi: AqiConfigDTO {
+                pm2_5: aqi::parse_breakpoints(
+                    option_env!("AQI_PM2_5_BREAKPOINTS").unwrap_or(AqiConstant::DEFAULT_PM2_5_BREAKPOINTS),
+                )
+                .expect("AQI_PM2_5_BREAKPOINTS must be ascending \"low-high:index_low-index_high\" rows"),
+                pm10: aqi::parse_breakpoints(
+                    option_env!("AQI_PM10_BREAKPOINTS").unwrap_or(AqiConstant::DEFAULT_PM10_BREAKPOINTS),
+                )
+                .expect("AQI_PM10_BREAKPOINTS must be ascending \"low-high:index_low-index_high\" rows"),
+                co2: aqi::parse_breakpoints(
+                    option_env!("AQI_CO2_BREAKPOINTS").unwrap_or(AqiConstant::DEFAULT_CO2_BREAKPOINTS),
+                )
+                .expect("AQI_CO2_BREAKPOINTS must be ascending \"low-high:index_low-index_high\" rows"),
+                categories: thresholds::parse(option_env!("AQI_CATEGORIES").unwrap_or(AqiConstant::DEFAULT_CATEGORIES))
+                    .expect("AQI_CATEGORIES must be ascending \"upper:LABEL\" steps followed by a label"),
+            },
+            // e.g. "25,26" for the two facing sensors of a people counter
+            vl53l0x_xshut_gpios: option_env!("VL53L0X_XSHUT_GPIOS")
+                .unwrap_or("")
+                .split(',')
+                .filter(|value| !value.trim().is_empty())
+                .map(|value| value.trim().parse().expect("VL53L0X_XSHUT_GPIOS must be comma separated GPIO numbers"))
+                .collect(),
+            people_counter_presence_mm: option_env!("PEOPLE_COUNTER_PRESENCE_MM")
+                .map(|value| value.parse().expect("PEOPLE_COUNTER_PRESENCE_MM must be an integer"))
+                .unwrap_or(PeopleCounterConstant::DEFAULT_PRESENCE_MM),
+            tamper: TamperConfigDTO {
+                shock_ms2: option_env!("TAMPER_SHOCK_MS2")
+                    .map(|value| value.parse().expect("TAMPER_SHOCK_MS2 must be a number"))
+                    .unwrap_or(TamperConstant::DEFAULT_SHOCK_MS2),
+                tilt_ms2: option_env!("TAMPER_TILT_MS2")
+                    .map(|value| value.parse().expect("TAMPER_TILT_MS2 must be a number"))
+                    .unwrap_or(TamperConstant::DEFAULT_TILT_MS2),
+                light_lux: option_env!("TAMPER_LIGHT_LUX")
+                    .map(|value| value.parse().expect("TAMPER_LIGHT_LUX must be a number"))
+                    .unwrap_or(TamperConstant::DEFAULT_LIGHT_LUX),
+            },
+            run_hours_rms_ms2: option_env!("RUN_HOURS_RMS_MS2")
+                .map(|value| value.parse().expect("RUN_HOURS_RMS_MS2 must be a number"))
+                .unwrap_or(RunHoursConstant::DEFAULT_RMS_MS2),
+            ble_beacons: option_env!("BLE_BEACONS")
+                .unwrap_or("")
+                .split(',')
+                .filter(|value| !value.trim().is_empty())
+                .map(|value| hex::parse_mac(value.trim()).expect("BLE_BEACONS must be comma separated MAC addresses"))
+                .collect(),
+            ble_absent_after_s: option_env!("BLE_ABSENT_AFTER_S")
+                .map(|value| value.parse().expect("BLE_ABSENT_AFTER_S must be an integer"))
+                .unwrap_or(BleConstant::DEFAULT_ABSENT_AFTER_S),
+            ble_advertise: option_env!("BLE_ADVERTISE").map_or(false, |value| value == "true"),
+            modbus: ModbusConfigDTO {
+                baud: option_env!("MODBUS_BAUD")
+                    .map(|value| value.parse().expect("MODBUS_BAUD must be an integer"))
+                    .unwrap_or(ModbusConstant::DEFAULT_BAUD),
+                de_gpio: option_env!("MODBUS_DE_GPIO")
+                    .map(|value| value.parse().expect("MODBUS_DE_GPIO must be a GPIO number")),
+                timeout_ms: option_env!("MODBUS_TIMEOUT_MS")
+                    .map(|value| value.parse().expect("MODBUS_TIMEOUT_MS must be an integer"))
+                    .unwrap_or(ModbusConstant::DEFAULT_TIMEOUT_MS),
+                registers: modbus::parse_registers(option_env!("MODBUS_REGISTERS").unwrap_or(""))
+                    .expect("MODBUS_REGISTERS must be \"field=slave:holding|input:address:type[*scale]\" entries"),
+            },
+            sdi12: Sdi12ConfigDTO {
+                break_gpio: option_env!("SDI12_BREAK_GPIO")
+                    .map(|value| value.parse().expect("SDI12_BREAK_GPIO must be a GPIO number")),
+                probes: sdi12::parse_probes(option_env!("SDI12_PROBES").unwrap_or(""))
+                    .expect("SDI12_PROBES must be \"name=address:command:field/field\" entries"),
+            },
+            can: {
+                let mut signals = can::parse_signals(option_env!("CAN_SIGNALS").unwrap_or(""))
+                    .expect("CAN_SIGNALS must be \"field=id:start:length:le|be:scale:offset[:signed]\" entries");
+                // e.g. "0x18fef100/0x00ffff00" for one J1939 PGN from any source
+                let filter = option_env!("CAN_FILTER").map(|value| {
+                    let (code, mask) = value.split_once('/').expect("CAN_FILTER must be \"code/mask\"");
+                    let hex = |value: &str| {
+                        u32::from_str_radix(value.trim().trim_start_matches("0x"), 16)
+                            .expect("CAN_FILTER must be hex \"code/mask\"")
+                    };
+                    (hex(code), hex(mask))
+                });
+                if let Some((code, mask)) = filter {
+                    for signal in signals.iter_mut().filter(|signal| (signal.id ^ code) & mask == 0) {
+                        signal.id_mask = mask;
+                    }
+                }
+                CanConfigDTO {
+                    bitrate_kbps: option_env!("CAN_BITRATE_KBPS")
+                        .map(|value| value.parse().expect("CAN_BITRATE_KBPS must be an integer"))
+                        .unwrap_or(CanConstant::DEFAULT_BITRATE_KBPS),
+                    filter: filter.or_else(|| can::acceptance_filter(&signals)),
+                    signals: signals,
+                }
+            },
+            ir: IrConfigDTO {
+                rx_gpio: option_env!("IR_RX_GPIO")
+                    .map(|value| value.parse().expect("IR_RX_GPIO must be a GPIO number")),
+                tx_gpio: option_env!("IR_TX_GPIO")
+                    .map(|value| value.parse().expect("IR_TX_GPIO must be a GPIO number")),
+                codes: ir::parse_codes(option_env!("IR_CODES").unwrap_or(""))
+                    .expect("IR_CODES must be \"name=nec|rc5:address:command\" entries"),
+            },
+            inputs: InputsConfigDTO {
+                // e.g. "select:0,back:35"
+                buttons: option_env!("INPUT_BUTTONS")
+                    .unwrap_or("")
+                    .split(',')
+                    .filter(|value| !value.trim().is_empty())
+                    .map(|value| {
+                        let (name, gpio) = value.split_once(':').expect("INPUT_BUTTONS must be \"name:gpio\" entries");
+                        let gpio = gpio.trim().parse().expect("INPUT_BUTTONS must be \"name:gpio\" entries");
+                        (name.trim().to_lowercase(), gpio)
+                    })
+                    .collect(),
+                // e.g. "dial:32/33"
+                encoders: option_env!("INPUT_ENCODERS")
+                    .unwrap_or("")
+                    .split(',')
+                    .filter(|value| !value.trim().is_empty())
+                    .map(|value| {
+                        let parse = || {
+                            let (name, pins) = value.split_once(':')?;
+                            let (a, b) = pins.split_once('/')?;
+                            Some((name.trim().to_lowercase(), (a.trim().parse().ok()?, b.trim().parse().ok()?)))
+                        };
+                        parse().expect("INPUT_ENCODERS must be \"name:gpio_a/gpio_b\" entries")
+                    })
+                    .collect(),
+            },
+            rfid_authorized_uids: option_env!("RFID_AUTHORIZED_UIDS")
+                .unwrap_or("")
+                .split(',')
+                .filter(|value| !value.trim().is_empty())
+                .map(|value| {
+                    hex::decode(value.replace(':', "").as_str()).expect("RFID_AUTHORIZED_UIDS must be hex UIDs")
+                })
+                .collect(),
+            rfid_unlock_s: option_env!("RFID_UNLOCK_S")
+                .map(|value| value.parse().expect("RFID_UNLOCK_S must be an integer"))
+                .unwrap_or(RfidConstant::DEFAULT_UNLOCK_S),
+            thermal_alert_c: option_env!("THERMAL_ALERT_C")
+                .map(|value| value.parse().expect("THERMAL_ALERT_C must be °C"))
+                .unwrap_or(ThermalConstant::DEFAULT_ALERT_C),
+            thermal_frame_interval_s: option_env!("THERMAL_FRAME_INTERVAL_S")
+                .map(|value| value.parse().expect("THERMAL_FRAME_INTERVAL_S must be seconds"))
+                .unwrap_or(ThermalConstant::DEFAULT_FRAME_INTERVAL_S),
+            audio_events: option_env!("AUDIO_EVENTS")
+                .unwrap_or("")
+                .split(',')
+                .filter(|value| !value.trim().is_empty())
+                .map(|value| {
+                    EventKind::parse(value.trim())
+                        .filter(|kind| matches!(kind, EventKind::GlassBreak | EventKind::SmokeAlarm))
+                        .expect("AUDIO_EVENTS must be glass_break and/or smoke_alarm")
+                })
+                .collect(),
+            baseline_lit_lux: option_env!("BASELINE_LIT_LUX")
+                .map(|value| value.parse().expect("BASELINE_LIT_LUX must be lux"))
+                .unwrap_or(BaselineConstant::DEFAULT_LIT_LUX),
+            metrics_interval_s: option_env!("METRICS_INTERVAL_S")
+                .map(|value| value.parse().expect("METRICS_INTERVAL_S must be seconds"))
+                .unwrap_or(MetricsConstant::DEFAULT_INTERVAL_S),
+            queue_retention: retention::parse(
+                option_env!("QUEUE_RETENTION").unwrap_or(RetentionConstant::DEFAULT_QUEUE),
+            )
+            .expect("QUEUE_RETENTION must be like records=5000,age_s=604800,bytes=524288"),
+            sd_retention: retention::parse(option_env!("SD_RETENTION").unwrap_or(RetentionConstant::DEFAULT_SD))
+                .expect("SD_RETENTION must be like age_s=7776000,bytes=1000000000"),
+            sensing_profile: SensingProfile::parse(
+                option_env!("SENSING_PROFILE").unwrap_or(ProfileConstant::DEFAULT_PROFILE),
+            )
+            .expect("SENSING_PROFILE must be fast, normal or eco"),
+            eco_battery_percent: option_env!("ECO_BATTERY_PERCENT")
+                .map(|value| value.parse().expect("ECO_BATTERY_PERCENT must be 0-100"))
+                .unwrap_or(ProfileConstant::DEFAULT_ECO_BATTERY_PERCENT),
+            status_led_gpio: option_env!("STATUS_LED_GPIO")
+                .map(|value| value.parse().expect("STATUS_LED_GPIO must be a GPIO number"))
+                .or(hardware_profile::profile().status_led_gpio),
+            ethernet_cs_gpio: option_env!("ETHERNET_CS_GPIO")
+                .map(|value| value.parse().expect("ETHERNET_CS_GPIO must be a GPIO number")),
+            ethernet_int_gpio: option_env!("ETHERNET_INT_GPIO")
+                .map(|value| value.parse().expect("ETHERNET_INT_GPIO must be a GPIO number")),
+            ethernet_reset_gpio: option_env!("ETHERNET_RESET_GPIO")
+                .map(|value| value.parse().expect("ETHERNET_RESET_GPIO must be a GPIO number")),
+            sd_cs_gpio: option_env!("SD_CS_GPIO").map(|value| value.parse().expect("SD_CS_GPIO must be a GPIO number")),
+        }
+    }
+
+    // Remote values win over the build-time ones, invalid ones are ignored
+    pub fn overlay(&mut self, remote: &ServerConfigResponseDTO) {
+        if let Some(interval_s) = remote.interval_s.filter(|interval_s| *interval_s > 0) {
+            self.sensor_interval_ms = interval_s as u64 * 1000;
+        }
+        if let Some(log_format) = remote.log_format.as_deref().and_then(LogFormat::parse) {
+            self.sd_log_format = log_format;
+        }
+        if let Some(server_base_url) = remote.server_base_url.as_ref().filter(|value| url::split(value).is_some()) {
+            self.server_base_url = server_base_url.clone();
+        }
+        if let Some(queue_retention) = remote.queue_retention.as_deref().and_then(retention::parse) {
+            self.queue_retention = queue_retention;
         }
+        if let Some(sd_retention) = remote.sd_retention.as_deref().and_then(retention::parse) {
+            self.sd_retention = sd_retention;
+        }
+    }
+}
+
+// WIFI_SSID/WIFI_PASSWORD is always the first profile, WIFI_PROFILES adds
+// fallbacks in priority order as "ssid:password;ssid:password". Setting
+// WIFI_EAP_METHOD turns the first profile into a WPA2-Enterprise one
+fn wifi_profiles() -> Vec<WifiProfileDTO> {
+    let mut profiles = Vec::new();
+    profiles.push(WifiProfileDTO {
+        ssid: option_env!("WIFI_SSID").expect("WIFI_SSID must be set").to_string(),
+        password: option_env!("WIFI_PASSWORD").expect("WIFI_PASSWORD must be set").to_string(),
+        enterprise: option_env!("WIFI_EAP_METHOD").map(|method| WifiEnterpriseDTO {
+            method: EapMethod::parse(method).expect("WIFI_EAP_METHOD must be peap or tls"),
+            identity: option_env!("WIFI_EAP_IDENTITY").unwrap_or("anonymous").to_string(),
+            username: option_env!("WIFI_EAP_USERNAME").unwrap_or("").to_string(),
+        }),
+    });
+    for profile in option_env!("WIFI_PROFILES").unwrap_or("").split(';').filter(|value| !value.is_empty()) {
+        let (ssid, password) = profile.split_once(':').unwrap_or((profile, ""));
+        profiles.push(WifiProfileDTO {
+            ssid: ssid.to_string(),
+            password: password.to_string(),
+            enterprise: None,
+        });
     }
-}
\ No newline at end of file
+    profiles
+}
+
+// One of `allowed`, `default` when unset
+fn ratio(value: Option<&str>, default: u8, allowed: &[u8]) -> Option<u8> {
+    let ratio = match value {
+        Some(value) => value.trim().parse().ok()?,
+        None => default,
+    };
+    allowed.contains(&ratio).then_some(ratio)
+}
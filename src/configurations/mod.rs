@@ -1 +1,2 @@
+pub mod pin_map;
 pub mod sensors;
\ No newline at end of file
@@ -0,0 +1,186 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::config::Config;
+use crate::configurations::sensors::SensorsConfig;
+use crate::constants::clock::ClockConstant;
+use crate::constants::gpio::GpioConstant;
+use crate::constants::hardware::HardwareConstant;
+use crate::constants::mfg_test::MfgTestConstant;
+use crate::constants::sensor::SensorConstant;
+use crate::constants::thermal::ThermalConstant;
+use crate::dtos::configurations::config_problem::ConfigProblemDTO;
+use crate::enums::config_problem_kind::ConfigProblemKind;
+use crate::services::hardware_profile;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Resource {
+    Gpio(u8),
+    // Bus name and 7-bit address
+    I2c(String, u8),
+}
+
+#[derive(Debug, Clone)]
+struct Claim {
+    resource: Resource,
+    // Setting that assigned it, e.g. "power_gpios.bme280"
+    owner: String,
+    output: bool,
+}
+
+// Every GPIO and I2C address the configured drivers will take, built from
+// the config before any driver is constructed, together with the pins the
+// board wires to its buses and straps. esp-hal panics deep inside
+// driver setup when a pin is taken twice; checking the map first turns that
+// into a CONFIG_REJECTED event naming both settings, and the caller leaves
+// the drivers unbuilt
+pub struct PinMap {
+    claims: Vec<Claim>,
+}
+
+impl PinMap {
+    pub fn build(config: &Config, sensors: &SensorsConfig) -> Self {
+        let mut map = Self { claims: Vec::new() };
+        // Claimed first so a setting reusing one of them is the one named
+        let profile = hardware_profile::profile();
+        let board = [
+            (profile.i2c_sda_gpio, "board.i2c_sda", true),
+            (profile.i2c_scl_gpio, "board.i2c_scl", true),
+            (HardwareConstant::SPI_SCLK_GPIO, "board.spi_sclk", true),
+            (HardwareConstant::SPI_MISO_GPIO, "board.spi_miso", false),
+            (HardwareConstant::SPI_MOSI_GPIO, "board.spi_mosi", true),
+            (HardwareConstant::CONSOLE_TX_GPIO, "board.console_tx", true),
+            (HardwareConstant::CONSOLE_RX_GPIO, "board.console_rx", false),
+            (HardwareConstant::UART_TX_GPIO, "board.uart_tx", true),
+            (HardwareConstant::UART_RX_GPIO, "board.uart_rx", false),
+            (HardwareConstant::STRAP_ADC_GPIO, "board.revision_strap", false),
+            (MfgTestConstant::STRAP_GPIO, "board.mfg_test_strap", false),
+        ];
+        for (gpio, owner, output) in board {
+            map.gpio(gpio, String::from(owner), output);
+        }
+        if !config.audio_events.is_empty() {
+            map.gpio(HardwareConstant::I2S_BCLK_GPIO, String::from("audio_events.i2s_bclk"), true);
+            map.gpio(HardwareConstant::I2S_WS_GPIO, String::from("audio_events.i2s_ws"), true);
+            map.gpio(HardwareConstant::I2S_DIN_GPIO, String::from("audio_events.i2s_din"), false);
+        }
+        for (sensor, gpio) in sensors.power_gpios.iter() {
+            map.gpio(*gpio, format!("power_gpios.{}", sensor), true);
+        }
+        for (sensor, gpio) in sensors.ready_gpios.iter() {
+            map.gpio(*gpio, format!("ready_gpios.{}", sensor), false);
+        }
+        for (index, gpio) in config.vl53l0x_xshut_gpios.iter().enumerate() {
+            map.gpio(*gpio, format!("vl53l0x_xshut_gpios.{}", index), true);
+        }
+        for (button, gpio) in config.inputs.buttons.iter() {
+            map.gpio(*gpio, format!("inputs.buttons.{}", button), false);
+        }
+        for (encoder, (a, b)) in config.inputs.encoders.iter() {
+            map.gpio(*a, format!("inputs.encoders.{}.a", encoder), false);
+            map.gpio(*b, format!("inputs.encoders.{}.b", encoder), false);
+        }
+        let optional = [
+            (config.status_led_gpio, "status_led_gpio", true),
+            (config.modbus.de_gpio, "modbus.de_gpio", true),
+            (config.sdi12.break_gpio, "sdi12.break_gpio", true),
+            (config.ir.rx_gpio, "ir.rx_gpio", false),
+            (config.ir.tx_gpio, "ir.tx_gpio", true),
+            (config.ethernet_cs_gpio, "ethernet_cs_gpio", true),
+            (config.ethernet_int_gpio, "ethernet_int_gpio", false),
+            (config.ethernet_reset_gpio, "ethernet_reset_gpio", true),
+            (config.sd_cs_gpio, "sd_cs_gpio", true),
+        ];
+        for (gpio, owner, output) in optional {
+            if let Some(gpio) = gpio {
+                map.gpio(gpio, String::from(owner), output);
+            }
+        }
+
+        for sensor in sensors.include.iter() {
+            let bus = sensors
+                .buses
+                .get(sensor)
+                .cloned()
+                .unwrap_or_else(|| String::from(SensorConstant::DEFAULT_BUS));
+            for address in addresses(sensor, config) {
+                map.claims.push(Claim {
+                    resource: Resource::I2c(bus.clone(), address),
+                    owner: format!("include.{}", sensor),
+                    output: false,
+                });
+            }
+        }
+        map
+    }
+
+    // Resources claimed twice, and GPIOs that cannot serve their role:
+    // missing, wired to the flash, or input only but driven
+    pub fn problems(&self) -> Vec<ConfigProblemDTO> {
+        let mut problems = Vec::new();
+        for (index, claim) in self.claims.iter().enumerate() {
+            if let Some(first) = self.claims[..index].iter().find(|other| other.resource == claim.resource) {
+                let detail = format!("{} is already {}", describe(&claim.resource), first.owner);
+                problems.push(conflict(&claim.owner, detail));
+            }
+            let gpio = match claim.resource {
+                Resource::Gpio(gpio) => gpio,
+                Resource::I2c(_, _) => continue,
+            };
+            let reason = if gpio > GpioConstant::MAX {
+                "does not exist"
+            } else if (GpioConstant::FLASH_FIRST..=GpioConstant::FLASH_LAST).contains(&gpio) {
+                "is wired to the flash"
+            } else if claim.output && gpio >= GpioConstant::INPUT_ONLY_FIRST {
+                "is input only"
+            } else {
+                continue;
+            };
+            problems.push(conflict(&claim.owner, format!("GPIO {} {}", gpio, reason)));
+        }
+        problems
+    }
+
+    fn gpio(&mut self, gpio: u8, owner: String, output: bool) {
+        self.claims.push(Claim {
+            resource: Resource::Gpio(gpio),
+            owner: owner,
+            output: output,
+        });
+    }
+}
+
+// Fixed addresses of the I2C sensors; a VL53L0X array is moved to
+// consecutive addresses behind its XSHUT pins
+pub fn addresses(sensor: &str, config: &Config) -> Vec<u8> {
+    match sensor {
+        SensorConstant::BH1750 => vec![SensorConstant::BH1750_ADDRESS],
+        SensorConstant::BME280 => vec![SensorConstant::BME280_ADDRESS],
+        SensorConstant::DS3231SN => vec![ClockConstant::DS3231_ADDRESS],
+        SensorConstant::AMG8833 => vec![ThermalConstant::AMG8833_ADDRESS],
+        SensorConstant::VL5310X if config.vl53l0x_xshut_gpios.is_empty() => {
+            vec![SensorConstant::VL53L0X_DEFAULT_ADDRESS]
+        },
+        SensorConstant::VL5310X => (0..config.vl53l0x_xshut_gpios.len() as u8)
+            .map(|unit| SensorConstant::VL53L0X_FIRST_ADDRESS + unit)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn describe(resource: &Resource) -> String {
+    match resource {
+        Resource::Gpio(gpio) => format!("GPIO {}", gpio),
+        Resource::I2c(bus, address) => format!("I2C address {:#04x} on {}", address, bus),
+    }
+}
+
+fn conflict(owner: &str, detail: String) -> ConfigProblemDTO {
+    ConfigProblemDTO {
+        kind: ConfigProblemKind::PinConflict,
+        field: String::from(owner),
+        detail: detail,
+    }
+}
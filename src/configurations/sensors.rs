@@ -1,18 +1,207 @@
+use alloc::collections::BTreeMap;
 use alloc::string::{String, ToString};
-use alloc::{vec::Vec, vec};
+use alloc::vec::Vec;
+use core::str::FromStr;
 
+use crate::constants::distance::DistanceConstant;
+use crate::constants::sensor::SensorConstant;
+use crate::constants::unit::UnitConstant;
+use crate::dtos::configurations::sensors::SensorsConfigDTO;
+use crate::dtos::configurations::thresholds::ThresholdsDTO;
+use crate::dtos::response::server::config::ServerConfigResponseDTO;
+use crate::enums::unit_system::UnitSystem;
+use crate::factories::pipeline::PipelineFactory;
+use crate::services::hardware_profile;
+use crate::utilities::thresholds;
+
+#[derive(Debug, Clone)]
 pub struct SensorsConfig {
     pub include: Vec<String>,
+    pub udp_stream: Vec<String>,
+    pub lux_thresholds: ThresholdsDTO,
+    pub distance_thresholds_mm: ThresholdsDTO,
+    pub temperature_thresholds: ThresholdsDTO,
+    pub humidity_thresholds: ThresholdsDTO,
+    pub warmup_ms: BTreeMap<String, u64>,
+    pub power_gpios: BTreeMap<String, u8>,
+    pub ready_gpios: BTreeMap<String, u8>,
+    pub location_urns: BTreeMap<String, String>,
+    pub pipelines: BTreeMap<String, Vec<String>>,
+    pub buses: BTreeMap<String, String>,
+    pub read_timeout_ms: u64,
+    pub last_value_max_age_s: u64,
+    pub units: UnitSystem,
+    pub compensation: BTreeMap<String, f32>,
 }
 
 impl SensorsConfig {
     pub fn new() -> Self {
-        let include = vec![
-            "bme280".to_string(),
-            "bh1750".to_string()
-        ];
+        // Whatever the detected board revision carries
+        let include = hardware_profile::profile()
+            .sensors
+            .iter()
+            .map(|sensor| sensor.to_string())
+            .collect();
+        let udp_stream = option_env!("UDP_SENSORS")
+            .unwrap_or("")
+            .split(',')
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| value.trim().to_lowercase())
+            .collect();
+        let lux_thresholds = thresholds::parse(option_env!("LUX_THRESHOLDS").unwrap_or(SensorConstant::DEFAULT_LUX_THRESHOLDS))
+            .expect("LUX_THRESHOLDS must be ascending \"upper:LABEL\" steps followed by a label");
+        let distance_thresholds_mm = thresholds::parse(
+            option_env!("DISTANCE_THRESHOLDS_MM").unwrap_or(DistanceConstant::DEFAULT_THRESHOLDS_MM),
+        )
+        .expect("DISTANCE_THRESHOLDS_MM must be ascending \"upper:LABEL\" steps followed by a label");
+        let temperature_thresholds = thresholds::parse(
+            option_env!("TEMPERATURE_THRESHOLDS").unwrap_or(SensorConstant::DEFAULT_TEMPERATURE_THRESHOLDS),
+        )
+        .expect("TEMPERATURE_THRESHOLDS must be ascending \"upper:LABEL\" steps followed by a label");
+        let humidity_thresholds = thresholds::parse(
+            option_env!("HUMIDITY_THRESHOLDS").unwrap_or(SensorConstant::DEFAULT_HUMIDITY_THRESHOLDS),
+        )
+        .expect("HUMIDITY_THRESHOLDS must be ascending \"upper:LABEL\" steps followed by a label");
+        // e.g. "scd4x:5000,mq135:180000"
+        let warmup_ms = per_sensor(option_env!("SENSOR_WARMUP_MS").unwrap_or(""))
+            .expect("SENSOR_WARMUP_MS must be comma separated \"sensor:milliseconds\" pairs");
+        // e.g. "pms5003:27,gps:14"
+        let power_gpios = per_sensor(option_env!("SENSOR_POWER_GPIOS").unwrap_or(""))
+            .expect("SENSOR_POWER_GPIOS must be comma separated \"sensor:gpio\" pairs");
+        // e.g. "vl53l0x.0:34,vl53l0x.1:35"
+        let ready_gpios = per_sensor(option_env!("SENSOR_READY_GPIOS").unwrap_or(""))
+            .expect("SENSOR_READY_GPIOS must be comma separated \"sensor:gpio\" pairs");
+        // e.g. "ds18b20.0:urn:senseplus:location:kitchen,ds18b20.1:urn:senseplus:location:cellar"
+        let location_urns = per_sensor(option_env!("SENSOR_LOCATION_URNS").unwrap_or(""))
+            .expect("SENSOR_LOCATION_URNS must be comma separated \"sensor:urn\" pairs");
+        // e.g. "bme280:outlier|moving_average(5)|threshold_alert(temperature>30);bh1750:moving_average(3)"
+        let pipelines = pipelines(option_env!("SENSOR_PIPELINES").unwrap_or(""))
+            .expect("SENSOR_PIPELINES must be ';' separated \"sensor:stage|stage\" chains of known stages");
+        // e.g. "vl53l0x.0:i2c1,vl53l0x.1:i2c1,ds18b20.0:onewire"
+        let buses = per_sensor(option_env!("SENSOR_BUSES").unwrap_or(""))
+            .expect("SENSOR_BUSES must be comma separated \"sensor:bus\" pairs");
+        let read_timeout_ms = option_env!("SENSOR_READ_TIMEOUT_MS")
+            .map(|value| value.parse().expect("SENSOR_READ_TIMEOUT_MS must be milliseconds"))
+            .unwrap_or(SensorConstant::DEFAULT_READ_TIMEOUT_MS);
+        let last_value_max_age_s = option_env!("LAST_VALUE_MAX_AGE_S")
+            .map(|value| value.parse().expect("LAST_VALUE_MAX_AGE_S must be seconds"))
+            .unwrap_or(SensorConstant::DEFAULT_LAST_VALUE_MAX_AGE_S);
+        let units = UnitSystem::parse(option_env!("UNITS").unwrap_or(UnitConstant::DEFAULT_SYSTEM))
+            .expect("UNITS must be metric, imperial or both");
+        // e.g. "distance_mm:0.25,lux:-1.5"
+        let compensation = per_sensor(option_env!("TEMPERATURE_COMPENSATION").unwrap_or(""))
+            .expect("TEMPERATURE_COMPENSATION must be comma separated \"field:coefficient\" pairs");
         Self { 
-            include: include
+            include: include,
+            udp_stream: udp_stream,
+            lux_thresholds: lux_thresholds,
+            distance_thresholds_mm: distance_thresholds_mm,
+            temperature_thresholds: temperature_thresholds,
+            humidity_thresholds: humidity_thresholds,
+            warmup_ms: warmup_ms,
+            power_gpios: power_gpios,
+            ready_gpios: ready_gpios,
+            location_urns: location_urns,
+            pipelines: pipelines,
+            buses: buses,
+            read_timeout_ms: read_timeout_ms,
+            last_value_max_age_s: last_value_max_age_s,
+            units: units,
+            compensation: compensation,
+        }
+    }
+
+    pub fn to_dto(&self) -> SensorsConfigDTO {
+        SensorsConfigDTO {
+            include: self.include.clone(),
+            udp_stream: self.udp_stream.clone(),
+            lux_thresholds: self.lux_thresholds.clone(),
+            distance_thresholds_mm: self.distance_thresholds_mm.clone(),
+            temperature_thresholds: self.temperature_thresholds.clone(),
+            humidity_thresholds: self.humidity_thresholds.clone(),
+            warmup_ms: self.warmup_ms.clone(),
+            power_gpios: self.power_gpios.clone(),
+            ready_gpios: self.ready_gpios.clone(),
+            location_urns: self.location_urns.clone(),
+            pipelines: self.pipelines.clone(),
+            buses: self.buses.clone(),
+            read_timeout_ms: self.read_timeout_ms,
+            last_value_max_age_s: self.last_value_max_age_s,
+            units: self.units,
+            compensation: self.compensation.clone(),
         }
     }
-}
\ No newline at end of file
+
+    pub fn overlay(&mut self, remote: &ServerConfigResponseDTO) {
+        if let Some(include) = &remote.include {
+            self.include = include.iter().map(|value| value.trim().to_lowercase()).collect();
+        }
+        if let Some(lux_thresholds) = remote.lux_thresholds.as_deref().and_then(thresholds::parse) {
+            self.lux_thresholds = lux_thresholds;
+        }
+        if let Some(distance_thresholds_mm) = remote.distance_thresholds_mm.as_deref().and_then(thresholds::parse) {
+            self.distance_thresholds_mm = distance_thresholds_mm;
+        }
+        if let Some(temperature_thresholds) = remote.temperature_thresholds.as_deref().and_then(thresholds::parse) {
+            self.temperature_thresholds = temperature_thresholds;
+        }
+        if let Some(humidity_thresholds) = remote.humidity_thresholds.as_deref().and_then(thresholds::parse) {
+            self.humidity_thresholds = humidity_thresholds;
+        }
+        if let Some(location_urns) = &remote.location_urns {
+            self.location_urns = location_urns
+                .iter()
+                .map(|(sensor, urn)| (sensor.trim().to_lowercase(), urn.trim().to_string()))
+                .collect();
+        }
+        if let Some(units) = remote.units.as_deref().and_then(UnitSystem::parse) {
+            self.units = units;
+        }
+        if let Some(compensation) = &remote.compensation {
+            self.compensation = compensation
+                .iter()
+                .map(|(field, coefficient)| (field.trim().to_lowercase(), *coefficient))
+                .collect();
+        }
+        if let Some(pipelines) = &remote.pipelines {
+            // One bad stage rejects the whole set rather than half applying it
+            if pipelines.values().flatten().all(|spec| PipelineFactory::stage(spec).is_some()) {
+                self.pipelines = pipelines
+                    .iter()
+                    .map(|(sensor, stages)| (sensor.trim().to_lowercase(), stages.clone()))
+                    .collect();
+            }
+        }
+    }
+
+    pub fn location_urn(&self, sensor: &str, device_location_urn: &str) -> String {
+        self.location_urns
+            .get(sensor)
+            .cloned()
+            .unwrap_or_else(|| device_location_urn.to_string())
+    }
+}
+
+// "key:value,key:value" with lowercase sensor keys, split at the first ':'
+// so values may contain more
+fn per_sensor<T: FromStr>(value: &str) -> Option<BTreeMap<String, T>> {
+    let mut values = BTreeMap::new();
+    for part in value.split(',').map(|part| part.trim()).filter(|part| !part.is_empty()) {
+        let (sensor, value) = part.split_once(':')?;
+        values.insert(sensor.trim().to_lowercase(), value.trim().parse().ok()?);
+    }
+    Some(values)
+}
+
+fn pipelines(value: &str) -> Option<BTreeMap<String, Vec<String>>> {
+    let mut chains = BTreeMap::new();
+    for chain in value.split(';').map(|chain| chain.trim()).filter(|chain| !chain.is_empty()) {
+        let (sensor, stages) = chain.split_once(':')?;
+        let stages: Vec<String> = stages.split('|').map(|stage| stage.trim().to_string()).collect();
+        if !stages.iter().all(|spec| PipelineFactory::stage(spec).is_some()) {
+            return None;
+        }
+        chains.insert(sensor.trim().to_lowercase(), stages);
+    }
+    Some(chains)
+}
@@ -0,0 +1,25 @@
+pub struct ActivityConstant;
+
+impl ActivityConstant {
+    // Walking is below 3 Hz, so the accelerometer can run at its lowest
+    // data rate that still resolves a step
+    pub const SAMPLE_RATE_HZ: u16 = 25;
+    // |a| rising above this after dropping below the low mark is a step;
+    // the gap between the two keeps vibration from counting twice
+    pub const STEP_HIGH_MS2: f32 = 11.5;
+    pub const STEP_LOW_MS2: f32 = 8.5;
+    // Faster than sprinting cadence, anything closer is the same step
+    pub const MIN_STEP_INTERVAL_MS: u64 = 250;
+    // A sample this far from 1 g counts as motion even without steps
+    pub const MOTION_MS2: f32 = 1.0;
+    // Share of moving samples in a window above which it is not idle
+    pub const MOTION_FRACTION: f32 = 0.1;
+    // Cadence in steps per minute from which a window is active
+    pub const ACTIVE_STEPS_PER_MIN: u32 = 100;
+    pub const GRAVITY_MS2: f32 = 9.81;
+
+    pub const STEPS: &'static str = "steps";
+    pub const STEPS_TOTAL: &'static str = "steps_total";
+    pub const ACTIVITY: &'static str = "activity";
+    pub const WINDOW_S: &'static str = "window_s";
+}
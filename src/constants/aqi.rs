@@ -0,0 +1,23 @@
+pub struct AqiConstant;
+
+impl AqiConstant {
+    // Fields read from a cycle's measurements and the ones added to it
+    pub const PM2_5: &'static str = "pm2_5";
+    pub const PM10: &'static str = "pm10";
+    pub const CO2: &'static str = "co2";
+    pub const AQI: &'static str = "aqi";
+    pub const CATEGORY: &'static str = "aqi_category";
+
+    // "low-high:index_low-index_high,..." in ascending order
+    // EPA, 2024 revision of the PM2.5 table
+    pub const DEFAULT_PM2_5_BREAKPOINTS: &'static str =
+        "0.0-9.0:0-50,9.1-35.4:51-100,35.5-55.4:101-150,55.5-125.4:151-200,125.5-225.4:201-300,225.5-325.4:301-500";
+    pub const DEFAULT_PM10_BREAKPOINTS: &'static str =
+        "0-54:0-50,55-154:51-100,155-254:101-150,255-354:151-200,355-424:201-300,425-604:301-500";
+    // Outdoor air is around 400 ppm; above 1000 ppm rooms feel stuffy and
+    // above 2000 ppm concentration suffers
+    pub const DEFAULT_CO2_BREAKPOINTS: &'static str =
+        "400-800:0-50,801-1000:51-100,1001-1500:101-150,1501-2000:151-200,2001-5000:201-300,5001-10000:301-500";
+    pub const DEFAULT_CATEGORIES: &'static str =
+        "50:GOOD,100:MODERATE,150:UNHEALTHY_FOR_SENSITIVE_GROUPS,200:UNHEALTHY,300:VERY_UNHEALTHY,HAZARDOUS";
+}
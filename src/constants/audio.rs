@@ -0,0 +1,47 @@
+pub struct AudioConstant;
+
+impl AudioConstant {
+    // I2S MEMS microphone, mono 16-bit on the left channel (L/R tied low)
+    pub const SAMPLE_RATE_HZ: u32 = 16_000;
+    // 32 ms per analysed frame
+    pub const FRAME_SAMPLES: usize = 512;
+    // Both channels are clocked in, 4 bytes a sample; four frames of DMA
+    // ring give the detector 128 ms to catch up before samples are lost
+    pub const DMA_BUFFER_BYTES: usize = 4 * Self::FRAME_SAMPLES * 4;
+    // Mean square below this is silence, about -40 dBFS
+    pub const MIN_LEVEL: f32 = 1.0e5;
+
+    // Piezo sounders of smoke and CO alarms sit around 3.1 kHz, spread by
+    // tolerance across this range
+    pub const ALARM_LOW_HZ: f32 = 2900.0;
+    pub const ALARM_HIGH_HZ: f32 = 3500.0;
+    // Half the 31.25 Hz bin width of a frame, so a tone is never more than a
+    // quarter bin off the nearest probe and keeps over 80% of its energy there
+    pub const ALARM_STEP_HZ: f32 = Self::SAMPLE_RATE_HZ as f32 / Self::FRAME_SAMPLES as f32 / 2.0;
+    // Share of the frame's energy in the strongest bin for it to be a tone
+    pub const ALARM_TONE_RATIO: f32 = 0.5;
+    // ISO 8201 temporal-three: three 0.5 s beeps 0.5 s apart
+    pub const ALARM_BEEP_MIN_MS: u64 = 300;
+    pub const ALARM_BEEP_MAX_MS: u64 = 800;
+    pub const ALARM_GAP_MAX_MS: u64 = 800;
+    pub const ALARM_BEEPS: u8 = 3;
+
+    // Breaking glass is a low thud of the impact followed within a few
+    // hundred ms by the high frequency shatter
+    pub const GLASS_LOW_HZ: [f32; 4] = [250.0, 500.0, 750.0, 1000.0];
+    pub const GLASS_HIGH_HZ: [f32; 4] = [4000.0, 5000.0, 6000.0, 7000.0];
+    // Jump over the background level that makes a frame an onset, ~15 dB
+    pub const GLASS_ONSET_RATIO: f32 = 30.0;
+    pub const GLASS_HIGH_TO_LOW: f32 = 4.0;
+    pub const GLASS_WINDOW_MS: u64 = 300;
+    // Weight of a new frame in the background level
+    pub const BACKGROUND_ALPHA: f32 = 0.05;
+
+    // The same kind is not reported again within this, a sounding alarm
+    // repeats its pattern every 4 s
+    pub const HOLDOFF_MS: u64 = 60_000;
+
+    pub const FREQUENCY_HZ: &'static str = "frequency_hz";
+    pub const BEEPS: &'static str = "beeps";
+    pub const ONSET_RATIO: &'static str = "onset_ratio";
+}
@@ -0,0 +1,27 @@
+pub struct BaselineConstant;
+
+impl BaselineConstant {
+    pub const HOURS_PER_WEEK: usize = 168;
+    // Lux at or above which a room counts as lit
+    pub const DEFAULT_LIT_LUX: f32 = 50.0;
+    // Weeks of history a slot needs before deviations from it are reported
+    pub const MIN_WEEKS: u8 = 3;
+    // Readings within an hour for it to be learned from or judged
+    pub const MIN_SAMPLES: u32 = 6;
+    // The first weeks are averaged evenly, after that newer weeks weigh
+    // this much so a changed routine is picked up within a month or so
+    pub const ALPHA: f32 = 0.2;
+    // Share of past weeks below which something is unusual, and at or
+    // above which its absence is
+    pub const UNUSUAL_SHARE: f32 = 0.1;
+    pub const EXPECTED_SHARE: f32 = 0.8;
+
+    pub const LIGHTS_ON: &'static str = "lights_on";
+    pub const LIGHTS_OFF: &'static str = "lights_off";
+    pub const OCCUPIED: &'static str = "occupied";
+    pub const VACANT: &'static str = "vacant";
+
+    pub const DEVIATION: &'static str = "deviation";
+    pub const HOUR_OF_WEEK: &'static str = "hour_of_week";
+    pub const BASELINE: &'static str = "baseline";
+}
@@ -0,0 +1,24 @@
+pub struct BleConstant;
+
+impl BleConstant {
+    // 50 ms of every 160 ms, in units of 0.625 ms: a beacon advertising
+    // once a second is still heard every few seconds, and the radio stays
+    // free enough for Wi-Fi to coexist
+    pub const SCAN_INTERVAL: u16 = 0x0100;
+    pub const SCAN_WINDOW: u16 = 0x0050;
+    // 1 s: phones in the foreground pick it up within a couple of seconds
+    pub const ADVERTISING_INTERVAL: u16 = 0x0640;
+    // A beacon not heard for this long has left
+    pub const DEFAULT_ABSENT_AFTER_S: u64 = 30;
+    // Longest wait for an HCI packet before absences are checked anyway
+    pub const POLL_MS: u64 = 1000;
+    // A read takes every queued packet that fits
+    pub const HCI_READ_BYTES: usize = 1024;
+
+    pub const ADDRESS: &'static str = "address";
+    pub const BEACON: &'static str = "beacon";
+    pub const RSSI: &'static str = "rssi";
+    // Gauge fields, "rssi_<mac>" per beacon in range
+    pub const RSSI_PREFIX: &'static str = "rssi_";
+    pub const PRESENT: &'static str = "beacons_present";
+}
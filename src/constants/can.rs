@@ -0,0 +1,11 @@
+pub struct CanConstant;
+
+impl CanConstant {
+    // J1939, what trucks and most off-highway equipment use
+    pub const DEFAULT_BITRATE_KBPS: u32 = 250;
+    // Identifiers above this only fit the 29-bit extended format
+    pub const MAX_STANDARD_ID: u32 = 0x7ff;
+    pub const MAX_EXTENDED_ID: u32 = 0x1fff_ffff;
+    // Frames handled per poll, so a busy bus cannot starve the executor
+    pub const POLL_BUDGET: usize = 64;
+}
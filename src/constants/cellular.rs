@@ -0,0 +1,12 @@
+pub struct CellularConstant;
+
+impl CellularConstant {
+    pub const DEFAULT_APN: &'static str = "internet";
+    pub const COMMAND_TIMEOUT_MS: u64 = 2_000;
+    // Network attach and PDP activation can take tens of seconds on NB-IoT
+    pub const ATTACH_TIMEOUT_MS: u64 = 60_000;
+    pub const CONNECT_TIMEOUT_MS: u64 = 20_000;
+    pub const RESPONSE_TIMEOUT_MS: u64 = 30_000;
+    // Quiet time after which leftover result codes count as drained
+    pub const DRAIN_QUIET_MS: u64 = 10;
+}
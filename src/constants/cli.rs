@@ -0,0 +1,13 @@
+pub struct CliConstant;
+
+impl CliConstant {
+    // Longer lines are cut, no command takes an argument near this long
+    pub const MAX_LINE_BYTES: usize = 256;
+    pub const READ_CHUNK_BYTES: usize = 32;
+    pub const LOG_LEVEL_COMMAND: &'static str = "log_level";
+    pub const LOGS_COMMAND: &'static str = "logs";
+    pub const UPLOADS_COMMAND: &'static str = "uploads";
+    pub const HELP_COMMAND: &'static str = "help";
+    pub const HELP: &'static str =
+        "log_level [spec] | logs | uploads | program_identity serial=...,revision=D | mfg_test | soak [start|stop]";
+}
@@ -0,0 +1,26 @@
+pub struct ClockConstant;
+
+impl ClockConstant {
+    pub const DS3231_ADDRESS: u8 = 0x68;
+
+    pub const DEFAULT_SNTP_SERVER: &'static str = "pool.ntp.org";
+    pub const SNTP_PORT: u16 = 123;
+    pub const SNTP_LOCAL_PORT: u16 = 47_123;
+    pub const SNTP_TIMEOUT_MS: u64 = 3_000;
+    pub const DISCIPLINE_INTERVAL_S: u64 = 3_600;
+    pub const SNTP_RETRY_S: u64 = 30;
+
+    // The RTC is rewritten once it is further than this from SNTP
+    pub const MAX_OFFSET_S: i64 = 1;
+    // The RTC only resolves whole seconds, so drift is measured over days
+    pub const MIN_DRIFT_WINDOW_S: u64 = 3 * 86_400;
+    // One aging offset LSB trims the oscillator by about 0.1 ppm at 25 °C
+    pub const AGING_PPB_PER_LSB: i64 = 100;
+
+    // Alarm 1 matches on the day of the month, so it can be at most this far out
+    pub const MAX_ALARM_SLEEP_S: u64 = 28 * 86_400;
+    // Shorter sleeps are not worth a full reboot
+    pub const MIN_DEEP_SLEEP_S: u64 = 5;
+    // The ESP32 timer stays armed behind the alarm in case INT/SQW never fires
+    pub const BACKUP_WAKE_MARGIN_S: u64 = 300;
+}
@@ -10,4 +10,6 @@ impl DistanceConstant {
     pub const FAR: &'static str = "FAR";
     pub const OUT_OF_RANGE: &'static str = "OUT_OF_RANGE";
     pub const UNKNOWN: &'static str = "UNKNOWN";
+    pub const DEFAULT_THRESHOLDS_MM: &'static str =
+        "10:TOO_CLOSE,50:VERY_CLOSE,200:CLOSE,500:NEAR,1000:MEDIUM,2000:FAR,OUT_OF_RANGE";
 }
\ No newline at end of file
@@ -0,0 +1,10 @@
+pub struct GpioConstant;
+
+impl GpioConstant {
+    pub const MAX: u8 = 39;
+    // Wired to the SPI flash on WROOM/WROVER modules
+    pub const FLASH_FIRST: u8 = 6;
+    pub const FLASH_LAST: u8 = 11;
+    // No output driver
+    pub const INPUT_ONLY_FIRST: u8 = 34;
+}
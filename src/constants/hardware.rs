@@ -0,0 +1,46 @@
+pub struct HardwareConstant;
+
+impl HardwareConstant {
+    // 24C02-style EEPROM on rev D and later, its first byte an ASCII
+    // revision letter
+    pub const EEPROM_ADDRESS: u8 = 0x50;
+    pub const EEPROM_REVISION_OFFSET: u8 = 0x00;
+    // Identity block programmed on the production line, see
+    // `utilities::board_identity`: the revision letter, a NUL padded serial
+    // and from `EEPROM_CALIBRATION_OFFSET` a calibration version and the
+    // constants in hundredths, the last two bytes a CRC-16 of the rest
+    pub const EEPROM_SERIAL_OFFSET: usize = 0x01;
+    pub const EEPROM_SERIAL_LENGTH: usize = 16;
+    pub const EEPROM_CALIBRATION_OFFSET: usize = 0x20;
+    pub const EEPROM_CALIBRATION_VERSION: u8 = 1;
+    pub const EEPROM_IDENTITY_LENGTH: usize = 0x30;
+    // Result of the last production line test, see `mfg_test`
+    pub const EEPROM_MFG_TEST_OFFSET: usize = 0x30;
+    // Rev C boards strap the revision on an ADC pin with a divider off
+    // 3V3; readings within `STRAP_TOLERANCE_MV` of a level select it
+    pub const STRAP_ADC_GPIO: u8 = 36;
+    pub const STRAP_B_MV: u32 = 0;
+    pub const STRAP_C_MV: u32 = 1650;
+    pub const STRAP_D_MV: u32 = 2475;
+    pub const STRAP_TOLERANCE_MV: u32 = 250;
+    // ADC1 at 11 dB attenuation reads about 0-3100 mV over 12 bits
+    pub const ADC_FULL_SCALE_MV: u32 = 3100;
+    pub const ADC_MAX_RAW: u32 = 4095;
+    pub const I2C_SDA_GPIO: u8 = 21;
+    pub const I2C_SCL_GPIO: u8 = 22;
+    // VSPI, shared by the W5500, the SX127x and the RC522 behind their own
+    // chip selects
+    pub const SPI_SCLK_GPIO: u8 = 18;
+    pub const SPI_MISO_GPIO: u8 = 19;
+    pub const SPI_MOSI_GPIO: u8 = 23;
+    // UART0, the USB serial console the log and the CLI share
+    pub const CONSOLE_TX_GPIO: u8 = 1;
+    pub const CONSOLE_RX_GPIO: u8 = 3;
+    // UART2 on the field connector: RS-485 Modbus, SDI-12 or the modem
+    pub const UART_TX_GPIO: u8 = 17;
+    pub const UART_RX_GPIO: u8 = 16;
+    // I2S MEMS microphone, see `I2sMicrophone`
+    pub const I2S_BCLK_GPIO: u8 = 26;
+    pub const I2S_WS_GPIO: u8 = 25;
+    pub const I2S_DIN_GPIO: u8 = 33;
+}
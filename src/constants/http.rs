@@ -0,0 +1,33 @@
+pub struct HttpConstant;
+
+impl HttpConstant {
+    pub const EXPORT_PATH: &'static str = "/export";
+    pub const LIVE_PATH: &'static str = "/live";
+    pub const HEALTH_PATH: &'static str = "/health";
+    pub const INFO_PATH: &'static str = "/info";
+    pub const METRICS_PATH: &'static str = "/metrics";
+    pub const LOG_PATH: &'static str = "/log";
+    pub const LOGS_PATH: &'static str = "/logs";
+    pub const SOAK_PATH: &'static str = "/soak";
+    pub const EXPORT_CONTENT_TYPE: &'static str = "text/csv";
+    // Bounds how long a single export can keep the SD card busy
+    pub const EXPORT_MAX_DAYS: u64 = 31;
+    pub const READ_CHUNK_BYTES: usize = 512;
+    pub const SERVER_PORT: u16 = 80;
+    // Responses are built in memory before they go out, this bounds an export
+    pub const RESPONSE_MAX_BYTES: usize = 32 * 1024;
+    pub const SOCKET_TIMEOUT_MS: u64 = 10_000;
+    pub const SOCKET_BUFFER_BYTES: usize = 1024;
+    // Status line, headers and a short body such as {"cursor":"42-17"}
+    pub const ACK_RESPONSE_BYTES: usize = 512;
+    // With PSRAM a whole TLS record fits, fewer round trips per upload
+    pub const LARGE_SOCKET_BUFFER_BYTES: usize = 16 * 1024;
+    pub const LIVE_QUEUE_DEPTH: usize = 4;
+    pub const LIVE_MAX_CLIENTS: usize = 2;
+    pub const FIRMWARE_VERSION: &'static str = env!("CARGO_PKG_VERSION");
+    pub const CHIP: &'static str = "esp32";
+    pub const MULTIPART_BOUNDARY: &'static str = "senseplus-7d3f9a1c5b";
+    pub const UPLOADS_PATH: &'static str = "/api/v1/uploads";
+    pub const MEASUREMENTS_PATH: &'static str = "/api/v1/measurements";
+    pub const EVENTS_PATH: &'static str = "/api/v1/events";
+}
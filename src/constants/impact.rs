@@ -0,0 +1,18 @@
+pub struct ImpactConstant;
+
+impl ImpactConstant {
+    // |a| below this is free fall; a device at rest reads 1 g
+    pub const FREE_FALL_MS2: f32 = 3.0;
+    // Shorter dips are vibration, 80 ms is a drop of about 3 cm
+    pub const FREE_FALL_MIN_MS: u64 = 80;
+    // |a| above this is an impact, about 4 g; needs the accelerometer set to
+    // ±8 g as `LSM303DLHCAccelSensor` does, at ±2 g it would never be reached
+    pub const IMPACT_MS2: f32 = 39.0;
+    // How long after free fall ends an impact still counts as its landing
+    pub const LANDING_WINDOW_MS: u64 = 500;
+    // A shock this soon after the previous event is the same one bouncing
+    pub const HOLDOFF_MS: u64 = 2000;
+
+    pub const FALL_MS: &'static str = "fall_ms";
+    pub const PEAK_MS2: &'static str = "peak_ms2";
+}
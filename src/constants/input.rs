@@ -0,0 +1,17 @@
+pub struct InputConstant;
+
+impl InputConstant {
+    // Contact bounce of tactile switches settles well within this
+    pub const DEBOUNCE_MS: u64 = 30;
+    pub const LONG_PRESS_MS: u64 = 800;
+    // Encoder counter polling; fast enough that turning feels immediate
+    pub const ENCODER_POLL_MS: u64 = 20;
+    // Counts per detent with both PCNT channels counting both edges
+    pub const COUNTS_PER_DETENT: i16 = 4;
+    // PCNT glitch filter in APB cycles, about 10 µs
+    pub const ENCODER_FILTER_CYCLES: u16 = 800;
+    pub const ENCODER_LIMIT: i16 = 1000;
+    pub const QUEUE_DEPTH: usize = 8;
+    // The display menu and the rules engine
+    pub const MAX_SUBSCRIBERS: usize = 2;
+}
@@ -0,0 +1,29 @@
+pub struct IrConstant;
+
+impl IrConstant {
+    // RMT ticks of 1 µs: 80 MHz APB clock divided by 80
+    pub const RMT_CLOCK_DIVIDER: u8 = 80;
+    pub const CARRIER_HZ: u32 = 38_000;
+    // Longer silence ends a received frame; NEC's longest gap is 4.5 ms
+    pub const IDLE_THRESHOLD_US: u16 = 12_000;
+    // Received durations may be this far off nominal, in percent
+    pub const TOLERANCE_PERCENT: u32 = 25;
+    // Codes per frame: one RMT RAM block of 64, the most an async receive or
+    // transmit accepts on a channel with the default memsize of one block.
+    // NEC needs 34, RC5 14
+    pub const MAX_PULSES: usize = 64;
+
+    pub const NEC_LEADER_MARK_US: u32 = 9000;
+    pub const NEC_LEADER_SPACE_US: u32 = 4500;
+    pub const NEC_REPEAT_SPACE_US: u32 = 2250;
+    pub const NEC_BIT_MARK_US: u32 = 562;
+    pub const NEC_ZERO_SPACE_US: u32 = 562;
+    pub const NEC_ONE_SPACE_US: u32 = 1687;
+    pub const RC5_HALF_BIT_US: u32 = 889;
+
+    pub const SEND_COMMAND: &'static str = "ir_send";
+    pub const PROTOCOL: &'static str = "protocol";
+    pub const ADDRESS: &'static str = "address";
+    pub const COMMAND: &'static str = "command";
+    pub const NAME: &'static str = "name";
+}
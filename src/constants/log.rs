@@ -0,0 +1,6 @@
+pub struct LogConstant;
+
+impl LogConstant {
+    // Recent log output kept in RAM for GET /logs, oldest lines go first
+    pub const BUFFER_BYTES: usize = 8 * 1024;
+}
@@ -0,0 +1,20 @@
+pub struct LoRaConstant;
+
+impl LoRaConstant {
+    pub const DEFAULT_FREQUENCY_HZ: u32 = 868_100_000;
+    pub const DEFAULT_SPREADING_FACTOR: u8 = 9;
+    pub const DEFAULT_BANDWIDTH_HZ: u32 = 125_000;
+    pub const DEFAULT_TX_POWER_DBM: u8 = 14;
+    pub const SYNC_WORD: u8 = 0x12;
+    pub const MAX_PAYLOAD_BYTES: usize = 255;
+    // Message number, fragment index and fragment count
+    pub const FRAGMENT_HEADER_BYTES: usize = 3;
+    // Slack on top of the computed airtime before TX_DONE counts as lost
+    pub const TX_TIMEOUT_MARGIN_MS: u64 = 500;
+    pub const TX_POLL_MS: u64 = 5;
+    // Preamble symbols as left by reset, the airtime depends on it
+    pub const PREAMBLE_SYMBOLS: u32 = 8;
+    // EU868 sub-bands g and g1 allow 1% of the time on air; 100 for
+    // regions without a duty cycle limit, e.g. US915
+    pub const DEFAULT_DUTY_CYCLE_PERCENT: u8 = 1;
+}
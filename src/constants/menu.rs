@@ -0,0 +1,18 @@
+pub struct MenuConstant;
+
+impl MenuConstant {
+    // 128x64 OLED with the 8x8 font
+    pub const LINES: usize = 8;
+    pub const COLUMNS: usize = 16;
+    // Buttons with these names move the cursor on devices without encoder
+    pub const UP_BUTTON: &'static str = "up";
+    pub const DOWN_BUTTON: &'static str = "down";
+    pub const INTERVAL_STEP_S: u64 = 10;
+    pub const MIN_INTERVAL_S: u64 = 10;
+    pub const MAX_INTERVAL_S: u64 = 3600;
+
+    pub const VALUES: &'static str = "Sensor values";
+    pub const I2C_SCAN: &'static str = "I2C scan";
+    pub const PROVISIONING: &'static str = "Provisioning";
+    pub const INTERVAL: &'static str = "Interval";
+}
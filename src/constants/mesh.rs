@@ -0,0 +1,15 @@
+pub struct MeshConstant;
+
+impl MeshConstant {
+    pub const DEFAULT_NODE_MODE: &'static str = "standalone";
+    pub const DEFAULT_CHANNEL: u8 = 1;
+    pub const BROADCAST_ADDRESS: [u8; 6] = [0xff; 6];
+    // ESP-NOW caps a single frame at 250 bytes
+    pub const FRAME_MAX_BYTES: usize = 250;
+    pub const FRAME_HEADER_BYTES: usize = 4;
+    pub const MAX_FRAGMENTS: usize = 8;
+    // Hardware limit for encrypted peers on the ESP32
+    pub const MAX_PEERS: usize = 6;
+    // Envelope ids a gateway remembers to drop resends, across all leaves
+    pub const RECENT_IDS: usize = 32;
+}
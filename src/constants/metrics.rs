@@ -0,0 +1,25 @@
+pub struct MetricsConstant;
+
+impl MetricsConstant {
+    // Device health goes up far less often than sensor data
+    pub const DEFAULT_INTERVAL_S: u64 = 900;
+    // Left unpainted below the stack pointer at `stack::paint`, for the
+    // frames of the painting itself
+    pub const STACK_PAINT_MARGIN_BYTES: usize = 512;
+    pub const STACK_PAINT: u32 = 0xa5a5_a5a5;
+
+    pub const UPTIME_S: &'static str = "uptime_s";
+    pub const HEAP_FREE_BYTES: &'static str = "heap_free_bytes";
+    pub const HEAP_MIN_FREE_BYTES: &'static str = "heap_min_free_bytes";
+    pub const STACK_HIGH_WATER_BYTES: &'static str = "stack_high_water_bytes";
+    pub const STACK_SIZE_BYTES: &'static str = "stack_size_bytes";
+    pub const RSSI_DBM: &'static str = "rssi_dbm";
+    pub const RECONNECTS: &'static str = "reconnects";
+    pub const QUEUE_DEPTH: &'static str = "queue_depth";
+    pub const UPLOAD_SUCCESSES: &'static str = "upload_successes";
+    pub const UPLOAD_FAILURES: &'static str = "upload_failures";
+    pub const UPLOAD_BYTES: &'static str = "upload_bytes";
+    pub const UPLOAD_MEAN_MS: &'static str = "upload_mean_ms";
+    pub const SENSOR_ERRORS: &'static str = "sensor_errors";
+    pub const RESET_REASON: &'static str = "reset_reason";
+}
@@ -0,0 +1,14 @@
+pub struct MfgTestConstant;
+
+impl MfgTestConstant {
+    // Pulled up on the board, the test fixture grounds it through a pogo
+    // pin; input only, so nothing else can claim it
+    pub const STRAP_GPIO: u8 = 35;
+    pub const COMMAND: &'static str = "mfg_test";
+    // Every indicator on and off this many times for the operator to see
+    pub const BLINK_COUNT: u32 = 5;
+    pub const BLINK_MS: u64 = 250;
+    // Layout of the stored result, see `mfg_test::record`
+    pub const RESULT_VERSION: u8 = 1;
+    pub const RESULT_LENGTH: usize = 14;
+}
@@ -1,2 +1,46 @@
+pub mod activity;
+pub mod aqi;
+pub mod audio;
+pub mod baseline;
+pub mod ble;
+pub mod can;
+pub mod cellular;
+pub mod cli;
+pub mod clock;
 pub mod distance;
-pub mod sensor;
\ No newline at end of file
+pub mod gpio;
+pub mod hardware;
+pub mod http;
+pub mod impact;
+pub mod input;
+pub mod ir;
+pub mod log;
+pub mod lora;
+pub mod menu;
+pub mod mesh;
+pub mod metrics;
+pub mod mfg_test;
+pub mod modbus;
+pub mod mqtt;
+pub mod network;
+pub mod offload;
+pub mod ota;
+pub mod people_counter;
+pub mod pipeline;
+pub mod profile;
+pub mod retention;
+pub mod rfid;
+pub mod run_hours;
+pub mod sdi12;
+pub mod secret;
+pub mod sensor;
+pub mod soak;
+pub mod status_led;
+pub mod storage;
+pub mod supervisor;
+pub mod tamper;
+pub mod thermal;
+pub mod udp;
+pub mod unit;
+pub mod upload;
+pub mod wifi;
\ No newline at end of file
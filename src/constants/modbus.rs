@@ -0,0 +1,15 @@
+pub struct ModbusConstant;
+
+impl ModbusConstant {
+    pub const DEFAULT_BAUD: u32 = 9600;
+    // Meters answer within tens of ms; some VFDs take a few hundred
+    pub const DEFAULT_TIMEOUT_MS: u32 = 500;
+    // Above 19200 baud the spec fixes the inter-frame gap instead of
+    // scaling it with the character time
+    pub const FAST_BAUD: u32 = 19200;
+    pub const FAST_FRAME_GAP_US: u32 = 1750;
+    // 11 bits per character, 8N1 plus parity or a second stop bit
+    pub const CHARACTER_BITS: u32 = 11;
+    // Largest RTU frame
+    pub const MAX_FRAME_BYTES: usize = 256;
+}
@@ -0,0 +1,8 @@
+pub struct MqttConstant;
+
+impl MqttConstant {
+    pub const KEEP_ALIVE_S: u16 = 60;
+    // {device_urn} is substituted at startup
+    pub const DEFAULT_TOPIC: &'static str = "senseplus/{device_urn}/measurements";
+    pub const EVENT_TOPIC: &'static str = "senseplus/{device_urn}/events";
+}
@@ -0,0 +1,9 @@
+pub struct NetworkConstant;
+
+impl NetworkConstant {
+    // DNS over TCP is usually open on the gateway, and a reset works too
+    pub const ARP_PROBE_PORT: u16 = 53;
+    pub const ARP_PROBE_TIMEOUT_MS: u64 = 3_000;
+    // Per interface: an upload, MQTT, OTA, SNTP, DNS and the HTTP server
+    pub const STACK_SOCKETS: usize = 6;
+}
@@ -0,0 +1,11 @@
+pub struct OffloadConstant;
+
+impl OffloadConstant {
+    // Jobs waiting for the offload core before `run` callers have to wait
+    pub const QUEUE_DEPTH: usize = 8;
+    // Stack of the second core; bit-banging drivers need little of it
+    pub const STACK_BYTES: usize = 8192;
+    // A job running longer than this is logged, it delays every job queued
+    // behind it
+    pub const SLOW_JOB_MS: u64 = 500;
+}
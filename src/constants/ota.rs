@@ -0,0 +1,16 @@
+pub struct OtaConstant;
+
+impl OtaConstant {
+    pub const MANIFEST_PATH: &'static str = "/api/v1/firmware";
+    // A new image has this long to complete an upload before it is rolled back
+    pub const SELF_TEST_WINDOW_S: u64 = 600;
+    // How often a pending image looks for its first upload
+    pub const POLL_INTERVAL_S: u64 = 5;
+    pub const CHECK_INTERVAL_S: u64 = 6 * 3_600;
+    // After a failed check, e.g. at boot before the network is up
+    pub const RETRY_INTERVAL_S: u64 = 300;
+    pub const MANIFEST_MAX_BYTES: usize = 1024;
+    pub const HEAD_MAX_BYTES: usize = 1024;
+    // Must match the ota_0/ota_1 rows in partitions.csv
+    pub const SLOT_SIZE: usize = 0x180000;
+}
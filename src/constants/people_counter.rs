@@ -0,0 +1,11 @@
+pub struct PeopleCounterConstant;
+
+impl PeopleCounterConstant {
+    // Below a typical door height seen from the frame, so the floor or the
+    // opposite jamb does not count as presence
+    pub const DEFAULT_PRESENCE_MM: u16 = 1200;
+    pub const RESET_COMMAND: &'static str = "reset_occupancy";
+    pub const OCCUPANCY: &'static str = "occupancy";
+    pub const ENTRIES: &'static str = "entries";
+    pub const EXITS: &'static str = "exits";
+}
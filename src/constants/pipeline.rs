@@ -0,0 +1,36 @@
+pub struct PipelineConstant;
+
+impl PipelineConstant {
+    pub const OUTLIER: &'static str = "outlier";
+    pub const MOVING_AVERAGE: &'static str = "moving_average";
+    pub const THRESHOLD_ALERT: &'static str = "threshold_alert";
+    pub const UNIT_CONVERSION: &'static str = "unit_conversion";
+    pub const TEMPERATURE_COMPENSATION: &'static str = "temperature_compensation";
+    pub const ANOMALY: &'static str = "anomaly";
+    pub const RATE_ALERT: &'static str = "rate_alert";
+
+    pub const DEFAULT_OUTLIER_DEVIATIONS: f32 = 3.0;
+    pub const DEFAULT_MOVING_AVERAGE_WINDOW: usize = 5;
+    // Readings kept per field to judge outliers, and how many are needed
+    // before anything is dropped
+    pub const OUTLIER_WINDOW: usize = 8;
+    pub const OUTLIER_MIN_SAMPLES: usize = 4;
+    // After this many consecutive drops the new level is accepted
+    pub const OUTLIER_MAX_REJECTED: usize = 3;
+    pub const DEFAULT_ANOMALY_DEVIATIONS: f32 = 4.0;
+    // Longer than the outlier window, the baseline should cover slow drift
+    pub const ANOMALY_WINDOW: usize = 32;
+    pub const ANOMALY_MIN_SAMPLES: usize = 12;
+    // Floor of the baseline's standard deviation, the larger of the two: a
+    // flat signal (a quantised sensor in a still room) would otherwise flag
+    // its first one-step change
+    pub const ANOMALY_MIN_DEVIATION: f32 = 0.1;
+    pub const ANOMALY_MIN_RELATIVE_DEVIATION: f32 = 0.001;
+    // Bounds the history of a rate alert, spread evenly over its window
+    pub const RATE_ALERT_MAX_SAMPLES: usize = 120;
+    // Temperature at which compensated sensors read true, the usual
+    // datasheet calibration point
+    pub const COMPENSATION_REFERENCE_C: f32 = 25.0;
+    // An older temperature is not trusted for compensation
+    pub const COMPENSATION_MAX_AGE_S: u64 = 120;
+}
@@ -0,0 +1,20 @@
+pub struct ProfileConstant;
+
+impl ProfileConstant {
+    pub const DEFAULT_PROFILE: &'static str = "normal";
+    pub const SET_COMMAND: &'static str = "set_profile";
+
+    pub const FAST_INTERVAL_MS: u64 = 1_000;
+    pub const FAST_BATCH_MAX_RECORDS: usize = 1;
+    pub const FAST_BATCH_MAX_AGE_S: u64 = 5;
+
+    pub const ECO_INTERVAL_MS: u64 = 900_000;
+    pub const ECO_BATCH_MAX_RECORDS: usize = 16;
+    pub const ECO_BATCH_MAX_AGE_S: u64 = 3_600;
+    pub const ECO_DEEP_SLEEP_INTERVAL_S: u64 = 900;
+
+    // Eco is forced below this charge and released again a few percent
+    // higher, so a battery hovering at the limit does not flap
+    pub const DEFAULT_ECO_BATTERY_PERCENT: u8 = 20;
+    pub const ECO_BATTERY_HYSTERESIS_PERCENT: u8 = 5;
+}
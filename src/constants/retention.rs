@@ -0,0 +1,11 @@
+pub struct RetentionConstant;
+
+impl RetentionConstant {
+    // Empty: the queue is only bounded by its partition, see
+    // `StorageConstant::QUEUE_MAX_SEGMENTS`, and the SD card by its size
+    pub const DEFAULT_QUEUE: &'static str = "";
+    pub const DEFAULT_SD: &'static str = "";
+    pub const COMPACT_INTERVAL_S: u64 = 3600;
+    // Queue records read per pass while looking for expired ones
+    pub const COMPACT_BATCH_RECORDS: usize = 64;
+}
@@ -0,0 +1,12 @@
+pub struct RfidConstant;
+
+impl RfidConstant {
+    // Long enough for a site visit, short enough that a forgotten unlock
+    // does not stay open for the day
+    pub const DEFAULT_UNLOCK_S: u64 = 900;
+    // The same card presented again within this is one visit
+    pub const SCAN_HOLDOFF_MS: u64 = 5000;
+
+    pub const UID: &'static str = "uid";
+    pub const AUTHORIZED: &'static str = "authorized";
+}
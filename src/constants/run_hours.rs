@@ -0,0 +1,21 @@
+pub struct RunHoursConstant;
+
+impl RunHoursConstant {
+    // Vibration RMS of a running pump or motor is well above the noise
+    // floor of a MEMS accelerometer (about 0.05 m/s²) and below shocks
+    pub const DEFAULT_RMS_MS2: f32 = 0.3;
+    // RMS is taken over blocks of this many samples, one second at 100 Hz
+    pub const WINDOW_SAMPLES: u32 = 100;
+    // Consecutive blocks above or below the threshold to change state, so
+    // a door slam does not start the machine and a pause does not stop it
+    pub const START_WINDOWS: u8 = 3;
+    pub const STOP_WINDOWS: u8 = 10;
+    // How often the counter is written while running; a reset loses at
+    // most this much, and flash sees a few thousand writes a year
+    pub const PERSIST_INTERVAL_S: u64 = 600;
+
+    pub const RUN_HOURS: &'static str = "run_hours";
+    pub const RUNNING: &'static str = "running";
+    pub const RMS_MS2: &'static str = "rms_ms2";
+    pub const RUN_S: &'static str = "run_s";
+}
@@ -0,0 +1,18 @@
+pub struct Sdi12Constant;
+
+impl Sdi12Constant {
+    // 1200 baud 7E1, the only rate the standard allows
+    pub const BAUD: u32 = 1200;
+    // At least 12 ms of spacing wakes every sensor on the bus, followed by
+    // at least 8.33 ms of marking before the command
+    pub const BREAK_MS: u32 = 13;
+    pub const MARKING_MS: u32 = 9;
+    // A sensor starts answering within 15 ms and a line is at most 75
+    // characters of 8.33 ms each
+    pub const RESPONSE_TIMEOUT_MS: u32 = 700;
+    // The standard asks masters to retry an unanswered command
+    pub const RETRIES: u8 = 3;
+    // Data commands D0..D9 each return part of the values
+    pub const MAX_DATA_COMMANDS: u8 = 10;
+    pub const DEFAULT_COMMAND: &'static str = "M";
+}
@@ -0,0 +1,28 @@
+pub struct SecretConstant;
+
+impl SecretConstant {
+    // Lives on the `queue` partition next to the flash queue directory
+    pub const SECRETS_DIR: &'static str = "/secrets";
+    pub const MAX_SECRET_BYTES: usize = 4096;
+    // Layout of a sealed file, see `SecretStoreService`
+    pub const SEALED_MAGIC: &'static [u8] = b"SEC1";
+    pub const NONCE_BYTES: usize = 12;
+    pub const TAG_BYTES: usize = 16;
+    pub const MAX_SEALED_BYTES: usize = 4 + Self::NONCE_BYTES + Self::MAX_SECRET_BYTES + Self::TAG_BYTES;
+
+    pub const WIFI_CA_CERT: &'static str = "wifi_ca.pem";
+    pub const WIFI_CLIENT_CERT: &'static str = "wifi_client.pem";
+    pub const WIFI_CLIENT_KEY: &'static str = "wifi_client.key";
+
+    // Not secret, but must survive reboots and OTA updates just the same
+    pub const OTA_VERSION_FLOOR: &'static str = "ota_version_floor";
+
+    pub const TLS_CA_CHAIN: &'static str = "tls_ca.pem";
+    pub const TLS_CLIENT_CERT: &'static str = "tls_client.pem";
+    pub const TLS_CLIENT_KEY: &'static str = "tls_client.key";
+    // Rotation writes the pair not in use, then flips this one-byte marker:
+    // "b" selects the pair below, absent or "a" the one above
+    pub const TLS_CLIENT_SLOT: &'static str = "tls_client.slot";
+    pub const TLS_CLIENT_CERT_B: &'static str = "tls_client_b.pem";
+    pub const TLS_CLIENT_KEY_B: &'static str = "tls_client_b.key";
+}
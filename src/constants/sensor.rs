@@ -1,6 +1,7 @@
 pub struct SensorConstant;
 
 impl SensorConstant {
+    pub const AMG8833: &'static str = "amg8833";
     pub const BH1750: &'static str = "bh1750";
     pub const BME280: &'static str = "bme280";
     pub const BME680: &'static str = "bme680";
@@ -8,4 +9,57 @@ impl SensorConstant {
     pub const LSM303DLHACCEL: &'static str = "lsm303dlhaccel";
     pub const LSM303DLHMAG: &'static str = "lsm303dlhmag";
     pub const VL5310X: &'static str = "vl53l0x";
+    // RTC die temperature, 0.25 °C steps refreshed every 64 s
+    pub const DS3231_TEMP: &'static str = "ds3231_temp";
+
+    pub const DEFAULT_INTERVAL_MS: u64 = 1000;
+
+    // ADDR pin low; 0x5c with ADDR high
+    pub const BH1750_ADDRESS: u8 = 0x23;
+    pub const BH1750_DEFAULT_MODE: &'static str = "one_time";
+    pub const BH1750_DEFAULT_RESOLUTION: &'static str = "h";
+    // Measurement time register, the datasheet default is 69; lower values
+    // extend the range in sunlight, higher ones the sensitivity in the dark
+    pub const BH1750_DEFAULT_MTREG: u8 = 69;
+    pub const BH1750_MIN_MTREG: u8 = 31;
+    pub const BH1750_MAX_MTREG: u8 = 254;
+    // SDO low; 0x77 with SDO high
+    pub const BME280_ADDRESS: u8 = 0x76;
+    pub const BME280_DEFAULT_MODE: &'static str = "forced";
+    // Datasheet "indoor navigation" profile minus the fast rate: heavy
+    // pressure oversampling and filtering against HVAC draughts
+    pub const BME280_DEFAULT_TEMPERATURE_OVERSAMPLING: u8 = 2;
+    pub const BME280_DEFAULT_PRESSURE_OVERSAMPLING: u8 = 16;
+    pub const BME280_DEFAULT_HUMIDITY_OVERSAMPLING: u8 = 1;
+    pub const BME280_DEFAULT_FILTER: u8 = 16;
+    pub const BME280_DEFAULT_STANDBY_MS: f32 = 500.0;
+    // Thermal time constant of the board around the BME280
+    pub const SELF_HEATING_TAU_S: f32 = 300.0;
+    pub const SELF_HEATING_PROBE_MS: u64 = 100;
+    pub const LSM303DLHC_ACCEL_ADDRESS: u8 = 0x19;
+    // Every VL53L0X boots at this address, units behind XSHUT pins are
+    // moved to consecutive addresses from `VL53L0X_FIRST_ADDRESS`
+    pub const VL53L0X_DEFAULT_ADDRESS: u8 = 0x29;
+    pub const VL53L0X_FIRST_ADDRESS: u8 = 0x30;
+    // Datasheet tBOOT is 1.2 ms
+    pub const VL53L0X_BOOT_MS: u32 = 2;
+    // A back-to-back ranging takes about 33 ms
+    pub const VL53L0X_POLL_MS: u64 = 5;
+
+    // Sensors not assigned to a bus in SENSOR_BUSES share this one
+    pub const DEFAULT_BUS: &'static str = "i2c0";
+    // Longest a single read may take before it is abandoned as TIMEOUT
+    pub const DEFAULT_READ_TIMEOUT_MS: u64 = 2000;
+    // How long a failed sensor keeps reporting its last good value
+    pub const DEFAULT_LAST_VALUE_MAX_AGE_S: u64 = 600;
+    // Fields added to a last good value reported in place of a failed read
+    pub const STALE: &'static str = "stale";
+    pub const AGE_S: &'static str = "age_s";
+
+    pub const DEFAULT_LUX_THRESHOLDS: &'static str =
+        "10:VERY_DARK,50:DARK,200:DIM,1000:NORMAL,5000:BRIGHT,10000:VERY_BRIGHT,EXTREME";
+    // °C; indoor comfort band after ASHRAE 55, loosely
+    pub const DEFAULT_TEMPERATURE_THRESHOLDS: &'static str = "0:FREEZING,18:COLD,26:COMFORTABLE,HOT";
+    // %RH
+    pub const DEFAULT_HUMIDITY_THRESHOLDS: &'static str = "30:DRY,60:COMFORTABLE,HUMID";
 }
\ No newline at end of file
@@ -0,0 +1,15 @@
+pub struct SoakConstant;
+
+impl SoakConstant {
+    pub const COMMAND: &'static str = "soak";
+    // Pre-shipment qualification length
+    pub const DURATION_S: u64 = 172_800;
+    // Every fitted sensor as fast as the loop allows, each reading uploaded
+    // on its own
+    pub const INTERVAL_MS: u64 = 250;
+    pub const BATCH_MAX_RECORDS: usize = 1;
+    pub const BATCH_MAX_AGE_S: u64 = 1;
+    // Counters are written to flash this often, a reset loses at most this
+    // much; more often would wear the flash over 48 h
+    pub const PERSIST_INTERVAL_S: u64 = 300;
+}
@@ -0,0 +1,8 @@
+pub struct StatusLedConstant;
+
+impl StatusLedConstant {
+    pub const FLASH_MS: u64 = 150;
+    pub const STAGE_PAUSE_MS: u64 = 1_000;
+    pub const RUNNING_PAUSE_MS: u64 = 5_000;
+    pub const ERROR_TOGGLE_MS: u64 = 100;
+}
@@ -0,0 +1,70 @@
+pub struct StorageConstant;
+
+impl StorageConstant {
+    pub const SD_VOLUME_INDEX: usize = 0;
+    pub const CSV_HEADER: &'static str = "timestamp,device_urn,location_urn,field,value\r\n";
+    pub const DEFAULT_LOG_FORMAT: &'static str = "csv";
+    pub const DEFAULT_STORE_MODE: &'static str = "backup";
+
+    // Must match the `queue` row in partitions.csv
+    pub const FLASH_SECTOR_SIZE: usize = 4096;
+    pub const QUEUE_PARTITION_OFFSET: u32 = 0x310000;
+    pub const QUEUE_PARTITION_SIZE: usize = 0xf0000;
+    pub const QUEUE_DIR: &'static str = "/queue";
+    pub const QUEUE_META_FILE: &'static str = "/queue/meta";
+    pub const QUEUE_SEGMENT_BYTES: usize = 4096;
+    pub const QUEUE_MAX_SEGMENTS: u32 = 192;
+    pub const QUEUE_BATCH_BYTES: usize = 1024;
+    // Measurements kept from the two oldest segments when the queue is
+    // full, one in this many, instead of dropping the oldest segment
+    pub const QUEUE_DOWNSAMPLE_FACTOR: u32 = 4;
+
+    // Remote configuration slots, on the same partition as the queue
+    pub const CONFIG_DIR: &'static str = "/config";
+    pub const CONFIG_ACTIVE_FILE: &'static str = "/config/active.json";
+    pub const CONFIG_PREVIOUS_FILE: &'static str = "/config/previous.json";
+    // Present while the active config has not yet proven itself, holding
+    // the boots it has been pending for as a little-endian u32
+    pub const CONFIG_PENDING_FILE: &'static str = "/config/pending";
+    // A pending config that keeps the device rebooting before it can
+    // upload is reverted at this boot, whatever the grace period
+    pub const CONFIG_PENDING_MAX_BOOTS: u32 = 3;
+    pub const CONFIG_MAX_BYTES: usize = 2048;
+    // Layout of `ServerConfigResponseDTO` this firmware reads and writes;
+    // bump it together with a step in `config_migration::MIGRATIONS`
+    pub const CONFIG_SCHEMA_VERSION: u16 = 2;
+    pub const DEFAULT_CONFIG_GRACE_PERIOD_S: u64 = 900;
+
+    // Incremented at every boot, part of each envelope id
+    pub const BOOT_COUNT_FILE: &'static str = "/boot_count";
+    // Furthest `BootStage` reached by the current boot, one byte
+    pub const BOOT_STAGE_FILE: &'static str = "/boot_stage";
+    // Accumulated machine run time in seconds, see `RunHoursService`
+    pub const RUN_SECONDS_FILE: &'static str = "/run_seconds";
+    // Learned hour-of-week pattern, see `ScheduleBaselineService`
+    pub const BASELINE_FILE: &'static str = "/baseline";
+    // Production line test result on boards without an EEPROM
+    pub const MFG_TEST_FILE: &'static str = "/mfg_test";
+    // Left by the console's "mfg_test" for the next boot to run the test
+    pub const MFG_TEST_REQUEST_FILE: &'static str = "/mfg_test_request";
+    // Counters of a running or finished soak test, see `soak`
+    pub const SOAK_FILE: &'static str = "/soak";
+    // End of the tamper arming window in unix seconds, 0 when open-ended;
+    // absent while disarmed, see `TamperService`
+    pub const TAMPER_FILE: &'static str = "/tamper";
+    // Name of the sensing profile last selected at runtime, see
+    // `ProfileService`
+    pub const PROFILE_FILE: &'static str = "/profile";
+
+    // Layouts of the state files above, see `state_file`; bump one together
+    // with an arm that still reads the previous layout
+    pub const QUEUE_META_VERSION: u8 = 1;
+    pub const CONFIG_PENDING_VERSION: u8 = 1;
+    pub const BOOT_COUNT_VERSION: u8 = 1;
+    pub const BOOT_STAGE_VERSION: u8 = 1;
+    pub const RUN_SECONDS_VERSION: u8 = 1;
+    pub const BASELINE_VERSION: u8 = 1;
+    pub const SOAK_VERSION: u8 = 1;
+    pub const TAMPER_VERSION: u8 = 1;
+    pub const PROFILE_VERSION: u8 = 1;
+}
@@ -0,0 +1,9 @@
+pub struct SupervisorConstant;
+
+impl SupervisorConstant {
+    // Doubles after every consecutive failure, reset by a successful run
+    pub const INITIAL_BACKOFF_MS: u64 = 1_000;
+    pub const MAX_BACKOFF_MS: u64 = 300_000;
+    // Shortest time between the starts of two successful runs
+    pub const MIN_PERIOD_MS: u64 = 10;
+}
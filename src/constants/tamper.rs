@@ -0,0 +1,27 @@
+pub struct TamperConstant;
+
+impl TamperConstant {
+    pub const ARM_COMMAND: &'static str = "arm_tamper";
+    pub const DISARM_COMMAND: &'static str = "disarm_tamper";
+    // Time to close the enclosure and walk away after arming
+    pub const ARM_DELAY_S: u64 = 30;
+    // One event per cause within this, a door left open is one tamper
+    pub const HOLDOFF_S: u64 = 60;
+
+    // Acceleration magnitude this far off 1 g is a knock or a blow
+    pub const DEFAULT_SHOCK_MS2: f32 = 6.0;
+    // Gravity vector this far off its armed direction means the enclosure
+    // was moved or tilted, about 15° at 1 g
+    pub const DEFAULT_TILT_MS2: f32 = 2.5;
+    // A jump of this many lux over the dark baseline means it was opened
+    pub const DEFAULT_LIGHT_LUX: f32 = 20.0;
+    // Weight of a new reading in the light baseline
+    pub const LIGHT_BASELINE_WEIGHT: f32 = 0.1;
+    pub const GRAVITY_MS2: f32 = 9.80665;
+
+    pub const CAUSE: &'static str = "cause";
+    pub const READING: &'static str = "reading";
+    pub const MOVED: &'static str = "moved";
+    pub const STRUCK: &'static str = "struck";
+    pub const OPENED: &'static str = "opened";
+}
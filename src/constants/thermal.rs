@@ -0,0 +1,29 @@
+pub struct ThermalConstant;
+
+impl ThermalConstant {
+    // AD_SELECT high; 0x68 with it low
+    pub const AMG8833_ADDRESS: u8 = 0x69;
+    pub const ROWS: usize = 8;
+    pub const COLUMNS: usize = 8;
+    // Pixels are 12-bit two's complement, the thermistor 12-bit sign and magnitude
+    pub const PIXEL_C: f32 = 0.25;
+    pub const THERMISTOR_C: f32 = 0.0625;
+    // The first frame after a reset is garbage until the sensor settles
+    pub const SETTLE_MS: u32 = 100;
+
+    // Hottest pixel that triggers a frame capture, °C
+    pub const DEFAULT_ALERT_C: f32 = 60.0;
+    // Frames are also sent this often without an alert; 0 only on alerts
+    pub const DEFAULT_FRAME_INTERVAL_S: u64 = 0;
+    // Minimum spacing of alert frames so a lasting hot spot is not streamed
+    pub const ALERT_HOLDOFF_S: u64 = 60;
+
+    pub const FRAME_FIELD: &'static str = "frame";
+    pub const FRAME_CONTENT_TYPE: &'static str = "application/octet-stream";
+
+    pub const MIN_C: &'static str = "thermal_min_c";
+    pub const MAX_C: &'static str = "thermal_max_c";
+    pub const MEAN_C: &'static str = "thermal_mean_c";
+    pub const HOTSPOT: &'static str = "thermal_hotspot";
+    pub const THERMISTOR_C_FIELD: &'static str = "thermistor_c";
+}
@@ -0,0 +1,10 @@
+pub struct UdpConstant;
+
+impl UdpConstant {
+    pub const LOCAL_PORT: u16 = 47_001;
+    // Sequence number prefixed to every datagram, echoed back as the ack
+    pub const HEADER_BYTES: usize = 4;
+    pub const BUFFER_BYTES: usize = 512;
+    pub const ACK_TIMEOUT_MS: u64 = 50;
+    pub const ACK_RETRIES: u8 = 2;
+}
@@ -2,10 +2,31 @@ pub struct UnitConstant;
 
 impl UnitConstant {
     pub const TEMPERATURE: &'static str = "°C";      // Celsius
-    pub const HUMIDITY: &'static str = "%";          // Percent
-    pub const PRESSURE: &'static str = "hPa";        // Hectopascal
+    pub const HUMIDITY: &'static str = "%RH";        // Relative humidity
+    pub const PERCENT: &'static str = "%";           // Plain percentage, e.g. battery
+    pub const PRESSURE: &'static str = "Pa";         // Pascal, as the BME280 reports it
     pub const LUMINOSITY: &'static str = "lux";      // Lux
     pub const DISTANCE: &'static str = "mm";         // Millimeter
     pub const ACCELERATION: &'static str = "m/s²";   // Meters per second squared
     pub const MAGNETIC_FIELD: &'static str = "µT";   // Microtesla
-}
\ No newline at end of file
+    pub const CO2: &'static str = "ppm";             // Parts per million
+    pub const PARTICULATE: &'static str = "µg/m³";   // Micrograms per cubic meter
+    pub const AQI: &'static str = "AQI";             // Air quality index, 0-500
+    pub const SOUND_LEVEL: &'static str = "dB SPL";  // Sound pressure level
+    pub const VOC_INDEX: &'static str = "VOC index"; // Sensirion index, 1-500 around a 100 baseline
+    pub const DURATION: &'static str = "s";          // Second
+    pub const COUNT: &'static str = "count";         // Dimensionless tally
+    pub const FAHRENHEIT: &'static str = "°F";       // Fahrenheit
+    pub const INCH: &'static str = "in";             // Inch
+    pub const INCH_OF_MERCURY: &'static str = "inHg"; // Inch of mercury at 0 °C
+
+    pub const DEFAULT_SYSTEM: &'static str = "metric";
+
+    // Factors to the SI base unit, for units that are not one
+    pub const DISTANCE_SI_SCALE: f32 = 0.001;
+    pub const MAGNETIC_FIELD_SI_SCALE: f32 = 0.000_001;
+    pub const INCH_SI_SCALE: f32 = 0.0254;
+    pub const INCH_OF_MERCURY_SI_SCALE: f32 = 3386.389;
+
+    pub const INCH_MM: f32 = 25.4;
+}
@@ -0,0 +1,20 @@
+pub struct UploadConstant;
+
+impl UploadConstant {
+    // Per-target retry queue depth held in RAM
+    pub const TARGET_QUEUE_DEPTH: usize = 32;
+    // Events are never evicted, instead they may grow the queue this far
+    // past its depth before new ones are refused
+    pub const EVENT_QUEUE_EXTRA: usize = 32;
+    pub const DEFAULT_BATCH_MAX_RECORDS: usize = 10;
+    pub const DEFAULT_BATCH_MAX_AGE_S: u64 = 60;
+    pub const MEASUREMENT_CHANNEL_DEPTH: usize = 8;
+    // Events raised by detectors outside the sensing loop awaiting upload
+    pub const EVENT_OUTBOX_DEPTH: usize = 16;
+    // Bounds on one request while forwarding the persistent queue, which
+    // is also the most a dropped connection makes the device resend
+    pub const FORWARD_CHUNK_RECORDS: usize = 20;
+    pub const FORWARD_CHUNK_BYTES: usize = 8192;
+    // Cap on a server's Retry-After, so a bogus value cannot mute a target
+    pub const MAX_RETRY_AFTER_S: u64 = 3600;
+}
@@ -0,0 +1,12 @@
+pub struct WifiConstant;
+
+impl WifiConstant {
+    pub const SCAN_MAX_RESULTS: usize = 16;
+    // Start looking for a better AP once the current one drops below this
+    pub const DEFAULT_ROAM_RSSI_DBM: i8 = -75;
+    // A candidate must beat the current AP by this much to be worth the hop
+    pub const ROAM_HYSTERESIS_DB: i8 = 8;
+    pub const MAINTAIN_INTERVAL_S: u64 = 15;
+    // Bounds a maintenance run that scans and works through every profile
+    pub const MAINTAIN_TIMEOUT_S: u64 = 60;
+}
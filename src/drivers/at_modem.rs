@@ -0,0 +1,138 @@
+use alloc::format;
+use alloc::string::String;
+
+use embassy_time::{with_timeout, Duration, Instant};
+use embedded_io_async::{Read, Write};
+
+use crate::constants::cellular::CellularConstant;
+
+#[derive(Debug)]
+pub enum AtModemError<E> {
+    Uart(E),
+    // The modem answered ERROR / +CME ERROR, carrying the response text
+    Rejected(String),
+    Timeout(String),
+    Unexpected(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignalQuality {
+    pub rssi_dbm: Option<i16>,
+    pub bit_error_rate: Option<u8>,
+}
+
+// Line oriented AT command driver using the SIMCom CIP* TCP stack, which
+// both the SIM800 and SIM7000 families implement. Responses are awaited on
+// the async UART, so a slow attach does not hold up other tasks
+pub struct AtModem<U: Read + Write> {
+    uart: U,
+}
+
+impl<U: Read + Write> AtModem<U> {
+    pub async fn new(uart: U) -> Result<Self, AtModemError<U::Error>> {
+        let mut modem = Self {
+            uart: uart,
+        };
+        modem.command("AT", "OK", CellularConstant::COMMAND_TIMEOUT_MS).await?;
+        // Echo would otherwise interleave with every response
+        modem.command("ATE0", "OK", CellularConstant::COMMAND_TIMEOUT_MS).await?;
+        Ok(modem)
+    }
+
+    pub async fn signal_quality(&mut self) -> Result<SignalQuality, AtModemError<U::Error>> {
+        let response = self.command("AT+CSQ", "OK", CellularConstant::COMMAND_TIMEOUT_MS).await?;
+        let line = response
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("+CSQ:"))
+            .ok_or_else(|| AtModemError::Unexpected(response.clone()))?;
+        let (rssi, ber) = line
+            .trim()
+            .split_once(',')
+            .ok_or_else(|| AtModemError::Unexpected(response.clone()))?;
+        let rssi: u8 = rssi.trim().parse().map_err(|_| AtModemError::Unexpected(response.clone()))?;
+        let ber: u8 = ber.trim().parse().map_err(|_| AtModemError::Unexpected(response.clone()))?;
+
+        // 99 means "not known or not detectable" for both values
+        Ok(SignalQuality {
+            rssi_dbm: if rssi == 99 { None } else { Some(-113 + 2 * rssi as i16) },
+            bit_error_rate: if ber == 99 { None } else { Some(ber) },
+        })
+    }
+
+    // Attaches to the packet network and brings up the PDP context
+    pub async fn connect_data(&mut self, apn: &str, user: &str, password: &str) -> Result<(), AtModemError<U::Error>> {
+        self.command("AT+CIPSHUT", "SHUT OK", CellularConstant::COMMAND_TIMEOUT_MS).await?;
+        self.command("AT+CGATT=1", "OK", CellularConstant::ATTACH_TIMEOUT_MS).await?;
+        self.command("AT+CIPMUX=0", "OK", CellularConstant::COMMAND_TIMEOUT_MS).await?;
+        self.command(
+            &format!("AT+CSTT=\"{}\",\"{}\",\"{}\"", apn, user, password),
+            "OK",
+            CellularConstant::COMMAND_TIMEOUT_MS,
+        )
+        .await?;
+        self.command("AT+CIICR", "OK", CellularConstant::ATTACH_TIMEOUT_MS).await?;
+        // CIFSR answers with the bare IP address instead of OK
+        self.command("AT+CIFSR", ".", CellularConstant::COMMAND_TIMEOUT_MS).await?;
+        Ok(())
+    }
+
+    pub async fn disconnect_data(&mut self) -> Result<(), AtModemError<U::Error>> {
+        self.command("AT+CIPSHUT", "SHUT OK", CellularConstant::COMMAND_TIMEOUT_MS).await?;
+        Ok(())
+    }
+
+    // Opens a TCP connection, writes `data` and returns whatever the peer
+    // sent back before closing the connection
+    pub async fn tcp_exchange(&mut self, host: &str, port: u16, data: &[u8]) -> Result<String, AtModemError<U::Error>> {
+        self.command(
+            &format!("AT+CIPSTART=\"TCP\",\"{}\",{}", host, port),
+            "CONNECT OK",
+            CellularConstant::CONNECT_TIMEOUT_MS,
+        )
+        .await?;
+        self.command(&format!("AT+CIPSEND={}", data.len()), ">", CellularConstant::COMMAND_TIMEOUT_MS).await?;
+        self.uart.write_all(data).await.map_err(AtModemError::Uart)?;
+        self.wait_for("SEND OK", CellularConstant::CONNECT_TIMEOUT_MS).await?;
+
+        let reply = self.wait_for("CLOSED", CellularConstant::RESPONSE_TIMEOUT_MS).await?;
+        Ok(reply)
+    }
+
+    pub async fn command(&mut self, command: &str, expected: &str, timeout_ms: u64) -> Result<String, AtModemError<U::Error>> {
+        self.drain().await?;
+        self.uart.write_all(command.as_bytes()).await.map_err(AtModemError::Uart)?;
+        self.uart.write_all(b"\r\n").await.map_err(AtModemError::Uart)?;
+        self.wait_for(expected, timeout_ms).await
+    }
+
+    async fn wait_for(&mut self, expected: &str, timeout_ms: u64) -> Result<String, AtModemError<U::Error>> {
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        let mut response = String::new();
+        let mut buffer = [0u8; 64];
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let read = match with_timeout(remaining, self.uart.read(&mut buffer)).await {
+                Ok(read) => read.map_err(AtModemError::Uart)?,
+                Err(_) => return Err(AtModemError::Timeout(String::from(expected))),
+            };
+            response.push_str(&String::from_utf8_lossy(&buffer[..read]));
+            if response.contains(expected) {
+                return Ok(response);
+            }
+            if response.contains("ERROR") {
+                return Err(AtModemError::Rejected(response));
+            }
+        }
+    }
+
+    // Discards unsolicited result codes left over from earlier commands,
+    // until the modem has been quiet for a moment
+    async fn drain(&mut self) -> Result<(), AtModemError<U::Error>> {
+        let mut buffer = [0u8; 64];
+        let quiet = Duration::from_millis(CellularConstant::DRAIN_QUIET_MS);
+        while let Ok(read) = with_timeout(quiet, self.uart.read(&mut buffer)).await {
+            read.map_err(AtModemError::Uart)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use alloc::vec::Vec;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+
+// tWR is 5 ms on every 24Cxx part; ACK polling usually ends sooner
+const WRITE_CYCLE_POLLS: u32 = 10;
+const WRITE_CYCLE_POLL_MS: u32 = 1;
+
+#[derive(Debug)]
+pub enum Eeprom24CxxError<E> {
+    I2c(E),
+    // Offset and length past the end of the part
+    OutOfRange,
+    // The part never acknowledged after a page write
+    Timeout,
+}
+
+// Microchip/ST/Atmel 24Cxx serial EEPROMs: 24C01-24C16 take a one byte
+// word address, 24C32 and up two. Writes are split on page boundaries, the
+// part wraps within a page otherwise
+pub struct Eeprom24Cxx<I: I2c, D: DelayNs> {
+    i2c: I,
+    delay: D,
+    address: u8,
+    capacity: usize,
+    page_size: usize,
+    wide_address: bool,
+}
+
+impl<I: I2c, D: DelayNs> Eeprom24Cxx<I, D> {
+    pub fn new(i2c: I, delay: D, address: u8, capacity: usize, page_size: usize) -> Self {
+        Self {
+            i2c: i2c,
+            delay: delay,
+            address: address,
+            capacity: capacity,
+            page_size: page_size,
+            wide_address: capacity > 2048,
+        }
+    }
+
+    // 24C02: 256 bytes in 8 byte pages
+    pub fn new_24c02(i2c: I, delay: D, address: u8) -> Self {
+        Self::new(i2c, delay, address, 256, 8)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn read(&mut self, offset: usize, buffer: &mut [u8]) -> Result<(), Eeprom24CxxError<I::Error>> {
+        self.check_range(offset, buffer.len())?;
+        let (address, word) = self.word_address(offset);
+        self.i2c.write_read(address, &word, buffer).map_err(Eeprom24CxxError::I2c)
+    }
+
+    pub fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), Eeprom24CxxError<I::Error>> {
+        self.check_range(offset, data.len())?;
+        let mut written = 0;
+        while written < data.len() {
+            let start = offset + written;
+            let length = (self.page_size - start % self.page_size).min(data.len() - written);
+            let (address, word) = self.word_address(start);
+            let mut frame: Vec<u8> = Vec::with_capacity(word.len() + length);
+            frame.extend_from_slice(&word);
+            frame.extend_from_slice(&data[written..written + length]);
+            self.i2c.write(address, &frame).map_err(Eeprom24CxxError::I2c)?;
+            self.wait_write_cycle(address)?;
+            written += length;
+        }
+        Ok(())
+    }
+
+    fn check_range(&self, offset: usize, length: usize) -> Result<(), Eeprom24CxxError<I::Error>> {
+        if offset + length > self.capacity {
+            return Err(Eeprom24CxxError::OutOfRange);
+        }
+        Ok(())
+    }
+
+    // One byte parts above 256 bytes carry the high address bits in the
+    // device address
+    fn word_address(&self, offset: usize) -> (u8, Vec<u8>) {
+        if self.wide_address {
+            (self.address, [(offset >> 8) as u8, offset as u8].to_vec())
+        } else {
+            (self.address | ((offset >> 8) as u8 & 0x07), [offset as u8].to_vec())
+        }
+    }
+
+    // The part does not acknowledge its address until the write has landed
+    fn wait_write_cycle(&mut self, address: u8) -> Result<(), Eeprom24CxxError<I::Error>> {
+        for _ in 0..WRITE_CYCLE_POLLS {
+            self.delay.delay_ms(WRITE_CYCLE_POLL_MS);
+            if self.i2c.write(address, &[]).is_ok() {
+                return Ok(());
+            }
+        }
+        Err(Eeprom24CxxError::Timeout)
+    }
+}
@@ -0,0 +1,81 @@
+use alloc::string::String;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::mutex::Mutex;
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c as BlockingI2c, Operation, SevenBitAddress};
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+use esp_hal::i2c::master::{Error, I2c};
+use esp_hal::Async;
+
+// One I2C controller in async mode, shared by every sensor configured on it
+// (see `SensorsConfigDTO::buses`). Transfers wait on the controller's
+// interrupt instead of spinning, so a burst read of an IMU FIFO or a whole
+// thermal frame lets the executor run other tasks meanwhile. The ESP32's
+// I2C has no DMA, its FIFO is drained from the interrupt instead
+pub struct I2cBus {
+    name: String,
+    bus: Mutex<CriticalSectionRawMutex, I2c<'static, Async>>,
+}
+
+impl I2cBus {
+    pub fn new(name: String, i2c: I2c<'static, Async>) -> Self {
+        Self {
+            name: name,
+            bus: Mutex::new(i2c),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    // A handle for one driver, each transaction takes the bus for its
+    // duration only
+    pub fn device(&self) -> I2cBusDevice<'_> {
+        I2cBusDevice { bus: self }
+    }
+}
+
+#[derive(Debug)]
+pub enum I2cBusError {
+    I2c(Error),
+    // A blocking transaction found an async one of another driver holding
+    // the bus across an await; waiting would deadlock the executor
+    Busy,
+}
+
+impl embedded_hal::i2c::Error for I2cBusError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            I2cBusError::I2c(error) => error.kind(),
+            I2cBusError::Busy => ErrorKind::Other,
+        }
+    }
+}
+
+// Implements both the async and the blocking embedded-hal traits, the
+// latter for drivers and third-party crates that only know those. A
+// blocking transaction runs to completion once it has the bus, and fails
+// with `Busy` instead of waiting for it
+#[derive(Clone, Copy)]
+pub struct I2cBusDevice<'a> {
+    bus: &'a I2cBus,
+}
+
+impl ErrorType for I2cBusDevice<'_> {
+    type Error = I2cBusError;
+}
+
+impl AsyncI2c<SevenBitAddress> for I2cBusDevice<'_> {
+    async fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), I2cBusError> {
+        let mut bus = self.bus.bus.lock().await;
+        AsyncI2c::transaction(&mut *bus, address, operations).await.map_err(I2cBusError::I2c)
+    }
+}
+
+impl BlockingI2c<SevenBitAddress> for I2cBusDevice<'_> {
+    fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), I2cBusError> {
+        let mut bus = self.bus.bus.try_lock().map_err(|_| I2cBusError::Busy)?;
+        BlockingI2c::transaction(&mut *bus, address, operations).map_err(I2cBusError::I2c)
+    }
+}
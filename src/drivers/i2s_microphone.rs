@@ -0,0 +1,83 @@
+use esp_hal::dma::DmaChannelFor;
+use esp_hal::dma_circular_buffers;
+use esp_hal::gpio::interconnect::{PeripheralInput, PeripheralOutput};
+use esp_hal::i2s::master::asynch::I2sReadDmaTransferAsync;
+use esp_hal::i2s::master::{DataFormat, Error, I2s, Instance, Standard};
+use esp_hal::i2s::AnyI2s;
+use esp_hal::time::Rate;
+use log::{error, info};
+
+use crate::constants::audio::AudioConstant;
+use crate::services::audio_events::AudioEventService;
+use crate::services::{event_outbox, offload};
+
+// I2S MEMS microphone (INMP441, SPH0645 and the like) read through a DMA
+// ring, so samples keep arriving while the executor runs other tasks. The
+// peripheral always clocks two channels; the microphone answers on the left
+// one and the right one is skipped
+pub struct I2sMicrophone {
+    transfer: I2sReadDmaTransferAsync<'static, &'static mut [u8; AudioConstant::DMA_BUFFER_BYTES]>,
+}
+
+impl I2sMicrophone {
+    pub fn new(
+        i2s: impl Instance + 'static,
+        dma: impl DmaChannelFor<AnyI2s<'static>>,
+        bclk: impl PeripheralOutput<'static>,
+        ws: impl PeripheralOutput<'static>,
+        din: impl PeripheralInput<'static>,
+    ) -> Result<Self, Error> {
+        let (buffer, descriptors, _, _) = dma_circular_buffers!(AudioConstant::DMA_BUFFER_BYTES, 0);
+        let rate = Rate::from_hz(AudioConstant::SAMPLE_RATE_HZ);
+        let rx = I2s::new(i2s, Standard::Philips, DataFormat::Data16Channel16, rate, dma)
+            .into_async()
+            .i2s_rx
+            .with_bclk(bclk)
+            .with_ws(ws)
+            .with_din(din)
+            .build(descriptors);
+        Ok(Self {
+            transfer: rx.read_dma_circular_async(buffer)?,
+        })
+    }
+
+    // Waits until `frame` is filled with the next samples
+    pub async fn read(&mut self, frame: &mut [i16]) -> Result<(), Error> {
+        let mut bytes = [0u8; AudioConstant::FRAME_SAMPLES * 4];
+        let bytes = &mut bytes[..frame.len() * 4];
+        let mut filled = 0;
+        while filled < bytes.len() {
+            filled += self.transfer.pop(&mut bytes[filled..]).await?;
+        }
+        for (sample, pair) in frame.iter_mut().zip(bytes.chunks_exact(4)) {
+            *sample = i16::from_le_bytes([pair[0], pair[1]]);
+        }
+        Ok(())
+    }
+}
+
+// Spawned in main when `Config::audio_events` enables a detector, after
+// `offload::start`: the detectors' filters run on the second core while the
+// next frame fills. A DMA error stops the task, the detectors are an extra,
+// not worth a reboot
+#[embassy_executor::task]
+pub async fn audio_task(mut microphone: I2sMicrophone, mut service: AudioEventService) {
+    info!("Listening for {} audio events", service.urn());
+    let mut frame = [0i16; AudioConstant::FRAME_SAMPLES];
+    loop {
+        if let Err(error) = microphone.read(&mut frame).await {
+            error!("Microphone read failed, audio events stopped: {:?}", error);
+            return;
+        }
+        // The service travels to the offload core with the frame and back
+        let event;
+        (service, event) = offload::run(move || {
+            let event = service.process(&frame);
+            (service, event)
+        })
+        .await;
+        if let Some(event) = event {
+            event_outbox::raise(event);
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use alloc::vec::Vec;
+
+use embassy_time::Timer;
+use embedded_hal::spi::{Operation, SpiDevice};
+
+const COMMAND: u8 = 0x01;
+const COM_IRQ: u8 = 0x04;
+const DIV_IRQ: u8 = 0x05;
+const ERROR: u8 = 0x06;
+const FIFO_DATA: u8 = 0x09;
+const FIFO_LEVEL: u8 = 0x0a;
+const BIT_FRAMING: u8 = 0x0d;
+const MODE: u8 = 0x11;
+const TX_CONTROL: u8 = 0x14;
+const TX_ASK: u8 = 0x15;
+const CRC_RESULT_HIGH: u8 = 0x21;
+const CRC_RESULT_LOW: u8 = 0x22;
+const T_MODE: u8 = 0x2a;
+const T_PRESCALER: u8 = 0x2b;
+const T_RELOAD_HIGH: u8 = 0x2c;
+const T_RELOAD_LOW: u8 = 0x2d;
+const VERSION: u8 = 0x37;
+
+const IDLE: u8 = 0x00;
+const CALC_CRC: u8 = 0x03;
+const TRANSCEIVE: u8 = 0x0c;
+const SOFT_RESET: u8 = 0x0f;
+
+// ISO 14443-3 type A
+const REQA: u8 = 0x26;
+const HLTA: u8 = 0x50;
+const SELECT: [u8; 3] = [0x93, 0x95, 0x97];
+const CASCADE_TAG: u8 = 0x88;
+const SAK_UID_INCOMPLETE: u8 = 0x04;
+
+const IRQ_RX_IDLE: u8 = 0x30;
+const IRQ_TIMER: u8 = 0x01;
+const IRQ_CRC: u8 = 0x04;
+// Buffer overflow, parity and protocol errors; collisions are left to the
+// UID check, a single reader rarely sees two cards at once
+const ERROR_MASK: u8 = 0x13;
+const TIMEOUT_MS: u32 = 30;
+
+#[derive(Debug)]
+pub enum Mfrc522Error<E> {
+    Spi(E),
+    // VERSION did not read as an MFRC522, usually wiring
+    NotFound(u8),
+    Protocol,
+    Timeout,
+}
+
+// Register level driver for the NXP MFRC522 (RC522 modules) on SPI, reading
+// the UID of ISO 14443-A cards: MIFARE badges and most NFC tags. Register
+// accesses are a few SPI bytes each, the waits on the card are timers
+pub struct Mfrc522<S: SpiDevice> {
+    spi: S,
+}
+
+impl<S: SpiDevice> Mfrc522<S> {
+    pub async fn new(spi: S) -> Result<Self, Mfrc522Error<S::Error>> {
+        let mut reader = Self { spi: spi };
+        reader.write(COMMAND, SOFT_RESET)?;
+        Timer::after_millis(50).await;
+        let version = reader.read(VERSION)?;
+        if !matches!(version, 0x88 | 0x90 | 0x91 | 0x92 | 0xb2) {
+            return Err(Mfrc522Error::NotFound(version));
+        }
+        // Timer of 25 ms bounding every transceive
+        reader.write(T_MODE, 0x80)?;
+        reader.write(T_PRESCALER, 0xa9)?;
+        reader.write(T_RELOAD_HIGH, 0x03)?;
+        reader.write(T_RELOAD_LOW, 0xe8)?;
+        // 100% ASK, CRC preset 0x6363 as ISO 14443-3 wants
+        reader.write(TX_ASK, 0x40)?;
+        reader.write(MODE, 0x3d)?;
+        let tx_control = reader.read(TX_CONTROL)?;
+        reader.write(TX_CONTROL, tx_control | 0x03)?;
+        Ok(reader)
+    }
+
+    // UID of the card in the field, 4, 7 or 10 bytes, None without one. The
+    // card is halted afterwards so it only answers again once it has left
+    // the field and come back
+    pub async fn read_uid(&mut self) -> Result<Option<Vec<u8>>, Mfrc522Error<S::Error>> {
+        match self.transceive(&[REQA], 7).await {
+            Ok(answer) if answer.len() == 2 => {},
+            Ok(_) | Err(Mfrc522Error::Timeout) => return Ok(None),
+            Err(error) => return Err(error),
+        }
+
+        let mut uid = Vec::with_capacity(10);
+        for select in SELECT {
+            let answer = self.transceive(&[select, 0x20], 0).await?;
+            let part: [u8; 5] = answer.as_slice().try_into().map_err(|_| Mfrc522Error::Protocol)?;
+            if part[..4].iter().fold(0, |bcc, byte| bcc ^ byte) != part[4] {
+                return Err(Mfrc522Error::Protocol);
+            }
+            let mut frame = Vec::with_capacity(9);
+            frame.extend_from_slice(&[select, 0x70]);
+            frame.extend_from_slice(&part);
+            let crc = self.crc(&frame).await?;
+            frame.extend_from_slice(&crc);
+            let sak = *self.transceive(&frame, 0).await?.first().ok_or(Mfrc522Error::Protocol)?;
+
+            if sak & SAK_UID_INCOMPLETE == 0 {
+                uid.extend_from_slice(&part[..4]);
+                self.halt().await?;
+                return Ok(Some(uid));
+            }
+            if part[0] != CASCADE_TAG {
+                return Err(Mfrc522Error::Protocol);
+            }
+            uid.extend_from_slice(&part[1..4]);
+        }
+        Err(Mfrc522Error::Protocol)
+    }
+
+    async fn halt(&mut self) -> Result<(), Mfrc522Error<S::Error>> {
+        let mut frame = [HLTA, 0x00, 0x00, 0x00];
+        let crc = self.crc(&frame[..2]).await?;
+        frame[2..].copy_from_slice(&crc);
+        // A halted card does not answer, the timeout is the success case
+        match self.transceive(&frame, 0).await {
+            Ok(_) | Err(Mfrc522Error::Timeout) => Ok(()),
+            Err(error) => Err(error),
+        }
+    }
+
+    // `last_bits` is the number of valid bits in the last byte, 0 for all 8
+    async fn transceive(&mut self, data: &[u8], last_bits: u8) -> Result<Vec<u8>, Mfrc522Error<S::Error>> {
+        self.write(COMMAND, IDLE)?;
+        self.write(COM_IRQ, 0x7f)?;
+        self.write(FIFO_LEVEL, 0x80)?;
+        for byte in data {
+            self.write(FIFO_DATA, *byte)?;
+        }
+        self.write(BIT_FRAMING, last_bits)?;
+        self.write(COMMAND, TRANSCEIVE)?;
+        // StartSend
+        self.write(BIT_FRAMING, 0x80 | last_bits)?;
+
+        let mut irq = 0;
+        for _ in 0..TIMEOUT_MS {
+            irq = self.read(COM_IRQ)?;
+            if irq & (IRQ_RX_IDLE | IRQ_TIMER) != 0 {
+                break;
+            }
+            Timer::after_millis(1).await;
+        }
+        self.write(BIT_FRAMING, 0)?;
+        if irq & IRQ_RX_IDLE == 0 {
+            return Err(Mfrc522Error::Timeout);
+        }
+        if self.read(ERROR)? & ERROR_MASK != 0 {
+            return Err(Mfrc522Error::Protocol);
+        }
+        let length = self.read(FIFO_LEVEL)? as usize;
+        (0..length).map(|_| self.read(FIFO_DATA)).collect()
+    }
+
+    // CRC_A of `data` from the chip's coprocessor, low byte first
+    async fn crc(&mut self, data: &[u8]) -> Result<[u8; 2], Mfrc522Error<S::Error>> {
+        self.write(COMMAND, IDLE)?;
+        self.write(DIV_IRQ, 0x04)?;
+        self.write(FIFO_LEVEL, 0x80)?;
+        for byte in data {
+            self.write(FIFO_DATA, *byte)?;
+        }
+        self.write(COMMAND, CALC_CRC)?;
+        for _ in 0..TIMEOUT_MS {
+            if self.read(DIV_IRQ)? & IRQ_CRC != 0 {
+                self.write(COMMAND, IDLE)?;
+                return Ok([self.read(CRC_RESULT_LOW)?, self.read(CRC_RESULT_HIGH)?]);
+            }
+            Timer::after_millis(1).await;
+        }
+        Err(Mfrc522Error::Timeout)
+    }
+
+    // Address byte: register in bits 6..1, bit 7 set for reads
+    fn read(&mut self, register: u8) -> Result<u8, Mfrc522Error<S::Error>> {
+        let mut value = [0u8; 1];
+        self.spi
+            .transaction(&mut [Operation::Write(&[0x80 | (register << 1)]), Operation::Read(&mut value)])
+            .map_err(Mfrc522Error::Spi)?;
+        Ok(value[0])
+    }
+
+    fn write(&mut self, register: u8, value: u8) -> Result<(), Mfrc522Error<S::Error>> {
+        self.spi.write(&[(register << 1) & 0x7e, value]).map_err(Mfrc522Error::Spi)
+    }
+}
@@ -0,0 +1,14 @@
+pub mod at_modem;
+pub mod eeprom_24cxx;
+#[cfg(not(test))]
+pub mod i2c_bus;
+#[cfg(not(test))]
+pub mod i2s_microphone;
+pub mod mfrc522;
+pub mod modbus_rtu;
+pub mod sdi12;
+#[cfg(not(test))]
+pub mod spi_bus;
+pub mod sx127x;
+#[cfg(not(test))]
+pub mod w5500;
@@ -0,0 +1,107 @@
+use alloc::vec::Vec;
+
+use embassy_time::{with_timeout, Duration, Timer};
+use embedded_hal::digital::OutputPin;
+use embedded_io::ReadReady;
+use embedded_io_async::{Read, Write};
+
+use crate::constants::modbus::ModbusConstant;
+use crate::utilities::modbus::{self, ResponseError};
+
+#[derive(Debug)]
+pub enum ModbusError<E> {
+    Uart(E),
+    // The direction pin of the transceiver could not be switched
+    Pin,
+    Timeout,
+    Response(ResponseError),
+}
+
+// Modbus RTU master on a half-duplex RS-485 transceiver. Without a DE pin
+// the transceiver is expected to switch direction by itself
+pub struct ModbusRtu<U: Read + Write + ReadReady, P: OutputPin> {
+    uart: U,
+    de: Option<P>,
+    frame_gap_us: u32,
+    timeout_ms: u32,
+}
+
+impl<U: Read + Write + ReadReady, P: OutputPin> ModbusRtu<U, P> {
+    pub fn new(uart: U, mut de: Option<P>, baud: u32, timeout_ms: u32) -> Result<Self, ModbusError<U::Error>> {
+        if let Some(de) = de.as_mut() {
+            de.set_low().map_err(|_| ModbusError::Pin)?;
+        }
+        // 3.5 character times of silence delimit a frame
+        let frame_gap_us = if baud > ModbusConstant::FAST_BAUD {
+            ModbusConstant::FAST_FRAME_GAP_US
+        } else {
+            7 * ModbusConstant::CHARACTER_BITS * 1_000_000 / (2 * baud)
+        };
+        Ok(Self {
+            uart: uart,
+            de: de,
+            frame_gap_us: frame_gap_us,
+            timeout_ms: timeout_ms,
+        })
+    }
+
+    // Function 0x03 or 0x04, `count` registers from `address`
+    pub async fn read_registers(
+        &mut self,
+        slave: u8,
+        function: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u16>, ModbusError<U::Error>> {
+        self.drain().await?;
+        Timer::after_micros(self.frame_gap_us as u64).await;
+        self.transmit(&modbus::read_request(slave, function, address, count)).await?;
+        let frame = self.receive().await?;
+        modbus::parse_read_response(&frame, slave, function, count).map_err(ModbusError::Response)
+    }
+
+    async fn transmit(&mut self, frame: &[u8]) -> Result<(), ModbusError<U::Error>> {
+        if let Some(de) = self.de.as_mut() {
+            de.set_high().map_err(|_| ModbusError::Pin)?;
+        }
+        let written = match self.uart.write_all(frame).await {
+            Ok(()) => self.uart.flush().await,
+            Err(error) => Err(error),
+        };
+        // Released even when the write failed, or the bus stays blocked
+        if let Some(de) = self.de.as_mut() {
+            de.set_low().map_err(|_| ModbusError::Pin)?;
+        }
+        written.map_err(ModbusError::Uart)
+    }
+
+    // Other tasks run while the slave takes its time to answer
+    async fn receive(&mut self) -> Result<Vec<u8>, ModbusError<U::Error>> {
+        match with_timeout(Duration::from_millis(self.timeout_ms as u64), self.read_frame()).await {
+            Ok(frame) => frame,
+            Err(_) => Err(ModbusError::Timeout),
+        }
+    }
+
+    async fn read_frame(&mut self) -> Result<Vec<u8>, ModbusError<U::Error>> {
+        let mut frame = Vec::new();
+        let mut buffer = [0u8; 64];
+        while !modbus::expected_length(&frame).is_some_and(|length| frame.len() >= length) {
+            let read = self.uart.read(&mut buffer).await.map_err(ModbusError::Uart)?;
+            frame.extend_from_slice(&buffer[..read]);
+            if frame.len() > ModbusConstant::MAX_FRAME_BYTES {
+                return Err(ModbusError::Response(ResponseError::Malformed));
+            }
+        }
+        Ok(frame)
+    }
+
+    // Drops late answers to an earlier request that timed out
+    async fn drain(&mut self) -> Result<(), ModbusError<U::Error>> {
+        let mut buffer = [0u8; 64];
+        while self.uart.read_ready().map_err(ModbusError::Uart)? {
+            self.uart.read(&mut buffer).await.map_err(ModbusError::Uart)?;
+        }
+        Ok(())
+    }
+}
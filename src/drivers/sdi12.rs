@@ -0,0 +1,133 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use embassy_time::{with_timeout, Duration, Timer};
+use embedded_hal::digital::OutputPin;
+use embedded_io::ReadReady;
+use embedded_io_async::{Read, Write};
+
+use crate::constants::sdi12::Sdi12Constant;
+use crate::utilities::sdi12;
+
+#[derive(Debug)]
+pub enum Sdi12Error<E> {
+    Uart(E),
+    Pin,
+    // No answer after all retries
+    Timeout,
+    Unexpected(String),
+}
+
+// SDI-12 master on a UART set to 1200 baud 7E1 with inverted levels, its
+// TX and RX joined onto the single data line. The UART cannot hold the line
+// at spacing for a whole break, so a separate GPIO does that through the
+// interface circuit. Our own commands echo back on RX and are skipped
+pub struct Sdi12Bus<U: Read + Write + ReadReady, P: OutputPin> {
+    uart: U,
+    break_pin: P,
+}
+
+impl<U: Read + Write + ReadReady, P: OutputPin> Sdi12Bus<U, P> {
+    pub fn new(uart: U, mut break_pin: P) -> Result<Self, Sdi12Error<U::Error>> {
+        break_pin.set_low().map_err(|_| Sdi12Error::Pin)?;
+        Ok(Self {
+            uart: uart,
+            break_pin: break_pin,
+        })
+    }
+
+    // Runs `command` ("M", "M1".., "R0"..) on the probe at `address` and
+    // returns all of its values. The bus is held for the whole measurement,
+    // other tasks run while the probe converts
+    pub async fn measure(&mut self, address: char, command: &str) -> Result<Vec<f32>, Sdi12Error<U::Error>> {
+        if command.starts_with('R') {
+            let reply = self.transaction(&format!("{}{}!", address, command)).await?;
+            return sdi12::parse_values(&reply, address).ok_or(Sdi12Error::Unexpected(reply));
+        }
+
+        let reply = self.transaction(&format!("{}{}!", address, command)).await?;
+        let (seconds, count) =
+            sdi12::parse_measurement_reply(&reply, address).ok_or_else(|| Sdi12Error::Unexpected(reply.clone()))?;
+        if seconds > 0 {
+            // The probe sends a service request "a" when it is done early
+            let _ = self.read_line(seconds as u32 * 1000).await;
+        }
+
+        let mut values = Vec::with_capacity(count as usize);
+        for index in 0..Sdi12Constant::MAX_DATA_COMMANDS {
+            if values.len() >= count as usize {
+                break;
+            }
+            let reply = self.transaction(&format!("{}D{}!", address, index)).await?;
+            let part = sdi12::parse_values(&reply, address).ok_or_else(|| Sdi12Error::Unexpected(reply.clone()))?;
+            if part.is_empty() {
+                break;
+            }
+            values.extend(part);
+        }
+        if values.len() < count as usize {
+            return Err(Sdi12Error::Unexpected(format!("{} of {} values", values.len(), count)));
+        }
+        Ok(values)
+    }
+
+    async fn transaction(&mut self, command: &str) -> Result<String, Sdi12Error<U::Error>> {
+        for _ in 0..Sdi12Constant::RETRIES {
+            self.wake().await?;
+            self.drain().await?;
+            self.uart.write_all(command.as_bytes()).await.map_err(Sdi12Error::Uart)?;
+            self.uart.flush().await.map_err(Sdi12Error::Uart)?;
+            match self.read_line(Sdi12Constant::RESPONSE_TIMEOUT_MS).await {
+                Ok(line) => {
+                    // The echo of our command ends in '!'
+                    let reply = line.rsplit_once('!').map_or(line.as_str(), |(_, reply)| reply);
+                    if !reply.is_empty() {
+                        return Ok(String::from(reply));
+                    }
+                },
+                Err(Sdi12Error::Timeout) => continue,
+                Err(error) => return Err(error),
+            }
+        }
+        Err(Sdi12Error::Timeout)
+    }
+
+    async fn wake(&mut self) -> Result<(), Sdi12Error<U::Error>> {
+        self.break_pin.set_high().map_err(|_| Sdi12Error::Pin)?;
+        Timer::after_millis(Sdi12Constant::BREAK_MS as u64).await;
+        self.break_pin.set_low().map_err(|_| Sdi12Error::Pin)?;
+        Timer::after_millis(Sdi12Constant::MARKING_MS as u64).await;
+        Ok(())
+    }
+
+    // One line without its CR LF; parity is left to the UART, the eighth bit
+    // is masked off in case it is configured for 8 data bits
+    async fn read_line(&mut self, timeout_ms: u32) -> Result<String, Sdi12Error<U::Error>> {
+        match with_timeout(Duration::from_millis(timeout_ms as u64), self.read_until_end()).await {
+            Ok(line) => line,
+            Err(_) => Err(Sdi12Error::Timeout),
+        }
+    }
+
+    async fn read_until_end(&mut self) -> Result<String, Sdi12Error<U::Error>> {
+        let mut line = String::new();
+        let mut buffer = [0u8; 16];
+        loop {
+            let read = self.uart.read(&mut buffer).await.map_err(Sdi12Error::Uart)?;
+            line.extend(buffer[..read].iter().map(|byte| (byte & 0x7f) as char));
+            if let Some(end) = line.find("\r\n") {
+                line.truncate(end);
+                return Ok(line);
+            }
+        }
+    }
+
+    async fn drain(&mut self) -> Result<(), Sdi12Error<U::Error>> {
+        let mut buffer = [0u8; 16];
+        while self.uart.read_ready().map_err(Sdi12Error::Uart)? {
+            self.uart.read(&mut buffer).await.map_err(Sdi12Error::Uart)?;
+        }
+        Ok(())
+    }
+}
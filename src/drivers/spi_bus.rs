@@ -0,0 +1,121 @@
+use alloc::string::String;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::mutex::Mutex;
+use embassy_time::Timer;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::spi::{ErrorKind, ErrorType, Operation, SpiBus as BlockingSpiBus, SpiDevice as BlockingSpiDevice};
+use embedded_hal_async::spi::{SpiBus as AsyncSpiBus, SpiDevice as AsyncSpiDevice};
+use esp_hal::delay::Delay;
+use esp_hal::gpio::Output;
+use esp_hal::spi::master::Spi;
+use esp_hal::spi::Error;
+use esp_hal::Async;
+
+// VSPI in async mode, shared like `I2cBus` by the parts wired to it (see
+// `HardwareConstant::SPI_SCLK_GPIO`), each behind a chip select of its own
+pub struct SpiBus {
+    name: String,
+    bus: Mutex<CriticalSectionRawMutex, Spi<'static, Async>>,
+}
+
+impl SpiBus {
+    pub fn new(name: String, spi: Spi<'static, Async>) -> Self {
+        Self {
+            name: name,
+            bus: Mutex::new(spi),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    // A handle for the part selected by `cs`, each transaction takes the bus
+    // and asserts the chip select for its duration only
+    pub fn device(&self, mut cs: Output<'static>) -> SpiBusDevice<'_> {
+        cs.set_high();
+        SpiBusDevice { bus: self, cs: cs }
+    }
+}
+
+#[derive(Debug)]
+pub enum SpiBusError {
+    Spi(Error),
+    // A blocking transaction found an async one of another driver holding
+    // the bus across an await; waiting would deadlock the executor
+    Busy,
+}
+
+impl embedded_hal::spi::Error for SpiBusError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            SpiBusError::Spi(error) => embedded_hal::spi::Error::kind(error),
+            SpiBusError::Busy => ErrorKind::Other,
+        }
+    }
+}
+
+// Implements both the async and the blocking embedded-hal traits, the W5500
+// driver needs the former and the SD card the latter. As on `I2cBusDevice`
+// a blocking transaction fails with `Busy` instead of waiting for the bus
+pub struct SpiBusDevice<'a> {
+    bus: &'a SpiBus,
+    cs: Output<'static>,
+}
+
+impl ErrorType for SpiBusDevice<'_> {
+    type Error = SpiBusError;
+}
+
+impl AsyncSpiDevice for SpiBusDevice<'_> {
+    async fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), SpiBusError> {
+        let mut bus = self.bus.bus.lock().await;
+        self.cs.set_low();
+        let mut result = Ok(());
+        for operation in operations.iter_mut() {
+            result = match operation {
+                Operation::Read(words) => AsyncSpiBus::read(&mut *bus, words).await,
+                Operation::Write(words) => AsyncSpiBus::write(&mut *bus, words).await,
+                Operation::Transfer(read, write) => AsyncSpiBus::transfer(&mut *bus, read, write).await,
+                Operation::TransferInPlace(words) => AsyncSpiBus::transfer_in_place(&mut *bus, words).await,
+                Operation::DelayNs(ns) => {
+                    Timer::after_nanos(*ns as u64).await;
+                    Ok(())
+                },
+            };
+            if result.is_err() {
+                break;
+            }
+        }
+        let flushed = AsyncSpiBus::flush(&mut *bus).await;
+        self.cs.set_high();
+        result.and(flushed).map_err(SpiBusError::Spi)
+    }
+}
+
+impl BlockingSpiDevice for SpiBusDevice<'_> {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), SpiBusError> {
+        let mut bus = self.bus.bus.try_lock().map_err(|_| SpiBusError::Busy)?;
+        self.cs.set_low();
+        let mut result = Ok(());
+        for operation in operations.iter_mut() {
+            result = match operation {
+                Operation::Read(words) => BlockingSpiBus::read(&mut *bus, words),
+                Operation::Write(words) => BlockingSpiBus::write(&mut *bus, words),
+                Operation::Transfer(read, write) => BlockingSpiBus::transfer(&mut *bus, read, write),
+                Operation::TransferInPlace(words) => BlockingSpiBus::transfer_in_place(&mut *bus, words),
+                Operation::DelayNs(ns) => {
+                    Delay::new().delay_ns(*ns);
+                    Ok(())
+                },
+            };
+            if result.is_err() {
+                break;
+            }
+        }
+        let flushed = BlockingSpiBus::flush(&mut *bus);
+        self.cs.set_high();
+        result.and(flushed).map_err(SpiBusError::Spi)
+    }
+}
@@ -0,0 +1,190 @@
+use embassy_time::{Duration, Instant, Timer};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::{Operation, SpiDevice};
+
+use crate::constants::lora::LoRaConstant;
+
+const REG_FIFO: u8 = 0x00;
+const REG_OP_MODE: u8 = 0x01;
+const REG_FRF_MSB: u8 = 0x06;
+const REG_PA_CONFIG: u8 = 0x09;
+const REG_FIFO_ADDR_PTR: u8 = 0x0d;
+const REG_FIFO_TX_BASE_ADDR: u8 = 0x0e;
+const REG_IRQ_FLAGS: u8 = 0x12;
+const REG_MODEM_CONFIG_1: u8 = 0x1d;
+const REG_MODEM_CONFIG_2: u8 = 0x1e;
+const REG_PAYLOAD_LENGTH: u8 = 0x22;
+const REG_MODEM_CONFIG_3: u8 = 0x26;
+const REG_SYNC_WORD: u8 = 0x39;
+const REG_VERSION: u8 = 0x42;
+const REG_PA_DAC: u8 = 0x4d;
+
+const MODE_LONG_RANGE: u8 = 0x80;
+const MODE_SLEEP: u8 = 0x00;
+const MODE_STANDBY: u8 = 0x01;
+const MODE_TX: u8 = 0x03;
+const IRQ_TX_DONE: u8 = 0x08;
+const SX1276_VERSION: u8 = 0x12;
+const CRYSTAL_HZ: u64 = 32_000_000;
+
+#[derive(Debug)]
+pub enum Sx127xError<S, P> {
+    Spi(S),
+    Pin(P),
+    UnknownVersion(u8),
+    PayloadTooLarge(usize),
+    InvalidConfig,
+    Timeout,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Sx127xConfig {
+    pub frequency_hz: u32,
+    pub spreading_factor: u8,
+    pub bandwidth_hz: u32,
+    pub tx_power_dbm: u8,
+}
+
+// Register level driver for the SX1276/RFM95 in LoRa mode (transmit only)
+pub struct Sx127x<S: SpiDevice, P: OutputPin, D: DelayNs> {
+    spi: S,
+    reset: P,
+    delay: D,
+    config: Sx127xConfig,
+}
+
+impl<S: SpiDevice, P: OutputPin, D: DelayNs> Sx127x<S, P, D> {
+    pub fn new(
+        spi: S,
+        reset: P,
+        delay: D,
+        config: &Sx127xConfig,
+    ) -> Result<Self, Sx127xError<S::Error, P::Error>> {
+        let mut radio = Self {
+            spi: spi,
+            reset: reset,
+            delay: delay,
+            config: *config,
+        };
+        radio.hard_reset()?;
+
+        let version = radio.read_register(REG_VERSION)?;
+        if version != SX1276_VERSION {
+            return Err(Sx127xError::UnknownVersion(version));
+        }
+        // The LoRa bit can only be changed while sleeping
+        radio.write_register(REG_OP_MODE, MODE_LONG_RANGE | MODE_SLEEP)?;
+        radio.configure(config)?;
+        radio.write_register(REG_OP_MODE, MODE_LONG_RANGE | MODE_STANDBY)?;
+        Ok(radio)
+    }
+
+    pub fn configure(&mut self, config: &Sx127xConfig) -> Result<(), Sx127xError<S::Error, P::Error>> {
+        let bandwidth = match config.bandwidth_hz {
+            62_500 => 0x06,
+            125_000 => 0x07,
+            250_000 => 0x08,
+            500_000 => 0x09,
+            _ => return Err(Sx127xError::InvalidConfig),
+        };
+        // SF6 only works in implicit header mode, which the gateway side
+        // does not speak
+        if !(7..=12).contains(&config.spreading_factor) || !(2..=20).contains(&config.tx_power_dbm) {
+            return Err(Sx127xError::InvalidConfig);
+        }
+
+        let frf = ((config.frequency_hz as u64) << 19) / CRYSTAL_HZ;
+        self.write_register(REG_FRF_MSB, (frf >> 16) as u8)?;
+        self.write_register(REG_FRF_MSB + 1, (frf >> 8) as u8)?;
+        self.write_register(REG_FRF_MSB + 2, frf as u8)?;
+
+        // Explicit header, coding rate 4/5
+        self.write_register(REG_MODEM_CONFIG_1, (bandwidth << 4) | 0x02)?;
+        // Payload CRC on
+        self.write_register(REG_MODEM_CONFIG_2, (config.spreading_factor << 4) | 0x04)?;
+        let low_data_rate = if low_data_rate(config) { 0x08 } else { 0x00 };
+        self.write_register(REG_MODEM_CONFIG_3, low_data_rate | 0x04)?;
+        self.write_register(REG_SYNC_WORD, LoRaConstant::SYNC_WORD)?;
+
+        // RFM95 modules only route the PA_BOOST pin to the antenna
+        if config.tx_power_dbm > 17 {
+            self.write_register(REG_PA_DAC, 0x87)?;
+            self.write_register(REG_PA_CONFIG, 0x80 | (config.tx_power_dbm - 5))?;
+        } else {
+            self.write_register(REG_PA_DAC, 0x84)?;
+            self.write_register(REG_PA_CONFIG, 0x80 | (config.tx_power_dbm - 2))?;
+        }
+        self.config = *config;
+        Ok(())
+    }
+
+    // Time on air of a packet of `length` bytes with the current settings,
+    // per the SX1276 datasheet: explicit header, CR 4/5, CRC on
+    pub fn airtime_ms(&self, length: usize) -> u64 {
+        let spreading_factor = self.config.spreading_factor as i64;
+        let symbol_us = (1u64 << spreading_factor) * 1_000_000 / self.config.bandwidth_hz as u64;
+        let optimised = if low_data_rate(&self.config) { 2 } else { 0 };
+        let bits = 8 * length as i64 - 4 * spreading_factor + 28 + 16;
+        let per_block = 4 * (spreading_factor - optimised);
+        let payload_symbols = 8 + (bits.max(0) + per_block - 1) / per_block * 5;
+        // The preamble plus 4.25 symbols of sync word
+        let preamble_quarters = (LoRaConstant::PREAMBLE_SYMBOLS as u64 * 4) + 17;
+        (preamble_quarters * symbol_us / 4 + payload_symbols as u64 * symbol_us).div_ceil(1_000)
+    }
+
+    // Waits for TX_DONE on the timer, for the packet's airtime plus a
+    // margin, then returns to standby
+    pub async fn transmit(&mut self, payload: &[u8]) -> Result<(), Sx127xError<S::Error, P::Error>> {
+        if payload.len() > LoRaConstant::MAX_PAYLOAD_BYTES {
+            return Err(Sx127xError::PayloadTooLarge(payload.len()));
+        }
+        self.write_register(REG_OP_MODE, MODE_LONG_RANGE | MODE_STANDBY)?;
+        self.write_register(REG_FIFO_TX_BASE_ADDR, 0x00)?;
+        self.write_register(REG_FIFO_ADDR_PTR, 0x00)?;
+        self.spi
+            .transaction(&mut [Operation::Write(&[REG_FIFO | 0x80]), Operation::Write(payload)])
+            .map_err(Sx127xError::Spi)?;
+        self.write_register(REG_PAYLOAD_LENGTH, payload.len() as u8)?;
+        self.write_register(REG_OP_MODE, MODE_LONG_RANGE | MODE_TX)?;
+
+        let timeout_ms = self.airtime_ms(payload.len()) + LoRaConstant::TX_TIMEOUT_MARGIN_MS;
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        while Instant::now() < deadline {
+            if self.read_register(REG_IRQ_FLAGS)? & IRQ_TX_DONE != 0 {
+                self.write_register(REG_IRQ_FLAGS, IRQ_TX_DONE)?;
+                return Ok(());
+            }
+            Timer::after_millis(LoRaConstant::TX_POLL_MS).await;
+        }
+        self.write_register(REG_OP_MODE, MODE_LONG_RANGE | MODE_STANDBY)?;
+        Err(Sx127xError::Timeout)
+    }
+
+    pub fn sleep(&mut self) -> Result<(), Sx127xError<S::Error, P::Error>> {
+        self.write_register(REG_OP_MODE, MODE_LONG_RANGE | MODE_SLEEP)
+    }
+
+    fn hard_reset(&mut self) -> Result<(), Sx127xError<S::Error, P::Error>> {
+        self.reset.set_low().map_err(Sx127xError::Pin)?;
+        self.delay.delay_ms(1);
+        self.reset.set_high().map_err(Sx127xError::Pin)?;
+        self.delay.delay_ms(10);
+        Ok(())
+    }
+
+    fn read_register(&mut self, register: u8) -> Result<u8, Sx127xError<S::Error, P::Error>> {
+        let mut buffer = [register & 0x7f, 0];
+        self.spi.transfer_in_place(&mut buffer).map_err(Sx127xError::Spi)?;
+        Ok(buffer[1])
+    }
+
+    fn write_register(&mut self, register: u8, value: u8) -> Result<(), Sx127xError<S::Error, P::Error>> {
+        self.spi.write(&[register | 0x80, value]).map_err(Sx127xError::Spi)
+    }
+}
+
+// Symbols longer than 16 ms need low data rate optimisation
+fn low_data_rate(config: &Sx127xConfig) -> bool {
+    (1u32 << config.spreading_factor) * 1_000 / (config.bandwidth_hz / 1_000) > 16_000
+}
@@ -0,0 +1,50 @@
+use embassy_net::{Runner, Stack, StackResources};
+use embassy_net_wiznet::chip::W5500;
+use embassy_net_wiznet::{Device, State};
+use esp_hal::gpio::{Input, Output};
+use static_cell::StaticCell;
+
+use crate::constants::network::NetworkConstant;
+use crate::drivers::spi_bus::{SpiBusDevice, SpiBusError};
+use crate::dtos::configurations::network::{StaticIpv4ConfigDTO, StaticIpv6ConfigDTO};
+use crate::services::network_manager;
+
+pub type W5500Spi = SpiBusDevice<'static>;
+pub type W5500Runner = embassy_net_wiznet::Runner<'static, W5500, W5500Spi, Input<'static>, Output<'static>>;
+pub type W5500NetRunner = Runner<'static, Device<'static>>;
+pub type W5500InitError = embassy_net_wiznet::InitError<SpiBusError>;
+
+// Brings up the W5500 in MACRAW mode and hands it to embassy-net (DHCPv4 or
+// static IPv4, plus `static_ipv6` when set),
+// the two returned runners must be spawned for the link to make progress
+pub async fn init(
+    spi: W5500Spi,
+    interrupt: Input<'static>,
+    reset: Output<'static>,
+    mac: [u8; 6],
+    seed: u64,
+    static_ipv6: Option<&StaticIpv6ConfigDTO>,
+    static_ipv4: Option<&StaticIpv4ConfigDTO>,
+) -> Result<(Stack<'static>, W5500Runner, W5500NetRunner), W5500InitError> {
+    static STATE: StaticCell<State<8, 8>> = StaticCell::new();
+    static RESOURCES: StaticCell<StackResources<{ NetworkConstant::STACK_SOCKETS }>> = StaticCell::new();
+
+    let (device, runner) = embassy_net_wiznet::new(mac, STATE.init(State::new()), spi, interrupt, reset).await?;
+    let (stack, net_runner) = embassy_net::new(
+        device,
+        network_manager::stack_config(static_ipv6, static_ipv4),
+        RESOURCES.init(StackResources::new()),
+        seed,
+    );
+    Ok((stack, runner, net_runner))
+}
+
+#[embassy_executor::task]
+pub async fn ethernet_task(runner: W5500Runner) -> ! {
+    runner.run().await
+}
+
+#[embassy_executor::task]
+pub async fn ethernet_net_task(mut runner: W5500NetRunner) -> ! {
+    runner.run().await
+}
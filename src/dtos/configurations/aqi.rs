@@ -0,0 +1,24 @@
+use alloc::vec::Vec;
+
+use crate::dtos::configurations::thresholds::ThresholdsDTO;
+
+// One row of a breakpoint table: concentrations in [low, high] map linearly
+// onto index values in [index_low, index_high]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AqiBreakpointDTO {
+    pub low: f32,
+    pub high: f32,
+    pub index_low: u16,
+    pub index_high: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AqiConfigDTO {
+    // µg/m³, EPA tables by default
+    pub pm2_5: Vec<AqiBreakpointDTO>,
+    pub pm10: Vec<AqiBreakpointDTO>,
+    // ppm; not part of the EPA index, an indoor ventilation scale instead
+    pub co2: Vec<AqiBreakpointDTO>,
+    // Category of the combined index, e.g. GOOD or UNHEALTHY
+    pub categories: ThresholdsDTO,
+}
@@ -0,0 +1,10 @@
+use crate::enums::bh1750_mode::Bh1750Mode;
+use crate::enums::bh1750_resolution::Bh1750Resolution;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Bh1750ConfigDTO {
+    pub mode: Bh1750Mode,
+    pub resolution: Bh1750Resolution,
+    // 31-254, scales measurement time and sensitivity around the default 69
+    pub mtreg: u8,
+}
@@ -0,0 +1,20 @@
+use crate::enums::bme280_mode::Bme280Mode;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Bme280ConfigDTO {
+    pub mode: Bme280Mode,
+    // Oversampling ratios: 1, 2, 4, 8 or 16. The chip's 0 skips the
+    // measurement and leaves 0x80000 in its registers, so it is refused
+    pub temperature_oversampling: u8,
+    pub pressure_oversampling: u8,
+    pub humidity_oversampling: u8,
+    // IIR filter coefficient: 0 (off), 2, 4, 8 or 16
+    pub filter: u8,
+    // Normal mode only: 0.5, 10, 20, 62.5, 125, 250, 500 or 1000
+    pub standby_ms: f32,
+    // Subtracted from every temperature, the board's warming at idle
+    pub temperature_offset_c: f32,
+    // Further warming at 100 % CPU or radio duty, see `self_heating`
+    pub cpu_heating_c: f32,
+    pub radio_heating_c: f32,
+}
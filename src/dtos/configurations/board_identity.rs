@@ -0,0 +1,15 @@
+use alloc::string::String;
+
+use crate::enums::board_revision::BoardRevision;
+
+// Written once per board on the production line, see `board_identity`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoardIdentityDTO {
+    pub serial: String,
+    pub revision: BoardRevision,
+    // Factory calibration, merged into `Config::bme280` unless the build
+    // sets the same value
+    pub temperature_offset_c: f32,
+    pub cpu_heating_c: f32,
+    pub radio_heating_c: f32,
+}
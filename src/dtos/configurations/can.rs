@@ -0,0 +1,33 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+// One signal of a CAN frame, as a DBC file would describe it
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanSignalDTO {
+    pub field: String,
+    pub id: u32,
+    pub extended: bool,
+    // Id bits a frame has to match to carry the signal; `CAN_FILTER`'s mask
+    // for signals it lets through, so one signal reads a J1939 PGN from any
+    // source address, every bit otherwise
+    pub id_mask: u32,
+    // Intel: least significant bit; Motorola: most significant bit, in the
+    // usual DBC numbering
+    pub start_bit: u16,
+    pub length: u8,
+    pub big_endian: bool,
+    pub signed: bool,
+    // Physical value is raw * scale + offset
+    pub scale: f32,
+    pub offset: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct CanConfigDTO {
+    pub bitrate_kbps: u32,
+    // Acceptance code and mask for the TWAI filter; derived from the signal
+    // ids when not given
+    pub filter: Option<(u32, u32)>,
+    // Empty leaves TWAI off
+    pub signals: Vec<CanSignalDTO>,
+}
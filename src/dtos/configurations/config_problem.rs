@@ -0,0 +1,12 @@
+use alloc::string::String;
+
+use crate::enums::config_problem_kind::ConfigProblemKind;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigProblemDTO {
+    pub kind: ConfigProblemKind,
+    // Setting at fault as named in the remote config, e.g. "include" or
+    // "pipelines.bme280"
+    pub field: String,
+    pub detail: String,
+}
@@ -0,0 +1,12 @@
+use crate::enums::board_revision::BoardRevision;
+
+// Defaults for one board revision; build-time settings still override them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HardwareProfileDTO {
+    pub revision: BoardRevision,
+    // Sensor keys fitted on the board, the default include list
+    pub sensors: &'static [&'static str],
+    pub status_led_gpio: Option<u8>,
+    pub i2c_sda_gpio: u8,
+    pub i2c_scl_gpio: u8,
+}
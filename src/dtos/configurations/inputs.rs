@@ -0,0 +1,10 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+#[derive(Debug, Clone)]
+pub struct InputsConfigDTO {
+    // Button name to GPIO, wired to ground
+    pub buttons: BTreeMap<String, u8>,
+    // Encoder name to its A and B GPIOs; the ESP32 has 8 PCNT units
+    pub encoders: BTreeMap<String, (u8, u8)>,
+}
@@ -0,0 +1,22 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+use crate::enums::ir_protocol::IrProtocol;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IrCodeDTO {
+    pub protocol: IrProtocol,
+    pub address: u16,
+    pub command: u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct IrConfigDTO {
+    // Demodulating receiver output (TSOP38238 and the like), active low
+    pub rx_gpio: Option<u8>,
+    // IR LED driver, the RMT adds the carrier
+    pub tx_gpio: Option<u8>,
+    // Named codes, so the downlink can say "ac_off" instead of raw numbers
+    // and received ones are reported by name
+    pub codes: BTreeMap<String, IrCodeDTO>,
+}
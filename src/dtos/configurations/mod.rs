@@ -1 +1,20 @@
-pub mod sensors;
\ No newline at end of file
+pub mod aqi;
+pub mod bh1750;
+pub mod bme280;
+pub mod board_identity;
+pub mod can;
+pub mod config_problem;
+pub mod hardware_profile;
+pub mod inputs;
+pub mod ir;
+pub mod modbus;
+pub mod network;
+pub mod profile;
+pub mod retention;
+pub mod schedule;
+pub mod sdi12;
+pub mod sensors;
+pub mod tamper;
+pub mod thresholds;
+pub mod timezone;
+pub mod wifi;
@@ -0,0 +1,28 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::enums::modbus_data_type::ModbusDataType;
+use crate::enums::modbus_register_kind::ModbusRegisterKind;
+
+// One value read from a meter and the measurement field it becomes
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModbusRegisterDTO {
+    pub field: String,
+    pub slave: u8,
+    pub kind: ModbusRegisterKind,
+    // Zero-based protocol address, not the 3xxxx/4xxxx register number
+    pub address: u16,
+    pub data_type: ModbusDataType,
+    // Raw value times this, e.g. 0.1 for a meter reporting decivolts
+    pub scale: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModbusConfigDTO {
+    pub baud: u32,
+    // Drives DE and /RE of the RS-485 transceiver, high while sending
+    pub de_gpio: Option<u8>,
+    pub timeout_ms: u32,
+    // Empty leaves the Modbus master off
+    pub registers: Vec<ModbusRegisterDTO>,
+}
@@ -0,0 +1,17 @@
+use alloc::vec::Vec;
+use core::net::{Ipv4Addr, Ipv6Addr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaticIpv4ConfigDTO {
+    pub address: Ipv4Addr,
+    pub prefix_length: u8,
+    pub gateway: Ipv4Addr,
+    pub dns_servers: Vec<Ipv4Addr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaticIpv6ConfigDTO {
+    pub address: Ipv6Addr,
+    pub prefix_length: u8,
+    pub gateway: Option<Ipv6Addr>,
+}
@@ -0,0 +1,14 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+// Settings bundled under a `SensingProfile`, applied on top of the config
+#[derive(Debug, Clone)]
+pub struct SensingProfileDTO {
+    pub interval_ms: u64,
+    // Empty keeps the configured sensors
+    pub include: Vec<String>,
+    pub batch_max_records: usize,
+    pub batch_max_age_s: u64,
+    // None stays awake between cycles
+    pub deep_sleep_interval_s: Option<u64>,
+}
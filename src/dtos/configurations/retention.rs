@@ -0,0 +1,9 @@
+// Limits on locally held data, any of which may be left unset. Whichever
+// is exceeded drops the oldest data first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionConfigDTO {
+    // Ignored by the SD logger, whose daily files are not counted in records
+    pub max_records: Option<u32>,
+    pub max_age_s: Option<u64>,
+    pub max_bytes: Option<u32>,
+}
@@ -0,0 +1,11 @@
+// One "days HH:MM-HH:MM [every N]" rule, times are local minutes of the day
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleWindowDTO {
+    // Bit 0 is Sunday, the day the window starts on
+    pub weekdays: u8,
+    pub start_minute: u16,
+    // Equal to start_minute for a full day, smaller for windows past midnight
+    pub end_minute: u16,
+    // Sensing cadence inside the window, aligned to its start
+    pub every_s: Option<u32>,
+}
@@ -0,0 +1,22 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+// One probe on the SDI-12 bus, read as a virtual sensor named `name`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sdi12ProbeDTO {
+    pub name: String,
+    // '0'-'9', 'a'-'z' or 'A'-'Z'
+    pub address: char,
+    // "M" or "M1".."M9" to start a measurement, "R0".."R9" for probes
+    // that measure continuously
+    pub command: String,
+    // Field names of the returned values, in order; extra values are dropped
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Sdi12ConfigDTO {
+    // Forces the data line to spacing for the break
+    pub break_gpio: Option<u8>,
+    pub probes: Vec<Sdi12ProbeDTO>,
+}
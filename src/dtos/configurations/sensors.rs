@@ -1,7 +1,46 @@
+use alloc::collections::BTreeMap;
 use alloc::string::String;
 use alloc::vec::Vec;
 
+use crate::dtos::configurations::thresholds::ThresholdsDTO;
+use crate::enums::unit_system::UnitSystem;
+
 #[derive(Debug, Clone)]
 pub struct SensorsConfigDTO {
     pub include: Vec<String>,
-}
\ No newline at end of file
+    // Subset of `include` streamed over UDP instead of the regular uplink
+    pub udp_stream: Vec<String>,
+    // Classification of BH1750 lux and VL53L0X distance (mm) readings
+    pub lux_thresholds: ThresholdsDTO,
+    pub distance_thresholds_mm: ThresholdsDTO,
+    // Classification of BME280 temperature (°C) and humidity (%RH)
+    pub temperature_thresholds: ThresholdsDTO,
+    pub humidity_thresholds: ThresholdsDTO,
+    // Settling time after power-up or init per sensor key; readings taken
+    // earlier are discarded. Sensors not listed are usable immediately
+    pub warmup_ms: BTreeMap<String, u64>,
+    // GPIO driving the load switch of a sensor, high while it is needed.
+    // Sensors not listed are powered permanently
+    pub power_gpios: BTreeMap<String, u8>,
+    // GPIO a sensor's INT/DRDY output is wired to; reads wait for it instead
+    // of polling. Sensors not listed are polled
+    pub ready_gpios: BTreeMap<String, u8>,
+    // Location of a sensor that sits elsewhere than the device, e.g. the
+    // probes of a multi-room unit. Sensors not listed use the device's
+    pub location_urns: BTreeMap<String, String>,
+    // Pipeline stage specs per sensor key, see `PipelineFactory`
+    pub pipelines: BTreeMap<String, Vec<String>>,
+    // Bus each sensor sits on; sensors on different buses are read
+    // concurrently. Sensors not listed share `SensorConstant::DEFAULT_BUS`
+    pub buses: BTreeMap<String, String>,
+    pub read_timeout_ms: u64,
+    // A failed read reports the sensor's last good value up to this age,
+    // see `last_value`
+    pub last_value_max_age_s: u64,
+    // Imperial values are added to or replace the metric ones at the end
+    // of every pipeline chain
+    pub units: UnitSystem,
+    // Temperature drift per field, in the field's unit per °C, e.g.
+    // distance_mm or lux; see `TemperatureCompensationStage`
+    pub compensation: BTreeMap<String, f32>,
+}
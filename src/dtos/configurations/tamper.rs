@@ -0,0 +1,9 @@
+#[derive(Debug, Clone, Copy)]
+pub struct TamperConfigDTO {
+    // m/s² off 1 g in magnitude, see `TamperConstant`
+    pub shock_ms2: f32,
+    // m/s² between the gravity vector and its armed direction
+    pub tilt_ms2: f32,
+    // lux over the baseline
+    pub light_lux: f32,
+}
@@ -0,0 +1,17 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThresholdStepDTO {
+    // Inclusive upper bound of this bucket
+    pub upper: f64,
+    pub label: String,
+}
+
+// Ascending buckets that classify a reading, e.g. lux into "DIM"/"BRIGHT"
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThresholdsDTO {
+    pub steps: Vec<ThresholdStepDTO>,
+    // Label for anything above the last step
+    pub above: String,
+}
@@ -0,0 +1,24 @@
+// Parsed POSIX TZ string, offsets are seconds east of UTC (the POSIX text
+// uses the opposite sign)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeZoneDTO {
+    pub std_offset_s: i32,
+    pub dst: Option<DstRuleDTO>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DstRuleDTO {
+    pub offset_s: i32,
+    pub start: TransitionDTO,
+    pub end: TransitionDTO,
+}
+
+// "Mm.w.d/time": day `weekday` (0 = Sunday) of week `week` (5 = last) of
+// `month`, at `time_s` local time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransitionDTO {
+    pub month: u8,
+    pub week: u8,
+    pub weekday: u8,
+    pub time_s: i32,
+}
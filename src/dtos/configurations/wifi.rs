@@ -0,0 +1,28 @@
+use alloc::string::String;
+
+use crate::enums::eap_method::EapMethod;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WifiProfileDTO {
+    pub ssid: String,
+    // PSK for personal networks, the EAP password for PEAP
+    pub password: String,
+    pub enterprise: Option<WifiEnterpriseDTO>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WifiEnterpriseDTO {
+    pub method: EapMethod,
+    // Outer (anonymous) identity sent before the tunnel is up
+    pub identity: String,
+    pub username: String,
+}
+
+// Certificates are read from the secret store once at boot and leaked, the
+// supplicant keeps pointers to them for as long as the station is up
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WifiCertificatesDTO {
+    pub ca_cert: Option<&'static [u8]>,
+    pub client_cert: Option<&'static [u8]>,
+    pub client_key: Option<&'static [u8]>,
+}
@@ -0,0 +1,26 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+use crate::enums::event_kind::EventKind;
+use crate::enums::value::Value;
+
+// Something that happened at a point in time, as opposed to a periodic
+// sample. Delivered separately from measurements and never evicted from the
+// upload queue to make room for them
+#[derive(Debug, Clone)]
+pub struct EventEnvelopeDTO {
+    // From `message_id::next`, lets the server discard redelivered copies
+    pub id: String,
+    pub device_urn: String,
+    pub location_urn: String,
+    // The sensor that raised the event
+    pub sensor_urn: String,
+    // Unix seconds, 0 while `relative_ms_since_boot` is set
+    pub timestamp: u64,
+    // Raised before the clock was set; the uploader rewrites it into
+    // `timestamp` once the clock is synced
+    pub relative_ms_since_boot: Option<u64>,
+    pub kind: EventKind,
+    // Event specific context, e.g. the value and limit of a threshold breach
+    pub detail: BTreeMap<String, Value>,
+}
@@ -0,0 +1,13 @@
+use alloc::string::String;
+
+use crate::enums::input_kind::InputKind;
+
+// A local control being used, as the display menu and the rules see it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputEventDTO {
+    // Name of the button or encoder from the config
+    pub source: String,
+    pub kind: InputKind,
+    // Detents for `Rotated`, 0 otherwise
+    pub steps: i32,
+}
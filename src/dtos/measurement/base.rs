@@ -1,7 +1,56 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+
+use crate::constants::sensor::SensorConstant;
+use crate::dtos::measurement::field_quality::FieldQualityDTO;
 use crate::dtos::measurement::sensor::bh1750::BH1750SensorMeasurement;
 use crate::dtos::measurement::sensor::bme280::BME280SensorMeasurement;
+use crate::dtos::measurement::sensor::ds323x::DS323XSensorMeasurement;
+use crate::enums::quality::Quality;
+use crate::enums::value::Value;
+use crate::services::last_value;
 
 pub struct BaseMeasurementDTO{
     pub bh1750: Option<BH1750SensorMeasurement>,
-    pub bme280: Option<BME280SensorMeasurement>
-}
\ No newline at end of file
+    pub bme280: Option<BME280SensorMeasurement>,
+    pub ds323x: Option<DS323XSensorMeasurement>,
+}
+
+impl BaseMeasurementDTO {
+    // Flattened into `MeasurementEnvelopeDTO::data`
+    pub fn data(&self) -> BTreeMap<String, Value> {
+        self.sensors().into_iter().filter_map(|(_, data)| data).flatten().collect()
+    }
+
+    // Like `data`, with a sensor that failed to read replaced by its last
+    // good value, tagged `<sensor>_stale` and `<sensor>_age_s` and its
+    // fields marked STALE for `MeasurementEnvelopeDTO::quality`
+    pub fn data_or_last(&self, max_age_s: u64) -> (BTreeMap<String, Value>, BTreeMap<String, FieldQualityDTO>) {
+        let mut flattened = BTreeMap::new();
+        let mut quality = BTreeMap::new();
+        for (sensor, data) in self.sensors() {
+            let stale = data.is_none();
+            for (field, value) in last_value::resolve(sensor, data, max_age_s).into_iter().flatten() {
+                let field = if field == SensorConstant::STALE || field == SensorConstant::AGE_S {
+                    format!("{}_{}", sensor, field)
+                } else {
+                    if stale {
+                        quality.insert(field.clone(), FieldQualityDTO { quality: Quality::Stale, raw: None });
+                    }
+                    field
+                };
+                flattened.insert(field, value);
+            }
+        }
+        (flattened, quality)
+    }
+
+    fn sensors(&self) -> [(&'static str, Option<BTreeMap<String, Value>>); 3] {
+        [
+            (SensorConstant::BH1750, self.bh1750.as_ref().map(BTreeMap::from)),
+            (SensorConstant::BME280, self.bme280.as_ref().map(BTreeMap::from)),
+            (SensorConstant::DS3231SN, self.ds323x.as_ref().map(BTreeMap::from)),
+        ]
+    }
+}
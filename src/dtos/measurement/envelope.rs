@@ -0,0 +1,39 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+use crate::dtos::measurement::field_quality::FieldQualityDTO;
+use crate::dtos::measurement::unit::UnitDTO;
+use crate::enums::quality::Quality;
+use crate::enums::value::Value;
+
+#[derive(Debug, Clone)]
+pub struct MeasurementEnvelopeDTO {
+    // From `message_id::next`, lets the server discard redelivered copies
+    pub id: String,
+    pub device_urn: String,
+    pub location_urn: String,
+    // Unix seconds, 0 while `relative_ms_since_boot` is set
+    pub timestamp: u64,
+    // Recorded before the clock was set; the uploader rewrites it into
+    // `timestamp` once the clock is synced
+    pub relative_ms_since_boot: Option<u64>,
+    pub data: BTreeMap<String, Value>,
+    // Per field of `data`; fields not listed are GOOD
+    pub quality: BTreeMap<String, FieldQualityDTO>,
+    // Per field of `data`, from `units::of`; fields without a unit are left out
+    pub units: BTreeMap<String, UnitDTO>,
+}
+
+impl MeasurementEnvelopeDTO {
+    pub fn quality(&self, field: &str) -> Quality {
+        self.quality.get(field).map_or(Quality::Good, |field| field.quality)
+    }
+
+    pub fn mark(&mut self, field: &str, quality: Quality, raw: Option<Value>) {
+        if quality == Quality::Good && raw.is_none() {
+            self.quality.remove(field);
+        } else {
+            self.quality.insert(String::from(field), FieldQualityDTO { quality: quality, raw: raw });
+        }
+    }
+}
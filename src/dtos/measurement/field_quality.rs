@@ -0,0 +1,9 @@
+use crate::enums::quality::Quality;
+use crate::enums::value::Value;
+
+#[derive(Debug, Clone)]
+pub struct FieldQualityDTO {
+    pub quality: Quality,
+    // The reading before it was corrected, clamped or replaced, if it differs
+    pub raw: Option<Value>,
+}
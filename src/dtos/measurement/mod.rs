@@ -1,2 +1,5 @@
 pub mod base;
-pub mod sensor;
\ No newline at end of file
+pub mod envelope;
+pub mod field_quality;
+pub mod sensor;
+pub mod unit;
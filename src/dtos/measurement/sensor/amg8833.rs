@@ -0,0 +1,32 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::constants::thermal::ThermalConstant;
+use crate::enums::value::Value;
+
+#[derive(Default, Debug)]
+pub struct AMG8833SensorMeasurement {
+    pub min_c: f32,
+    pub max_c: f32,
+    pub mean_c: f32,
+    // Row-major index of the hottest pixel
+    pub hotspot: usize,
+    // Board temperature next to the array
+    pub thermistor_c: f32,
+    // 8x8 pixels in °C, row-major
+    pub frame: Vec<f32>,
+}
+
+// The summary only, the frame goes out through `ThermalFrameService`
+impl From<&AMG8833SensorMeasurement> for BTreeMap<String, Value> {
+    fn from(measurement: &AMG8833SensorMeasurement) -> Self {
+        let mut data = BTreeMap::new();
+        data.insert(ThermalConstant::MIN_C.to_string(), Value::Float(measurement.min_c));
+        data.insert(ThermalConstant::MAX_C.to_string(), Value::Float(measurement.max_c));
+        data.insert(ThermalConstant::MEAN_C.to_string(), Value::Float(measurement.mean_c));
+        data.insert(ThermalConstant::HOTSPOT.to_string(), Value::Integer(measurement.hotspot as i32));
+        data.insert(ThermalConstant::THERMISTOR_C_FIELD.to_string(), Value::Float(measurement.thermistor_c));
+        data
+    }
+}
@@ -1,7 +1,20 @@
+use alloc::collections::BTreeMap;
 use alloc::string::String;
 
+use crate::enums::value::Value;
+
 #[derive(Default, Debug)]
 pub struct BH1750SensorMeasurement {
     pub lux: f64,
+    // Data register as read, before resolution and MTreg scaling
+    pub raw: u16,
     pub condition: String,
 }
+
+impl From<&BH1750SensorMeasurement> for BTreeMap<String, Value> {
+    fn from(measurement: &BH1750SensorMeasurement) -> Self {
+        let mut data = BTreeMap::new();
+        data.insert(String::from("lux"), Value::Float(measurement.lux as f32));
+        data
+    }
+}
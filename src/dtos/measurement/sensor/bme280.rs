@@ -1,6 +1,27 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+use crate::enums::value::Value;
+
 #[derive(Default, Debug)]
 pub struct BME280SensorMeasurement {
     pub temperature: f32,
     pub humidity: f32,
-    pub pressure: f32
+    pub pressure: f32,
+    // e.g. COLD or COMFORTABLE, from `SensorsConfigDTO::temperature_thresholds`
+    pub temperature_condition: String,
+    // e.g. DRY or HUMID, from `SensorsConfigDTO::humidity_thresholds`
+    pub humidity_condition: String,
+}
+
+impl From<&BME280SensorMeasurement> for BTreeMap<String, Value> {
+    fn from(measurement: &BME280SensorMeasurement) -> Self {
+        let mut data = BTreeMap::new();
+        data.insert(String::from("temperature"), Value::Float(measurement.temperature));
+        data.insert(String::from("humidity"), Value::Float(measurement.humidity));
+        data.insert(String::from("pressure"), Value::Float(measurement.pressure));
+        data.insert(String::from("temperature_condition"), Value::String(measurement.temperature_condition.clone()));
+        data.insert(String::from("humidity_condition"), Value::String(measurement.humidity_condition.clone()));
+        data
+    }
 }
@@ -1,6 +1,23 @@
-use alloc::string::String;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+
+use crate::constants::sensor::SensorConstant;
+use crate::enums::value::Value;
 
 #[derive(Default, Debug)]
 pub struct DS323XSensorMeasurement {
-    pub datetime: String
+    pub datetime: String,
+    pub timestamp: u64,
+    // Die temperature of the RTC in °C
+    pub temperature: f32,
+}
+
+// A cheap second opinion next to the BME280: the die sits on the board, so
+// a growing gap points at the enclosure heating up
+impl From<&DS323XSensorMeasurement> for BTreeMap<String, Value> {
+    fn from(measurement: &DS323XSensorMeasurement) -> Self {
+        let mut data = BTreeMap::new();
+        data.insert(SensorConstant::DS3231_TEMP.to_string(), Value::Float(measurement.temperature));
+        data
+    }
 }
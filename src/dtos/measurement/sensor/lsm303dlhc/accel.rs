@@ -1,8 +1,22 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+use crate::enums::value::Value;
+
+// m/s² per axis, gravity included
 #[derive(Default, Debug)]
 pub struct LSM303DLHCACCELSensorMeasurement {
     pub x: f32,
     pub y: f32,
     pub z: f32,
-    pub magnitude: f32,
-    pub tilt: f32,
+}
+
+impl From<&LSM303DLHCACCELSensorMeasurement> for BTreeMap<String, Value> {
+    fn from(measurement: &LSM303DLHCACCELSensorMeasurement) -> Self {
+        let mut data = BTreeMap::new();
+        data.insert(String::from("accel_x"), Value::Float(measurement.x));
+        data.insert(String::from("accel_y"), Value::Float(measurement.y));
+        data.insert(String::from("accel_z"), Value::Float(measurement.z));
+        data
+    }
 }
@@ -1,5 +1,7 @@
+pub mod amg8833;
 pub mod bh1750;
 pub mod bme280;
 pub mod ds323x;
 pub mod lsm303dlhc;
+pub mod sdi12;
 pub mod vl53l0x;
\ No newline at end of file
@@ -0,0 +1,16 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+use crate::enums::value::Value;
+
+#[derive(Default, Debug)]
+pub struct SDI12SensorMeasurement {
+    // Configured field name to value, in the probe's own units
+    pub values: BTreeMap<String, f32>,
+}
+
+impl From<&SDI12SensorMeasurement> for BTreeMap<String, Value> {
+    fn from(measurement: &SDI12SensorMeasurement) -> Self {
+        measurement.values.iter().map(|(field, value)| (field.clone(), Value::Float(*value))).collect()
+    }
+}
@@ -1,7 +1,19 @@
+use alloc::collections::BTreeMap;
 use alloc::string::String;
 
+use crate::enums::value::Value;
+
 #[derive(Default, Debug)]
 pub struct VL53L0XSensorMeasurement {
     pub distance_mm: f32,
     pub status: String
 }
+
+impl From<&VL53L0XSensorMeasurement> for BTreeMap<String, Value> {
+    fn from(measurement: &VL53L0XSensorMeasurement) -> Self {
+        let mut data = BTreeMap::new();
+        data.insert(String::from("distance_mm"), Value::Float(measurement.distance_mm));
+        data.insert(String::from("distance_status"), Value::String(measurement.status.clone()));
+        data
+    }
+}
@@ -0,0 +1,8 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnitDTO {
+    // One of the `UnitConstant` strings
+    pub unit: &'static str,
+    // Multiply by this to get the SI base unit, None when already SI or
+    // when there is none (an index, a count)
+    pub si_scale: Option<f32>,
+}
@@ -1,3 +1,7 @@
 pub mod configurations;
+pub mod event;
 pub mod measurement;
+pub mod pipeline;
 pub mod response;
+pub mod telemetry;
+pub mod transport;
\ No newline at end of file
@@ -0,0 +1,20 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::dtos::measurement::field_quality::FieldQualityDTO;
+use crate::enums::event_kind::EventKind;
+use crate::enums::priority::Priority;
+use crate::enums::value::Value;
+
+// Passed through every stage of a chain, starting from one sensor reading
+#[derive(Debug, Clone)]
+pub struct PipelineOutputDTO {
+    pub values: BTreeMap<String, Value>,
+    // Copied to `MeasurementEnvelopeDTO::quality`
+    pub quality: BTreeMap<String, FieldQualityDTO>,
+    // Batch priority of the resulting envelope
+    pub priority: Priority,
+    // Raised by stages, e.g. a breached limit or a field turning anomalous
+    pub events: Vec<(EventKind, BTreeMap<String, Value>)>,
+}
@@ -1,2 +1,3 @@
 pub mod base;
+pub mod server;
 pub mod services;
\ No newline at end of file
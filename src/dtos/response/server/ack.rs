@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+// Reply to a measurement upload
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServerAckResponseDTO {
+    pub accepted: u32,
+    // Server-requested sensing interval, overrides the local one when present
+    pub next_interval_s: Option<u32>,
+}
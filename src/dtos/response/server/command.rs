@@ -0,0 +1,10 @@
+use alloc::string::String;
+use serde::Deserialize;
+
+// Downlink command, e.g. {"id":7,"command":"reboot"}
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServerCommandResponseDTO {
+    pub id: u32,
+    pub command: String,
+    pub argument: Option<String>,
+}
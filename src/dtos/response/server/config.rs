@@ -0,0 +1,32 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::Deserialize;
+
+// Remote configuration, absent fields keep their local value
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServerConfigResponseDTO {
+    // Schema the server wrote this config in, see
+    // `StorageConstant::CONFIG_SCHEMA_VERSION`; absent means 1
+    pub version: Option<u16>,
+    pub include: Option<Vec<String>>,
+    pub interval_s: Option<u32>,
+    pub log_format: Option<String>,
+    pub server_base_url: Option<String>,
+    // Same "upper:LABEL,...,LABEL" form as the build-time settings
+    pub lux_thresholds: Option<String>,
+    pub distance_thresholds_mm: Option<String>,
+    pub temperature_thresholds: Option<String>,
+    pub humidity_thresholds: Option<String>,
+    // Replaces all per-sensor locations, sensor key to location URN
+    pub location_urns: Option<BTreeMap<String, String>>,
+    // Sensor key to stage specs, e.g. {"bme280":["outlier","moving_average(5)"]}
+    pub pipelines: Option<BTreeMap<String, Vec<String>>>,
+    // "metric", "imperial" or "both"
+    pub units: Option<String>,
+    // Replaces all temperature compensation coefficients, field to drift per °C
+    pub compensation: Option<BTreeMap<String, f32>>,
+    // Same "records=N,age_s=N,bytes=N" form as QUEUE_RETENTION/SD_RETENTION
+    pub queue_retention: Option<String>,
+    pub sd_retention: Option<String>,
+}
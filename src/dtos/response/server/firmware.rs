@@ -0,0 +1,28 @@
+use alloc::string::String;
+use serde::Deserialize;
+
+// Answer to GET /api/v1/firmware, e.g.
+// {"version":12,"semver":"1.4.0","path":"/firmware/1.4.0.bin","size":1310720,"signature":"<hex>",
+//  "patch":{"base_version":11,"path":"/firmware/11-12.delta","size":41230}}
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServerFirmwareResponseDTO {
+    // Monotonic security version used for anti-rollback
+    pub version: u32,
+    pub semver: String,
+    // Path on the same server as the manifest
+    pub path: String,
+    pub size: usize,
+    // Ed25519ph signature over `version` as 4 little-endian bytes followed
+    // by the whole image, 64 bytes hex encoded
+    pub signature: String,
+    // Offered when the server has a patch from the version the device reported
+    pub patch: Option<ServerFirmwarePatchResponseDTO>,
+}
+
+// The signature above covers the rebuilt image, not the patch
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServerFirmwarePatchResponseDTO {
+    pub base_version: u32,
+    pub path: String,
+    pub size: usize,
+}
@@ -0,0 +1,4 @@
+pub mod ack;
+pub mod command;
+pub mod config;
+pub mod firmware;
\ No newline at end of file
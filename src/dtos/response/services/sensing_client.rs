@@ -1,7 +1,17 @@
 use alloc::collections::BTreeMap;
 use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::enums::read_status::ReadStatus;
+use crate::enums::value::Value;
 
 #[derive(Debug, Clone)]
 pub struct SensingClientServiceResponseDTO {
-    pub data: BTreeMap<String, String>,
+    // Flattened measurement per sensor key
+    pub data: BTreeMap<String, BTreeMap<String, Value>>,
+    // Outcome of every attempted read; sensors without an OK status have
+    // no entry in `data`
+    pub statuses: BTreeMap<String, ReadStatus>,
+    // Keys from `SensorsConfigDTO::include` the factory does not know
+    pub unknown_sensors: Vec<String>,
 }
\ No newline at end of file
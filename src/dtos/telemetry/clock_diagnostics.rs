@@ -0,0 +1,15 @@
+use crate::enums::clock_source::ClockSource;
+
+#[derive(Debug, Clone, Default)]
+pub struct ClockDiagnosticsDTO {
+    // Where the system clock was last set from, None while it is unset
+    pub source: Option<ClockSource>,
+    // Unix time of the last successful SNTP discipline
+    pub last_sync: Option<u64>,
+    // RTC minus SNTP at the last discipline, before any correction
+    pub rtc_offset_s: Option<i64>,
+    // Measured RTC drift, positive when it runs fast
+    pub drift_ppm: Option<f32>,
+    pub rtc_aging_offset: Option<i8>,
+    pub rtc_temperature: Option<f32>,
+}
@@ -0,0 +1,19 @@
+use alloc::string::String;
+
+#[derive(Debug, Clone)]
+pub struct DeviceInfoDTO {
+    // Build time
+    pub firmware_version: &'static str,
+    pub git_hash: &'static str,
+    pub build_timestamp: u64,
+    pub esp_hal_version: &'static str,
+    // Read from the chip at boot
+    pub chip_model: &'static str,
+    pub chip_revision: u16,
+    pub flash_size_bytes: u32,
+    // 0 on modules without PSRAM, see `memory::init_psram`
+    pub psram_size_bytes: u32,
+    pub mac: [u8; 6],
+    // From the board EEPROM, None on boards never programmed
+    pub serial: Option<String>,
+}
@@ -0,0 +1,26 @@
+use alloc::string::String;
+
+#[derive(Debug, Clone, Default)]
+pub struct DeviceMetricsDTO {
+    pub uptime_s: u64,
+    pub heap_free_bytes: usize,
+    // Lowest free heap seen at any collection since boot
+    pub heap_min_free_bytes: usize,
+    // Deepest the main stack has been, which every embassy task runs on;
+    // None when it was not painted at boot, see `stack::paint`
+    pub stack_high_water_bytes: Option<usize>,
+    pub stack_size_bytes: usize,
+    // None when the station interface is not in use
+    pub rssi_dbm: Option<i8>,
+    pub reconnects: u32,
+    // Summed over all upload targets
+    pub queue_depth: usize,
+    pub upload_successes: u32,
+    pub upload_failures: u32,
+    pub upload_bytes: u64,
+    // Mean duration of a successful upload, None before the first
+    pub upload_mean_ms: Option<u32>,
+    // Failed and timed out reads over all sensors
+    pub sensor_errors: u32,
+    pub reset_reason: String,
+}
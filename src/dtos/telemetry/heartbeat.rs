@@ -0,0 +1,35 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::dtos::telemetry::clock_diagnostics::ClockDiagnosticsDTO;
+use crate::dtos::telemetry::device_info::DeviceInfoDTO;
+use crate::dtos::telemetry::sensor_stats::SensorStatsDTO;
+use crate::dtos::telemetry::service_health::ServiceHealthDTO;
+use crate::dtos::telemetry::wifi_diagnostics::WifiDiagnosticsDTO;
+use crate::enums::boot_stage::BootStage;
+
+#[derive(Debug, Clone)]
+pub struct HeartbeatDTO {
+    pub device_urn: String,
+    pub location_urn: String,
+    pub timestamp: u64,
+    pub uptime_s: u64,
+    pub queue_depth: usize,
+    // Uplink queue evictions since boot, summed over all targets
+    pub dropped_periodic: u32,
+    pub dropped_state_change: u32,
+    pub dropped_alert: u32,
+    pub device: DeviceInfoDTO,
+    pub clock: ClockDiagnosticsDTO,
+    // None when the station interface is not in use
+    pub wifi: Option<WifiDiagnosticsDTO>,
+    // From `ServiceSupervisor::report`
+    pub services: Vec<ServiceHealthDTO>,
+    // From `sensor_stats::report`
+    pub sensors: Vec<SensorStatsDTO>,
+    // From `boot::report`; the previous boot's stage shows where a device
+    // that kept resetting got stuck
+    pub boot_stage: BootStage,
+    pub boot_error: Option<String>,
+    pub previous_boot_stage: Option<BootStage>,
+}
@@ -0,0 +1,11 @@
+use alloc::string::String;
+
+// One row of the production line test matrix
+#[derive(Debug, Clone)]
+pub struct MfgCheckDTO {
+    // e.g. "i2c.0x76" or "sensor.bme280"
+    pub name: String,
+    pub passed: bool,
+    // Reading or error text printed next to the result
+    pub detail: String,
+}
@@ -0,0 +1,11 @@
+pub mod clock_diagnostics;
+pub mod device_metrics;
+pub mod device_info;
+pub mod heartbeat;
+pub mod mfg_check;
+pub mod sensor_stats;
+pub mod service_health;
+pub mod soak_stats;
+pub mod upload_stats;
+pub mod upload_timing;
+pub mod wifi_diagnostics;
\ No newline at end of file
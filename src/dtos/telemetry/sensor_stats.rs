@@ -0,0 +1,15 @@
+use alloc::string::String;
+
+#[derive(Debug, Clone, Default)]
+pub struct SensorStatsDTO {
+    pub name: String,
+    pub successes: u32,
+    // Failed reads by class, see `ReadStatus`
+    pub errors: u32,
+    pub timeouts: u32,
+    // Unix time of the last successful read, None until the clock is set
+    pub last_success: Option<u64>,
+    // Over all reads since boot, successful or not
+    pub min_latency_ms: Option<u32>,
+    pub max_latency_ms: Option<u32>,
+}
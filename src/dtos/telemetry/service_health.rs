@@ -0,0 +1,12 @@
+use alloc::string::String;
+
+use crate::enums::service_state::ServiceState;
+
+#[derive(Debug, Clone)]
+pub struct ServiceHealthDTO {
+    pub name: String,
+    pub state: ServiceState,
+    // Failed or wedged runs since boot
+    pub restarts: u32,
+    pub last_error: Option<String>,
+}
@@ -0,0 +1,19 @@
+// Soak test counters, kept across resets until the next test starts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SoakStatsDTO {
+    pub active: bool,
+    // Ran the full duration; stopping early leaves it false
+    pub completed: bool,
+    // Unix time, 0 when started before the clock was set
+    pub started_at: u64,
+    // Uptime accumulated over every boot of the test
+    pub elapsed_s: u64,
+    pub reads: u32,
+    pub read_errors: u32,
+    pub uploads: u32,
+    pub upload_errors: u32,
+    // Boots during the test, by cause
+    pub resets: u32,
+    pub watchdog_resets: u32,
+    pub brownout_resets: u32,
+}
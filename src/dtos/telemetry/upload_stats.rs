@@ -0,0 +1,17 @@
+use alloc::string::String;
+
+use crate::dtos::telemetry::upload_timing::UploadTimingDTO;
+
+#[derive(Debug, Clone, Default)]
+pub struct UploadStatsDTO {
+    // The transport's URN, as in `UploaderService::metrics`
+    pub name: String,
+    // Successful uploads only, failures stop partway and are counted by the
+    // uploader
+    pub uploads: u32,
+    pub bytes: u64,
+    // Summed over all uploads, for the mean and the throughput
+    pub total_ms: u64,
+    pub max_total_ms: u32,
+    pub last: Option<UploadTimingDTO>,
+}
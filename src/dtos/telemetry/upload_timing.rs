@@ -0,0 +1,14 @@
+// Phases of one upload, each measured from the end of the previous one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UploadTimingDTO {
+    pub dns_ms: u32,
+    // Includes every address tried before one accepted
+    pub connect_ms: u32,
+    // None over plain TCP
+    pub tls_ms: Option<u32>,
+    // Writing the request and waiting for the server's answer
+    pub request_ms: u32,
+    pub total_ms: u32,
+    // Payload only, without headers or framing
+    pub bytes: usize,
+}
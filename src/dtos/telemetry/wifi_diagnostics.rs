@@ -0,0 +1,16 @@
+use alloc::string::String;
+
+#[derive(Debug, Clone, Default)]
+pub struct WifiDiagnosticsDTO {
+    pub ssid: Option<String>,
+    pub bssid: Option<[u8; 6]>,
+    pub channel: Option<u8>,
+    pub rssi_dbm: Option<i8>,
+    // Time from starting association to connected, for the last connect
+    pub connect_latency_ms: Option<u64>,
+    // 802.11 reason code reported with the most recent disconnect
+    pub last_disconnect_reason: Option<u16>,
+    pub disconnects: u32,
+    // Successful connects after the first one since boot
+    pub reconnects: u32,
+}
@@ -0,0 +1,14 @@
+use alloc::string::String;
+
+use crate::enums::uplink::Uplink;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportAck {
+    pub uplink: Uplink,
+    // HTTP status, MQTT packet id, or None for fire-and-forget radios
+    pub code: Option<u16>,
+    pub bytes: usize,
+    // Id of the last envelope the server confirms it stored, the "cursor"
+    // of its response. None from uplinks without a response body
+    pub cursor: Option<String>,
+}
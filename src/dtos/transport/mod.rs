@@ -0,0 +1,2 @@
+pub mod ack;
+pub mod target_metrics;
\ No newline at end of file
@@ -0,0 +1,10 @@
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TargetMetricsDTO {
+    pub successes: u32,
+    pub failures: u32,
+    // Payloads evicted because the target's retry queue was full, indexed by
+    // `Priority::index`
+    pub dropped: [u32; 3],
+    pub queued: usize,
+    pub last_success: Option<u64>,
+}
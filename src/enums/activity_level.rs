@@ -0,0 +1,28 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ActivityLevel {
+    // Resting, or being carried without walking
+    Idle,
+    // Moving about, slow walking
+    Light,
+    // Brisk walking or running
+    Active,
+}
+
+impl ActivityLevel {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "idle" => Some(ActivityLevel::Idle),
+            "light" => Some(ActivityLevel::Light),
+            "active" => Some(ActivityLevel::Active),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityLevel::Idle => "idle",
+            ActivityLevel::Light => "light",
+            ActivityLevel::Active => "active",
+        }
+    }
+}
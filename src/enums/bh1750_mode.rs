@@ -0,0 +1,17 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bh1750Mode {
+    // Keeps measuring, reads return the latest result without waiting
+    Continuous,
+    // Measures once per read and powers down in between (~1 µA)
+    OneTime,
+}
+
+impl Bh1750Mode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "continuous" => Some(Bh1750Mode::Continuous),
+            "one_time" | "onetime" => Some(Bh1750Mode::OneTime),
+            _ => None,
+        }
+    }
+}
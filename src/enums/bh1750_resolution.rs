@@ -0,0 +1,20 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bh1750Resolution {
+    // 1 lx steps, 120 ms
+    High,
+    // 0.5 lx steps, 120 ms
+    High2,
+    // 4 lx steps, 16 ms
+    Low,
+}
+
+impl Bh1750Resolution {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "h" | "high" => Some(Bh1750Resolution::High),
+            "h2" | "high2" => Some(Bh1750Resolution::High2),
+            "l" | "low" => Some(Bh1750Resolution::Low),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,18 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bme280Mode {
+    // One conversion per read, sleeping in between
+    Forced,
+    // Free running with `standby_ms` between conversions, needed for the
+    // IIR filter to see a steady sample rate
+    Normal,
+}
+
+impl Bme280Mode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "forced" => Some(Bme280Mode::Forced),
+            "normal" => Some(Bme280Mode::Normal),
+            _ => None,
+        }
+    }
+}
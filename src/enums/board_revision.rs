@@ -0,0 +1,28 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardRevision {
+    // BME280 and BH1750 only, no strapping and no EEPROM
+    B,
+    // Adds the DS3231 RTC and a VL53L0X header
+    C,
+    // Adds the AMG8833 and moves the status LED off the boot strap pin
+    D,
+}
+
+impl BoardRevision {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "b" => Some(BoardRevision::B),
+            "c" => Some(BoardRevision::C),
+            "d" => Some(BoardRevision::D),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BoardRevision::B => "B",
+            BoardRevision::C => "C",
+            BoardRevision::D => "D",
+        }
+    }
+}
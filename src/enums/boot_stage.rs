@@ -0,0 +1,44 @@
+// Boot progress in order, declared first to last so the derived ordering
+// tells how far a boot got
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BootStage {
+    Reset,
+    ConfigLoaded,
+    BusesUp,
+    SensorsOk,
+    NetUp,
+    TimeSynced,
+    Running,
+}
+
+impl BootStage {
+    pub const ALL: [BootStage; 7] = [
+        BootStage::Reset,
+        BootStage::ConfigLoaded,
+        BootStage::BusesUp,
+        BootStage::SensorsOk,
+        BootStage::NetUp,
+        BootStage::TimeSynced,
+        BootStage::Running,
+    ];
+
+    pub fn index(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BootStage::Reset => "RESET",
+            BootStage::ConfigLoaded => "CONFIG_LOADED",
+            BootStage::BusesUp => "BUSES_UP",
+            BootStage::SensorsOk => "SENSORS_OK",
+            BootStage::NetUp => "NET_UP",
+            BootStage::TimeSynced => "TIME_SYNCED",
+            BootStage::Running => "RUNNING",
+        }
+    }
+}
@@ -0,0 +1,15 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockSource {
+    // Battery backed DS3231, authoritative while offline
+    Rtc,
+    Sntp,
+}
+
+impl ClockSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClockSource::Rtc => "rtc",
+            ClockSource::Sntp => "sntp",
+        }
+    }
+}
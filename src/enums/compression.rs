@@ -0,0 +1,23 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    // zlib-wrapped DEFLATE, what HTTP calls "Content-Encoding: deflate"
+    Deflate,
+}
+
+impl Compression {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "none" | "" => Some(Compression::None),
+            "deflate" => Some(Compression::Deflate),
+            _ => None,
+        }
+    }
+
+    pub fn content_encoding(&self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            Compression::Deflate => Some("deflate"),
+        }
+    }
+}
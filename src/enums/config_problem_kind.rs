@@ -0,0 +1,27 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigProblemKind {
+    // Not valid JSON, or a schema this firmware cannot migrate
+    Malformed,
+    // A sensor key no driver in this firmware answers to
+    UnknownSensor,
+    // One GPIO claimed twice, or a pin that cannot serve its role
+    PinConflict,
+    InvalidInterval,
+    // An included sensor whose classification has no steps
+    MissingThreshold,
+    // A setting that does not parse, e.g. a pipeline stage spec
+    InvalidValue,
+}
+
+impl ConfigProblemKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConfigProblemKind::Malformed => "malformed",
+            ConfigProblemKind::UnknownSensor => "unknown_sensor",
+            ConfigProblemKind::PinConflict => "pin_conflict",
+            ConfigProblemKind::InvalidInterval => "invalid_interval",
+            ConfigProblemKind::MissingThreshold => "missing_threshold",
+            ConfigProblemKind::InvalidValue => "invalid_value",
+        }
+    }
+}
@@ -0,0 +1,7 @@
+// What the caller should do with the socket once a request is handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Close,
+    // Handshake sent; hand the socket to `live_stream::serve`
+    WebSocket,
+}
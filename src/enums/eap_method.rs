@@ -0,0 +1,17 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EapMethod {
+    // Username/password inside a TLS tunnel, server verified by CA cert
+    Peap,
+    // Mutual certificate authentication, no password
+    Tls,
+}
+
+impl EapMethod {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "peap" => Some(EapMethod::Peap),
+            "tls" | "eap-tls" => Some(EapMethod::Tls),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::enums::priority::Priority;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    DoorOpened,
+    DoorClosed,
+    MotionDetected,
+    PersonEntered,
+    PersonExited,
+    // Vibration of the monitored machine rose above or fell below its
+    // running threshold
+    MachineStarted,
+    MachineStopped,
+    // A configured BLE beacon was heard, or not heard for a while
+    BeaconArrived,
+    BeaconDeparted,
+    // A remote control code was seen, e.g. someone switching the AC
+    IrReceived,
+    // A badge was presented at the RFID reader
+    CardScanned,
+    ThresholdBreached,
+    // A field changing faster than its configured slope
+    RateExceeded,
+    // A sensor stopped answering or returned implausible data
+    SensorFault,
+    // The enclosure was struck, moved or opened while armed
+    Tampered,
+    // Free fall followed by a shock: the asset was dropped
+    Fall,
+    // A shock without a preceding free fall: the asset was hit
+    Impact,
+    // A reading far off its rolling baseline: drift, tampering, a fault
+    Anomaly,
+    // An hour unlike the learned weekly pattern: lights on at night, an
+    // office empty on a workday
+    Deviation,
+    // Heard by the microphone: a window breaking, a smoke alarm sounding
+    GlassBreak,
+    SmokeAlarm,
+    // A remote or local config failed validation, see `ConfigValidator`
+    ConfigRejected,
+}
+
+impl EventKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "door_opened" => Some(EventKind::DoorOpened),
+            "door_closed" => Some(EventKind::DoorClosed),
+            "motion_detected" => Some(EventKind::MotionDetected),
+            "person_entered" => Some(EventKind::PersonEntered),
+            "person_exited" => Some(EventKind::PersonExited),
+            "machine_started" => Some(EventKind::MachineStarted),
+            "machine_stopped" => Some(EventKind::MachineStopped),
+            "beacon_arrived" => Some(EventKind::BeaconArrived),
+            "beacon_departed" => Some(EventKind::BeaconDeparted),
+            "ir_received" => Some(EventKind::IrReceived),
+            "card_scanned" => Some(EventKind::CardScanned),
+            "threshold_breached" => Some(EventKind::ThresholdBreached),
+            "rate_exceeded" => Some(EventKind::RateExceeded),
+            "sensor_fault" => Some(EventKind::SensorFault),
+            "tampered" => Some(EventKind::Tampered),
+            "fall" => Some(EventKind::Fall),
+            "impact" => Some(EventKind::Impact),
+            "anomaly" => Some(EventKind::Anomaly),
+            "deviation" => Some(EventKind::Deviation),
+            "glass_break" => Some(EventKind::GlassBreak),
+            "smoke_alarm" => Some(EventKind::SmokeAlarm),
+            "config_rejected" => Some(EventKind::ConfigRejected),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::DoorOpened => "door_opened",
+            EventKind::DoorClosed => "door_closed",
+            EventKind::MotionDetected => "motion_detected",
+            EventKind::PersonEntered => "person_entered",
+            EventKind::PersonExited => "person_exited",
+            EventKind::MachineStarted => "machine_started",
+            EventKind::MachineStopped => "machine_stopped",
+            EventKind::BeaconArrived => "beacon_arrived",
+            EventKind::BeaconDeparted => "beacon_departed",
+            EventKind::IrReceived => "ir_received",
+            EventKind::CardScanned => "card_scanned",
+            EventKind::ThresholdBreached => "threshold_breached",
+            EventKind::RateExceeded => "rate_exceeded",
+            EventKind::SensorFault => "sensor_fault",
+            EventKind::Tampered => "tampered",
+            EventKind::Fall => "fall",
+            EventKind::Impact => "impact",
+            EventKind::Anomaly => "anomaly",
+            EventKind::Deviation => "deviation",
+            EventKind::GlassBreak => "glass_break",
+            EventKind::SmokeAlarm => "smoke_alarm",
+            EventKind::ConfigRejected => "config_rejected",
+        }
+    }
+
+    pub fn priority(&self) -> Priority {
+        match self {
+            EventKind::DoorOpened
+            | EventKind::DoorClosed
+            | EventKind::MotionDetected
+            | EventKind::PersonEntered
+            | EventKind::PersonExited
+            | EventKind::MachineStarted
+            | EventKind::MachineStopped
+            | EventKind::BeaconArrived
+            | EventKind::BeaconDeparted
+            | EventKind::IrReceived
+            | EventKind::CardScanned
+            | EventKind::ConfigRejected => Priority::StateChange,
+            EventKind::ThresholdBreached
+            | EventKind::RateExceeded
+            | EventKind::SensorFault
+            | EventKind::Tampered
+            | EventKind::Fall
+            | EventKind::Impact
+            | EventKind::Anomaly
+            | EventKind::Deviation
+            | EventKind::GlassBreak
+            | EventKind::SmokeAlarm => Priority::Alert,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use alloc::string::String;
+use core::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HttpError {
+    // No status line or header/body separator
+    MalformedResponse,
+    Status(u16),
+    Deserialize(String),
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::MalformedResponse => write!(f, "malformed HTTP response"),
+            HttpError::Status(status) => write!(f, "HTTP status {}", status),
+            HttpError::Deserialize(reason) => write!(f, "invalid response body: {}", reason),
+        }
+    }
+}
+
+impl core::error::Error for HttpError {}
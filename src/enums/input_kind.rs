@@ -0,0 +1,28 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputKind {
+    // Released before the long press time
+    Pressed,
+    // Held past the long press time, reported while still held
+    LongPressed,
+    // Encoder turned, `steps` detents clockwise (negative counter-clockwise)
+    Rotated,
+}
+
+impl InputKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "pressed" => Some(InputKind::Pressed),
+            "long_pressed" => Some(InputKind::LongPressed),
+            "rotated" => Some(InputKind::Rotated),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InputKind::Pressed => "pressed",
+            InputKind::LongPressed => "long_pressed",
+            InputKind::Rotated => "rotated",
+        }
+    }
+}
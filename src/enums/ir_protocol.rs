@@ -0,0 +1,26 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrProtocol {
+    // Pulse distance, 8 or 16-bit address and 8-bit command; most AC units,
+    // TVs and projectors from Asian makers
+    Nec,
+    // Manchester coded, 5-bit address and 7-bit command; Philips and much
+    // European equipment
+    Rc5,
+}
+
+impl IrProtocol {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "nec" => Some(IrProtocol::Nec),
+            "rc5" => Some(IrProtocol::Rc5),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IrProtocol::Nec => "nec",
+            IrProtocol::Rc5 => "rc5",
+        }
+    }
+}
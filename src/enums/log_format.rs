@@ -0,0 +1,23 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Csv,
+    Jsonl,
+}
+
+impl LogFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "csv" => Some(LogFormat::Csv),
+            "jsonl" => Some(LogFormat::Jsonl),
+            _ => None,
+        }
+    }
+
+    // FAT short file names only allow three character extensions
+    pub fn extension(&self) -> &'static str {
+        match self {
+            LogFormat::Csv => "CSV",
+            LogFormat::Jsonl => "JSL",
+        }
+    }
+}
@@ -0,0 +1,8 @@
+// What the installer asked for in the local menu; carried out by whoever
+// owns the bus, the provisioning flow or the config
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    ScanI2c,
+    StartProvisioning,
+    SetIntervalS(u64),
+}
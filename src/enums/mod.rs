@@ -1 +1,33 @@
+pub mod activity_level;
+pub mod bh1750_mode;
+pub mod bh1750_resolution;
+pub mod bme280_mode;
+pub mod board_revision;
+pub mod boot_stage;
+pub mod clock_source;
+pub mod compression;
+pub mod config_problem_kind;
+pub mod connection_state;
+pub mod eap_method;
+pub mod event_kind;
+pub mod http_error;
+pub mod input_kind;
+pub mod ir_protocol;
+pub mod log_format;
+pub mod menu_action;
+pub mod modbus_data_type;
+pub mod modbus_register_kind;
+pub mod network_interface;
+pub mod node_mode;
+pub mod payload_kind;
+pub mod priority;
+pub mod quality;
+pub mod read_status;
+pub mod sensing_profile;
+pub mod sensor_error;
+pub mod service_state;
+pub mod store_mode;
+pub mod transport_error;
+pub mod unit_system;
+pub mod uplink;
 pub mod value;
\ No newline at end of file
@@ -0,0 +1,39 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModbusDataType {
+    U16,
+    I16,
+    // 32-bit types span two registers, high word first as most meters do
+    U32,
+    I32,
+    F32,
+}
+
+impl ModbusDataType {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "u16" => Some(ModbusDataType::U16),
+            "i16" => Some(ModbusDataType::I16),
+            "u32" => Some(ModbusDataType::U32),
+            "i32" => Some(ModbusDataType::I32),
+            "f32" => Some(ModbusDataType::F32),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModbusDataType::U16 => "u16",
+            ModbusDataType::I16 => "i16",
+            ModbusDataType::U32 => "u32",
+            ModbusDataType::I32 => "i32",
+            ModbusDataType::F32 => "f32",
+        }
+    }
+
+    pub fn registers(&self) -> u16 {
+        match self {
+            ModbusDataType::U16 | ModbusDataType::I16 => 1,
+            ModbusDataType::U32 | ModbusDataType::I32 | ModbusDataType::F32 => 2,
+        }
+    }
+}
@@ -0,0 +1,31 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModbusRegisterKind {
+    // Read/write registers, function 0x03
+    Holding,
+    // Read-only registers, function 0x04
+    Input,
+}
+
+impl ModbusRegisterKind {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "holding" => Some(ModbusRegisterKind::Holding),
+            "input" => Some(ModbusRegisterKind::Input),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModbusRegisterKind::Holding => "holding",
+            ModbusRegisterKind::Input => "input",
+        }
+    }
+
+    pub fn function(&self) -> u8 {
+        match self {
+            ModbusRegisterKind::Holding => 0x03,
+            ModbusRegisterKind::Input => 0x04,
+        }
+    }
+}
@@ -0,0 +1,15 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkInterface {
+    Ethernet,
+    Wifi,
+}
+
+impl NetworkInterface {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "ethernet" => Some(NetworkInterface::Ethernet),
+            "wifi" => Some(NetworkInterface::Wifi),
+            _ => None,
+        }
+    }
+}
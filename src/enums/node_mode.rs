@@ -0,0 +1,20 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeMode {
+    // Uploads its own measurements over Wi-Fi, no mesh
+    Standalone,
+    // Battery node that hands envelopes to a gateway over ESP-NOW
+    Leaf,
+    // Mains node that receives leaf envelopes and forwards them upstream
+    Gateway,
+}
+
+impl NodeMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "standalone" => Some(NodeMode::Standalone),
+            "leaf" => Some(NodeMode::Leaf),
+            "gateway" => Some(NodeMode::Gateway),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,7 @@
+// Which stream a queued payload belongs to; transports route events to their
+// own endpoint or topic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadKind {
+    Measurement,
+    Event,
+}
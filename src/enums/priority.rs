@@ -0,0 +1,16 @@
+// Upload priority, declared lowest first so the derived ordering ranks
+// Alert > StateChange > Periodic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Periodic,
+    StateChange,
+    Alert,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 3] = [Priority::Periodic, Priority::StateChange, Priority::Alert];
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
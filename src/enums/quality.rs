@@ -0,0 +1,39 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quality {
+    Good,
+    // A cached value standing in for a failed read
+    Stale,
+    // Outside the sensor's rated range, kept for reference only
+    OutOfRange,
+    // Derived or filled in rather than measured, e.g. smoothed
+    Estimated,
+    // The sensor reported a fault along with the value
+    SensorFault,
+    // Far off the field's rolling baseline, see `AnomalyStage`
+    Anomalous,
+}
+
+impl Quality {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_uppercase().as_str() {
+            "GOOD" => Some(Quality::Good),
+            "STALE" => Some(Quality::Stale),
+            "OUT_OF_RANGE" => Some(Quality::OutOfRange),
+            "ESTIMATED" => Some(Quality::Estimated),
+            "SENSOR_FAULT" => Some(Quality::SensorFault),
+            "ANOMALOUS" => Some(Quality::Anomalous),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Quality::Good => "GOOD",
+            Quality::Stale => "STALE",
+            Quality::OutOfRange => "OUT_OF_RANGE",
+            Quality::Estimated => "ESTIMATED",
+            Quality::SensorFault => "SENSOR_FAULT",
+            Quality::Anomalous => "ANOMALOUS",
+        }
+    }
+}
@@ -0,0 +1,18 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadStatus {
+    Ok,
+    // The sensor answered with an error
+    Error,
+    // No answer within the read timeout, the read was abandoned
+    Timeout,
+}
+
+impl ReadStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReadStatus::Ok => "OK",
+            ReadStatus::Error => "ERROR",
+            ReadStatus::Timeout => "TIMEOUT",
+        }
+    }
+}
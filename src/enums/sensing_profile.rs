@@ -0,0 +1,28 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensingProfile {
+    // Short interval, unbatched, for commissioning and incidents
+    Fast,
+    // The build-time and remote configuration as is
+    Normal,
+    // Long interval, large batches, deep sleep in between
+    Eco,
+}
+
+impl SensingProfile {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "fast" => Some(SensingProfile::Fast),
+            "normal" => Some(SensingProfile::Normal),
+            "eco" => Some(SensingProfile::Eco),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SensingProfile::Fast => "fast",
+            SensingProfile::Normal => "normal",
+            SensingProfile::Eco => "eco",
+        }
+    }
+}
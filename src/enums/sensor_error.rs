@@ -0,0 +1,23 @@
+use alloc::string::String;
+use core::fmt;
+
+// Why a read produced no measurement. Returned instead of placeholder
+// values, which downstream cannot tell apart from real readings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SensorError {
+    // The bus transaction failed: wiring, pull-ups, a wrong address
+    Bus(String),
+    // The device answered, but with something that is not a measurement
+    InvalidData(String),
+}
+
+impl fmt::Display for SensorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SensorError::Bus(reason) => write!(f, "bus error: {}", reason),
+            SensorError::InvalidData(reason) => write!(f, "invalid data: {}", reason),
+        }
+    }
+}
+
+impl core::error::Error for SensorError {}
@@ -0,0 +1,21 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceState {
+    // Registered, first run not finished yet
+    Starting,
+    Running,
+    // Last run returned an error, restarting after the backoff
+    Failed,
+    // Last run exceeded its timeout and was cancelled
+    Wedged,
+}
+
+impl ServiceState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServiceState::Starting => "starting",
+            ServiceState::Running => "running",
+            ServiceState::Failed => "failed",
+            ServiceState::Wedged => "wedged",
+        }
+    }
+}
@@ -0,0 +1,19 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreMode {
+    // Local storage is the only sink (offline logger)
+    Primary,
+    // Local storage mirrors whatever is sent over the uplink
+    Backup,
+    Disabled,
+}
+
+impl StoreMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "primary" => Some(StoreMode::Primary),
+            "backup" => Some(StoreMode::Backup),
+            "disabled" => Some(StoreMode::Disabled),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use alloc::string::String;
+use core::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransportError {
+    // No link or route, worth trying the next transport
+    Unavailable,
+    Timeout,
+    // The peer answered but refused the payload, retrying won't help
+    Rejected(String),
+    PayloadTooLarge(usize),
+    Io(String),
+    // The peer is busy or timed out waiting (HTTP 408, 429) and asked to try
+    // again, after the seconds it gave in Retry-After if any
+    Throttled(Option<u64>),
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransportError::Unavailable => write!(f, "transport unavailable"),
+            TransportError::Timeout => write!(f, "transport timed out"),
+            TransportError::Rejected(reason) => write!(f, "payload rejected: {}", reason),
+            TransportError::PayloadTooLarge(size) => write!(f, "payload of {} bytes too large", size),
+            TransportError::Io(reason) => write!(f, "transport error: {}", reason),
+            TransportError::Throttled(Some(seconds)) => write!(f, "throttled, retry after {} s", seconds),
+            TransportError::Throttled(None) => write!(f, "throttled"),
+        }
+    }
+}
+
+impl core::error::Error for TransportError {}
+
+impl TransportError {
+    // Whether the same payload could succeed on another transport
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, TransportError::Rejected(_))
+    }
+}
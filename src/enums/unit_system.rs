@@ -0,0 +1,27 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitSystem {
+    Metric,
+    // Converted values replace the metric ones
+    Imperial,
+    // Converted values are added next to the metric ones
+    Both,
+}
+
+impl UnitSystem {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "metric" => Some(UnitSystem::Metric),
+            "imperial" => Some(UnitSystem::Imperial),
+            "both" => Some(UnitSystem::Both),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "metric",
+            UnitSystem::Imperial => "imperial",
+            UnitSystem::Both => "both",
+        }
+    }
+}
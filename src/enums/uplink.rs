@@ -0,0 +1,27 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Uplink {
+    Wifi,
+    Mqtt,
+    EspNow,
+    // Low-latency datagrams to a LAN collector
+    Udp,
+    // Point-to-point LoRa for sites without Wi-Fi coverage
+    LoRa,
+    // LTE-M/NB-IoT or GPRS through an AT-command modem
+    Cellular,
+}
+
+impl Uplink {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            // HTTP over whichever IP interface is up
+            "wifi" | "http" => Some(Uplink::Wifi),
+            "mqtt" => Some(Uplink::Mqtt),
+            "espnow" => Some(Uplink::EspNow),
+            "udp" => Some(Uplink::Udp),
+            "lora" => Some(Uplink::LoRa),
+            "cellular" => Some(Uplink::Cellular),
+            _ => None,
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use alloc::string::String;
+use core::fmt;
 
 #[derive(Debug, Clone)]
 pub enum Value {
@@ -6,4 +7,26 @@ pub enum Value {
     Float(f32),
     Integer(i32),
     Boolean(bool),
-}
\ No newline at end of file
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(value) => write!(f, "{}", value),
+            Value::Float(value) => write!(f, "{}", value),
+            Value::Integer(value) => write!(f, "{}", value),
+            Value::Boolean(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl Value {
+    // Numeric values as f32, for filters that do not care about the variant
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Value::Float(value) => Some(*value),
+            Value::Integer(value) => Some(*value as f32),
+            _ => None,
+        }
+    }
+}
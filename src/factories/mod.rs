@@ -1 +1,2 @@
+pub mod pipeline;
 pub mod sensor;
\ No newline at end of file
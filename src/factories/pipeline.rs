@@ -0,0 +1,128 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::fmt::Error;
+
+use log::warn;
+
+use crate::abstractions::factory::IFactory;
+use crate::abstractions::pipeline::IPipelineStage;
+use crate::constants::pipeline::PipelineConstant;
+use crate::enums::unit_system::UnitSystem;
+use crate::pipelines::anomaly::AnomalyStage;
+use crate::pipelines::chain::PipelineChain;
+use crate::pipelines::moving_average::MovingAverageStage;
+use crate::pipelines::outlier::OutlierStage;
+use crate::pipelines::rate_alert::RateAlertStage;
+use crate::pipelines::temperature_compensation::TemperatureCompensationStage;
+use crate::pipelines::threshold_alert::ThresholdAlertStage;
+use crate::pipelines::unit_conversion::UnitConversionStage;
+
+// Builds the chain configured for a sensor key from stage specs such as
+// ["outlier", "moving_average(5)", "threshold_alert(temperature>30)"]. Every
+// `get` returns a fresh chain, so build it once per sensor and keep it
+pub struct PipelineFactory {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    chains: BTreeMap<String, Vec<String>>,
+    units: UnitSystem,
+    compensation: BTreeMap<String, f32>,
+}
+
+impl IFactory<PipelineChain> for PipelineFactory {
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    fn get(&self, key: String) -> Result<PipelineChain, Error> {
+        self._get(key)
+    }
+}
+
+impl PipelineFactory {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        chains: BTreeMap<String, Vec<String>>,
+        units: UnitSystem,
+        compensation: BTreeMap<String, f32>,
+    ) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            chains: chains,
+            units: units,
+            compensation: compensation,
+        }
+    }
+
+    // Sensors without a configured chain get an empty, pass-through one,
+    // plus temperature compensation in front when coefficients are set and
+    // the unit conversion at the end when `units` is not metric
+    fn _get(&self, key: String) -> Result<PipelineChain, Error> {
+        let mut stages: Vec<Box<dyn IPipelineStage>> = Vec::new();
+        if !self.compensation.is_empty() {
+            stages.push(Box::new(TemperatureCompensationStage::new(self.compensation.clone())));
+        }
+        for spec in self.chains.get(&key).map(|specs| specs.as_slice()).unwrap_or(&[]) {
+            match Self::stage(spec) {
+                Some(stage) => stages.push(stage),
+                None => {
+                    warn!("Unknown pipeline stage {} for {}", spec, key);
+                    return Err(Error);
+                },
+            }
+        }
+        if self.units != UnitSystem::Metric {
+            stages.push(Box::new(UnitConversionStage::new(self.units)));
+        }
+        Ok(PipelineChain::new(
+            format!("{}:{}", self.urn, key),
+            self.device_urn.clone(),
+            self.location_urn.clone(),
+            stages,
+        ))
+    }
+
+    // "name" or "name(argument)"
+    pub fn stage(spec: &str) -> Option<Box<dyn IPipelineStage>> {
+        let spec = spec.trim();
+        let (name, argument) = match spec.split_once('(') {
+            Some((name, rest)) => (name.trim(), Some(rest.strip_suffix(')')?.trim())),
+            None => (spec, None),
+        };
+        let stage: Box<dyn IPipelineStage> = match name {
+            PipelineConstant::OUTLIER => Box::new(OutlierStage::new(match argument {
+                Some(argument) => argument.parse().ok().filter(|deviations: &f32| *deviations > 0.0)?,
+                None => PipelineConstant::DEFAULT_OUTLIER_DEVIATIONS,
+            })),
+            PipelineConstant::MOVING_AVERAGE => Box::new(MovingAverageStage::new(match argument {
+                Some(argument) => argument.parse().ok().filter(|window: &usize| *window > 0)?,
+                None => PipelineConstant::DEFAULT_MOVING_AVERAGE_WINDOW,
+            })),
+            PipelineConstant::ANOMALY => Box::new(AnomalyStage::new(match argument {
+                Some(argument) => argument.parse().ok().filter(|deviations: &f32| *deviations > 0.0)?,
+                None => PipelineConstant::DEFAULT_ANOMALY_DEVIATIONS,
+            })),
+            // The condition is required, there is no sensible default limit
+            PipelineConstant::THRESHOLD_ALERT => Box::new(ThresholdAlertStage::parse(argument?)?),
+            PipelineConstant::RATE_ALERT => Box::new(RateAlertStage::parse(argument?)?),
+            PipelineConstant::UNIT_CONVERSION => Box::new(UnitConversionStage::new(UnitSystem::parse(argument?)?)),
+            _ => return None,
+        };
+        Some(stage)
+    }
+}
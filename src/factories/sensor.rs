@@ -1,23 +1,38 @@
+use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
+use alloc::fmt::Error;
+use alloc::rc::Rc;
 use alloc::string::{String, ToString};
-use core::error::Error;
+use alloc::vec::Vec;
+use core::future::Future;
+use core::marker::PhantomData;
+use core::pin::Pin;
+
+use embedded_hal::i2c::I2c;
+use log::info;
 
 use crate::abstractions::factory::IFactory;
 use crate::abstractions::sensor::ISensor;
 use crate::constants::sensor::SensorConstant;
-use crate::sensors::bh1750::BH1750Sensor;
-use crate::sensors::bme280::BME280Sensor;
-use crate::sensors::ds323x::DS323XSensor;
-use crate::sensors::vl53l0x::VL53L0XSensor;
+use crate::enums::sensor_error::SensorError;
+use crate::enums::value::Value;
+use crate::services::hardware_profile;
+use crate::sensors::vl53l0x::{self, VL53L0XSensor};
+
+// Any sensor, its measurement flattened to named values so readings of
+// every type are cached and enveloped alike
+pub type Sensor = Rc<dyn ISensor<BTreeMap<String, Value>>>;
 
+// The sensors brought up in main on their buses, by key. Drivers need their
+// bus handles and pins to exist, so the factory no longer builds them
 pub struct SensorFactory {
     pub urn: String,
     pub device_urn: String,
     pub location_urn: String,
-    pub store: BTreeMap<String, Box<dyn ISensor<Box<dyn Error + Send + Sync>> + Send + Sync>>,
+    pub store: BTreeMap<String, Sensor>,
 }
 
-impl IFactory<Box<dyn ISensor<Box<dyn Error + Send + Sync>> + Send + Sync>> for SensorFactory {
+impl IFactory<Sensor> for SensorFactory {
 
     fn urn(&self) -> String {
         self.urn.clone()
@@ -31,7 +46,7 @@ impl IFactory<Box<dyn ISensor<Box<dyn Error + Send + Sync>> + Send + Sync>> for
         self.location_urn.clone()
     }
 
-    fn get(&self, key: String) -> Result<Box<dyn ISensor<Box<dyn Error + Send + Sync>> + Send + Sync>, Box<dyn Error + Send + Sync>> {
+    fn get(&self, key: String) -> Result<Sensor, Error> {
         self._get(key)
     }
 }
@@ -43,29 +58,88 @@ impl SensorFactory {
         device_urn: String,
         location_urn: String,
     ) -> Self {
-
-        let mut store: BTreeMap<String, Box<dyn ISensor<Box<dyn Error + Send + Sync>> + Send + Sync>> = BTreeMap::new();
-        
-        store.insert(SensorConstant::BME280.to_string(), Box::new(BME280Sensor::new()));
-        store.insert(SensorConstant::BH1750.to_string(), Box::new(BH1750Sensor::new()));
-        store.insert(SensorConstant::DS3231SN.to_string(), Box::new(DS323XSensor::new()));
-        store.insert(SensorConstant::VL5310X.to_string(), Box::new(VL53L0XSensor::new()));
-        
         Self {
             urn: urn,
             device_urn: device_urn,
             location_urn: location_urn,
-            store: store
+            store: BTreeMap::new()
+        }
+    }
+
+    // Only drivers for parts the detected board revision carries, e.g.
+    // `insert(SensorConstant::BME280, bme280)`
+    pub fn insert<S, T>(&mut self, key: &str, sensor: S)
+    where
+        S: ISensor<T> + 'static,
+        T: 'static,
+        for<'a> BTreeMap<String, Value>: From<&'a T>,
+    {
+        if !hardware_profile::fitted(key) {
+            info!("Sensor {} is not fitted on this board revision", key);
+            return;
+        }
+        self.store.insert(key.to_string(), Rc::new(Flattened::new(sensor)));
+    }
+
+    // Units from `VL53L0XSensor::bring_up`, keyed `vl53l0x.0`, `vl53l0x.1`, ...
+    pub fn insert_vl53l0x<I: I2c + 'static>(&mut self, sensors: Vec<VL53L0XSensor<I>>) {
+        if !hardware_profile::fitted(SensorConstant::VL5310X) {
+            info!("Sensor {} is not fitted on this board revision", SensorConstant::VL5310X);
+            return;
         }
+        for (index, sensor) in sensors.into_iter().enumerate() {
+            let sensor: Sensor = Rc::new(Flattened::new(sensor));
+            self.store.insert(vl53l0x::key(index), sensor);
+        }
+    }
+
+    fn _get(&self, key: String) -> Result<Sensor, Error> {
+        self.store.get(&key).cloned().ok_or(Error)
     }
 
-    fn _get(&self, key: String) -> Result<Box<dyn ISensor<Box<dyn Error + Send + Sync>> + Send + Sync>, Box<dyn Error + Send + Sync>> {
-        self.store.get(&key)
-            .cloned()
-            .ok_or_else(|| Box::new(core::io::Error::new(
-                core::io::ErrorKind::NotFound,
-                format!("Sensor not found for key: {}", key)
-            )))
+}
+
+// Wraps a sensor so it reads into `BTreeMap<String, Value>`
+struct Flattened<S, T> {
+    sensor: S,
+    _measurement: PhantomData<T>,
+}
+
+impl<S, T> Flattened<S, T> {
+    fn new(sensor: S) -> Self {
+        Self {
+            sensor: sensor,
+            _measurement: PhantomData,
+        }
     }
-    
-}
\ No newline at end of file
+}
+
+impl<S, T> ISensor<BTreeMap<String, Value>> for Flattened<S, T>
+where
+    S: ISensor<T>,
+    for<'a> BTreeMap<String, Value>: From<&'a T>,
+{
+    fn urn(&self) -> String {
+        self.sensor.urn()
+    }
+
+    fn device_urn(&self) -> String {
+        self.sensor.device_urn()
+    }
+
+    fn location_urn(&self) -> String {
+        self.sensor.location_urn()
+    }
+
+    fn name(&self) -> String {
+        self.sensor.name()
+    }
+
+    fn read(&self) -> Result<BTreeMap<String, Value>, SensorError> {
+        self.sensor.read().map(|measurement| BTreeMap::from(&measurement))
+    }
+
+    fn read_async(&self) -> Pin<Box<dyn Future<Output = Result<BTreeMap<String, Value>, SensorError>> + '_>> {
+        Box::pin(async move { self.sensor.read_async().await.map(|measurement| BTreeMap::from(&measurement)) })
+    }
+}
@@ -0,0 +1,95 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use embassy_time::{Duration, Instant};
+
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::enums::priority::Priority;
+use crate::enums::value::Value;
+use crate::services::batcher::{BatchItem, BatcherService};
+
+fn item(id: &str, priority: Priority) -> BatchItem {
+    let mut data = BTreeMap::new();
+    data.insert(String::from("temperature"), Value::Float(21.5));
+    BatchItem {
+        envelope: MeasurementEnvelopeDTO {
+            id: String::from(id),
+            device_urn: String::from("urn:test:device"),
+            location_urn: String::from("urn:test:location"),
+            timestamp: 1_700_000_000,
+            relative_ms_since_boot: None,
+            data: data,
+            quality: BTreeMap::new(),
+            units: BTreeMap::new(),
+        },
+        priority: priority,
+    }
+}
+
+fn batcher(max_records: usize, max_age: Duration) -> BatcherService {
+    BatcherService::new(
+        String::from("urn:test:batcher"),
+        String::from("urn:test:device"),
+        String::from("urn:test:location"),
+        max_records,
+        max_age,
+    )
+}
+
+#[test]
+fn full_batch_is_flushed_at_the_record_count() {
+    let mut batcher = batcher(3, Duration::from_secs(60));
+    assert!(batcher.push(item("1-1", Priority::Periodic)).is_none());
+    assert!(batcher.push(item("1-2", Priority::Periodic)).is_none());
+    let (batch, priority) = batcher.push(item("1-3", Priority::Periodic)).unwrap();
+
+    let ids: Vec<&str> = batch.iter().map(|envelope| envelope.id.as_str()).collect();
+    assert_eq!(ids, ["1-1", "1-2", "1-3"]);
+    assert_eq!(priority, Priority::Periodic);
+    assert_eq!(batcher.len(), 0);
+    assert!(batcher.deadline().is_none());
+}
+
+#[test]
+fn deadline_is_max_age_after_the_first_item() {
+    let mut batcher = batcher(10, Duration::from_secs(60));
+    assert!(batcher.deadline().is_none());
+    let before = Instant::now();
+    batcher.push(item("1-1", Priority::Periodic));
+    let deadline = batcher.deadline().unwrap();
+    assert!(deadline >= before + Duration::from_secs(60));
+    assert!(deadline <= Instant::now() + Duration::from_secs(60));
+
+    // A later item does not push the deadline back, and when it passes
+    // `run` takes the partial batch
+    batcher.push(item("1-2", Priority::Periodic));
+    assert_eq!(batcher.deadline(), Some(deadline));
+    let (batch, _) = batcher.take().unwrap();
+    assert_eq!(batch.len(), 2);
+    assert!(batcher.deadline().is_none());
+}
+
+#[test]
+fn alert_flushes_at_once_and_carries_its_priority() {
+    let mut batcher = batcher(10, Duration::from_secs(60));
+    assert!(batcher.push(item("1-1", Priority::Periodic)).is_none());
+    let (batch, priority) = batcher.push(item("1-2", Priority::Alert)).unwrap();
+    assert_eq!(batch.len(), 2);
+    assert_eq!(priority, Priority::Alert);
+}
+
+#[test]
+fn batch_goes_out_at_its_highest_priority() {
+    let mut batcher = batcher(10, Duration::from_secs(60));
+    batcher.push(item("1-1", Priority::StateChange));
+    batcher.push(item("1-2", Priority::Periodic));
+    let (batch, priority) = batcher.take().unwrap();
+    assert_eq!(batch.len(), 2);
+    assert_eq!(priority, Priority::StateChange);
+
+    // The next batch starts over at the lowest class
+    batcher.push(item("1-3", Priority::Periodic));
+    assert_eq!(batcher.take().unwrap().1, Priority::Periodic);
+    assert!(batcher.take().is_none());
+}
@@ -0,0 +1,127 @@
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::convert::Infallible;
+
+use embedded_io_async::{ErrorType, Read, Write};
+
+use crate::abstractions::transport::ITransport;
+use crate::dtos::transport::ack::TransportAck;
+use crate::enums::payload_kind::PayloadKind;
+use crate::enums::transport_error::TransportError;
+use crate::enums::uplink::Uplink;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FakeRequest {
+    pub kind: PayloadKind,
+    pub body: Vec<u8>,
+}
+
+impl FakeRequest {
+    pub fn text(&self) -> &str {
+        core::str::from_utf8(&self.body).unwrap_or("")
+    }
+}
+
+// What the fake server received and how it answers. Shared with the test,
+// the transport itself is moved into the service under test
+#[derive(Debug, Default)]
+pub struct FakeServer {
+    pub requests: Vec<FakeRequest>,
+    // Answers in order, each request takes one; an ack once they run out.
+    // An ack may carry the cursor the server confirms
+    pub responses: VecDeque<Result<Option<String>, TransportError>>,
+}
+
+// `ITransport` backed by a `FakeServer` instead of a connection
+pub struct FakeBackend {
+    urn: String,
+    server: Rc<RefCell<FakeServer>>,
+}
+
+impl FakeBackend {
+    pub fn new(urn: &str) -> (Self, Rc<RefCell<FakeServer>>) {
+        let server = Rc::new(RefCell::new(FakeServer::default()));
+        let backend = Self {
+            urn: String::from(urn),
+            server: server.clone(),
+        };
+        (backend, server)
+    }
+
+    fn receive(&mut self, kind: PayloadKind, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        let mut server = self.server.borrow_mut();
+        server.requests.push(FakeRequest {
+            kind: kind,
+            body: payload.to_vec(),
+        });
+        server.responses.pop_front().unwrap_or(Ok(None)).map(|cursor| TransportAck {
+            uplink: Uplink::Wifi,
+            code: Some(201),
+            bytes: payload.len(),
+            cursor: cursor,
+        })
+    }
+}
+
+impl ITransport for FakeBackend {
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        String::from("urn:test:device")
+    }
+
+    fn location_urn(&self) -> String {
+        String::from("urn:test:location")
+    }
+
+    async fn send(&mut self, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        self.receive(PayloadKind::Measurement, payload)
+    }
+
+    async fn send_event(&mut self, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        self.receive(PayloadKind::Event, payload)
+    }
+}
+
+// A connection to a server that answers with a canned response: keeps
+// everything written to it and reads back `response`
+pub struct FakeStream {
+    pub written: Vec<u8>,
+    response: Vec<u8>,
+    read: usize,
+}
+
+impl FakeStream {
+    pub fn new(response: &str) -> Self {
+        Self {
+            written: Vec::new(),
+            response: response.as_bytes().to_vec(),
+            read: 0,
+        }
+    }
+}
+
+impl ErrorType for FakeStream {
+    type Error = Infallible;
+}
+
+impl Read for FakeStream {
+    async fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Self::Error> {
+        let length = buffer.len().min(self.response.len() - self.read);
+        buffer[..length].copy_from_slice(&self.response[self.read..self.read + length]);
+        self.read += length;
+        Ok(length)
+    }
+}
+
+impl Write for FakeStream {
+    async fn write(&mut self, buffer: &[u8]) -> Result<usize, Self::Error> {
+        self.written.extend_from_slice(buffer);
+        Ok(buffer.len())
+    }
+}
@@ -0,0 +1,133 @@
+use alloc::collections::BTreeMap;
+use core::cell::RefCell;
+use core::marker::PhantomData;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+use embedded_storage::nor_flash::{ErrorType, NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash};
+
+use crate::constants::storage::StorageConstant;
+
+// Stand-ins for the esp-hal and esp-storage types the portable modules
+// hold, swapped in with `#[cfg(test)]` at their `use` lines so the host
+// build compiles everything but the radio, timers and peripherals
+
+// Sectors written so far, erased ones read back as 0xFF. Shared by every
+// `FlashStorage` like the real chip, so data outlives the handle as it
+// outlives a reboot; `erase_all` starts a test from a blank chip
+static FLASH: Mutex<CriticalSectionRawMutex, RefCell<BTreeMap<u32, [u8; StorageConstant::FLASH_SECTOR_SIZE]>>> =
+    Mutex::new(RefCell::new(BTreeMap::new()));
+
+const SECTOR: u32 = StorageConstant::FLASH_SECTOR_SIZE as u32;
+
+pub fn erase_all() {
+    FLASH.lock(|flash| flash.borrow_mut().clear());
+}
+
+#[derive(Debug)]
+pub struct FlashError;
+
+impl NorFlashError for FlashError {
+    fn kind(&self) -> NorFlashErrorKind {
+        NorFlashErrorKind::Other
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct FlashStorage;
+
+impl FlashStorage {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl ErrorType for FlashStorage {
+    type Error = FlashError;
+}
+
+impl ReadNorFlash for FlashStorage {
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        FLASH.lock(|flash| {
+            let flash = flash.borrow();
+            for (index, byte) in bytes.iter_mut().enumerate() {
+                let address = offset + index as u32;
+                *byte = flash.get(&(address / SECTOR)).map_or(0xFF, |sector| sector[(address % SECTOR) as usize]);
+            }
+        });
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        4 * 1024 * 1024
+    }
+}
+
+impl NorFlash for FlashStorage {
+    const WRITE_SIZE: usize = 1;
+    const ERASE_SIZE: usize = StorageConstant::FLASH_SECTOR_SIZE;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+        if from % SECTOR != 0 || to % SECTOR != 0 {
+            return Err(FlashError);
+        }
+        FLASH.lock(|flash| {
+            let mut flash = flash.borrow_mut();
+            for sector in from / SECTOR..to / SECTOR {
+                flash.remove(&sector);
+            }
+        });
+        Ok(())
+    }
+
+    // NOR flash only clears bits, as the real chip does
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+        FLASH.lock(|flash| {
+            let mut flash = flash.borrow_mut();
+            for (index, byte) in bytes.iter().enumerate() {
+                let address = offset + index as u32;
+                let sector = flash.entry(address / SECTOR).or_insert([0xFF; StorageConstant::FLASH_SECTOR_SIZE]);
+                sector[(address % SECTOR) as usize] &= byte;
+            }
+        });
+        Ok(())
+    }
+}
+
+// A data-ready or button line that is always ready
+pub struct Input<'d> {
+    _pin: PhantomData<&'d ()>,
+}
+
+impl Input<'_> {
+    pub async fn wait_for_low(&mut self) {}
+
+    pub async fn wait_for_high(&mut self) {}
+
+    pub fn is_low(&self) -> bool {
+        true
+    }
+}
+
+// A power switch or indicator, remembering its level
+#[derive(Debug, Default)]
+pub struct Output<'d> {
+    high: bool,
+    _pin: PhantomData<&'d ()>,
+}
+
+impl Output<'_> {
+    pub fn set_high(&mut self) {
+        self.high = true;
+    }
+
+    pub fn set_low(&mut self) {
+        self.high = false;
+    }
+
+    pub fn is_set_high(&self) -> bool {
+        self.high
+    }
+}
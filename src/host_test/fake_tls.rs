@@ -0,0 +1,27 @@
+use alloc::vec::Vec;
+
+use embassy_net::tcp::TcpSocket;
+
+use crate::enums::transport_error::TransportError;
+
+// `services::tls` without mbedtls: the transports compile against the same
+// calls and a "session" is the plain socket. Nothing on the host connects
+
+#[derive(Debug, Clone, Default)]
+pub struct TlsCredentials {
+    pub ca_chain: Option<Vec<u8>>,
+    pub client_cert: Option<Vec<u8>>,
+    pub client_key: Option<Vec<u8>>,
+}
+
+pub fn credentials() -> Option<TlsCredentials> {
+    None
+}
+
+pub async fn connect<'a>(
+    socket: TcpSocket<'a>,
+    _host: &str,
+    _credentials: &'a TlsCredentials,
+) -> Result<TcpSocket<'a>, TransportError> {
+    Ok(socket)
+}
@@ -0,0 +1,127 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use std::sync::Mutex;
+
+use littlefs2::fs::Filesystem;
+use littlefs2::path::PathBuf;
+
+use crate::abstractions::queue::IQueue;
+use crate::constants::storage::StorageConstant;
+use crate::host_test::fake_hal;
+use crate::services::flash_queue::FlashQueueService;
+use crate::utilities::flash_partition::{self, FlashPartition};
+
+// The fake flash is one chip shared by every test, as it is by every handle
+static CHIP: Mutex<()> = Mutex::new(());
+
+// 18 of these fill segment 0, in batches of 6, and the next 12 go to segment 1
+fn record(index: usize) -> Vec<u8> {
+    format!("{:0200}", index).into_bytes()
+}
+
+fn reopened() -> FlashQueueService {
+    FlashQueueService::new(
+        String::from("urn:test:queue"),
+        String::from("urn:test:device"),
+        String::from("urn:test:location"),
+    )
+    .unwrap()
+}
+
+fn filled(count: usize) -> FlashQueueService {
+    fake_hal::erase_all();
+    flash_partition::prepare(StorageConstant::QUEUE_PARTITION_OFFSET).unwrap();
+    let mut queue = reopened();
+    for index in 0..count {
+        queue.push(&record(index)).unwrap();
+    }
+    queue.flush().unwrap();
+    queue
+}
+
+fn on_flash(f: impl FnOnce(&Filesystem<FlashPartition>) -> littlefs2::io::Result<()>) {
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    Filesystem::mount_and_then(&mut storage, f).unwrap();
+}
+
+#[test]
+fn records_outlive_a_reboot_across_segments() {
+    let _chip = CHIP.lock().unwrap();
+    let mut queue = filled(30);
+    queue.pop_many(20).unwrap();
+    drop(queue);
+
+    let mut queue = reopened();
+    assert_eq!(queue.len(), 10);
+    assert_eq!(queue.peek().unwrap(), Some(record(20)));
+}
+
+#[test]
+fn used_up_head_segment_is_skipped() {
+    let _chip = CHIP.lock().unwrap();
+    let mut queue = filled(18);
+    queue.pop_many(18).unwrap();
+    // Segment 0 was the tail, so it stays until the next batch rolls over
+    for index in 18..24 {
+        queue.push(&record(index)).unwrap();
+    }
+    queue.flush().unwrap();
+    queue.push(&record(24)).unwrap();
+
+    assert_eq!(queue.peek().unwrap(), Some(record(18)));
+    queue.pop().unwrap();
+    assert_eq!(queue.peek().unwrap(), Some(record(19)));
+    queue.pop_many(5).unwrap();
+    assert_eq!(queue.peek().unwrap(), Some(record(24)));
+    queue.pop().unwrap();
+    assert_eq!(queue.len(), 0);
+}
+
+#[test]
+fn missing_head_segment_is_stepped_over() {
+    let _chip = CHIP.lock().unwrap();
+    drop(filled(30));
+    on_flash(|fs| fs.remove(&PathBuf::from("/queue/00000000")));
+
+    let mut queue = reopened();
+    assert_eq!(queue.len(), 12);
+    assert_eq!(queue.peek().unwrap(), Some(record(18)));
+}
+
+#[test]
+fn segments_left_below_the_head_are_removed() {
+    let _chip = CHIP.lock().unwrap();
+    let mut queue = filled(30);
+    queue.pop_many(20).unwrap();
+    drop(queue);
+    on_flash(|fs| fs.write(&PathBuf::from("/queue/00000000"), &[0u8; 16]));
+
+    let mut queue = reopened();
+    assert_eq!(queue.len(), 10);
+    assert_eq!(queue.peek().unwrap(), Some(record(20)));
+    on_flash(|fs| {
+        assert!(fs.metadata(&PathBuf::from("/queue/00000000")).is_err());
+        Ok(())
+    });
+}
+
+#[test]
+fn cursor_written_before_versioning_is_read() {
+    let _chip = CHIP.lock().unwrap();
+    let mut queue = filled(30);
+    queue.pop_many(20).unwrap();
+    drop(queue);
+    // Older firmware wrote the bare 16 byte cursor
+    on_flash(|fs| {
+        let meta = PathBuf::from(StorageConstant::QUEUE_META_FILE);
+        let mut bytes = [0u8; 18];
+        let read = fs.open_file_and_then(&meta, |file| file.read(&mut bytes))?;
+        assert_eq!(read, 18);
+        fs.write(&meta, &bytes[2..])
+    });
+
+    let mut queue = reopened();
+    assert_eq!(queue.len(), 10);
+    assert_eq!(queue.peek().unwrap(), Some(record(20)));
+}
@@ -0,0 +1,91 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use embassy_futures::block_on;
+
+use crate::enums::compression::Compression;
+use crate::enums::transport_error::TransportError;
+use crate::host_test::fake_backend::FakeStream;
+use crate::services::http_client::HttpClientService;
+use crate::services::http_transport;
+
+const BODY: &[u8] = br#"{"id":"1-1","temperature":21.5}"#;
+
+fn request() -> Vec<u8> {
+    let client = HttpClientService::new(
+        String::from("urn:test:client"),
+        String::from("urn:test:device"),
+        String::from("urn:test:location"),
+        String::from("192.168.1.10:8080"),
+    );
+    client.create_encoded_post_request("/api/v1/measurements", BODY, Compression::None, &BTreeMap::new())
+}
+
+#[test]
+fn request_carries_identity_headers_and_body() {
+    let mut stream = FakeStream::new("HTTP/1.1 201 Created\r\n\r\n");
+    block_on(http_transport::exchange(&mut stream, &request(), BODY.len())).unwrap();
+    let written = core::str::from_utf8(&stream.written).unwrap();
+    assert!(written.starts_with("POST /api/v1/measurements HTTP/1.1\r\n"));
+    assert!(written.contains("X-Device-Urn: urn:test:device\r\n"));
+    assert!(written.contains("X-Location-Urn: urn:test:location\r\n"));
+    assert!(written.contains(&format!("Content-Length: {}\r\n", BODY.len())));
+    assert!(written.ends_with(core::str::from_utf8(BODY).unwrap()));
+}
+
+#[test]
+fn created_is_an_ack() {
+    let mut stream = FakeStream::new("HTTP/1.1 201 Created\r\n\r\n");
+    let ack = block_on(http_transport::exchange(&mut stream, &request(), BODY.len())).unwrap();
+    assert_eq!(ack.code, Some(201));
+    assert_eq!(ack.bytes, BODY.len());
+}
+
+#[test]
+fn conflict_is_rejected() {
+    let mut stream = FakeStream::new("HTTP/1.1 409 Conflict\r\n\r\n");
+    let error = block_on(http_transport::exchange(&mut stream, &request(), BODY.len())).unwrap_err();
+    assert_eq!(error, TransportError::Rejected(String::from("HTTP 409")));
+    assert!(!error.is_retryable());
+}
+
+#[test]
+fn too_many_requests_is_retried_after_the_delay() {
+    let mut stream = FakeStream::new("HTTP/1.1 429 Too Many Requests\r\nRetry-After: 120\r\n\r\n");
+    let error = block_on(http_transport::exchange(&mut stream, &request(), BODY.len())).unwrap_err();
+    assert_eq!(error, TransportError::Throttled(Some(120)));
+    assert!(error.is_retryable());
+}
+
+#[test]
+fn request_timeout_is_retried() {
+    let mut stream = FakeStream::new("HTTP/1.1 408 Request Timeout\r\n\r\n");
+    let error = block_on(http_transport::exchange(&mut stream, &request(), BODY.len())).unwrap_err();
+    assert_eq!(error, TransportError::Throttled(None));
+    assert!(error.is_retryable());
+}
+
+#[test]
+fn server_error_is_retried() {
+    let mut stream = FakeStream::new("HTTP/1.1 503 Service Unavailable\r\n\r\n");
+    let error = block_on(http_transport::exchange(&mut stream, &request(), BODY.len())).unwrap_err();
+    assert_eq!(error, TransportError::Io(String::from("HTTP 503")));
+    assert!(error.is_retryable());
+}
+
+#[test]
+fn client_error_is_rejected_for_good() {
+    let mut stream = FakeStream::new("HTTP/1.1 400 Bad Request\r\n\r\n");
+    let error = block_on(http_transport::exchange(&mut stream, &request(), BODY.len())).unwrap_err();
+    assert_eq!(error, TransportError::Rejected(String::from("HTTP 400")));
+    assert!(!error.is_retryable());
+}
+
+#[test]
+fn garbage_response_is_retried() {
+    let mut stream = FakeStream::new("SSH-2.0-OpenSSH\r\n");
+    let error = block_on(http_transport::exchange(&mut stream, &request(), BODY.len())).unwrap_err();
+    assert!(error.is_retryable());
+}
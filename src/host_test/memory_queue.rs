@@ -0,0 +1,74 @@
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+
+use crate::abstractions::queue::IQueue;
+use crate::dtos::configurations::retention::RetentionConfigDTO;
+
+// `IQueue` in RAM with the flash queue's semantics, minus the flash
+#[derive(Debug, Default)]
+pub struct MemoryQueue {
+    pub records: VecDeque<Vec<u8>>,
+}
+
+impl IQueue for MemoryQueue {
+    fn urn(&self) -> String {
+        String::from("urn:test:queue")
+    }
+
+    fn device_urn(&self) -> String {
+        String::from("urn:test:device")
+    }
+
+    fn location_urn(&self) -> String {
+        String::from("urn:test:location")
+    }
+
+    fn push(&mut self, payload: &[u8]) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.records.push_back(payload.to_vec());
+        Ok(())
+    }
+
+    fn peek(&mut self) -> Result<Option<Vec<u8>>, Box<dyn Error + Send + Sync>> {
+        Ok(self.records.front().cloned())
+    }
+
+    fn pop(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.records.pop_front();
+        Ok(())
+    }
+
+    fn peek_many(&mut self, skip: usize, max_records: usize, max_bytes: usize) -> Result<Vec<Vec<u8>>, Box<dyn Error + Send + Sync>> {
+        let mut records = Vec::new();
+        let mut bytes = 0;
+        for record in self.records.iter().skip(skip).take(max_records) {
+            if !records.is_empty() && bytes + record.len() > max_bytes {
+                break;
+            }
+            bytes += record.len();
+            records.push(record.clone());
+        }
+        Ok(records)
+    }
+
+    fn pop_many(&mut self, count: usize) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.records.drain(..count.min(self.records.len()));
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        Ok(())
+    }
+
+    fn compact(&mut self, retention: &RetentionConfigDTO, _now: u64) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let excess = retention.max_records.map_or(0, |max| self.records.len().saturating_sub(max as usize));
+        self.pop_many(excess)?;
+        Ok(excess)
+    }
+}
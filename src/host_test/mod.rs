@@ -0,0 +1,16 @@
+// Networking logic on the host, without a board or a network: the uploader,
+// the batcher, retries and serializers run against `FakeBackend`, the HTTP
+// exchange against `FakeStream`, and tests assert the exact bytes a server
+// would have received; `serializers` pins every wire format to the goldens
+// in goldens/, and the flash queue runs on LittleFS over a fake chip.
+// Built for `cargo test --features host-test`
+pub mod fake_backend;
+pub mod fake_hal;
+pub mod fake_tls;
+pub mod memory_queue;
+
+mod batcher;
+mod flash_queue;
+mod http_transport;
+mod serializers;
+mod uploader;
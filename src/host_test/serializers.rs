@@ -0,0 +1,127 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::dtos::measurement::field_quality::FieldQualityDTO;
+use crate::dtos::measurement::unit::UnitDTO;
+use crate::enums::quality::Quality;
+use crate::enums::value::Value;
+use crate::utilities::{cbor, csv, json, line_protocol, senml};
+
+// Payloads the backend parses today. A failing comparison here is a wire
+// format change: update the golden only together with the backend. Run with
+// UPDATE_GOLDENS=1 to rewrite every golden from the encoders, then review
+// the diff before committing it
+fn assert_golden(name: &str, encoded: &str) {
+    let path = format!("{}/src/host_test/goldens/{}", env!("CARGO_MANIFEST_DIR"), name);
+    if std::env::var_os("UPDATE_GOLDENS").is_some() {
+        std::fs::write(&path, format!("{}\n", encoded.trim_end())).unwrap();
+        return;
+    }
+    let golden = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(encoded.trim_end(), golden.trim_end(), "{} no longer matches", name);
+}
+
+// Two float fields with units, every field GOOD
+fn basic() -> MeasurementEnvelopeDTO {
+    let mut data = BTreeMap::new();
+    data.insert(String::from("temperature"), Value::Float(21.25));
+    data.insert(String::from("humidity"), Value::Float(48.5));
+    let mut units = BTreeMap::new();
+    units.insert(String::from("temperature"), UnitDTO { unit: "°C", si_scale: None });
+    units.insert(String::from("humidity"), UnitDTO { unit: "%RH", si_scale: None });
+    MeasurementEnvelopeDTO {
+        id: String::from("42-7"),
+        device_urn: String::from("urn:esp32:device:001"),
+        location_urn: String::from("urn:site:lab:1"),
+        timestamp: 1_700_000_000,
+        relative_ms_since_boot: None,
+        data: data,
+        quality: BTreeMap::new(),
+        units: units,
+    }
+}
+
+// Every value variant, a string needing escapes, a non-SI unit, a flagged
+// field with its raw reading, and a stamp taken before the clock was set
+fn flagged() -> MeasurementEnvelopeDTO {
+    let mut data = BTreeMap::new();
+    data.insert(String::from("distance_mm"), Value::Integer(1234));
+    data.insert(String::from("door"), Value::Boolean(true));
+    data.insert(String::from("label"), Value::String(String::from("bay \"A\", north")));
+    let mut units = BTreeMap::new();
+    units.insert(String::from("distance_mm"), UnitDTO { unit: "mm", si_scale: Some(0.001) });
+    let mut quality = BTreeMap::new();
+    quality.insert(
+        String::from("distance_mm"),
+        FieldQualityDTO {
+            quality: Quality::OutOfRange,
+            raw: Some(Value::Integer(9999)),
+        },
+    );
+    MeasurementEnvelopeDTO {
+        id: String::from("42-8"),
+        device_urn: String::from("urn:esp32:device:001"),
+        location_urn: String::from("urn:site:lab:1"),
+        timestamp: 0,
+        relative_ms_since_boot: Some(1500),
+        data: data,
+        quality: quality,
+        units: units,
+    }
+}
+
+#[test]
+fn json_matches_golden() {
+    assert_golden("basic.json", &json::envelope_to_json(&basic()));
+    assert_golden("flagged.json", &json::envelope_to_json(&flagged()));
+}
+
+#[test]
+fn json_batch_matches_golden() {
+    assert_golden("batch.json", &json::envelopes_to_json_array(&[basic(), flagged()]));
+}
+
+// Stored as hex so a diff stays readable
+#[test]
+fn cbor_matches_golden() {
+    let hex = |bytes: Vec<u8>| bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+    assert_golden("basic.cbor.hex", &hex(cbor::envelope_to_cbor(&basic())));
+    assert_golden("flagged.cbor.hex", &hex(cbor::envelope_to_cbor(&flagged())));
+}
+
+// What the LoRa uplink sends for a JSON batch
+#[test]
+fn json_transcodes_to_cbor() {
+    let hex = |bytes: Vec<u8>| bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+    let batch = json::envelopes_to_json_array(&[basic(), flagged()]);
+    assert_golden("batch.cbor.hex", &hex(cbor::from_json(batch.as_bytes()).unwrap()));
+    let escaped = cbor::from_json(br#" "a\n\u00e9\ud83d\ude00" "#).unwrap();
+    assert_eq!(hex(escaped), "68610ac3a9f09f9880");
+    assert_eq!(cbor::from_json(b"[1, 2] 3"), None);
+    assert_eq!(cbor::from_json(b"{\"a\": }"), None);
+}
+
+// Goldens are stored with LF line ends, the SD log writes CRLF
+#[test]
+fn csv_matches_golden() {
+    for (name, envelope) in [("basic.csv", basic()), ("flagged.csv", flagged())] {
+        let rows = csv::envelope_to_rows(&envelope);
+        assert_eq!(rows.matches("\r\n").count(), envelope.data.len());
+        assert_golden(name, &rows.replace("\r\n", "\n"));
+    }
+}
+
+#[test]
+fn line_protocol_matches_golden() {
+    assert_golden("basic.line", &line_protocol::envelope_to_line(&basic()));
+    assert_golden("flagged.line", &line_protocol::envelope_to_line(&flagged()));
+}
+
+#[test]
+fn senml_matches_golden() {
+    assert_golden("basic.senml.json", &senml::envelope_to_senml(&basic()));
+    assert_golden("flagged.senml.json", &senml::envelope_to_senml(&flagged()));
+}
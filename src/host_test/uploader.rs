@@ -0,0 +1,192 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use embassy_futures::block_on;
+
+use crate::abstractions::queue::IQueue;
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::enums::payload_kind::PayloadKind;
+use crate::enums::priority::Priority;
+use crate::enums::transport_error::TransportError;
+use crate::enums::value::Value;
+use crate::host_test::fake_backend::FakeBackend;
+use crate::host_test::memory_queue::MemoryQueue;
+use crate::services::uploader::{self, UploaderService};
+use crate::utilities::json;
+
+fn envelope(id: &str, temperature: f32) -> MeasurementEnvelopeDTO {
+    let mut data = BTreeMap::new();
+    data.insert(String::from("temperature"), Value::Float(temperature));
+    MeasurementEnvelopeDTO {
+        id: String::from(id),
+        device_urn: String::from("urn:test:device"),
+        location_urn: String::from("urn:test:location"),
+        timestamp: 1_700_000_000,
+        relative_ms_since_boot: None,
+        data: data,
+        quality: BTreeMap::new(),
+        units: BTreeMap::new(),
+    }
+}
+
+fn uploader(backend: FakeBackend) -> UploaderService<FakeBackend> {
+    UploaderService::new(
+        String::from("urn:test:uploader"),
+        String::from("urn:test:device"),
+        String::from("urn:test:location"),
+        vec![backend],
+    )
+}
+
+#[test]
+fn single_upload_is_the_envelope_json() {
+    let (backend, server) = FakeBackend::new("urn:test:backend");
+    let mut uploader = uploader(backend);
+    let first = envelope("1-1", 21.5);
+    assert_eq!(block_on(uploader.upload(&first, Priority::Periodic)), 1);
+    let server = server.borrow();
+    assert_eq!(server.requests.len(), 1);
+    assert_eq!(server.requests[0].kind, PayloadKind::Measurement);
+    assert_eq!(server.requests[0].text(), json::envelope_to_json(&first));
+}
+
+#[test]
+fn batch_is_one_json_array() {
+    let (backend, server) = FakeBackend::new("urn:test:backend");
+    let mut uploader = uploader(backend);
+    let batch = [envelope("1-1", 21.5), envelope("1-2", 21.75)];
+    block_on(uploader.upload_batch(&batch, Priority::Periodic));
+    let server = server.borrow();
+    assert_eq!(server.requests.len(), 1);
+    assert_eq!(server.requests[0].text(), json::envelopes_to_json_array(&batch));
+}
+
+#[test]
+fn retryable_failure_resends_the_same_body_first() {
+    let (backend, server) = FakeBackend::new("urn:test:backend");
+    server.borrow_mut().responses.push_back(Err(TransportError::Timeout));
+    let mut uploader = uploader(backend);
+    let (first, second) = (envelope("1-1", 21.5), envelope("1-2", 21.75));
+    assert_eq!(block_on(uploader.upload(&first, Priority::Periodic)), 0);
+    assert_eq!(block_on(uploader.upload(&second, Priority::Periodic)), 1);
+
+    let bodies: Vec<String> = server.borrow().requests.iter().map(|request| String::from(request.text())).collect();
+    let (first, second) = (json::envelope_to_json(&first), json::envelope_to_json(&second));
+    assert_eq!(bodies, [first.clone(), first, second]);
+    let metrics = uploader.metrics()[0].1;
+    assert_eq!((metrics.successes, metrics.failures), (2, 1));
+}
+
+#[test]
+fn rejected_payload_is_not_resent() {
+    let (backend, server) = FakeBackend::new("urn:test:backend");
+    server.borrow_mut().responses.push_back(Err(TransportError::Rejected(String::from("HTTP 422"))));
+    let mut uploader = uploader(backend);
+    let (first, second) = (envelope("1-1", 21.5), envelope("1-2", 21.75));
+    block_on(uploader.upload(&first, Priority::Periodic));
+    assert_eq!(block_on(uploader.upload(&second, Priority::Periodic)), 1);
+
+    let server = server.borrow();
+    assert_eq!(server.requests.len(), 2);
+    assert_eq!(server.requests[1].text(), json::envelope_to_json(&second));
+}
+
+#[test]
+fn forward_pops_only_what_was_acknowledged() {
+    let (backend, server) = FakeBackend::new("urn:test:backend");
+    server.borrow_mut().responses.push_back(Err(TransportError::Timeout));
+    let mut uploader = uploader(backend);
+    let mut queue = MemoryQueue::default();
+    let envelopes = [envelope("1-1", 21.5), envelope("1-2", 21.75), envelope("1-3", 22.0)];
+    for envelope in envelopes.iter() {
+        uploader.persist(&mut queue, envelope).unwrap();
+    }
+
+    // A timeout is ambiguous, the chunk stays queued and goes again
+    assert_eq!(block_on(uploader.forward(&mut queue, 0)).unwrap(), 0);
+    assert_eq!(queue.len(), 3);
+    assert_eq!(block_on(uploader.forward(&mut queue, 0)).unwrap(), 3);
+    assert_eq!(queue.len(), 0);
+
+    let server = server.borrow();
+    let array = format!(
+        "[{},{},{}]",
+        json::envelope_to_json(&envelopes[0]),
+        json::envelope_to_json(&envelopes[1]),
+        json::envelope_to_json(&envelopes[2])
+    );
+    assert_eq!(server.requests.len(), 2);
+    assert_eq!(server.requests[0].body, server.requests[1].body);
+    assert_eq!(server.requests[1].text(), array);
+}
+
+#[test]
+fn forward_pops_up_to_the_server_cursor() {
+    let (backend, server) = FakeBackend::new("urn:test:backend");
+    server.borrow_mut().responses.push_back(Ok(Some(String::from("1-2"))));
+    let mut uploader = uploader(backend);
+    let mut queue = MemoryQueue::default();
+    let envelopes = [envelope("1-1", 21.5), envelope("1-2", 21.75), envelope("1-3", 22.0)];
+    for envelope in envelopes.iter() {
+        uploader.persist(&mut queue, envelope).unwrap();
+    }
+
+    // The server stored two of the three, only the third goes again
+    assert_eq!(block_on(uploader.forward(&mut queue, 0)).unwrap(), 2);
+    assert_eq!(queue.len(), 1);
+    assert_eq!(block_on(uploader.forward(&mut queue, 0)).unwrap(), 1);
+
+    let server = server.borrow();
+    assert_eq!(server.requests.len(), 2);
+    assert_eq!(server.requests[1].text(), json::envelope_to_json(&envelopes[2]));
+}
+
+#[test]
+fn forward_uploads_an_envelope_relayed_by_the_gateway() {
+    let (backend, server) = FakeBackend::new("urn:test:backend");
+    let mut uploader = uploader(backend);
+    let mut queue = MemoryQueue::default();
+    let relayed = json::envelope_to_json(&envelope("7-1", 19.25));
+    uploader::persist_relayed(&mut queue, relayed.as_bytes()).unwrap();
+
+    assert_eq!(block_on(uploader.forward(&mut queue, 0)).unwrap(), 1);
+    assert_eq!(queue.len(), 0);
+    let server = server.borrow();
+    assert_eq!(server.requests.len(), 1);
+    assert_eq!(server.requests[0].text(), relayed);
+}
+
+#[test]
+fn forward_keeps_going_to_a_target_while_another_is_down() {
+    let (healthy, healthy_server) = FakeBackend::new("urn:test:healthy");
+    let (down, down_server) = FakeBackend::new("urn:test:down");
+    for _ in 0..3 {
+        down_server.borrow_mut().responses.push_back(Err(TransportError::Unavailable));
+    }
+    let mut uploader = UploaderService::new(
+        String::from("urn:test:uploader"),
+        String::from("urn:test:device"),
+        String::from("urn:test:location"),
+        vec![healthy, down],
+    );
+    let mut queue = MemoryQueue::default();
+    let (first, second) = (envelope("1-1", 21.5), envelope("1-2", 21.75));
+    uploader.persist(&mut queue, &first).unwrap();
+    assert_eq!(block_on(uploader.forward(&mut queue, 0)).unwrap(), 1);
+    uploader.persist(&mut queue, &second).unwrap();
+    assert_eq!(block_on(uploader.forward(&mut queue, 0)).unwrap(), 1);
+
+    // Each record reached the healthy target once, and both wait for the other
+    let bodies: Vec<String> = healthy_server.borrow().requests.iter().map(|request| String::from(request.text())).collect();
+    assert_eq!(bodies, [json::envelope_to_json(&first), json::envelope_to_json(&second)]);
+    assert_eq!(queue.len(), 2);
+
+    // Once the other comes back it catches up and the queue empties
+    assert_eq!(block_on(uploader.forward(&mut queue, 0)).unwrap(), 0);
+    assert_eq!(block_on(uploader.forward(&mut queue, 0)).unwrap(), 2);
+    assert_eq!(queue.len(), 0);
+    assert_eq!(healthy_server.borrow().requests.len(), 2);
+}
@@ -0,0 +1,40 @@
+use alloc::string::String;
+
+use embassy_time::{with_timeout, Duration, Timer};
+use esp_hal::gpio::Input;
+use log::debug;
+
+use crate::constants::input::InputConstant;
+use crate::dtos::event::input::InputEventDTO;
+use crate::enums::input_kind::InputKind;
+use crate::inputs;
+
+// One push button to ground, the pin configured with its pull-up. Spawn one
+// per configured button. A press is only taken once the pin stayed low for
+// `DEBOUNCE_MS`, and a long press is reported while the button is still
+// held so the menu can react without waiting for the release
+pub async fn run(name: String, mut pin: Input<'static>) -> ! {
+    loop {
+        pin.wait_for_low().await;
+        Timer::after(Duration::from_millis(InputConstant::DEBOUNCE_MS)).await;
+        if pin.is_high() {
+            continue;
+        }
+
+        let held = Duration::from_millis(InputConstant::LONG_PRESS_MS - InputConstant::DEBOUNCE_MS);
+        let kind = match with_timeout(held, pin.wait_for_high()).await {
+            Ok(()) => InputKind::Pressed,
+            Err(_) => InputKind::LongPressed,
+        };
+        debug!("Button {} {}", name, kind.as_str());
+        inputs::publish(InputEventDTO {
+            source: name.clone(),
+            kind: kind,
+            steps: 0,
+        });
+
+        // Bounce on release must not start another press
+        pin.wait_for_high().await;
+        Timer::after(Duration::from_millis(InputConstant::DEBOUNCE_MS)).await;
+    }
+}
@@ -0,0 +1,66 @@
+use alloc::string::String;
+
+use embassy_time::{Duration, Timer};
+use esp_hal::gpio::interconnect::InputSignal;
+use esp_hal::gpio::Input;
+use esp_hal::pcnt::channel::{CtrlMode, EdgeMode};
+use esp_hal::pcnt::unit::Unit;
+use log::debug;
+
+use crate::constants::input::InputConstant;
+use crate::dtos::event::input::InputEventDTO;
+use crate::enums::input_kind::InputKind;
+use crate::inputs;
+
+// Sets a PCNT unit up as a full quadrature decoder: both channels count
+// both edges, direction taken from the other phase. A and B need their
+// pull-ups for the usual mechanical encoders switching to ground
+pub fn configure<const NUM: usize>(unit: &Unit<'static, NUM>, a: Input<'static>, b: Input<'static>) {
+    let _ = unit.set_low_limit(Some(-InputConstant::ENCODER_LIMIT));
+    let _ = unit.set_high_limit(Some(InputConstant::ENCODER_LIMIT));
+    let _ = unit.set_filter(Some(InputConstant::ENCODER_FILTER_CYCLES));
+    unit.clear();
+
+    // Each phase feeds both channels; the signals keep the pins' pull-ups
+    let a = InputSignal::from(a);
+    let b = InputSignal::from(b);
+    let channel = &unit.channel0;
+    channel.set_ctrl_signal(a.clone());
+    channel.set_edge_signal(b.clone());
+    channel.set_ctrl_mode(CtrlMode::Reverse, CtrlMode::Keep);
+    channel.set_input_mode(EdgeMode::Increment, EdgeMode::Decrement);
+    let channel = &unit.channel1;
+    channel.set_ctrl_signal(b);
+    channel.set_edge_signal(a);
+    channel.set_ctrl_mode(CtrlMode::Reverse, CtrlMode::Keep);
+    channel.set_input_mode(EdgeMode::Decrement, EdgeMode::Increment);
+    unit.resume();
+}
+
+// Turns the counter into whole detents; counts short of a detent carry over
+// to the next poll so slow turning is not lost
+pub async fn run<const NUM: usize>(name: String, unit: Unit<'static, NUM>) -> ! {
+    let mut carry: i16 = 0;
+    loop {
+        Timer::after(Duration::from_millis(InputConstant::ENCODER_POLL_MS)).await;
+        // A count landing between the read and the clear is lost, at most a
+        // quarter detent
+        let counts = unit.value();
+        if counts == 0 {
+            continue;
+        }
+        unit.clear();
+        let total = carry + counts;
+        let steps = total / InputConstant::COUNTS_PER_DETENT;
+        carry = total % InputConstant::COUNTS_PER_DETENT;
+        if steps == 0 {
+            continue;
+        }
+        debug!("Encoder {} rotated {}", name, steps);
+        inputs::publish(InputEventDTO {
+            source: name.clone(),
+            kind: InputKind::Rotated,
+            steps: steps as i32,
+        });
+    }
+}
@@ -0,0 +1,37 @@
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::pubsub::{PubSubChannel, Subscriber};
+
+use crate::constants::input::InputConstant;
+use crate::dtos::event::input::InputEventDTO;
+
+pub mod button;
+pub mod encoder;
+
+pub type InputSubscriber = Subscriber<
+    'static,
+    CriticalSectionRawMutex,
+    InputEventDTO,
+    { InputConstant::QUEUE_DEPTH },
+    { InputConstant::MAX_SUBSCRIBERS },
+    1,
+>;
+
+// Buttons and encoders publish here; the display menu and the rules engine
+// each subscribe, so neither needs to know which pins exist
+static INPUT_CHANNEL: PubSubChannel<
+    CriticalSectionRawMutex,
+    InputEventDTO,
+    { InputConstant::QUEUE_DEPTH },
+    { InputConstant::MAX_SUBSCRIBERS },
+    1,
+> = PubSubChannel::new();
+
+// A subscriber that falls behind loses the oldest events, never blocks input
+pub fn publish(event: InputEventDTO) {
+    INPUT_CHANNEL.immediate_publisher().publish_immediate(event);
+}
+
+// None once `MAX_SUBSCRIBERS` are taken
+pub fn subscribe() -> Option<InputSubscriber> {
+    INPUT_CHANNEL.subscriber().ok()
+}
@@ -1,19 +1,195 @@
-#![no_std]
-#![no_main]
+#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(test), no_main)]
 #![deny(
     clippy::mem_forget,
     reason = "mem::forget is generally not safe to do with esp_hal types, especially those \
     holding buffers for the duration of a data transfer."
 )]
 
+#[cfg(not(test))]
 use embassy_executor::Spawner;
-use embassy_time::{Duration, Timer};
+#[cfg(not(test))]
+use embassy_time::{with_timeout, Duration, Instant, Timer};
+#[cfg(not(test))]
+use alloc::boxed::Box;
+#[cfg(not(test))]
+use alloc::format;
+#[cfg(not(test))]
+use core::error::Error;
+#[cfg(not(test))]
+use alloc::string::{String, ToString};
+#[cfg(not(test))]
+use alloc::vec::Vec;
+#[cfg(not(test))]
+use esp_hal::analog::adc::{Adc, AdcConfig, Attenuation};
+#[cfg(not(test))]
 use esp_hal::clock::CpuClock;
+#[cfg(not(test))]
+use esp_hal::delay::Delay;
+#[cfg(not(test))]
+use esp_hal::gpio::{AnyPin, Input, InputConfig, Level, Output, OutputConfig};
+#[cfg(not(test))]
+use esp_hal::i2c::master::{Config as I2cConfig, I2c};
+#[cfg(not(test))]
+use esp_hal::peripherals::{ADC1, GPIO36};
+#[cfg(not(test))]
+use esp_hal::system::CpuControl;
+#[cfg(not(test))]
 use esp_hal::timer::timg::TimerGroup;
-use log::{info, debug, warn, error};
+#[cfg(not(test))]
+use esp_hal::uart::{Config as UartConfig, Uart};
+#[cfg(not(test))]
+use esp_hal::Async;
+#[cfg(not(test))]
+use esp_hal::efuse::Efuse;
+#[cfg(not(test))]
+use esp_hal::peripherals::WIFI;
+#[cfg(not(test))]
+use esp_hal::rng::Rng;
+#[cfg(not(test))]
+use esp_hal::spi::master::{Config as SpiConfig, Spi};
+#[cfg(not(test))]
+use esp_hal::timer::timg::Timer as TimgTimer;
+#[cfg(not(test))]
+use embassy_net::tcp::TcpSocket;
+#[cfg(not(test))]
+use embassy_net::{Runner, Stack, StackResources};
+#[cfg(not(test))]
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+#[cfg(not(test))]
+use embassy_sync::mutex::Mutex;
+#[cfg(not(test))]
+use embedded_io_async::Write;
+#[cfg(not(test))]
+use embassy_sync::channel::Channel;
+#[cfg(not(test))]
+use esp_mbedtls::Tls;
+#[cfg(not(test))]
+use esp_wifi::wifi::WifiDevice;
+#[cfg(not(test))]
+use esp_wifi::EspWifiController;
+#[cfg(feature = "defmt")]
+use defmt_rtt as _;
+#[cfg(not(test))]
+use static_cell::StaticCell;
 
+#[cfg(not(test))]
+use crate::utilities::logging::{info, debug, warn, error};
+#[cfg(not(test))]
+use crate::utilities::device_info;
+#[cfg(not(test))]
+use crate::config::Config;
+#[cfg(not(test))]
+use crate::constants::hardware::HardwareConstant;
+#[cfg(not(test))]
+use crate::configurations::sensors::SensorsConfig;
+#[cfg(not(test))]
+use crate::constants::sensor::SensorConstant;
+#[cfg(not(test))]
+use crate::drivers::eeprom_24cxx::Eeprom24Cxx;
+#[cfg(not(test))]
+use crate::drivers::i2c_bus::{I2cBus, I2cBusDevice};
+#[cfg(not(test))]
+use crate::drivers::i2s_microphone::{self, I2sMicrophone};
+#[cfg(not(test))]
+use crate::configurations::pin_map::PinMap;
+#[cfg(not(test))]
+use crate::factories::pipeline::PipelineFactory;
+#[cfg(not(test))]
+use crate::factories::sensor::SensorFactory;
+#[cfg(not(test))]
+use crate::sensors::amg8833::AMG8833Sensor;
+#[cfg(not(test))]
+use crate::sensors::bh1750::BH1750Sensor;
+#[cfg(not(test))]
+use crate::sensors::bme280::BME280Sensor;
+#[cfg(not(test))]
+use crate::sensors::ds323x::DS323XSensor;
+#[cfg(not(test))]
+use crate::sensors::lsm303dlhc::accel::LSM303DLHCAccelSensor;
+#[cfg(not(test))]
+use crate::sensors::vl53l0x::{self, VL53L0XSensor};
+#[cfg(not(test))]
+use crate::services::audio_events::AudioEventService;
+#[cfg(not(test))]
+use crate::services::cli::CliService;
+#[cfg(not(test))]
+use crate::services::config_validator::ConfigValidator;
+#[cfg(not(test))]
+use crate::services::mfg_test::MfgTestService;
+#[cfg(not(test))]
+use crate::enums::board_revision::BoardRevision;
+#[cfg(not(test))]
+use crate::enums::boot_stage::BootStage;
+#[cfg(not(test))]
+use crate::services::{board_identity, boot, clock, hardware_profile, heartbeat, live_stream, metrics, offload, soak, status_led, upload_stats};
+#[cfg(not(test))]
+use crate::services::event_outbox;
+#[cfg(not(test))]
+use crate::services::sensing_client::SensingClientService;
+#[cfg(not(test))]
+use crate::services::supervisor::ServiceSupervisor;
+#[cfg(not(test))]
+use crate::services::batcher::{BatchItem, BatcherService};
+#[cfg(not(test))]
+use crate::services::http_transport::HttpTransportService;
+#[cfg(not(test))]
+use crate::services::mqtt_transport::MqttTransportService;
+#[cfg(not(test))]
+use crate::services::network_manager::{self, NetworkManagerService};
+#[cfg(not(test))]
+use crate::services::secret_store::SecretStoreService;
+#[cfg(not(test))]
+use crate::services::sensing_cycle::SensingCycleService;
+#[cfg(not(test))]
+use crate::services::scheduler::SchedulerService;
+#[cfg(not(test))]
+use crate::services::tls;
+#[cfg(not(test))]
+use crate::services::ota::OtaService;
+#[cfg(not(test))]
+use crate::services::uploader::UploaderService;
+#[cfg(not(test))]
+use crate::services::uplink_chain::{IpTransport, UplinkChain};
+#[cfg(not(test))]
+use crate::services::wifi_manager::{self, WifiManagerService};
+#[cfg(not(test))]
+use crate::constants::upload::UploadConstant;
+#[cfg(not(test))]
+use crate::constants::ota::OtaConstant;
+#[cfg(not(test))]
+use crate::constants::supervisor::SupervisorConstant;
+#[cfg(not(test))]
+use crate::constants::wifi::WifiConstant;
+#[cfg(not(test))]
+use crate::drivers::spi_bus::{SpiBus, SpiBusDevice};
+#[cfg(not(test))]
+use crate::abstractions::store::IStore;
+#[cfg(not(test))]
+use crate::constants::http::HttpConstant;
+#[cfg(not(test))]
+use crate::constants::network::NetworkConstant;
+#[cfg(not(test))]
+use crate::enums::connection_state::ConnectionState;
+#[cfg(not(test))]
+use crate::enums::store_mode::StoreMode;
+#[cfg(not(test))]
+use crate::services::http_server::HttpServerService;
+#[cfg(not(test))]
+use crate::services::sd_logger::SdCardLoggerService;
+#[cfg(not(test))]
+use crate::drivers::w5500;
+#[cfg(not(test))]
+use crate::enums::network_interface::NetworkInterface;
+#[cfg(not(test))]
+use crate::enums::uplink::Uplink;
+
+#[cfg(not(test))]
 #[panic_handler]
 fn panic(info: &core::panic::PanicInfo) -> ! {
+    #[cfg(feature = "defmt")]
+    error!("PANIC: {}", defmt::Display2Format(info));
+    #[cfg(not(feature = "defmt"))]
     error!("PANIC: {:?}", info);
     loop {}
 }
@@ -24,27 +200,41 @@ pub mod abstractions;
 pub mod config;
 pub mod configurations;
 pub mod constants;
+pub mod drivers;
 pub mod dtos;
 pub mod enums;
 pub mod factories;
+#[cfg(not(test))]
+pub mod inputs;
 pub mod pipelines;
-pub mod  sensors;
+pub mod sensors;
 pub mod services;
 pub mod utilities;
 
+#[cfg(all(test, feature = "host-test"))]
+mod host_test;
+#[cfg(all(test, not(feature = "host-test")))]
+compile_error!("the host build needs `--features host-test`, see Cargo.toml");
+
 // This creates a default app-descriptor required by the esp-idf bootloader.
 // For more information see: <https://docs.espressif.com/projects/esp-idf/en/stable/esp32/api-reference/system/app_image_format.html#application-description>
+#[cfg(not(test))]
 esp_bootloader_esp_idf::esp_app_desc!();
 
+#[cfg(not(test))]
 #[esp_hal_embassy::main]
 async fn main(spawner: Spawner) {
     // generator version: 0.5.0
 
-    // Initialize the logger
-    esp_println::logger::init_logger_from_env();
+    // Before anything deepens the stack, for the high-water mark in the metrics
+    utilities::stack::paint();
+
+    // Initialize the logger for `log` records; with defmt it forwards them
+    // to RTT, which is set up on first use
+    services::log_filter::init();
     debug!("Starting ESP32 application initialization...");
     
-    debug!("Logger ready (using log + esp_println)");
+    debug!("Logger ready");
 
     let config = esp_hal::Config::default().with_cpu_clock(CpuClock::max());
     debug!("ESP-HAL config created with max CPU clock");
@@ -52,9 +242,22 @@ async fn main(spawner: Spawner) {
     let peripherals = esp_hal::init(config);
     debug!("ESP-HAL peripherals initialized");
 
+    // Added before the internal heap so general allocations land in PSRAM
+    // when there is some, leaving internal RAM to the Wi-Fi stack
+    #[cfg(feature = "psram")]
+    services::memory::init_psram(&peripherals.PSRAM);
+
     esp_alloc::heap_allocator!(size: 64 * 1024);
     debug!("Heap allocator configured with 64KB");
 
+    // The only place the shared data partition gets formatted, before any
+    // service mounts it
+    match utilities::flash_partition::prepare(constants::storage::StorageConstant::QUEUE_PARTITION_OFFSET) {
+        Ok(true) => warn!("Data partition was blank or corrupted and has been formatted"),
+        Ok(false) => debug!("Data partition mounted"),
+        Err(_) => error!("Data partition could not be formatted, persistence is unavailable"),
+    }
+
     let timer0 = TimerGroup::new(peripherals.TIMG1);
     debug!("Timer group TIMG1 created");
     
@@ -64,24 +267,868 @@ async fn main(spawner: Spawner) {
     info!("Embassy initialized!");
     debug!("Application startup complete, entering main loop");
 
-    // TODO: Spawn some tasks
-    let _ = spawner;
-    debug!("Spawner ready (no tasks spawned yet)");
+    // One async controller shared by every sensor on the default bus, the
+    // pins are the same on every board revision
+    static I2C_BUS: StaticCell<I2cBus> = StaticCell::new();
+    let i2c = I2c::new(peripherals.I2C0, I2cConfig::default())
+        .expect("I2C0 accepts the default configuration")
+        .with_sda(peripherals.GPIO21)
+        .with_scl(peripherals.GPIO22)
+        .into_async();
+    let i2c_bus: &'static I2cBus = I2C_BUS.init(I2cBus::new(String::from(SensorConstant::DEFAULT_BUS), i2c));
+
+    // The config and the sensor factory take their defaults from the
+    // detected revision, so this comes before either
+    let strap_raw = read_revision_strap(peripherals.ADC1, peripherals.GPIO36);
+    hardware_profile::detect(&mut i2c_bus.device(), strap_raw);
+    // Kept for the console, which programs it on the production line
+    let mut eeprom = Eeprom24Cxx::new_24c02(i2c_bus.device(), Delay::new(), HardwareConstant::EEPROM_ADDRESS);
+    if hardware_profile::revision() == BoardRevision::D {
+        board_identity::load(&mut eeprom);
+    }
+
+    let mut config = Config::new();
+    let mut sensors = SensorsConfig::new();
+    // Reads how far the previous boot got, then records CONFIG_LOADED
+    if let Err(error) = boot::init() {
+        log::error!("Boot stage unavailable: {}", error);
+    }
+    // A running soak test counts this boot's reset and takes over the
+    // intervals and the sensor list
+    soak::boot(&metrics::reset_reason());
+    soak::apply(&mut config, &mut sensors);
+    heartbeat::init(config.device_urn.clone(), config.location_urn.clone(), device_info::collect());
+
+    // Checked before any driver takes a pin, a conflict is reported and
+    // leaves the configured pins unused instead of panicking in esp-hal
+    let validator = ConfigValidator::new(
+        format!("{}:config_validator", config.device_urn),
+        config.device_urn.clone(),
+        config.location_urn.clone(),
+        SUPPORTED_SENSORS.iter().map(|sensor| String::from(*sensor)).collect(),
+    );
+    let pins = match validator.pins(&config, &sensors) {
+        Ok(pins) => Some(pins),
+        Err(event) => {
+            error!("Pin assignments conflict, configured GPIOs are left unused");
+            event_outbox::raise(event);
+            None
+        },
+    };
+
+    let console = Uart::new(peripherals.UART0, UartConfig::default())
+        .expect("UART0 accepts the default configuration")
+        .with_tx(peripherals.GPIO1)
+        .with_rx(peripherals.GPIO3)
+        .into_async();
+    let cli = CliService::new(
+        format!("{}:cli", config.device_urn),
+        config.device_urn.clone(),
+        config.location_urn.clone(),
+        eeprom,
+    );
+    spawner.must_spawn(cli_task(cli, console));
+
+    boot::advance(BootStage::BusesUp);
+
+    let strap = Input::new(peripherals.GPIO35, InputConfig::default());
+    if MfgTestService::requested(strap.is_low()) {
+        run_mfg_test(i2c_bus, &config, &sensors, pins.as_ref()).await;
+    }
+    // Left to the production line test above when it runs, which blinks it
+    // itself; the pin map has vouched for the pin, see `ready_pin`
+    if let Some(gpio) = config.status_led_gpio.filter(|_| pins.is_some()) {
+        let led = Output::new(unsafe { AnyPin::steal(gpio) }, Level::Low, OutputConfig::default());
+        spawner.must_spawn(status_led_task(led));
+    }
+
+    static SUPERVISOR: StaticCell<ServiceSupervisor> = StaticCell::new();
+    let supervisor: &'static ServiceSupervisor = SUPERVISOR.init(ServiceSupervisor::new(
+        format!("{}:supervisor", config.device_urn),
+        config.device_urn.clone(),
+        config.location_urn.clone(),
+    ));
+    let factory = bring_up_sensors(i2c_bus, &config, &sensors, pins.as_ref());
+    if factory.store.is_empty() && !sensors.include.is_empty() {
+        boot::fail("no included sensor answered");
+    } else {
+        boot::advance(BootStage::SensorsOk);
+    }
+    // The clock's own handle on the RTC, the one in the factory reads it as
+    // a sensor. Seeded before the first envelope is stamped
+    let rtc = sensors
+        .include
+        .iter()
+        .any(|sensor| sensor.eq_ignore_ascii_case(SensorConstant::DS3231SN))
+        .then(|| {
+            DS323XSensor::new(
+                format!("{}:clock:rtc", config.device_urn),
+                config.device_urn.clone(),
+                config.location_urn.clone(),
+                String::from(SensorConstant::DS3231SN),
+                i2c_bus.device(),
+            )
+        });
+    if let Some(rtc) = rtc.as_ref() {
+        clock::seed(rtc);
+    }
+    let sensing = SensingClientService::new(
+        format!("{}:sensing", config.device_urn),
+        config.device_urn.clone(),
+        config.location_urn.clone(),
+        sensors.to_dto(),
+        factory,
+    );
+
+    // Shared by the W5500 and the SD card, each behind its own chip select
+    static SPI_BUS: StaticCell<SpiBus> = StaticCell::new();
+    let spi = Spi::new(peripherals.SPI3, SpiConfig::default())
+        .expect("SPI3 accepts the default configuration")
+        .with_sck(peripherals.GPIO18)
+        .with_miso(peripherals.GPIO19)
+        .with_mosi(peripherals.GPIO23)
+        .into_async();
+    let spi_bus: &'static SpiBus = SPI_BUS.init(SpiBus::new(String::from("vspi"), spi));
+
+    // Left alone without a chip select or with SD_STORE_MODE=disabled; the
+    // card is mounted on first use, so a missing one only fails its requests
+    static SD_STORE: StaticCell<SdStore> = StaticCell::new();
+    let sd_store: Option<&'static SdStore> = match config.sd_cs_gpio.filter(|_| pins.is_some()) {
+        Some(cs) if config.sd_store_mode != StoreMode::Disabled => Some(SD_STORE.init(Mutex::new(SdCardLoggerService::new(
+            format!("{}:sd_logger", config.device_urn),
+            config.device_urn.clone(),
+            config.location_urn.clone(),
+            spi_bus.device(Output::new(unsafe { AnyPin::steal(cs) }, Level::High, OutputConfig::default())),
+            Delay::new(),
+            config.sd_log_format,
+            config.sd_store_mode,
+            config.timezone.clone(),
+        )))),
+        _ => None,
+    };
+
+    let included: Vec<String> = sensors.include.iter().map(|sensor| sensor.to_lowercase()).collect();
+    let mut scheduler = SchedulerService::new(
+        format!("{}:scheduler", config.device_urn),
+        config.device_urn.clone(),
+        config.location_urn.clone(),
+        config.schedule.clone(),
+        config.timezone.clone(),
+        config.sensor_interval_ms,
+        sensors.warmup_ms.clone(),
+        included.clone(),
+    );
+    // Load switches only once the pin map has vouched for the GPIOs; a
+    // sensor without one has been on since `bring_up_sensors`, its warm-up
+    // runs from now
+    for sensor in included.iter() {
+        match sensors.power_gpios.get(sensor) {
+            Some(gpio) if pins.is_some() => scheduler.add_power_pin(
+                sensor,
+                Output::new(unsafe { AnyPin::steal(*gpio) }, Level::Low, OutputConfig::default()),
+            ),
+            _ => scheduler.powered_up(sensor),
+        }
+    }
+    let cycle = SensingCycleService::new(
+        format!("{}:sensing_cycle", config.device_urn),
+        config.device_urn.clone(),
+        config.location_urn.clone(),
+        sensing,
+        scheduler,
+        PipelineFactory::new(
+            format!("{}:pipelines", config.device_urn),
+            config.device_urn.clone(),
+            config.location_urn.clone(),
+            sensors.pipelines.clone(),
+            sensors.units,
+            sensors.compensation.clone(),
+        ),
+        MEASUREMENTS.sender(),
+    );
+    // A run covers the longest warm-up and the reads, the wait in between is
+    // outside it
+    let warmup = Duration::from_millis(sensors.warmup_ms.values().copied().max().unwrap_or(0));
+    let timeout = warmup + Duration::from_millis(sensors.read_timeout_ms);
+    spawner.must_spawn(sensing_task(
+        supervisor,
+        cycle,
+        Duration::from_millis(config.sensor_interval_ms),
+        timeout,
+        sd_store,
+    ));
+    debug!("Sensing task spawned");
+
+    // The pin map claims the I2S pins once a detector is enabled, so a
+    // setting reusing one of them keeps the microphone off
+    if !config.audio_events.is_empty() && pins.is_some() {
+        if let Err(error) = offload::start(CpuControl::new(peripherals.CPU_CTRL)) {
+            log::error!("Offload core not started: {:?}", error);
+        }
+        match I2sMicrophone::new(
+            peripherals.I2S0,
+            peripherals.DMA_I2S0,
+            peripherals.GPIO26,
+            peripherals.GPIO25,
+            peripherals.GPIO33,
+        ) {
+            Ok(microphone) => {
+                let service = AudioEventService::new(
+                    format!("{}:audio_events", config.device_urn),
+                    config.device_urn.clone(),
+                    config.location_urn.clone(),
+                    config.audio_events.clone(),
+                );
+                spawner.must_spawn(i2s_microphone::audio_task(microphone, service));
+            },
+            Err(error) => log::error!("Microphone not started, audio events disabled: {:?}", error),
+        }
+    }
+
+    let mut rng = Rng::new(peripherals.RNG);
+    let mut secrets = match SecretStoreService::new(peripherals.AES, rng) {
+        Ok(secrets) => Some(secrets),
+        Err(error) => {
+            log::error!("Secret store unavailable: {}", error);
+            None
+        },
+    };
+    if let Some(secrets) = secrets.as_mut() {
+        static TLS: StaticCell<Tls<'static>> = StaticCell::new();
+        match Tls::new(peripherals.SHA) {
+            Ok(tls) => tls::init(TLS.init(tls.with_hardware_rsa(peripherals.RSA)).reference(), secrets),
+            Err(error) => log::error!("TLS unavailable, https targets will fail: {:?}", error),
+        }
+    }
+
+    // Network manager, then the transports over it, then the uploader and
+    // the batcher the sensing cycle feeds
+    let mut network = NetworkManagerService::new(
+        format!("{}:network", config.device_urn),
+        config.device_urn.clone(),
+        config.location_urn.clone(),
+        config.network_interfaces.clone(),
+    );
+    let seed = (rng.random() as u64) << 32 | rng.random() as u64;
+    if config.network_interfaces.contains(&NetworkInterface::Wifi) {
+        let timer = TimerGroup::new(peripherals.TIMG0);
+        match bring_up_wifi(spawner, supervisor, timer.timer0, rng, peripherals.WIFI, &config, secrets.as_mut(), seed) {
+            Ok(stack) => network.attach(NetworkInterface::Wifi, stack),
+            Err(error) => log::error!("Wi-Fi not started: {}", error),
+        }
+    }
+    if config.network_interfaces.contains(&NetworkInterface::Ethernet) {
+        match ethernet_pins(&config, pins.as_ref()) {
+            Some((cs, interrupt, reset)) => {
+                // The ESP32 reserves the base MAC + 3 for Ethernet
+                let mut mac = Efuse::mac_address();
+                mac[5] = mac[5].wrapping_add(3);
+                match w5500::init(
+                    spi_bus.device(cs),
+                    interrupt,
+                    reset,
+                    mac,
+                    seed.rotate_left(32),
+                    config.static_ipv6.as_ref(),
+                    config.static_ipv4.as_ref(),
+                )
+                .await
+                {
+                    Ok((stack, runner, net_runner)) => {
+                        spawner.must_spawn(w5500::ethernet_task(runner));
+                        spawner.must_spawn(w5500::ethernet_net_task(net_runner));
+                        network.attach(NetworkInterface::Ethernet, stack);
+                    },
+                    Err(error) => log::error!("W5500 not started: {:?}", error),
+                }
+            },
+            None => warn!("No W5500 pins configured, Ethernet left out"),
+        }
+    }
+
+    for stack in network.stacks() {
+        let server = HttpServerService::new(
+            format!("{}:http_server", config.device_urn),
+            config.device_urn.clone(),
+            config.location_urn.clone(),
+        );
+        spawner.must_spawn(http_task(stack, server, sd_store));
+    }
+
+    let uploader = UploaderService::new(
+        format!("{}:uploader", config.device_urn),
+        config.device_urn.clone(),
+        config.location_urn.clone(),
+        build_transports(&config, &network),
+    );
+    let batcher = BatcherService::new(
+        format!("{}:batcher", config.device_urn),
+        config.device_urn.clone(),
+        config.location_urn.clone(),
+        config.batch_max_records,
+        Duration::from_secs(config.batch_max_age_s),
+    );
+    spawner.must_spawn(upload_task(batcher, uploader));
+
+    // Behind the uploads: a new image is confirmed by its first successful
+    // upload, and firmware comes from the same server over the same network
+    match (config.ota_public_key, secrets) {
+        (Some(public_key), Some(secrets)) => match OtaService::new(
+            format!("{}:ota", config.device_urn),
+            config.device_urn.clone(),
+            config.location_urn.clone(),
+            network.clone(),
+            &config.server_base_url,
+            public_key,
+            config.ota_security_version,
+            config.ota_pinned_version,
+        ) {
+            Some(mut ota) => {
+                if let Err(error) = ota.check_boot() {
+                    log::error!("OTA state unreadable: {}", error);
+                }
+                spawner.must_spawn(ota_task(ota, secrets));
+            },
+            None => error!("OTA not started, SEVER_BASE_URL or OTA_PUBLIC_KEY is invalid"),
+        },
+        (Some(_), None) => error!("OTA not started, the version floor needs the secret store"),
+        (None, _) => info!("No OTA_PUBLIC_KEY, firmware updates disabled"),
+    }
+
+    let offline = !network.has_interface();
+    if !offline {
+        spawner.must_spawn(clock_task(network.clone(), config.sntp_server.clone(), rtc));
+    }
 
-    let mut loop_count = 0;
     loop {
-        loop_count += 1;
-        debug!("Main loop iteration: {}", loop_count);
-        
-        info!("Hello world!");
-        
-        if loop_count % 10 == 0 {
-            warn!("Main loop has been running for {} iterations", loop_count);
-        }
-        
+        // NET_UP and TIME_SYNCED are recorded by the network manager and the
+        // clock as they happen. An offline logger is running once it reads,
+        // a connected device once its clock is synced
+        if offline || clock::is_set() {
+            boot::advance(BootStage::Running);
+        }
+        heartbeat::update(|heartbeat| supervisor.report(heartbeat));
+
+        // The soak test's clock, its end restarts into the normal settings
+        if soak::poll(Instant::now()) {
+            esp_hal::system::software_reset();
+        }
+
         Timer::after(Duration::from_secs(1)).await;
-        debug!("Timer delay completed, continuing loop");
     }
 
     // for inspiration have a look at the examples at https://github.com/esp-rs/esp-hal/tree/esp-hal-v1.0.0-rc.0/examples/src/bin
 }
+
+// Production line test in place of the normal boot: the bus scan, one read
+// of every sensor the revision carries, the indicators blinking for the
+// operator, then the matrix and the stored result. The device then idles
+// with the console up, for the line station to program the identity
+#[cfg(not(test))]
+async fn run_mfg_test(i2c_bus: &'static I2cBus, config: &Config, sensors: &SensorsConfig, pins: Option<&PinMap>) -> ! {
+    info!("Entering MFG_TEST");
+    let mut test = MfgTestService::new();
+    test.check_i2c(SensorConstant::DEFAULT_BUS, &mut i2c_bus.device(), config);
+
+    let fitted = hardware_profile::profile().sensors;
+    let mut everything = sensors.clone();
+    everything.include = fitted.iter().map(|sensor| String::from(*sensor)).collect();
+    let factory = bring_up_sensors(i2c_bus, config, &everything, pins);
+    let timeout = Duration::from_millis(sensors.read_timeout_ms);
+    for sensor in fitted.iter() {
+        let prefix = format!("{}.", sensor);
+        let mut units = factory.store.iter().filter(|(key, _)| key == sensor || key.starts_with(&prefix)).peekable();
+        if units.peek().is_none() {
+            test.check(&format!("sensor.{}", sensor), Err::<String, _>("not brought up"));
+        }
+        for (key, unit) in units {
+            let result = match with_timeout(timeout, unit.read_async()).await {
+                Ok(Ok(values)) => Ok(format!("{} values", values.len())),
+                Ok(Err(error)) => Err(error.to_string()),
+                Err(_) => Err(String::from("timed out")),
+            };
+            test.check(&format!("sensor.{}", key), result);
+        }
+    }
+
+    // The pin map has vouched for the indicator's pin, see `ready_pin`
+    let mut indicators: alloc::vec::Vec<Output<'static>> = config
+        .status_led_gpio
+        .filter(|_| pins.is_some())
+        .map(|gpio| Output::new(unsafe { AnyPin::steal(gpio) }, Level::Low, OutputConfig::default()))
+        .into_iter()
+        .collect();
+    test.blink(&mut indicators).await;
+
+    test.print();
+    let mut eeprom = Eeprom24Cxx::new_24c02(i2c_bus.device(), Delay::new(), HardwareConstant::EEPROM_ADDRESS);
+    let saved = if hardware_profile::revision() == BoardRevision::D {
+        test.save_eeprom(&mut eeprom)
+    } else {
+        test.save_flash()
+    };
+    if let Err(error) = saved {
+        log::error!("MFG_TEST result not stored: {}", error);
+    }
+    loop {
+        Timer::after(Duration::from_secs(3600)).await;
+    }
+}
+
+// One-shot reading of the rev C strap divider for `hardware_profile::detect`
+#[cfg(not(test))]
+fn read_revision_strap(adc: ADC1<'static>, pin: GPIO36<'static>) -> Option<u16> {
+    let mut adc_config = AdcConfig::new();
+    let mut strap = adc_config.enable_pin(pin, Attenuation::_11dB);
+    let mut adc = Adc::new(adc, adc_config);
+    nb::block!(adc.read_oneshot(&mut strap)).ok()
+}
+
+// The SD card, appended to by the sensing task and read by /export on
+// every interface
+#[cfg(not(test))]
+type SdStore = Mutex<CriticalSectionRawMutex, SdCardLoggerService<SpiBusDevice<'static>, Delay>>;
+
+// Envelopes from the sensing cycle on their way to the batcher
+#[cfg(not(test))]
+static MEASUREMENTS: Channel<CriticalSectionRawMutex, BatchItem, { UploadConstant::MEASUREMENT_CHANNEL_DEPTH }> =
+    Channel::new();
+
+// Sensors `bring_up_sensors` has a driver for
+#[cfg(not(test))]
+const SUPPORTED_SENSORS: [&str; 6] = [
+    SensorConstant::AMG8833,
+    SensorConstant::BH1750,
+    SensorConstant::BME280,
+    SensorConstant::DS3231SN,
+    SensorConstant::LSM303DLHACCEL,
+    SensorConstant::VL5310X,
+];
+
+// Drivers for the included sensors on the shared bus, each on a handle of
+// its own. A part that does not answer is logged and left out, the others
+// still report. Without a conflict-free pin map the configured GPIOs stay
+// untouched: a VL53L0X array is not brought up and every sensor is polled
+// Driver errors only implement Debug, so they go out on the `log` macros
+#[cfg(not(test))]
+fn bring_up_sensors(
+    i2c_bus: &'static I2cBus,
+    config: &Config,
+    sensors: &SensorsConfig,
+    pins: Option<&PinMap>,
+) -> SensorFactory {
+    let mut factory = SensorFactory::new(
+        format!("{}:sensors", config.device_urn),
+        config.device_urn.clone(),
+        config.location_urn.clone(),
+    );
+    let included = |key: &str| sensors.include.iter().any(|sensor| sensor.eq_ignore_ascii_case(key));
+    let urn = |key: &str| format!("{}:{}", config.device_urn, key);
+    let location_urn = |key: &str| sensors.location_urn(key, &config.location_urn);
+    let device = || -> I2cBusDevice<'static> { i2c_bus.device() };
+
+    if included(SensorConstant::BME280) {
+        match BME280Sensor::new(
+            urn(SensorConstant::BME280),
+            config.device_urn.clone(),
+            location_urn(SensorConstant::BME280),
+            String::from(SensorConstant::BME280),
+            device(),
+            Delay::new(),
+            config.bme280.clone(),
+            sensors.temperature_thresholds.clone(),
+            sensors.humidity_thresholds.clone(),
+        ) {
+            Ok(sensor) => factory.insert(SensorConstant::BME280, sensor),
+            Err(error) => log::error!("BME280 bring-up failed: {:?}", error),
+        }
+    }
+    if included(SensorConstant::BH1750) {
+        match BH1750Sensor::new(
+            urn(SensorConstant::BH1750),
+            config.device_urn.clone(),
+            location_urn(SensorConstant::BH1750),
+            String::from(SensorConstant::BH1750),
+            device(),
+            Delay::new(),
+            config.bh1750.clone(),
+            sensors.lux_thresholds.clone(),
+        ) {
+            Ok(sensor) => factory.insert(SensorConstant::BH1750, sensor),
+            Err(error) => log::error!("BH1750 bring-up failed: {:?}", error),
+        }
+    }
+    if included(SensorConstant::DS3231SN) {
+        let sensor = DS323XSensor::new(
+            urn(SensorConstant::DS3231SN),
+            config.device_urn.clone(),
+            location_urn(SensorConstant::DS3231SN),
+            String::from(SensorConstant::DS3231SN),
+            device(),
+        );
+        factory.insert(SensorConstant::DS3231SN, sensor);
+    }
+    if included(SensorConstant::AMG8833) {
+        match AMG8833Sensor::new(
+            urn(SensorConstant::AMG8833),
+            config.device_urn.clone(),
+            location_urn(SensorConstant::AMG8833),
+            String::from(SensorConstant::AMG8833),
+            device(),
+            &mut Delay::new(),
+        ) {
+            Ok(sensor) => factory.insert(SensorConstant::AMG8833, sensor),
+            Err(error) => log::error!("AMG8833 bring-up failed: {:?}", error),
+        }
+    }
+    if included(SensorConstant::LSM303DLHACCEL) {
+        match LSM303DLHCAccelSensor::new(
+            urn(SensorConstant::LSM303DLHACCEL),
+            config.device_urn.clone(),
+            location_urn(SensorConstant::LSM303DLHACCEL),
+            String::from(SensorConstant::LSM303DLHACCEL),
+            device(),
+        ) {
+            Ok(mut sensor) => {
+                if let Some(pin) = pins.and_then(|_| ready_pin(sensors, SensorConstant::LSM303DLHACCEL)) {
+                    if let Err(error) = sensor.set_ready_pin(pin) {
+                        log::warn!("LSM303DLHC data ready not enabled, polling: {:?}", error);
+                    }
+                }
+                factory.insert(SensorConstant::LSM303DLHACCEL, sensor);
+            },
+            Err(error) => log::error!("LSM303DLHC bring-up failed: {:?}", error),
+        }
+    }
+    if included(SensorConstant::VL5310X) && config.vl53l0x_xshut_gpios.is_empty() {
+        match VL53L0XSensor::new(
+            urn(SensorConstant::VL5310X),
+            config.device_urn.clone(),
+            location_urn(SensorConstant::VL5310X),
+            String::from(SensorConstant::VL5310X),
+            device(),
+            sensors.distance_thresholds_mm.clone(),
+        ) {
+            Ok(mut sensor) => {
+                if let Some(pin) = pins.and_then(|_| ready_pin(sensors, SensorConstant::VL5310X)) {
+                    sensor.set_ready_pin(pin);
+                }
+                factory.insert(SensorConstant::VL5310X, sensor);
+            },
+            Err(error) => log::error!("VL53L0X bring-up failed: {:?}", error),
+        }
+    } else if included(SensorConstant::VL5310X) && pins.is_some() {
+        // Held for the life of the firmware, releasing a pin resets its unit
+        let xshut: &'static mut [Output<'static>] = config
+            .vl53l0x_xshut_gpios
+            .iter()
+            .map(|gpio| Output::new(unsafe { AnyPin::steal(*gpio) }, Level::Low, OutputConfig::default()))
+            .collect::<alloc::vec::Vec<_>>()
+            .leak();
+        match VL53L0XSensor::bring_up(
+            &urn(SensorConstant::VL5310X),
+            &config.device_urn,
+            &location_urn(SensorConstant::VL5310X),
+            xshut.iter().map(|_| device()).collect(),
+            xshut,
+            &mut Delay::new(),
+            &sensors.distance_thresholds_mm,
+        ) {
+            Ok(mut units) => {
+                for (index, unit) in units.iter_mut().enumerate() {
+                    if let Some(pin) = ready_pin(sensors, &vl53l0x::key(index)) {
+                        unit.set_ready_pin(pin);
+                    }
+                }
+                factory.insert_vl53l0x(units);
+            },
+            Err(error) => log::error!("VL53L0X array bring-up failed: {:?}", error),
+        }
+    }
+    info!("{} sensors brought up", factory.store.len());
+    factory
+}
+
+// The sensor's data ready line from `SensorsConfigDTO::ready_gpios`. Only
+// called once the pin map has found every configured GPIO with one owner,
+// so the pin conjured from its number is not held by any other driver
+#[cfg(not(test))]
+fn ready_pin(sensors: &SensorsConfig, key: &str) -> Option<Input<'static>> {
+    let gpio = *sensors.ready_gpios.get(key)?;
+    Some(Input::new(unsafe { AnyPin::steal(gpio) }, InputConfig::default()))
+}
+
+#[cfg(not(test))]
+#[embassy_executor::task]
+async fn cli_task(mut cli: CliService<I2cBusDevice<'static>, Delay>, mut console: Uart<'static, Async>) {
+    match cli.run(&mut console).await {
+        // A command that takes effect on the next boot, e.g. "mfg_test"
+        Ok(()) => esp_hal::system::software_reset(),
+        Err(error) => log::error!("Serial console stopped: {}", error),
+    }
+}
+
+// The local endpoints on one interface, a connection at a time. A /live
+// client keeps its connection, and the interface's server, until it leaves
+#[cfg(not(test))]
+#[embassy_executor::task(pool_size = 2)]
+async fn http_task(stack: Stack<'static>, server: HttpServerService, store: Option<&'static SdStore>) -> ! {
+    let mut rx_buffer = [0u8; HttpConstant::SOCKET_BUFFER_BYTES];
+    let mut tx_buffer = [0u8; HttpConstant::SOCKET_BUFFER_BYTES];
+    loop {
+        let mut socket = TcpSocket::new(stack, &mut rx_buffer, &mut tx_buffer);
+        socket.set_timeout(Some(Duration::from_millis(HttpConstant::SOCKET_TIMEOUT_MS)));
+        if let Err(error) = socket.accept(HttpConstant::SERVER_PORT).await {
+            log::warn!("HTTP accept failed: {:?}", error);
+            continue;
+        }
+        match serve_http(&mut socket, &server, store).await {
+            Ok(ConnectionState::WebSocket) => live_stream::serve(&mut socket).await,
+            Ok(ConnectionState::Close) => {},
+            Err(error) => log::warn!("HTTP request failed: {}", error),
+        }
+        socket.close();
+        let _ = socket.flush().await;
+    }
+}
+
+// Reads one request, lets the server answer it into memory and writes the
+// answer out; the SD card is only held while the answer is built
+#[cfg(not(test))]
+async fn serve_http(
+    socket: &mut TcpSocket<'_>,
+    server: &HttpServerService,
+    store: Option<&'static SdStore>,
+) -> Result<ConnectionState, Box<dyn Error + Send + Sync>> {
+    let mut request = [0u8; HttpConstant::SOCKET_BUFFER_BYTES];
+    let mut read = 0;
+    while !request[..read].windows(4).any(|window| window == b"\r\n\r\n") {
+        if read == request.len() {
+            return Err(Box::from("Request headers too large"));
+        }
+        match socket.read(&mut request[read..]).await {
+            Ok(0) => return Err(Box::from("Connection closed before the request was complete")),
+            Ok(count) => read += count,
+            Err(error) => return Err(Box::from(format!("Read failed: {:?}", error))),
+        }
+    }
+
+    let health = heartbeat::current().ok_or("Heartbeat not initialised")?;
+    let mut response = String::new();
+    let mut sink = |text: &str| -> Result<(), Box<dyn Error + Send + Sync>> {
+        if response.len() + text.len() > HttpConstant::RESPONSE_MAX_BYTES {
+            return Err(Box::from("Response too large"));
+        }
+        response.push_str(text);
+        Ok(())
+    };
+    let handled = match store {
+        Some(store) => {
+            let mut store = store.lock().await;
+            server.handle(&request[..read], Some(&mut *store as &mut dyn IStore), &health, &mut sink)
+        },
+        None => server.handle(&request[..read], None, &health, &mut sink),
+    };
+    // What was built before a failure still goes out, e.g. an export cut
+    // short, whose missing last chunk tells the client
+    socket
+        .write_all(response.as_bytes())
+        .await
+        .map_err(|error| format!("Write failed: {:?}", error))?;
+    handled
+}
+
+// A pending image is confirmed once any transport has completed an upload
+// and rolled back when its self-test window runs out first. Updates are only
+// looked for on a confirmed image, and an activated one is rebooted into
+#[cfg(not(test))]
+#[embassy_executor::task]
+async fn ota_task(mut ota: OtaService, mut secrets: SecretStoreService) -> ! {
+    let mut next_check = Instant::now();
+    loop {
+        if ota.is_pending() {
+            let uploaded = upload_stats::snapshot().iter().any(|target| target.uploads > 0);
+            let result = if uploaded { ota.confirm(&mut secrets) } else { ota.poll_self_test() };
+            if let Err(error) = result {
+                log::warn!("OTA self-test check failed: {}", error);
+            }
+        } else if Instant::now() >= next_check {
+            let interval_s = match ota.update(&mut secrets).await {
+                Ok(true) => esp_hal::system::software_reset(),
+                Ok(false) => OtaConstant::CHECK_INTERVAL_S,
+                Err(error) => {
+                    log::warn!("Firmware update failed: {}", error);
+                    OtaConstant::RETRY_INTERVAL_S
+                },
+            };
+            next_check = Instant::now() + Duration::from_secs(interval_s);
+        }
+        Timer::after(Duration::from_secs(OtaConstant::POLL_INTERVAL_S)).await;
+    }
+}
+
+#[cfg(not(test))]
+#[embassy_executor::task]
+async fn status_led_task(led: Output<'static>) -> ! {
+    status_led::run(led).await
+}
+
+#[cfg(not(test))]
+#[embassy_executor::task]
+async fn clock_task(
+    mut network: NetworkManagerService,
+    server: String,
+    rtc: Option<DS323XSensor<I2cBusDevice<'static>>>,
+) -> ! {
+    clock::run(&mut network, &server, rtc.as_ref()).await
+}
+
+#[cfg(not(test))]
+#[embassy_executor::task]
+async fn sensing_task(
+    supervisor: &'static ServiceSupervisor,
+    mut cycle: SensingCycleService,
+    interval: Duration,
+    timeout: Duration,
+    store: Option<&'static SdStore>,
+) -> ! {
+    let mut backoff_ms = SupervisorConstant::INITIAL_BACKOFF_MS;
+    loop {
+        cycle.wait(interval).await;
+        let envelopes = match supervisor.run_once("sensing", &mut cycle, timeout, &mut backoff_ms).await {
+            Some(envelopes) => envelopes,
+            None => continue,
+        };
+        // Primary or backup, the card gets every envelope either way
+        if let Some(store) = store {
+            let mut store = store.lock().await;
+            for envelope in envelopes.iter() {
+                if let Err(error) = store.append(envelope) {
+                    log::warn!("SD append of {} failed: {}", envelope.id, error);
+                }
+            }
+        }
+    }
+}
+
+// esp-wifi on TIMG0, the station interface handed to embassy-net and the
+// connection kept up by `WifiManagerService` under the supervisor
+#[cfg(not(test))]
+fn bring_up_wifi(
+    spawner: Spawner,
+    supervisor: &'static ServiceSupervisor,
+    timer: TimgTimer<'static>,
+    rng: Rng,
+    wifi: WIFI<'static>,
+    config: &Config,
+    secrets: Option<&mut SecretStoreService>,
+    seed: u64,
+) -> Result<Stack<'static>, Box<dyn Error + Send + Sync>> {
+    static CONTROLLER: StaticCell<EspWifiController<'static>> = StaticCell::new();
+    static RESOURCES: StaticCell<StackResources<{ NetworkConstant::STACK_SOCKETS }>> = StaticCell::new();
+
+    let controller = esp_wifi::init(timer, rng).map_err(|error| format!("esp-wifi init failed: {:?}", error))?;
+    let (controller, interfaces) = esp_wifi::wifi::new(CONTROLLER.init(controller), wifi)
+        .map_err(|error| format!("Wi-Fi driver failed: {:?}", error))?;
+    let (stack, runner) = embassy_net::new(
+        interfaces.sta,
+        network_manager::stack_config(config.static_ipv6.as_ref(), config.static_ipv4.as_ref()),
+        RESOURCES.init(StackResources::new()),
+        seed,
+    );
+    let certificates = secrets.map(wifi_manager::load_certificates).unwrap_or_default();
+    let manager = WifiManagerService::new(
+        format!("{}:wifi", config.device_urn),
+        config.device_urn.clone(),
+        config.location_urn.clone(),
+        controller,
+        config.wifi_profiles.clone(),
+        certificates,
+        config.wifi_roam_rssi_dbm,
+    );
+    spawner.must_spawn(wifi_net_task(runner));
+    spawner.must_spawn(wifi_task(supervisor, manager));
+    Ok(stack)
+}
+
+// The W5500's chip select, interrupt and reset, once the pin map has vouched
+// for them like for `ready_pin`
+#[cfg(not(test))]
+fn ethernet_pins(config: &Config, pins: Option<&PinMap>) -> Option<(Output<'static>, Input<'static>, Output<'static>)> {
+    pins?;
+    let (cs, interrupt, reset) = (config.ethernet_cs_gpio?, config.ethernet_int_gpio?, config.ethernet_reset_gpio?);
+    Some((
+        Output::new(unsafe { AnyPin::steal(cs) }, Level::High, OutputConfig::default()),
+        Input::new(unsafe { AnyPin::steal(interrupt) }, InputConfig::default()),
+        Output::new(unsafe { AnyPin::steal(reset) }, Level::High, OutputConfig::default()),
+    ))
+}
+
+// One target per `Config.upload_targets`, each reached over the uplinks in
+// `Config.uplinks` order. The first target is the primary server, MQTT
+// stands in for it alone so the broker does not get every envelope twice.
+// Radio uplinks with drivers of their own are not started here
+#[cfg(not(test))]
+fn build_transports(config: &Config, network: &NetworkManagerService) -> alloc::vec::Vec<UplinkChain> {
+    for uplink in config.uplinks.iter() {
+        if !matches!(uplink, Uplink::Wifi | Uplink::Mqtt) {
+            log::warn!("{:?} uplink is not started from main, skipped", uplink);
+        }
+    }
+    let mut transports = alloc::vec::Vec::new();
+    for (index, target) in config.upload_targets.iter().enumerate() {
+        let urn = format!("{}:upload:{}", config.device_urn, index);
+        let links = config
+            .uplinks
+            .iter()
+            .filter_map(|uplink| match uplink {
+                Uplink::Wifi => HttpTransportService::new(
+                    urn.clone(),
+                    config.device_urn.clone(),
+                    config.location_urn.clone(),
+                    network.clone(),
+                    target,
+                    config.http_compression,
+                )
+                .map(IpTransport::Http),
+                Uplink::Mqtt if index == 0 => config.mqtt_broker_url.as_deref().and_then(|broker_url| {
+                    MqttTransportService::new(
+                        format!("{}:mqtt", urn),
+                        config.device_urn.clone(),
+                        config.location_urn.clone(),
+                        network.clone(),
+                        broker_url,
+                        config.mqtt_username.clone(),
+                        config.mqtt_password.clone(),
+                    )
+                    .map(IpTransport::Mqtt)
+                }),
+                _ => None,
+            })
+            .collect();
+        match UplinkChain::new(links) {
+            Some(chain) => transports.push(chain),
+            None => warn!("Upload target {} has no usable uplink, skipped", target.as_str()),
+        }
+    }
+    transports
+}
+
+#[cfg(not(test))]
+#[embassy_executor::task]
+async fn wifi_task(supervisor: &'static ServiceSupervisor, mut manager: WifiManagerService) -> ! {
+    let period = Duration::from_secs(WifiConstant::MAINTAIN_INTERVAL_S);
+    supervisor.supervise("wifi", &mut manager, period, Duration::from_secs(WifiConstant::MAINTAIN_TIMEOUT_S)).await
+}
+
+#[cfg(not(test))]
+#[embassy_executor::task]
+async fn wifi_net_task(mut runner: Runner<'static, WifiDevice<'static>>) -> ! {
+    runner.run().await
+}
+
+#[cfg(not(test))]
+#[embassy_executor::task]
+async fn upload_task(mut batcher: BatcherService, mut uploader: UploaderService<UplinkChain>) -> ! {
+    batcher.run(MEASUREMENTS.receiver(), &mut uploader).await
+}
@@ -0,0 +1,86 @@
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::format;
+use alloc::string::String;
+
+use log::debug;
+
+use crate::abstractions::pipeline::IPipelineStage;
+use crate::constants::pipeline::PipelineConstant;
+use crate::dtos::measurement::field_quality::FieldQualityDTO;
+use crate::dtos::pipeline::output::PipelineOutputDTO;
+use crate::enums::event_kind::EventKind;
+use crate::enums::quality::Quality;
+use crate::enums::value::Value;
+
+struct Baseline {
+    samples: VecDeque<f32>,
+    anomalous: bool,
+}
+
+// "anomaly" or "anomaly(n)": marks numeric values more than n standard
+// deviations from a rolling per-field baseline as ANOMALOUS and raises an
+// anomaly event when a field turns anomalous. Unlike `outlier` the value is
+// kept, and it joins the baseline, so a lasting shift stops being flagged
+// once the window has caught up with it
+pub struct AnomalyStage {
+    deviations: f32,
+    baselines: BTreeMap<String, Baseline>,
+}
+
+impl AnomalyStage {
+    pub fn new(deviations: f32) -> Self {
+        Self {
+            deviations: deviations,
+            baselines: BTreeMap::new(),
+        }
+    }
+}
+
+impl IPipelineStage for AnomalyStage {
+    fn name(&self) -> String {
+        format!("{}({})", PipelineConstant::ANOMALY, self.deviations)
+    }
+
+    fn process(&mut self, output: &mut PipelineOutputDTO) {
+        for (field, value) in output.values.iter() {
+            let sample = match value.as_f32() {
+                Some(sample) => sample,
+                None => continue,
+            };
+            let baseline = self.baselines.entry(field.clone()).or_insert_with(|| Baseline {
+                samples: VecDeque::new(),
+                anomalous: false,
+            });
+            let count = baseline.samples.len();
+            let mut anomalous = false;
+            if count >= PipelineConstant::ANOMALY_MIN_SAMPLES {
+                let mean = baseline.samples.iter().sum::<f32>() / count as f32;
+                let floor = PipelineConstant::ANOMALY_MIN_DEVIATION
+                    .max(mean.abs() * PipelineConstant::ANOMALY_MIN_RELATIVE_DEVIATION);
+                let variance = (baseline.samples.iter().map(|sample| (sample - mean) * (sample - mean)).sum::<f32>()
+                    / count as f32)
+                    .max(floor * floor);
+                // Squared on both sides, there is no sqrt in core
+                anomalous = (sample - mean) * (sample - mean) > self.deviations * self.deviations * variance;
+                if anomalous && !baseline.anomalous {
+                    debug!("{} = {} is anomalous around {}", field, sample, mean);
+                    let mut detail = BTreeMap::new();
+                    detail.insert(String::from("field"), Value::String(field.clone()));
+                    detail.insert(String::from("value"), Value::Float(sample));
+                    detail.insert(String::from("baseline"), Value::Float(mean));
+                    detail.insert(String::from("variance"), Value::Float(variance));
+                    output.events.push((EventKind::Anomaly, detail));
+                }
+            }
+            if anomalous {
+                let raw = output.quality.get(field).and_then(|quality| quality.raw.clone());
+                output.quality.insert(field.clone(), FieldQualityDTO { quality: Quality::Anomalous, raw: raw });
+            }
+            baseline.anomalous = anomalous;
+            if count == PipelineConstant::ANOMALY_WINDOW {
+                baseline.samples.pop_front();
+            }
+            baseline.samples.push_back(sample);
+        }
+    }
+}
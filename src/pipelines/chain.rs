@@ -0,0 +1,98 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use crate::abstractions::pipeline::IPipelineStage;
+use crate::dtos::event::envelope::EventEnvelopeDTO;
+use crate::dtos::pipeline::output::PipelineOutputDTO;
+use crate::enums::priority::Priority;
+use crate::enums::value::Value;
+use crate::services::batcher::BatchItem;
+use crate::services::{clock, envelope, message_id};
+
+// The stages configured for one sensor, run in order between its read and
+// the batcher. An empty chain passes readings through unchanged
+pub struct PipelineChain {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    stages: RefCell<Vec<Box<dyn IPipelineStage>>>,
+}
+
+impl PipelineChain {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        stages: Vec<Box<dyn IPipelineStage>>,
+    ) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            stages: RefCell::new(stages),
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    pub fn stages(&self) -> Vec<String> {
+        self.stages.borrow().iter().map(|stage| stage.name()).collect()
+    }
+
+    pub fn process(&self, values: BTreeMap<String, Value>) -> PipelineOutputDTO {
+        let mut output = PipelineOutputDTO {
+            values: values,
+            quality: BTreeMap::new(),
+            priority: Priority::Periodic,
+            events: Vec::new(),
+        };
+        for stage in self.stages.borrow_mut().iter_mut() {
+            stage.process(&mut output);
+        }
+        output
+    }
+
+    // What goes on the measurement channel, None when every value was dropped
+    pub fn item(&self, output: &PipelineOutputDTO) -> Option<BatchItem> {
+        if output.values.is_empty() {
+            return None;
+        }
+        let mut envelope = envelope::measurement(&self.device_urn, &self.location_urn, output.values.clone());
+        envelope.quality = output.quality.clone();
+        Some(BatchItem {
+            envelope: envelope,
+            priority: output.priority,
+        })
+    }
+
+    pub fn events(&self, output: &PipelineOutputDTO) -> Vec<EventEnvelopeDTO> {
+        let (timestamp, relative_ms_since_boot) = clock::stamp();
+        output
+            .events
+            .iter()
+            .map(|(kind, detail)| EventEnvelopeDTO {
+                id: message_id::next(),
+                device_urn: self.device_urn.clone(),
+                location_urn: self.location_urn.clone(),
+                sensor_urn: self.urn.clone(),
+                timestamp: timestamp,
+                relative_ms_since_boot: relative_ms_since_boot,
+                kind: *kind,
+                detail: detail.clone(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,8 @@
+pub mod anomaly;
+pub mod chain;
+pub mod moving_average;
+pub mod outlier;
+pub mod rate_alert;
+pub mod temperature_compensation;
+pub mod threshold_alert;
+pub mod unit_conversion;
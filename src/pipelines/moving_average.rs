@@ -0,0 +1,56 @@
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::format;
+use alloc::string::String;
+
+use crate::abstractions::pipeline::IPipelineStage;
+use crate::dtos::measurement::field_quality::FieldQualityDTO;
+use crate::dtos::pipeline::output::PipelineOutputDTO;
+use crate::enums::quality::Quality;
+use crate::enums::value::Value;
+
+// "moving_average(n)": each numeric value becomes the mean of its last n
+// readings, fewer right after boot. The result is no single measurement, so
+// the field is marked ESTIMATED with the reading itself kept as its raw value
+pub struct MovingAverageStage {
+    window: usize,
+    history: BTreeMap<String, VecDeque<f32>>,
+}
+
+impl MovingAverageStage {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            history: BTreeMap::new(),
+        }
+    }
+}
+
+impl IPipelineStage for MovingAverageStage {
+    fn name(&self) -> String {
+        format!("moving_average({})", self.window)
+    }
+
+    fn process(&mut self, output: &mut PipelineOutputDTO) {
+        for (field, value) in output.values.iter_mut() {
+            let sample = match value.as_f32() {
+                Some(sample) => sample,
+                None => continue,
+            };
+            let history = self.history.entry(field.clone()).or_default();
+            if history.len() == self.window {
+                history.pop_front();
+            }
+            history.push_back(sample);
+            // An earlier stage may already have kept the original, and a
+            // worse flag than ESTIMATED stays
+            let quality = output.quality.entry(field.clone()).or_insert_with(|| FieldQualityDTO {
+                quality: Quality::Estimated,
+                raw: Some(value.clone()),
+            });
+            if quality.quality == Quality::Good {
+                quality.quality = Quality::Estimated;
+            }
+            *value = Value::Float(history.iter().sum::<f32>() / history.len() as f32);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use log::debug;
+
+use crate::abstractions::pipeline::IPipelineStage;
+use crate::constants::pipeline::PipelineConstant;
+use crate::dtos::pipeline::output::PipelineOutputDTO;
+
+struct History {
+    samples: VecDeque<f32>,
+    // Consecutive rejections, enough of them means the level really moved
+    rejected: usize,
+}
+
+// "outlier" or "outlier(k)": drops numeric values more than k standard
+// deviations from the recent mean, e.g. a single BME280 pressure glitch
+pub struct OutlierStage {
+    deviations: f32,
+    history: BTreeMap<String, History>,
+}
+
+impl OutlierStage {
+    pub fn new(deviations: f32) -> Self {
+        Self {
+            deviations: deviations,
+            history: BTreeMap::new(),
+        }
+    }
+}
+
+impl IPipelineStage for OutlierStage {
+    fn name(&self) -> String {
+        format!("outlier({})", self.deviations)
+    }
+
+    fn process(&mut self, output: &mut PipelineOutputDTO) {
+        let mut dropped: Vec<String> = Vec::new();
+        for (field, value) in output.values.iter() {
+            let sample = match value.as_f32() {
+                Some(sample) => sample,
+                None => continue,
+            };
+            let history = self.history.entry(field.clone()).or_insert_with(|| History {
+                samples: VecDeque::new(),
+                rejected: 0,
+            });
+            let count = history.samples.len();
+            if count >= PipelineConstant::OUTLIER_MIN_SAMPLES
+                && history.rejected < PipelineConstant::OUTLIER_MAX_REJECTED
+            {
+                let mean = history.samples.iter().sum::<f32>() / count as f32;
+                let variance = history.samples.iter().map(|sample| (sample - mean) * (sample - mean)).sum::<f32>()
+                    / count as f32;
+                // Squared on both sides, there is no sqrt in core
+                if (sample - mean) * (sample - mean) > self.deviations * self.deviations * variance {
+                    history.rejected += 1;
+                    debug!("Dropped {} = {} as an outlier around {}", field, sample, mean);
+                    dropped.push(field.clone());
+                    continue;
+                }
+            }
+            history.rejected = 0;
+            if count == PipelineConstant::OUTLIER_WINDOW {
+                history.samples.pop_front();
+            }
+            history.samples.push_back(sample);
+        }
+        for field in dropped {
+            output.values.remove(&field);
+        }
+    }
+}
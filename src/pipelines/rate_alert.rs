@@ -0,0 +1,110 @@
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use embassy_time::Instant;
+
+use crate::abstractions::pipeline::IPipelineStage;
+use crate::constants::pipeline::PipelineConstant;
+use crate::dtos::pipeline::output::PipelineOutputDTO;
+use crate::enums::event_kind::EventKind;
+use crate::enums::priority::Priority;
+use crate::enums::value::Value;
+
+// "rate_alert(temperature>2/60)" or "rate_alert(pressure<-300/3600)": raises
+// the reading to alert priority and records a rate event when the field
+// changes faster than the slope over the window in seconds, here 2 °C per
+// minute (a fire) or a 300 Pa per hour drop (a storm). The change is taken
+// against the oldest reading inside the window, so nothing fires until half
+// of it is covered; like threshold alerts only the crossing is reported
+pub struct RateAlertStage {
+    field: String,
+    above: bool,
+    slope: f32,
+    window_s: u32,
+    // Monotonic milliseconds and value
+    history: VecDeque<(u64, f32)>,
+    exceeded: bool,
+}
+
+impl RateAlertStage {
+    // "field>slope/window_s" or "field<slope/window_s"
+    pub fn parse(condition: &str) -> Option<Self> {
+        let (index, above) = match (condition.find('>'), condition.find('<')) {
+            (Some(index), None) => (index, true),
+            (None, Some(index)) => (index, false),
+            _ => return None,
+        };
+        let field = condition[..index].trim();
+        let (slope, window_s) = condition[index + 1..].split_once('/')?;
+        let slope = slope.trim().parse().ok()?;
+        let window_s: u32 = window_s.trim().parse().ok()?;
+        if field.is_empty() || window_s == 0 {
+            return None;
+        }
+        Some(Self {
+            field: field.to_string(),
+            above: above,
+            slope: slope,
+            window_s: window_s,
+            history: VecDeque::new(),
+            exceeded: false,
+        })
+    }
+}
+
+impl IPipelineStage for RateAlertStage {
+    fn name(&self) -> String {
+        format!(
+            "{}({}{}{}/{})",
+            PipelineConstant::RATE_ALERT,
+            self.field,
+            if self.above { '>' } else { '<' },
+            self.slope,
+            self.window_s
+        )
+    }
+
+    fn process(&mut self, output: &mut PipelineOutputDTO) {
+        let value = match output.values.get(&self.field).and_then(|value| value.as_f32()) {
+            Some(value) => value,
+            // Dropped by an earlier stage, keep the current state
+            None => return,
+        };
+        let now_ms = Instant::now().as_millis();
+        let window_ms = self.window_s as u64 * 1000;
+        while self.history.front().is_some_and(|(at_ms, _)| now_ms.saturating_sub(*at_ms) > window_ms) {
+            self.history.pop_front();
+        }
+        // Kept at most one per slot of the window, so the bounded history
+        // still spans an hour long window at a 1 s interval. The current
+        // value is compared directly, it need not be kept to be used
+        let spacing_ms = window_ms / PipelineConstant::RATE_ALERT_MAX_SAMPLES as u64;
+        if self.history.back().is_none_or(|(at_ms, _)| now_ms - at_ms >= spacing_ms) {
+            if self.history.len() == PipelineConstant::RATE_ALERT_MAX_SAMPLES {
+                self.history.pop_front();
+            }
+            self.history.push_back((now_ms, value));
+        }
+
+        let (oldest_ms, oldest) = self.history[0];
+        let elapsed_ms = now_ms - oldest_ms;
+        if elapsed_ms * 2 < window_ms {
+            return;
+        }
+        // Change per window, scaled from however much of it is covered
+        let rate = (value - oldest) * window_ms as f32 / elapsed_ms as f32;
+        let exceeded = if self.above { rate > self.slope } else { rate < self.slope };
+        if exceeded && !self.exceeded {
+            output.priority = output.priority.max(Priority::Alert);
+            let mut detail = BTreeMap::new();
+            detail.insert(String::from("field"), Value::String(self.field.clone()));
+            detail.insert(String::from("value"), Value::Float(value));
+            detail.insert(String::from("rate"), Value::Float(rate));
+            detail.insert(String::from("limit"), Value::Float(self.slope));
+            detail.insert(String::from("window_s"), Value::Integer(self.window_s as i32));
+            output.events.push((EventKind::RateExceeded, detail));
+        }
+        self.exceeded = exceeded;
+    }
+}
@@ -0,0 +1,61 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+
+use crate::abstractions::pipeline::IPipelineStage;
+use crate::constants::pipeline::PipelineConstant;
+use crate::constants::sensor::SensorConstant;
+use crate::dtos::measurement::field_quality::FieldQualityDTO;
+use crate::dtos::pipeline::output::PipelineOutputDTO;
+use crate::enums::quality::Quality;
+use crate::enums::value::Value;
+use crate::services::last_value;
+
+// "temperature_compensation": removes the temperature drift of fields such
+// as the VL53L0X distance or the BH1750 lux, using the BME280's latest
+// temperature from the last-known-value cache. Each coefficient is the drift
+// in the field's own unit per °C away from the reference temperature. Put
+// first in every chain by `PipelineFactory` when coefficients are configured;
+// without a recent temperature values pass through uncorrected
+pub struct TemperatureCompensationStage {
+    coefficients: BTreeMap<String, f32>,
+}
+
+impl TemperatureCompensationStage {
+    pub fn new(coefficients: BTreeMap<String, f32>) -> Self {
+        Self { coefficients: coefficients }
+    }
+}
+
+impl IPipelineStage for TemperatureCompensationStage {
+    fn name(&self) -> String {
+        format!("{}({})", PipelineConstant::TEMPERATURE_COMPENSATION, self.coefficients.len())
+    }
+
+    fn process(&mut self, output: &mut PipelineOutputDTO) {
+        let temperature = match last_value::field(
+            SensorConstant::BME280,
+            "temperature",
+            PipelineConstant::COMPENSATION_MAX_AGE_S,
+        ) {
+            Some(temperature) => temperature,
+            None => return,
+        };
+        let delta = temperature - PipelineConstant::COMPENSATION_REFERENCE_C;
+        for (field, coefficient) in self.coefficients.iter() {
+            let value = match output.values.get_mut(field) {
+                Some(value) => value,
+                None => continue,
+            };
+            let reading = match value.as_f32() {
+                Some(reading) => reading,
+                None => continue,
+            };
+            output.quality.entry(field.clone()).or_insert_with(|| FieldQualityDTO {
+                quality: Quality::Good,
+                raw: Some(value.clone()),
+            });
+            *value = Value::Float(reading - coefficient * delta);
+        }
+    }
+}
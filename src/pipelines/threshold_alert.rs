@@ -0,0 +1,65 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use crate::abstractions::pipeline::IPipelineStage;
+use crate::dtos::pipeline::output::PipelineOutputDTO;
+use crate::enums::event_kind::EventKind;
+use crate::enums::priority::Priority;
+use crate::enums::value::Value;
+
+// "threshold_alert(temperature>30)" or "threshold_alert(lux<5)": raises the
+// reading to alert priority and records a breach while the limit is crossed.
+// The breach is only reported on crossing, not on every reading beyond it
+pub struct ThresholdAlertStage {
+    field: String,
+    above: bool,
+    limit: f32,
+    breached: bool,
+}
+
+impl ThresholdAlertStage {
+    // "field>limit" or "field<limit"
+    pub fn parse(condition: &str) -> Option<Self> {
+        let (index, above) = match (condition.find('>'), condition.find('<')) {
+            (Some(index), None) => (index, true),
+            (None, Some(index)) => (index, false),
+            _ => return None,
+        };
+        let field = condition[..index].trim();
+        let limit = condition[index + 1..].trim().parse().ok()?;
+        if field.is_empty() {
+            return None;
+        }
+        Some(Self {
+            field: field.to_string(),
+            above: above,
+            limit: limit,
+            breached: false,
+        })
+    }
+}
+
+impl IPipelineStage for ThresholdAlertStage {
+    fn name(&self) -> String {
+        format!("threshold_alert({}{}{})", self.field, if self.above { '>' } else { '<' }, self.limit)
+    }
+
+    fn process(&mut self, output: &mut PipelineOutputDTO) {
+        let value = match output.values.get(&self.field).and_then(|value| value.as_f32()) {
+            Some(value) => value,
+            // Dropped by an earlier stage, keep the current state
+            None => return,
+        };
+        let breached = if self.above { value > self.limit } else { value < self.limit };
+        if breached && !self.breached {
+            output.priority = output.priority.max(Priority::Alert);
+            let mut detail = BTreeMap::new();
+            detail.insert(String::from("field"), Value::String(self.field.clone()));
+            detail.insert(String::from("value"), Value::Float(value));
+            detail.insert(String::from("limit"), Value::Float(self.limit));
+            output.events.push((EventKind::ThresholdBreached, detail));
+        }
+        self.breached = breached;
+    }
+}
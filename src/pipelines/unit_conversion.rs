@@ -0,0 +1,73 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::abstractions::pipeline::IPipelineStage;
+use crate::constants::pipeline::PipelineConstant;
+use crate::constants::sensor::SensorConstant;
+use crate::constants::unit::UnitConstant;
+use crate::dtos::pipeline::output::PipelineOutputDTO;
+use crate::enums::unit_system::UnitSystem;
+use crate::enums::value::Value;
+
+// "unit_conversion(imperial)" or "unit_conversion(both)": Fahrenheit, inches
+// and inHg under their own field names, e.g. `temperature_f`, so a field's
+// unit never depends on the configuration. Appended to every chain by
+// `PipelineFactory` when `units` is not metric; placed last, earlier stages
+// and their thresholds keep working on metric values
+pub struct UnitConversionStage {
+    system: UnitSystem,
+}
+
+impl UnitConversionStage {
+    pub fn new(system: UnitSystem) -> Self {
+        Self { system: system }
+    }
+}
+
+impl IPipelineStage for UnitConversionStage {
+    fn name(&self) -> String {
+        format!("{}({})", PipelineConstant::UNIT_CONVERSION, self.system.as_str())
+    }
+
+    fn process(&mut self, output: &mut PipelineOutputDTO) {
+        if self.system == UnitSystem::Metric {
+            return;
+        }
+        let fields: Vec<String> = output.values.keys().cloned().collect();
+        for field in fields {
+            let (imperial, convert): (String, fn(f32) -> f32) = match field.as_str() {
+                "temperature" | SensorConstant::DS3231_TEMP => (format!("{}_f", field), celsius_to_fahrenheit),
+                "distance_mm" => (String::from("distance_in"), millimeters_to_inches),
+                "pressure" => (String::from("pressure_inhg"), pascals_to_inches_of_mercury),
+                _ => continue,
+            };
+            let value = match output.values.get(&field).and_then(|value| value.as_f32()) {
+                Some(value) => value,
+                None => continue,
+            };
+            output.values.insert(imperial.clone(), Value::Float(convert(value)));
+            // Quality and raw value follow the reading; a raw metric value
+            // stays as recorded
+            if let Some(quality) = output.quality.get(&field).cloned() {
+                output.quality.insert(imperial, quality);
+            }
+            if self.system == UnitSystem::Imperial {
+                output.values.remove(&field);
+                output.quality.remove(&field);
+            }
+        }
+    }
+}
+
+fn celsius_to_fahrenheit(celsius: f32) -> f32 {
+    celsius * 9.0 / 5.0 + 32.0
+}
+
+fn millimeters_to_inches(millimeters: f32) -> f32 {
+    millimeters / UnitConstant::INCH_MM
+}
+
+fn pascals_to_inches_of_mercury(pascals: f32) -> f32 {
+    pascals / UnitConstant::INCH_OF_MERCURY_SI_SCALE
+}
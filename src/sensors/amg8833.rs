@@ -0,0 +1,132 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::future::Future;
+use core::pin::Pin;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+
+use crate::abstractions::sensor::ISensor;
+use crate::constants::thermal::ThermalConstant;
+use crate::dtos::measurement::sensor::amg8833::AMG8833SensorMeasurement;
+use crate::enums::sensor_error::SensorError;
+use crate::utilities::thermal;
+
+const POWER_CONTROL: u8 = 0x00;
+const RESET: u8 = 0x01;
+const FRAME_RATE: u8 = 0x02;
+const THERMISTOR: u8 = 0x0e;
+const PIXELS: u8 = 0x80;
+
+const NORMAL_MODE: u8 = 0x00;
+const INITIAL_RESET: u8 = 0x3f;
+// 10 fps; 0x01 would be 1 fps with more noise averaged out per frame
+const TEN_FPS: u8 = 0x00;
+const PIXEL_BYTES: usize = ThermalConstant::ROWS * ThermalConstant::COLUMNS * 2;
+
+// Panasonic Grid-EYE 8x8 thermopile array. The full frame is kept in the
+// measurement for `ThermalFrameService`, telemetry only carries its summary.
+// Takes a cloneable bus handle with both the blocking and the async traits,
+// such as `I2cBusDevice`, so the 128 byte frame is read without holding the
+// executor
+pub struct AMG8833Sensor<I: I2c + AsyncI2c + Clone> {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    name: String,
+    i2c: RefCell<I>,
+}
+
+impl<I: I2c + AsyncI2c + Clone> ISensor<AMG8833SensorMeasurement> for AMG8833Sensor<I> {
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn read(&self) -> Result<AMG8833SensorMeasurement, SensorError> {
+        let mut i2c = self.i2c.borrow_mut();
+        let mut thermistor = [0u8; 2];
+        let mut pixels = [0u8; PIXEL_BYTES];
+        I2c::write_read(&mut *i2c, ThermalConstant::AMG8833_ADDRESS, &[THERMISTOR], &mut thermistor)
+            .and_then(|_| I2c::write_read(&mut *i2c, ThermalConstant::AMG8833_ADDRESS, &[PIXELS], &mut pixels))
+            .map_err(|error| SensorError::Bus(format!("{:?}", error)))?;
+        measurement(&thermistor, &pixels)
+    }
+
+    fn read_async(&self) -> Pin<Box<dyn Future<Output = Result<AMG8833SensorMeasurement, SensorError>> + '_>> {
+        Box::pin(async move {
+            // A handle of its own, so no borrow is held across the awaits
+            let mut i2c = self.i2c.borrow().clone();
+            let mut thermistor = [0u8; 2];
+            let mut pixels = [0u8; PIXEL_BYTES];
+            AsyncI2c::write_read(&mut i2c, ThermalConstant::AMG8833_ADDRESS, &[THERMISTOR], &mut thermistor)
+                .await
+                .map_err(|error| SensorError::Bus(format!("{:?}", error)))?;
+            AsyncI2c::write_read(&mut i2c, ThermalConstant::AMG8833_ADDRESS, &[PIXELS], &mut pixels)
+                .await
+                .map_err(|error| SensorError::Bus(format!("{:?}", error)))?;
+            measurement(&thermistor, &pixels)
+        })
+    }
+}
+
+impl<I: I2c + AsyncI2c + Clone> AMG8833Sensor<I> {
+    pub fn new<D: DelayNs>(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        name: String,
+        i2c: I,
+        delay: &mut D,
+    ) -> Result<Self, I::Error> {
+        let sensor = Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            name: name,
+            i2c: RefCell::new(i2c),
+        };
+        sensor.write(POWER_CONTROL, NORMAL_MODE)?;
+        sensor.write(RESET, INITIAL_RESET)?;
+        sensor.write(FRAME_RATE, TEN_FPS)?;
+        delay.delay_ms(ThermalConstant::SETTLE_MS);
+        Ok(sensor)
+    }
+
+    fn write(&self, register: u8, value: u8) -> Result<(), I::Error> {
+        I2c::write(&mut *self.i2c.borrow_mut(), ThermalConstant::AMG8833_ADDRESS, &[register, value])
+    }
+}
+
+// The register pointer auto-increments over all 64 pixel pairs
+fn measurement(thermistor: &[u8; 2], pixels: &[u8; PIXEL_BYTES]) -> Result<AMG8833SensorMeasurement, SensorError> {
+    let frame: Vec<f32> = pixels
+        .chunks_exact(2)
+        .map(|pair| thermal::pixel(pair[0], pair[1]) * ThermalConstant::PIXEL_C)
+        .collect();
+    let (min_c, max_c, mean_c, hotspot) =
+        thermal::summary(&frame).ok_or(SensorError::InvalidData(String::from("empty frame")))?;
+    Ok(AMG8833SensorMeasurement {
+        min_c: min_c,
+        max_c: max_c,
+        mean_c: mean_c,
+        hotspot: hotspot,
+        thermistor_c: thermal::thermistor(thermistor[0], thermistor[1]) * ThermalConstant::THERMISTOR_C,
+        frame: frame,
+    })
+}
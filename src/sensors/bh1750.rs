@@ -1,24 +1,50 @@
-use bh1750::{BH1750, Resolution};
-use esp_hal::{
-    delay::Delay,
-    i2c::{I2cConfig, I2cDriver},
-    peripherals::Peripherals,
-    prelude::*,
-};
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::cell::{Cell, RefCell};
+use core::future::Future;
+use core::pin::Pin;
+
+use embassy_time::Timer;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
 
 use crate::abstractions::sensor::ISensor;
+use crate::constants::sensor::SensorConstant;
+use crate::dtos::configurations::bh1750::Bh1750ConfigDTO;
+use crate::dtos::configurations::thresholds::ThresholdsDTO;
 use crate::dtos::measurement::sensor::bh1750::BH1750SensorMeasurement;
+use crate::enums::bh1750_mode::Bh1750Mode;
+use crate::enums::bh1750_resolution::Bh1750Resolution;
+use crate::enums::sensor_error::SensorError;
+use crate::utilities::thresholds;
+
+const POWER_ON: u8 = 0x01;
+const CONTINUOUS: u8 = 0x10;
+const ONE_TIME: u8 = 0x20;
+const MTREG_HIGH: u8 = 0x40;
+const MTREG_LOW: u8 = 0x60;
 
-pub struct BH1750Sensor {
+// Typical conversion times at the default MTreg of 69
+const HIGH_MEASUREMENT_MS: u32 = 120;
+const LOW_MEASUREMENT_MS: u32 = 16;
+// Counts per lux at the default MTreg in H mode
+const COUNTS_PER_LUX: f64 = 1.2;
+
+pub struct BH1750Sensor<I: I2c, D: DelayNs> {
     urn: String,
     device_urn: String,
     location_urn: String,
     name: String,
-    sensor: BH1750<I2cDriver<'static>, Delay>,
+    i2c: RefCell<I>,
+    delay: RefCell<D>,
+    config: Bh1750ConfigDTO,
+    thresholds: ThresholdsDTO,
+    // Continuous mode only needs to be started once
+    running: Cell<bool>,
 }
 
-impl ISensor<BH1750SensorMeasurement> for BH1750Sensor {
-
+impl<I: I2c, D: DelayNs> ISensor<BH1750SensorMeasurement> for BH1750Sensor<I, D> {
     fn urn(&self) -> String {
         self.urn.clone()
     }
@@ -35,72 +61,118 @@ impl ISensor<BH1750SensorMeasurement> for BH1750Sensor {
         self.name.clone()
     }
 
-    fn read_sync(&self) -> Result<BH1750SensorMeasurement, Box<dyn core::error::Error + Send + Sync>> {
-        self._read()
+    fn read(&self) -> Result<BH1750SensorMeasurement, SensorError> {
+        let raw = self.measure().map_err(|error| SensorError::Bus(format!("{:?}", error)))?;
+        Ok(self.measurement(raw))
+    }
+
+    // Sleeps through the conversion instead of blocking on it
+    fn read_async(&self) -> Pin<Box<dyn Future<Output = Result<BH1750SensorMeasurement, SensorError>> + '_>> {
+        Box::pin(async move {
+            let wait_ms = self.start().map_err(|error| SensorError::Bus(format!("{:?}", error)))?;
+            Timer::after_millis(wait_ms as u64).await;
+            let raw = self.fetch().map_err(|error| SensorError::Bus(format!("{:?}", error)))?;
+            Ok(self.measurement(raw))
+        })
     }
 }
 
-impl BH1750Sensor {
+impl<I: I2c, D: DelayNs> BH1750Sensor<I, D> {
     pub fn new(
         urn: String,
         device_urn: String,
         location_urn: String,
         name: String,
-    ) -> Result<Self, Box<dyn core::error::Error + Send + Sync>> {
-
-        let peripherals = Peripherals::take().unwrap();
-        let sda = peripherals.pins.gpio21;
-        let scl = peripherals.pins.gpio22;
-        
-        let config = I2cConfig::new().baudrate(400u32.kHz().into());
-        let i2c = I2cDriver::new(
-            peripherals.i2c0,
-            sda,
-            scl,
-            &config,
-        )?;
-
-        let delay = Delay::new();
-
-        let mut sensor: BH1750<I2cDriver<'static>, Delay> = BH1750::new(i2c, delay, false);
-        Ok(Self { 
+        i2c: I,
+        delay: D,
+        config: Bh1750ConfigDTO,
+        thresholds: ThresholdsDTO,
+    ) -> Result<Self, I::Error> {
+        let sensor = Self {
             urn: urn,
             device_urn: device_urn,
             location_urn: location_urn,
             name: name,
-            sensor: sensor
-        })
+            i2c: RefCell::new(i2c),
+            delay: RefCell::new(delay),
+            config: config,
+            thresholds: thresholds,
+            running: Cell::new(false),
+        };
+        sensor.command(POWER_ON)?;
+        sensor.command(MTREG_HIGH | (config.mtreg >> 5))?;
+        sensor.command(MTREG_LOW | (config.mtreg & 0x1f))?;
+        Ok(sensor)
+    }
+
+    fn measurement(&self, raw: u16) -> BH1750SensorMeasurement {
+        let lux = self.to_lux(raw);
+        BH1750SensorMeasurement {
+            lux: lux,
+            raw: raw,
+            condition: thresholds::classify(&self.thresholds, lux).to_string(),
+        }
+    }
+
+    // Applies remotely updated `SensorsConfigDTO::lux_thresholds`
+    pub fn set_thresholds(&mut self, thresholds: ThresholdsDTO) {
+        self.thresholds = thresholds;
     }
 
-    fn _read(&self) -> Result<BH1750SensorMeasurement, Box<dyn core::error::Error + Send + Sync>> {
-        let measurement: BH1750SensorMeasurement = match self.sensor.get_one_time_measurement(Resolution::High) {
-            Ok(lux) => {
-                let condition: String = get_light_condition(lux);
-                BH1750SensorMeasurement{
-                    lux: lux,
-                    condition: condition
-                }
+    fn measure(&self) -> Result<u16, I::Error> {
+        let wait_ms = self.start()?;
+        self.delay.borrow_mut().delay_ms(wait_ms);
+        self.fetch()
+    }
+
+    // Starts a conversion when one is needed, returns the ms until its
+    // result can be fetched
+    fn start(&self) -> Result<u32, I::Error> {
+        let instruction = match self.config.resolution {
+            Bh1750Resolution::High => 0x00,
+            Bh1750Resolution::High2 => 0x01,
+            Bh1750Resolution::Low => 0x03,
+        };
+        match self.config.mode {
+            Bh1750Mode::OneTime => {
+                self.command(ONE_TIME | instruction)?;
+                Ok(self.measurement_ms())
             },
-            Err(_err) => {
-                let condition: &'static str = "UNKNOWN";
-                BH1750SensorMeasurement{
-                    lux: 0.0,
-                    condition: condition
-                }
-            }
+            Bh1750Mode::Continuous if !self.running.get() => {
+                self.command(CONTINUOUS | instruction)?;
+                self.running.set(true);
+                Ok(self.measurement_ms())
+            },
+            Bh1750Mode::Continuous => Ok(0),
+        }
+    }
+
+    fn fetch(&self) -> Result<u16, I::Error> {
+        let mut data = [0u8; 2];
+        self.i2c.borrow_mut().read(SensorConstant::BH1750_ADDRESS, &mut data)?;
+        Ok(u16::from_be_bytes(data))
+    }
+
+    // Conversion time scales with MTreg, rounded up with a little margin
+    fn measurement_ms(&self) -> u32 {
+        let base = match self.config.resolution {
+            Bh1750Resolution::High | Bh1750Resolution::High2 => HIGH_MEASUREMENT_MS,
+            Bh1750Resolution::Low => LOW_MEASUREMENT_MS,
         };
-        Ok(measurement)
+        let default = SensorConstant::BH1750_DEFAULT_MTREG as u32;
+        (base * self.config.mtreg as u32).div_ceil(default) * 3 / 2
     }
-}
 
-fn get_light_condition(lux: f32) -> String {
-    match lux {
-        0.0..=10.0 => "VERY_DARK".to_string(),
-        10.1..=50.0 => "DARK".to_string(),
-        50.1..=200.0 => "DIM".to_string(),
-        200.1..=1000.0 => "NORMAL".to_string(),
-        1000.1..=5000.0 => "BRIGHT".to_string(),
-        5000.1..=10000.0 => "VERY_BRIGHT".to_string(),
-        _ => "EXTREME".to_string(),
+    fn to_lux(&self, raw: u16) -> f64 {
+        let scale = SensorConstant::BH1750_DEFAULT_MTREG as f64 / self.config.mtreg as f64;
+        let lux = raw as f64 / COUNTS_PER_LUX * scale;
+        match self.config.resolution {
+            Bh1750Resolution::High2 => lux / 2.0,
+            _ => lux,
+        }
     }
-}
\ No newline at end of file
+
+    fn command(&self, command: u8) -> Result<(), I::Error> {
+        self.i2c.borrow_mut().write(SensorConstant::BH1750_ADDRESS, &[command])
+    }
+}
@@ -1,27 +1,101 @@
-use alloc::string::{String};
 use alloc::boxed::Box;
-use core::fmt::Error;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::cell::RefCell;
+use core::fmt::Debug;
+use core::future::Future;
+use core::pin::Pin;
 
-use bme280::{BME280};
-use esp_hal::{
-    delay::Delay,
-    i2c::{I2cConfig, I2cDriver},
-    peripherals::Peripherals,
-};
-
-use crate::dtos::measurement::{sensor::bme280::BME280SensorMeasurement};
+use embassy_time::Timer;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
 
 use crate::abstractions::sensor::ISensor;
+use crate::constants::sensor::SensorConstant;
+use crate::dtos::configurations::bme280::Bme280ConfigDTO;
+use crate::dtos::configurations::thresholds::ThresholdsDTO;
+use crate::dtos::measurement::sensor::bme280::BME280SensorMeasurement;
+use crate::enums::bme280_mode::Bme280Mode;
+use crate::enums::sensor_error::SensorError;
+use crate::services::self_heating;
+use crate::utilities::thresholds;
+
+const REG_CALIBRATION_TP: u8 = 0x88;
+const REG_CALIBRATION_H1: u8 = 0xa1;
+const REG_ID: u8 = 0xd0;
+const REG_RESET: u8 = 0xe0;
+const REG_CALIBRATION_H2: u8 = 0xe1;
+const REG_CTRL_HUM: u8 = 0xf2;
+const REG_STATUS: u8 = 0xf3;
+const REG_CTRL_MEAS: u8 = 0xf4;
+const REG_CONFIG: u8 = 0xf5;
+const REG_DATA: u8 = 0xf7;
+
+const CHIP_ID: u8 = 0x60;
+const RESET_COMMAND: u8 = 0xb6;
+const STATUS_MEASURING: u8 = 0x08;
+const STATUS_UPDATING: u8 = 0x01;
+const MODE_FORCED: u8 = 0x01;
+const MODE_NORMAL: u8 = 0x03;
+const STARTUP_MS: u32 = 2;
+
+// Standby codes in register order
+const STANDBY_MS: [f32; 8] = [0.5, 62.5, 125.0, 250.0, 500.0, 1000.0, 10.0, 20.0];
 
-pub struct BME280Sensor {
+#[derive(Debug)]
+pub enum Bme280Error<E> {
+    I2c(E),
+    UnknownChipId(u8),
+}
+
+impl<E: Debug> From<Bme280Error<E>> for SensorError {
+    fn from(error: Bme280Error<E>) -> Self {
+        match error {
+            Bme280Error::I2c(error) => SensorError::Bus(format!("{:?}", error)),
+            Bme280Error::UnknownChipId(id) => SensorError::InvalidData(format!("chip id {:#04x}", id)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Calibration {
+    t1: u16,
+    t2: i16,
+    t3: i16,
+    p1: u16,
+    p2: i16,
+    p3: i16,
+    p4: i16,
+    p5: i16,
+    p6: i16,
+    p7: i16,
+    p8: i16,
+    p9: i16,
+    h1: u8,
+    h2: i16,
+    h3: u8,
+    h4: i16,
+    h5: i16,
+    h6: i8,
+}
+
+// Register level driver with the datasheet's integer compensation, so the
+// oversampling, IIR filter and standby settings are ours rather than a
+// library's defaults
+pub struct BME280Sensor<I: I2c, D: DelayNs> {
     urn: String,
     device_urn: String,
     location_urn: String,
     name: String,
-    sensor: BME280<I2cDriver, Delay>,
+    i2c: RefCell<I>,
+    delay: RefCell<D>,
+    config: Bme280ConfigDTO,
+    calibration: Calibration,
+    temperature_thresholds: ThresholdsDTO,
+    humidity_thresholds: ThresholdsDTO,
 }
 
-impl ISensor<BME280SensorMeasurement> for BME280Sensor {
+impl<I: I2c, D: DelayNs> ISensor<BME280SensorMeasurement> for BME280Sensor<I, D> {
     fn urn(&self) -> String {
         self.urn.clone()
     }
@@ -38,65 +112,244 @@ impl ISensor<BME280SensorMeasurement> for BME280Sensor {
         self.name.clone()
     }
 
-    fn read(&self) -> Result<BME280SensorMeasurement, Error> {
-        self._read()
+    fn read(&self) -> Result<BME280SensorMeasurement, SensorError> {
+        let (adc_t, adc_p, adc_h) = self.measure().map_err(SensorError::from)?;
+        Ok(self.measurement(adc_t, adc_p, adc_h))
     }
 
+    // Sleeps through a forced conversion instead of blocking on it
+    fn read_async(&self) -> Pin<Box<dyn Future<Output = Result<BME280SensorMeasurement, SensorError>> + '_>> {
+        Box::pin(async move {
+            if self.config.mode == Bme280Mode::Forced {
+                self.write_register(REG_CTRL_MEAS, self.ctrl_meas(MODE_FORCED)).map_err(SensorError::from)?;
+                Timer::after_micros(self.measurement_us() as u64).await;
+                while self.read_register(REG_STATUS).map_err(SensorError::from)? & STATUS_MEASURING != 0 {
+                    Timer::after_millis(1).await;
+                }
+            }
+            let (adc_t, adc_p, adc_h) = self.fetch().map_err(SensorError::from)?;
+            Ok(self.measurement(adc_t, adc_p, adc_h))
+        })
+    }
 }
 
-impl BME280Sensor {
-    fn new(
+impl<I: I2c, D: DelayNs> BME280Sensor<I, D> {
+    pub fn new(
         urn: String,
         device_urn: String,
         location_urn: String,
         name: String,
-    ) -> Self {
-        let peripherals = Peripherals::take().unwrap();
-        let sda = peripherals.pins.gpio21;
-        let scl = peripherals.pins.gpio22;
-
-        let config = I2cConfig::new().baudrate(400_000.into());
-        let i2c = I2cDriver::new(
-            peripherals.i2c0,
-            sda,
-            scl,
-            &config,
-        );
-
-        let delay: Delay = Delay::new();
-
-        let mut sensor: BME280<I2cDriver, Delay> = BME280::new_primary(
-            i2c,
-            delay,
-        );
-        sensor.init().unwrap();
-
-        Self {
+        i2c: I,
+        delay: D,
+        config: Bme280ConfigDTO,
+        temperature_thresholds: ThresholdsDTO,
+        humidity_thresholds: ThresholdsDTO,
+    ) -> Result<Self, Bme280Error<I::Error>> {
+        let mut sensor = Self {
             urn: urn,
             device_urn: device_urn,
             location_urn: location_urn,
             name: name,
-            sensor: sensor,
+            i2c: RefCell::new(i2c),
+            delay: RefCell::new(delay),
+            config: config,
+            calibration: Calibration::default(),
+            temperature_thresholds: temperature_thresholds,
+            humidity_thresholds: humidity_thresholds,
+        };
+        let id = sensor.read_register(REG_ID)?;
+        if id != CHIP_ID {
+            return Err(Bme280Error::UnknownChipId(id));
+        }
+        sensor.write_register(REG_RESET, RESET_COMMAND)?;
+        sensor.delay.borrow_mut().delay_ms(STARTUP_MS);
+        while sensor.read_register(REG_STATUS)? & STATUS_UPDATING != 0 {
+            sensor.delay.borrow_mut().delay_ms(1);
         }
+        sensor.calibration = sensor.read_calibration()?;
+        sensor.configure()?;
+        Ok(sensor)
     }
 
-    pub fn _read(&self) -> Result<BME280SensorMeasurement, Error> {
-        let measurement: BME280SensorMeasurement = match self.sensor.measure(){
-            Ok(measurements) => {
-                BME280SensorMeasurement { 
-                    temperature: measurements.temperature,
-                    humidity: measurements.humidity,
-                    pressure: measurements.pressure 
-                }
-            },
-            Err(e) => {
-                BME280SensorMeasurement { 
-                    temperature: 0.0,
-                    humidity: 0.0,
-                    pressure: 0.0 
-                }
-            }
+    fn measurement(&self, adc_t: i32, adc_p: i32, adc_h: i32) -> BME280SensorMeasurement {
+        let t_fine = self.t_fine(adc_t);
+        let temperature = ((t_fine * 5 + 128) >> 8) as f32 / 100.0 - self_heating::offset_c(&self.config);
+        let humidity = self.humidity(adc_h, t_fine) as f32 / 1024.0;
+        BME280SensorMeasurement {
+            temperature: temperature,
+            humidity: humidity,
+            pressure: self.pressure(adc_p, t_fine) as f32 / 256.0,
+            temperature_condition: thresholds::classify(&self.temperature_thresholds, temperature as f64).to_string(),
+            humidity_condition: thresholds::classify(&self.humidity_thresholds, humidity as f64).to_string(),
+        }
+    }
+
+    // Applies remotely updated `SensorsConfigDTO::temperature_thresholds`
+    // and `humidity_thresholds`
+    pub fn set_thresholds(&mut self, temperature: ThresholdsDTO, humidity: ThresholdsDTO) {
+        self.temperature_thresholds = temperature;
+        self.humidity_thresholds = humidity;
+    }
+
+    // Settings only take effect in sleep mode, and ctrl_hum only once
+    // ctrl_meas is written after it
+    fn configure(&self) -> Result<(), Bme280Error<I::Error>> {
+        let standby = STANDBY_MS
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                (**a - self.config.standby_ms).abs().total_cmp(&(**b - self.config.standby_ms).abs())
+            })
+            .map_or(0, |(code, _)| code as u8);
+        let filter = match self.config.filter {
+            0 => 0,
+            coefficient => coefficient.trailing_zeros() as u8,
+        };
+        self.write_register(REG_CTRL_MEAS, 0)?;
+        self.write_register(REG_CONFIG, (standby << 5) | (filter << 2))?;
+        self.write_register(REG_CTRL_HUM, oversampling_code(self.config.humidity_oversampling))?;
+        let mode = match self.config.mode {
+            // Started per read
+            Bme280Mode::Forced => 0,
+            Bme280Mode::Normal => MODE_NORMAL,
         };
-        Ok(measurement)
+        self.write_register(REG_CTRL_MEAS, self.ctrl_meas(mode))
     }
-}
\ No newline at end of file
+
+    fn ctrl_meas(&self, mode: u8) -> u8 {
+        (oversampling_code(self.config.temperature_oversampling) << 5)
+            | (oversampling_code(self.config.pressure_oversampling) << 2)
+            | mode
+    }
+
+    // Raw temperature, pressure and humidity ADC values
+    fn measure(&self) -> Result<(i32, i32, i32), Bme280Error<I::Error>> {
+        if self.config.mode == Bme280Mode::Forced {
+            self.write_register(REG_CTRL_MEAS, self.ctrl_meas(MODE_FORCED))?;
+            self.delay.borrow_mut().delay_us(self.measurement_us());
+            while self.read_register(REG_STATUS)? & STATUS_MEASURING != 0 {
+                self.delay.borrow_mut().delay_ms(1);
+            }
+        }
+        self.fetch()
+    }
+
+    fn fetch(&self) -> Result<(i32, i32, i32), Bme280Error<I::Error>> {
+        let mut data = [0u8; 8];
+        self.read_registers(REG_DATA, &mut data)?;
+        let adc_p = ((data[0] as i32) << 12) | ((data[1] as i32) << 4) | (data[2] as i32 >> 4);
+        let adc_t = ((data[3] as i32) << 12) | ((data[4] as i32) << 4) | (data[5] as i32 >> 4);
+        let adc_h = ((data[6] as i32) << 8) | data[7] as i32;
+        Ok((adc_t, adc_p, adc_h))
+    }
+
+    // Datasheet maximum: 1.25 ms + 2.3 ms per sample, plus 0.575 ms for
+    // each of pressure and humidity when enabled
+    fn measurement_us(&self) -> u32 {
+        let mut us = 1_250 + 2_300 * self.config.temperature_oversampling as u32;
+        for oversampling in [self.config.pressure_oversampling, self.config.humidity_oversampling] {
+            if oversampling > 0 {
+                us += 2_300 * oversampling as u32 + 575;
+            }
+        }
+        us
+    }
+
+    fn t_fine(&self, adc_t: i32) -> i32 {
+        let c = &self.calibration;
+        let var1 = (((adc_t >> 3) - ((c.t1 as i32) << 1)) * c.t2 as i32) >> 11;
+        let delta = (adc_t >> 4) - c.t1 as i32;
+        let var2 = (((delta * delta) >> 12) * c.t3 as i32) >> 14;
+        var1 + var2
+    }
+
+    // Pa in Q24.8
+    fn pressure(&self, adc_p: i32, t_fine: i32) -> u32 {
+        let c = &self.calibration;
+        let mut var1 = t_fine as i64 - 128_000;
+        let mut var2 = var1 * var1 * c.p6 as i64;
+        var2 += (var1 * c.p5 as i64) << 17;
+        var2 += (c.p4 as i64) << 35;
+        var1 = ((var1 * var1 * c.p3 as i64) >> 8) + ((var1 * c.p2 as i64) << 12);
+        var1 = (((1i64 << 47) + var1) * c.p1 as i64) >> 33;
+        if var1 == 0 {
+            return 0;
+        }
+        let mut p = 1_048_576 - adc_p as i64;
+        p = (((p << 31) - var2) * 3_125) / var1;
+        let var1 = (c.p9 as i64 * (p >> 13) * (p >> 13)) >> 25;
+        let var2 = (c.p8 as i64 * p) >> 19;
+        (((p + var1 + var2) >> 8) + ((c.p7 as i64) << 4)) as u32
+    }
+
+    // %RH in Q22.10
+    fn humidity(&self, adc_h: i32, t_fine: i32) -> u32 {
+        let c = &self.calibration;
+        let v = t_fine - 76_800;
+        let mut v = ((((adc_h << 14) - ((c.h4 as i32) << 20) - (c.h5 as i32 * v)) + 16_384) >> 15)
+            * (((((((v * c.h6 as i32) >> 10) * (((v * c.h3 as i32) >> 11) + 32_768)) >> 10) + 2_097_152)
+                * c.h2 as i32
+                + 8_192)
+                >> 14);
+        v -= ((((v >> 15) * (v >> 15)) >> 7) * c.h1 as i32) >> 4;
+        (v.clamp(0, 419_430_400) >> 12) as u32
+    }
+
+    fn read_calibration(&self) -> Result<Calibration, Bme280Error<I::Error>> {
+        let mut tp = [0u8; 24];
+        self.read_registers(REG_CALIBRATION_TP, &mut tp)?;
+        let h1 = self.read_register(REG_CALIBRATION_H1)?;
+        let mut h = [0u8; 7];
+        self.read_registers(REG_CALIBRATION_H2, &mut h)?;
+
+        let unsigned = |i: usize| u16::from_le_bytes([tp[i], tp[i + 1]]);
+        let signed = |i: usize| i16::from_le_bytes([tp[i], tp[i + 1]]);
+        Ok(Calibration {
+            t1: unsigned(0),
+            t2: signed(2),
+            t3: signed(4),
+            p1: unsigned(6),
+            p2: signed(8),
+            p3: signed(10),
+            p4: signed(12),
+            p5: signed(14),
+            p6: signed(16),
+            p7: signed(18),
+            p8: signed(20),
+            p9: signed(22),
+            h1: h1,
+            h2: i16::from_le_bytes([h[0], h[1]]),
+            h3: h[2],
+            // 12-bit values sharing the nibbles of 0xe5
+            h4: ((h[3] as i8 as i16) << 4) | (h[4] & 0x0f) as i16,
+            h5: ((h[5] as i8 as i16) << 4) | (h[4] >> 4) as i16,
+            h6: h[6] as i8,
+        })
+    }
+
+    fn read_register(&self, register: u8) -> Result<u8, Bme280Error<I::Error>> {
+        let mut value = [0u8; 1];
+        self.read_registers(register, &mut value)?;
+        Ok(value[0])
+    }
+
+    fn read_registers(&self, register: u8, buffer: &mut [u8]) -> Result<(), Bme280Error<I::Error>> {
+        self.i2c
+            .borrow_mut()
+            .write_read(SensorConstant::BME280_ADDRESS, &[register], buffer)
+            .map_err(Bme280Error::I2c)
+    }
+
+    fn write_register(&self, register: u8, value: u8) -> Result<(), Bme280Error<I::Error>> {
+        self.i2c
+            .borrow_mut()
+            .write(SensorConstant::BME280_ADDRESS, &[register, value])
+            .map_err(Bme280Error::I2c)
+    }
+}
+
+// x1, x2, x4, x8, x16 map to register codes 1-5; code 0 skips the
+// measurement and is refused in `Config::new`
+fn oversampling_code(ratio: u8) -> u8 {
+    ratio.trailing_zeros() as u8 + 1
+}
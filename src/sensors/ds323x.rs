@@ -1,97 +1,319 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use alloc::format;
+use alloc::string::String;
+use core::cell::{Cell, RefCell};
+use core::fmt::Debug;
 
-use chrono::NaiveDateTime;
-use ds323x::NaiveDateTime;
-use ds323x::{Ds323x, rtc::Hours, NaiveDate, NaiveTime, Rtcc};
-use esp_hal::peripheral::Peripherals;
+use embedded_hal::i2c::I2c;
+use log::{info, warn};
 
 use crate::abstractions::sensor::ISensor;
+use crate::constants::clock::ClockConstant;
 use crate::dtos::measurement::sensor::ds323x::DS323XSensorMeasurement;
+use crate::enums::sensor_error::SensorError;
+use crate::utilities::{datetime, timezone};
 
-pub struct DS323XSensor {
+const REG_SECONDS: u8 = 0x00;
+const REG_ALARM1_SECONDS: u8 = 0x07;
+const REG_CONTROL: u8 = 0x0e;
+const REG_STATUS: u8 = 0x0f;
+const REG_AGING_OFFSET: u8 = 0x10;
+const REG_TEMPERATURE_MSB: u8 = 0x11;
+
+const HOURS_12H: u8 = 0x40;
+const MONTH_CENTURY: u8 = 0x80;
+const CONTROL_CONVERT: u8 = 0x20;
+const CONTROL_INTERRUPT: u8 = 0x04;
+const CONTROL_ALARM2_ENABLE: u8 = 0x02;
+const CONTROL_ALARM1_ENABLE: u8 = 0x01;
+const STATUS_OSCILLATOR_STOPPED: u8 = 0x80;
+const STATUS_BUSY: u8 = 0x04;
+const STATUS_ALARM_FLAGS: u8 = 0x03;
+
+#[derive(Debug)]
+pub enum Ds323xError<E> {
+    I2c(E),
+    // The oscillator stopped at some point (e.g. flat backup battery), the
+    // time registers cannot be trusted until they are written again
+    OscillatorStopped,
+    InvalidTime,
+}
+
+impl<E: Debug> From<Ds323xError<E>> for SensorError {
+    fn from(error: Ds323xError<E>) -> Self {
+        match error {
+            Ds323xError::I2c(error) => SensorError::Bus(format!("{:?}", error)),
+            Ds323xError::OscillatorStopped => SensorError::InvalidData(String::from("oscillator stopped")),
+            Ds323xError::InvalidTime => SensorError::InvalidData(String::from("invalid time")),
+        }
+    }
+}
+
+// Offset of the RTC against SNTP at the start of the current drift window
+#[derive(Debug, Clone, Copy)]
+struct DriftWindow {
+    started_at: u64,
+    offset_s: i64,
+}
+
+// Register level driver for the DS3231 real time clock. The RTC keeps UTC
+// and is the authoritative clock while offline: it seeds the system clock
+// at boot and is disciplined from SNTP whenever the network is up
+pub struct DS323XSensor<I: I2c> {
     urn: String,
     device_urn: String,
     location_urn: String,
     name: String,
-    sensor: Ds323x,
+    i2c: RefCell<I>,
+    window: Cell<Option<DriftWindow>>,
+    drift_ppm: Cell<Option<f32>>,
 }
 
-impl ISensor<DS323XSensorMeasurement> for DS323XSensor {
-
+impl<I: I2c> ISensor<DS323XSensorMeasurement> for DS323XSensor<I> {
     fn urn(&self) -> String {
-        &self.urn
+        self.urn.clone()
     }
 
     fn device_urn(&self) -> String {
-        &self.device_urn
+        self.device_urn.clone()
     }
 
     fn location_urn(&self) -> String {
-        &self.location_urn
+        self.location_urn.clone()
     }
 
     fn name(&self) -> String {
-        &self.name
+        self.name.clone()
     }
 
-    async fn read(&self) -> Result<DS323XSensorMeasurement, Error> {
-        self._read().await
+    fn read(&self) -> Result<DS323XSensorMeasurement, SensorError> {
+        self._read()
     }
 }
 
-impl DS323XSensor {
-
-    fn new(
+impl<I: I2c> DS323XSensor<I> {
+    pub fn new(
         urn: String,
         device_urn: String,
         location_urn: String,
         name: String,
+        i2c: I,
     ) -> Self {
-        
-        let peripherals = Peripherals::take().unwrap();
-        let sda = peripherals.pins.gpio21;
-        let scl = peripherals.pins.gpio22;
-
-        let config = I2cConfig::new().baudrate(400.kHz().into());
-        let i2c = I2cDriver::new(
-            peripherals.i2c0,
-            sda,
-            scl,
-            &config,
-        );
-
-        let timestamp: u64 = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
-        let mut sensor: Ds323x = Ds323x::new_ds3231(i2c);
-
-        let datetime: NaiveDateTime = NaiveDateTime::from_timestamp_opt(timestamp as i64, 0)
-            .expect("Invalid timestamp");
-        sensor.set_datetime(&datetime)?;
         Self {
             urn: urn,
             device_urn: device_urn,
             location_urn: location_urn,
             name: name,
-            sensor: sensor
+            i2c: RefCell::new(i2c),
+            window: Cell::new(None),
+            drift_ppm: Cell::new(None),
+        }
+    }
+
+    fn _read(&self) -> Result<DS323XSensorMeasurement, SensorError> {
+        let timestamp = self.read_unix()?;
+        Ok(DS323XSensorMeasurement {
+            datetime: timezone::format_local(&timezone::UTC, timestamp),
+            timestamp: timestamp,
+            temperature: self.temperature()?,
+        })
+    }
+
+    pub fn drift_ppm(&self) -> Option<f32> {
+        self.drift_ppm.get()
+    }
+
+    // UTC seconds since the unix epoch
+    pub fn read_unix(&self) -> Result<u64, Ds323xError<I::Error>> {
+        if self.read_register(REG_STATUS)? & STATUS_OSCILLATOR_STOPPED != 0 {
+            return Err(Ds323xError::OscillatorStopped);
+        }
+        let mut registers = [0u8; 7];
+        self.read_registers(REG_SECONDS, &mut registers)?;
+        if registers[2] & HOURS_12H != 0 {
+            // Only ever written in 24 hour mode by this driver
+            return Err(Ds323xError::InvalidTime);
+        }
+        let second = from_bcd(registers[0] & 0x7f);
+        let minute = from_bcd(registers[1] & 0x7f);
+        let hour = from_bcd(registers[2] & 0x3f);
+        let day = from_bcd(registers[4] & 0x3f);
+        let month = from_bcd(registers[5] & 0x1f);
+        let century = if registers[5] & MONTH_CENTURY != 0 { 100 } else { 0 };
+        let year = 2000 + century + from_bcd(registers[6]) as i32;
+        if second > 59 || minute > 59 || hour > 23 || !(1..=12).contains(&month) || day == 0
+            || day > datetime::days_in_month(year, month)
+        {
+            return Err(Ds323xError::InvalidTime);
+        }
+        let days = datetime::days_from_civil(year, month, day);
+        Ok((days * 86_400) as u64 + hour as u64 * 3_600 + minute as u64 * 60 + second as u64)
+    }
+
+    // Writing the seconds register restarts the RTC's one second countdown,
+    // so the caller should write right at a second boundary
+    pub fn write_unix(&self, timestamp: u64) -> Result<(), Ds323xError<I::Error>> {
+        let date = datetime::from_unix(timestamp);
+        if !(2000..2200).contains(&date.year) {
+            return Err(Ds323xError::InvalidTime);
+        }
+        // 1970-01-01 was a Thursday, the DS3231 counts weekdays 1-7 from Sunday
+        let weekday = ((timestamp / 86_400 + 4) % 7) as u8 + 1;
+        let century = if date.year >= 2100 { MONTH_CENTURY } else { 0 };
+        let registers = [
+            REG_SECONDS,
+            to_bcd(date.second),
+            to_bcd(date.minute),
+            to_bcd(date.hour),
+            weekday,
+            to_bcd(date.day),
+            to_bcd(date.month) | century,
+            to_bcd((date.year % 100) as u8),
+        ];
+        self.i2c
+            .borrow_mut()
+            .write(ClockConstant::DS3231_ADDRESS, &registers)
+            .map_err(Ds323xError::I2c)?;
+        // Writing the time makes it valid again
+        let status = self.read_register(REG_STATUS)?;
+        self.write_register(REG_STATUS, status & !STATUS_OSCILLATOR_STOPPED)
+    }
+
+    // Signed trim in ~0.1 ppm steps, positive values slow the oscillator
+    pub fn aging_offset(&self) -> Result<i8, Ds323xError<I::Error>> {
+        Ok(self.read_register(REG_AGING_OFFSET)? as i8)
+    }
+
+    pub fn set_aging_offset(&self, offset: i8) -> Result<(), Ds323xError<I::Error>> {
+        self.write_register(REG_AGING_OFFSET, offset as u8)?;
+        // The new trim only applies after the next temperature conversion,
+        // which would otherwise happen up to 64 s later
+        if self.read_register(REG_STATUS)? & STATUS_BUSY == 0 {
+            let control = self.read_register(REG_CONTROL)?;
+            self.write_register(REG_CONTROL, control | CONTROL_CONVERT)?;
         }
+        Ok(())
     }
 
-    async fn _read(&self) -> Result<DS323XSensorMeasurement, Error> {
-        let measurement: DS323XSensorMeasurement = match self.sensor.now() {
-            Ok(datetime) => {
-                DS323XSensorMeasurement{
-                    datetime: datetime
-                }
+    // Die temperature in °C with 0.25 °C resolution, used by the RTC for its
+    // own crystal compensation
+    pub fn temperature(&self) -> Result<f32, Ds323xError<I::Error>> {
+        let mut registers = [0u8; 2];
+        self.read_registers(REG_TEMPERATURE_MSB, &mut registers)?;
+        Ok(registers[0] as i8 as f32 + (registers[1] >> 6) as f32 * 0.25)
+    }
+
+    // Compares the RTC against an SNTP `reference` (taken at a second
+    // boundary) and returns the offset before correction. Drift is measured
+    // across calls and, once a window is long enough, fed back into the
+    // aging offset; the RTC itself is only rewritten once it is more than
+    // `MAX_OFFSET_S` off, which also starts a new window
+    pub fn discipline(&self, reference: u64) -> Result<i64, Ds323xError<I::Error>> {
+        let offset = match self.read_unix() {
+            Ok(rtc) => rtc as i64 - reference as i64,
+            Err(Ds323xError::OscillatorStopped) | Err(Ds323xError::InvalidTime) => {
+                warn!("RTC time is invalid, setting it from SNTP");
+                self.write_unix(reference)?;
+                self.window.set(Some(DriftWindow { started_at: reference, offset_s: 0 }));
+                return Ok(0);
             },
-            Err(e) => {
-                let timestamp: u64 = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
-                let datetime: NaiveDateTime = NaiveDateTime::from_timestamp_opt(timestamp as i64, 0)
-                    .expect("Invalid timestamp");
-                DS323XSensorMeasurement{
-                    datetime: datetime
-                }
-            }
+            Err(error) => return Err(error),
         };
-        Ok(measurement)
+
+        let window = match self.window.get() {
+            Some(window) => window,
+            None => {
+                let window = DriftWindow { started_at: reference, offset_s: offset };
+                self.window.set(Some(window));
+                window
+            },
+        };
+        let elapsed = reference.saturating_sub(window.started_at);
+        let measured = elapsed >= ClockConstant::MIN_DRIFT_WINDOW_S;
+        let drift_ppb = if measured { (offset - window.offset_s) * 1_000_000_000 / elapsed as i64 } else { 0 };
+        if measured {
+            self.drift_ppm.set(Some(drift_ppb as f32 / 1_000.0));
+        }
+
+        if offset.abs() > ClockConstant::MAX_OFFSET_S {
+            self.write_unix(reference)?;
+            self.window.set(Some(DriftWindow { started_at: reference, offset_s: 0 }));
+            info!("RTC was {} s off, rewritten from SNTP", offset);
+        }
+        let steps = drift_ppb / ClockConstant::AGING_PPB_PER_LSB;
+        if steps != 0 {
+            let aging = self.aging_offset()?;
+            let trimmed = (aging as i64 + steps).clamp(i8::MIN as i64, i8::MAX as i64) as i8;
+            if trimmed != aging {
+                self.set_aging_offset(trimmed)?;
+                info!("RTC drift {} ppb, aging offset {} -> {}", drift_ppb, aging, trimmed);
+            }
+            // The trim changes the rate, so the old window no longer applies
+            let offset_s = if offset.abs() > ClockConstant::MAX_OFFSET_S { 0 } else { offset };
+            self.window.set(Some(DriftWindow { started_at: reference, offset_s: offset_s }));
+        }
+        Ok(offset)
+    }
+
+    // Arms alarm 1 to pull INT/SQW low at `timestamp`, matching on date,
+    // hours, minutes and seconds so it can be up to a month ahead. The pin
+    // stays low until `clear_alarms` is called
+    pub fn set_alarm(&self, timestamp: u64) -> Result<(), Ds323xError<I::Error>> {
+        let date = datetime::from_unix(timestamp);
+        let registers = [
+            REG_ALARM1_SECONDS,
+            to_bcd(date.second),
+            to_bcd(date.minute),
+            to_bcd(date.hour),
+            to_bcd(date.day),
+        ];
+        self.clear_alarms()?;
+        self.i2c
+            .borrow_mut()
+            .write(ClockConstant::DS3231_ADDRESS, &registers)
+            .map_err(Ds323xError::I2c)?;
+        let control = self.read_register(REG_CONTROL)?;
+        let control = (control & !CONTROL_ALARM2_ENABLE) | CONTROL_INTERRUPT | CONTROL_ALARM1_ENABLE;
+        self.write_register(REG_CONTROL, control)
+    }
+
+    // Releases INT/SQW; call after every wake, a set flag keeps the pin
+    // asserted and would wake the chip again immediately
+    pub fn clear_alarms(&self) -> Result<(), Ds323xError<I::Error>> {
+        let status = self.read_register(REG_STATUS)?;
+        self.write_register(REG_STATUS, status & !STATUS_ALARM_FLAGS)
+    }
+
+    pub fn disable_alarms(&self) -> Result<(), Ds323xError<I::Error>> {
+        let control = self.read_register(REG_CONTROL)?;
+        self.write_register(REG_CONTROL, control & !(CONTROL_ALARM1_ENABLE | CONTROL_ALARM2_ENABLE))?;
+        self.clear_alarms()
+    }
+
+    fn read_register(&self, register: u8) -> Result<u8, Ds323xError<I::Error>> {
+        let mut value = [0u8; 1];
+        self.read_registers(register, &mut value)?;
+        Ok(value[0])
+    }
+
+    fn read_registers(&self, register: u8, buffer: &mut [u8]) -> Result<(), Ds323xError<I::Error>> {
+        self.i2c
+            .borrow_mut()
+            .write_read(ClockConstant::DS3231_ADDRESS, &[register], buffer)
+            .map_err(Ds323xError::I2c)
     }
-    
-}
\ No newline at end of file
+
+    fn write_register(&self, register: u8, value: u8) -> Result<(), Ds323xError<I::Error>> {
+        self.i2c
+            .borrow_mut()
+            .write(ClockConstant::DS3231_ADDRESS, &[register, value])
+            .map_err(Ds323xError::I2c)
+    }
+}
+
+fn from_bcd(value: u8) -> u8 {
+    (value >> 4) * 10 + (value & 0x0f)
+}
+
+fn to_bcd(value: u8) -> u8 {
+    ((value / 10) << 4) | (value % 10)
+}
@@ -1,9 +1,129 @@
-use esp_idf_hal::{
-    delay::Delay,
-    i2c::{I2cConfig, I2cDriver},
-    peripherals::Peripherals,
-};
-use lsm303dlhc::Lsm303dlhc;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use core::cell::RefCell;
+use core::future::Future;
+use core::pin::Pin;
+
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embassy_sync::mutex::Mutex;
+use embedded_hal::i2c::I2c;
+use embedded_hal_async::i2c::I2c as AsyncI2c;
+#[cfg(not(test))]
+use esp_hal::gpio::Input;
 
 use crate::abstractions::sensor::ISensor;
+use crate::constants::sensor::SensorConstant;
 use crate::dtos::measurement::sensor::lsm303dlhc::accel::LSM303DLHCACCELSensorMeasurement;
+use crate::enums::sensor_error::SensorError;
+#[cfg(test)]
+use crate::host_test::fake_hal::Input;
+
+const CTRL_REG1_A: u8 = 0x20;
+const CTRL_REG3_A: u8 = 0x22;
+const CTRL_REG4_A: u8 = 0x23;
+// Setting the top bit of the register address auto-increments it
+const OUT_X_L_A: u8 = 0x28 | 0x80;
+
+// 100 Hz with all three axes on, as `ImpactService` wants 100 Hz or more
+const HUNDRED_HZ_XYZ: u8 = 0x57;
+// The part powers up at ±2 g, which clips a drop onto a hard floor well
+// below `ImpactConstant::IMPACT_MS2`. ±8 g with high resolution still
+// resolves 4 mg, plenty for tilt and step detection
+const EIGHT_G_HIGH_RESOLUTION: u8 = 0x28;
+// Routes data ready to INT1, which idles low and goes high until the
+// sample is read
+const I1_DRDY1: u8 = 0x10;
+const G_PER_COUNT: f32 = 0.004;
+const GRAVITY_MS2: f32 = 9.80665;
+
+// Accelerometer half of the LSM303DLHC, configured for ±8 g at 100 Hz.
+// Takes a cloneable bus handle with both the blocking and the async traits,
+// such as `I2cBusDevice`, like `AMG8833Sensor`
+pub struct LSM303DLHCAccelSensor<I: I2c + AsyncI2c + Clone> {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    name: String,
+    i2c: RefCell<I>,
+    // INT1, see `I1_DRDY1` and `SensorsConfigDTO::ready_gpios`
+    ready: Option<Mutex<NoopRawMutex, Input<'static>>>,
+}
+
+impl<I: I2c + AsyncI2c + Clone> ISensor<LSM303DLHCACCELSensorMeasurement> for LSM303DLHCAccelSensor<I> {
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn read(&self) -> Result<LSM303DLHCACCELSensorMeasurement, SensorError> {
+        let mut data = [0u8; 6];
+        I2c::write_read(&mut *self.i2c.borrow_mut(), SensorConstant::LSM303DLHC_ACCEL_ADDRESS, &[OUT_X_L_A], &mut data)
+            .map_err(|error| SensorError::Bus(format!("{:?}", error)))?;
+        Ok(measurement(&data))
+    }
+
+    // Waits for INT1 when it is wired, otherwise reads the latest sample
+    fn read_async(&self) -> Pin<Box<dyn Future<Output = Result<LSM303DLHCACCELSensorMeasurement, SensorError>> + '_>> {
+        Box::pin(async move {
+            if let Some(ready) = &self.ready {
+                ready.lock().await.wait_for_high().await;
+            }
+            // A handle of its own, so no borrow is held across the await
+            let mut i2c = self.i2c.borrow().clone();
+            let mut data = [0u8; 6];
+            AsyncI2c::write_read(&mut i2c, SensorConstant::LSM303DLHC_ACCEL_ADDRESS, &[OUT_X_L_A], &mut data)
+                .await
+                .map_err(|error| SensorError::Bus(format!("{:?}", error)))?;
+            Ok(measurement(&data))
+        })
+    }
+}
+
+impl<I: I2c + AsyncI2c + Clone> LSM303DLHCAccelSensor<I> {
+    pub fn new(urn: String, device_urn: String, location_urn: String, name: String, i2c: I) -> Result<Self, I::Error> {
+        let sensor = Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            name: name,
+            i2c: RefCell::new(i2c),
+            ready: None,
+        };
+        sensor.write(CTRL_REG1_A, HUNDRED_HZ_XYZ)?;
+        sensor.write(CTRL_REG4_A, EIGHT_G_HIGH_RESOLUTION)?;
+        Ok(sensor)
+    }
+
+    // Turns on the data ready interrupt on INT1
+    pub fn set_ready_pin(&mut self, pin: Input<'static>) -> Result<(), I::Error> {
+        self.write(CTRL_REG3_A, I1_DRDY1)?;
+        self.ready = Some(Mutex::new(pin));
+        Ok(())
+    }
+
+    fn write(&self, register: u8, value: u8) -> Result<(), I::Error> {
+        I2c::write(&mut *self.i2c.borrow_mut(), SensorConstant::LSM303DLHC_ACCEL_ADDRESS, &[register, value])
+    }
+}
+
+// Little-endian, 12 bits left-justified in each 16 bit word
+fn measurement(data: &[u8; 6]) -> LSM303DLHCACCELSensorMeasurement {
+    let axis = |low: u8, high: u8| (i16::from_le_bytes([low, high]) >> 4) as f32 * G_PER_COUNT * GRAVITY_MS2;
+    LSM303DLHCACCELSensorMeasurement {
+        x: axis(data[0], data[1]),
+        y: axis(data[2], data[3]),
+        z: axis(data[4], data[5]),
+    }
+}
@@ -1,2 +1,2 @@
 pub mod accel;
-pub mod mag;
\ No newline at end of file
+//pub mod mag;
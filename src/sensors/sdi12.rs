@@ -0,0 +1,99 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use core::fmt::Debug;
+use core::future::Future;
+use core::pin::Pin;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::mutex::Mutex;
+use embedded_hal::digital::OutputPin;
+use embedded_io::ReadReady;
+use embedded_io_async::{Read, Write};
+
+use crate::abstractions::sensor::ISensor;
+use crate::drivers::sdi12::{Sdi12Bus, Sdi12Error};
+use crate::dtos::configurations::sdi12::Sdi12ProbeDTO;
+use crate::dtos::measurement::sensor::sdi12::SDI12SensorMeasurement;
+use crate::enums::sensor_error::SensorError;
+
+impl<E: Debug> From<Sdi12Error<E>> for SensorError {
+    fn from(error: Sdi12Error<E>) -> Self {
+        match error {
+            Sdi12Error::Uart(error) => SensorError::Bus(format!("{:?}", error)),
+            Sdi12Error::Pin => SensorError::Bus(String::from("break pin")),
+            Sdi12Error::Timeout => SensorError::Bus(String::from("no answer")),
+            Sdi12Error::Unexpected(reply) => SensorError::InvalidData(reply),
+        }
+    }
+}
+
+// One probe on the shared SDI-12 bus, configured entirely from
+// `Sdi12ProbeDTO` so a new probe model needs no code. A measurement takes
+// seconds, so the bus is only read asynchronously
+pub struct SDI12Sensor<'a, U: Read + Write + ReadReady, P: OutputPin> {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    bus: &'a Mutex<CriticalSectionRawMutex, Sdi12Bus<U, P>>,
+    probe: Sdi12ProbeDTO,
+}
+
+impl<'a, U: Read + Write + ReadReady, P: OutputPin> ISensor<SDI12SensorMeasurement> for SDI12Sensor<'a, U, P>
+where
+    U::Error: Debug,
+{
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    fn name(&self) -> String {
+        self.probe.name.clone()
+    }
+
+    fn read(&self) -> Result<SDI12SensorMeasurement, SensorError> {
+        Err(SensorError::Bus(String::from("SDI-12 is only read asynchronously")))
+    }
+
+    fn read_async(&self) -> Pin<Box<dyn Future<Output = Result<SDI12SensorMeasurement, SensorError>> + '_>> {
+        Box::pin(async move {
+            let values = self.bus.lock().await.measure(self.probe.address, &self.probe.command).await?;
+            if values.len() < self.probe.fields.len() {
+                return Err(SensorError::InvalidData(format!(
+                    "{} values for {} fields",
+                    values.len(),
+                    self.probe.fields.len()
+                )));
+            }
+            Ok(SDI12SensorMeasurement {
+                values: self.probe.fields.iter().cloned().zip(values).collect(),
+            })
+        })
+    }
+}
+
+impl<'a, U: Read + Write + ReadReady, P: OutputPin> SDI12Sensor<'a, U, P> {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        bus: &'a Mutex<CriticalSectionRawMutex, Sdi12Bus<U, P>>,
+        probe: Sdi12ProbeDTO,
+    ) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            bus: bus,
+            probe: probe,
+        }
+    }
+}
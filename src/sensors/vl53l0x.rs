@@ -1,108 +1,191 @@
-use esp_idf_hal::{
-    delay::Delay,
-    i2c::{I2cConfig, I2cDriver},
-    peripherals::Peripherals,
-};
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt::Debug;
+use core::future::Future;
+use core::pin::Pin;
+
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embassy_sync::mutex::Mutex;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use embassy_time::{Duration, Timer};
+use embedded_hal::i2c::I2c;
+#[cfg(not(test))]
+use esp_hal::gpio::Input;
+use log::info;
 use vl53l0x::VL53L0x;
 
 use crate::abstractions::sensor::ISensor;
-use crate::constants::distance::DistanceConstant;
+use crate::constants::sensor::SensorConstant;
+use crate::dtos::configurations::thresholds::ThresholdsDTO;
 use crate::dtos::measurement::sensor::vl53l0x::VL53L0XSensorMeasurement;
+use crate::enums::sensor_error::SensorError;
+#[cfg(test)]
+use crate::host_test::fake_hal::Input;
+use crate::utilities::thresholds;
+
+#[derive(Debug)]
+pub enum Vl53l0xError<E> {
+    Sensor(vl53l0x::Error<E>),
+    Xshut(usize),
+}
 
-pub struct VL53L0XSensor {
-    pub urn: String,
-    pub device_urn: String,
-    pub location_urn: String,
-    pub name: String,
-    pub sensor: VL53L0x
+pub struct VL53L0XSensor<I: I2c> {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    name: String,
+    sensor: RefCell<VL53L0x<I>>,
+    thresholds: ThresholdsDTO,
+    // GPIO1, pulled low by the sensor when a new range is ready; see
+    // `SensorsConfigDTO::ready_gpios`
+    ready: Option<Mutex<NoopRawMutex, Input<'static>>>,
 }
 
-impl ISensor for VL53L0XSensor  {
-    
+impl<I: I2c> ISensor<VL53L0XSensorMeasurement> for VL53L0XSensor<I> {
     fn urn(&self) -> String {
-        &self.urn
+        self.urn.clone()
     }
 
     fn device_urn(&self) -> String {
-        &self.device_urn
+        self.device_urn.clone()
     }
 
     fn location_urn(&self) -> String {
-        &self.location_urn
+        self.location_urn.clone()
     }
 
     fn name(&self) -> String {
-        &self.name
+        self.name.clone()
     }
 
-    async fn read(&self) -> Result<T, Error> {
-        self._read().await
+    fn read(&self) -> Result<VL53L0XSensorMeasurement, SensorError> {
+        self._read()
     }
-}
 
-impl VL53L0XSensor {
+    // Waits for GPIO1 when it is wired, otherwise polls the ranging status
+    // instead of spinning on it, so a unit that stops answering can be timed
+    // out by the caller
+    fn read_async(&self) -> Pin<Box<dyn Future<Output = Result<VL53L0XSensorMeasurement, SensorError>> + '_>> {
+        Box::pin(async move {
+            if let Some(ready) = &self.ready {
+                ready.lock().await.wait_for_low().await;
+            }
+            loop {
+                let range = self.sensor.borrow_mut().read_range_mm();
+                match range {
+                    Ok(distance_mm) => return Ok(self.measurement(distance_mm)),
+                    Err(nb::Error::WouldBlock) => {
+                        Timer::after(Duration::from_millis(SensorConstant::VL53L0X_POLL_MS)).await;
+                    },
+                    Err(nb::Error::Other(error)) => return Err(sensor_error(error)),
+                }
+            }
+        })
+    }
+}
 
-    fn new(
+impl<I: I2c> VL53L0XSensor<I> {
+    // A single unit left at the default address
+    pub fn new(
         urn: String,
         device_urn: String,
         location_urn: String,
         name: String,
-    ) -> Self {
-        let peripherals = Peripherals::take().unwrap();
-        let sda = peripherals.pins.gpio21;
-        let scl = peripherals.pins.gpio22;
-
-        let config = I2cConfig::new().baudrate(400.kHz().into());
-        let i2c = I2cDriver::new(
-            peripherals.i2c0,
-            sda,
-            scl,
-            &config,
-        );
-
-        let mut sensor: VL53L0x = VL53L0x::new(
-            i2c,
-        ).unwrap();
-
-        Self {
+        i2c: I,
+        thresholds: ThresholdsDTO,
+    ) -> Result<Self, vl53l0x::Error<I::Error>> {
+        let mut sensor = VL53L0x::new(i2c)?;
+        sensor.start_continuous(0)?;
+        Ok(Self {
             urn: urn,
             device_urn: device_urn,
             location_urn: location_urn,
             name: name,
-            sensor: sensor
-        };
+            sensor: RefCell::new(sensor),
+            thresholds: thresholds,
+            ready: None,
+        })
     }
 
-    fn get_distance_status(&self, distance_mm: u16) -> &'static str {
-        match distance_mm {
-            0..=10 => DistanceConstant::TOO_CLOSE,
-            11..=50 => DistanceConstant::VERY_CLOSE, 
-            51..=200 => DistanceConstant::CLOSE,
-            201..=500 => DistanceConstant::NEAR,
-            501..=1000 => DistanceConstant::MEDIUM,
-            1001..=2000 => DistanceConstant::FAR,
-            _ => DistanceConstant::OUT_OF_RANGE,
+    // Every unit powers up at the same address, so all of them are held in
+    // reset through XSHUT and released one at a time, each moved to its own
+    // address before the next one boots. `buses` holds one handle on the
+    // shared bus per XSHUT pin, in the same order; unit n answers on
+    // VL53L0X_FIRST_ADDRESS + n and is named `vl53l0x.n`
+    pub fn bring_up<P: OutputPin, D: DelayNs>(
+        urn: &str,
+        device_urn: &str,
+        location_urn: &str,
+        buses: Vec<I>,
+        xshut: &mut [P],
+        delay: &mut D,
+        thresholds: &ThresholdsDTO,
+    ) -> Result<Vec<Self>, Vl53l0xError<I::Error>> {
+        for (index, pin) in xshut.iter_mut().enumerate() {
+            pin.set_low().map_err(|_| Vl53l0xError::Xshut(index))?;
+        }
+        delay.delay_ms(SensorConstant::VL53L0X_BOOT_MS);
+
+        let mut sensors = Vec::new();
+        for (index, (i2c, pin)) in buses.into_iter().zip(xshut.iter_mut()).enumerate() {
+            pin.set_high().map_err(|_| Vl53l0xError::Xshut(index))?;
+            delay.delay_ms(SensorConstant::VL53L0X_BOOT_MS);
+            let mut sensor = VL53L0x::new(i2c).map_err(Vl53l0xError::Sensor)?;
+            let address = SensorConstant::VL53L0X_FIRST_ADDRESS + index as u8;
+            sensor.set_address(address).map_err(Vl53l0xError::Sensor)?;
+            // Back-to-back ranging, a read only collects the latest result
+            sensor.start_continuous(0).map_err(|error| Vl53l0xError::Sensor(vl53l0x::Error::BusError(error)))?;
+            info!("VL53L0X {} at address {:#04x}", index, address);
+            sensors.push(Self {
+                urn: format!("{}.{}", urn, index),
+                device_urn: device_urn.to_string(),
+                location_urn: location_urn.to_string(),
+                name: key(index),
+                sensor: RefCell::new(sensor),
+                thresholds: thresholds.clone(),
+                ready: None,
+            });
         }
+        Ok(sensors)
     }
 
-    pub async fn _read(&self) -> Result<VL53L0XSensorMeasurement, Error> {
+    fn _read(&self) -> Result<VL53L0XSensorMeasurement, SensorError> {
+        let distance_mm = self.sensor.borrow_mut().read_range_continuous_millimeters_blocking().map_err(sensor_error)?;
+        Ok(self.measurement(distance_mm))
+    }
 
-        let measurement: VL53L0XSensorMeasurement = match self.sensor.read_range_single_millimeters_blocking () {
-            Ok(distance_mm) => {
-                let status = self.get_distance_status(distance_mm);
-                VL53L0XSensorMeasurement{
-                    distance_mm: distance_mm,
-                    status: status
-                }
-            },
-            Err(e) => {
-                VL53L0XSensorMeasurement{
-                    distance_mm: i32::MAX,
-                    status: DistanceConstant::UNKNOWN
-                }
-            }
-        };
-        Ok(measurement)
+    fn measurement(&self, distance_mm: u16) -> VL53L0XSensorMeasurement {
+        VL53L0XSensorMeasurement {
+            distance_mm: distance_mm as f32,
+            status: self.get_distance_status(distance_mm),
+        }
+    }
+
+    // The driver configures GPIO1 as an active low new-sample interrupt and
+    // clears it with every read
+    pub fn set_ready_pin(&mut self, pin: Input<'static>) {
+        self.ready = Some(Mutex::new(pin));
     }
-    
-}
\ No newline at end of file
+
+    // Applies remotely updated `SensorsConfigDTO::distance_thresholds_mm`
+    pub fn set_thresholds(&mut self, thresholds: ThresholdsDTO) {
+        self.thresholds = thresholds;
+    }
+
+    fn get_distance_status(&self, distance_mm: u16) -> String {
+        thresholds::classify(&self.thresholds, distance_mm as f64).to_string()
+    }
+}
+
+fn sensor_error<E: Debug>(error: vl53l0x::Error<E>) -> SensorError {
+    SensorError::Bus(format!("{:?}", error))
+}
+
+// Factory key of the unit behind the n-th XSHUT pin
+pub fn key(index: usize) -> String {
+    format!("{}.{}", SensorConstant::VL5310X, index)
+}
@@ -0,0 +1,135 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+
+use embassy_time::Instant;
+use log::debug;
+
+use crate::constants::activity::ActivityConstant;
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::enums::activity_level::ActivityLevel;
+use crate::enums::value::Value;
+use crate::services::envelope;
+
+// Pedometer and activity level for the asset-tracking variant, worn or
+// carried. Steps are peaks in |a| with hysteresis and a minimum interval;
+// the level of a window comes from its cadence, or from how often the
+// device moved at all when it was not walking. Feed it every accelerometer
+// sample at `ActivityConstant::SAMPLE_RATE_HZ` and publish `summary` with
+// the periodic measurements; the raw stream never leaves the device
+pub struct ActivityService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    // Armed once |a| dropped below the low mark, a step fires it
+    armed: bool,
+    last_step_ms: Option<u64>,
+    window_start_ms: u64,
+    window_samples: u32,
+    window_moving: u32,
+    window_steps: u32,
+    total_steps: u32,
+}
+
+impl ActivityService {
+    pub fn new(urn: String, device_urn: String, location_urn: String) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            armed: false,
+            last_step_ms: None,
+            window_start_ms: Instant::now().as_millis(),
+            window_samples: 0,
+            window_moving: 0,
+            window_steps: 0,
+            total_steps: 0,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    pub fn total_steps(&self) -> u32 {
+        self.total_steps
+    }
+
+    // One sample in m/s²
+    pub fn update(&mut self, x: f32, y: f32, z: f32) {
+        let now_ms = Instant::now().as_millis();
+        // Compared squared, there is no sqrt in core
+        let magnitude_squared = x * x + y * y + z * z;
+
+        self.window_samples = self.window_samples.saturating_add(1);
+        let low = ActivityConstant::GRAVITY_MS2 - ActivityConstant::MOTION_MS2;
+        let high = ActivityConstant::GRAVITY_MS2 + ActivityConstant::MOTION_MS2;
+        if magnitude_squared < low * low || magnitude_squared > high * high {
+            self.window_moving = self.window_moving.saturating_add(1);
+        }
+
+        if magnitude_squared < ActivityConstant::STEP_LOW_MS2 * ActivityConstant::STEP_LOW_MS2 {
+            self.armed = true;
+        } else if self.armed && magnitude_squared > ActivityConstant::STEP_HIGH_MS2 * ActivityConstant::STEP_HIGH_MS2 {
+            self.armed = false;
+            let too_soon = self
+                .last_step_ms
+                .is_some_and(|last_ms| now_ms - last_ms < ActivityConstant::MIN_STEP_INTERVAL_MS);
+            if !too_soon {
+                self.last_step_ms = Some(now_ms);
+                self.window_steps = self.window_steps.saturating_add(1);
+                self.total_steps = self.total_steps.saturating_add(1);
+            }
+        }
+    }
+
+    // Aggregate since the previous summary, which starts a new window
+    pub fn summary(&mut self) -> MeasurementEnvelopeDTO {
+        let now_ms = Instant::now().as_millis();
+        let window_ms = now_ms - self.window_start_ms;
+        let level = self.level(window_ms);
+        debug!(
+            "Activity over {} ms: {} steps, {}/{} samples moving, {}",
+            window_ms,
+            self.window_steps,
+            self.window_moving,
+            self.window_samples,
+            level.as_str()
+        );
+
+        let mut data = BTreeMap::new();
+        data.insert(ActivityConstant::STEPS.to_string(), Value::Integer(self.window_steps as i32));
+        data.insert(ActivityConstant::STEPS_TOTAL.to_string(), Value::Integer(self.total_steps as i32));
+        data.insert(ActivityConstant::ACTIVITY.to_string(), Value::String(level.as_str().to_string()));
+        data.insert(ActivityConstant::WINDOW_S.to_string(), Value::Integer((window_ms / 1000) as i32));
+
+        self.window_start_ms = now_ms;
+        self.window_samples = 0;
+        self.window_moving = 0;
+        self.window_steps = 0;
+        envelope::measurement(&self.device_urn, &self.location_urn, data)
+    }
+
+    fn level(&self, window_ms: u64) -> ActivityLevel {
+        if window_ms > 0 {
+            let steps_per_min = self.window_steps as u64 * 60_000 / window_ms;
+            if steps_per_min >= ActivityConstant::ACTIVE_STEPS_PER_MIN as u64 {
+                return ActivityLevel::Active;
+            }
+        }
+        let moving = self.window_samples > 0
+            && self.window_moving as f32 / self.window_samples as f32 >= ActivityConstant::MOTION_FRACTION;
+        if self.window_steps > 0 || moving {
+            ActivityLevel::Light
+        } else {
+            ActivityLevel::Idle
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+
+use crate::constants::aqi::AqiConstant;
+use crate::dtos::configurations::aqi::AqiConfigDTO;
+use crate::enums::value::Value;
+use crate::utilities::aqi;
+
+// Combines PM2.5, PM10 and CO2 into one air quality index on the device, so
+// the display and local alerts work without the backend. The inputs usually
+// come from different sensors, so this runs on a cycle's merged readings
+// rather than in a per-sensor pipeline
+pub struct AirQualityService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    config: AqiConfigDTO,
+}
+
+impl AirQualityService {
+    pub fn new(urn: String, device_urn: String, location_urn: String, config: AqiConfigDTO) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            config: config,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // Adds `aqi` and `aqi_category` when any input is present. Returns the
+    // index for the display and alert rules
+    pub fn derive(&self, data: &mut BTreeMap<String, Value>) -> Option<u16> {
+        let field = |name: &str| data.get(name).and_then(|value| value.as_f32());
+        let (index, category) = aqi::compute(
+            &self.config,
+            field(AqiConstant::PM2_5),
+            field(AqiConstant::PM10),
+            field(AqiConstant::CO2),
+        )?;
+        let category = category.to_string();
+        data.insert(AqiConstant::AQI.to_string(), Value::Integer(index as i32));
+        data.insert(AqiConstant::CATEGORY.to_string(), Value::String(category));
+        Some(index)
+    }
+}
@@ -0,0 +1,185 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use log::warn;
+
+use crate::constants::audio::AudioConstant;
+use crate::dtos::event::envelope::EventEnvelopeDTO;
+use crate::enums::event_kind::EventKind;
+use crate::enums::value::Value;
+use crate::services::{clock, message_id};
+use crate::utilities::goertzel;
+
+// Listens to the I2S microphone for the signatures of breaking glass and of
+// a sounding smoke alarm. Audio is analysed frame by frame and dropped right
+// away: only the resulting events ever leave the device, never samples.
+// Feed it `AudioConstant::FRAME_SAMPLES` of mono PCM at a time as they come
+// off the I2S DMA; timing follows the sample count, not the clock
+pub struct AudioEventService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    // Detectors enabled, `EventKind::GlassBreak` and/or `EventKind::SmokeAlarm`
+    detectors: Vec<EventKind>,
+    // Frequencies probed for a sounder, `ALARM_STEP_HZ` apart
+    alarm_bins: Vec<f32>,
+    // Audio time in ms since the first frame
+    position_ms: u64,
+    background: f32,
+    // Start of the tone currently heard, end of the last beep and the beeps
+    // of the current temporal-three sequence so far
+    tone_since_ms: Option<u64>,
+    beep_ended_ms: Option<u64>,
+    beeps: u8,
+    tone_hz: f32,
+    // When the last low frequency thud was heard
+    thud_ms: Option<u64>,
+    last_event_ms: BTreeMap<&'static str, u64>,
+}
+
+impl AudioEventService {
+    pub fn new(urn: String, device_urn: String, location_urn: String, detectors: Vec<EventKind>) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            detectors: detectors,
+            alarm_bins: (0..)
+                .map(|step| AudioConstant::ALARM_LOW_HZ + step as f32 * AudioConstant::ALARM_STEP_HZ)
+                .take_while(|frequency| *frequency <= AudioConstant::ALARM_HIGH_HZ)
+                .collect(),
+            position_ms: 0,
+            background: AudioConstant::MIN_LEVEL,
+            tone_since_ms: None,
+            beep_ended_ms: None,
+            beeps: 0,
+            tone_hz: 0.0,
+            thud_ms: None,
+            last_event_ms: BTreeMap::new(),
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    pub fn process(&mut self, frame: &[i16]) -> Option<EventEnvelopeDTO> {
+        self.position_ms += frame.len() as u64 * 1000 / AudioConstant::SAMPLE_RATE_HZ as u64;
+        let level = goertzel::mean_square(frame);
+
+        let mut found = None;
+        if self.detectors.contains(&EventKind::SmokeAlarm) {
+            found = self.smoke_alarm(frame, level);
+        }
+        if found.is_none() && self.detectors.contains(&EventKind::GlassBreak) {
+            found = self.glass_break(frame, level);
+        }
+        if level < self.background * AudioConstant::GLASS_ONSET_RATIO {
+            self.background += AudioConstant::BACKGROUND_ALPHA * (level - self.background);
+            self.background = self.background.max(AudioConstant::MIN_LEVEL);
+        }
+
+        let (kind, detail) = found?;
+        if self
+            .last_event_ms
+            .get(kind.as_str())
+            .is_some_and(|last_ms| self.position_ms - last_ms < AudioConstant::HOLDOFF_MS)
+        {
+            return None;
+        }
+        self.last_event_ms.insert(kind.as_str(), self.position_ms);
+        warn!("Audio event: {}", kind.as_str());
+        let (timestamp, relative_ms_since_boot) = clock::stamp();
+        Some(EventEnvelopeDTO {
+            id: message_id::next(),
+            device_urn: self.device_urn.clone(),
+            location_urn: self.location_urn.clone(),
+            sensor_urn: self.urn.clone(),
+            timestamp: timestamp,
+            relative_ms_since_boot: relative_ms_since_boot,
+            kind: kind,
+            detail: detail,
+        })
+    }
+
+    // Counts beeps of a steady tone in the sounder band and reports the
+    // third one of a temporal-three pattern
+    fn smoke_alarm(&mut self, frame: &[i16], level: f32) -> Option<(EventKind, BTreeMap<String, Value>)> {
+        let now_ms = self.position_ms;
+        // A pure tone concentrates N² / 2 * mean square in its bin
+        let full = frame.len() as f32 * frame.len() as f32 / 2.0 * level;
+        let (tone_hz, power) = self
+            .alarm_bins
+            .iter()
+            .map(|frequency| (*frequency, goertzel::power(frame, AudioConstant::SAMPLE_RATE_HZ, *frequency)))
+            .fold((0.0, 0.0), |best, bin| if bin.1 > best.1 { bin } else { best });
+        let tone = level >= AudioConstant::MIN_LEVEL && power >= full * AudioConstant::ALARM_TONE_RATIO;
+
+        if tone {
+            if self.tone_since_ms.is_none() {
+                if self.beep_ended_ms.is_none_or(|ended_ms| now_ms - ended_ms > AudioConstant::ALARM_GAP_MAX_MS) {
+                    self.beeps = 0;
+                }
+                self.tone_since_ms = Some(now_ms);
+            }
+            self.tone_hz = tone_hz;
+            return None;
+        }
+        let since_ms = self.tone_since_ms.take()?;
+        let beep_ms = now_ms - since_ms;
+        if !(AudioConstant::ALARM_BEEP_MIN_MS..=AudioConstant::ALARM_BEEP_MAX_MS).contains(&beep_ms) {
+            self.beeps = 0;
+            return None;
+        }
+        self.beep_ended_ms = Some(now_ms);
+        self.beeps += 1;
+        if self.beeps < AudioConstant::ALARM_BEEPS {
+            return None;
+        }
+        self.beeps = 0;
+        let mut detail = BTreeMap::new();
+        detail.insert(AudioConstant::FREQUENCY_HZ.to_string(), Value::Float(self.tone_hz));
+        detail.insert(AudioConstant::BEEPS.to_string(), Value::Integer(AudioConstant::ALARM_BEEPS as i32));
+        Some((EventKind::SmokeAlarm, detail))
+    }
+
+    // A loud onset dominated by low frequencies arms the detector, a loud
+    // one dominated by high frequencies shortly after fires it
+    fn glass_break(&mut self, frame: &[i16], level: f32) -> Option<(EventKind, BTreeMap<String, Value>)> {
+        let now_ms = self.position_ms;
+        let onset_ratio = level / self.background;
+        if level < AudioConstant::MIN_LEVEL || onset_ratio < AudioConstant::GLASS_ONSET_RATIO {
+            return None;
+        }
+        let band = |frequencies: &[f32]| -> f32 {
+            frequencies
+                .iter()
+                .map(|frequency| goertzel::power(frame, AudioConstant::SAMPLE_RATE_HZ, *frequency))
+                .sum()
+        };
+        let low = band(&AudioConstant::GLASS_LOW_HZ);
+        let high = band(&AudioConstant::GLASS_HIGH_HZ);
+        if low > high {
+            self.thud_ms = Some(now_ms);
+            return None;
+        }
+        if high < low * AudioConstant::GLASS_HIGH_TO_LOW {
+            return None;
+        }
+        self.thud_ms
+            .take()
+            .filter(|thud_ms| now_ms - thud_ms <= AudioConstant::GLASS_WINDOW_MS)?;
+        let mut detail = BTreeMap::new();
+        detail.insert(AudioConstant::ONSET_RATIO.to_string(), Value::Float(onset_ratio));
+        Some((EventKind::GlassBreak, detail))
+    }
+}
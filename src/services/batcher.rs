@@ -0,0 +1,124 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use embassy_futures::select::{select3, Either3};
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::channel::Receiver;
+use embassy_time::{Duration, Instant, Timer};
+
+use crate::abstractions::transport::ITransport;
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::enums::priority::Priority;
+use crate::services::{event_outbox, heartbeat};
+use crate::services::uploader::UploaderService;
+
+// What the sensing loop puts on the measurement channel
+#[derive(Debug, Clone)]
+pub struct BatchItem {
+    pub envelope: MeasurementEnvelopeDTO,
+    // Alerts skip the wait and flush everything pending with them
+    pub priority: Priority,
+}
+
+// Collects envelopes until `max_records` are pending or the oldest has waited
+// `max_age`, so the uploader sends one request per batch instead of per reading
+pub struct BatcherService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    max_records: usize,
+    max_age: Duration,
+    pending: Vec<MeasurementEnvelopeDTO>,
+    // Highest class among the pending envelopes, the batch is queued as that
+    priority: Priority,
+    opened_at: Option<Instant>,
+}
+
+impl BatcherService {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        max_records: usize,
+        max_age: Duration,
+    ) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            max_records: max_records.max(1),
+            max_age: max_age,
+            pending: Vec::with_capacity(max_records),
+            priority: Priority::Periodic,
+            opened_at: None,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    // Returns a batch when this item completes one
+    pub fn push(&mut self, item: BatchItem) -> Option<(Vec<MeasurementEnvelopeDTO>, Priority)> {
+        if self.pending.is_empty() {
+            self.opened_at = Some(Instant::now());
+        }
+        self.pending.push(item.envelope);
+        self.priority = self.priority.max(item.priority);
+        if item.priority == Priority::Alert || self.pending.len() >= self.max_records {
+            return self.take();
+        }
+        None
+    }
+
+    // When the current batch must go out even if it never fills up
+    pub fn deadline(&self) -> Option<Instant> {
+        self.opened_at.map(|opened_at| opened_at + self.max_age)
+    }
+
+    pub fn take(&mut self) -> Option<(Vec<MeasurementEnvelopeDTO>, Priority)> {
+        self.opened_at = None;
+        let priority = core::mem::replace(&mut self.priority, Priority::Periodic);
+        if self.pending.is_empty() {
+            return None;
+        }
+        let batch = core::mem::replace(&mut self.pending, Vec::with_capacity(self.max_records));
+        Some((batch, priority))
+    }
+
+    // Drives the batcher between the measurement channel and the uploader.
+    // Events from the outbox are not batched, they go up as they come
+    pub async fn run<T: ITransport, const N: usize>(
+        &mut self,
+        receiver: Receiver<'_, CriticalSectionRawMutex, BatchItem, N>,
+        uploader: &mut UploaderService<T>,
+    ) -> ! {
+        loop {
+            let deadline = self.deadline().unwrap_or(Instant::MAX);
+            let batch = match select3(receiver.receive(), event_outbox::next(), Timer::at(deadline)).await {
+                Either3::First(item) => self.push(item),
+                Either3::Second(event) => {
+                    uploader.upload_event(&event).await;
+                    None
+                },
+                Either3::Third(_) => self.take(),
+            };
+            if let Some((batch, priority)) = batch {
+                uploader.upload_batch(&batch, priority).await;
+            }
+            heartbeat::update(|heartbeat| uploader.report(heartbeat));
+        }
+    }
+}
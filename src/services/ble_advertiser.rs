@@ -0,0 +1,86 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use core::error::Error;
+use core::fmt::Debug;
+
+use embedded_io_async::Write;
+use esp_wifi::ble::controller::BleConnector;
+use log::{debug, info};
+
+use crate::constants::ble::BleConstant;
+use crate::enums::value::Value;
+use crate::utilities::{bthome, hci};
+
+// Broadcasts the latest readings as BTHome advertisements, so a phone app
+// or a Home Assistant BLE proxy can read them with no network at all.
+// Non-connectable and unencrypted: anyone in range can read the values
+pub struct BleAdvertiserService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    connector: BleConnector<'static>,
+    // Lets receivers drop the repeats of an unchanged advertisement
+    packet_id: u8,
+    advertising: bool,
+}
+
+impl BleAdvertiserService {
+    pub fn new(urn: String, device_urn: String, location_urn: String, connector: BleConnector<'static>) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            connector: connector,
+            packet_id: 0,
+            advertising: false,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // Call once per sensing cycle with the merged readings; advertising
+    // starts with the first update
+    pub async fn update(&mut self, data: &BTreeMap<String, Value>) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.packet_id = self.packet_id.wrapping_add(1);
+        let advertisement = bthome::encode(data, self.packet_id);
+        debug!("BLE advertisement of {} bytes", advertisement.len());
+        self.connector
+            .write_all(&hci::set_advertising_data(&advertisement))
+            .await
+            .map_err(ble_error)?;
+        if !self.advertising {
+            self.connector
+                .write_all(&hci::set_advertising_parameters(BleConstant::ADVERTISING_INTERVAL))
+                .await
+                .map_err(ble_error)?;
+            self.connector.write_all(&hci::set_advertise_enable(true)).await.map_err(ble_error)?;
+            self.advertising = true;
+            info!("BLE advertising started");
+        }
+        Ok(())
+    }
+
+    pub async fn stop(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.advertising {
+            self.connector.write_all(&hci::set_advertise_enable(false)).await.map_err(ble_error)?;
+            self.advertising = false;
+        }
+        Ok(())
+    }
+}
+
+fn ble_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("BLE error: {:?}", error))
+}
@@ -0,0 +1,193 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::Debug;
+
+use embassy_time::{with_timeout, Duration, Instant};
+use embedded_io_async::{Read, Write};
+use esp_wifi::ble::controller::BleConnector;
+use log::{info, warn};
+
+use crate::abstractions::service::IService;
+use crate::constants::ble::BleConstant;
+use crate::dtos::event::envelope::EventEnvelopeDTO;
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::enums::event_kind::EventKind;
+use crate::enums::value::Value;
+use crate::services::{clock, envelope, event_outbox, message_id};
+use crate::utilities::{hci, hex};
+
+struct BeaconState {
+    rssi: i8,
+    last_seen_ms: u64,
+    present: bool,
+}
+
+// BLE observer for tagged equipment: scans passively and tracks only the
+// configured beacon MACs, iBeacon or Eddystone. Arrival is the first
+// advertisement heard, departure `absent_after_s` without one
+pub struct BleScannerService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    connector: BleConnector<'static>,
+    // Start of a packet the previous read cut off
+    pending: Vec<u8>,
+    absent_after_ms: u64,
+    beacons: BTreeMap<[u8; 6], BeaconState>,
+}
+
+// Supervised with a period of zero, each run waits up to `POLL_MS` for
+// advertisements
+impl IService for BleScannerService {
+    type Response = ();
+
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    async fn run(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        for event in self.poll().await {
+            event_outbox::raise(event);
+        }
+        Ok(())
+    }
+}
+
+impl BleScannerService {
+    pub async fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        mut connector: BleConnector<'static>,
+        beacons: &[[u8; 6]],
+        absent_after_s: u64,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        connector
+            .write_all(&hci::set_scan_parameters(BleConstant::SCAN_INTERVAL, BleConstant::SCAN_WINDOW))
+            .await
+            .map_err(ble_error)?;
+        connector.write_all(&hci::set_scan_enable(true)).await.map_err(ble_error)?;
+        info!("BLE scan started for {} beacons", beacons.len());
+
+        Ok(Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            connector: connector,
+            pending: Vec::new(),
+            absent_after_ms: absent_after_s * 1000,
+            beacons: beacons
+                .iter()
+                .map(|address| {
+                    (
+                        *address,
+                        BeaconState {
+                            rssi: 0,
+                            last_seen_ms: 0,
+                            present: false,
+                        },
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    // Waits up to `POLL_MS` for advertisements, call in a loop. Returns the
+    // arrivals and departures since the previous call
+    pub async fn poll(&mut self) -> Vec<EventEnvelopeDTO> {
+        let mut events = Vec::new();
+        let mut packet = vec![0u8; BleConstant::HCI_READ_BYTES];
+        match with_timeout(Duration::from_millis(BleConstant::POLL_MS), self.connector.read(&mut packet)).await {
+            Ok(Ok(length)) => {
+                let now_ms = Instant::now().as_millis();
+                self.pending.extend_from_slice(&packet[..length]);
+                let (reports, parsed) = hci::parse_advertising_reports(&self.pending);
+                self.pending.drain(..parsed);
+                for report in reports {
+                    let kind = hci::beacon_kind(&report.data);
+                    let beacon = match self.beacons.get_mut(&report.address) {
+                        Some(beacon) => beacon,
+                        None => continue,
+                    };
+                    beacon.rssi = report.rssi;
+                    beacon.last_seen_ms = now_ms;
+                    if !beacon.present {
+                        beacon.present = true;
+                        info!("Beacon {} arrived at {} dBm", hex::mac_to_string(&report.address), report.rssi);
+                        events.push(self.event(EventKind::BeaconArrived, &report.address, kind));
+                    }
+                }
+            },
+            Ok(Err(error)) => warn!("BLE read failed: {:?}", error),
+            Err(_) => {},
+        }
+
+        let now_ms = Instant::now().as_millis();
+        let departed: Vec<[u8; 6]> = self
+            .beacons
+            .iter_mut()
+            .filter(|(_, beacon)| beacon.present && now_ms - beacon.last_seen_ms > self.absent_after_ms)
+            .map(|(address, beacon)| {
+                beacon.present = false;
+                *address
+            })
+            .collect();
+        for address in departed {
+            info!("Beacon {} departed", hex::mac_to_string(&address));
+            events.push(self.event(EventKind::BeaconDeparted, &address, None));
+        }
+        events
+    }
+
+    // RSSI of each beacon in range, published with the periodic measurements
+    pub fn gauge(&self) -> MeasurementEnvelopeDTO {
+        let mut data = BTreeMap::new();
+        let mut present = 0;
+        for (address, beacon) in self.beacons.iter().filter(|(_, beacon)| beacon.present) {
+            let key = format!("{}{}", BleConstant::RSSI_PREFIX, hex::mac_to_string(address).replace(':', ""));
+            data.insert(key, Value::Integer(beacon.rssi as i32));
+            present += 1;
+        }
+        data.insert(BleConstant::PRESENT.to_string(), Value::Integer(present));
+        envelope::measurement(&self.device_urn, &self.location_urn, data)
+    }
+
+    fn event(&self, kind: EventKind, address: &[u8; 6], beacon: Option<&'static str>) -> EventEnvelopeDTO {
+        let mut detail = BTreeMap::new();
+        detail.insert(BleConstant::ADDRESS.to_string(), Value::String(hex::mac_to_string(address)));
+        if let Some(state) = self.beacons.get(address) {
+            detail.insert(BleConstant::RSSI.to_string(), Value::Integer(state.rssi as i32));
+        }
+        if let Some(beacon) = beacon {
+            detail.insert(BleConstant::BEACON.to_string(), Value::String(beacon.to_string()));
+        }
+        let (timestamp, relative_ms_since_boot) = clock::stamp();
+        EventEnvelopeDTO {
+            id: message_id::next(),
+            device_urn: self.device_urn.clone(),
+            location_urn: self.location_urn.clone(),
+            sensor_urn: self.urn.clone(),
+            timestamp: timestamp,
+            relative_ms_since_boot: relative_ms_since_boot,
+            kind: kind,
+            detail: detail,
+        }
+    }
+}
+
+fn ble_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("BLE error: {:?}", error))
+}
@@ -0,0 +1,91 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::cell::RefCell;
+use core::error::Error;
+use core::fmt::Debug;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+use log::{info, warn};
+
+use crate::constants::hardware::HardwareConstant;
+use crate::drivers::eeprom_24cxx::Eeprom24Cxx;
+use crate::dtos::configurations::board_identity::BoardIdentityDTO;
+use crate::dtos::response::server::command::ServerCommandResponseDTO;
+use crate::enums::transport_error::TransportError;
+use crate::utilities::board_identity;
+
+// Serial number, revision and factory calibration from the board EEPROM.
+// `load` runs at boot after `hardware_profile::detect` and before the
+// config is built, `Config::new` then merges the calibration in. Boards
+// before rev D, or never programmed, have none and keep the build time
+// values
+static IDENTITY: Mutex<CriticalSectionRawMutex, RefCell<Option<BoardIdentityDTO>>> = Mutex::new(RefCell::new(None));
+
+pub fn load<I: I2c, D: DelayNs>(eeprom: &mut Eeprom24Cxx<I, D>) -> Option<BoardIdentityDTO> {
+    let mut block = [0u8; HardwareConstant::EEPROM_IDENTITY_LENGTH];
+    let identity = match eeprom.read(0, &mut block) {
+        Ok(()) => board_identity::decode(&block),
+        Err(error) => {
+            warn!("Board EEPROM unreadable: {:?}", error);
+            None
+        },
+    };
+    match &identity {
+        Some(identity) => info!("Board serial {} rev {}", identity.serial, identity.revision.as_str()),
+        None => info!("No board identity programmed"),
+    }
+    IDENTITY.lock(|current| *current.borrow_mut() = identity.clone());
+    identity
+}
+
+pub fn identity() -> Option<BoardIdentityDTO> {
+    IDENTITY.lock(|current| current.borrow().clone())
+}
+
+pub fn serial() -> Option<String> {
+    IDENTITY.lock(|current| current.borrow().as_ref().map(|identity| identity.serial.clone()))
+}
+
+// Production line programming: writes the whole block, reads it back and
+// takes it as the running identity. The revision letter shares offset 0
+// with `hardware_profile`, so it takes effect on the next boot
+pub fn program<I: I2c, D: DelayNs>(
+    eeprom: &mut Eeprom24Cxx<I, D>,
+    identity: &BoardIdentityDTO,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let block = board_identity::encode(identity);
+    eeprom.write(0, &block).map_err(board_identity_error)?;
+    let mut written = [0u8; HardwareConstant::EEPROM_IDENTITY_LENGTH];
+    eeprom.read(0, &mut written).map_err(board_identity_error)?;
+    if written != block {
+        return Err(board_identity_error("read back mismatch"));
+    }
+    let verified = board_identity::decode(&written).ok_or_else(|| board_identity_error("read back undecodable"))?;
+    info!("Programmed board serial {} rev {}", verified.serial, verified.revision.as_str());
+    IDENTITY.lock(|current| *current.borrow_mut() = Some(verified));
+    Ok(())
+}
+
+// {"command":"program_identity","argument":"serial=SP-0001234,revision=D"},
+// sent by the line station over the serial console, see `CliService`
+pub fn handle_command<I: I2c, D: DelayNs>(
+    command: &ServerCommandResponseDTO,
+    eeprom: &mut Eeprom24Cxx<I, D>,
+) -> Option<Result<(), TransportError>> {
+    if command.command != "program_identity" {
+        return None;
+    }
+    let argument = command.argument.as_deref().unwrap_or("");
+    let Some(identity) = board_identity::parse(argument) else {
+        return Some(Err(TransportError::Rejected(format!("Invalid board identity: {}", argument))));
+    };
+    Some(program(eeprom, &identity).map_err(|error| TransportError::Io(error.to_string())))
+}
+
+fn board_identity_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("Board identity error: {:?}", error))
+}
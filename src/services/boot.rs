@@ -0,0 +1,117 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use core::cell::RefCell;
+use core::error::Error;
+use core::fmt::Debug;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+use littlefs2::fs::Filesystem;
+use littlefs2::path::PathBuf;
+use log::{info, warn};
+
+use crate::constants::storage::StorageConstant;
+use crate::dtos::telemetry::heartbeat::HeartbeatDTO;
+use crate::enums::boot_stage::BootStage;
+use crate::utilities::flash_partition::FlashPartition;
+use crate::utilities::state_file;
+
+struct BootState {
+    stage: BootStage,
+    // Why the boot is stuck at `stage`, from `fail`
+    error: Option<String>,
+    // Furthest stage of the previous boot, read back from flash
+    previous: Option<BootStage>,
+}
+
+// Boot progress, driving the status LED and reported in the heartbeat. Each
+// stage is also written to flash, so a device that resets before it ever
+// gets online reports how far that boot got once one does
+static BOOT: Mutex<CriticalSectionRawMutex, RefCell<BootState>> = Mutex::new(RefCell::new(BootState {
+    stage: BootStage::Reset,
+    error: None,
+    previous: None,
+}));
+
+// Call once, right after the config is loaded
+pub fn init() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    let previous = Filesystem::mount_and_then(&mut storage, |fs| {
+        let mut bytes = [0u8; state_file::HEADER_BYTES + 1];
+        let read = fs
+            .open_file_and_then(&PathBuf::from(StorageConstant::BOOT_STAGE_FILE), |file| file.read(&mut bytes))
+            .unwrap_or(0);
+        Ok(match state_file::decode(&bytes[..read], Some(1)) {
+            Some((StorageConstant::BOOT_STAGE_VERSION, [index])) => BootStage::from_index(*index),
+            other => state_file::unsupported(StorageConstant::BOOT_STAGE_FILE, other),
+        })
+    })
+    .map_err(boot_error)?;
+    if let Some(previous) = previous.filter(|previous| *previous != BootStage::Running) {
+        warn!("Previous boot stopped at {}", previous.as_str());
+    }
+    BOOT.lock(|state| state.borrow_mut().previous = previous);
+    advance(BootStage::ConfigLoaded);
+    Ok(())
+}
+
+// Stages only move forward; skipping one (e.g. NET_UP on an offline logger)
+// is allowed and logged
+pub fn advance(stage: BootStage) {
+    let current = self::stage();
+    if stage <= current {
+        return;
+    }
+    if stage.index() > current.index() + 1 {
+        info!("Boot stage {} -> {}, skipping ahead", current.as_str(), stage.as_str());
+    } else {
+        info!("Boot stage {}", stage.as_str());
+    }
+    BOOT.lock(|state| {
+        let mut state = state.borrow_mut();
+        state.stage = stage;
+        state.error = None;
+    });
+    if let Err(error) = persist(stage) {
+        warn!("Failed to persist boot stage: {}", error);
+    }
+}
+
+// Records why the next stage cannot be reached, the stage itself stays
+pub fn fail(error: &str) {
+    warn!("Boot stuck at {}: {}", stage().as_str(), error);
+    BOOT.lock(|state| state.borrow_mut().error = Some(String::from(error)));
+}
+
+pub fn stage() -> BootStage {
+    BOOT.lock(|state| state.borrow().stage)
+}
+
+pub fn has_failed() -> bool {
+    BOOT.lock(|state| state.borrow().error.is_some())
+}
+
+pub fn report(heartbeat: &mut HeartbeatDTO) {
+    BOOT.lock(|state| {
+        let state = state.borrow();
+        heartbeat.boot_stage = state.stage;
+        heartbeat.boot_error = state.error.clone();
+        heartbeat.previous_boot_stage = state.previous;
+    });
+}
+
+fn persist(stage: BootStage) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    Filesystem::mount_and_then(&mut storage, |fs| {
+        fs.write(
+            &PathBuf::from(StorageConstant::BOOT_STAGE_FILE),
+            &state_file::encode(StorageConstant::BOOT_STAGE_VERSION, &[stage.index()]),
+        )
+    })
+    .map_err(boot_error)
+}
+
+fn boot_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("Boot stage error: {:?}", error))
+}
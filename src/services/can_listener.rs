@@ -0,0 +1,104 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use embedded_can::nb::Can;
+use embedded_can::{Frame, Id};
+use log::{debug, warn};
+
+use crate::constants::can::CanConstant;
+use crate::dtos::configurations::can::CanSignalDTO;
+use crate::enums::value::Value;
+use crate::utilities::can;
+
+// Listens on the TWAI controller and decodes the configured signals out of
+// the frames that carry them. Only the latest value of each signal is kept;
+// the sensing cycle merges `values` into its data so they go through the
+// pipelines like any other reading
+pub struct CanListenerService<C: Can> {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    can: C,
+    signals: Vec<CanSignalDTO>,
+    latest: BTreeMap<String, Value>,
+    frames: u32,
+}
+
+impl<C: Can> CanListenerService<C>
+where
+    C::Error: Debug,
+{
+    pub fn new(urn: String, device_urn: String, location_urn: String, can: C, signals: Vec<CanSignalDTO>) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            can: can,
+            signals: signals,
+            latest: BTreeMap::new(),
+            frames: 0,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // Frames that carried at least one configured signal
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    // Drains what the controller has buffered, call often enough that its
+    // receive FIFO does not overrun on a busy bus
+    pub fn poll(&mut self) {
+        for _ in 0..CanConstant::POLL_BUDGET {
+            let frame = match self.can.receive() {
+                Ok(frame) => frame,
+                Err(nb::Error::WouldBlock) => return,
+                Err(nb::Error::Other(error)) => {
+                    warn!("CAN receive failed: {:?}", error);
+                    return;
+                },
+            };
+            if frame.is_remote_frame() {
+                continue;
+            }
+            let (id, extended) = match frame.id() {
+                Id::Standard(id) => (id.as_raw() as u32, false),
+                Id::Extended(id) => (id.as_raw(), true),
+            };
+            let mut matched = false;
+            // Under the signal's mask, so frames from every source address
+            // of a filtered PGN carry it
+            let carries = |signal: &&CanSignalDTO| (signal.id ^ id) & signal.id_mask == 0 && signal.extended == extended;
+            for signal in self.signals.iter().filter(carries) {
+                matched = true;
+                match can::extract(frame.data(), signal) {
+                    Some(value) => {
+                        self.latest.insert(signal.field.clone(), Value::Float(value));
+                    },
+                    None => debug!("CAN frame {:#x} too short for {}", id, signal.field),
+                }
+            }
+            if matched {
+                self.frames = self.frames.wrapping_add(1);
+            }
+        }
+    }
+
+    // Latest value of every signal seen so far
+    pub fn values(&self) -> BTreeMap<String, Value> {
+        self.latest.clone()
+    }
+}
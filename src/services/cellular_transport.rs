@@ -0,0 +1,144 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::error::Error;
+use core::fmt::Debug;
+
+use embedded_io_async::{Read, Write};
+
+use crate::abstractions::transport::ITransport;
+use crate::constants::http::HttpConstant;
+use crate::drivers::at_modem::{AtModem, SignalQuality};
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::dtos::transport::ack::TransportAck;
+use crate::enums::compression::Compression;
+use crate::enums::transport_error::TransportError;
+use crate::enums::uplink::Uplink;
+use crate::services::http_client::{HttpClientService, UrlBuilder};
+use crate::services::http_transport;
+use crate::utilities::{json, url};
+
+// Uploads envelopes as HTTP POSTs over the modem's own TCP stack, for
+// installs with LTE-M/NB-IoT coverage but no Wi-Fi
+pub struct CellularTransportService<U: Read + Write> {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    modem: AtModem<U>,
+    apn: String,
+    user: String,
+    password: String,
+    client: HttpClientService,
+    host: String,
+    port: u16,
+    path: String,
+    connected: bool,
+    compression: Compression,
+}
+
+impl<U: Read + Write> CellularTransportService<U>
+where
+    U::Error: Debug,
+{
+    pub async fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        uart: U,
+        apn: String,
+        user: String,
+        password: String,
+        server_base_url: &str,
+        compression: Compression,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let modem = AtModem::new(uart).await.map_err(cellular_error)?;
+        // TLS is not available through the modem's CIP stack
+        let parts = url::split(server_base_url)
+            .filter(|parts| parts.scheme == "http")
+            .ok_or_else(|| Box::<dyn Error + Send + Sync>::from("Cellular uplink needs an http:// server URL"))?;
+        let client = HttpClientService::new(urn.clone(), device_urn.clone(), location_urn.clone(), parts.host.clone());
+        Ok(Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            modem: modem,
+            apn: apn,
+            user: user,
+            password: password,
+            client: client,
+            host: parts.host,
+            port: parts.port,
+            path: UrlBuilder::new(&parts.path).path(HttpConstant::MEASUREMENTS_PATH).target(),
+            connected: false,
+            compression: compression,
+        })
+    }
+
+    pub async fn signal_quality(&mut self) -> Result<SignalQuality, Box<dyn Error + Send + Sync>> {
+        self.modem.signal_quality().await.map_err(cellular_error)
+    }
+
+    pub async fn send_envelope(&mut self, envelope: &MeasurementEnvelopeDTO) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self._send(json::envelope_to_json(envelope).as_bytes()).await?;
+        Ok(())
+    }
+
+    async fn _send(&mut self, payload: &[u8]) -> Result<u16, TransportError> {
+        if !self.connected {
+            self.modem
+                .connect_data(&self.apn, &self.user, &self.password)
+                .await
+                .map_err(|error| TransportError::Io(cellular_error(error).to_string()))?;
+            self.connected = true;
+        }
+
+        let request = self
+            .client
+            .create_encoded_post_request(&self.path, payload, self.compression, &BTreeMap::new());
+
+        let reply = match self.modem.tcp_exchange(&self.host, self.port, &request).await {
+            Ok(reply) => reply,
+            Err(error) => {
+                // Force a fresh PDP context on the next attempt
+                self.connected = false;
+                let _ = self.modem.disconnect_data().await;
+                return Err(TransportError::Io(cellular_error(error).to_string()));
+            },
+        };
+        // The modem may prefix the response with its own result codes
+        let response = reply.find("HTTP/").map_or("", |start| &reply[start..]);
+        http_transport::check_status(response.as_bytes())
+    }
+}
+
+impl<U: Read + Write> ITransport for CellularTransportService<U>
+where
+    U::Error: Debug,
+{
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    async fn send(&mut self, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        let status = self._send(payload).await?;
+        Ok(TransportAck {
+            uplink: Uplink::Cellular,
+            code: Some(status),
+            bytes: payload.len(),
+            cursor: None,
+        })
+    }
+}
+
+fn cellular_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("Cellular error: {:?}", error))
+}
@@ -0,0 +1,143 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::Debug;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+use embedded_io_async::{Read, Write};
+
+use crate::constants::cli::CliConstant;
+use crate::drivers::eeprom_24cxx::Eeprom24Cxx;
+use crate::dtos::response::server::command::ServerCommandResponseDTO;
+use crate::enums::transport_error::TransportError;
+use crate::constants::mfg_test::MfgTestConstant;
+use crate::constants::soak::SoakConstant;
+use crate::services::{board_identity, log_buffer, log_filter, mfg_test, soak, upload_stats};
+use crate::utilities::json;
+
+// Serial console for a technician or the production line station with a
+// cable on the UART the log goes out on. One command per line, a name and
+// an optional argument, e.g. "log_level info,services::http_client=off",
+// answered on the same UART. Commands the downlink also carries go through
+// the same handlers. Holds the board EEPROM for the production line
+pub struct CliService<I: I2c, D: DelayNs> {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    eeprom: Eeprom24Cxx<I, D>,
+    // Set by a command that takes effect on the next boot
+    restart: bool,
+}
+
+impl<I: I2c, D: DelayNs> CliService<I, D> {
+    pub fn new(urn: String, device_urn: String, location_urn: String, eeprom: Eeprom24Cxx<I, D>) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            eeprom: eeprom,
+            restart: false,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // Answers line after line until the UART fails, or returns once a
+    // command wants the chip restarted, after its reply has gone out
+    pub async fn run<U: Read + Write>(&mut self, uart: &mut U) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut line: Vec<u8> = Vec::new();
+        let mut buffer = [0u8; CliConstant::READ_CHUNK_BYTES];
+        loop {
+            let read = uart.read(&mut buffer).await.map_err(cli_error)?;
+            for byte in buffer[..read].iter() {
+                match byte {
+                    b'\r' | b'\n' if line.is_empty() => {},
+                    b'\r' | b'\n' => {
+                        let reply = self.handle(&String::from_utf8_lossy(&line));
+                        line.clear();
+                        uart.write_all(reply.as_bytes()).await.map_err(cli_error)?;
+                        uart.write_all(b"\r\n").await.map_err(cli_error)?;
+                        if self.restart {
+                            uart.flush().await.map_err(cli_error)?;
+                            return Ok(());
+                        }
+                    },
+                    _ if line.len() < CliConstant::MAX_LINE_BYTES => line.push(*byte),
+                    _ => {},
+                }
+            }
+        }
+    }
+
+    // The reply to one line, "OK" or "ERROR ..." for a command that only
+    // changes something
+    pub fn handle(&mut self, line: &str) -> String {
+        let line = line.trim();
+        let (name, argument) = match line.split_once(' ') {
+            Some((name, argument)) => (name, Some(argument.trim())),
+            None => (line, None),
+        };
+        let command = ServerCommandResponseDTO {
+            id: 0,
+            command: String::from(name),
+            argument: argument.filter(|argument| !argument.is_empty()).map(String::from),
+        };
+        match (name, command.argument.as_deref()) {
+            (CliConstant::HELP_COMMAND, _) => String::from(CliConstant::HELP),
+            (CliConstant::LOG_LEVEL_COMMAND, None) => log_filter::spec(),
+            // The RAM ring buffer, GET /logs without the network
+            (CliConstant::LOGS_COMMAND, _) => log_buffer::contents().replace('\n', "\r\n"),
+            // Timing and sizes per transport, as in GET /metrics
+            (CliConstant::UPLOADS_COMMAND, _) => json::upload_stats_to_json(&upload_stats::snapshot()),
+            // Restarts into the production line test
+            (MfgTestConstant::COMMAND, _) => match mfg_test::handle_command(&command) {
+                Some(Ok(())) => {
+                    self.restart = true;
+                    String::from("OK restarting into MFG_TEST")
+                },
+                other => reply(name, other),
+            },
+            // Counters of the running or last soak test, as in GET /soak
+            (SoakConstant::COMMAND, None) => json::soak_stats_to_json(&soak::snapshot(), SoakConstant::DURATION_S),
+            // Starting or stopping changes the settings the services were
+            // built with
+            (SoakConstant::COMMAND, Some(_)) => match soak::handle_command(&command) {
+                Some(Ok(())) => {
+                    self.restart = true;
+                    String::from("OK restarting")
+                },
+                other => reply(name, other),
+            },
+            _ => {
+                let result = log_filter::handle_command(&command)
+                    .or_else(|| board_identity::handle_command(&command, &mut self.eeprom));
+                reply(name, result)
+            },
+        }
+    }
+}
+
+fn reply(name: &str, result: Option<Result<(), TransportError>>) -> String {
+    match result {
+        Some(Ok(())) => String::from("OK"),
+        Some(Err(error)) => format!("ERROR {}", error),
+        None => format!("ERROR unknown command {}, try help", name),
+    }
+}
+
+fn cli_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("CLI error: {:?}", error))
+}
@@ -0,0 +1,151 @@
+use core::cell::RefCell;
+
+use embassy_net::Stack;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_time::{Duration, Instant, Timer};
+use embedded_hal::i2c::I2c;
+use log::{info, warn};
+
+use crate::constants::clock::ClockConstant;
+use crate::dtos::telemetry::clock_diagnostics::ClockDiagnosticsDTO;
+use crate::dtos::telemetry::heartbeat::HeartbeatDTO;
+use crate::enums::boot_stage::BootStage;
+use crate::enums::clock_source::ClockSource;
+use crate::enums::transport_error::TransportError;
+use crate::sensors::ds323x::DS323XSensor;
+use crate::services::network_manager::NetworkManagerService;
+use crate::services::{boot, sntp};
+
+#[derive(Default)]
+struct ClockState {
+    // Unix milliseconds at a given monotonic instant
+    anchor: Option<(u64, Instant)>,
+    diagnostics: ClockDiagnosticsDTO,
+}
+
+// The system wall clock, shared by everything that stamps data
+static CLOCK: Mutex<CriticalSectionRawMutex, RefCell<Option<ClockState>>> = Mutex::new(RefCell::new(None));
+
+fn with_state<R>(f: impl FnOnce(&mut ClockState) -> R) -> R {
+    CLOCK.lock(|state| f(state.borrow_mut().get_or_insert_with(ClockState::default)))
+}
+
+pub fn set(unix_ms: u64, at: Instant, source: ClockSource) {
+    with_state(|state| {
+        state.anchor = Some((unix_ms, at));
+        state.diagnostics.source = Some(source);
+    });
+    boot::advance(BootStage::TimeSynced);
+}
+
+// Unix seconds, None until the clock was set from the RTC or SNTP
+pub fn now() -> Option<u64> {
+    now_ms().map(|unix_ms| unix_ms / 1_000)
+}
+
+pub fn now_ms() -> Option<u64> {
+    with_state(|state| state.anchor).map(|(unix_ms, at)| unix_ms + (Instant::now() - at).as_millis())
+}
+
+pub fn is_set() -> bool {
+    with_state(|state| state.anchor.is_some())
+}
+
+// Unix milliseconds of a moment given as milliseconds since boot
+pub fn at_boot_ms(boot_ms: u64) -> Option<u64> {
+    let (unix_ms, at) = with_state(|state| state.anchor)?;
+    Some((unix_ms + boot_ms).saturating_sub(at.as_millis()))
+}
+
+// Timestamp for a new envelope: unix seconds, or 0 together with the
+// milliseconds since boot while the clock is still unset
+pub fn stamp() -> (u64, Option<u64>) {
+    match now() {
+        Some(timestamp) => (timestamp, None),
+        None => (0, Some(Instant::now().as_millis())),
+    }
+}
+
+pub fn diagnostics() -> ClockDiagnosticsDTO {
+    with_state(|state| state.diagnostics.clone())
+}
+
+pub fn report(heartbeat: &mut HeartbeatDTO) {
+    heartbeat.clock = diagnostics();
+}
+
+// Call once at boot, before anything is timestamped. Returns false when the
+// RTC is missing or lost its time, the clock then stays unset until SNTP
+pub fn seed<I: I2c>(rtc: &DS323XSensor<I>) -> bool {
+    update_rtc_diagnostics(rtc);
+    match rtc.read_unix() {
+        Ok(timestamp) => {
+            set(timestamp * 1_000, Instant::now(), ClockSource::Rtc);
+            info!("System clock set from RTC: {}", timestamp);
+            true
+        },
+        Err(error) => {
+            warn!("RTC time unavailable: {:?}", error);
+            false
+        },
+    }
+}
+
+// Sets the system clock from SNTP and disciplines the RTC against it
+pub async fn sync<I: I2c>(
+    stack: Stack<'static>,
+    server: &str,
+    rtc: Option<&DS323XSensor<I>>,
+) -> Result<(), TransportError> {
+    let (unix_ms, at) = sntp::query(stack, server).await?;
+    set(unix_ms, at, ClockSource::Sntp);
+    with_state(|state| state.diagnostics.last_sync = Some(unix_ms / 1_000));
+
+    let rtc = match rtc {
+        Some(rtc) => rtc,
+        None => return Ok(()),
+    };
+    // The RTC only holds whole seconds, so compare and write on a boundary
+    let now_ms = unix_ms + (Instant::now() - at).as_millis();
+    Timer::after(Duration::from_millis(1_000 - now_ms % 1_000)).await;
+    let reference = now_ms / 1_000 + 1;
+    match rtc.discipline(reference) {
+        Ok(offset) => with_state(|state| {
+            state.diagnostics.rtc_offset_s = Some(offset);
+            state.diagnostics.drift_ppm = rtc.drift_ppm();
+        }),
+        Err(error) => warn!("RTC discipline failed: {:?}", error),
+    }
+    update_rtc_diagnostics(rtc);
+    Ok(())
+}
+
+// Over whichever interface the network manager selects; until a sync
+// succeeds it is retried every `SNTP_RETRY_S`, the interfaces may still be
+// coming up
+pub async fn run<I: I2c>(network: &mut NetworkManagerService, server: &str, rtc: Option<&DS323XSensor<I>>) -> ! {
+    loop {
+        let synced = match network.select() {
+            Some(stack) => match sync(stack, server, rtc).await {
+                Ok(()) => true,
+                Err(error) => {
+                    warn!("SNTP sync with {} failed: {}", server, error);
+                    false
+                },
+            },
+            None => false,
+        };
+        let interval_s = if synced { ClockConstant::DISCIPLINE_INTERVAL_S } else { ClockConstant::SNTP_RETRY_S };
+        Timer::after(Duration::from_secs(interval_s)).await;
+    }
+}
+
+fn update_rtc_diagnostics<I: I2c>(rtc: &DS323XSensor<I>) {
+    let aging = rtc.aging_offset().ok();
+    let temperature = rtc.temperature().ok();
+    with_state(|state| {
+        state.diagnostics.rtc_aging_offset = aging;
+        state.diagnostics.rtc_temperature = temperature;
+    });
+}
@@ -0,0 +1,230 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::config::Config;
+use crate::configurations::pin_map::PinMap;
+use crate::configurations::sensors::SensorsConfig;
+use crate::constants::sensor::SensorConstant;
+use crate::dtos::configurations::config_problem::ConfigProblemDTO;
+use crate::dtos::configurations::thresholds::ThresholdsDTO;
+use crate::dtos::event::envelope::EventEnvelopeDTO;
+use crate::dtos::response::server::config::ServerConfigResponseDTO;
+use crate::enums::config_problem_kind::ConfigProblemKind;
+use crate::enums::event_kind::EventKind;
+use crate::enums::log_format::LogFormat;
+use crate::enums::unit_system::UnitSystem;
+use crate::enums::value::Value;
+use crate::factories::pipeline::PipelineFactory;
+use crate::services::{clock, message_id, remote_config};
+use crate::utilities::{retention, thresholds, url};
+
+// Checks a config before it is used and lists everything wrong with it
+// rather than stopping at the first problem. A remote config with problems
+// is not applied, the device keeps its last-known-good one and reports the
+// list as a CONFIG_REJECTED event. The build-time config cannot be refused,
+// its problems are reported the same way once at boot
+pub struct ConfigValidator {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    // Keys the sensor factory has a driver for
+    known_sensors: Vec<String>,
+}
+
+impl ConfigValidator {
+    pub fn new(urn: String, device_urn: String, location_urn: String, known_sensors: Vec<String>) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            known_sensors: known_sensors,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // A config received from the server, by `RemoteConfigService::apply`.
+    // `config` and `sensors` are the ones in effect, the remote one is
+    // checked as it would be once overlaid on them
+    pub fn check_remote(&self, json: &[u8], config: &Config, sensors: &SensorsConfig) -> Vec<ConfigProblemDTO> {
+        let remote = match remote_config::decode(json) {
+            Ok(remote) => remote,
+            Err(error) => return vec![problem(ConfigProblemKind::Malformed, "", error.to_string())],
+        };
+
+        let mut problems = Vec::new();
+        if let Some(include) = &remote.include {
+            self.unknown_sensors(&mut problems, "include", include.iter());
+        }
+        if let Some(location_urns) = &remote.location_urns {
+            self.unknown_sensors(&mut problems, "location_urns", location_urns.keys());
+        }
+        if let Some(pipelines) = &remote.pipelines {
+            self.unknown_sensors(&mut problems, "pipelines", pipelines.keys());
+            for (sensor, stages) in pipelines.iter() {
+                for spec in stages.iter().filter(|spec| PipelineFactory::stage(spec).is_none()) {
+                    problems.push(problem(
+                        ConfigProblemKind::InvalidValue,
+                        &format!("pipelines.{}", sensor),
+                        format!("unknown stage {}", spec),
+                    ));
+                }
+            }
+        }
+        if remote.interval_s == Some(0) {
+            problems.push(problem(ConfigProblemKind::InvalidInterval, "interval_s", String::from("must be above 0")));
+        }
+        invalid_if(&mut problems, "log_format", &remote.log_format, |value| LogFormat::parse(value).is_some());
+        invalid_if(&mut problems, "units", &remote.units, |value| UnitSystem::parse(value).is_some());
+        invalid_if(&mut problems, "server_base_url", &remote.server_base_url, |value| url::split(value).is_some());
+        let retention_valid = |value: &str| retention::parse(value).is_some();
+        invalid_if(&mut problems, "queue_retention", &remote.queue_retention, retention_valid);
+        invalid_if(&mut problems, "sd_retention", &remote.sd_retention, retention_valid);
+        for (field, value) in thresholds_of(&remote) {
+            invalid_if(&mut problems, field, value, |value| thresholds::parse(value).is_some());
+        }
+        if !problems.is_empty() {
+            return problems;
+        }
+
+        // Overlaid, the remaining checks are the ones of a local config
+        let mut config = config.clone();
+        let mut sensors = sensors.clone();
+        config.overlay(&remote);
+        sensors.overlay(&remote);
+        self.check(&config, &sensors)
+    }
+
+    pub fn check(&self, config: &Config, sensors: &SensorsConfig) -> Vec<ConfigProblemDTO> {
+        let mut problems = Vec::new();
+        self.unknown_sensors(&mut problems, "include", sensors.include.iter());
+        problems.extend(PinMap::build(config, sensors).problems());
+
+        if config.sensor_interval_ms == 0 {
+            problems.push(problem(ConfigProblemKind::InvalidInterval, "interval_s", String::from("must be above 0")));
+        } else if sensors.read_timeout_ms >= config.sensor_interval_ms {
+            problems.push(problem(
+                ConfigProblemKind::InvalidInterval,
+                "interval_s",
+                format!(
+                    "{} ms leaves no room for the {} ms read timeout",
+                    config.sensor_interval_ms, sensors.read_timeout_ms
+                ),
+            ));
+        }
+        if config.metrics_interval_s == 0 {
+            let detail = String::from("must be above 0");
+            problems.push(problem(ConfigProblemKind::InvalidInterval, "metrics_interval_s", detail));
+        }
+
+        let classified: [(&str, &str, &ThresholdsDTO); 4] = [
+            (SensorConstant::BH1750, "lux_thresholds", &sensors.lux_thresholds),
+            (SensorConstant::VL5310X, "distance_thresholds_mm", &sensors.distance_thresholds_mm),
+            (SensorConstant::BME280, "temperature_thresholds", &sensors.temperature_thresholds),
+            (SensorConstant::BME280, "humidity_thresholds", &sensors.humidity_thresholds),
+        ];
+        for (sensor, field, thresholds) in classified {
+            if thresholds.steps.is_empty() && sensors.include.iter().any(|included| included == sensor) {
+                problems.push(problem(
+                    ConfigProblemKind::MissingThreshold,
+                    field,
+                    format!("{} is included but every reading would be {}", sensor, thresholds.above),
+                ));
+            }
+        }
+        problems
+    }
+
+    // Call before constructing any driver: the pin map when every GPIO and
+    // I2C address has one owner, otherwise the event to upload in place of
+    // the esp-hal panic the drivers would hit
+    pub fn pins(&self, config: &Config, sensors: &SensorsConfig) -> Result<PinMap, EventEnvelopeDTO> {
+        let map = PinMap::build(config, sensors);
+        match self.event(&map.problems()) {
+            Some(event) => Err(event),
+            None => Ok(map),
+        }
+    }
+
+    // The problems as one event, keyed by field; None when there are none
+    pub fn event(&self, problems: &[ConfigProblemDTO]) -> Option<EventEnvelopeDTO> {
+        if problems.is_empty() {
+            return None;
+        }
+        let mut detail: BTreeMap<String, Value> = BTreeMap::new();
+        for problem in problems.iter() {
+            let text = format!("{}: {}", problem.kind.as_str(), problem.detail);
+            let key = if problem.field.is_empty() { String::from("config") } else { problem.field.clone() };
+            match detail.get_mut(&key) {
+                Some(Value::String(existing)) => {
+                    existing.push_str("; ");
+                    existing.push_str(&text);
+                },
+                _ => {
+                    detail.insert(key, Value::String(text));
+                },
+            }
+        }
+        let (timestamp, relative_ms_since_boot) = clock::stamp();
+        Some(EventEnvelopeDTO {
+            id: message_id::next(),
+            device_urn: self.device_urn.clone(),
+            location_urn: self.location_urn.clone(),
+            sensor_urn: self.urn.clone(),
+            timestamp: timestamp,
+            relative_ms_since_boot: relative_ms_since_boot,
+            kind: EventKind::ConfigRejected,
+            detail: detail,
+        })
+    }
+
+    fn unknown_sensors<'a>(
+        &self,
+        problems: &mut Vec<ConfigProblemDTO>,
+        field: &str,
+        sensors: impl Iterator<Item = &'a String>,
+    ) {
+        for sensor in sensors {
+            let key = sensor.trim().to_lowercase();
+            if !self.known_sensors.contains(&key) {
+                problems.push(problem(ConfigProblemKind::UnknownSensor, field, key));
+            }
+        }
+    }
+}
+
+fn problem(kind: ConfigProblemKind, field: &str, detail: String) -> ConfigProblemDTO {
+    ConfigProblemDTO {
+        kind: kind,
+        field: String::from(field),
+        detail: detail,
+    }
+}
+
+fn invalid_if(problems: &mut Vec<ConfigProblemDTO>, field: &str, value: &Option<String>, valid: impl Fn(&str) -> bool) {
+    if let Some(value) = value.as_deref().filter(|value| !valid(value)) {
+        problems.push(problem(ConfigProblemKind::InvalidValue, field, format!("cannot parse {}", value)));
+    }
+}
+
+fn thresholds_of(remote: &ServerConfigResponseDTO) -> [(&'static str, &Option<String>); 4] {
+    [
+        ("lux_thresholds", &remote.lux_thresholds),
+        ("distance_thresholds_mm", &remote.distance_thresholds_mm),
+        ("temperature_thresholds", &remote.temperature_thresholds),
+        ("humidity_thresholds", &remote.humidity_thresholds),
+    ]
+}
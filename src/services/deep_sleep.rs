@@ -0,0 +1,111 @@
+use alloc::string::String;
+use core::time::Duration;
+
+use embedded_hal::i2c::I2c;
+use esp_hal::gpio::RtcPin;
+use esp_hal::rtc_cntl::sleep::{Ext0WakeupSource, TimerWakeupSource, WakeSource, WakeupLevel};
+use esp_hal::rtc_cntl::Rtc;
+use log::{info, warn};
+
+use crate::constants::clock::ClockConstant;
+use crate::dtos::configurations::timezone::TimeZoneDTO;
+use crate::sensors::ds323x::DS323XSensor;
+use crate::utilities::timezone;
+
+// Ultra-low-power mode: between cycles the ESP32 is put into deep sleep and
+// woken by the DS3231 alarm on INT/SQW (open drain, active low, wired to an
+// RTC capable GPIO with a pull-up). The RTC's crystal keeps wakeups on the
+// calendar over multi-hour sleeps where the ESP32's RC timer drifts by
+// minutes. Every wake is a reboot, so `on_wake` runs early in main
+pub struct DeepSleepService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    interval_s: u64,
+    timezone: TimeZoneDTO,
+}
+
+impl DeepSleepService {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        interval_s: u64,
+        timezone: TimeZoneDTO,
+    ) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            interval_s: interval_s.min(ClockConstant::MAX_ALARM_SLEEP_S),
+            timezone: timezone,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // Next multiple of the interval on the local calendar, so 3600 wakes at
+    // the top of every hour and 86400 at local midnight
+    pub fn next_wake(&self, now_utc: u64) -> u64 {
+        let local = timezone::to_local(&self.timezone, now_utc);
+        let interval = self.interval_s as i64;
+        let mut wake = now_utc as i64 + (interval - local.rem_euclid(interval));
+        if wake - (now_utc as i64) < ClockConstant::MIN_DEEP_SLEEP_S as i64 {
+            wake += interval;
+        }
+        wake as u64
+    }
+
+    // Releases INT/SQW so the next alarm can pull it low again
+    pub fn on_wake<I: I2c>(&self, clock: &DS323XSensor<I>) {
+        if let Err(error) = clock.clear_alarms() {
+            warn!("Failed to clear RTC alarm: {:?}", error);
+        }
+    }
+
+    // Arms the RTC alarm for `wake_at` (UTC) and enters deep sleep. The ESP32
+    // timer is armed as well, a little later, so a missing or unpowered RTC
+    // costs one late cycle instead of a device that never wakes up
+    pub fn sleep<I: I2c, P: RtcPin>(
+        &self,
+        rtc: &mut Rtc,
+        clock: &DS323XSensor<I>,
+        interrupt: P,
+        now_utc: u64,
+        wake_at: u64,
+    ) -> ! {
+        let sleep_s = wake_at.saturating_sub(now_utc).max(ClockConstant::MIN_DEEP_SLEEP_S);
+        let alarm_armed = match clock.set_alarm(wake_at) {
+            Ok(()) => true,
+            Err(error) => {
+                warn!("Failed to arm RTC alarm, waking on the ESP32 timer: {:?}", error);
+                false
+            },
+        };
+        let backup_s = if alarm_armed { sleep_s + ClockConstant::BACKUP_WAKE_MARGIN_S } else { sleep_s };
+        info!(
+            "Deep sleep for {} s, waking at {}",
+            sleep_s,
+            timezone::format_local(&self.timezone, now_utc + sleep_s)
+        );
+
+        let timer = TimerWakeupSource::new(Duration::from_secs(backup_s));
+        if alarm_armed {
+            let alarm = Ext0WakeupSource::new(interrupt, WakeupLevel::Low);
+            let sources: [&dyn WakeSource; 2] = [&alarm, &timer];
+            rtc.sleep_deep(&sources)
+        } else {
+            rtc.sleep_deep(&[&timer])
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::enums::value::Value;
+use crate::utilities::units;
+use crate::services::{clock, message_id};
+
+// A new envelope with its id and the current clock stamp
+pub fn measurement(device_urn: &str, location_urn: &str, data: BTreeMap<String, Value>) -> MeasurementEnvelopeDTO {
+    let (timestamp, relative_ms_since_boot) = clock::stamp();
+    // A field namespaced by `by_location` has the unit of its plain name
+    let units = data
+        .keys()
+        .filter_map(|field| units::of(field.rsplit('.').next().unwrap_or(field)).map(|unit| (field.clone(), unit)))
+        .collect();
+    MeasurementEnvelopeDTO {
+        id: message_id::next(),
+        device_urn: String::from(device_urn),
+        location_urn: String::from(location_urn),
+        timestamp: timestamp,
+        relative_ms_since_boot: relative_ms_since_boot,
+        data: data,
+        quality: BTreeMap::new(),
+        units: units,
+    }
+}
+
+// One envelope per location for a cycle's readings, given as the sensor
+// key, the location of the sensor (see `SensorsConfig::location_urn`) and
+// its values. Sensors sharing a location share an envelope; a field more
+// than one of them reports, such as `distance_mm` of several VL53L0X, goes
+// out once per sensor as `<sensor>.<field>` instead of one overwriting the
+// other. A sensor missing from the cycle leaves the other's field plain,
+// which `last_value` standing in for failed reads keeps rare
+pub fn by_location(
+    device_urn: &str,
+    readings: Vec<(String, String, BTreeMap<String, Value>)>,
+) -> Vec<MeasurementEnvelopeDTO> {
+    let mut reporters: BTreeMap<(String, String), usize> = BTreeMap::new();
+    for (_, location_urn, data) in readings.iter() {
+        for field in data.keys() {
+            *reporters.entry((location_urn.clone(), field.clone())).or_default() += 1;
+        }
+    }
+    let mut locations: BTreeMap<String, BTreeMap<String, Value>> = BTreeMap::new();
+    for (sensor, location_urn, data) in readings {
+        let merged = locations.entry(location_urn.clone()).or_default();
+        for (field, value) in data {
+            if reporters.get(&(location_urn.clone(), field.clone())).is_some_and(|count| *count > 1) {
+                merged.insert(format!("{}.{}", sensor, field), value);
+            } else {
+                merged.insert(field, value);
+            }
+        }
+    }
+    locations
+        .into_iter()
+        .map(|(location_urn, data)| measurement(device_urn, &location_urn, data))
+        .collect()
+}
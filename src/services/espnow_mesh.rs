@@ -0,0 +1,233 @@
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::Debug;
+
+use esp_wifi::esp_now::{EspNow, EspNowWifiInterface, PeerInfo};
+use log::{debug, warn};
+
+use crate::abstractions::queue::IQueue;
+use crate::abstractions::transport::ITransport;
+use crate::constants::mesh::MeshConstant;
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::dtos::transport::ack::TransportAck;
+use crate::enums::node_mode::NodeMode;
+use crate::enums::transport_error::TransportError;
+use crate::enums::uplink::Uplink;
+use crate::services::uploader;
+use crate::utilities::json;
+
+const CHUNK_BYTES: usize = MeshConstant::FRAME_MAX_BYTES - MeshConstant::FRAME_HEADER_BYTES;
+
+// Fragments of one envelope received from a leaf, keyed by its MAC
+struct Reassembly {
+    sequence: u16,
+    chunks: Vec<Option<Vec<u8>>>,
+}
+
+pub struct EspNowMeshService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    mode: NodeMode,
+    esp_now: EspNow<'static>,
+    gateway: [u8; 6],
+    lmk: Option<[u8; 16]>,
+    sequence: u16,
+    pending: BTreeMap<[u8; 6], Reassembly>,
+    // Ids of the envelopes forwarded last, oldest first. A leaf resends an
+    // envelope whose delivery it could not confirm, even when it got through
+    recent_ids: VecDeque<String>,
+}
+
+impl EspNowMeshService {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        mode: NodeMode,
+        esp_now: EspNow<'static>,
+        channel: u8,
+        gateway: [u8; 6],
+        peers: &[[u8; 6]],
+        pmk: Option<[u8; 16]>,
+        lmk: Option<[u8; 16]>,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        esp_now.set_channel(channel).map_err(mesh_error)?;
+        if let Some(pmk) = pmk {
+            esp_now.set_pmk(&pmk).map_err(mesh_error)?;
+        }
+
+        let mut service = Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            mode: mode,
+            esp_now: esp_now,
+            gateway: gateway,
+            lmk: lmk,
+            sequence: 0,
+            pending: BTreeMap::new(),
+            recent_ids: VecDeque::with_capacity(MeshConstant::RECENT_IDS),
+        };
+        match mode {
+            NodeMode::Leaf if gateway != MeshConstant::BROADCAST_ADDRESS => service.add_peer(gateway)?,
+            NodeMode::Gateway => {
+                for peer in peers {
+                    service.add_peer(*peer)?;
+                }
+            },
+            _ => {},
+        }
+        Ok(service)
+    }
+
+    pub fn mode(&self) -> NodeMode {
+        self.mode
+    }
+
+    // Broadcast frames cannot be encrypted, so only unicast peers get the LMK
+    pub fn add_peer(&mut self, address: [u8; 6]) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.esp_now.peer_exists(&address) {
+            return Ok(());
+        }
+        if self.esp_now.peer_count().map_err(mesh_error)?.total_count as usize >= MeshConstant::MAX_PEERS {
+            return Err(Box::from("ESP-NOW peer table is full"));
+        }
+        let encrypt = self.lmk.is_some() && address != MeshConstant::BROADCAST_ADDRESS;
+        self.esp_now
+            .add_peer(PeerInfo {
+                interface: EspNowWifiInterface::Sta,
+                peer_address: address,
+                lmk: if encrypt { self.lmk } else { None },
+                channel: None,
+                encrypt: encrypt,
+            })
+            .map_err(mesh_error)
+    }
+
+    pub fn remove_peer(&mut self, address: [u8; 6]) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.pending.remove(&address);
+        self.esp_now.remove_peer(&address).map_err(mesh_error)
+    }
+
+    // Leaf side: the envelope keeps its own device_urn so the gateway can
+    // forward it verbatim
+    pub fn send_envelope(&mut self, envelope: &MeasurementEnvelopeDTO) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self._send(json::envelope_to_json(envelope).as_bytes())
+    }
+
+    fn _send(&mut self, payload: &[u8]) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let count = payload.len().div_ceil(CHUNK_BYTES).max(1);
+        if count > MeshConstant::MAX_FRAGMENTS {
+            return Err(Box::from(format!("Payload of {} bytes is too large for ESP-NOW", payload.len())));
+        }
+        self.sequence = self.sequence.wrapping_add(1);
+
+        for index in 0..count {
+            let chunk = &payload[index * CHUNK_BYTES..payload.len().min((index + 1) * CHUNK_BYTES)];
+            let mut frame = Vec::with_capacity(MeshConstant::FRAME_HEADER_BYTES + chunk.len());
+            frame.extend_from_slice(&self.sequence.to_le_bytes());
+            frame.push(index as u8);
+            frame.push(count as u8);
+            frame.extend_from_slice(chunk);
+            self.esp_now
+                .send(&self.gateway, &frame)
+                .map_err(mesh_error)?
+                .wait()
+                .map_err(mesh_error)?;
+        }
+        Ok(())
+    }
+
+    // Gateway side: drains received frames and persists every completed
+    // envelope for `UploaderService::forward`, returning how many were queued.
+    // An envelope whose id was forwarded recently is a resend and dropped
+    pub fn poll(&mut self, queue: &mut dyn IQueue) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let mut forwarded = 0;
+        while let Some(received) = self.esp_now.receive() {
+            let source = received.info.src_address;
+            let payload = match self.reassemble(source, received.data()) {
+                Some(payload) => payload,
+                None => continue,
+            };
+            if let Some(id) = json::id_of(&payload) {
+                if self.recent_ids.iter().any(|recent| recent == id) {
+                    debug!("Dropping resent ESP-NOW envelope {} from {:02x?}", id, source);
+                    continue;
+                }
+                if self.recent_ids.len() == MeshConstant::RECENT_IDS {
+                    self.recent_ids.pop_front();
+                }
+                self.recent_ids.push_back(id.to_string());
+            }
+            debug!("ESP-NOW envelope of {} bytes from {:02x?}", payload.len(), source);
+            uploader::persist_relayed(queue, &payload)?;
+            forwarded += 1;
+        }
+        Ok(forwarded)
+    }
+
+    fn reassemble(&mut self, source: [u8; 6], frame: &[u8]) -> Option<Vec<u8>> {
+        if frame.len() < MeshConstant::FRAME_HEADER_BYTES {
+            warn!("Dropping short ESP-NOW frame from {:02x?}", source);
+            return None;
+        }
+        let sequence = u16::from_le_bytes([frame[0], frame[1]]);
+        let index = frame[2] as usize;
+        let count = frame[3] as usize;
+        if count == 0 || count > MeshConstant::MAX_FRAGMENTS || index >= count {
+            warn!("Dropping malformed ESP-NOW frame from {:02x?}", source);
+            return None;
+        }
+
+        // A new sequence number means the previous envelope lost a fragment
+        let entry = self.pending.entry(source).or_insert_with(|| Reassembly {
+            sequence: sequence,
+            chunks: vec![None; count],
+        });
+        if entry.sequence != sequence || entry.chunks.len() != count {
+            entry.sequence = sequence;
+            entry.chunks = vec![None; count];
+        }
+        entry.chunks[index] = Some(frame[MeshConstant::FRAME_HEADER_BYTES..].to_vec());
+
+        if entry.chunks.iter().any(|chunk| chunk.is_none()) {
+            return None;
+        }
+        let reassembly = self.pending.remove(&source)?;
+        Some(reassembly.chunks.into_iter().flatten().flatten().collect())
+    }
+}
+
+impl ITransport for EspNowMeshService {
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    async fn send(&mut self, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        self._send(payload).map_err(|error| TransportError::Io(error.to_string()))?;
+        Ok(TransportAck {
+            uplink: Uplink::EspNow,
+            code: None,
+            bytes: payload.len(),
+            cursor: None,
+        })
+    }
+}
+
+fn mesh_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("ESP-NOW error: {:?}", error))
+}
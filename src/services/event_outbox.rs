@@ -0,0 +1,24 @@
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::channel::{Channel, TrySendError};
+use log::warn;
+
+use crate::constants::upload::UploadConstant;
+use crate::dtos::event::envelope::EventEnvelopeDTO;
+
+// Detectors running in tasks of their own (audio, impact, tamper) hand their
+// events over here; the upload task takes them out and sends them with
+// `UploaderService::upload_event`, so none of them needs the uploader
+static OUTBOX: Channel<CriticalSectionRawMutex, EventEnvelopeDTO, { UploadConstant::EVENT_OUTBOX_DEPTH }> =
+    Channel::new();
+
+// Never blocks the detector; with the uploader that far behind the event is
+// dropped and logged
+pub fn raise(event: EventEnvelopeDTO) {
+    if let Err(TrySendError::Full(event)) = OUTBOX.try_send(event) {
+        warn!("Event outbox full, dropping {} {}", event.kind.as_str(), event.id);
+    }
+}
+
+pub async fn next() -> EventEnvelopeDTO {
+    OUTBOX.receive().await
+}
@@ -0,0 +1,57 @@
+use alloc::string::String;
+
+use log::warn;
+
+use crate::abstractions::transport::ITransport;
+use crate::dtos::transport::ack::TransportAck;
+use crate::enums::transport_error::TransportError;
+
+// Tries `primary` first and falls back to `secondary` on retryable errors.
+// Nest them to build longer chains in the order given by `Config.uplinks`
+pub struct FailoverTransport<P: ITransport, S: ITransport> {
+    primary: P,
+    secondary: S,
+}
+
+impl<P: ITransport, S: ITransport> FailoverTransport<P, S> {
+    pub fn new(primary: P, secondary: S) -> Self {
+        Self {
+            primary: primary,
+            secondary: secondary,
+        }
+    }
+}
+
+impl<P: ITransport, S: ITransport> ITransport for FailoverTransport<P, S> {
+    fn urn(&self) -> String {
+        self.primary.urn()
+    }
+
+    fn device_urn(&self) -> String {
+        self.primary.device_urn()
+    }
+
+    fn location_urn(&self) -> String {
+        self.primary.location_urn()
+    }
+
+    async fn send(&mut self, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        match self.primary.send(payload).await {
+            Err(error) if error.is_retryable() => {
+                warn!("{} failed ({}), falling back to {}", self.primary.urn(), error, self.secondary.urn());
+                self.secondary.send(payload).await
+            },
+            result => result,
+        }
+    }
+
+    async fn send_event(&mut self, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        match self.primary.send_event(payload).await {
+            Err(error) if error.is_retryable() => {
+                warn!("{} failed ({}), falling back to {}", self.primary.urn(), error, self.secondary.urn());
+                self.secondary.send_event(payload).await
+            },
+            result => result,
+        }
+    }
+}
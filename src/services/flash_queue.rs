@@ -0,0 +1,579 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::Debug;
+
+use littlefs2::fs::Filesystem;
+use littlefs2::io::SeekFrom;
+use littlefs2::path::PathBuf;
+use log::info;
+
+use crate::abstractions::queue::IQueue;
+use crate::constants::retention::RetentionConstant;
+use crate::constants::storage::StorageConstant;
+use crate::dtos::configurations::retention::RetentionConfigDTO;
+use crate::utilities::flash_partition::FlashPartition;
+use crate::utilities::state_file;
+use crate::utilities::json;
+
+// Each record is stored as a little-endian u16 length followed by the payload
+const RECORD_HEADER_BYTES: usize = 2;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct QueueCursor {
+    head_segment: u32,
+    head_offset: u32,
+    tail_segment: u32,
+    length: u32,
+}
+
+impl QueueCursor {
+    fn to_bytes(&self) -> [u8; 16] {
+        let mut bytes = [0u8; 16];
+        bytes[0..4].copy_from_slice(&self.head_segment.to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.head_offset.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.tail_segment.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.length.to_le_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 16 {
+            return None;
+        }
+        let word = |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        Some(Self {
+            head_segment: word(0),
+            head_offset: word(4),
+            tail_segment: word(8),
+            length: word(12),
+        })
+    }
+}
+
+pub struct FlashQueueService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    storage: FlashPartition,
+    cursor: QueueCursor,
+    // Records waiting to be written, batched to limit flash program cycles.
+    // Lost on a reset or power cut, so `flush` before a planned reboot or
+    // deep sleep
+    pending: Vec<u8>,
+    pending_count: u32,
+    // Thins the two oldest segments into one when the queue is full, the
+    // oldest segment is dropped without one or when it frees no room
+    downsampler: Option<fn(Vec<Vec<u8>>) -> Vec<Vec<u8>>>,
+}
+
+impl IQueue for FlashQueueService {
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    fn push(&mut self, payload: &[u8]) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self._push(payload)
+    }
+
+    fn peek(&mut self) -> Result<Option<Vec<u8>>, Box<dyn Error + Send + Sync>> {
+        self._peek()
+    }
+
+    fn pop(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self._pop()
+    }
+
+    fn peek_many(&mut self, skip: usize, max_records: usize, max_bytes: usize) -> Result<Vec<Vec<u8>>, Box<dyn Error + Send + Sync>> {
+        self._peek_many(skip, max_records, max_bytes)
+    }
+
+    fn pop_many(&mut self, count: usize) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self._pop_many(count)
+    }
+
+    fn len(&self) -> usize {
+        (self.cursor.length + self.pending_count) as usize
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self._flush()
+    }
+
+    fn compact(&mut self, retention: &RetentionConfigDTO, now: u64) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        self._compact(retention, now)
+    }
+}
+
+impl FlashQueueService {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+
+        let cursor = Filesystem::mount_and_then(&mut storage, |fs| {
+            if fs.metadata(&path(StorageConstant::QUEUE_DIR)).is_err() {
+                fs.create_dir(&path(StorageConstant::QUEUE_DIR))?;
+            }
+            let mut bytes = [0u8; state_file::HEADER_BYTES + 16];
+            let read = fs
+                .open_file_and_then(&path(StorageConstant::QUEUE_META_FILE), |file| file.read(&mut bytes))
+                .unwrap_or(0);
+            let cursor = match state_file::decode(&bytes[..read], Some(16)) {
+                Some((StorageConstant::QUEUE_META_VERSION, body)) => QueueCursor::from_bytes(body),
+                other => state_file::unsupported(StorageConstant::QUEUE_META_FILE, other),
+            };
+            recover(fs, cursor.unwrap_or_default())
+        })
+        .map_err(fs_error)?;
+
+        Ok(Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            storage: storage,
+            cursor: cursor,
+            pending: Vec::with_capacity(StorageConstant::QUEUE_BATCH_BYTES),
+            pending_count: 0,
+            downsampler: None,
+        })
+    }
+
+    // `UploaderService` records are thinned by `uploader::downsample`
+    pub fn set_downsampler(&mut self, downsampler: fn(Vec<Vec<u8>>) -> Vec<Vec<u8>>) {
+        self.downsampler = Some(downsampler);
+    }
+
+    fn _push(&mut self, payload: &[u8]) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if payload.len() + RECORD_HEADER_BYTES > StorageConstant::QUEUE_SEGMENT_BYTES {
+            return Err(Box::from(format!("Queue record of {} bytes exceeds segment size", payload.len())));
+        }
+        self.pending.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        self.pending.extend_from_slice(payload);
+        self.pending_count += 1;
+
+        if self.pending.len() >= StorageConstant::QUEUE_BATCH_BYTES {
+            self._flush()?;
+        }
+        Ok(())
+    }
+
+    // The head segment can be used up without being removed yet, when it
+    // was the tail at the time; the batch walks skip over it
+    fn _peek(&mut self) -> Result<Option<Vec<u8>>, Box<dyn Error + Send + Sync>> {
+        Ok(self._peek_many(0, 1, usize::MAX)?.pop())
+    }
+
+    fn _pop(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self._pop_many(1)
+    }
+
+    // Walks the flash segments from the head and then the records still in
+    // RAM, so a chunk costs one mount rather than one per record. Skipped
+    // records only have their header read
+    fn _peek_many(&mut self, skip: usize, max_records: usize, max_bytes: usize) -> Result<Vec<Vec<u8>>, Box<dyn Error + Send + Sync>> {
+        let cursor = self.cursor;
+        let mut records: Vec<Vec<u8>> = Vec::new();
+        let mut bytes = 0;
+        let mut skip = skip;
+        let fits = |records: &Vec<Vec<u8>>, bytes: usize, length: usize| {
+            records.len() < max_records && (records.is_empty() || bytes + length <= max_bytes)
+        };
+
+        if cursor.length > 0 {
+            // Whether the chunk filled up before the end of flash
+            let full = Filesystem::mount_and_then(&mut self.storage, |fs| {
+                let mut segment_index = cursor.head_segment;
+                let mut offset = cursor.head_offset;
+                let mut remaining = cursor.length;
+                while remaining > 0 && segment_index <= cursor.tail_segment {
+                    let segment = path(&segment_name(segment_index));
+                    let segment_len = fs.metadata(&segment)?.len() as u32;
+                    let full = fs.open_file_and_then(&segment, |file| {
+                        while remaining > 0 && offset < segment_len {
+                            file.seek(SeekFrom::Start(offset))?;
+                            let mut header = [0u8; RECORD_HEADER_BYTES];
+                            file.read(&mut header)?;
+                            let length = u16::from_le_bytes(header) as usize;
+                            if skip > 0 {
+                                skip -= 1;
+                                offset += (RECORD_HEADER_BYTES + length) as u32;
+                                remaining -= 1;
+                                continue;
+                            }
+                            if !fits(&records, bytes, length) {
+                                return Ok(true);
+                            }
+                            let mut payload = vec![0u8; length];
+                            file.read(&mut payload)?;
+                            records.push(payload);
+                            bytes += length;
+                            offset += (RECORD_HEADER_BYTES + length) as u32;
+                            remaining -= 1;
+                        }
+                        Ok(false)
+                    })?;
+                    if full {
+                        return Ok(true);
+                    }
+                    segment_index += 1;
+                    offset = 0;
+                }
+                Ok(false)
+            })
+            .map_err(fs_error)?;
+            if full {
+                return Ok(records);
+            }
+        }
+
+        let mut offset = 0;
+        for _ in 0..self.pending_count {
+            let length = u16::from_le_bytes([self.pending[offset], self.pending[offset + 1]]) as usize;
+            if skip > 0 {
+                skip -= 1;
+                offset += RECORD_HEADER_BYTES + length;
+                continue;
+            }
+            if !fits(&records, bytes, length) {
+                break;
+            }
+            offset += RECORD_HEADER_BYTES;
+            records.push(self.pending[offset..offset + length].to_vec());
+            bytes += length;
+            offset += length;
+        }
+        Ok(records)
+    }
+
+    // Advances past `count` records with a single cursor write. Used up
+    // segments are only removed once the cursor no longer points into them
+    fn _pop_many(&mut self, count: usize) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut cursor = self.cursor;
+        let from_flash = count.min(cursor.length as usize) as u32;
+        if from_flash > 0 {
+            Filesystem::mount_and_then(&mut self.storage, |fs| {
+                let first_segment = cursor.head_segment;
+                let mut remaining = from_flash;
+                while remaining > 0 {
+                    let segment = path(&segment_name(cursor.head_segment));
+                    let segment_len = fs.metadata(&segment)?.len() as u32;
+                    fs.open_file_and_then(&segment, |file| {
+                        while remaining > 0 && cursor.head_offset < segment_len {
+                            let mut header = [0u8; RECORD_HEADER_BYTES];
+                            file.seek(SeekFrom::Start(cursor.head_offset))?;
+                            file.read(&mut header)?;
+                            cursor.head_offset += (RECORD_HEADER_BYTES + u16::from_le_bytes(header) as usize) as u32;
+                            cursor.length -= 1;
+                            remaining -= 1;
+                        }
+                        Ok(())
+                    })?;
+                    if cursor.head_offset < segment_len {
+                        continue;
+                    }
+                    if cursor.head_segment == cursor.tail_segment {
+                        break;
+                    }
+                    cursor.head_segment += 1;
+                    cursor.head_offset = 0;
+                }
+                write_cursor(fs, &cursor)?;
+                remove_segments(fs, first_segment, cursor.head_segment)
+            })
+            .map_err(fs_error)?;
+            self.cursor = cursor;
+        }
+
+        for _ in from_flash as usize..count {
+            match self.pending_head() {
+                Some(record) => {
+                    self.pending.drain(..RECORD_HEADER_BYTES + record.len());
+                    self.pending_count -= 1;
+                },
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    // Record count first, then bytes and age by walking from the head,
+    // which holds the oldest data. Records stamped relative to boot have no
+    // age to judge and only go for the other limits
+    fn _compact(&mut self, retention: &RetentionConfigDTO, now: u64) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let mut dropped = 0;
+        if let Some(max_records) = retention.max_records {
+            let over = self.len().saturating_sub(max_records as usize);
+            self._pop_many(over)?;
+            dropped += over;
+        }
+
+        let cutoff = retention.max_age_s.map(|max_age_s| now.saturating_sub(max_age_s));
+        loop {
+            let mut over_bytes = match retention.max_bytes {
+                Some(max_bytes) => self.bytes()?.saturating_sub(max_bytes as usize),
+                None => 0,
+            };
+            let records = self._peek_many(0, RetentionConstant::COMPACT_BATCH_RECORDS, usize::MAX)?;
+            let expired = |record: &Vec<u8>| {
+                cutoff.is_some_and(|cutoff| json::timestamp_of(record).is_some_and(|at| at > 0 && at < cutoff))
+            };
+            let mut count = 0;
+            for record in records.iter() {
+                if over_bytes == 0 && !expired(record) {
+                    break;
+                }
+                over_bytes = over_bytes.saturating_sub(RECORD_HEADER_BYTES + record.len());
+                count += 1;
+            }
+            if count == 0 {
+                break;
+            }
+            self._pop_many(count)?;
+            dropped += count;
+            if count < records.len() {
+                break;
+            }
+        }
+        if dropped > 0 {
+            info!("Retention dropped {} queued records", dropped);
+        }
+        Ok(dropped)
+    }
+
+    // Flash taken by unsent records, including those not yet flushed
+    pub fn bytes(&mut self) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let cursor = self.cursor;
+        let stored = if cursor.length == 0 {
+            0
+        } else {
+            Filesystem::mount_and_then(&mut self.storage, |fs| {
+                let mut bytes = 0;
+                for segment_index in cursor.head_segment..=cursor.tail_segment {
+                    bytes += fs.metadata(&path(&segment_name(segment_index)))?.len();
+                }
+                Ok(bytes - cursor.head_offset as usize)
+            })
+            .map_err(fs_error)?
+        };
+        Ok(stored + self.pending.len())
+    }
+
+    // Appends the pending batch to the tail segment in a single write, rolling
+    // over to a new segment when full and dropping the oldest once capped
+    fn _flush(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let mut cursor = self.cursor;
+        let pending = &self.pending;
+        let pending_count = self.pending_count;
+        let downsampler = self.downsampler;
+
+        Filesystem::mount_and_then(&mut self.storage, |fs| {
+            let tail = path(&segment_name(cursor.tail_segment));
+            let tail_len = fs.metadata(&tail).map(|m| m.len()).unwrap_or(0);
+            if tail_len > 0 && tail_len + pending.len() > StorageConstant::QUEUE_SEGMENT_BYTES {
+                cursor.tail_segment += 1;
+            }
+
+            while cursor.tail_segment - cursor.head_segment >= StorageConstant::QUEUE_MAX_SEGMENTS {
+                if let Some(downsampler) = downsampler {
+                    if merge_oldest(fs, &mut cursor, downsampler)? {
+                        continue;
+                    }
+                }
+                let oldest = path(&segment_name(cursor.head_segment));
+                let dropped = count_records(fs, &oldest, cursor.head_offset)?;
+                cursor.length -= dropped;
+                cursor.head_segment += 1;
+                cursor.head_offset = 0;
+                write_cursor(fs, &cursor)?;
+                fs.remove(&oldest)?;
+            }
+
+            fs.open_file_with_options_and_then(
+                |options| options.write(true).create(true).append(true),
+                &path(&segment_name(cursor.tail_segment)),
+                |file| file.write(pending),
+            )?;
+            cursor.length += pending_count;
+            write_cursor(fs, &cursor)
+        })
+        .map_err(fs_error)?;
+
+        self.cursor = cursor;
+        self.pending.clear();
+        self.pending_count = 0;
+        Ok(())
+    }
+
+    fn pending_head(&self) -> Option<Vec<u8>> {
+        if self.pending_count == 0 {
+            return None;
+        }
+        let length = u16::from_le_bytes([self.pending[0], self.pending[1]]) as usize;
+        Some(self.pending[RECORD_HEADER_BYTES..RECORD_HEADER_BYTES + length].to_vec())
+    }
+}
+
+// Brings the cursor in line with the segments on flash after a reset. The
+// cursor is written before segments are removed, so a reset in between
+// leaves used up segments below the head; one between a segment write and
+// the cursor write leaves records the cursor does not count yet, possibly in
+// a new segment past the tail. A head segment that is gone is stepped over
+fn recover(fs: &Filesystem<FlashPartition>, cursor: QueueCursor) -> littlefs2::io::Result<QueueCursor> {
+    let mut recovered = cursor;
+    let mut stale: Vec<u32> = Vec::new();
+    fs.read_dir_and_then(&path(StorageConstant::QUEUE_DIR), |dir| {
+        for entry in dir {
+            let entry = entry?;
+            let name: &str = entry.file_name().as_ref();
+            match name.parse::<u32>() {
+                Ok(index) if index < cursor.head_segment => stale.push(index),
+                Ok(index) => recovered.tail_segment = recovered.tail_segment.max(index),
+                Err(_) => {},
+            }
+        }
+        Ok(())
+    })?;
+    for segment_index in stale {
+        fs.remove(&path(&segment_name(segment_index)))?;
+    }
+
+    while recovered.head_segment < recovered.tail_segment
+        && fs.metadata(&path(&segment_name(recovered.head_segment))).is_err()
+    {
+        recovered.head_segment += 1;
+        recovered.head_offset = 0;
+    }
+    recovered.length = 0;
+    for segment_index in recovered.head_segment..=recovered.tail_segment {
+        let segment = path(&segment_name(segment_index));
+        if fs.metadata(&segment).is_ok() {
+            let from = if segment_index == recovered.head_segment { recovered.head_offset } else { 0 };
+            recovered.length += count_records(fs, &segment, from)?;
+        }
+    }
+
+    if recovered != cursor {
+        info!("Flash queue recovered {} records after a reset", recovered.length);
+        write_cursor(fs, &recovered)?;
+    }
+    Ok(recovered)
+}
+
+// Removes segments `from..to` once the cursor has moved past them; one
+// that is already gone was removed before a reset
+fn remove_segments(fs: &Filesystem<FlashPartition>, from: u32, to: u32) -> littlefs2::io::Result<()> {
+    for segment_index in from..to {
+        match fs.remove(&path(&segment_name(segment_index))) {
+            Ok(()) | Err(littlefs2::io::Error::NoSuchEntry) => {},
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(())
+}
+
+fn count_records(
+    fs: &Filesystem<FlashPartition>,
+    segment: &PathBuf,
+    from: u32,
+) -> littlefs2::io::Result<u32> {
+    fs.open_file_and_then(segment, |file| {
+        let mut count = 0;
+        let mut offset = from as usize;
+        let mut header = [0u8; RECORD_HEADER_BYTES];
+        file.seek(SeekFrom::Start(from))?;
+        while file.read(&mut header)? == RECORD_HEADER_BYTES {
+            offset += RECORD_HEADER_BYTES + u16::from_le_bytes(header) as usize;
+            file.seek(SeekFrom::Start(offset as u32))?;
+            count += 1;
+        }
+        Ok(count)
+    })
+}
+
+// Rewrites the head segment and the one after it as a single thinned
+// segment. Returns false, leaving both alone, when the result would not fit
+fn merge_oldest(
+    fs: &Filesystem<FlashPartition>,
+    cursor: &mut QueueCursor,
+    downsampler: fn(Vec<Vec<u8>>) -> Vec<Vec<u8>>,
+) -> littlefs2::io::Result<bool> {
+    let oldest = path(&segment_name(cursor.head_segment));
+    let next = path(&segment_name(cursor.head_segment + 1));
+    let mut records = read_records(fs, &oldest, cursor.head_offset)?;
+    records.extend(read_records(fs, &next, 0)?);
+    let before = records.len() as u32;
+    let records = downsampler(records);
+
+    let mut merged = Vec::with_capacity(StorageConstant::QUEUE_SEGMENT_BYTES);
+    for record in records.iter() {
+        merged.extend_from_slice(&(record.len() as u16).to_le_bytes());
+        merged.extend_from_slice(record);
+    }
+    if merged.len() > StorageConstant::QUEUE_SEGMENT_BYTES {
+        return Ok(false);
+    }
+    // A reset before the cursor write leaves it on the old head, which
+    // resends the merged records; their envelope ids let the server drop them.
+    // One after it leaves the old head behind for `recover` to remove
+    fs.write(&next, &merged)?;
+    cursor.length -= before - records.len() as u32;
+    cursor.head_segment += 1;
+    cursor.head_offset = 0;
+    write_cursor(fs, cursor)?;
+    fs.remove(&oldest)?;
+    Ok(true)
+}
+
+fn read_records(
+    fs: &Filesystem<FlashPartition>,
+    segment: &PathBuf,
+    from: u32,
+) -> littlefs2::io::Result<Vec<Vec<u8>>> {
+    fs.open_file_and_then(segment, |file| {
+        let mut records = Vec::new();
+        let mut header = [0u8; RECORD_HEADER_BYTES];
+        file.seek(SeekFrom::Start(from))?;
+        while file.read(&mut header)? == RECORD_HEADER_BYTES {
+            let mut payload = vec![0u8; u16::from_le_bytes(header) as usize];
+            file.read(&mut payload)?;
+            records.push(payload);
+        }
+        Ok(records)
+    })
+}
+
+fn write_cursor(fs: &Filesystem<FlashPartition>, cursor: &QueueCursor) -> littlefs2::io::Result<()> {
+    fs.write(
+        &path(StorageConstant::QUEUE_META_FILE),
+        &state_file::encode(StorageConstant::QUEUE_META_VERSION, &cursor.to_bytes()),
+    )
+}
+
+fn segment_name(index: u32) -> String {
+    format!("{}/{:08}", StorageConstant::QUEUE_DIR, index)
+}
+
+fn path(name: &str) -> PathBuf {
+    PathBuf::from(name)
+}
+
+fn fs_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("Flash queue error: {:?}", error))
+}
@@ -0,0 +1,102 @@
+use core::cell::Cell;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+use embedded_hal::i2c::I2c;
+use log::{info, warn};
+
+use crate::constants::hardware::HardwareConstant;
+use crate::constants::sensor::SensorConstant;
+use crate::dtos::configurations::hardware_profile::HardwareProfileDTO;
+use crate::enums::board_revision::BoardRevision;
+
+// One binary for every board revision: `detect` runs at boot before the
+// config and the sensor factory are built, both of which take their
+// defaults from `profile`. Rev D answers on its EEPROM, rev C straps a
+// level on an ADC pin, and a board with neither is rev B
+static REVISION: Mutex<CriticalSectionRawMutex, Cell<Option<BoardRevision>>> = Mutex::new(Cell::new(None));
+
+const REV_B: HardwareProfileDTO = HardwareProfileDTO {
+    revision: BoardRevision::B,
+    sensors: &[SensorConstant::BME280, SensorConstant::BH1750],
+    status_led_gpio: Some(2),
+    i2c_sda_gpio: HardwareConstant::I2C_SDA_GPIO,
+    i2c_scl_gpio: HardwareConstant::I2C_SCL_GPIO,
+};
+
+const REV_C: HardwareProfileDTO = HardwareProfileDTO {
+    revision: BoardRevision::C,
+    sensors: &[SensorConstant::BME280, SensorConstant::BH1750, SensorConstant::DS3231SN, SensorConstant::VL5310X],
+    status_led_gpio: Some(2),
+    i2c_sda_gpio: HardwareConstant::I2C_SDA_GPIO,
+    i2c_scl_gpio: HardwareConstant::I2C_SCL_GPIO,
+};
+
+const REV_D: HardwareProfileDTO = HardwareProfileDTO {
+    revision: BoardRevision::D,
+    sensors: &[
+        SensorConstant::BME280,
+        SensorConstant::BH1750,
+        SensorConstant::DS3231SN,
+        SensorConstant::VL5310X,
+        SensorConstant::AMG8833,
+    ],
+    status_led_gpio: Some(13),
+    i2c_sda_gpio: HardwareConstant::I2C_SDA_GPIO,
+    i2c_scl_gpio: HardwareConstant::I2C_SCL_GPIO,
+};
+
+// `strap_raw` is a one-shot ADC1 reading of `STRAP_ADC_GPIO` at 11 dB, None
+// when the caller could not take one. BOARD_REVISION overrides detection
+// for boards whose strapping was never fitted
+pub fn detect<I: I2c>(i2c: &mut I, strap_raw: Option<u16>) -> BoardRevision {
+    let revision = option_env!("BOARD_REVISION")
+        .map(|value| BoardRevision::parse(value).expect("BOARD_REVISION must be B, C or D"))
+        .or_else(|| from_eeprom(i2c))
+        .or_else(|| strap_raw.and_then(from_strap))
+        .unwrap_or(BoardRevision::B);
+    info!("Board revision {}", revision.as_str());
+    REVISION.lock(|current| current.set(Some(revision)));
+    revision
+}
+
+// Rev B until `detect` has run
+pub fn revision() -> BoardRevision {
+    REVISION.lock(|current| current.get()).unwrap_or(BoardRevision::B)
+}
+
+pub fn profile() -> HardwareProfileDTO {
+    match revision() {
+        BoardRevision::B => REV_B,
+        BoardRevision::C => REV_C,
+        BoardRevision::D => REV_D,
+    }
+}
+
+pub fn fitted(sensor: &str) -> bool {
+    profile().sensors.contains(&sensor)
+}
+
+fn from_eeprom<I: I2c>(i2c: &mut I) -> Option<BoardRevision> {
+    let mut letter = [0u8; 1];
+    i2c.write_read(HardwareConstant::EEPROM_ADDRESS, &[HardwareConstant::EEPROM_REVISION_OFFSET], &mut letter)
+        .ok()?;
+    let revision = core::str::from_utf8(&letter).ok().and_then(BoardRevision::parse);
+    if revision.is_none() {
+        warn!("Board EEPROM holds unknown revision byte {:#04x}", letter[0]);
+    }
+    revision
+}
+
+fn from_strap(raw: u16) -> Option<BoardRevision> {
+    let millivolts = raw as u32 * HardwareConstant::ADC_FULL_SCALE_MV / HardwareConstant::ADC_MAX_RAW;
+    let levels = [
+        (HardwareConstant::STRAP_B_MV, BoardRevision::B),
+        (HardwareConstant::STRAP_C_MV, BoardRevision::C),
+        (HardwareConstant::STRAP_D_MV, BoardRevision::D),
+    ];
+    levels
+        .iter()
+        .find(|(level, _)| millivolts.abs_diff(*level) <= HardwareConstant::STRAP_TOLERANCE_MV)
+        .map(|(_, revision)| *revision)
+}
@@ -0,0 +1,61 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_time::Instant;
+
+use crate::dtos::telemetry::clock_diagnostics::ClockDiagnosticsDTO;
+use crate::dtos::telemetry::device_info::DeviceInfoDTO;
+use crate::dtos::telemetry::heartbeat::HeartbeatDTO;
+use crate::enums::boot_stage::BootStage;
+use crate::services::{boot, clock, sensor_stats};
+
+// The heartbeat served on /health. The parts owned by a service are reported
+// by whoever runs it, the uploader after each upload and the Wi-Fi manager
+// after each check, through `update`; the parts kept in statics (boot stage,
+// clock, sensor counters) are read fresh by `current`
+static HEARTBEAT: Mutex<CriticalSectionRawMutex, RefCell<Option<HeartbeatDTO>>> = Mutex::new(RefCell::new(None));
+
+// Call once at boot, `update` does nothing before
+pub fn init(device_urn: String, location_urn: String, device: DeviceInfoDTO) {
+    let heartbeat = HeartbeatDTO {
+        device_urn: device_urn,
+        location_urn: location_urn,
+        timestamp: 0,
+        uptime_s: 0,
+        queue_depth: 0,
+        dropped_periodic: 0,
+        dropped_state_change: 0,
+        dropped_alert: 0,
+        device: device,
+        clock: ClockDiagnosticsDTO::default(),
+        wifi: None,
+        services: Vec::new(),
+        sensors: Vec::new(),
+        boot_stage: BootStage::Reset,
+        boot_error: None,
+        previous_boot_stage: None,
+    };
+    HEARTBEAT.lock(|current| *current.borrow_mut() = Some(heartbeat));
+}
+
+pub fn update(f: impl FnOnce(&mut HeartbeatDTO)) {
+    HEARTBEAT.lock(|current| {
+        if let Some(heartbeat) = current.borrow_mut().as_mut() {
+            f(heartbeat);
+        }
+    })
+}
+
+// None before `init`
+pub fn current() -> Option<HeartbeatDTO> {
+    let mut heartbeat = HEARTBEAT.lock(|current| current.borrow().clone())?;
+    heartbeat.timestamp = clock::now().unwrap_or(0);
+    heartbeat.uptime_s = Instant::now().as_secs();
+    boot::report(&mut heartbeat);
+    clock::report(&mut heartbeat);
+    sensor_stats::report(&mut heartbeat);
+    Some(heartbeat)
+}
@@ -1,14 +1,82 @@
+use alloc::collections::BTreeMap;
 use alloc::string::String;
 use alloc::string::ToString;
+use alloc::vec::Vec;
 use core::error::Error;
 use alloc::boxed::Box;
+#[cfg(not(test))]
 use esp_println::println;
 use alloc::format;
+use serde::Deserialize;
 
 use crate::abstractions::service::IService;
+use crate::constants::http::HttpConstant;
 use crate::dtos::response::base::BaseResponseDTO;
 
+use crate::enums::compression::Compression;
+use crate::enums::http_error::HttpError;
 use crate::enums::value::Value;
+use crate::utilities::{compression, http, url};
+
+// Joins a base URL with path segments and query parameters, encoding each
+// piece so values like "urn:esp32:device:001" survive intact
+#[derive(Debug, Clone)]
+pub struct UrlBuilder {
+    origin: String,
+    path: String,
+    query: String,
+}
+
+impl UrlBuilder {
+    // Accepts a full URL ("http://host:8080/base") or a bare path ("/base")
+    pub fn new(base_url: &str) -> Self {
+        let (origin, path) = match base_url.find("://") {
+            Some(scheme_end) => match base_url[scheme_end + 3..].find('/') {
+                Some(index) => base_url.split_at(scheme_end + 3 + index),
+                None => (base_url, ""),
+            },
+            None => ("", base_url),
+        };
+        Self {
+            origin: origin.to_string(),
+            path: path.trim_end_matches('/').to_string(),
+            query: String::new(),
+        }
+    }
+
+    // Appends one segment, encoding any '/' it contains
+    pub fn segment(mut self, segment: &str) -> Self {
+        self.path.push('/');
+        self.path.push_str(&url::encode_component(segment));
+        self
+    }
+
+    // Appends a relative path such as "/api/v1/measurements" segment by segment
+    pub fn path(mut self, path: &str) -> Self {
+        for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+            self = self.segment(segment);
+        }
+        self
+    }
+
+    pub fn query(mut self, key: &str, value: &str) -> Self {
+        self.query.push(if self.query.is_empty() { '?' } else { '&' });
+        self.query.push_str(&url::encode_component(key));
+        self.query.push('=');
+        self.query.push_str(&url::encode_component(value));
+        self
+    }
+
+    // Origin-form target for the request line, e.g. "/api/v1/x?device_urn=..."
+    pub fn target(&self) -> String {
+        let path = if self.path.is_empty() { "/" } else { self.path.as_str() };
+        format!("{}{}", path, self.query)
+    }
+
+    pub fn build(&self) -> String {
+        format!("{}{}", self.origin, self.target())
+    }
+}
 
 // Simple HTTP client using Embassy networking
 pub struct HttpClientService {
@@ -19,7 +87,47 @@ pub struct HttpClientService {
     //server_port: u16,
 }
 
-impl IService<BaseResponseDTO> for HttpClientService {
+// A single-file multipart/form-data body, framed around a streamed payload:
+// head, then `preamble()`, the file bytes, and finally `epilogue()`
+#[derive(Debug, Clone)]
+pub struct MultipartPart {
+    pub boundary: String,
+    pub field_name: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub length: usize,
+}
+
+impl MultipartPart {
+    pub fn new(field_name: &str, file_name: &str, content_type: &str, length: usize) -> Self {
+        Self {
+            boundary: String::from(HttpConstant::MULTIPART_BOUNDARY),
+            field_name: field_name.to_string(),
+            file_name: file_name.to_string(),
+            content_type: content_type.to_string(),
+            length: length,
+        }
+    }
+
+    pub fn preamble(&self) -> String {
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+            self.boundary, self.field_name, self.file_name, self.content_type
+        )
+    }
+
+    pub fn epilogue(&self) -> String {
+        format!("\r\n--{}--\r\n", self.boundary)
+    }
+
+    pub fn content_length(&self) -> usize {
+        self.preamble().len() + self.length + self.epilogue().len()
+    }
+}
+
+impl IService for HttpClientService {
+    type Response = BaseResponseDTO;
+
     fn urn(&self) -> String {
         self.urn.clone()
     }
@@ -32,7 +140,7 @@ impl IService<BaseResponseDTO> for HttpClientService {
         self.location_urn.clone()
     }
 
-    fn run(&self) -> Result<BaseResponseDTO, core::fmt::Error> {
+    async fn run(&mut self) -> Result<BaseResponseDTO, Box<dyn Error + Send + Sync>> {
         // For now, return a placeholder response
         // In a real implementation, this would make an HTTP request
         Ok(BaseResponseDTO {
@@ -60,22 +168,123 @@ impl HttpClientService {
         }
     }
 
-    // Method to create HTTP GET request string
-    pub fn create_get_request(&self, endpoint: &str) -> String {
+    pub fn server_ip(&self) -> String {
+        self.server_ip.clone()
+    }
+
+    // Identity headers sent on every request, the ingestion gateway routes
+    // on X-Device-Urn / X-Location-Urn. Entries in `headers` override them
+    fn header_block(&self, headers: &BTreeMap<String, String>) -> String {
+        let mut merged: BTreeMap<&str, &str> = BTreeMap::new();
+        let user_agent = format!("senseplus/{} ({})", HttpConstant::FIRMWARE_VERSION, HttpConstant::CHIP);
+        merged.insert("User-Agent", &user_agent);
+        merged.insert("X-Device-Urn", &self.device_urn);
+        merged.insert("X-Location-Urn", &self.location_urn);
+        for (name, value) in headers.iter() {
+            // CR/LF in a value would let it inject extra headers
+            if value.contains(['\r', '\n']) {
+                continue;
+            }
+            merged.retain(|existing, _| !existing.eq_ignore_ascii_case(name));
+            merged.insert(name, value);
+        }
+
+        let mut block = String::new();
+        for (name, value) in merged {
+            block.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        block
+    }
+
+    // Method to create HTTP GET request string, `endpoint` should come from
+    // `UrlBuilder::target`
+    pub fn create_get_request(&self, endpoint: &str, headers: &BTreeMap<String, String>) -> String {
         format!(
-            "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
-            endpoint, self.server_ip
+            "GET {} HTTP/1.1\r\nHost: {}\r\n{}Connection: close\r\n\r\n",
+            endpoint, url::host_header(&self.server_ip), self.header_block(headers)
         )
     }
 
     // Method to create HTTP POST request string
-    pub fn create_post_request(&self, endpoint: &str, json_data: &str) -> String {
+    pub fn create_post_request(&self, endpoint: &str, json_data: &str, headers: &BTreeMap<String, String>) -> String {
+        format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            endpoint, url::host_header(&self.server_ip), self.header_block(headers), json_data.len(), json_data
+        )
+    }
+
+    // Request head only, the caller streams exactly `content_length` body
+    // bytes afterwards so large artifacts never sit in the heap
+    pub fn create_binary_request_head(
+        &self,
+        endpoint: &str,
+        content_type: &str,
+        content_length: usize,
+        headers: &BTreeMap<String, String>,
+    ) -> String {
         format!(
-            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
-            endpoint, self.server_ip, json_data.len(), json_data
+            "POST {} HTTP/1.1\r\nHost: {}\r\n{}Content-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            endpoint, url::host_header(&self.server_ip), self.header_block(headers), content_type, content_length
+        )
+    }
+
+    // Full JSON POST as bytes, compressed and labelled with Content-Encoding
+    // when `compression` asks for it
+    pub fn create_encoded_post_request(
+        &self,
+        endpoint: &str,
+        json_data: &[u8],
+        compression: Compression,
+        headers: &BTreeMap<String, String>,
+    ) -> Vec<u8> {
+        let body = compression::compress(compression, json_data);
+        let mut headers = headers.clone();
+        if let Some(encoding) = compression.content_encoding() {
+            headers.insert("Content-Encoding".to_string(), encoding.to_string());
+        }
+        let mut request = self
+            .create_binary_request_head(endpoint, "application/json", body.len(), &headers)
+            .into_bytes();
+        request.extend_from_slice(&body);
+        request
+    }
+
+    pub fn create_multipart_request_head(
+        &self,
+        endpoint: &str,
+        part: &MultipartPart,
+        headers: &BTreeMap<String, String>,
+    ) -> String {
+        self.create_binary_request_head(
+            endpoint,
+            &format!("multipart/form-data; boundary={}", part.boundary),
+            part.content_length(),
+            headers,
         )
     }
 
+    // Checks for a 2xx status and deserializes the JSON body into `T`,
+    // e.g. ServerAckResponseDTO after an upload
+    pub fn parse_json_response<'a, T: Deserialize<'a>>(&self, response: &'a [u8]) -> Result<T, HttpError> {
+        let status = http::parse_status(response).ok_or(HttpError::MalformedResponse)?;
+        if !(200..300).contains(&status) {
+            return Err(HttpError::Status(status));
+        }
+        let body_start = response
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .ok_or(HttpError::MalformedResponse)?;
+        self.parse_json_body(&response[body_start + 4..])
+    }
+
+    // For bodies that were already separated from the response head
+    pub fn parse_json_body<'a, T: Deserialize<'a>>(&self, body: &'a [u8]) -> Result<T, HttpError> {
+        let body = core::str::from_utf8(body).map_err(|error| HttpError::Deserialize(error.to_string()))?;
+        serde_json_core::from_str::<T>(body)
+            .map(|(value, _)| value)
+            .map_err(|error| HttpError::Deserialize(format!("{:?}", error)))
+    }
+
     // Parse HTTP response to extract body
     pub fn parse_http_response(&self, response: &[u8]) -> Result<String, Box<dyn Error + Send + Sync>> {
         // Convert response bytes to string
@@ -101,12 +310,18 @@ pub fn example_http_usage() -> Result<(), Box<dyn Error + Send + Sync>> {
     );
 
     // Create a GET request
-    let get_request = http_client.create_get_request("/api/sensors");
+    let endpoint = UrlBuilder::new("/api")
+        .segment("sensors")
+        .query("device_urn", "urn:esp32:device:001")
+        .target();
+    let get_request = http_client.create_get_request(&endpoint, &BTreeMap::new());
     println!("GET Request: {}", get_request);
 
     // Create a POST request with JSON data
     let json_data = r#"{"temperature": 25.5, "humidity": 60.0}"#;
-    let post_request = http_client.create_post_request("/api/data", json_data);
+    let mut headers = BTreeMap::new();
+    headers.insert("X-Batch-Size".to_string(), "1".to_string());
+    let post_request = http_client.create_post_request("/api/data", json_data, &headers);
     println!("POST Request: {}", post_request);
 
     Ok(())
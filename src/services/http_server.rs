@@ -0,0 +1,192 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use core::error::Error;
+
+use log::warn;
+
+use crate::abstractions::store::IStore;
+use crate::constants::http::HttpConstant;
+use crate::constants::soak::SoakConstant;
+use crate::dtos::telemetry::heartbeat::HeartbeatDTO;
+use crate::enums::connection_state::ConnectionState;
+use crate::services::{local_access, log_buffer, log_filter, sensor_stats, soak, upload_stats};
+use crate::utilities::{http, json, websocket};
+
+// Local HTTP endpoints served by the device itself, transport agnostic: the
+// caller feeds in the raw request and forwards whatever is passed to `sink`
+pub struct HttpServerService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+}
+
+impl HttpServerService {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+    ) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    pub fn handle(
+        &self,
+        request: &[u8],
+        // None without an SD card, /export then answers 503
+        store: Option<&mut dyn IStore>,
+        health: &HeartbeatDTO,
+        sink: &mut dyn FnMut(&str) -> Result<(), Box<dyn Error + Send + Sync>>,
+    ) -> Result<ConnectionState, Box<dyn Error + Send + Sync>> {
+        let request = core::str::from_utf8(request)?;
+        let line = match http::parse_request_line(request) {
+            Some(line) => line,
+            None => {
+                sink(&http::status_response(400, "Bad Request", "Malformed request line"))?;
+                return Ok(ConnectionState::Close);
+            },
+        };
+
+        match (line.method, line.path) {
+            // Stored and live data only go to a technician who badged in
+            ("GET", HttpConstant::LIVE_PATH) if !local_access::is_allowed() => {
+                sink(&http::status_response(403, "Forbidden", "Present an authorized badge first"))?
+            },
+            ("GET", HttpConstant::LIVE_PATH) => return self.upgrade(request, sink),
+            ("GET", HttpConstant::EXPORT_PATH) if !local_access::is_allowed() => {
+                sink(&http::status_response(403, "Forbidden", "Present an authorized badge first"))?
+            },
+            ("GET", HttpConstant::EXPORT_PATH) => match store {
+                Some(store) => self.export(line.query, store, sink)?,
+                None => sink(&http::status_response(503, "Service Unavailable", "No SD card to export from"))?,
+            },
+            // Same document the device sends as its heartbeat
+            ("GET", HttpConstant::HEALTH_PATH) => sink(&http::json_response(&json::heartbeat_to_json(health)))?,
+            ("GET", HttpConstant::INFO_PATH) => sink(&http::json_response(&json::device_info_to_json(&health.device)))?,
+            // Live counters rather than the heartbeat's copy, which may be a cycle old
+            ("GET", HttpConstant::METRICS_PATH) => {
+                let body = format!(
+                    "{{\"sensors\":{},\"uploads\":{}}}",
+                    json::sensor_stats_to_json(&sensor_stats::snapshot()),
+                    json::upload_stats_to_json(&upload_stats::snapshot())
+                );
+                sink(&http::json_response(&body))?
+            },
+            // Counters of the running or last soak test, kept across resets
+            ("GET", HttpConstant::SOAK_PATH) => {
+                sink(&http::json_response(&json::soak_stats_to_json(&soak::snapshot(), SoakConstant::DURATION_S)))?
+            },
+            ("GET", HttpConstant::LOG_PATH) => {
+                sink(&http::json_response(&format!("{{\"levels\":{}}}", json::escape(&log_filter::spec()))))?
+            },
+            // PUT /log?levels=info,services::http_client=off
+            ("PUT", HttpConstant::LOG_PATH) if !local_access::is_allowed() => {
+                sink(&http::status_response(403, "Forbidden", "Present an authorized badge first"))?
+            },
+            ("PUT", HttpConstant::LOG_PATH) => match http::query_param(line.query, "levels") {
+                Some(levels) if log_filter::apply(levels) => {
+                    sink(&http::status_response(200, "OK", "Log levels updated"))?
+                },
+                _ => sink(&http::status_response(400, "Bad Request", "levels must be like info,sensors=debug"))?,
+            },
+            // Recent log output names hosts and badge UIDs, technicians only
+            ("GET", HttpConstant::LOGS_PATH) if !local_access::is_allowed() => {
+                sink(&http::status_response(403, "Forbidden", "Present an authorized badge first"))?
+            },
+            ("GET", HttpConstant::LOGS_PATH) => sink(&http::status_response(200, "OK", &log_buffer::contents()))?,
+            (_, HttpConstant::LOG_PATH) => sink(&http::status_response(405, "Method Not Allowed", "Use GET or PUT"))?,
+            (_, HttpConstant::EXPORT_PATH)
+            | (_, HttpConstant::LOGS_PATH)
+            | (_, HttpConstant::LIVE_PATH)
+            | (_, HttpConstant::HEALTH_PATH)
+            | (_, HttpConstant::INFO_PATH)
+            | (_, HttpConstant::METRICS_PATH)
+            | (_, HttpConstant::SOAK_PATH) => {
+                sink(&http::status_response(405, "Method Not Allowed", "Use GET"))?
+            },
+            _ => sink(&http::status_response(404, "Not Found", "Not found"))?,
+        }
+        Ok(ConnectionState::Close)
+    }
+
+    // GET /live, switches the connection to a WebSocket fed by live_stream
+    fn upgrade(
+        &self,
+        request: &str,
+        sink: &mut dyn FnMut(&str) -> Result<(), Box<dyn Error + Send + Sync>>,
+    ) -> Result<ConnectionState, Box<dyn Error + Send + Sync>> {
+        let is_websocket = websocket::header(request, "Upgrade")
+            .map_or(false, |value| value.eq_ignore_ascii_case("websocket"));
+        let key = match websocket::header(request, "Sec-WebSocket-Key") {
+            Some(key) if is_websocket => key,
+            _ => {
+                sink(&http::status_response(426, "Upgrade Required", "Expected a WebSocket handshake"))?;
+                return Ok(ConnectionState::Close);
+            },
+        };
+        sink(&format!(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            websocket::accept_key(key)
+        ))?;
+        Ok(ConnectionState::WebSocket)
+    }
+
+    // GET /export?from=<unix>&to=<unix>, streamed as chunked CSV
+    fn export(
+        &self,
+        query: &str,
+        store: &mut dyn IStore,
+        sink: &mut dyn FnMut(&str) -> Result<(), Box<dyn Error + Send + Sync>>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let from = http::query_param(query, "from").and_then(|value| value.parse::<u64>().ok());
+        let to = http::query_param(query, "to").and_then(|value| value.parse::<u64>().ok());
+        let (from, to) = match (from, to) {
+            (Some(from), Some(to)) if from <= to => (from, to),
+            _ => return sink(&http::status_response(400, "Bad Request", "from and to must be unix timestamps with from <= to")),
+        };
+        if (to - from) / 86_400 >= HttpConstant::EXPORT_MAX_DAYS {
+            let message = format!("Range may span at most {} days", HttpConstant::EXPORT_MAX_DAYS);
+            return sink(&http::status_response(400, "Bad Request", &message));
+        }
+
+        // The 200 goes out with the first rows, so a card that cannot be
+        // mounted or read still gets its 500. A failure after that can only
+        // cut the body short, which the missing last chunk tells the client
+        let mut started = false;
+        let exported = store.export_csv(from, to, &mut |rows| {
+            if !started {
+                sink(&http::chunked_header(HttpConstant::EXPORT_CONTENT_TYPE))?;
+                started = true;
+            }
+            sink(&http::chunk(rows))
+        });
+        match exported {
+            Err(error) if !started => {
+                warn!("Export failed: {}", error);
+                sink(&http::status_response(500, "Internal Server Error", "Stored data could not be read"))
+            },
+            Err(error) => Err(error),
+            Ok(()) if !started => {
+                sink(&http::chunked_header(HttpConstant::EXPORT_CONTENT_TYPE))?;
+                sink(http::LAST_CHUNK)
+            },
+            Ok(()) => sink(http::LAST_CHUNK),
+        }
+    }
+}
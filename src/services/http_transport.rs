@@ -0,0 +1,261 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+
+use embassy_time::Instant;
+use embedded_io_async::{Read, Write};
+
+use crate::abstractions::transport::ITransport;
+use crate::constants::http::HttpConstant;
+use crate::dtos::telemetry::upload_timing::UploadTimingDTO;
+use crate::dtos::transport::ack::TransportAck;
+use crate::enums::compression::Compression;
+use crate::enums::transport_error::TransportError;
+use crate::enums::uplink::Uplink;
+use crate::services::http_client::{HttpClientService, MultipartPart, UrlBuilder};
+use crate::services::memory;
+use crate::services::network_manager::NetworkManagerService;
+use crate::services::tls;
+use crate::services::upload_stats;
+use crate::utilities::{http, json, url};
+
+// POSTs JSON payloads, optionally DEFLATE compressed, to `server_base_url` over whichever IP interface the
+// network manager currently prefers
+pub struct HttpTransportService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    network: NetworkManagerService,
+    client: HttpClientService,
+    port: u16,
+    path: String,
+    events_path: String,
+    uploads_path: String,
+    compression: Compression,
+    // https:// base URLs, mutual TLS when a client certificate is provisioned
+    tls: bool,
+}
+
+impl HttpTransportService {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        network: NetworkManagerService,
+        server_base_url: &str,
+        compression: Compression,
+    ) -> Option<Self> {
+        let parts = url::split(server_base_url)?;
+        let client = HttpClientService::new(urn.clone(), device_urn.clone(), location_urn.clone(), parts.host.clone());
+        let path = UrlBuilder::new(&parts.path).path(HttpConstant::MEASUREMENTS_PATH).target();
+        let events_path = UrlBuilder::new(&parts.path).path(HttpConstant::EVENTS_PATH).target();
+        let uploads_path = UrlBuilder::new(&parts.path).path(HttpConstant::UPLOADS_PATH).target();
+        Some(Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            network: network,
+            client: client,
+            port: parts.port,
+            path: path,
+            events_path: events_path,
+            uploads_path: uploads_path,
+            compression: compression,
+            tls: parts.scheme == "https",
+        })
+    }
+
+    async fn post(&mut self, path: &str, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        let request = self
+            .client
+            .create_encoded_post_request(path, payload, self.compression, &BTreeMap::new());
+
+        let credentials = tls::credentials().unwrap_or_default();
+        let mut rx_buffer = memory::socket_buffer();
+        let mut tx_buffer = memory::socket_buffer();
+        let host = self.client.server_ip();
+        let started = Instant::now();
+        let mut timing = UploadTimingDTO {
+            bytes: payload.len(),
+            ..UploadTimingDTO::default()
+        };
+        let mut socket = self
+            .network
+            .connect_timed(&host, self.port, &mut rx_buffer, &mut tx_buffer, &mut timing)
+            .await?;
+        let mut phase = Instant::now();
+        let result = if self.tls {
+            let mut session = tls::connect(socket, &host, &credentials).await?;
+            timing.tls_ms = Some(upload_stats::lap(&mut phase));
+            exchange(&mut session, &request, payload.len()).await
+        } else {
+            let result = exchange(&mut socket, &request, payload.len()).await;
+            socket.close();
+            result
+        };
+        self.finish(result, &mut timing, phase, started)
+    }
+
+    fn finish(
+        &self,
+        result: Result<TransportAck, TransportError>,
+        timing: &mut UploadTimingDTO,
+        mut phase: Instant,
+        mut started: Instant,
+    ) -> Result<TransportAck, TransportError> {
+        if result.is_ok() {
+            timing.request_ms = upload_stats::lap(&mut phase);
+            timing.total_ms = upload_stats::lap(&mut started);
+            upload_stats::record(&self.urn, timing);
+        }
+        result
+    }
+
+    // Streams `length` bytes from `source` as a raw application/octet-stream
+    // body, or wrapped in multipart/form-data when `multipart` is given
+    pub async fn upload<R: Read>(
+        &mut self,
+        source: &mut R,
+        length: usize,
+        content_type: &str,
+        multipart: Option<MultipartPart>,
+    ) -> Result<TransportAck, TransportError> {
+        let headers = BTreeMap::new();
+        let head = match &multipart {
+            Some(part) => self.client.create_multipart_request_head(&self.uploads_path, part, &headers),
+            None => self.client.create_binary_request_head(&self.uploads_path, content_type, length, &headers),
+        };
+
+        let credentials = tls::credentials().unwrap_or_default();
+        let mut rx_buffer = memory::socket_buffer();
+        let mut tx_buffer = memory::socket_buffer();
+        let host = self.client.server_ip();
+        let started = Instant::now();
+        let mut timing = UploadTimingDTO {
+            bytes: length,
+            ..UploadTimingDTO::default()
+        };
+        let mut socket = self
+            .network
+            .connect_timed(&host, self.port, &mut rx_buffer, &mut tx_buffer, &mut timing)
+            .await?;
+        let mut phase = Instant::now();
+        let result = if self.tls {
+            let mut session = tls::connect(socket, &host, &credentials).await?;
+            timing.tls_ms = Some(upload_stats::lap(&mut phase));
+            stream_upload(&mut session, &head, source, length, multipart.as_ref()).await
+        } else {
+            let result = stream_upload(&mut socket, &head, source, length, multipart.as_ref()).await;
+            socket.close();
+            result
+        };
+        self.finish(result, &mut timing, phase, started)
+    }
+}
+
+impl ITransport for HttpTransportService {
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    async fn send(&mut self, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        let path = self.path.clone();
+        self.post(&path, payload).await
+    }
+
+    async fn send_event(&mut self, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        let path = self.events_path.clone();
+        self.post(&path, payload).await
+    }
+}
+
+// One request on an open connection, `bytes` the payload size reported in
+// the ack. Kept apart from the socket so host tests can run it on a fake
+pub(crate) async fn exchange<S: Read + Write>(
+    stream: &mut S,
+    request: &[u8],
+    bytes: usize,
+) -> Result<TransportAck, TransportError> {
+    stream.write_all(request).await.map_err(io_error)?;
+    read_ack(stream, bytes).await
+}
+
+async fn stream_upload<S: Read + Write, R: Read>(
+    stream: &mut S,
+    head: &str,
+    source: &mut R,
+    length: usize,
+    multipart: Option<&MultipartPart>,
+) -> Result<TransportAck, TransportError> {
+    stream.write_all(head.as_bytes()).await.map_err(io_error)?;
+    if let Some(part) = multipart {
+        stream.write_all(part.preamble().as_bytes()).await.map_err(io_error)?;
+    }
+
+    let mut chunk = [0u8; HttpConstant::READ_CHUNK_BYTES];
+    let mut remaining = length;
+    while remaining > 0 {
+        let read = source
+            .read(&mut chunk[..remaining.min(HttpConstant::READ_CHUNK_BYTES)])
+            .await
+            .map_err(io_error)?;
+        if read == 0 {
+            // Content-Length was already promised, the server will reject it
+            return Err(TransportError::Io(String::from("Upload source ended early")));
+        }
+        stream.write_all(&chunk[..read]).await.map_err(io_error)?;
+        remaining -= read;
+    }
+    if let Some(part) = multipart {
+        stream.write_all(part.epilogue().as_bytes()).await.map_err(io_error)?;
+    }
+    read_ack(stream, length).await
+}
+
+async fn read_ack<S: Read>(stream: &mut S, bytes: usize) -> Result<TransportAck, TransportError> {
+    // The status line, and the cursor when the body carries one; the rest
+    // of the body is read by typed clients
+    let mut response = [0u8; HttpConstant::ACK_RESPONSE_BYTES];
+    let mut read = stream.read(&mut response).await.map_err(|_| TransportError::Timeout)?;
+    while read > 0 && read < response.len() && !http::response_complete(&response[..read]) {
+        match stream.read(&mut response[read..]).await {
+            Ok(more) if more > 0 => read += more,
+            _ => break,
+        }
+    }
+    let response = &response[..read];
+
+    let status = check_status(response)?;
+    Ok(TransportAck {
+        uplink: Uplink::Wifi,
+        code: Some(status),
+        bytes: bytes,
+        cursor: http::body_of(response).and_then(json::cursor_of).map(String::from),
+    })
+}
+
+// The status of an upload response, an error unless it is a 2xx. Other
+// uplinks speaking HTTP map it the same way
+pub(crate) fn check_status(response: &[u8]) -> Result<u16, TransportError> {
+    match http::parse_status(response) {
+        // Only a 2xx acknowledges; any 4xx but 408 and 429, 409 included,
+        // rejects the payload and resending it would not change the answer
+        Some(status) if (200..300).contains(&status) => Ok(status),
+        Some(status) if (500..600).contains(&status) => Err(TransportError::Io(format!("HTTP {}", status))),
+        Some(408) | Some(429) => Err(TransportError::Throttled(http::retry_after(response))),
+        Some(status) => Err(TransportError::Rejected(format!("HTTP {}", status))),
+        None => Err(TransportError::Io(String::from("Malformed HTTP response"))),
+    }
+}
+
+fn io_error<E: core::fmt::Debug>(error: E) -> TransportError {
+    TransportError::Io(format!("{:?}", error))
+}
@@ -0,0 +1,108 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+
+use embassy_time::Instant;
+use log::warn;
+
+use crate::constants::impact::ImpactConstant;
+use crate::dtos::event::envelope::EventEnvelopeDTO;
+use crate::enums::event_kind::EventKind;
+use crate::enums::value::Value;
+use crate::services::{clock, message_id};
+
+// Recognises drops and knocks in the accelerometer stream of the asset
+// tracker: a stretch of free fall followed by a shock is a fall, a shock on
+// its own an impact. Feed it every sample at the accelerometer's data rate,
+// 100 Hz or more, and send what it returns right away; both kinds are alerts
+pub struct ImpactService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    // Monotonic ms at which the current free fall began
+    falling_since_ms: Option<u64>,
+    // Duration and end of the last free fall long enough to count
+    last_fall: Option<(u64, u64)>,
+    last_event_ms: Option<u64>,
+}
+
+impl ImpactService {
+    pub fn new(urn: String, device_urn: String, location_urn: String) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            falling_since_ms: None,
+            last_fall: None,
+            last_event_ms: None,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // One sample in m/s²
+    pub fn update(&mut self, x: f32, y: f32, z: f32) -> Option<EventEnvelopeDTO> {
+        let now_ms = Instant::now().as_millis();
+        // Compared squared, there is no sqrt in core
+        let magnitude_squared = x * x + y * y + z * z;
+
+        if magnitude_squared < ImpactConstant::FREE_FALL_MS2 * ImpactConstant::FREE_FALL_MS2 {
+            self.falling_since_ms.get_or_insert(now_ms);
+            return None;
+        }
+        if let Some(since_ms) = self.falling_since_ms.take() {
+            let fall_ms = now_ms - since_ms;
+            if fall_ms >= ImpactConstant::FREE_FALL_MIN_MS {
+                self.last_fall = Some((fall_ms, now_ms));
+            }
+        }
+        if magnitude_squared <= ImpactConstant::IMPACT_MS2 * ImpactConstant::IMPACT_MS2 {
+            return None;
+        }
+        if self.last_event_ms.is_some_and(|last_ms| now_ms - last_ms < ImpactConstant::HOLDOFF_MS) {
+            return None;
+        }
+        self.last_event_ms = Some(now_ms);
+
+        let mut detail = BTreeMap::new();
+        // The largest component is a lower bound of the peak, good enough
+        // to tell a knock from a drop onto concrete
+        let peak = x.abs().max(y.abs()).max(z.abs());
+        detail.insert(ImpactConstant::PEAK_MS2.to_string(), Value::Float(peak));
+        let fall = self
+            .last_fall
+            .take()
+            .filter(|(_, ended_ms)| now_ms - ended_ms <= ImpactConstant::LANDING_WINDOW_MS);
+        let kind = match fall {
+            Some((fall_ms, _)) => {
+                detail.insert(ImpactConstant::FALL_MS.to_string(), Value::Integer(fall_ms as i32));
+                warn!("Fall detected: {} ms of free fall, impact over {:.1} m/s²", fall_ms, peak);
+                EventKind::Fall
+            },
+            None => {
+                warn!("Impact detected over {:.1} m/s²", peak);
+                EventKind::Impact
+            },
+        };
+        let (timestamp, relative_ms_since_boot) = clock::stamp();
+        Some(EventEnvelopeDTO {
+            id: message_id::next(),
+            device_urn: self.device_urn.clone(),
+            location_urn: self.location_urn.clone(),
+            sensor_urn: self.urn.clone(),
+            timestamp: timestamp,
+            relative_ms_since_boot: relative_ms_since_boot,
+            kind: kind,
+            detail: detail,
+        })
+    }
+}
@@ -0,0 +1,164 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use esp_hal::gpio::Level;
+use esp_hal::rmt::{PulseCode, RxChannelAsync, TxChannelAsync};
+use log::{info, warn};
+
+use crate::constants::ir::IrConstant;
+use crate::dtos::configurations::ir::IrCodeDTO;
+use crate::dtos::event::envelope::EventEnvelopeDTO;
+use crate::dtos::response::server::command::ServerCommandResponseDTO;
+use crate::enums::event_kind::EventKind;
+use crate::enums::transport_error::TransportError;
+use crate::enums::value::Value;
+use crate::services::{clock, message_id};
+use crate::utilities::ir;
+
+// Infrared remote control on two RMT channels: what the room's remotes
+// send to the AC or projector is reported as events, and the downlink can
+// send codes itself. The TX channel has to be set up with the 38 kHz
+// carrier and both with 1 µs ticks, see `IrConstant`
+pub struct IrService<T: TxChannelAsync, R: RxChannelAsync> {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    tx: T,
+    rx: R,
+    codes: BTreeMap<String, IrCodeDTO>,
+    // RC5 flips it per key press so receivers can tell a new press from a
+    // held key
+    toggle: bool,
+}
+
+impl<T: TxChannelAsync, R: RxChannelAsync> IrService<T, R> {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        tx: T,
+        rx: R,
+        codes: BTreeMap<String, IrCodeDTO>,
+    ) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            tx: tx,
+            rx: rx,
+            codes: codes,
+            toggle: false,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // Waits for the next frame and returns its event, None for anything
+    // that does not decode
+    pub async fn receive(&mut self) -> Option<EventEnvelopeDTO> {
+        let mut data: Vec<u32> = vec![PulseCode::empty(); IrConstant::MAX_PULSES];
+        if let Err(error) = self.rx.receive(&mut data).await {
+            warn!("IR receive failed: {:?}", error);
+            return None;
+        }
+        // The receiver's output is active low
+        let mut pulses: Vec<(bool, u32)> = Vec::with_capacity(IrConstant::MAX_PULSES * 2);
+        'codes: for code in data {
+            for (level, length) in [(code.level1(), code.length1()), (code.level2(), code.length2())] {
+                if length == 0 {
+                    break 'codes;
+                }
+                pulses.push((level == Level::Low, length as u32));
+            }
+        }
+        let code = ir::decode(&pulses)?;
+        let name = self.codes.iter().find(|(_, known)| **known == code).map(|(name, _)| name.clone());
+        info!(
+            "IR {} address {:#x} command {:#x}{}",
+            code.protocol.as_str(),
+            code.address,
+            code.command,
+            name.as_deref().map(|name| format!(" ({})", name)).unwrap_or_default()
+        );
+        Some(self.event(&code, name))
+    }
+
+    // Downlink "ir_send" with a configured name or "protocol:address:command"
+    pub async fn handle_command(&mut self, command: &ServerCommandResponseDTO) -> Option<Result<(), TransportError>> {
+        if command.command != IrConstant::SEND_COMMAND {
+            return None;
+        }
+        let argument = command.argument.as_deref().unwrap_or("").trim();
+        let code = match self.codes.get(argument).copied().or_else(|| ir::parse_code(argument)) {
+            Some(code) => code,
+            None => {
+                return Some(Err(TransportError::Rejected(String::from(
+                    "Expected a configured IR code name or \"protocol:address:command\"",
+                ))));
+            },
+        };
+        Some(self.send(&code).await)
+    }
+
+    pub async fn send(&mut self, code: &IrCodeDTO) -> Result<(), TransportError> {
+        self.toggle = !self.toggle;
+        let pulses = ir::encode(code, self.toggle);
+        let mut data: Vec<u32> = pulses
+            .chunks(2)
+            .map(|pair| match pair {
+                [(mark, length), (next, next_length)] => {
+                    PulseCode::new(level(*mark), *length as u16, level(*next), *next_length as u16)
+                },
+                [(mark, length)] => PulseCode::new(level(*mark), *length as u16, Level::Low, 0),
+                _ => PulseCode::empty(),
+            })
+            .collect();
+        // A zero length marks the end for the RMT
+        data.push(PulseCode::empty());
+        self.tx.transmit(&data).await.map_err(|error| {
+            warn!("IR transmit failed: {:?}", error);
+            TransportError::Unavailable
+        })?;
+        info!("IR sent {} address {:#x} command {:#x}", code.protocol.as_str(), code.address, code.command);
+        Ok(())
+    }
+
+    fn event(&self, code: &IrCodeDTO, name: Option<String>) -> EventEnvelopeDTO {
+        let mut detail = BTreeMap::new();
+        detail.insert(IrConstant::PROTOCOL.to_string(), Value::String(code.protocol.as_str().to_string()));
+        detail.insert(IrConstant::ADDRESS.to_string(), Value::Integer(code.address as i32));
+        detail.insert(IrConstant::COMMAND.to_string(), Value::Integer(code.command as i32));
+        if let Some(name) = name {
+            detail.insert(IrConstant::NAME.to_string(), Value::String(name));
+        }
+        let (timestamp, relative_ms_since_boot) = clock::stamp();
+        EventEnvelopeDTO {
+            id: message_id::next(),
+            device_urn: self.device_urn.clone(),
+            location_urn: self.location_urn.clone(),
+            sensor_urn: self.urn.clone(),
+            timestamp: timestamp,
+            relative_ms_since_boot: relative_ms_since_boot,
+            kind: EventKind::IrReceived,
+            detail: detail,
+        }
+    }
+}
+
+// The LED is on, carrier modulated, for a mark
+fn level(mark: bool) -> Level {
+    if mark { Level::High } else { Level::Low }
+}
@@ -0,0 +1,78 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_time::Instant;
+
+use crate::constants::sensor::SensorConstant;
+use crate::enums::value::Value;
+
+struct LastValue {
+    data: BTreeMap<String, Value>,
+    // Monotonic, so a clock sync does not age the value
+    read_at_s: u64,
+}
+
+// Latest good reading per sensor key. A failed read is reported with this
+// value tagged stale instead of being left out or, worse, replaced by zeros
+// that look like a real measurement; past the max age nothing is reported
+static CACHE: Mutex<CriticalSectionRawMutex, RefCell<BTreeMap<String, LastValue>>> =
+    Mutex::new(RefCell::new(BTreeMap::new()));
+
+pub fn update(sensor: &str, data: &BTreeMap<String, Value>) {
+    CACHE.lock(|cache| {
+        cache.borrow_mut().insert(String::from(sensor), LastValue {
+            data: data.clone(),
+            read_at_s: Instant::now().as_secs(),
+        });
+    })
+}
+
+// Sensors with a cached reading, however old
+pub fn sensors() -> Vec<String> {
+    CACHE.lock(|cache| cache.borrow().keys().cloned().collect())
+}
+
+// The cached value with `stale` and `age_s` added, None when there is none
+// or it is older than `max_age_s`
+pub fn get(sensor: &str, max_age_s: u64) -> Option<BTreeMap<String, Value>> {
+    CACHE.lock(|cache| {
+        let cache = cache.borrow();
+        let last = cache.get(sensor)?;
+        let age_s = Instant::now().as_secs().saturating_sub(last.read_at_s);
+        if age_s > max_age_s {
+            return None;
+        }
+        let mut data = last.data.clone();
+        data.insert(String::from(SensorConstant::STALE), Value::Boolean(true));
+        data.insert(String::from(SensorConstant::AGE_S), Value::Integer(age_s.min(i32::MAX as u64) as i32));
+        Some(data)
+    })
+}
+
+// One numeric field of another sensor's last good reading, for stages that
+// correct one sensor with another, e.g. temperature compensation
+pub fn field(sensor: &str, field: &str, max_age_s: u64) -> Option<f32> {
+    CACHE.lock(|cache| {
+        let cache = cache.borrow();
+        let last = cache.get(sensor)?;
+        if Instant::now().as_secs().saturating_sub(last.read_at_s) > max_age_s {
+            return None;
+        }
+        last.data.get(field)?.as_f32()
+    })
+}
+
+// Caches a good reading, or falls back to the cache when the read failed
+pub fn resolve(sensor: &str, read: Option<BTreeMap<String, Value>>, max_age_s: u64) -> Option<BTreeMap<String, Value>> {
+    match read {
+        Some(data) => {
+            update(sensor, &data);
+            Some(data)
+        },
+        None => get(sensor, max_age_s),
+    }
+}
@@ -0,0 +1,51 @@
+use alloc::string::String;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::pubsub::{PubSubChannel, WaitResult};
+use embedded_io_async::Write;
+use log::{debug, warn};
+
+use crate::constants::http::HttpConstant;
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::utilities::{json, websocket};
+
+// Envelopes published here are pushed to every open WebSocket so the
+// commissioning UI can show live values
+static LIVE_CHANNEL: PubSubChannel<
+    CriticalSectionRawMutex,
+    String,
+    { HttpConstant::LIVE_QUEUE_DEPTH },
+    { HttpConstant::LIVE_MAX_CLIENTS },
+    1,
+> = PubSubChannel::new();
+
+// Never blocks the sensing loop, slow clients just miss values
+pub fn publish(envelope: &MeasurementEnvelopeDTO) {
+    LIVE_CHANNEL.immediate_publisher().publish_immediate(json::envelope_to_json(envelope));
+}
+
+// Runs for as long as the client stays connected, taking over the socket the
+// HTTP server upgraded
+pub async fn serve<W: Write>(connection: &mut W) {
+    let mut subscriber = match LIVE_CHANNEL.subscriber() {
+        Ok(subscriber) => subscriber,
+        Err(_) => {
+            warn!("Live stream client limit reached");
+            let _ = connection.write_all(&websocket::close_frame()).await;
+            return;
+        },
+    };
+
+    loop {
+        let message = match subscriber.next_message().await {
+            WaitResult::Message(message) => message,
+            WaitResult::Lagged(missed) => {
+                debug!("Live stream client skipped {} values", missed);
+                continue;
+            },
+        };
+        if connection.write_all(&websocket::text_frame(&message)).await.is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use core::cell::RefCell;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_time::{Duration, Instant};
+use log::info;
+
+struct LocalAccess {
+    // Set once technician badges are configured; without them the local
+    // endpoints stay open as before
+    required: bool,
+    unlocked_until: Option<Instant>,
+}
+
+// Gate for the local HTTP endpoints that expose stored data or change the
+// device, opened for a while by an authorized badge at the RFID reader
+static ACCESS: Mutex<CriticalSectionRawMutex, RefCell<LocalAccess>> = Mutex::new(RefCell::new(LocalAccess {
+    required: false,
+    unlocked_until: None,
+}));
+
+pub fn require(required: bool) {
+    ACCESS.lock(|access| access.borrow_mut().required = required);
+}
+
+pub fn unlock(duration_s: u64) {
+    info!("Local access unlocked for {} s", duration_s);
+    ACCESS.lock(|access| access.borrow_mut().unlocked_until = Some(Instant::now() + Duration::from_secs(duration_s)));
+}
+
+pub fn lock() {
+    ACCESS.lock(|access| access.borrow_mut().unlocked_until = None);
+}
+
+pub fn is_allowed() -> bool {
+    ACCESS.lock(|access| {
+        let access = access.borrow();
+        !access.required || access.unlocked_until.is_some_and(|until| Instant::now() < until)
+    })
+}
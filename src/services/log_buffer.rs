@@ -0,0 +1,43 @@
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use core::cell::RefCell;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+
+use crate::constants::log::LogConstant;
+
+// The last `BUFFER_BYTES` of log output, fed by `log_filter`'s logger, so an
+// intermittent problem can be looked at after the fact through GET /logs
+// without a serial cable attached. Lost on reset
+static BUFFER: Mutex<CriticalSectionRawMutex, RefCell<VecDeque<u8>>> = Mutex::new(RefCell::new(VecDeque::new()));
+
+pub fn push(line: &str) {
+    BUFFER.lock(|buffer| {
+        let mut buffer = buffer.borrow_mut();
+        buffer.extend(line.bytes());
+        buffer.push_back(b'\n');
+        if buffer.len() <= LogConstant::BUFFER_BYTES {
+            return;
+        }
+        // Whole lines only, a dump never starts mid-line
+        let excess = buffer.len() - LogConstant::BUFFER_BYTES;
+        let cut = buffer
+            .iter()
+            .skip(excess)
+            .position(|byte| *byte == b'\n')
+            .map_or(buffer.len(), |index| excess + index + 1);
+        buffer.drain(..cut);
+    });
+}
+
+pub fn contents() -> String {
+    BUFFER.lock(|buffer| {
+        let buffer = buffer.borrow();
+        let (front, back) = buffer.as_slices();
+        let mut contents = String::with_capacity(buffer.len());
+        contents.push_str(&String::from_utf8_lossy(front));
+        contents.push_str(&String::from_utf8_lossy(back));
+        contents
+    })
+}
@@ -0,0 +1,150 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::str::FromStr;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+#[cfg(all(not(test), not(feature = "defmt")))]
+use esp_println::println;
+use log::{LevelFilter, Log, Metadata, Record};
+
+use crate::dtos::response::server::command::ServerCommandResponseDTO;
+use crate::enums::transport_error::TransportError;
+use crate::services::log_buffer;
+
+struct FilterState {
+    default: LevelFilter,
+    // Module path prefix to level, the longest matching prefix wins
+    modules: BTreeMap<String, LevelFilter>,
+}
+
+// Replaces esp-println's logger, whose ESP_LOG filter is fixed at build
+// time, with one whose levels can be changed while running: from the
+// "log_level" downlink command or PUT /log on the local HTTP server. The
+// spec is the ESP_LOG syntax, e.g. "info,services::http_client=off,sensors=debug";
+// module paths are written without the crate name. In a defmt build the
+// records go out over RTT as preformatted defmt lines, so services logging
+// through `log` and esp-hal are not lost
+static FILTER: Mutex<CriticalSectionRawMutex, RefCell<FilterState>> = Mutex::new(RefCell::new(FilterState {
+    default: LevelFilter::Info,
+    modules: BTreeMap::new(),
+}));
+
+struct RuntimeLogger;
+
+static LOGGER: RuntimeLogger = RuntimeLogger;
+
+impl Log for RuntimeLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let line = format!("{} {} - {}", record.level(), record.target(), record.args());
+            #[cfg(feature = "defmt")]
+            defmt::println!("{=str}", line.as_str());
+            #[cfg(not(feature = "defmt"))]
+            println!("{}", line);
+            log_buffer::push(&line);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+// Call once instead of `esp_println::logger::init_logger_from_env`, starting
+// from the build-time ESP_LOG
+pub fn init() {
+    if log::set_logger(&LOGGER).is_err() {
+        return;
+    }
+    if !apply(option_env!("ESP_LOG").unwrap_or("info")) {
+        apply("info");
+    }
+}
+
+// Replaces the whole filter; an invalid spec changes nothing
+pub fn apply(spec: &str) -> bool {
+    let (default, modules) = match parse(spec) {
+        Some(filter) => filter,
+        None => return false,
+    };
+    let max = modules.values().copied().fold(default, Ord::max);
+    FILTER.lock(|state| {
+        let mut state = state.borrow_mut();
+        state.default = default;
+        state.modules = modules;
+    });
+    // Records above every level are dropped before formatting
+    log::set_max_level(max);
+    true
+}
+
+// The filter in effect, in the syntax `apply` takes
+pub fn spec() -> String {
+    FILTER.lock(|state| {
+        let state = state.borrow();
+        let mut parts: Vec<String> = Vec::new();
+        parts.push(state.default.as_str().to_lowercase());
+        parts.extend(
+            state
+                .modules
+                .iter()
+                .map(|(module, level)| format!("{}={}", module, level.as_str().to_lowercase())),
+        );
+        parts.join(",")
+    })
+}
+
+// Downlink "log_level", the argument is a filter spec
+pub fn handle_command(command: &ServerCommandResponseDTO) -> Option<Result<(), TransportError>> {
+    if command.command != "log_level" {
+        return None;
+    }
+    let argument = command.argument.as_deref().unwrap_or("");
+    Some(if apply(argument) {
+        Ok(())
+    } else {
+        Err(TransportError::Rejected(format!("Invalid log filter: {}", argument)))
+    })
+}
+
+fn level(target: &str) -> LevelFilter {
+    let target = target.strip_prefix(concat!(env!("CARGO_CRATE_NAME"), "::")).unwrap_or(target);
+    FILTER.lock(|state| {
+        let state = state.borrow();
+        state
+            .modules
+            .iter()
+            .filter(|(module, _)| {
+                target == module.as_str()
+                    || (target.starts_with(module.as_str()) && target[module.len()..].starts_with("::"))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map_or(state.default, |(_, level)| *level)
+    })
+}
+
+// "level", "module=level" or both, comma separated; "module::*" is read as
+// "module"
+fn parse(spec: &str) -> Option<(LevelFilter, BTreeMap<String, LevelFilter>)> {
+    let mut default = LevelFilter::Info;
+    let mut modules = BTreeMap::new();
+    for part in spec.split(',').map(|part| part.trim()).filter(|part| !part.is_empty()) {
+        match part.split_once('=') {
+            Some((module, level)) => {
+                let module = module.trim().trim_end_matches("::*");
+                if module.is_empty() {
+                    return None;
+                }
+                modules.insert(module.to_string(), LevelFilter::from_str(level.trim()).ok()?);
+            },
+            None => default = LevelFilter::from_str(part).ok()?,
+        }
+    }
+    Some((default, modules))
+}
@@ -0,0 +1,141 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::Debug;
+
+use embassy_time::{Duration, Instant, Timer};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::SpiDevice;
+use log::warn;
+
+use crate::abstractions::transport::ITransport;
+use crate::constants::lora::LoRaConstant;
+use crate::drivers::sx127x::{Sx127x, Sx127xConfig};
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::dtos::transport::ack::TransportAck;
+use crate::enums::compression::Compression;
+use crate::enums::transport_error::TransportError;
+use crate::enums::uplink::Uplink;
+use crate::utilities::{cbor, compression};
+
+// Point-to-point LoRa uplink, envelopes are sent as compact CBOR so a
+// typical sensor set fits in a single 255 byte packet. Anything longer, a
+// batch say, goes out as up to 255 fragments of one packet each, every one
+// prefixed with the message number, its index and the fragment count for
+// the gateway to reassemble. Packets are spaced to keep the time on air
+// within the regional duty cycle
+pub struct LoRaTransportService<S: SpiDevice, P: OutputPin, D: DelayNs> {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    radio: Sx127x<S, P, D>,
+    // The receiving gateway must be configured with the same setting
+    compression: Compression,
+    // Numbers the fragmented messages, wraps around
+    message: u8,
+    duty_cycle_percent: u8,
+    // Earliest the next packet may go out under the duty cycle
+    next_transmit: Instant,
+}
+
+impl<S: SpiDevice, P: OutputPin, D: DelayNs> LoRaTransportService<S, P, D>
+where
+    S::Error: Debug,
+    P::Error: Debug,
+{
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        spi: S,
+        reset: P,
+        delay: D,
+        config: Sx127xConfig,
+        compression: Compression,
+        duty_cycle_percent: u8,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        if !(1..=100).contains(&duty_cycle_percent) {
+            return Err(lora_error("Duty cycle must be 1-100%"));
+        }
+        let radio = Sx127x::new(spi, reset, delay, &config).map_err(lora_error)?;
+        Ok(Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            radio: radio,
+            compression: compression,
+            message: 0,
+            duty_cycle_percent: duty_cycle_percent,
+            next_transmit: Instant::now(),
+        })
+    }
+
+    pub async fn send_envelope(&mut self, envelope: &MeasurementEnvelopeDTO) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self._send(&cbor::envelope_to_cbor(envelope)).await
+    }
+
+    async fn _send(&mut self, payload: &[u8]) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let payload = compression::compress(self.compression, payload);
+        let fragment_bytes = LoRaConstant::MAX_PAYLOAD_BYTES - LoRaConstant::FRAGMENT_HEADER_BYTES;
+        let count = payload.len().div_ceil(fragment_bytes).max(1);
+        if count > u8::MAX as usize {
+            return Err(lora_error(format!("{} bytes need more than 255 fragments", payload.len())));
+        }
+        self.message = self.message.wrapping_add(1);
+        for index in 0..count {
+            let fragment = &payload[index * fragment_bytes..payload.len().min((index + 1) * fragment_bytes)];
+            let mut packet = Vec::with_capacity(LoRaConstant::FRAGMENT_HEADER_BYTES + fragment.len());
+            packet.extend_from_slice(&[self.message, index as u8, count as u8]);
+            packet.extend_from_slice(fragment);
+            Timer::at(self.next_transmit).await;
+            self.radio.transmit(&packet).await.map_err(lora_error)?;
+            // Off air for the rest of the cycle, 99 times the airtime at 1%
+            let airtime_ms = self.radio.airtime_ms(packet.len());
+            let off_ms = airtime_ms * (100 - self.duty_cycle_percent as u64) / self.duty_cycle_percent as u64;
+            self.next_transmit = Instant::now() + Duration::from_millis(off_ms);
+        }
+        // Nothing is received in point-to-point mode, so idle in sleep
+        self.radio.sleep().map_err(lora_error)
+    }
+}
+
+impl<S: SpiDevice, P: OutputPin, D: DelayNs> ITransport for LoRaTransportService<S, P, D>
+where
+    S::Error: Debug,
+    P::Error: Debug,
+{
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // Uploads arrive as JSON, they go over the air as the same document in
+    // CBOR
+    async fn send(&mut self, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        let encoded = cbor::from_json(payload).unwrap_or_else(|| {
+            warn!("LoRa payload is not JSON, sent as is");
+            payload.to_vec()
+        });
+        self._send(&encoded).await.map_err(|error| TransportError::Io(error.to_string()))?;
+        Ok(TransportAck {
+            uplink: Uplink::LoRa,
+            code: None,
+            bytes: payload.len(),
+            cursor: None,
+        })
+    }
+}
+
+fn lora_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("LoRa error: {:?}", error))
+}
@@ -0,0 +1,68 @@
+#[cfg(feature = "psram")]
+use alloc::vec;
+#[cfg(feature = "psram")]
+use alloc::vec::Vec;
+use core::cell::Cell;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+#[cfg(feature = "psram")]
+use log::info;
+
+use crate::constants::http::HttpConstant;
+
+// PSRAM made part of the heap at boot, 0 without
+static PSRAM_BYTES: Mutex<CriticalSectionRawMutex, Cell<usize>> = Mutex::new(Cell::new(0));
+
+// Maps the external PSRAM of WROVER modules into the heap. Call before the
+// internal heap region is added: regions are tried in order, so everything
+// without a capability requirement (batches, TLS sessions, frames) goes to
+// PSRAM while the Wi-Fi stack, which asks for internal memory, keeps it.
+// A WROOM build with the feature enabled finds none and carries on
+#[cfg(feature = "psram")]
+pub fn init_psram(psram: &esp_hal::peripherals::PSRAM<'_>) -> usize {
+    let (start, size) = esp_hal::psram::psram_raw_parts(psram);
+    if size == 0 {
+        info!("No PSRAM found, using internal RAM only");
+        return 0;
+    }
+    // Mapped by esp-hal during init and used by nothing else
+    unsafe {
+        esp_alloc::HEAP.add_region(esp_alloc::HeapRegion::new(
+            start,
+            size,
+            esp_alloc::MemoryCapability::External.into(),
+        ));
+    }
+    PSRAM_BYTES.lock(|bytes| bytes.set(size));
+    info!("PSRAM: {} KB added to the heap", size / 1024);
+    size
+}
+
+pub fn psram_bytes() -> usize {
+    PSRAM_BYTES.lock(|bytes| bytes.get())
+}
+
+// Per direction of a TCP or TLS connection. Without the `psram` feature it
+// stays a fixed array on the task's stack, as heap fragmentation would
+// otherwise cost the Wi-Fi stack its internal RAM; with it, it comes from the
+// heap, large when PSRAM was found
+#[cfg(not(feature = "psram"))]
+pub type SocketBuffer = [u8; HttpConstant::SOCKET_BUFFER_BYTES];
+#[cfg(feature = "psram")]
+pub type SocketBuffer = Vec<u8>;
+
+#[cfg(not(feature = "psram"))]
+pub fn socket_buffer() -> SocketBuffer {
+    [0u8; HttpConstant::SOCKET_BUFFER_BYTES]
+}
+
+#[cfg(feature = "psram")]
+pub fn socket_buffer() -> SocketBuffer {
+    let bytes = if psram_bytes() > 0 {
+        HttpConstant::LARGE_SOCKET_BUFFER_BYTES
+    } else {
+        HttpConstant::SOCKET_BUFFER_BYTES
+    };
+    vec![0u8; bytes]
+}
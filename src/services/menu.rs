@@ -0,0 +1,177 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use log::info;
+
+use crate::constants::menu::MenuConstant;
+use crate::constants::sensor::SensorConstant;
+use crate::dtos::event::input::InputEventDTO;
+use crate::enums::input_kind::InputKind;
+use crate::enums::menu_action::MenuAction;
+use crate::services::last_value;
+
+const ITEMS: [&str; 4] = [
+    MenuConstant::VALUES,
+    MenuConstant::I2C_SCAN,
+    MenuConstant::PROVISIONING,
+    MenuConstant::INTERVAL,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Screen {
+    Main,
+    // Index into the sensors with a cached reading
+    Values(usize),
+    // None while the scan is running
+    I2cScan(Option<Vec<u8>>),
+    Provisioning,
+    // The interval being edited, in seconds
+    Interval(u64),
+}
+
+// Installer menu, so a device can be checked and set up in the field
+// without a serial console. Turning or up/down moves, a press selects and
+// a long press goes back. It only renders text lines; the display driver
+// draws them and the caller carries out the returned actions
+pub struct MenuService {
+    screen: Screen,
+    cursor: usize,
+    interval_s: u64,
+}
+
+impl MenuService {
+    pub fn new(interval_s: u64) -> Self {
+        Self {
+            screen: Screen::Main,
+            cursor: 0,
+            interval_s: interval_s,
+        }
+    }
+
+    pub fn handle(&mut self, event: &InputEventDTO) -> Option<MenuAction> {
+        let steps = match (event.kind, event.source.as_str()) {
+            (InputKind::Rotated, _) => event.steps,
+            (InputKind::Pressed, MenuConstant::UP_BUTTON) => -1,
+            (InputKind::Pressed, MenuConstant::DOWN_BUTTON) => 1,
+            (InputKind::LongPressed, _) => {
+                self.screen = Screen::Main;
+                return None;
+            },
+            (InputKind::Pressed, _) => return self.select(),
+        };
+        self.scroll(steps);
+        None
+    }
+
+    // Called by whoever ran `MenuAction::ScanI2c`
+    pub fn set_scan_result(&mut self, addresses: Vec<u8>) {
+        if let Screen::I2cScan(result) = &mut self.screen {
+            *result = Some(addresses);
+        }
+    }
+
+    // At most `LINES` lines of at most `COLUMNS` characters
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = match &self.screen {
+            Screen::Main => ITEMS
+                .iter()
+                .enumerate()
+                .map(|(index, item)| format!("{}{}", if index == self.cursor { '>' } else { ' ' }, item))
+                .collect(),
+            Screen::Values(index) => {
+                let sensors = last_value::sensors();
+                match sensors.get(*index) {
+                    Some(sensor) => {
+                        let mut lines = vec![format!("{}/{} {}", index + 1, sensors.len(), sensor)];
+                        let data = last_value::get(sensor, u64::MAX).unwrap_or_default();
+                        lines.extend(
+                            data.iter()
+                                .filter(|(field, _)| field.as_str() != SensorConstant::STALE)
+                                .map(|(field, value)| format!("{} {}", field, value)),
+                        );
+                        lines
+                    },
+                    None => vec!["No readings yet".to_string()],
+                }
+            },
+            Screen::I2cScan(None) => vec!["Scanning...".to_string()],
+            Screen::I2cScan(Some(addresses)) if addresses.is_empty() => vec!["No devices".to_string()],
+            Screen::I2cScan(Some(addresses)) => {
+                let mut lines = vec![format!("{} devices", addresses.len())];
+                // Five addresses fit a line
+                lines.extend(addresses.chunks(5).map(|chunk| {
+                    chunk.iter().map(|address| format!("{:02x}", address)).collect::<Vec<_>>().join(" ")
+                }));
+                lines
+            },
+            Screen::Provisioning => vec!["Provisioning".to_string(), "started".to_string()],
+            Screen::Interval(interval_s) => vec![
+                MenuConstant::INTERVAL.to_string(),
+                format!("< {} s >", interval_s),
+                "Press to save".to_string(),
+            ],
+        };
+        lines.truncate(MenuConstant::LINES);
+        for line in lines.iter_mut() {
+            if let Some((end, _)) = line.char_indices().nth(MenuConstant::COLUMNS) {
+                line.truncate(end);
+            }
+        }
+        lines
+    }
+
+    fn scroll(&mut self, steps: i32) {
+        match &mut self.screen {
+            Screen::Main => {
+                self.cursor = (self.cursor as i32 + steps).rem_euclid(ITEMS.len() as i32) as usize;
+            },
+            Screen::Values(index) => {
+                let count = last_value::sensors().len().max(1) as i32;
+                *index = (*index as i32 + steps).rem_euclid(count) as usize;
+            },
+            Screen::Interval(interval_s) => {
+                let change = steps.unsigned_abs() as u64 * MenuConstant::INTERVAL_STEP_S;
+                let changed = if steps < 0 { interval_s.saturating_sub(change) } else { *interval_s + change };
+                *interval_s = changed.clamp(MenuConstant::MIN_INTERVAL_S, MenuConstant::MAX_INTERVAL_S);
+            },
+            Screen::I2cScan(_) | Screen::Provisioning => {},
+        }
+    }
+
+    fn select(&mut self) -> Option<MenuAction> {
+        match self.screen {
+            Screen::Main => match ITEMS[self.cursor] {
+                MenuConstant::VALUES => {
+                    self.screen = Screen::Values(0);
+                    None
+                },
+                MenuConstant::I2C_SCAN => {
+                    self.screen = Screen::I2cScan(None);
+                    Some(MenuAction::ScanI2c)
+                },
+                MenuConstant::PROVISIONING => {
+                    self.screen = Screen::Provisioning;
+                    Some(MenuAction::StartProvisioning)
+                },
+                _ => {
+                    self.screen = Screen::Interval(self.interval_s);
+                    None
+                },
+            },
+            Screen::Interval(interval_s) => {
+                info!("Interval set to {} s from the menu", interval_s);
+                self.interval_s = interval_s;
+                self.screen = Screen::Main;
+                Some(MenuAction::SetIntervalS(interval_s))
+            },
+            // Rerun the scan
+            Screen::I2cScan(Some(_)) => {
+                self.screen = Screen::I2cScan(None);
+                Some(MenuAction::ScanI2c)
+            },
+            _ => None,
+        }
+    }
+}
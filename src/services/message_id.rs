@@ -0,0 +1,68 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use core::cell::RefCell;
+use core::error::Error;
+use core::fmt::Debug;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+use littlefs2::fs::Filesystem;
+use littlefs2::path::PathBuf;
+use log::info;
+
+use crate::constants::storage::StorageConstant;
+use crate::utilities::flash_partition::FlashPartition;
+use crate::utilities::state_file;
+
+// "<mac>-<boot>-<sequence>", unique per device without a random source or a
+// synchronized clock, so the server can drop redelivered copies
+static PREFIX: Mutex<CriticalSectionRawMutex, RefCell<Option<String>>> = Mutex::new(RefCell::new(None));
+static SEQUENCE: AtomicU32 = AtomicU32::new(0);
+
+// Call once at boot before any envelope is created. Returns the boot count
+pub fn init(mac: &[u8; 6]) -> Result<u32, Box<dyn Error + Send + Sync>> {
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    let boot_count = Filesystem::mount_and_then(&mut storage, |fs| {
+        let path = PathBuf::from(StorageConstant::BOOT_COUNT_FILE);
+        let mut bytes = [0u8; state_file::HEADER_BYTES + 4];
+        let read = fs.open_file_and_then(&path, |file| file.read(&mut bytes)).unwrap_or(0);
+        let previous = match state_file::decode(&bytes[..read], Some(4)) {
+            Some((StorageConstant::BOOT_COUNT_VERSION, body)) => body.try_into().ok().map(u32::from_le_bytes),
+            other => state_file::unsupported(StorageConstant::BOOT_COUNT_FILE, other),
+        };
+        let boot_count = previous.unwrap_or(0).wrapping_add(1);
+        fs.write(&path, &state_file::encode(StorageConstant::BOOT_COUNT_VERSION, &boot_count.to_le_bytes()))?;
+        Ok(boot_count)
+    })
+    .map_err(id_error)?;
+
+    let mac: String = mac.iter().map(|byte| format!("{:02x}", byte)).collect();
+    PREFIX.lock(|prefix| *prefix.borrow_mut() = Some(format!("{}-{:08x}", mac, boot_count)));
+    info!("Boot count {}", boot_count);
+    Ok(boot_count)
+}
+
+pub fn next() -> String {
+    let sequence = SEQUENCE.fetch_add(1, Ordering::Relaxed);
+    let prefix = PREFIX.lock(|prefix| prefix.borrow().clone());
+    // Without `init` ids are only unique within this boot
+    format!("{}-{:08x}", prefix.as_deref().unwrap_or("000000000000-00000000"), sequence)
+}
+
+// Whether `id` was handed out since this boot, so a boot-relative stamp
+// that comes with it can be resolved against this boot's clock. Unknown
+// without `init`
+pub fn is_this_boot(id: &str) -> bool {
+    PREFIX.lock(|prefix| {
+        prefix
+            .borrow()
+            .as_deref()
+            .is_some_and(|prefix| id.strip_prefix(prefix).is_some_and(|sequence| sequence.starts_with('-')))
+    })
+}
+
+fn id_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("Message id error: {:?}", error))
+}
@@ -0,0 +1,139 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use embassy_time::Instant;
+use esp_hal::rtc_cntl;
+use esp_hal::system::Cpu;
+
+use crate::constants::metrics::MetricsConstant;
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::dtos::telemetry::device_metrics::DeviceMetricsDTO;
+use crate::dtos::telemetry::wifi_diagnostics::WifiDiagnosticsDTO;
+use crate::dtos::transport::target_metrics::TargetMetricsDTO;
+use crate::enums::value::Value;
+use crate::services::{envelope, sensor_stats, upload_stats};
+use crate::utilities::stack;
+
+// Device health as ordinary measurements on a cadence of its own, so the
+// backend can chart heap, signal and upload trouble over a fleet without
+// asking each device for its heartbeat. Hand the envelope from `due` to
+// the uploader at periodic priority
+pub struct MetricsService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    interval_s: u64,
+    last_sent_ms: Option<u64>,
+    heap_min_free_bytes: usize,
+    // Fixed for this boot
+    reset_reason: String,
+}
+
+impl MetricsService {
+    pub fn new(urn: String, device_urn: String, location_urn: String, interval_s: u64) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            interval_s: interval_s,
+            last_sent_ms: None,
+            heap_min_free_bytes: usize::MAX,
+            reset_reason: reset_reason(),
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // `targets` is `UploaderService::metrics`
+    pub fn collect(
+        &mut self,
+        wifi: Option<&WifiDiagnosticsDTO>,
+        targets: &[(String, TargetMetricsDTO)],
+    ) -> DeviceMetricsDTO {
+        let heap_free_bytes = esp_alloc::HEAP.free();
+        self.heap_min_free_bytes = self.heap_min_free_bytes.min(heap_free_bytes);
+        let uploads = upload_stats::snapshot();
+        let upload_count: u32 = uploads.iter().map(|target| target.uploads).sum();
+        DeviceMetricsDTO {
+            uptime_s: Instant::now().as_secs(),
+            heap_free_bytes: heap_free_bytes,
+            heap_min_free_bytes: self.heap_min_free_bytes,
+            stack_high_water_bytes: stack::high_water(),
+            stack_size_bytes: stack::size(),
+            rssi_dbm: wifi.and_then(|wifi| wifi.rssi_dbm),
+            reconnects: wifi.map_or(0, |wifi| wifi.reconnects),
+            queue_depth: targets.iter().map(|(_, metrics)| metrics.queued).sum(),
+            upload_successes: targets.iter().map(|(_, metrics)| metrics.successes).sum(),
+            upload_failures: targets.iter().map(|(_, metrics)| metrics.failures).sum(),
+            upload_bytes: uploads.iter().map(|target| target.bytes).sum(),
+            upload_mean_ms: (upload_count > 0).then(|| {
+                (uploads.iter().map(|target| target.total_ms).sum::<u64>() / upload_count as u64) as u32
+            }),
+            sensor_errors: sensor_stats::snapshot()
+                .iter()
+                .map(|sensor| sensor.errors.saturating_add(sensor.timeouts))
+                .sum(),
+            reset_reason: self.reset_reason.clone(),
+        }
+    }
+
+    // The metrics envelope once `interval_s` has passed since the last one,
+    // right away after boot
+    pub fn due(
+        &mut self,
+        wifi: Option<&WifiDiagnosticsDTO>,
+        targets: &[(String, TargetMetricsDTO)],
+    ) -> Option<MeasurementEnvelopeDTO> {
+        let now_ms = Instant::now().as_millis();
+        if self.last_sent_ms.is_some_and(|sent_ms| now_ms - sent_ms < self.interval_s * 1000) {
+            return None;
+        }
+        self.last_sent_ms = Some(now_ms);
+        let metrics = self.collect(wifi, targets);
+        Some(envelope::measurement(&self.device_urn, &self.location_urn, to_data(&metrics)))
+    }
+}
+
+fn to_data(metrics: &DeviceMetricsDTO) -> BTreeMap<String, Value> {
+    let mut data = BTreeMap::new();
+    let mut integer = |field: &str, value: u64| {
+        data.insert(field.to_string(), Value::Integer(value.min(i32::MAX as u64) as i32));
+    };
+    integer(MetricsConstant::UPTIME_S, metrics.uptime_s);
+    integer(MetricsConstant::HEAP_FREE_BYTES, metrics.heap_free_bytes as u64);
+    integer(MetricsConstant::HEAP_MIN_FREE_BYTES, metrics.heap_min_free_bytes as u64);
+    integer(MetricsConstant::STACK_SIZE_BYTES, metrics.stack_size_bytes as u64);
+    integer(MetricsConstant::RECONNECTS, metrics.reconnects as u64);
+    integer(MetricsConstant::QUEUE_DEPTH, metrics.queue_depth as u64);
+    integer(MetricsConstant::UPLOAD_SUCCESSES, metrics.upload_successes as u64);
+    integer(MetricsConstant::UPLOAD_FAILURES, metrics.upload_failures as u64);
+    integer(MetricsConstant::UPLOAD_BYTES, metrics.upload_bytes);
+    integer(MetricsConstant::SENSOR_ERRORS, metrics.sensor_errors as u64);
+    if let Some(high_water) = metrics.stack_high_water_bytes {
+        integer(MetricsConstant::STACK_HIGH_WATER_BYTES, high_water as u64);
+    }
+    if let Some(mean_ms) = metrics.upload_mean_ms {
+        integer(MetricsConstant::UPLOAD_MEAN_MS, mean_ms as u64);
+    }
+    if let Some(rssi_dbm) = metrics.rssi_dbm {
+        data.insert(MetricsConstant::RSSI_DBM.to_string(), Value::Integer(rssi_dbm as i32));
+    }
+    data.insert(MetricsConstant::RESET_REASON.to_string(), Value::String(metrics.reset_reason.clone()));
+    data
+}
+
+// Why this boot happened, e.g. "ChipPowerOn" or "CoreRtcWdt"
+pub fn reset_reason() -> String {
+    rtc_cntl::reset_reason(Cpu::ProCpu).map_or(String::from("unknown"), |reason| format!("{:?}", reason))
+}
@@ -0,0 +1,200 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{Debug, Display};
+
+use embassy_time::{Duration, Timer};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+#[cfg(not(test))]
+use esp_hal::gpio::Output;
+use littlefs2::fs::Filesystem;
+use littlefs2::path::PathBuf;
+use log::{error, info};
+
+use crate::config::Config;
+use crate::configurations::pin_map;
+use crate::constants::hardware::HardwareConstant;
+use crate::constants::mfg_test::MfgTestConstant;
+use crate::constants::storage::StorageConstant;
+use crate::drivers::eeprom_24cxx::Eeprom24Cxx;
+use crate::dtos::response::server::command::ServerCommandResponseDTO;
+use crate::dtos::telemetry::mfg_check::MfgCheckDTO;
+use crate::enums::board_revision::BoardRevision;
+use crate::enums::transport_error::TransportError;
+#[cfg(test)]
+use crate::host_test::fake_hal::Output;
+use crate::services::{clock, hardware_profile};
+use crate::utilities::flash_partition::FlashPartition;
+use crate::utilities::{i2c, modbus};
+
+// Production line test, run in place of the normal boot when the fixture
+// grounds `MfgTestConstant::STRAP_GPIO`, the build sets MFG_TEST or the
+// console asked for it. main runs each check, every bus and sensor the
+// board revision carries, then prints the matrix to the serial log and
+// stores the result, so the factory needs no separate test firmware.
+// Indicators can only be judged by the operator, `blink` just drives them
+pub struct MfgTestService {
+    checks: Vec<MfgCheckDTO>,
+}
+
+impl MfgTestService {
+    pub fn new() -> Self {
+        Self { checks: Vec::new() }
+    }
+
+    // `strap_low` is the level of `MfgTestConstant::STRAP_GPIO` at boot. A
+    // console request is used up by asking
+    pub fn requested(strap_low: bool) -> bool {
+        let console = match take_request() {
+            Ok(console) => console,
+            Err(error) => {
+                error!("MFG_TEST request unreadable: {}", error);
+                false
+            },
+        };
+        strap_low || console || option_env!("MFG_TEST").is_some_and(|value| value == "1" || value == "true")
+    }
+
+    // Every address the detected revision should answer on, and nothing
+    // left unaccounted for: a stray address is a solder bridge or a wrong
+    // part as often as a missing one is
+    pub fn check_i2c<I: I2c>(&mut self, bus: &str, i2c: &mut I, config: &Config) {
+        let mut expected: Vec<u8> = hardware_profile::profile()
+            .sensors
+            .iter()
+            .flat_map(|sensor| pin_map::addresses(sensor, config))
+            .collect();
+        if hardware_profile::revision() == BoardRevision::D {
+            expected.push(HardwareConstant::EEPROM_ADDRESS);
+        }
+        let found = i2c::scan(i2c);
+        for address in expected.iter() {
+            let passed = found.contains(address);
+            let detail = String::from(if passed { "ack" } else { "no ack" });
+            self.add(format!("{}.{:#04x}", bus, address), passed, detail);
+        }
+        for address in found.iter().filter(|address| !expected.contains(address)) {
+            self.add(format!("{}.{:#04x}", bus, address), false, String::from("unexpected device"));
+        }
+    }
+
+    // Any other check: a bus bring-up, a sensor read, the SD card. Ok
+    // carries the reading or value printed in the matrix
+    pub fn check<E: Display>(&mut self, name: &str, result: Result<String, E>) {
+        match result {
+            Ok(detail) => self.add(String::from(name), true, detail),
+            Err(error) => self.add(String::from(name), false, error.to_string()),
+        }
+    }
+
+    pub async fn blink(&mut self, indicators: &mut [Output<'static>]) {
+        for _ in 0..MfgTestConstant::BLINK_COUNT {
+            indicators.iter_mut().for_each(|indicator| indicator.set_high());
+            Timer::after(Duration::from_millis(MfgTestConstant::BLINK_MS)).await;
+            indicators.iter_mut().for_each(|indicator| indicator.set_low());
+            Timer::after(Duration::from_millis(MfgTestConstant::BLINK_MS)).await;
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        !self.checks.is_empty() && self.checks.iter().all(|check| check.passed)
+    }
+
+    pub fn checks(&self) -> Vec<MfgCheckDTO> {
+        self.checks.clone()
+    }
+
+    // One line per check, fixed width so the fixture can grep for FAIL
+    pub fn print(&self) {
+        let width = self.checks.iter().map(|check| check.name.len()).max().unwrap_or(0);
+        info!("MFG_TEST rev {} {} checks", hardware_profile::revision().as_str(), self.checks.len());
+        for check in self.checks.iter() {
+            let result = if check.passed { "PASS" } else { "FAIL" };
+            info!("MFG_TEST {} {:<width$} {}", result, check.name, check.detail, width = width);
+        }
+        if self.passed() {
+            info!("MFG_TEST RESULT PASS");
+        } else {
+            error!("MFG_TEST RESULT FAIL");
+        }
+    }
+
+    // Version, pass flag, check and failure counts, a bitmask of the first
+    // 32 failed checks, the Unix time and a CRC-16 of the rest
+    pub fn record(&self) -> [u8; MfgTestConstant::RESULT_LENGTH] {
+        let mut record = [0u8; MfgTestConstant::RESULT_LENGTH];
+        let failed_mask = self
+            .checks
+            .iter()
+            .take(32)
+            .enumerate()
+            .filter(|(_, check)| !check.passed)
+            .fold(0u32, |mask, (index, _)| mask | 1 << index);
+        record[0] = MfgTestConstant::RESULT_VERSION;
+        record[1] = self.passed() as u8;
+        record[2] = self.checks.len().min(u8::MAX as usize) as u8;
+        record[3] = self.checks.iter().filter(|check| !check.passed).count().min(u8::MAX as usize) as u8;
+        record[4..8].copy_from_slice(&failed_mask.to_le_bytes());
+        record[8..12].copy_from_slice(&(clock::now().unwrap_or(0) as u32).to_le_bytes());
+        let crc = modbus::crc16(&record[..12]);
+        record[12..].copy_from_slice(&crc.to_le_bytes());
+        record
+    }
+
+    // Behind the identity block, see `board_identity`
+    pub fn save_eeprom<I: I2c, D: DelayNs>(
+        &self,
+        eeprom: &mut Eeprom24Cxx<I, D>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        eeprom.write(HardwareConstant::EEPROM_MFG_TEST_OFFSET, &self.record()).map_err(mfg_test_error)
+    }
+
+    // Boards without an EEPROM keep it on the flash filesystem
+    pub fn save_flash(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+        Filesystem::mount_and_then(&mut storage, |fs| {
+            fs.write(&PathBuf::from(StorageConstant::MFG_TEST_FILE), &self.record())
+        })
+        .map_err(mfg_test_error)
+    }
+
+    fn add(&mut self, name: String, passed: bool, detail: String) {
+        self.checks.push(MfgCheckDTO {
+            name: name,
+            passed: passed,
+            detail: detail,
+        });
+    }
+}
+
+// Console "mfg_test". The checks need the buses before any service holds
+// them, so the request is stored for the next boot and the caller restarts
+pub fn handle_command(command: &ServerCommandResponseDTO) -> Option<Result<(), TransportError>> {
+    if command.command != MfgTestConstant::COMMAND {
+        return None;
+    }
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    let stored = Filesystem::mount_and_then(&mut storage, |fs| {
+        fs.write(&PathBuf::from(StorageConstant::MFG_TEST_REQUEST_FILE), &[])
+    });
+    Some(stored.map_err(|error| TransportError::Io(mfg_test_error(error).to_string())))
+}
+
+fn take_request() -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    Filesystem::mount_and_then(&mut storage, |fs| {
+        match fs.remove(&PathBuf::from(StorageConstant::MFG_TEST_REQUEST_FILE)) {
+            Ok(()) => Ok(true),
+            Err(littlefs2::io::Error::NoSuchEntry) => Ok(false),
+            Err(error) => Err(error),
+        }
+    })
+    .map_err(mfg_test_error)
+}
+
+fn mfg_test_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("Manufacturing test error: {:?}", error))
+}
@@ -1,3 +1,83 @@
 pub mod rest_client;
-//pub mod sensing_client;
-pub mod http_client;
\ No newline at end of file
+pub mod sensing_client;
+pub mod sensing_cycle;
+pub mod activity;
+pub mod air_quality;
+pub mod audio_events;
+pub mod batcher;
+#[cfg(not(test))]
+pub mod ble_advertiser;
+#[cfg(not(test))]
+pub mod ble_scanner;
+pub mod board_identity;
+pub mod boot;
+pub mod can_listener;
+pub mod http_client;
+pub mod http_server;
+pub mod http_transport;
+pub mod impact;
+#[cfg(not(test))]
+pub mod ir;
+pub mod cellular_transport;
+pub mod cli;
+pub mod clock;
+pub mod config_validator;
+#[cfg(not(test))]
+pub mod deep_sleep;
+pub mod envelope;
+pub mod event_outbox;
+#[cfg(not(test))]
+pub mod espnow_mesh;
+pub mod failover_transport;
+pub mod flash_queue;
+pub mod hardware_profile;
+pub mod heartbeat;
+pub mod last_value;
+pub mod local_access;
+pub mod menu;
+pub mod live_stream;
+pub mod log_buffer;
+pub mod log_filter;
+pub mod memory;
+pub mod message_id;
+#[cfg(not(test))]
+pub mod metrics;
+pub mod mfg_test;
+pub mod modbus;
+pub mod lora_transport;
+pub mod mqtt_transport;
+pub mod network_manager;
+#[cfg(not(test))]
+pub mod offload;
+#[cfg(not(test))]
+pub mod ota;
+pub mod people_counter;
+pub mod profile;
+pub mod remote_config;
+pub mod retention;
+pub mod rfid;
+pub mod run_hours;
+pub mod schedule_baseline;
+pub mod scheduler;
+pub mod sd_logger;
+pub mod sensor_stats;
+pub mod sntp;
+#[cfg(not(test))]
+pub mod secret_store;
+pub mod self_heating;
+pub mod soak;
+#[cfg(not(test))]
+pub mod status_led;
+pub mod supervisor;
+pub mod tamper;
+pub mod thermal_frame;
+#[cfg(not(test))]
+pub mod tls;
+#[cfg(test)]
+pub use crate::host_test::fake_tls as tls;
+pub mod udp_transport;
+pub mod upload_stats;
+pub mod uploader;
+pub mod uplink_chain;
+#[cfg(not(test))]
+pub mod wifi_manager;
\ No newline at end of file
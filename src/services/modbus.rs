@@ -0,0 +1,87 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use embedded_hal::digital::OutputPin;
+use embedded_io::ReadReady;
+use embedded_io_async::{Read, Write};
+use log::{debug, warn};
+
+use crate::drivers::modbus_rtu::ModbusRtu;
+use crate::dtos::configurations::modbus::ModbusRegisterDTO;
+use crate::enums::value::Value;
+use crate::utilities::modbus;
+
+// Polls the configured registers of external Modbus meters, power meters or
+// VFDs, and turns them into measurement fields. A register that fails is
+// left out of that cycle rather than failing the others on the same bus
+pub struct ModbusService<U: Read + Write + ReadReady, P: OutputPin> {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    master: ModbusRtu<U, P>,
+    registers: Vec<ModbusRegisterDTO>,
+}
+
+impl<U: Read + Write + ReadReady, P: OutputPin> ModbusService<U, P>
+where
+    U::Error: Debug,
+{
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        master: ModbusRtu<U, P>,
+        registers: Vec<ModbusRegisterDTO>,
+    ) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            master: master,
+            registers: registers,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // One pass over all registers, to be merged into the cycle's data
+    pub async fn poll(&mut self) -> BTreeMap<String, Value> {
+        let mut data = BTreeMap::new();
+        for register in &self.registers {
+            let read = self.master.read_registers(
+                register.slave,
+                register.kind.function(),
+                register.address,
+                register.data_type.registers(),
+            ).await;
+            match read.map(|words| modbus::decode(&words, register.data_type)) {
+                Ok(Some(value)) => {
+                    debug!("Modbus {} = {}", register.field, value * register.scale);
+                    data.insert(register.field.clone(), Value::Float(value * register.scale));
+                },
+                Ok(None) => warn!("Modbus {} returned too few registers", register.field),
+                Err(error) => warn!(
+                    "Modbus read of {} (slave {}, {} {}) failed: {:?}",
+                    register.field,
+                    register.slave,
+                    register.kind.as_str(),
+                    register.address,
+                    error
+                ),
+            }
+        }
+        data
+    }
+}
@@ -0,0 +1,161 @@
+use alloc::format;
+use alloc::string::String;
+
+use embassy_time::Instant;
+use embedded_io_async::{Read, Write};
+
+use crate::abstractions::transport::ITransport;
+use crate::constants::mqtt::MqttConstant;
+use crate::dtos::telemetry::upload_timing::UploadTimingDTO;
+use crate::dtos::transport::ack::TransportAck;
+use crate::enums::transport_error::TransportError;
+use crate::enums::uplink::Uplink;
+use crate::services::memory;
+use crate::services::network_manager::NetworkManagerService;
+use crate::services::tls;
+use crate::services::upload_stats;
+use crate::utilities::{mqtt, url};
+
+// Publishes each payload at QoS 1 on a short-lived broker session, which
+// keeps no socket open between the long gaps of a sensing cycle
+pub struct MqttTransportService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    network: NetworkManagerService,
+    host: String,
+    port: u16,
+    topic: String,
+    event_topic: String,
+    username: Option<String>,
+    password: Option<String>,
+    packet_id: u16,
+    // mqtts:// brokers, e.g. AWS IoT which requires a client certificate
+    tls: bool,
+}
+
+impl MqttTransportService {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        network: NetworkManagerService,
+        broker_url: &str,
+        username: Option<String>,
+        password: Option<String>,
+    ) -> Option<Self> {
+        let parts = url::split(broker_url)?;
+        let topic = MqttConstant::DEFAULT_TOPIC.replace("{device_urn}", &device_urn);
+        let event_topic = MqttConstant::EVENT_TOPIC.replace("{device_urn}", &device_urn);
+        Some(Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            network: network,
+            host: parts.host,
+            port: parts.port,
+            topic: topic,
+            event_topic: event_topic,
+            username: username,
+            password: password,
+            packet_id: 0,
+            tls: parts.scheme == "mqtts",
+        })
+    }
+
+    async fn publish<S: Read + Write>(&mut self, socket: &mut S, topic: &str, payload: &[u8]) -> Result<u16, TransportError> {
+        let connect = mqtt::connect(
+            &self.device_urn,
+            self.username.as_deref(),
+            self.password.as_deref(),
+            MqttConstant::KEEP_ALIVE_S,
+        );
+        socket.write_all(&connect).await.map_err(io_error)?;
+        let mut reply = [0u8; 4];
+        socket.read_exact(&mut reply).await.map_err(|_| TransportError::Timeout)?;
+        match mqtt::parse_connack(&reply) {
+            Some(0) => {},
+            Some(code) => return Err(TransportError::Rejected(format!("MQTT CONNACK {}", code))),
+            None => return Err(TransportError::Io(String::from("Expected MQTT CONNACK"))),
+        }
+
+        // Packet id 0 is reserved
+        self.packet_id = self.packet_id.wrapping_add(1).max(1);
+        socket
+            .write_all(&mqtt::publish(topic, payload, self.packet_id))
+            .await
+            .map_err(io_error)?;
+        socket.read_exact(&mut reply).await.map_err(|_| TransportError::Timeout)?;
+        if mqtt::parse_puback(&reply) != Some(self.packet_id) {
+            return Err(TransportError::Io(String::from("Expected MQTT PUBACK")));
+        }
+        socket.write_all(&mqtt::disconnect()).await.map_err(io_error)?;
+        Ok(self.packet_id)
+    }
+
+    async fn deliver(&mut self, topic: &str, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        let credentials = tls::credentials().unwrap_or_default();
+        let mut rx_buffer = memory::socket_buffer();
+        let mut tx_buffer = memory::socket_buffer();
+        let host = self.host.clone();
+        let mut started = Instant::now();
+        let mut timing = UploadTimingDTO {
+            bytes: payload.len(),
+            ..UploadTimingDTO::default()
+        };
+        let mut socket = self
+            .network
+            .connect_timed(&host, self.port, &mut rx_buffer, &mut tx_buffer, &mut timing)
+            .await?;
+        let mut phase = Instant::now();
+        let result = if self.tls {
+            let mut session = tls::connect(socket, &host, &credentials).await?;
+            timing.tls_ms = Some(upload_stats::lap(&mut phase));
+            self.publish(&mut session, topic, payload).await
+        } else {
+            let result = self.publish(&mut socket, topic, payload).await;
+            socket.close();
+            result
+        };
+
+        let packet_id = result?;
+        // CONNECT through PUBACK counts as the request
+        timing.request_ms = upload_stats::lap(&mut phase);
+        timing.total_ms = upload_stats::lap(&mut started);
+        upload_stats::record(&self.urn, &timing);
+        Ok(TransportAck {
+            uplink: Uplink::Mqtt,
+            code: Some(packet_id),
+            bytes: payload.len(),
+            cursor: None,
+        })
+    }
+}
+
+impl ITransport for MqttTransportService {
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    async fn send(&mut self, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        let topic = self.topic.clone();
+        self.deliver(&topic, payload).await
+    }
+
+    async fn send_event(&mut self, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        let topic = self.event_topic.clone();
+        self.deliver(&topic, payload).await
+    }
+}
+
+fn io_error<E: core::fmt::Debug>(error: E) -> TransportError {
+    TransportError::Io(format!("{:?}", error))
+}
@@ -0,0 +1,218 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use core::net::IpAddr;
+
+use embassy_net::dns::DnsQueryType;
+use embassy_net::tcp::{ConnectError, TcpSocket};
+use embassy_net::{Config, ConfigV4, ConfigV6, IpAddress, Ipv4Cidr, Ipv6Cidr, Stack, StaticConfigV4, StaticConfigV6};
+use embassy_time::{Duration, Instant};
+use log::{info, warn};
+
+use crate::constants::http::HttpConstant;
+use crate::constants::network::NetworkConstant;
+use crate::dtos::configurations::network::{StaticIpv4ConfigDTO, StaticIpv6ConfigDTO};
+use crate::dtos::telemetry::upload_timing::UploadTimingDTO;
+use crate::enums::boot_stage::BootStage;
+use crate::enums::network_interface::NetworkInterface;
+use crate::enums::transport_error::TransportError;
+use crate::services::{boot, upload_stats};
+
+// Picks the first usable interface in configured priority order, so a wired
+// link takes over as soon as it has an address and Wi-Fi covers outages
+#[derive(Clone)]
+pub struct NetworkManagerService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    priority: Vec<NetworkInterface>,
+    ethernet: Option<Stack<'static>>,
+    wifi: Option<Stack<'static>>,
+    active: Option<NetworkInterface>,
+}
+
+impl NetworkManagerService {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        priority: Vec<NetworkInterface>,
+    ) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            priority: priority,
+            ethernet: None,
+            wifi: None,
+            active: None,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    pub fn attach(&mut self, interface: NetworkInterface, stack: Stack<'static>) {
+        match interface {
+            NetworkInterface::Ethernet => self.ethernet = Some(stack),
+            NetworkInterface::Wifi => self.wifi = Some(stack),
+        }
+    }
+
+    // False when no interface came up at boot, the device then logs offline
+    pub fn has_interface(&self) -> bool {
+        self.ethernet.is_some() || self.wifi.is_some()
+    }
+
+    // Every attached interface, for servers that listen on all of them
+    pub fn stacks(&self) -> impl Iterator<Item = Stack<'static>> {
+        self.ethernet.into_iter().chain(self.wifi)
+    }
+
+    pub fn active(&self) -> Option<NetworkInterface> {
+        self.active
+    }
+
+    // Re-evaluated before every upload; interfaces missing from the priority
+    // list are never used even when attached
+    pub fn select(&mut self) -> Option<Stack<'static>> {
+        let selected = self.priority.iter().copied().find_map(|interface| {
+            let stack = match interface {
+                NetworkInterface::Ethernet => self.ethernet,
+                NetworkInterface::Wifi => self.wifi,
+            }?;
+            (stack.is_link_up() && stack.is_config_up()).then_some((interface, stack))
+        });
+
+        let interface = selected.map(|(interface, _)| interface);
+        if interface != self.active {
+            info!("Network uplink changed from {:?} to {:?}", self.active, interface);
+            self.active = interface;
+        }
+        if interface.is_some() {
+            boot::advance(BootStage::NetUp);
+        }
+        selected.map(|(_, stack)| stack)
+    }
+
+    // Resolves `host` and opens a TCP connection on the selected interface
+    pub async fn connect<'a>(
+        &mut self,
+        host: &str,
+        port: u16,
+        rx_buffer: &'a mut [u8],
+        tx_buffer: &'a mut [u8],
+    ) -> Result<TcpSocket<'a>, TransportError> {
+        let mut timing = UploadTimingDTO::default();
+        self.connect_timed(host, port, rx_buffer, tx_buffer, &mut timing).await
+    }
+
+    // `connect`, filling in the DNS and connect phases of `timing`
+    pub async fn connect_timed<'a>(
+        &mut self,
+        host: &str,
+        port: u16,
+        rx_buffer: &'a mut [u8],
+        tx_buffer: &'a mut [u8],
+        timing: &mut UploadTimingDTO,
+    ) -> Result<TcpSocket<'a>, TransportError> {
+        let stack = self.select().ok_or(TransportError::Unavailable)?;
+        let mut phase = Instant::now();
+        let addresses = resolve(stack, host).await;
+        timing.dns_ms = upload_stats::lap(&mut phase);
+        if addresses.is_empty() {
+            return Err(TransportError::Unavailable);
+        }
+
+        // Happy-eyeballs without the parallelism: IPv6 candidates come
+        // first and each failure falls through to the next address
+        let mut socket = TcpSocket::new(stack, rx_buffer, tx_buffer);
+        socket.set_timeout(Some(Duration::from_millis(HttpConstant::SOCKET_TIMEOUT_MS)));
+        for address in addresses {
+            if socket.connect((address, port)).await.is_ok() {
+                timing.connect_ms = upload_stats::lap(&mut phase);
+                return Ok(socket);
+            }
+            socket.abort();
+        }
+        Err(TransportError::Unavailable)
+    }
+}
+
+// Interface configuration: static IPv4 when configured, DHCPv4 otherwise,
+// plus the static IPv6 address when one is configured
+pub fn stack_config(static_ipv6: Option<&StaticIpv6ConfigDTO>, static_ipv4: Option<&StaticIpv4ConfigDTO>) -> Config {
+    let mut config = match static_ipv4 {
+        Some(static_ipv4) => Config::ipv4_static(static_config(static_ipv4)),
+        None => Config::dhcpv4(Default::default()),
+    };
+    if let Some(static_ipv6) = static_ipv6 {
+        config.ipv6 = ConfigV6::Static(StaticConfigV6 {
+            address: Ipv6Cidr::new(static_ipv6.address, static_ipv6.prefix_length),
+            gateway: static_ipv6.gateway,
+            dns_servers: heapless::Vec::new(),
+        });
+    }
+    config
+}
+
+fn static_config(static_ipv4: &StaticIpv4ConfigDTO) -> StaticConfigV4 {
+    let mut dns_servers = heapless::Vec::new();
+    for server in static_ipv4.dns_servers.iter().take(dns_servers.capacity()) {
+        let _ = dns_servers.push(*server);
+    }
+    StaticConfigV4 {
+        address: Ipv4Cidr::new(static_ipv4.address, static_ipv4.prefix_length),
+        gateway: Some(static_ipv4.gateway),
+        dns_servers: dns_servers,
+    }
+}
+
+// embassy-net has no ARP API, so reachability of the gateway stands in for
+// the ARP check: any answer to a TCP probe (even a reset) means the neighbour
+// resolved, while a timeout means the static settings are wrong for this
+// VLAN and the interface is switched back to DHCP
+pub async fn verify_static(stack: Stack<'static>, static_ipv4: &StaticIpv4ConfigDTO) -> bool {
+    let mut rx_buffer = [0u8; 64];
+    let mut tx_buffer = [0u8; 64];
+    let mut socket = TcpSocket::new(stack, &mut rx_buffer, &mut tx_buffer);
+    socket.set_timeout(Some(Duration::from_millis(NetworkConstant::ARP_PROBE_TIMEOUT_MS)));
+    let reachable = match socket.connect((static_ipv4.gateway, NetworkConstant::ARP_PROBE_PORT)).await {
+        Ok(()) | Err(ConnectError::ConnectionReset) => true,
+        Err(_) => false,
+    };
+    socket.abort();
+
+    if !reachable {
+        warn!("Gateway {} did not answer, falling back to DHCP", static_ipv4.gateway);
+        stack.set_config_v4(ConfigV4::Dhcp(Default::default()));
+    }
+    reachable
+}
+
+// Literal addresses are used as-is; names get AAAA records first (only when
+// the interface actually has an IPv6 address) followed by A records
+async fn resolve(stack: Stack<'static>, host: &str) -> Vec<IpAddress> {
+    if let Ok(address) = host.parse::<IpAddr>() {
+        return alloc::vec![address.into()];
+    }
+    let mut addresses = Vec::new();
+    if stack.config_v6().is_some() {
+        if let Ok(found) = stack.dns_query(host, DnsQueryType::Aaaa).await {
+            addresses.extend(found.iter().copied());
+        }
+    }
+    if let Ok(found) = stack.dns_query(host, DnsQueryType::A).await {
+        addresses.extend(found.iter().copied());
+    }
+    addresses
+}
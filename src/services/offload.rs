@@ -0,0 +1,68 @@
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::channel::Channel;
+use embassy_sync::signal::Signal;
+use embassy_time::Instant;
+use esp_hal::system::{AppCoreGuard, CpuControl, Error, Stack};
+use esp_hal_embassy::Executor;
+use log::{info, warn};
+use static_cell::StaticCell;
+
+use crate::constants::offload::OffloadConstant;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+// Work that holds the CPU for long stretches, the audio detectors' filter
+// banks or drivers that bit-bang microsecond timings, must not run on the
+// main executor: while it runs nothing else is polled, the Wi-Fi stack
+// starves and the watchdog fed from there trips. `start` hands the second
+// core to an executor of its own that does nothing but run such jobs one
+// after the other; callers on the main core await the result without
+// blocking. Until `start` has run, `run` waits for good
+static JOBS: Channel<CriticalSectionRawMutex, Job, { OffloadConstant::QUEUE_DEPTH }> = Channel::new();
+
+static APP_CORE_STACK: StaticCell<Stack<{ OffloadConstant::STACK_BYTES }>> = StaticCell::new();
+// Dropping the guard would park the core again
+static APP_CORE_GUARD: StaticCell<AppCoreGuard<'static>> = StaticCell::new();
+
+pub fn start(mut cpu_control: CpuControl<'static>) -> Result<(), Error> {
+    let stack = APP_CORE_STACK.init(Stack::new());
+    let guard = cpu_control.start_app_core(stack, || {
+        static EXECUTOR: StaticCell<Executor> = StaticCell::new();
+        let executor = EXECUTOR.init(Executor::new());
+        executor.run(|spawner| spawner.must_spawn(worker()));
+    })?;
+    APP_CORE_GUARD.init(guard);
+    info!("Blocking driver offload running on the app core");
+    Ok(())
+}
+
+// Runs `job` on the offload core and waits for its result. The job itself
+// may block and busy-wait as long as it needs, but not inside a critical
+// section: on the ESP32 those also take a spinlock shared by both cores, so
+// the main core stalls at its next critical section until the job leaves
+pub async fn run<T, F>(job: F) -> T
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let done = Arc::new(Signal::<CriticalSectionRawMutex, T>::new());
+    let signal = done.clone();
+    JOBS.send(Box::new(move || signal.signal(job()))).await;
+    done.wait().await
+}
+
+#[embassy_executor::task]
+async fn worker() -> ! {
+    loop {
+        let job = JOBS.receive().await;
+        let started = Instant::now();
+        job();
+        let elapsed_ms = started.elapsed().as_millis();
+        if elapsed_ms > OffloadConstant::SLOW_JOB_MS {
+            warn!("Offloaded job took {} ms", elapsed_ms);
+        }
+    }
+}
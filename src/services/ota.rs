@@ -0,0 +1,489 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use embassy_time::{Duration, Instant};
+use embedded_io_async::{Read, Write};
+use embedded_storage::nor_flash::{NorFlash, ReadNorFlash};
+use esp_bootloader_esp_idf::ota::{Ota, OtaImageState, Slot};
+use esp_bootloader_esp_idf::partitions::{
+    self, AppPartitionSubType, DataPartitionSubType, FlashRegion, PartitionEntry, PartitionTable, PartitionType,
+};
+use esp_storage::FlashStorage;
+use log::{info, warn};
+use sha2::{Digest, Sha512};
+
+use crate::constants::http::HttpConstant;
+use crate::constants::ota::OtaConstant;
+use crate::constants::secret::SecretConstant;
+use crate::constants::storage::StorageConstant;
+use crate::dtos::response::server::firmware::ServerFirmwareResponseDTO;
+use crate::enums::transport_error::TransportError;
+use crate::services::http_client::{HttpClientService, UrlBuilder};
+use crate::services::memory;
+use crate::services::network_manager::NetworkManagerService;
+use crate::services::secret_store::SecretStoreService;
+use crate::services::tls;
+use crate::utilities::delta::{DeltaDecoder, DeltaError};
+use crate::utilities::{hex, http, url};
+
+// Signed A/B firmware updates. An image is only activated after its
+// signature and security version check out, and a freshly booted image is
+// rolled back unless `confirm` is called within the self-test window
+pub struct OtaService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    network: NetworkManagerService,
+    client: HttpClientService,
+    port: u16,
+    manifest_path: String,
+    tls: bool,
+    public_key: VerifyingKey,
+    // Security version of the running image
+    version: u32,
+    // Only this version is installed when set, for staged fleet rollouts
+    pinned_version: Option<u32>,
+    self_test_deadline: Option<Instant>,
+}
+
+impl OtaService {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        network: NetworkManagerService,
+        server_base_url: &str,
+        public_key: [u8; 32],
+        version: u32,
+        pinned_version: Option<u32>,
+    ) -> Option<Self> {
+        let parts = url::split(server_base_url)?;
+        let public_key = VerifyingKey::from_bytes(&public_key).ok()?;
+        let client = HttpClientService::new(urn.clone(), device_urn.clone(), location_urn.clone(), parts.host.clone());
+        let manifest_path = UrlBuilder::new(&parts.path)
+            .path(OtaConstant::MANIFEST_PATH)
+            .query("device_urn", &device_urn)
+            .query("version", &format!("{}", version))
+            .target();
+        Some(Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            network: network,
+            client: client,
+            port: parts.port,
+            manifest_path: manifest_path,
+            tls: parts.scheme == "https",
+            public_key: public_key,
+            version: version,
+            pinned_version: pinned_version,
+            self_test_deadline: None,
+        })
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // Call once at boot, starts the self-test window for a new image
+    pub fn check_boot(&mut self) -> Result<(), TransportError> {
+        let state = with_ota(|ota| ota.current_ota_state())?;
+        if matches!(state, OtaImageState::New | OtaImageState::PendingVerify) {
+            info!("Running unconfirmed firmware, self-test window of {} s", OtaConstant::SELF_TEST_WINDOW_S);
+            with_ota(|ota| ota.set_current_ota_state(OtaImageState::PendingVerify))?;
+            self.self_test_deadline = Some(Instant::now() + Duration::from_secs(OtaConstant::SELF_TEST_WINDOW_S));
+        }
+        Ok(())
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.self_test_deadline.is_some()
+    }
+
+    // Call after the first successful upload. Raises the anti-rollback floor
+    // to the running version so older images are refused from now on
+    pub fn confirm(&mut self, secrets: &mut SecretStoreService) -> Result<(), TransportError> {
+        if self.self_test_deadline.take().is_none() {
+            return Ok(());
+        }
+        with_ota(|ota| ota.set_current_ota_state(OtaImageState::Valid))?;
+        if self.version > version_floor(secrets)? {
+            secrets
+                .put(SecretConstant::OTA_VERSION_FLOOR, &self.version.to_le_bytes())
+                .map_err(|error| TransportError::Io(error.to_string()))?;
+        }
+        info!("Firmware version {} confirmed", self.version);
+        Ok(())
+    }
+
+    // Call periodically, reboots into the previous image once the window
+    // has passed without a `confirm`
+    pub fn poll_self_test(&mut self) -> Result<(), TransportError> {
+        match self.self_test_deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                warn!("Self-test window expired, rolling back");
+                with_ota(|ota| {
+                    ota.set_current_ota_state(OtaImageState::Invalid)?;
+                    let previous = match ota.current_slot()? {
+                        Slot::None => Slot::Slot0,
+                        slot => slot,
+                    }
+                    .next();
+                    ota.set_current_slot(previous)
+                })?;
+                esp_hal::system::software_reset()
+            },
+            _ => Ok(()),
+        }
+    }
+
+    // Returns true when a new image was written and activated, the caller
+    // decides when to reboot into it
+    pub async fn update(&mut self, secrets: &mut SecretStoreService) -> Result<bool, TransportError> {
+        let request = self.client.create_get_request(&self.manifest_path, &BTreeMap::new());
+        let mut body = vec![0u8; OtaConstant::MANIFEST_MAX_BYTES];
+        let length = self.fetch(request.as_bytes(), &mut Manifest { body: &mut body, length: 0 }).await?;
+        if length == 0 {
+            // 204 No Content, nothing newer for this device
+            return Ok(false);
+        }
+        let manifest: ServerFirmwareResponseDTO = self
+            .client
+            .parse_json_body(&body[..length])
+            .map_err(|error| TransportError::Io(format!("{:?}", error)))?;
+
+        let floor = version_floor(secrets)?.max(self.version);
+        if manifest.version <= floor {
+            return Ok(false);
+        }
+        if self.pinned_version.map_or(false, |pinned| pinned != manifest.version) {
+            info!("Skipping firmware {} ({}), pinned to {:?}", manifest.version, manifest.semver, self.pinned_version);
+            return Ok(false);
+        }
+        if manifest.size > OtaConstant::SLOT_SIZE {
+            return Err(TransportError::PayloadTooLarge(manifest.size));
+        }
+        let signature = hex::decode(&manifest.signature)
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .ok_or_else(|| TransportError::Rejected(String::from("Malformed firmware signature")))?;
+
+        let running = running_slot()?;
+        let target = running.next();
+        let delta = manifest.patch.as_ref().filter(|patch| patch.base_version == self.version);
+        let (hasher, written) = match delta {
+            Some(patch) => {
+                info!("Downloading firmware {} ({}) as a {} byte delta", manifest.version, manifest.semver, patch.size);
+                match self.install_delta(&patch.path, running, target, manifest.version).await {
+                    Ok(image) => image,
+                    Err(error) => {
+                        warn!("Delta update failed ({:?}), downloading the full image", error);
+                        self.install_full(&manifest.path, target, manifest.version).await?
+                    },
+                }
+            },
+            None => {
+                info!("Downloading firmware {} ({}), {} bytes", manifest.version, manifest.semver, manifest.size);
+                self.install_full(&manifest.path, target, manifest.version).await?
+            },
+        };
+        if written != manifest.size {
+            return Err(TransportError::Io(format!("Firmware is {} bytes, expected {}", written, manifest.size)));
+        }
+        // Nothing is activated unless the signature holds. It covers the
+        // version too, so an old image cannot be replayed under a new number
+        self.public_key
+            .verify_prehashed(hasher, None, &signature)
+            .map_err(|_| TransportError::Rejected(String::from("Firmware signature mismatch")))?;
+
+        with_ota(|ota| {
+            ota.set_current_slot(target)?;
+            ota.set_current_ota_state(OtaImageState::New)
+        })?;
+        info!("Firmware {} activated, reboot to apply", manifest.version);
+        Ok(true)
+    }
+
+    async fn install_full(&mut self, path: &str, target: Slot, version: u32) -> Result<(Sha512, usize), TransportError> {
+        let mut flash = FlashStorage::new();
+        let mut table = [0u8; partitions::PARTITION_TABLE_MAX_LEN];
+        let table = partitions::read_partition_table(&mut flash, &mut table).map_err(ota_error)?;
+        let partition = app_partition(&table, target)?;
+        let mut image = ImageWriter::new(partition.as_embedded_storage(&mut flash), version);
+
+        let request = self.client.create_get_request(&UrlBuilder::new(path).target(), &BTreeMap::new());
+        self.fetch(request.as_bytes(), &mut image).await?;
+        image.finish()
+    }
+
+    // Rebuilds the new image from the running one plus a downloaded patch,
+    // see utilities::delta for the format
+    async fn install_delta(
+        &mut self,
+        path: &str,
+        running: Slot,
+        target: Slot,
+        version: u32,
+    ) -> Result<(Sha512, usize), TransportError> {
+        let mut flash = FlashStorage::new();
+        let mut old_flash = FlashStorage::new();
+        let mut table = [0u8; partitions::PARTITION_TABLE_MAX_LEN];
+        let table = partitions::read_partition_table(&mut flash, &mut table).map_err(ota_error)?;
+        let old = app_partition(&table, running)?;
+        let new = app_partition(&table, target)?;
+        let mut patch = PatchWriter {
+            decoder: DeltaDecoder::new(),
+            old: old.as_embedded_storage(&mut old_flash),
+            image: ImageWriter::new(new.as_embedded_storage(&mut flash), version),
+        };
+
+        let request = self.client.create_get_request(&UrlBuilder::new(path).target(), &BTreeMap::new());
+        self.fetch(request.as_bytes(), &mut patch).await?;
+        if !patch.decoder.is_complete() {
+            return Err(TransportError::Io(String::from("Delta patch ended early")));
+        }
+        patch.image.finish()
+    }
+
+    // Sends `request` and feeds the response body to `sink`, returning the
+    // body length
+    async fn fetch(&mut self, request: &[u8], sink: &mut dyn BodySink) -> Result<usize, TransportError> {
+        let credentials = tls::credentials().unwrap_or_default();
+        let mut rx_buffer = memory::socket_buffer();
+        let mut tx_buffer = memory::socket_buffer();
+        let host = self.client.server_ip();
+        let mut socket = self.network.connect(&host, self.port, &mut rx_buffer, &mut tx_buffer).await?;
+        if self.tls {
+            let mut session = tls::connect(socket, &host, &credentials).await?;
+            return read_body(&mut session, request, sink).await;
+        }
+        let result = read_body(&mut socket, request, sink).await;
+        socket.close();
+        result
+    }
+}
+
+trait BodySink {
+    fn accept(&mut self, data: &[u8]) -> Result<(), TransportError>;
+}
+
+struct Manifest<'a> {
+    body: &'a mut [u8],
+    length: usize,
+}
+
+impl BodySink for Manifest<'_> {
+    fn accept(&mut self, data: &[u8]) -> Result<(), TransportError> {
+        let end = self.length + data.len();
+        if end > self.body.len() {
+            return Err(TransportError::PayloadTooLarge(end));
+        }
+        self.body[self.length..end].copy_from_slice(data);
+        self.length = end;
+        Ok(())
+    }
+}
+
+// Buffers one sector at a time so every flash write is erase-aligned, and
+// hashes the image on the way through, after the little-endian security
+// version it was announced with
+struct ImageWriter<'a> {
+    region: FlashRegion<'a, FlashStorage>,
+    sector: Vec<u8>,
+    offset: u32,
+    length: usize,
+    hasher: Sha512,
+}
+
+impl<'a> ImageWriter<'a> {
+    fn new(region: FlashRegion<'a, FlashStorage>, version: u32) -> Self {
+        let mut hasher = Sha512::new();
+        hasher.update(version.to_le_bytes());
+        Self {
+            region: region,
+            sector: Vec::with_capacity(StorageConstant::FLASH_SECTOR_SIZE),
+            offset: 0,
+            length: 0,
+            hasher: hasher,
+        }
+    }
+
+    fn write_sector(&mut self) -> Result<(), TransportError> {
+        // Pad to the flash word size, only the last sector can be short
+        while self.sector.len() % FlashStorage::WRITE_SIZE != 0 {
+            self.sector.push(0xff);
+        }
+        let end = self.offset + StorageConstant::FLASH_SECTOR_SIZE as u32;
+        self.region.erase(self.offset, end).map_err(ota_error)?;
+        self.region.write(self.offset, &self.sector).map_err(ota_error)?;
+        self.offset = end;
+        self.sector.clear();
+        Ok(())
+    }
+
+    // Returns the image hash and length
+    fn finish(mut self) -> Result<(Sha512, usize), TransportError> {
+        if !self.sector.is_empty() {
+            self.write_sector()?;
+        }
+        Ok((self.hasher, self.length))
+    }
+}
+
+impl BodySink for ImageWriter<'_> {
+    fn accept(&mut self, mut data: &[u8]) -> Result<(), TransportError> {
+        if self.length + data.len() > OtaConstant::SLOT_SIZE {
+            return Err(TransportError::PayloadTooLarge(self.length + data.len()));
+        }
+        self.hasher.update(data);
+        self.length += data.len();
+        while !data.is_empty() {
+            let take = data.len().min(StorageConstant::FLASH_SECTOR_SIZE - self.sector.len());
+            self.sector.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.sector.len() == StorageConstant::FLASH_SECTOR_SIZE {
+                self.write_sector()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct PatchWriter<'a> {
+    decoder: DeltaDecoder,
+    old: FlashRegion<'a, FlashStorage>,
+    image: ImageWriter<'a>,
+}
+
+impl BodySink for PatchWriter<'_> {
+    fn accept(&mut self, data: &[u8]) -> Result<(), TransportError> {
+        let old = &mut self.old;
+        let image = &mut self.image;
+        self.decoder
+            .feed(
+                data,
+                &mut |offset, buffer| read_unaligned(old, offset, buffer),
+                &mut |bytes| image.accept(bytes),
+            )
+            .map_err(|error| match error {
+                DeltaError::Io(error) => error,
+                error => TransportError::Rejected(format!("Delta patch: {:?}", error)),
+            })
+    }
+}
+
+// Flash reads must be word aligned, patches seek to arbitrary offsets
+fn read_unaligned(region: &mut FlashRegion<'_, FlashStorage>, offset: u32, buffer: &mut [u8]) -> Result<(), TransportError> {
+    let word = FlashStorage::READ_SIZE as u32;
+    let start = offset - offset % word;
+    let end = (offset + buffer.len() as u32).div_ceil(word) * word;
+    let mut aligned = vec![0u8; (end - start) as usize];
+    region.read(start, &mut aligned).map_err(ota_error)?;
+    let skip = (offset - start) as usize;
+    buffer.copy_from_slice(&aligned[skip..skip + buffer.len()]);
+    Ok(())
+}
+
+async fn read_body<S: Read + Write>(stream: &mut S, request: &[u8], sink: &mut dyn BodySink) -> Result<usize, TransportError> {
+    stream.write_all(request).await.map_err(io_error)?;
+
+    // Read until the end of the headers, whatever follows is body
+    let mut head = vec![0u8; OtaConstant::HEAD_MAX_BYTES];
+    let mut filled = 0;
+    let body_start = loop {
+        if filled == head.len() {
+            return Err(TransportError::Io(String::from("HTTP response head too large")));
+        }
+        let read = stream.read(&mut head[filled..]).await.map_err(|_| TransportError::Timeout)?;
+        if read == 0 {
+            return Err(TransportError::Io(String::from("Connection closed before HTTP headers")));
+        }
+        filled += read;
+        if let Some(index) = head[..filled].windows(4).position(|window| window == b"\r\n\r\n") {
+            break index + 4;
+        }
+    };
+    match http::parse_status(&head[..filled]) {
+        Some(204) => return Ok(0),
+        Some(status) if (200..300).contains(&status) => {},
+        Some(status) => return Err(TransportError::Rejected(format!("HTTP {}", status))),
+        None => return Err(TransportError::Io(String::from("Malformed HTTP response"))),
+    }
+
+    // The request asks for Connection: close, so the body ends at EOF
+    let mut length = filled - body_start;
+    sink.accept(&head[body_start..filled])?;
+    let mut chunk = [0u8; HttpConstant::READ_CHUNK_BYTES];
+    loop {
+        let read = stream.read(&mut chunk).await.map_err(|_| TransportError::Timeout)?;
+        if read == 0 {
+            return Ok(length);
+        }
+        sink.accept(&chunk[..read])?;
+        length += read;
+    }
+}
+
+// 0 until a first image is confirmed. A floor that cannot be read or fails
+// authentication is an error, treating it as 0 would let old images back in
+fn version_floor(secrets: &mut SecretStoreService) -> Result<u32, TransportError> {
+    match secrets.get(SecretConstant::OTA_VERSION_FLOOR) {
+        Ok(None) => Ok(0),
+        Ok(Some(bytes)) if bytes.len() == 4 => Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+        Ok(Some(bytes)) => Err(TransportError::Io(format!("Version floor of {} bytes", bytes.len()))),
+        Err(error) => Err(TransportError::Io(error.to_string())),
+    }
+}
+
+// Without valid otadata the bootloader runs the first app slot
+fn running_slot() -> Result<Slot, TransportError> {
+    with_ota(|ota| ota.current_slot()).map(|slot| match slot {
+        Slot::None => Slot::Slot0,
+        slot => slot,
+    })
+}
+
+fn app_partition<'a>(table: &'a PartitionTable<'a>, slot: Slot) -> Result<PartitionEntry<'a>, TransportError> {
+    let subtype = match slot {
+        Slot::Slot1 => AppPartitionSubType::Ota1,
+        _ => AppPartitionSubType::Ota0,
+    };
+    table
+        .find_partition(PartitionType::App(subtype))
+        .map_err(ota_error)?
+        .ok_or_else(|| TransportError::Io(format!("No {:?} partition", subtype)))
+}
+
+fn with_ota<R>(
+    f: impl FnOnce(&mut Ota<'_, FlashStorage>) -> Result<R, esp_bootloader_esp_idf::partitions::Error>,
+) -> Result<R, TransportError> {
+    let mut flash = FlashStorage::new();
+    let mut table = [0u8; partitions::PARTITION_TABLE_MAX_LEN];
+    let table = partitions::read_partition_table(&mut flash, &mut table).map_err(ota_error)?;
+    let otadata = table
+        .find_partition(PartitionType::Data(DataPartitionSubType::Ota))
+        .map_err(ota_error)?
+        .ok_or_else(|| TransportError::Io(String::from("No otadata partition")))?;
+    let mut region = otadata.as_embedded_storage(&mut flash);
+    let mut ota = Ota::new(&mut region).map_err(ota_error)?;
+    f(&mut ota).map_err(ota_error)
+}
+
+fn ota_error<E: core::fmt::Debug>(error: E) -> TransportError {
+    TransportError::Io(format!("OTA error: {:?}", error))
+}
+
+fn io_error<E: core::fmt::Debug>(error: E) -> TransportError {
+    TransportError::Io(format!("{:?}", error))
+}
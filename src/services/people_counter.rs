@@ -0,0 +1,160 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+
+use log::info;
+
+use crate::constants::people_counter::PeopleCounterConstant;
+use crate::dtos::event::envelope::EventEnvelopeDTO;
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::dtos::response::server::command::ServerCommandResponseDTO;
+use crate::enums::event_kind::EventKind;
+use crate::enums::transport_error::TransportError;
+use crate::enums::value::Value;
+use crate::services::{clock, envelope, message_id};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Zone {
+    Outside,
+    Inside,
+}
+
+// Counts people through a doorway from two distance zones, one on each side
+// of the threshold: two VL53L0X units facing across the door, or two ROIs of
+// a VL53L1X. A crossing is only counted once both zones are clear again, and
+// only when the first zone to trigger differs from the last one to clear, so
+// someone who steps into the door and turns back is not counted
+pub struct PeopleCounterService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    // A zone is occupied while its distance is below this
+    presence_mm: u16,
+    first: Option<Zone>,
+    last: Option<Zone>,
+    occupancy: u32,
+    entries: u32,
+    exits: u32,
+}
+
+impl PeopleCounterService {
+    pub fn new(urn: String, device_urn: String, location_urn: String, presence_mm: u16) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            presence_mm: presence_mm,
+            first: None,
+            last: None,
+            occupancy: 0,
+            entries: 0,
+            exits: 0,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    pub fn occupancy(&self) -> u32 {
+        self.occupancy
+    }
+
+    // Feed one sample of both zones, as fast as the sensors allow. Returns
+    // the entry or exit event when a crossing just completed
+    pub fn update(&mut self, outside_mm: f32, inside_mm: f32) -> Option<EventEnvelopeDTO> {
+        let outside = outside_mm < self.presence_mm as f32;
+        let inside = inside_mm < self.presence_mm as f32;
+        match (outside, inside) {
+            (false, false) => {
+                let crossing = (self.first.take(), self.last.take());
+                match crossing {
+                    (Some(Zone::Outside), Some(Zone::Inside)) => Some(self.enter()),
+                    (Some(Zone::Inside), Some(Zone::Outside)) => Some(self.exit()),
+                    _ => None,
+                }
+            },
+            _ => {
+                // While both are occupied `last` keeps the zone that was
+                // occupied alone most recently
+                let zone = match (outside, inside) {
+                    (true, false) => Some(Zone::Outside),
+                    (false, true) => Some(Zone::Inside),
+                    _ => None,
+                };
+                if self.first.is_none() {
+                    self.first = zone;
+                }
+                if zone.is_some() {
+                    self.last = zone;
+                }
+                None
+            },
+        }
+    }
+
+    // Occupancy gauge, published with the periodic measurements
+    pub fn gauge(&self) -> MeasurementEnvelopeDTO {
+        let mut data = BTreeMap::new();
+        data.insert(PeopleCounterConstant::OCCUPANCY.to_string(), Value::Integer(self.occupancy as i32));
+        data.insert(PeopleCounterConstant::ENTRIES.to_string(), Value::Integer(self.entries as i32));
+        data.insert(PeopleCounterConstant::EXITS.to_string(), Value::Integer(self.exits as i32));
+        envelope::measurement(&self.device_urn, &self.location_urn, data)
+    }
+
+    // Downlink "reset_occupancy", the optional argument is the actual head
+    // count when the room is not empty
+    pub fn handle_command(&mut self, command: &ServerCommandResponseDTO) -> Option<Result<(), TransportError>> {
+        if command.command != PeopleCounterConstant::RESET_COMMAND {
+            return None;
+        }
+        let occupancy = match command.argument.as_deref().map(|value| value.trim().parse::<u32>()) {
+            None => 0,
+            Some(Ok(occupancy)) => occupancy,
+            Some(Err(_)) => {
+                return Some(Err(TransportError::Rejected(String::from("Expected a non-negative head count"))));
+            },
+        };
+        info!("Occupancy reset from {} to {}", self.occupancy, occupancy);
+        self.occupancy = occupancy;
+        self.entries = 0;
+        self.exits = 0;
+        Some(Ok(()))
+    }
+
+    fn enter(&mut self) -> EventEnvelopeDTO {
+        self.entries = self.entries.saturating_add(1);
+        self.occupancy = self.occupancy.saturating_add(1);
+        self.event(EventKind::PersonEntered)
+    }
+
+    // Missed entries would otherwise drive the count negative
+    fn exit(&mut self) -> EventEnvelopeDTO {
+        self.exits = self.exits.saturating_add(1);
+        self.occupancy = self.occupancy.saturating_sub(1);
+        self.event(EventKind::PersonExited)
+    }
+
+    fn event(&self, kind: EventKind) -> EventEnvelopeDTO {
+        let mut detail = BTreeMap::new();
+        detail.insert(PeopleCounterConstant::OCCUPANCY.to_string(), Value::Integer(self.occupancy as i32));
+        let (timestamp, relative_ms_since_boot) = clock::stamp();
+        EventEnvelopeDTO {
+            id: message_id::next(),
+            device_urn: self.device_urn.clone(),
+            location_urn: self.location_urn.clone(),
+            sensor_urn: self.urn.clone(),
+            timestamp: timestamp,
+            relative_ms_since_boot: relative_ms_since_boot,
+            kind: kind,
+            detail: detail,
+        }
+    }
+}
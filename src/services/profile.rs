@@ -0,0 +1,200 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::Debug;
+
+use littlefs2::fs::Filesystem;
+use littlefs2::path::PathBuf;
+use log::{info, warn};
+
+use crate::config::Config;
+use crate::configurations::sensors::SensorsConfig;
+use crate::constants::profile::ProfileConstant;
+use crate::constants::storage::StorageConstant;
+use crate::dtos::configurations::profile::SensingProfileDTO;
+use crate::dtos::response::server::command::ServerCommandResponseDTO;
+use crate::enums::sensing_profile::SensingProfile;
+use crate::enums::transport_error::TransportError;
+use crate::utilities::flash_partition::FlashPartition;
+use crate::utilities::state_file;
+
+// Switches between named bundles of interval, sensors, batching and sleep
+// settings without pushing a full config. The selected profile comes from
+// the `set_profile` downlink or a rule calling `select` and is kept across
+// reboots; a low battery overrides it with eco until the charge recovers
+pub struct ProfileService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    fast: SensingProfileDTO,
+    normal: SensingProfileDTO,
+    eco: SensingProfileDTO,
+    selected: SensingProfile,
+    eco_battery_percent: u8,
+    low_battery: bool,
+}
+
+impl ProfileService {
+    // `config` and `sensors` as they are before any profile was applied,
+    // they become the normal profile. A stored selection takes precedence
+    // over `config.sensing_profile`
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        config: &Config,
+        sensors: &SensorsConfig,
+    ) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            fast: SensingProfileDTO {
+                interval_ms: ProfileConstant::FAST_INTERVAL_MS,
+                include: Vec::new(),
+                batch_max_records: ProfileConstant::FAST_BATCH_MAX_RECORDS,
+                batch_max_age_s: ProfileConstant::FAST_BATCH_MAX_AGE_S,
+                deep_sleep_interval_s: None,
+            },
+            normal: SensingProfileDTO {
+                interval_ms: config.sensor_interval_ms,
+                include: sensors.include.clone(),
+                batch_max_records: config.batch_max_records,
+                batch_max_age_s: config.batch_max_age_s,
+                deep_sleep_interval_s: config.deep_sleep_interval_s,
+            },
+            eco: SensingProfileDTO {
+                interval_ms: ProfileConstant::ECO_INTERVAL_MS,
+                include: Vec::new(),
+                batch_max_records: ProfileConstant::ECO_BATCH_MAX_RECORDS,
+                batch_max_age_s: ProfileConstant::ECO_BATCH_MAX_AGE_S,
+                deep_sleep_interval_s: Some(ProfileConstant::ECO_DEEP_SLEEP_INTERVAL_S),
+            },
+            selected: match load() {
+                Ok(selected) => selected.unwrap_or(config.sensing_profile),
+                Err(error) => {
+                    warn!("Stored sensing profile not loaded: {}", error);
+                    config.sensing_profile
+                },
+            },
+            eco_battery_percent: config.eco_battery_percent,
+            low_battery: false,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // The profile in effect, eco while the battery is low
+    pub fn active(&self) -> SensingProfile {
+        if self.low_battery { SensingProfile::Eco } else { self.selected }
+    }
+
+    pub fn profile(&self, profile: SensingProfile) -> &SensingProfileDTO {
+        match profile {
+            SensingProfile::Fast => &self.fast,
+            SensingProfile::Normal => &self.normal,
+            SensingProfile::Eco => &self.eco,
+        }
+    }
+
+    // Returns true when the active profile changed and has to be applied
+    pub fn select(&mut self, profile: SensingProfile) -> bool {
+        let previous = self.active();
+        if profile != self.selected {
+            if let Err(error) = store(profile) {
+                warn!("Sensing profile not stored: {}", error);
+            }
+        }
+        self.selected = profile;
+        self.changed(previous)
+    }
+
+    // Downlink "set_profile" with the profile name as argument
+    pub fn handle_command(&mut self, command: &ServerCommandResponseDTO) -> Option<Result<bool, TransportError>> {
+        if command.command != ProfileConstant::SET_COMMAND {
+            return None;
+        }
+        let argument = command.argument.as_deref().unwrap_or("");
+        Some(match SensingProfile::parse(argument.trim()) {
+            Some(profile) => Ok(self.select(profile)),
+            None => Err(TransportError::Rejected(format!("Unknown profile: {}", argument))),
+        })
+    }
+
+    // Feed every battery reading. Returns true when the active profile changed
+    pub fn on_battery(&mut self, percent: u8) -> bool {
+        if self.eco_battery_percent == 0 {
+            return false;
+        }
+        let previous = self.active();
+        if percent < self.eco_battery_percent {
+            self.low_battery = true;
+        } else if percent >= self.eco_battery_percent.saturating_add(ProfileConstant::ECO_BATTERY_HYSTERESIS_PERCENT) {
+            self.low_battery = false;
+        }
+        self.changed(previous)
+    }
+
+    pub fn apply(&self, config: &mut Config, sensors: &mut SensorsConfig) {
+        let profile = self.profile(self.active());
+        config.sensor_interval_ms = profile.interval_ms;
+        config.batch_max_records = profile.batch_max_records;
+        config.batch_max_age_s = profile.batch_max_age_s;
+        config.deep_sleep_interval_s = profile.deep_sleep_interval_s;
+        if !profile.include.is_empty() {
+            sensors.include = profile.include.clone();
+        }
+    }
+
+    fn changed(&self, previous: SensingProfile) -> bool {
+        let active = self.active();
+        if active != previous {
+            info!("Sensing profile {} -> {}", previous.as_str(), active.as_str());
+        }
+        active != previous
+    }
+}
+
+fn load() -> Result<Option<SensingProfile>, Box<dyn Error + Send + Sync>> {
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    Filesystem::mount_and_then(&mut storage, |fs| {
+        let mut bytes = [0u8; state_file::HEADER_BYTES + 8];
+        let read = fs
+            .open_file_and_then(&PathBuf::from(StorageConstant::PROFILE_FILE), |file| file.read(&mut bytes))
+            .unwrap_or(0);
+        Ok(match state_file::decode(&bytes[..read], None) {
+            Some((StorageConstant::PROFILE_VERSION, name)) => {
+                core::str::from_utf8(name).ok().and_then(SensingProfile::parse)
+            },
+            other => state_file::unsupported(StorageConstant::PROFILE_FILE, other),
+        })
+    })
+    .map_err(profile_error)
+}
+
+fn store(profile: SensingProfile) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    Filesystem::mount_and_then(&mut storage, |fs| {
+        fs.write(
+            &PathBuf::from(StorageConstant::PROFILE_FILE),
+            &state_file::encode(StorageConstant::PROFILE_VERSION, profile.as_str().as_bytes()),
+        )
+    })
+    .map_err(profile_error)
+}
+
+fn profile_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("Profile error: {:?}", error))
+}
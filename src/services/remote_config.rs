@@ -0,0 +1,247 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::Debug;
+
+use embassy_time::{Duration, Instant};
+use littlefs2::fs::Filesystem;
+use littlefs2::path::PathBuf;
+use log::{info, warn};
+
+use crate::config::Config;
+use crate::configurations::sensors::SensorsConfig;
+use crate::constants::storage::StorageConstant;
+use crate::dtos::event::envelope::EventEnvelopeDTO;
+use crate::dtos::response::server::config::ServerConfigResponseDTO;
+use crate::services::config_validator::ConfigValidator;
+use crate::utilities::config_migration;
+use crate::utilities::flash_partition::FlashPartition;
+use crate::utilities::state_file;
+
+// Keeps the last two remote configs (active and previous) as raw JSON. A new
+// config starts out pending and is reverted to the previous one unless an
+// upload succeeds within the grace period and `CONFIG_PENDING_MAX_BOOTS`
+// boots, so a bad include list or server URL cannot take a device offline
+// for good
+pub struct RemoteConfigService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    storage: FlashPartition,
+    grace_period: Duration,
+    pending_deadline: Option<Instant>,
+}
+
+impl RemoteConfigService {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        grace_period_s: u64,
+    ) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+        // Counts this boot against a pending config, None when there is none
+        let pending_boots = Filesystem::mount_and_then(&mut storage, |fs| {
+            if fs.metadata(&path(StorageConstant::CONFIG_DIR)).is_err() {
+                fs.create_dir(&path(StorageConstant::CONFIG_DIR))?;
+            }
+            let pending = path(StorageConstant::CONFIG_PENDING_FILE);
+            if fs.metadata(&pending).is_err() {
+                return Ok(None);
+            }
+            let mut bytes = [0u8; state_file::HEADER_BYTES + 4];
+            let read = fs.open_file_and_then(&pending, |file| file.read(&mut bytes)).unwrap_or(0);
+            let previous = match state_file::decode(&bytes[..read], Some(4)) {
+                Some((StorageConstant::CONFIG_PENDING_VERSION, body)) => body.try_into().ok().map(u32::from_le_bytes),
+                other => state_file::unsupported(StorageConstant::CONFIG_PENDING_FILE, other),
+            };
+            let boots = previous.unwrap_or(0).saturating_add(1);
+            fs.write(&pending, &state_file::encode(StorageConstant::CONFIG_PENDING_VERSION, &boots.to_le_bytes()))?;
+            Ok(Some(boots))
+        })
+        .map_err(config_error)?;
+
+        let grace_period = Duration::from_secs(grace_period_s);
+        // A reboot restarts the window instead of confirming the config,
+        // until the config has been pending for too many boots: one that
+        // crashes the device before its first upload is reverted by `poll`
+        let pending_deadline = pending_boots.map(|boots| {
+            if boots >= StorageConstant::CONFIG_PENDING_MAX_BOOTS {
+                warn!("Remote config still pending after {} boots", boots);
+                Instant::now()
+            } else {
+                Instant::now() + grace_period
+            }
+        });
+        Ok(Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            storage: storage,
+            grace_period: grace_period,
+            pending_deadline: pending_deadline,
+        })
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending_deadline.is_some()
+    }
+
+    // The config to overlay at boot, None when nothing was ever applied.
+    // A config persisted by older firmware is migrated and written back; one
+    // this firmware cannot read, e.g. after a downgrade, gives way to the
+    // previous config and then to the build-time defaults
+    pub fn active(&mut self) -> Result<Option<ServerConfigResponseDTO>, Box<dyn Error + Send + Sync>> {
+        for name in [StorageConstant::CONFIG_ACTIVE_FILE, StorageConstant::CONFIG_PREVIOUS_FILE] {
+            let bytes = match self.read(name)? {
+                Some(bytes) => bytes,
+                None => continue,
+            };
+            let (version, json) = config_migration::decode(&bytes);
+            let config = config_migration::migrate(version, json).and_then(|json| Ok((parse(json.as_bytes())?, json)));
+            match config {
+                Ok((config, json)) => {
+                    if version != StorageConstant::CONFIG_SCHEMA_VERSION {
+                        info!("Migrated {} from config schema {}", name, version);
+                        self.write(name, &config_migration::encode(json.as_bytes()))?;
+                    }
+                    return Ok(Some(config));
+                },
+                Err(error) => warn!("Ignoring {}: {}", name, error),
+            }
+        }
+        Ok(None)
+    }
+
+    // Validates and stores `json` as the active config, keeping the current
+    // one as the fallback. The caller applies the returned config. One the
+    // validator finds problems with, checked as overlaid on the `config` and
+    // `sensors` in effect, is not stored: the last-known-good config stays
+    // active and the CONFIG_REJECTED event comes back to be uploaded
+    pub fn apply(
+        &mut self,
+        json: &[u8],
+        validator: &ConfigValidator,
+        config: &Config,
+        sensors: &SensorsConfig,
+    ) -> Result<Result<ServerConfigResponseDTO, EventEnvelopeDTO>, Box<dyn Error + Send + Sync>> {
+        if let Some(event) = validator.event(&validator.check_remote(json, config, sensors)) {
+            warn!("Rejected remote config, keeping the current one");
+            return Ok(Err(event));
+        }
+        let json = config_migration::migrate(config_migration::declared_version(json)?, json)?;
+        let config = parse(json.as_bytes())?;
+        let stored = config_migration::encode(json.as_bytes());
+        if stored.len() > StorageConstant::CONFIG_MAX_BYTES {
+            return Err(Box::from(format!("Remote config of {} bytes is too large", json.len())));
+        }
+        // A config applied while another is still pending replaces it, the
+        // fallback stays the last one that was confirmed
+        let keep_previous = self.is_pending();
+        Filesystem::mount_and_then(&mut self.storage, |fs| {
+            let active = path(StorageConstant::CONFIG_ACTIVE_FILE);
+            if !keep_previous && fs.metadata(&active).is_ok() {
+                fs.rename(&active, &path(StorageConstant::CONFIG_PREVIOUS_FILE))?;
+            }
+            fs.write(
+                &path(StorageConstant::CONFIG_PENDING_FILE),
+                &state_file::encode(StorageConstant::CONFIG_PENDING_VERSION, &0u32.to_le_bytes()),
+            )?;
+            fs.write(&active, &stored)
+        })
+        .map_err(config_error)?;
+
+        self.pending_deadline = Some(Instant::now() + self.grace_period);
+        info!("Applied remote config, pending confirmation");
+        Ok(Ok(config))
+    }
+
+    // Call after every successful upload
+    pub fn confirm(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.pending_deadline.take().is_none() {
+            return Ok(());
+        }
+        Filesystem::mount_and_then(&mut self.storage, |fs| fs.remove(&path(StorageConstant::CONFIG_PENDING_FILE)))
+            .map_err(config_error)?;
+        info!("Remote config confirmed");
+        Ok(())
+    }
+
+    // Call periodically. Once the grace period has passed, restores the
+    // previous config and returns true: the caller then rebuilds its config
+    // from `Config::new()` overlaid with `active()`, which is the build-time
+    // default when there was no previous remote config
+    pub fn poll(&mut self) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        match self.pending_deadline {
+            Some(deadline) if Instant::now() >= deadline => {},
+            _ => return Ok(false),
+        }
+        warn!("Remote config was not confirmed within the grace period, reverting");
+        Filesystem::mount_and_then(&mut self.storage, |fs| {
+            let active = path(StorageConstant::CONFIG_ACTIVE_FILE);
+            let previous = path(StorageConstant::CONFIG_PREVIOUS_FILE);
+            if fs.metadata(&previous).is_ok() {
+                fs.rename(&previous, &active)?;
+            } else {
+                fs.remove(&active)?;
+            }
+            fs.remove(&path(StorageConstant::CONFIG_PENDING_FILE))
+        })
+        .map_err(config_error)?;
+        self.pending_deadline = None;
+        Ok(true)
+    }
+
+    fn read(&mut self, name: &str) -> Result<Option<Vec<u8>>, Box<dyn Error + Send + Sync>> {
+        Filesystem::mount_and_then(&mut self.storage, |fs| {
+            if fs.metadata(&path(name)).is_err() {
+                return Ok(None);
+            }
+            let mut bytes = vec![0u8; StorageConstant::CONFIG_MAX_BYTES];
+            let read = fs.open_file_and_then(&path(name), |file| file.read(&mut bytes))?;
+            bytes.truncate(read);
+            Ok(Some(bytes))
+        })
+        .map_err(config_error)
+    }
+
+    fn write(&mut self, name: &str, bytes: &[u8]) -> Result<(), Box<dyn Error + Send + Sync>> {
+        Filesystem::mount_and_then(&mut self.storage, |fs| fs.write(&path(name), bytes)).map_err(config_error)
+    }
+}
+
+// A config as received from the server, migrated to the current schema
+pub fn decode(json: &[u8]) -> Result<ServerConfigResponseDTO, Box<dyn Error + Send + Sync>> {
+    let json = config_migration::migrate(config_migration::declared_version(json)?, json)?;
+    parse(json.as_bytes())
+}
+
+fn parse(json: &[u8]) -> Result<ServerConfigResponseDTO, Box<dyn Error + Send + Sync>> {
+    let json = core::str::from_utf8(json).map_err(|error| Box::<dyn Error + Send + Sync>::from(error.to_string()))?;
+    serde_json_core::from_str::<ServerConfigResponseDTO>(json)
+        .map(|(config, _)| config)
+        .map_err(config_error)
+}
+
+fn path(name: &str) -> PathBuf {
+    PathBuf::from(name)
+}
+
+fn config_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("Remote config error: {:?}", error))
+}
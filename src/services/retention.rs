@@ -0,0 +1,98 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::error::Error;
+
+use embassy_time::Instant;
+
+use crate::abstractions::queue::IQueue;
+use crate::abstractions::store::IStore;
+use crate::constants::retention::RetentionConstant;
+use crate::dtos::configurations::retention::RetentionConfigDTO;
+
+// Periodic compaction of the persistent upload queue and the SD card log
+// against their retention limits. Call `poll` from the idle part of the
+// main loop; it only walks storage once `COMPACT_INTERVAL_S` has passed,
+// and `set_limits` applies limits that arrived in a remote config
+pub struct RetentionService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    queue: RetentionConfigDTO,
+    sd: RetentionConfigDTO,
+    last_run_s: Option<u64>,
+}
+
+impl RetentionService {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        queue: RetentionConfigDTO,
+        sd: RetentionConfigDTO,
+    ) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            queue: queue,
+            sd: sd,
+            last_run_s: None,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // Takes effect right away rather than at the next interval
+    pub fn set_limits(&mut self, queue: RetentionConfigDTO, sd: RetentionConfigDTO) {
+        if queue != self.queue || sd != self.sd {
+            self.last_run_s = None;
+        }
+        self.queue = queue;
+        self.sd = sd;
+    }
+
+    // `now` in Unix seconds, ages are not judged while it is 0. Returns
+    // whether compaction ran
+    pub fn poll(
+        &mut self,
+        queue: &mut dyn IQueue,
+        store: Option<&mut dyn IStore>,
+        now: u64,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let uptime_s = Instant::now().as_secs();
+        if self
+            .last_run_s
+            .is_some_and(|last_run_s| uptime_s - last_run_s < RetentionConstant::COMPACT_INTERVAL_S)
+        {
+            return Ok(false);
+        }
+        self.last_run_s = Some(uptime_s);
+
+        let (queue_limits, sd_limits) = match now {
+            0 => (without_age(self.queue), without_age(self.sd)),
+            _ => (self.queue, self.sd),
+        };
+        queue.compact(&queue_limits, now)?;
+        if let Some(store) = store {
+            store.compact(&sd_limits, now)?;
+        }
+        Ok(true)
+    }
+}
+
+fn without_age(retention: RetentionConfigDTO) -> RetentionConfigDTO {
+    RetentionConfigDTO {
+        max_age_s: None,
+        ..retention
+    }
+}
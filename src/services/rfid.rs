@@ -0,0 +1,134 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::Debug;
+
+use embassy_time::Instant;
+use embedded_hal::spi::SpiDevice;
+use log::{info, warn};
+
+use crate::abstractions::service::IService;
+use crate::constants::rfid::RfidConstant;
+use crate::drivers::mfrc522::Mfrc522;
+use crate::dtos::event::envelope::EventEnvelopeDTO;
+use crate::enums::event_kind::EventKind;
+use crate::enums::value::Value;
+use crate::services::{clock, event_outbox, local_access, message_id};
+
+// Technician badges at the RC522 reader: every scan is logged as an event,
+// so the backend has a record of site visits, and an authorized badge opens
+// the local endpoints for `unlock_s`
+pub struct RfidService<S: SpiDevice> {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    reader: Mfrc522<S>,
+    authorized: Vec<Vec<u8>>,
+    unlock_s: u64,
+    last: Option<(Vec<u8>, u64)>,
+}
+
+// Supervised with a short period, each run is one look for a card
+impl<S: SpiDevice> IService for RfidService<S>
+where
+    S::Error: Debug,
+{
+    type Response = ();
+
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    async fn run(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if let Some(event) = self.poll().await {
+            event_outbox::raise(event);
+        }
+        Ok(())
+    }
+}
+
+impl<S: SpiDevice> RfidService<S>
+where
+    S::Error: Debug,
+{
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        reader: Mfrc522<S>,
+        authorized: Vec<Vec<u8>>,
+        unlock_s: u64,
+    ) -> Self {
+        local_access::require(!authorized.is_empty());
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            reader: reader,
+            authorized: authorized,
+            unlock_s: unlock_s,
+            last: None,
+        }
+    }
+
+    // Call a few times a second; returns the scan event of a new card
+    pub async fn poll(&mut self) -> Option<EventEnvelopeDTO> {
+        let uid = match self.reader.read_uid().await {
+            Ok(Some(uid)) => uid,
+            Ok(None) => return None,
+            Err(error) => {
+                warn!("RFID read failed: {:?}", error);
+                return None;
+            },
+        };
+        let now_ms = Instant::now().as_millis();
+        let repeated = self
+            .last
+            .as_ref()
+            .is_some_and(|(last, at_ms)| *last == uid && now_ms - at_ms < RfidConstant::SCAN_HOLDOFF_MS);
+        self.last = Some((uid.clone(), now_ms));
+        if repeated {
+            return None;
+        }
+
+        let uid_hex: String = uid.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let authorized = self.authorized.contains(&uid);
+        // Badging again while unlocked closes the endpoints before the
+        // time runs out
+        if authorized && local_access::is_allowed() {
+            info!("Authorized badge {}, locking", uid_hex);
+            local_access::lock();
+        } else if authorized {
+            info!("Authorized badge {}", uid_hex);
+            local_access::unlock(self.unlock_s);
+        } else {
+            warn!("Unknown badge {}", uid_hex);
+        }
+
+        let mut detail = BTreeMap::new();
+        detail.insert(RfidConstant::UID.to_string(), Value::String(uid_hex));
+        detail.insert(RfidConstant::AUTHORIZED.to_string(), Value::Boolean(authorized));
+        let (timestamp, relative_ms_since_boot) = clock::stamp();
+        Some(EventEnvelopeDTO {
+            id: message_id::next(),
+            device_urn: self.device_urn.clone(),
+            location_urn: self.location_urn.clone(),
+            sensor_urn: self.urn.clone(),
+            timestamp: timestamp,
+            relative_ms_since_boot: relative_ms_since_boot,
+            kind: EventKind::CardScanned,
+            detail: detail,
+        })
+    }
+}
@@ -0,0 +1,220 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::error::Error;
+use core::fmt::Debug;
+
+use embassy_time::Instant;
+use littlefs2::fs::Filesystem;
+use littlefs2::path::PathBuf;
+use log::{info, warn};
+
+use crate::constants::run_hours::RunHoursConstant;
+use crate::constants::storage::StorageConstant;
+use crate::dtos::event::envelope::EventEnvelopeDTO;
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::enums::event_kind::EventKind;
+use crate::enums::value::Value;
+use crate::services::{clock, envelope, message_id};
+use crate::utilities::flash_partition::FlashPartition;
+use crate::utilities::state_file;
+
+// Run-hour meter for a machine the device is mounted on. The vibration RMS
+// of each block of samples, gravity removed as the block mean, decides
+// whether the machine runs; a few blocks in a row have to agree before the
+// state changes. Run time survives reboots in flash, written on every stop
+// and every `PERSIST_INTERVAL_S` while running
+pub struct RunHoursService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    rms_ms2: f32,
+    samples: u32,
+    sum: [f32; 3],
+    sum_squared: [f32; 3],
+    // Consecutive blocks above and below the threshold
+    above: u8,
+    below: u8,
+    running_since_ms: Option<u64>,
+    // Total run time up to `accounted_ms`
+    run_ms: u64,
+    accounted_ms: u64,
+    persisted_ms: u64,
+}
+
+impl RunHoursService {
+    pub fn new(urn: String, device_urn: String, location_urn: String, rms_ms2: f32) -> Self {
+        // A counter that cannot be read starts over rather than keeping the
+        // device from monitoring at all
+        let run_s = load().unwrap_or_else(|error| {
+            warn!("Failed to load run hours, starting from zero: {}", error);
+            0
+        });
+        info!("Run hours {:.1}", run_s as f32 / 3600.0);
+        let now_ms = Instant::now().as_millis();
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            rms_ms2: rms_ms2,
+            samples: 0,
+            sum: [0.0; 3],
+            sum_squared: [0.0; 3],
+            above: 0,
+            below: 0,
+            running_since_ms: None,
+            run_ms: run_s * 1000,
+            accounted_ms: now_ms,
+            persisted_ms: now_ms,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running_since_ms.is_some()
+    }
+
+    pub fn run_hours(&mut self) -> f32 {
+        self.accrue(Instant::now().as_millis());
+        self.run_ms as f32 / 3_600_000.0
+    }
+
+    // One sample in m/s², at the rate `WINDOW_SAMPLES` assumes. Returns the
+    // start or stop event when the state just changed
+    pub fn update(&mut self, x: f32, y: f32, z: f32) -> Option<EventEnvelopeDTO> {
+        for (axis, value) in [x, y, z].into_iter().enumerate() {
+            self.sum[axis] += value;
+            self.sum_squared[axis] += value * value;
+        }
+        self.samples += 1;
+        if self.samples < RunHoursConstant::WINDOW_SAMPLES {
+            return None;
+        }
+
+        // Sum of the per-axis variances is the squared RMS, compared squared
+        // as there is no sqrt in core
+        let n = self.samples as f32;
+        let mean_squared: f32 = (0..3)
+            .map(|axis| self.sum_squared[axis] / n - (self.sum[axis] / n) * (self.sum[axis] / n))
+            .sum();
+        self.samples = 0;
+        self.sum = [0.0; 3];
+        self.sum_squared = [0.0; 3];
+
+        let now_ms = Instant::now().as_millis();
+        self.accrue(now_ms);
+        if mean_squared > self.rms_ms2 * self.rms_ms2 {
+            self.above = self.above.saturating_add(1);
+            self.below = 0;
+        } else {
+            self.below = self.below.saturating_add(1);
+            self.above = 0;
+        }
+
+        match self.running_since_ms {
+            None if self.above >= RunHoursConstant::START_WINDOWS => {
+                info!("Machine started");
+                self.running_since_ms = Some(now_ms);
+                Some(self.event(EventKind::MachineStarted, None))
+            },
+            Some(since_ms) if self.below >= RunHoursConstant::STOP_WINDOWS => {
+                let run_s = (now_ms - since_ms) / 1000;
+                info!("Machine stopped after {} s", run_s);
+                self.running_since_ms = None;
+                self.persist(now_ms);
+                Some(self.event(EventKind::MachineStopped, Some(run_s)))
+            },
+            Some(_) => {
+                if now_ms - self.persisted_ms >= RunHoursConstant::PERSIST_INTERVAL_S * 1000 {
+                    self.persist(now_ms);
+                }
+                None
+            },
+            None => None,
+        }
+    }
+
+    // Run hours and state, published with the periodic measurements
+    pub fn gauge(&mut self) -> MeasurementEnvelopeDTO {
+        let mut data = BTreeMap::new();
+        data.insert(RunHoursConstant::RUN_HOURS.to_string(), Value::Float(self.run_hours()));
+        data.insert(RunHoursConstant::RUNNING.to_string(), Value::Boolean(self.is_running()));
+        envelope::measurement(&self.device_urn, &self.location_urn, data)
+    }
+
+    fn accrue(&mut self, now_ms: u64) {
+        if self.running_since_ms.is_some() {
+            self.run_ms += now_ms - self.accounted_ms;
+        }
+        self.accounted_ms = now_ms;
+    }
+
+    fn persist(&mut self, now_ms: u64) {
+        self.persisted_ms = now_ms;
+        if let Err(error) = store(self.run_ms / 1000) {
+            warn!("Failed to persist run hours: {}", error);
+        }
+    }
+
+    fn event(&self, kind: EventKind, run_s: Option<u64>) -> EventEnvelopeDTO {
+        let mut detail = BTreeMap::new();
+        detail.insert(RunHoursConstant::RUN_HOURS.to_string(), Value::Float(self.run_ms as f32 / 3_600_000.0));
+        if let Some(run_s) = run_s {
+            detail.insert(RunHoursConstant::RUN_S.to_string(), Value::Integer(run_s as i32));
+        }
+        let (timestamp, relative_ms_since_boot) = clock::stamp();
+        EventEnvelopeDTO {
+            id: message_id::next(),
+            device_urn: self.device_urn.clone(),
+            location_urn: self.location_urn.clone(),
+            sensor_urn: self.urn.clone(),
+            timestamp: timestamp,
+            relative_ms_since_boot: relative_ms_since_boot,
+            kind: kind,
+            detail: detail,
+        }
+    }
+}
+
+fn load() -> Result<u64, Box<dyn Error + Send + Sync>> {
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    Filesystem::mount_and_then(&mut storage, |fs| {
+        let mut bytes = [0u8; state_file::HEADER_BYTES + 8];
+        let read = fs
+            .open_file_and_then(&PathBuf::from(StorageConstant::RUN_SECONDS_FILE), |file| file.read(&mut bytes))
+            .unwrap_or(0);
+        let run_s = match state_file::decode(&bytes[..read], Some(8)) {
+            Some((StorageConstant::RUN_SECONDS_VERSION, body)) => body.try_into().ok().map(u64::from_le_bytes),
+            other => state_file::unsupported(StorageConstant::RUN_SECONDS_FILE, other),
+        };
+        Ok(run_s.unwrap_or(0))
+    })
+    .map_err(run_hours_error)
+}
+
+fn store(run_s: u64) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    Filesystem::mount_and_then(&mut storage, |fs| {
+        fs.write(
+            &PathBuf::from(StorageConstant::RUN_SECONDS_FILE),
+            &state_file::encode(StorageConstant::RUN_SECONDS_VERSION, &run_s.to_le_bytes()),
+        )
+    })
+    .map_err(run_hours_error)
+}
+
+fn run_hours_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("Run hours error: {:?}", error))
+}
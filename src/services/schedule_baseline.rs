@@ -0,0 +1,260 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::Debug;
+
+use littlefs2::fs::Filesystem;
+use littlefs2::path::PathBuf;
+use log::{info, warn};
+
+use crate::constants::baseline::BaselineConstant;
+use crate::constants::storage::StorageConstant;
+use crate::dtos::configurations::timezone::TimeZoneDTO;
+use crate::dtos::event::envelope::EventEnvelopeDTO;
+use crate::enums::event_kind::EventKind;
+use crate::enums::value::Value;
+use crate::services::{clock, message_id};
+use crate::utilities::flash_partition::FlashPartition;
+use crate::utilities::state_file;
+use crate::utilities::timezone;
+
+// Share of past weeks in which an hour-of-week slot was lit and occupied
+#[derive(Clone, Copy, Default)]
+struct Slot {
+    lit: f32,
+    occupied: f32,
+    weeks: u8,
+}
+
+// Learns when a room is usually lit and occupied, per local hour of the
+// week, and reports hours that break the pattern: lights on at 3 a.m., no
+// one in during business hours. Each hour's readings are folded into its
+// slot when the hour ends and the table is written to flash, so a reboot
+// costs at most the hour in progress. Nothing is reported while the clock
+// is unset or a slot has seen fewer than `MIN_WEEKS`
+pub struct ScheduleBaselineService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    timezone: TimeZoneDTO,
+    lit_lux: f32,
+    slots: Vec<Slot>,
+    // Slot of the hour being observed and its readings so far
+    current: Option<usize>,
+    lux_samples: u32,
+    lit_samples: u32,
+    occupancy_samples: u32,
+    occupied_samples: u32,
+    // Deviations reported as they happen are only reported once per hour
+    lights_on_reported: bool,
+    occupied_reported: bool,
+}
+
+impl ScheduleBaselineService {
+    pub fn new(urn: String, device_urn: String, location_urn: String, timezone: TimeZoneDTO, lit_lux: f32) -> Self {
+        // A table that cannot be read is learned again from scratch
+        let slots = load().unwrap_or_else(|error| {
+            warn!("Failed to load the schedule baseline, learning from scratch: {}", error);
+            Vec::new()
+        });
+        let slots = if slots.len() == BaselineConstant::HOURS_PER_WEEK {
+            slots
+        } else {
+            vec![Slot::default(); BaselineConstant::HOURS_PER_WEEK]
+        };
+        info!("Schedule baseline: {} hours learned", slots.iter().filter(|slot| learned(slot)).count());
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            timezone: timezone,
+            lit_lux: lit_lux,
+            slots: slots,
+            current: None,
+            lux_samples: 0,
+            lit_samples: 0,
+            occupancy_samples: 0,
+            occupied_samples: 0,
+            lights_on_reported: false,
+            occupied_reported: false,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // One reading of whichever of the two the device has. Lights on and
+    // presence are reported right away, their absence when the hour ends
+    pub fn update(&mut self, lux: Option<f32>, occupied: Option<bool>) -> Vec<EventEnvelopeDTO> {
+        let mut events = Vec::new();
+        let utc = match clock::now() {
+            Some(utc) => utc,
+            None => return events,
+        };
+        let slot = hour_of_week(timezone::to_local(&self.timezone, utc));
+        if self.current != Some(slot) {
+            if let Some(previous) = self.current {
+                events.extend(self.close(previous));
+            }
+            self.current = Some(slot);
+            self.lux_samples = 0;
+            self.lit_samples = 0;
+            self.occupancy_samples = 0;
+            self.occupied_samples = 0;
+            self.lights_on_reported = false;
+            self.occupied_reported = false;
+        }
+        let baseline = self.slots[slot];
+
+        if let Some(lux) = lux {
+            self.lux_samples += 1;
+            if lux >= self.lit_lux {
+                self.lit_samples += 1;
+                if !self.lights_on_reported && learned(&baseline) && baseline.lit < BaselineConstant::UNUSUAL_SHARE {
+                    self.lights_on_reported = true;
+                    events.push(self.event(slot, BaselineConstant::LIGHTS_ON, baseline.lit));
+                }
+            }
+        }
+        if let Some(occupied) = occupied {
+            self.occupancy_samples += 1;
+            if occupied {
+                self.occupied_samples += 1;
+                if !self.occupied_reported
+                    && learned(&baseline)
+                    && baseline.occupied < BaselineConstant::UNUSUAL_SHARE
+                {
+                    self.occupied_reported = true;
+                    events.push(self.event(slot, BaselineConstant::OCCUPIED, baseline.occupied));
+                }
+            }
+        }
+        events
+    }
+
+    // Judges the finished hour against its slot, then learns from it
+    fn close(&mut self, slot: usize) -> Vec<EventEnvelopeDTO> {
+        let mut events = Vec::new();
+        let baseline = self.slots[slot];
+        let lux_observed = self.lux_samples >= BaselineConstant::MIN_SAMPLES;
+        let occupancy_observed = self.occupancy_samples >= BaselineConstant::MIN_SAMPLES;
+        if !lux_observed && !occupancy_observed {
+            return events;
+        }
+
+        if learned(&baseline) {
+            if lux_observed && self.lit_samples == 0 && baseline.lit >= BaselineConstant::EXPECTED_SHARE {
+                events.push(self.event(slot, BaselineConstant::LIGHTS_OFF, baseline.lit));
+            }
+            if occupancy_observed
+                && self.occupied_samples == 0
+                && baseline.occupied >= BaselineConstant::EXPECTED_SHARE
+            {
+                events.push(self.event(slot, BaselineConstant::VACANT, baseline.occupied));
+            }
+        }
+
+        let alpha = (1.0 / (baseline.weeks as f32 + 1.0)).max(BaselineConstant::ALPHA);
+        let entry = &mut self.slots[slot];
+        if lux_observed {
+            let lit = if self.lit_samples > 0 { 1.0 } else { 0.0 };
+            entry.lit += alpha * (lit - entry.lit);
+        }
+        if occupancy_observed {
+            let occupied = if self.occupied_samples > 0 { 1.0 } else { 0.0 };
+            entry.occupied += alpha * (occupied - entry.occupied);
+        }
+        entry.weeks = entry.weeks.saturating_add(1);
+        if let Err(error) = store(&self.slots) {
+            warn!("Failed to persist the schedule baseline: {}", error);
+        }
+        events
+    }
+
+    fn event(&self, slot: usize, deviation: &str, baseline: f32) -> EventEnvelopeDTO {
+        warn!("Schedule deviation at hour {} of the week: {} (baseline {:.2})", slot, deviation, baseline);
+        let mut detail = BTreeMap::new();
+        detail.insert(BaselineConstant::DEVIATION.to_string(), Value::String(deviation.to_string()));
+        detail.insert(BaselineConstant::HOUR_OF_WEEK.to_string(), Value::Integer(slot as i32));
+        detail.insert(BaselineConstant::BASELINE.to_string(), Value::Float(baseline));
+        let (timestamp, relative_ms_since_boot) = clock::stamp();
+        EventEnvelopeDTO {
+            id: message_id::next(),
+            device_urn: self.device_urn.clone(),
+            location_urn: self.location_urn.clone(),
+            sensor_urn: self.urn.clone(),
+            timestamp: timestamp,
+            relative_ms_since_boot: relative_ms_since_boot,
+            kind: EventKind::Deviation,
+            detail: detail,
+        }
+    }
+}
+
+fn learned(slot: &Slot) -> bool {
+    slot.weeks >= BaselineConstant::MIN_WEEKS
+}
+
+// 0 is Monday 00:00-01:00 local time; 1970-01-01 was a Thursday
+fn hour_of_week(local: i64) -> usize {
+    let weekday = (local.div_euclid(86_400) + 3).rem_euclid(7);
+    let hour = local.rem_euclid(86_400) / 3_600;
+    (weekday * 24 + hour) as usize
+}
+
+// Three bytes per slot: both shares scaled to 0-255 and the weeks seen
+fn load() -> Result<Vec<Slot>, Box<dyn Error + Send + Sync>> {
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    Filesystem::mount_and_then(&mut storage, |fs| {
+        const SLOT_BYTES: usize = BaselineConstant::HOURS_PER_WEEK * 3;
+        let mut bytes = [0u8; state_file::HEADER_BYTES + SLOT_BYTES];
+        let read = fs
+            .open_file_and_then(&PathBuf::from(StorageConstant::BASELINE_FILE), |file| file.read(&mut bytes))
+            .unwrap_or(0);
+        let slots = match state_file::decode(&bytes[..read], Some(SLOT_BYTES)) {
+            Some((StorageConstant::BASELINE_VERSION, body)) if body.len() == SLOT_BYTES => body,
+            other => return Ok(state_file::unsupported(StorageConstant::BASELINE_FILE, other).unwrap_or_default()),
+        };
+        Ok(slots
+            .chunks_exact(3)
+            .map(|slot| Slot {
+                lit: slot[0] as f32 / 255.0,
+                occupied: slot[1] as f32 / 255.0,
+                weeks: slot[2],
+            })
+            .collect())
+    })
+    .map_err(baseline_error)
+}
+
+fn store(slots: &[Slot]) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let bytes: Vec<u8> = slots
+        .iter()
+        .flat_map(|slot| [(slot.lit * 255.0 + 0.5) as u8, (slot.occupied * 255.0 + 0.5) as u8, slot.weeks])
+        .collect();
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    Filesystem::mount_and_then(&mut storage, |fs| {
+        fs.write(
+            &PathBuf::from(StorageConstant::BASELINE_FILE),
+            &state_file::encode(StorageConstant::BASELINE_VERSION, &bytes),
+        )
+    })
+    .map_err(baseline_error)
+}
+
+fn baseline_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("Schedule baseline error: {:?}", error))
+}
@@ -0,0 +1,181 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use embassy_time::{Duration, Instant, Timer};
+#[cfg(not(test))]
+use esp_hal::gpio::Output;
+use log::debug;
+
+use crate::dtos::configurations::schedule::ScheduleWindowDTO;
+use crate::dtos::configurations::timezone::TimeZoneDTO;
+#[cfg(test)]
+use crate::host_test::fake_hal::Output;
+use crate::utilities::{schedule, timezone};
+
+// Gates sensing and uploads to the configured local-time windows. Without
+// windows everything runs at the plain sensor interval.
+//
+// Sensors with a warm-up time are handled per cycle as: `wait` returns early
+// by the longest warm-up of the sensors that are still cold, `power_up`
+// switches on the gated ones (the caller reports sensors it merely
+// initialises through `powered_up`), `settle` sleeps out the remaining time,
+// readings from sensors for which `is_settled` is still false are discarded
+// rather than uploaded, and `power_down` switches the gated ones off again
+pub struct SchedulerService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    windows: Vec<ScheduleWindowDTO>,
+    timezone: TimeZoneDTO,
+    interval_ms: u64,
+    warmup_ms: BTreeMap<String, u64>,
+    // `SensorsConfig::include`; the others are neither powered nor waited for
+    included: Vec<String>,
+    // When each sensor was last powered up or initialised
+    powered_at: BTreeMap<String, Instant>,
+    // Load switch enables from `SensorsConfigDTO::power_gpios`
+    power_pins: BTreeMap<String, Output<'static>>,
+    // Local time of the tick the last `wait` slept until
+    last_due_local: Option<i64>,
+}
+
+impl SchedulerService {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        windows: Vec<ScheduleWindowDTO>,
+        timezone: TimeZoneDTO,
+        interval_ms: u64,
+        warmup_ms: BTreeMap<String, u64>,
+        included: Vec<String>,
+    ) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            windows: windows,
+            timezone: timezone,
+            interval_ms: interval_ms,
+            warmup_ms: warmup_ms,
+            included: included,
+            powered_at: BTreeMap::new(),
+            power_pins: BTreeMap::new(),
+            last_due_local: None,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // Also used to hold back uploads and radio activity outside the windows
+    pub fn is_active(&self, now_utc: u64) -> bool {
+        schedule::is_active(&self.windows, timezone::to_local(&self.timezone, now_utc))
+    }
+
+    // Sleeps until the next sensing cycle is due, less the warm-up of any
+    // cold sensor; not at all when a tick is due right now. `now_utc` comes
+    // from the RTC/SNTP clock, a few seconds of error only shifts the cadence
+    pub async fn wait(&mut self, now_utc: u64) {
+        let local = timezone::to_local(&self.timezone, now_utc);
+        // A cycle shorter than a second would otherwise find its own tick
+        // still due
+        let from = if self.last_due_local == Some(local) { local + 1 } else { local };
+        let interval_s = (self.interval_ms / 1_000).max(1) as i64;
+        let delay = match schedule::next_due_in(&self.windows, from, interval_s) {
+            None => Duration::from_millis(self.interval_ms),
+            Some(seconds) => {
+                let due = from + seconds;
+                self.last_due_local = Some(due);
+                Duration::from_secs((due - local) as u64)
+            },
+        };
+        let lead = Duration::from_millis(self.cold_warmup_ms());
+        debug!("Next sensing cycle in {} ms, warm-up lead {} ms", delay.as_millis(), lead.as_millis());
+        Timer::after(delay.checked_sub(lead).unwrap_or(Duration::from_ticks(0))).await;
+    }
+
+    // `pin` starts low, the sensor stays unpowered until `power_up`
+    pub fn add_power_pin(&mut self, sensor: &str, mut pin: Output<'static>) {
+        pin.set_low();
+        self.power_pins.insert(String::from(sensor), pin);
+        self.powered_at.remove(sensor);
+    }
+
+    // After a remote config changed `include`
+    pub fn set_included(&mut self, included: Vec<String>) {
+        self.included = included;
+    }
+
+    // Switches on every included gated sensor that is off, before its warm-up
+    pub fn power_up(&mut self) {
+        for (sensor, pin) in self.power_pins.iter_mut() {
+            if self.included.contains(sensor) && !self.powered_at.contains_key(sensor) {
+                pin.set_high();
+                self.powered_at.insert(sensor.clone(), Instant::now());
+            }
+        }
+    }
+
+    // After the cycle's readings; sensors without a load switch stay on
+    pub fn power_down(&mut self) {
+        for (sensor, pin) in self.power_pins.iter_mut() {
+            pin.set_low();
+            self.powered_at.remove(sensor);
+        }
+    }
+
+    pub fn powered_up(&mut self, sensor: &str) {
+        self.powered_at.insert(String::from(sensor), Instant::now());
+    }
+
+    // The next cycle has to warm it up again
+    pub fn powered_down(&mut self, sensor: &str) {
+        self.powered_at.remove(sensor);
+    }
+
+    pub fn is_settled(&self, sensor: &str) -> bool {
+        self.remaining(sensor) == Duration::from_ticks(0)
+    }
+
+    // Sleeps until every powered sensor has settled
+    pub async fn settle(&self) {
+        let remaining = self
+            .powered_at
+            .keys()
+            .map(|sensor| self.remaining(sensor))
+            .max()
+            .unwrap_or(Duration::from_ticks(0));
+        Timer::after(remaining).await;
+    }
+
+    fn remaining(&self, sensor: &str) -> Duration {
+        let warmup = match self.warmup_ms.get(sensor) {
+            Some(warmup_ms) => Duration::from_millis(*warmup_ms),
+            None => return Duration::from_ticks(0),
+        };
+        match self.powered_at.get(sensor) {
+            Some(powered_at) => warmup.checked_sub(powered_at.elapsed()).unwrap_or(Duration::from_ticks(0)),
+            None => warmup,
+        }
+    }
+
+    fn cold_warmup_ms(&self) -> u64 {
+        self.warmup_ms
+            .iter()
+            .filter(|(sensor, _)| self.included.contains(sensor) && !self.powered_at.contains_key(sensor.as_str()))
+            .map(|(_, warmup_ms)| *warmup_ms)
+            .max()
+            .unwrap_or(0)
+    }
+}
@@ -0,0 +1,266 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::Debug;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::spi::SpiDevice;
+use embedded_sdmmc::{Mode, SdCard, ShortFileName, TimeSource, Timestamp, VolumeIdx, VolumeManager};
+use log::info;
+
+use crate::abstractions::store::IStore;
+use crate::constants::http::HttpConstant;
+use crate::constants::storage::StorageConstant;
+use crate::dtos::configurations::retention::RetentionConfigDTO;
+use crate::dtos::configurations::timezone::TimeZoneDTO;
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::enums::log_format::LogFormat;
+use crate::enums::store_mode::StoreMode;
+use crate::utilities::{csv, datetime, json, timezone};
+
+// Last envelope timestamp, used to stamp FAT directory entries
+static LOG_CLOCK: AtomicU32 = AtomicU32::new(0);
+
+pub struct EnvelopeTimeSource;
+
+impl TimeSource for EnvelopeTimeSource {
+    fn get_timestamp(&self) -> Timestamp {
+        let now = datetime::from_unix(LOG_CLOCK.load(Ordering::Relaxed) as u64);
+        Timestamp {
+            year_since_1970: (now.year - 1970).max(0) as u8,
+            zero_indexed_month: now.month - 1,
+            zero_indexed_day: now.day - 1,
+            hours: now.hour,
+            minutes: now.minute,
+            seconds: now.second,
+        }
+    }
+}
+
+pub struct SdCardLoggerService<S: SpiDevice, D: DelayNs> {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    format: LogFormat,
+    mode: StoreMode,
+    timezone: TimeZoneDTO,
+    volume_manager: VolumeManager<SdCard<S, D>, EnvelopeTimeSource>,
+}
+
+impl<S: SpiDevice, D: DelayNs> IStore for SdCardLoggerService<S, D> {
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    fn append(&mut self, envelope: &MeasurementEnvelopeDTO) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self._append(envelope)
+    }
+
+    fn export_csv(
+        &mut self,
+        from: u64,
+        to: u64,
+        sink: &mut dyn FnMut(&str) -> Result<(), Box<dyn Error + Send + Sync>>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self._export_csv(from, to, sink)
+    }
+
+    fn compact(&mut self, retention: &RetentionConfigDTO, now: u64) -> Result<u64, Box<dyn Error + Send + Sync>> {
+        self._compact(retention, now)
+    }
+}
+
+impl<S: SpiDevice, D: DelayNs> SdCardLoggerService<S, D> {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        spi: S,
+        delay: D,
+        format: LogFormat,
+        mode: StoreMode,
+        timezone: TimeZoneDTO,
+    ) -> Self {
+        let sd_card: SdCard<S, D> = SdCard::new(spi, delay);
+        let volume_manager = VolumeManager::new(sd_card, EnvelopeTimeSource);
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            format: format,
+            mode: mode,
+            timezone: timezone,
+            volume_manager: volume_manager,
+        }
+    }
+
+    pub fn mode(&self) -> StoreMode {
+        self.mode
+    }
+
+    // One file per local day, e.g. 20251017.CSV, rows inside keep UTC
+    // timestamps
+    pub fn file_name(&self, timestamp: u64) -> String {
+        self.day_file_name(timezone::to_local(&self.timezone, timestamp).div_euclid(86_400))
+    }
+
+    fn day_file_name(&self, local_day: i64) -> String {
+        let date = datetime::from_unix((local_day * 86_400).max(0) as u64);
+        format!("{:04}{:02}{:02}.{}", date.year, date.month, date.day, self.format.extension())
+    }
+
+    fn _append(&mut self, envelope: &MeasurementEnvelopeDTO) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.mode == StoreMode::Disabled {
+            return Ok(());
+        }
+        LOG_CLOCK.store(envelope.timestamp as u32, Ordering::Relaxed);
+
+        let file_name = self.file_name(envelope.timestamp);
+        let mut volume = self.volume_manager
+            .open_volume(VolumeIdx(StorageConstant::SD_VOLUME_INDEX))
+            .map_err(sd_error)?;
+        let mut root_dir = volume.open_root_dir().map_err(sd_error)?;
+        let mut file = root_dir
+            .open_file_in_dir(file_name.as_str(), Mode::ReadWriteCreateOrAppend)
+            .map_err(sd_error)?;
+
+        let lines: String = match self.format {
+            LogFormat::Csv => {
+                let mut lines = String::new();
+                if file.length() == 0 {
+                    lines.push_str(StorageConstant::CSV_HEADER);
+                }
+                lines.push_str(&csv::envelope_to_rows(envelope));
+                lines
+            },
+            LogFormat::Jsonl => {
+                format!("{}\n", json::envelope_to_json(envelope))
+            }
+        };
+        file.write(lines.as_bytes()).map_err(sd_error)?;
+        file.flush().map_err(sd_error)?;
+        Ok(())
+    }
+
+    // Deletes whole daily files, oldest first, while any is older than
+    // `max_age_s` or the files together exceed `max_bytes`. Today's file is
+    // never deleted, it is still being written
+    fn _compact(&mut self, retention: &RetentionConfigDTO, now: u64) -> Result<u64, Box<dyn Error + Send + Sync>> {
+        if self.mode == StoreMode::Disabled {
+            return Ok(0);
+        }
+        let today = timezone::to_local(&self.timezone, now).div_euclid(86_400);
+        let extension = self.format.extension();
+        let mut volume = self.volume_manager
+            .open_volume(VolumeIdx(StorageConstant::SD_VOLUME_INDEX))
+            .map_err(sd_error)?;
+        let mut root_dir = volume.open_root_dir().map_err(sd_error)?;
+
+        let mut files: Vec<(i64, String, u64)> = Vec::new();
+        root_dir
+            .iterate_dir(|entry| {
+                if let Some(day) = file_day(&entry.name, extension) {
+                    files.push((day, format!("{}", entry.name), entry.size as u64));
+                }
+            })
+            .map_err(sd_error)?;
+        files.sort_unstable_by_key(|(day, _, _)| *day);
+
+        let oldest_kept = retention.max_age_s.map(|max_age_s| today - (max_age_s / 86_400) as i64);
+        let mut total: u64 = files.iter().map(|(_, _, size)| size).sum();
+        let mut freed = 0;
+        for (day, name, size) in files {
+            let expired = oldest_kept.is_some_and(|oldest_kept| day < oldest_kept);
+            let over = retention.max_bytes.is_some_and(|max_bytes| total > max_bytes as u64);
+            if day >= today || !(expired || over) {
+                break;
+            }
+            root_dir.delete_file_in_dir(name.as_str()).map_err(sd_error)?;
+            info!("Retention deleted {} ({} bytes)", name, size);
+            total -= size;
+            freed += size;
+        }
+        Ok(freed)
+    }
+
+    // Replays the daily CSV files covering [from, to], emitting the header once
+    // and only the rows whose timestamp falls inside the range
+    fn _export_csv(
+        &mut self,
+        from: u64,
+        to: u64,
+        sink: &mut dyn FnMut(&str) -> Result<(), Box<dyn Error + Send + Sync>>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.format != LogFormat::Csv {
+            return Err(Box::from("CSV export requires SD_LOG_FORMAT=csv"));
+        }
+        let first_day = timezone::to_local(&self.timezone, from).div_euclid(86_400);
+        let last_day = timezone::to_local(&self.timezone, to).div_euclid(86_400);
+        let file_names: Vec<String> = (first_day..=last_day).map(|day| self.day_file_name(day)).collect();
+        let mut volume = self.volume_manager
+            .open_volume(VolumeIdx(StorageConstant::SD_VOLUME_INDEX))
+            .map_err(sd_error)?;
+        let mut root_dir = volume.open_root_dir().map_err(sd_error)?;
+        // Only once the card is mounted, the caller starts its response on
+        // the first call
+        sink(StorageConstant::CSV_HEADER)?;
+
+        for file_name in file_names {
+            let mut file = match root_dir.open_file_in_dir(file_name.as_str(), Mode::ReadOnly) {
+                Ok(file) => file,
+                Err(embedded_sdmmc::Error::NotFound) => continue,
+                Err(error) => return Err(sd_error(error)),
+            };
+
+            let mut buffer = [0u8; HttpConstant::READ_CHUNK_BYTES];
+            // Raw bytes, a read may end inside a multi-byte character
+            let mut carry: Vec<u8> = Vec::new();
+            while !file.is_eof() {
+                let read = file.read(&mut buffer).map_err(sd_error)?;
+                carry.extend_from_slice(&buffer[..read]);
+                while let Some(end) = carry.iter().position(|byte| *byte == b'\n') {
+                    let line: Vec<u8> = carry.drain(..=end).collect();
+                    let line = String::from_utf8_lossy(&line);
+                    if csv::row_in_range(&line, from, to) {
+                        sink(&line)?;
+                    }
+                }
+            }
+            let line = String::from_utf8_lossy(&carry);
+            if csv::row_in_range(&line, from, to) {
+                sink(&line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Day number of a log file named by `day_file_name`, None for other files
+fn file_day(name: &ShortFileName, extension: &str) -> Option<i64> {
+    if !name.extension().eq_ignore_ascii_case(extension.as_bytes()) {
+        return None;
+    }
+    let base = core::str::from_utf8(name.base_name()).ok()?;
+    if base.len() != 8 || !base.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let year = base[0..4].parse().ok()?;
+    let month = base[4..6].parse().ok()?;
+    let day = base[6..8].parse().ok()?;
+    Some(datetime::days_from_civil(year, month, day))
+}
+
+fn sd_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("SD card error: {:?}", error))
+}
@@ -0,0 +1,206 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::Debug;
+
+use esp_hal::aes::{Aes, Mode};
+use esp_hal::efuse::{Efuse, BLOCK2};
+use esp_hal::peripherals::AES;
+use esp_hal::rng::Rng;
+use littlefs2::fs::Filesystem;
+use littlefs2::path::PathBuf;
+use log::warn;
+use sha2::{Digest, Sha256};
+
+use crate::constants::secret::SecretConstant;
+use crate::constants::storage::StorageConstant;
+use crate::utilities::flash_partition::FlashPartition;
+
+// Named blobs (certificates, keys) kept on the LittleFS partition, one file
+// per secret under SECRETS_DIR. Provisioning writes them, services read them.
+// Each file is sealed with the device key from eFuse: AES-256-CTR on the
+// hardware AES, then an HMAC-SHA256 tag over the name and ciphertext, so a
+// flash dump reveals nothing and a file moved to another name or board does
+// not open
+pub struct SecretStoreService {
+    storage: FlashPartition,
+    aes: Aes<'static>,
+    rng: Rng,
+    // None while no device key is burned, secrets can then not be written
+    keys: Option<SecretKeys>,
+}
+
+struct SecretKeys {
+    cipher: [u8; 32],
+    mac: [u8; 32],
+}
+
+impl SecretStoreService {
+    pub fn new(aes: AES<'static>, rng: Rng) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+        Filesystem::mount_and_then(&mut storage, |fs| {
+            if fs.metadata(&path(SecretConstant::SECRETS_DIR)).is_err() {
+                fs.create_dir(&path(SecretConstant::SECRETS_DIR))?;
+            }
+            Ok(())
+        })
+        .map_err(secret_error)?;
+        // Burned per board at manufacture with `espefuse.py burn_block_data
+        // BLOCK2`, which leaves the block unavailable to secure boot
+        let device_key: [u8; 32] = Efuse::read_field_le(BLOCK2);
+        // Blank, or read protected and so read back as zeros
+        let keys = if device_key == [0; 32] {
+            warn!("No device key in eFuse BLOCK2, secrets cannot be stored");
+            None
+        } else {
+            Some(SecretKeys {
+                cipher: derive(&device_key, b"cipher"),
+                mac: derive(&device_key, b"mac"),
+            })
+        };
+        Ok(Self {
+            storage: storage,
+            aes: Aes::new(aes),
+            rng: rng,
+            keys: keys,
+        })
+    }
+
+    pub fn get(&mut self, name: &str) -> Result<Option<Vec<u8>>, Box<dyn Error + Send + Sync>> {
+        let stored = match self.read(name)? {
+            Some(stored) => stored,
+            None => return Ok(None),
+        };
+        // Only `put` writes the store, a file it did not seal was planted
+        if !stored.starts_with(SecretConstant::SEALED_MAGIC) {
+            return Err(Box::from(format!("Secret {} failed authentication", name)));
+        }
+        self.open(name, &stored).map(Some)
+    }
+
+    pub fn put(&mut self, name: &str, value: &[u8]) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if value.len() > SecretConstant::MAX_SECRET_BYTES {
+            return Err(Box::from(format!("Secret {} of {} bytes is too large", name, value.len())));
+        }
+        let sealed = self.seal(name, value)?;
+        let name = secret_name(name);
+        Filesystem::mount_and_then(&mut self.storage, |fs| fs.write(&path(&name), &sealed)).map_err(secret_error)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let name = secret_name(name);
+        Filesystem::mount_and_then(&mut self.storage, |fs| fs.remove(&path(&name))).map_err(secret_error)
+    }
+
+    // For buffers that must outlive the store, e.g. certificates handed to
+    // the Wi-Fi supplicant
+    pub fn get_static(&mut self, name: &str) -> Result<Option<&'static [u8]>, Box<dyn Error + Send + Sync>> {
+        Ok(self.get(name)?.map(|bytes| &*Box::leak(bytes.into_boxed_slice())))
+    }
+
+    fn read(&mut self, name: &str) -> Result<Option<Vec<u8>>, Box<dyn Error + Send + Sync>> {
+        let name = secret_name(name);
+        Filesystem::mount_and_then(&mut self.storage, |fs| {
+            if fs.metadata(&path(&name)).is_err() {
+                return Ok(None);
+            }
+            let mut bytes = vec![0u8; SecretConstant::MAX_SEALED_BYTES];
+            let read = fs.open_file_and_then(&path(&name), |file| file.read(&mut bytes))?;
+            bytes.truncate(read);
+            Ok(Some(bytes))
+        })
+        .map_err(secret_error)
+    }
+
+    // Magic, nonce, ciphertext, tag
+    fn seal(&mut self, name: &str, value: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let keys = self.keys.as_ref().ok_or("No device key burned, secrets cannot be sealed")?;
+        let (cipher, mac) = (keys.cipher, keys.mac);
+        let mut nonce = [0u8; SecretConstant::NONCE_BYTES];
+        self.rng.read(&mut nonce);
+
+        let mut sealed = Vec::with_capacity(SecretConstant::MAX_SEALED_BYTES);
+        sealed.extend_from_slice(SecretConstant::SEALED_MAGIC);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(value);
+        let body_at = SecretConstant::SEALED_MAGIC.len() + nonce.len();
+        self.apply_keystream(&cipher, &nonce, &mut sealed[body_at..]);
+        let tag = tag(&mac, name, &sealed);
+        sealed.extend_from_slice(&tag[..SecretConstant::TAG_BYTES]);
+        Ok(sealed)
+    }
+
+    fn open(&mut self, name: &str, sealed: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let keys = self.keys.as_ref().ok_or("No device key burned, secrets cannot be sealed")?;
+        let (cipher, mac) = (keys.cipher, keys.mac);
+        let body_at = SecretConstant::SEALED_MAGIC.len() + SecretConstant::NONCE_BYTES;
+        if sealed.len() < body_at + SecretConstant::TAG_BYTES {
+            return Err(Box::from(format!("Secret {} is truncated", name)));
+        }
+        let (authenticated, stored_tag) = sealed.split_at(sealed.len() - SecretConstant::TAG_BYTES);
+        let expected = tag(&mac, name, authenticated);
+        // Compared in full every time so timing tells nothing
+        let mismatch = stored_tag.iter().zip(expected.iter()).fold(0u8, |acc, (a, b)| acc | (a ^ b));
+        if mismatch != 0 {
+            return Err(Box::from(format!("Secret {} failed authentication", name)));
+        }
+        let nonce: [u8; SecretConstant::NONCE_BYTES] =
+            authenticated[SecretConstant::SEALED_MAGIC.len()..body_at].try_into().map_err(secret_error)?;
+        let mut value = authenticated[body_at..].to_vec();
+        self.apply_keystream(&cipher, &nonce, &mut value);
+        Ok(value)
+    }
+
+    // CTR mode: the nonce followed by a big-endian block counter, encrypted
+    // and XORed in. The same call encrypts and decrypts
+    fn apply_keystream(&mut self, key: &[u8; 32], nonce: &[u8; SecretConstant::NONCE_BYTES], data: &mut [u8]) {
+        for (counter, chunk) in data.chunks_mut(16).enumerate() {
+            let mut block = [0u8; 16];
+            block[..SecretConstant::NONCE_BYTES].copy_from_slice(nonce);
+            block[SecretConstant::NONCE_BYTES..].copy_from_slice(&(counter as u32).to_be_bytes());
+            self.aes.process(&mut block, Mode::Encryption256, *key);
+            chunk.iter_mut().zip(block.iter()).for_each(|(byte, key)| *byte ^= key);
+        }
+    }
+}
+
+fn derive(device_key: &[u8; 32], purpose: &[u8]) -> [u8; 32] {
+    let mut hash = Sha256::new();
+    hash.update(purpose);
+    hash.update(device_key);
+    hash.finalize().into()
+}
+
+// HMAC-SHA256 over the secret's name and the sealed bytes before the tag
+fn tag(key: &[u8; 32], name: &str, sealed: &[u8]) -> [u8; 32] {
+    let mut inner_pad = [0x36u8; 64];
+    let mut outer_pad = [0x5cu8; 64];
+    for (index, byte) in key.iter().enumerate() {
+        inner_pad[index] ^= byte;
+        outer_pad[index] ^= byte;
+    }
+    let mut inner = Sha256::new();
+    inner.update(inner_pad);
+    inner.update((name.len() as u32).to_le_bytes());
+    inner.update(name.as_bytes());
+    inner.update(sealed);
+    let mut outer = Sha256::new();
+    outer.update(outer_pad);
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+fn secret_name(name: &str) -> String {
+    format!("{}/{}", SecretConstant::SECRETS_DIR, name)
+}
+
+fn path(name: &str) -> PathBuf {
+    PathBuf::from(name)
+}
+
+fn secret_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("Secret store error: {:?}", error))
+}
@@ -0,0 +1,76 @@
+use core::cell::RefCell;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_time::{Duration, Instant, Timer};
+
+use crate::constants::sensor::SensorConstant;
+use crate::dtos::configurations::bme280::Bme280ConfigDTO;
+
+struct HeatState {
+    // Accumulated since `window_started`
+    cpu_busy_ms: u64,
+    radio_active_ms: u64,
+    window_started: Instant,
+    // Modelled board warming at the BME280, lags the load like the board does
+    offset_c: f32,
+    updated: Option<Instant>,
+}
+
+// The BME280 sits next to the ESP32 on our board and reads 1-2 °C high while
+// the CPU runs at full clock or the radio transmits. The error is modelled as
+// a fixed offset plus a share per unit of CPU and radio duty, approached with
+// the board's thermal time constant rather than applied instantly
+static HEAT: Mutex<CriticalSectionRawMutex, RefCell<HeatState>> = Mutex::new(RefCell::new(HeatState {
+    cpu_busy_ms: 0,
+    radio_active_ms: 0,
+    window_started: Instant::from_ticks(0),
+    offset_c: 0.0,
+    updated: None,
+}));
+
+pub fn record_cpu_busy(ms: u64) {
+    HEAT.lock(|state| state.borrow_mut().cpu_busy_ms += ms)
+}
+
+// Time spent sending, a stand-in for the Wi-Fi or modem transmit duty
+pub fn record_radio_active(ms: u64) {
+    HEAT.lock(|state| state.borrow_mut().radio_active_ms += ms)
+}
+
+// Degrees to subtract from a BME280 temperature read now
+pub fn offset_c(config: &Bme280ConfigDTO) -> f32 {
+    let now = Instant::now();
+    HEAT.lock(|state| {
+        let mut state = state.borrow_mut();
+        let window_ms = now.duration_since(state.window_started).as_millis().max(1);
+        let cpu_duty = (state.cpu_busy_ms as f32 / window_ms as f32).min(1.0);
+        let radio_duty = (state.radio_active_ms as f32 / window_ms as f32).min(1.0);
+        let target = config.cpu_heating_c * cpu_duty + config.radio_heating_c * radio_duty;
+        // The first read has no history to lag behind
+        let weight = match state.updated {
+            Some(updated) => {
+                (now.duration_since(updated).as_millis() as f32 / (SensorConstant::SELF_HEATING_TAU_S * 1000.0)).min(1.0)
+            },
+            None => 1.0,
+        };
+        state.offset_c += (target - state.offset_c) * weight;
+        state.updated = Some(now);
+        state.cpu_busy_ms = 0;
+        state.radio_active_ms = 0;
+        state.window_started = now;
+        config.temperature_offset_c + state.offset_c
+    })
+}
+
+// Lowest priority task estimating CPU load: a timer that should fire every
+// probe interval wakes up late by about as long as other tasks kept the CPU
+pub async fn run_load_probe() -> ! {
+    let interval = Duration::from_millis(SensorConstant::SELF_HEATING_PROBE_MS);
+    loop {
+        let started = Instant::now();
+        Timer::after(interval).await;
+        let late_ms = started.elapsed().as_millis().saturating_sub(interval.as_millis());
+        record_cpu_busy(late_ms);
+    }
+}
@@ -1,22 +1,37 @@
+use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
-use alloc::string::String;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::Pin;
+
+use embassy_time::{with_timeout, Duration, Instant, TimeoutError};
+use log::warn;
 
 use crate::abstractions::factory::IFactory;
-use crate::abstractions::sensor::ISensor;
 use crate::abstractions::service::IService;
+use crate::constants::sensor::SensorConstant;
 use crate::dtos::configurations::sensors::SensorsConfigDTO;
 use crate::dtos::response::services::sensing_client::SensingClientServiceResponseDTO;
-use crate::factories::sensor::SensorFactory;
+use crate::enums::read_status::ReadStatus;
+use crate::enums::value::Value;
+use crate::factories::sensor::{Sensor, SensorFactory};
+use crate::services::{last_value, sensor_stats};
+use crate::utilities::join;
+
+type Reading = (String, ReadStatus, Option<BTreeMap<String, Value>>);
 
 pub struct SensingClientService {
     pub urn: String,
     pub device_urn: String,
     pub location_urn: String,
-    pub config: SensorsConfigDTO
+    pub config: SensorsConfigDTO,
+    // Sensors brought up in main, see `SensorFactory::insert`
+    sensor_factory: SensorFactory,
 }
 
-impl IService<SensorsConfigDTO> for SensingClientService  {
+impl IService for SensingClientService  {
+    type Response = SensingClientServiceResponseDTO;
 
     fn urn(&self) -> String {
         self.urn.clone()
@@ -30,8 +45,8 @@ impl IService<SensorsConfigDTO> for SensingClientService  {
         self.location_urn.clone()
     }
 
-    fn run(&self) -> Result<SensingClientServiceResponseDTO, Box<dyn core::error::Error + Send + Sync>> {
-        self._run()
+    async fn run(&mut self) -> Result<SensingClientServiceResponseDTO, Box<dyn core::error::Error + Send + Sync>> {
+        self._run().await
     }
     
 }
@@ -46,43 +61,91 @@ impl SensingClientService {
         urn: String,
         device_urn: String,
         location_urn: String,
-        config: SensorsConfigDTO
+        config: SensorsConfigDTO,
+        sensor_factory: SensorFactory,
     ) -> Self {
         Self {
             urn: urn,
             device_urn: device_urn,
             location_urn: location_urn,
-            config: config
+            config: config,
+            sensor_factory: sensor_factory,
         }
     }
 
-    fn _run(&self) -> Result<SensingClientServiceResponseDTO, Box<dyn core::error::Error + Send + Sync>> {
+    async fn _run(&self) -> Result<SensingClientServiceResponseDTO, Box<dyn core::error::Error + Send + Sync>> {
 
-        let include_sensors: Vec<String> = self.config.include.clone();
-        let sensor_factory: SensorFactory = SensorFactory::new(
-            self.urn.clone(),
-            self.device_urn.clone(),
-            self.location_urn.clone()
-        );
-
-        let mut data: BTreeMap<String, String> = BTreeMap::new();
+        let include_sensors: Vec<String> = self.config().include.clone();
+        let mut unknown_sensors: Vec<String> = Vec::new();
+        let mut buses: BTreeMap<String, Vec<(String, Sensor)>> = BTreeMap::new();
         for sensor_key in include_sensors {
-            let sensor = sensor_factory.get(sensor_key.to_lowercase())?;
-            let sensor_measurements = match sensor.read_sync(){
-                Ok(data) => {
-                    format!("{:?}", data)
+            // A typo or a key from a newer backend must not cost the other
+            // sensors their readings
+            let sensor = match self.sensor_factory.get(sensor_key.to_lowercase()) {
+                Ok(sensor) => sensor,
+                Err(_) => {
+                    warn!("Skipping unknown sensor {}", sensor_key);
+                    unknown_sensors.push(sensor_key);
+                    continue;
                 },
-                Err(e) => {
-                    return Err(e);
-                }
             };
-            data.insert(sensor_key.to_uppercase(), sensor_measurements);
+            let bus = self.config().buses
+                .get(&sensor_key.to_lowercase())
+                .cloned()
+                .unwrap_or_else(|| SensorConstant::DEFAULT_BUS.to_string());
+            buses.entry(bus).or_insert_with(Vec::new).push((sensor_key, sensor));
+        }
+
+        // Sensors sharing a bus are read one after the other, the buses
+        // themselves concurrently while their sensors await; a sensor on the
+        // blocking default of `read_async` holds up every bus until it is done
+        let timeout = Duration::from_millis(self.config().read_timeout_ms);
+        let reads: Vec<Pin<Box<dyn Future<Output = Vec<Reading>> + '_>>> = buses
+            .into_values()
+            .map(|sensors| {
+                let read: Pin<Box<dyn Future<Output = Vec<Reading>> + '_>> = Box::pin(async move {
+                    let mut results = Vec::new();
+                    for (sensor_key, sensor) in sensors {
+                        let started = Instant::now();
+                        let (status, measurement) = match with_timeout(timeout, sensor.read_async()).await {
+                            Ok(Ok(data)) => {
+                                // Stands in for this sensor when a later read fails
+                                last_value::update(&sensor_key.to_lowercase(), &data);
+                                (ReadStatus::Ok, Some(data))
+                            },
+                            Ok(Err(error)) => {
+                                warn!("Sensor {} read failed: {}", sensor_key, error);
+                                (ReadStatus::Error, None)
+                            },
+                            Err(TimeoutError) => {
+                                warn!("Sensor {} did not answer within {:?}", sensor_key, timeout);
+                                (ReadStatus::Timeout, None)
+                            },
+                        };
+                        sensor_stats::record(&sensor_key.to_lowercase(), status, started.elapsed().as_millis() as u32);
+                        results.push((sensor_key, status, measurement));
+                    }
+                    results
+                });
+                read
+            })
+            .collect();
+
+        let mut data: BTreeMap<String, BTreeMap<String, Value>> = BTreeMap::new();
+        let mut statuses: BTreeMap<String, ReadStatus> = BTreeMap::new();
+        for (sensor_key, status, measurement) in join::join_all(reads).await.into_iter().flatten() {
+            if let Some(measurement) = measurement {
+                data.insert(sensor_key.to_uppercase(), measurement);
+            }
+            statuses.insert(sensor_key.to_uppercase(), status);
         }
         Ok(
             SensingClientServiceResponseDTO {
                 data: data,
+                statuses: statuses,
+                unknown_sensors: unknown_sensors,
             }
         )
     }
     
-}
\ No newline at end of file
+}
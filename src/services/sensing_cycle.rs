@@ -0,0 +1,179 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::channel::{Sender, TrySendError};
+use embassy_time::{Duration, Timer};
+use log::{debug, warn};
+
+use crate::abstractions::factory::IFactory;
+use crate::abstractions::service::IService;
+use crate::constants::upload::UploadConstant;
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::dtos::pipeline::output::PipelineOutputDTO;
+use crate::enums::priority::Priority;
+use crate::factories::pipeline::PipelineFactory;
+use crate::pipelines::chain::PipelineChain;
+use crate::services::batcher::BatchItem;
+use crate::services::clock;
+use crate::services::scheduler::SchedulerService;
+use crate::services::sensing_client::SensingClientService;
+use crate::services::{envelope, event_outbox, live_stream};
+
+pub type MeasurementSender = Sender<'static, CriticalSectionRawMutex, BatchItem, { UploadConstant::MEASUREMENT_CHANNEL_DEPTH }>;
+
+// One sensing cycle end to end, the unit the supervisor runs: power up and
+// settle the sensors with a warm-up, read the included ones, run each
+// reading through the sensor's pipeline chain, turn what is left into one
+// envelope per location and put them on the measurement channel for the
+// batcher. The time between cycles is the scheduler's, slept out in `wait`
+// outside the supervised run
+pub struct SensingCycleService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    sensing: SensingClientService,
+    scheduler: SchedulerService,
+    pipelines: PipelineFactory,
+    // Built on a sensor's first reading and kept, stages hold state
+    chains: BTreeMap<String, PipelineChain>,
+    measurements: MeasurementSender,
+}
+
+impl IService for SensingCycleService {
+    // Every envelope of the cycle, also those the channel had no room for,
+    // for the SD card
+    type Response = Vec<MeasurementEnvelopeDTO>;
+
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    async fn run(&mut self) -> Result<Vec<MeasurementEnvelopeDTO>, Box<dyn Error + Send + Sync>> {
+        self._run().await
+    }
+}
+
+impl SensingCycleService {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        sensing: SensingClientService,
+        scheduler: SchedulerService,
+        pipelines: PipelineFactory,
+        measurements: MeasurementSender,
+    ) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            sensing: sensing,
+            scheduler: scheduler,
+            pipelines: pipelines,
+            chains: BTreeMap::new(),
+            measurements: measurements,
+        }
+    }
+
+    // Until the next cycle is due. Windows mean nothing without the time of
+    // day, so until SNTP or the RTC set the clock it is the plain interval
+    pub async fn wait(&mut self, interval: Duration) {
+        match clock::now() {
+            Some(now) => self.scheduler.wait(now).await,
+            None => Timer::after(interval).await,
+        }
+    }
+
+    // A chain the factory cannot build is logged there and replaced by an
+    // empty one, the readings then go out unprocessed
+    fn chain(&mut self, sensor: &str) -> &PipelineChain {
+        if !self.chains.contains_key(sensor) {
+            let chain = self.pipelines.get(String::from(sensor)).unwrap_or_else(|_| {
+                PipelineChain::new(
+                    format!("{}:{}", self.pipelines.urn(), sensor),
+                    self.device_urn.clone(),
+                    self.location_urn.clone(),
+                    Vec::new(),
+                )
+            });
+            self.chains.insert(String::from(sensor), chain);
+        }
+        &self.chains[sensor]
+    }
+
+    async fn _run(&mut self) -> Result<Vec<MeasurementEnvelopeDTO>, Box<dyn Error + Send + Sync>> {
+        self.scheduler.power_up();
+        self.scheduler.settle().await;
+        let response = self.sensing.run().await;
+        self.scheduler.power_down();
+        let response = response?;
+
+        let mut outputs: Vec<(String, String, PipelineOutputDTO)> = Vec::new();
+        for (sensor, data) in response.data {
+            let sensor = sensor.to_lowercase();
+            if !self.scheduler.is_settled(&sensor) {
+                debug!("Discarding {} reading, still warming up", sensor);
+                continue;
+            }
+            let location_urn = self
+                .sensing
+                .config
+                .location_urns
+                .get(&sensor)
+                .cloned()
+                .unwrap_or_else(|| self.location_urn.clone());
+            let chain = self.chain(&sensor);
+            let output = chain.process(data);
+            for event in chain.events(&output) {
+                event_outbox::raise(event);
+            }
+            // Every value dropped, e.g. an outlier
+            if !output.values.is_empty() {
+                outputs.push((sensor, location_urn, output));
+            }
+        }
+
+        let readings = outputs
+            .iter()
+            .map(|(sensor, location_urn, output)| (sensor.clone(), location_urn.clone(), output.values.clone()))
+            .collect();
+        let mut envelopes = Vec::new();
+        for mut envelope in envelope::by_location(&self.device_urn, readings) {
+            // Goes out at the most urgent class of the readings in it
+            let mut priority = Priority::Periodic;
+            for (sensor, _, output) in outputs.iter().filter(|(_, location_urn, _)| *location_urn == envelope.location_urn) {
+                priority = priority.max(output.priority);
+                for (field, quality) in output.quality.iter() {
+                    let namespaced = format!("{}.{}", sensor, field);
+                    let field = if envelope.data.contains_key(&namespaced) { namespaced } else { field.clone() };
+                    envelope.quality.insert(field, quality.clone());
+                }
+            }
+            live_stream::publish(&envelope);
+            let item = BatchItem {
+                envelope: envelope.clone(),
+                priority: priority,
+            };
+            // Never blocks sensing; with the uploader that far behind the
+            // envelope is dropped and logged
+            if let Err(TrySendError::Full(item)) = self.measurements.try_send(item) {
+                warn!("Measurement channel full, dropping {}", item.envelope.id);
+            }
+            envelopes.push(envelope);
+        }
+        Ok(envelopes)
+    }
+}
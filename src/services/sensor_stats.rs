@@ -0,0 +1,47 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+
+use crate::dtos::telemetry::heartbeat::HeartbeatDTO;
+use crate::dtos::telemetry::sensor_stats::SensorStatsDTO;
+use crate::enums::read_status::ReadStatus;
+use crate::services::{clock, soak};
+
+// Read counters per sensor since boot, so a sensor whose errors or latency
+// creep up on flaky wiring shows before it stops answering altogether.
+// Served on /metrics and reported in the heartbeat
+static STATS: Mutex<CriticalSectionRawMutex, RefCell<BTreeMap<String, SensorStatsDTO>>> =
+    Mutex::new(RefCell::new(BTreeMap::new()));
+
+pub fn record(name: &str, status: ReadStatus, latency_ms: u32) {
+    soak::record_read(matches!(status, ReadStatus::Ok));
+    STATS.lock(|stats| {
+        let mut stats = stats.borrow_mut();
+        let sensor = stats.entry(String::from(name)).or_insert_with(|| SensorStatsDTO {
+            name: String::from(name),
+            ..SensorStatsDTO::default()
+        });
+        match status {
+            ReadStatus::Ok => {
+                sensor.successes = sensor.successes.saturating_add(1);
+                sensor.last_success = clock::now().or(sensor.last_success);
+            },
+            ReadStatus::Error => sensor.errors = sensor.errors.saturating_add(1),
+            ReadStatus::Timeout => sensor.timeouts = sensor.timeouts.saturating_add(1),
+        }
+        sensor.min_latency_ms = Some(sensor.min_latency_ms.map_or(latency_ms, |min| min.min(latency_ms)));
+        sensor.max_latency_ms = Some(sensor.max_latency_ms.map_or(latency_ms, |max| max.max(latency_ms)));
+    })
+}
+
+pub fn snapshot() -> Vec<SensorStatsDTO> {
+    STATS.lock(|stats| stats.borrow().values().cloned().collect())
+}
+
+pub fn report(heartbeat: &mut HeartbeatDTO) {
+    heartbeat.sensors = snapshot();
+}
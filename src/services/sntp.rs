@@ -0,0 +1,74 @@
+use alloc::string::String;
+
+use embassy_net::dns::DnsQueryType;
+use embassy_net::udp::{PacketMetadata, UdpSocket};
+use embassy_net::{IpAddress, IpEndpoint, Ipv4Address, Stack};
+use embassy_time::{with_timeout, Duration, Instant};
+
+use crate::constants::clock::ClockConstant;
+use crate::enums::transport_error::TransportError;
+
+const PACKET_BYTES: usize = 48;
+// LI 0, version 4, mode 3 (client)
+const CLIENT_HEADER: u8 = 0x23;
+const MODE_SERVER: u8 = 4;
+// Seconds between the NTP epoch (1900) and the unix epoch
+const NTP_UNIX_OFFSET_S: u64 = 2_208_988_800;
+
+// One SNTP exchange. Returns the server's unix time in milliseconds, corrected
+// by half the round trip, together with the instant it applies to
+pub async fn query(stack: Stack<'static>, server: &str) -> Result<(u64, Instant), TransportError> {
+    let address = resolve(stack, server).await?;
+
+    let mut rx_meta = [PacketMetadata::EMPTY; 1];
+    let mut tx_meta = [PacketMetadata::EMPTY; 1];
+    let mut rx_buffer = [0u8; PACKET_BYTES];
+    let mut tx_buffer = [0u8; PACKET_BYTES];
+    let mut socket = UdpSocket::new(stack, &mut rx_meta, &mut rx_buffer, &mut tx_meta, &mut tx_buffer);
+    socket
+        .bind(ClockConstant::SNTP_LOCAL_PORT)
+        .map_err(|_| TransportError::Unavailable)?;
+
+    let mut request = [0u8; PACKET_BYTES];
+    request[0] = CLIENT_HEADER;
+    let sent = Instant::now();
+    socket
+        .send_to(&request, IpEndpoint::new(address, ClockConstant::SNTP_PORT))
+        .await
+        .map_err(|_| TransportError::Unavailable)?;
+
+    let mut reply = [0u8; PACKET_BYTES];
+    let timeout = Duration::from_millis(ClockConstant::SNTP_TIMEOUT_MS);
+    let read = match with_timeout(timeout, socket.recv_from(&mut reply)).await {
+        Ok(Ok((read, _))) => read,
+        Ok(Err(_)) => return Err(TransportError::Unavailable),
+        Err(_) => return Err(TransportError::Timeout),
+    };
+    let received = Instant::now();
+    socket.close();
+
+    // Stratum 0 is a kiss-o'-death, the server wants us to back off
+    if read < PACKET_BYTES || reply[0] & 0x07 != MODE_SERVER || reply[1] == 0 {
+        return Err(TransportError::Rejected(String::from("invalid SNTP reply")));
+    }
+    let seconds = u32::from_be_bytes([reply[40], reply[41], reply[42], reply[43]]) as u64;
+    let fraction = u32::from_be_bytes([reply[44], reply[45], reply[46], reply[47]]) as u64;
+    if seconds < NTP_UNIX_OFFSET_S {
+        return Err(TransportError::Rejected(String::from("SNTP time before 1970")));
+    }
+    let unix_ms = (seconds - NTP_UNIX_OFFSET_S) * 1_000 + (fraction * 1_000 >> 32);
+    let half_round_trip = (received - sent).as_millis() / 2;
+    Ok((unix_ms + half_round_trip, received))
+}
+
+async fn resolve(stack: Stack<'static>, server: &str) -> Result<IpAddress, TransportError> {
+    if let Ok(address) = server.parse::<Ipv4Address>() {
+        return Ok(IpAddress::Ipv4(address));
+    }
+    stack
+        .dns_query(server, DnsQueryType::A)
+        .await
+        .ok()
+        .and_then(|addresses| addresses.first().copied())
+        .ok_or(TransportError::Unavailable)
+}
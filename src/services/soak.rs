@@ -0,0 +1,320 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::error::Error;
+use core::fmt::Debug;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_time::{Duration, Instant};
+use littlefs2::fs::Filesystem;
+use littlefs2::path::PathBuf;
+use log::{info, warn};
+
+use crate::config::Config;
+use crate::configurations::sensors::SensorsConfig;
+use crate::constants::soak::SoakConstant;
+use crate::constants::storage::StorageConstant;
+use crate::dtos::response::server::command::ServerCommandResponseDTO;
+use crate::dtos::telemetry::soak_stats::SoakStatsDTO;
+use crate::enums::transport_error::TransportError;
+use crate::services::{clock, hardware_profile};
+use crate::utilities::flash_partition::FlashPartition;
+use crate::utilities::state_file;
+
+const ACTIVE: u8 = 0x01;
+const COMPLETED: u8 = 0x02;
+const RECORD_LENGTH: usize = 45;
+
+struct SoakState {
+    stats: SoakStatsDTO,
+    last_poll: Option<Instant>,
+    persisted_s: u64,
+}
+
+// Burn-in before shipment: every fitted sensor at the fastest interval and
+// every reading uploaded on its own for `SoakConstant::DURATION_S` of
+// uptime, counting read and upload failures and the resets in between.
+// Started by the SOAK_TEST build flag or the "soak" downlink, the counters
+// live on flash so they survive the resets they count, and stay readable
+// on /soak once the test is over
+static STATE: Mutex<CriticalSectionRawMutex, RefCell<SoakState>> = Mutex::new(RefCell::new(SoakState {
+    stats: SoakStatsDTO {
+        active: false,
+        completed: false,
+        started_at: 0,
+        elapsed_s: 0,
+        reads: 0,
+        read_errors: 0,
+        uploads: 0,
+        upload_errors: 0,
+        resets: 0,
+        watchdog_resets: 0,
+        brownout_resets: 0,
+    },
+    last_poll: None,
+    persisted_s: 0,
+}));
+
+// Once at boot, `reset_reason` as `MetricsService` reports it. A build with
+// SOAK_TEST starts a test on its first boot only, never a second one after
+// the first completed
+pub fn boot(reset_reason: &str) {
+    let stats = match load() {
+        Ok(stats) => stats,
+        Err(error) => {
+            warn!("Soak counters unreadable: {}", error);
+            None
+        },
+    };
+    match stats {
+        Some(mut stats) if stats.active => {
+            let reason = reset_reason.to_ascii_lowercase();
+            stats.resets = stats.resets.saturating_add(1);
+            if reason.contains("wdt") {
+                stats.watchdog_resets = stats.watchdog_resets.saturating_add(1);
+            }
+            if reason.contains("brownout") {
+                stats.brownout_resets = stats.brownout_resets.saturating_add(1);
+            }
+            info!("Soak test resumed at {} s after reset {}", stats.elapsed_s, reset_reason);
+            STATE.lock(|state| {
+                let mut state = state.borrow_mut();
+                state.stats = stats;
+                state.persisted_s = stats.elapsed_s;
+            });
+            persist();
+        },
+        Some(stats) => STATE.lock(|state| state.borrow_mut().stats = stats),
+        None if option_env!("SOAK_TEST").is_some_and(|value| value == "1" || value == "true") => start(),
+        None => {},
+    }
+}
+
+// Clears the counters of any earlier test
+pub fn start() {
+    info!("Soak test started for {} s", SoakConstant::DURATION_S);
+    STATE.lock(|state| {
+        let mut state = state.borrow_mut();
+        state.stats = SoakStatsDTO {
+            active: true,
+            started_at: clock::now().unwrap_or(0),
+            ..SoakStatsDTO::default()
+        };
+        state.last_poll = Some(Instant::now());
+        state.persisted_s = 0;
+    });
+    persist();
+}
+
+pub fn stop() {
+    let stopped = STATE.lock(|state| {
+        let mut state = state.borrow_mut();
+        let active = state.stats.active;
+        state.stats.active = false;
+        active
+    });
+    if stopped {
+        info!("Soak test stopped");
+        persist();
+    }
+}
+
+pub fn active() -> bool {
+    STATE.lock(|state| state.borrow().stats.active)
+}
+
+pub fn snapshot() -> SoakStatsDTO {
+    STATE.lock(|state| state.borrow().stats)
+}
+
+pub fn record_read(ok: bool) {
+    with_active(|stats| {
+        stats.reads = stats.reads.saturating_add(1);
+        if !ok {
+            stats.read_errors = stats.read_errors.saturating_add(1);
+        }
+    })
+}
+
+pub fn record_upload(ok: bool) {
+    with_active(|stats| {
+        stats.uploads = stats.uploads.saturating_add(1);
+        if !ok {
+            stats.upload_errors = stats.upload_errors.saturating_add(1);
+        }
+    })
+}
+
+// Every cycle. Returns true when the test just completed, for the caller
+// to put the normal settings back
+pub fn poll(now: Instant) -> bool {
+    let (due, completed) = STATE.lock(|state| {
+        let mut state = state.borrow_mut();
+        if !state.stats.active {
+            state.last_poll = Some(now);
+            return (false, false);
+        }
+        // Only whole seconds are counted, the remainder carries over to the
+        // next poll instead of being lost at every cycle
+        match state.last_poll {
+            Some(previous) => {
+                let whole_s = (now - previous).as_secs();
+                state.stats.elapsed_s += whole_s;
+                state.last_poll = Some(previous + Duration::from_secs(whole_s));
+            },
+            None => state.last_poll = Some(now),
+        }
+        let completed = state.stats.elapsed_s >= SoakConstant::DURATION_S;
+        if completed {
+            state.stats.active = false;
+            state.stats.completed = true;
+        }
+        let due = completed || state.stats.elapsed_s >= state.persisted_s + SoakConstant::PERSIST_INTERVAL_S;
+        if due {
+            state.persisted_s = state.stats.elapsed_s;
+        }
+        (due, completed)
+    });
+    if completed {
+        let stats = snapshot();
+        info!(
+            "Soak test completed: {} reads, {} failed, {} uploads, {} failed, {} resets",
+            stats.reads, stats.read_errors, stats.uploads, stats.upload_errors, stats.resets
+        );
+    }
+    if due {
+        persist();
+    }
+    completed
+}
+
+// Over the profile settings while a test runs
+pub fn apply(config: &mut Config, sensors: &mut SensorsConfig) {
+    if !active() {
+        return;
+    }
+    config.sensor_interval_ms = SoakConstant::INTERVAL_MS;
+    config.batch_max_records = SoakConstant::BATCH_MAX_RECORDS;
+    config.batch_max_age_s = SoakConstant::BATCH_MAX_AGE_S;
+    config.deep_sleep_interval_s = None;
+    sensors.include = hardware_profile::profile().sensors.iter().map(|sensor| String::from(*sensor)).collect();
+}
+
+// Downlink "soak" with "start" or "stop"
+pub fn handle_command(command: &ServerCommandResponseDTO) -> Option<Result<(), TransportError>> {
+    if command.command != SoakConstant::COMMAND {
+        return None;
+    }
+    let argument = command.argument.as_deref().unwrap_or("");
+    Some(match argument.trim() {
+        "start" => {
+            start();
+            Ok(())
+        },
+        "stop" => {
+            stop();
+            Ok(())
+        },
+        _ => Err(TransportError::Rejected(format!("Unknown soak action: {}", argument))),
+    })
+}
+
+fn with_active(update: impl FnOnce(&mut SoakStatsDTO)) {
+    STATE.lock(|state| {
+        let mut state = state.borrow_mut();
+        if state.stats.active {
+            update(&mut state.stats);
+        }
+    })
+}
+
+fn persist() {
+    if let Err(error) = store(&snapshot()) {
+        warn!("Soak counters not saved: {}", error);
+    }
+}
+
+fn encode(stats: &SoakStatsDTO) -> Vec<u8> {
+    let mut record = Vec::with_capacity(RECORD_LENGTH);
+    let mut flags = 0;
+    if stats.active {
+        flags |= ACTIVE;
+    }
+    if stats.completed {
+        flags |= COMPLETED;
+    }
+    record.push(flags);
+    record.extend_from_slice(&stats.started_at.to_le_bytes());
+    record.extend_from_slice(&stats.elapsed_s.to_le_bytes());
+    let counters = [
+        stats.reads,
+        stats.read_errors,
+        stats.uploads,
+        stats.upload_errors,
+        stats.resets,
+        stats.watchdog_resets,
+        stats.brownout_resets,
+    ];
+    for counter in counters {
+        record.extend_from_slice(&counter.to_le_bytes());
+    }
+    record
+}
+
+fn decode(record: &[u8]) -> Option<SoakStatsDTO> {
+    if record.len() != RECORD_LENGTH {
+        return None;
+    }
+    let u64_at = |offset: usize| u64::from_le_bytes(record[offset..offset + 8].try_into().unwrap_or([0; 8]));
+    let u32_at = |index: usize| {
+        let offset = 17 + index * 4;
+        u32::from_le_bytes(record[offset..offset + 4].try_into().unwrap_or([0; 4]))
+    };
+    Some(SoakStatsDTO {
+        active: record[0] & ACTIVE != 0,
+        completed: record[0] & COMPLETED != 0,
+        started_at: u64_at(1),
+        elapsed_s: u64_at(9),
+        reads: u32_at(0),
+        read_errors: u32_at(1),
+        uploads: u32_at(2),
+        upload_errors: u32_at(3),
+        resets: u32_at(4),
+        watchdog_resets: u32_at(5),
+        brownout_resets: u32_at(6),
+    })
+}
+
+// None when no test has ever run
+fn load() -> Result<Option<SoakStatsDTO>, Box<dyn Error + Send + Sync>> {
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    Filesystem::mount_and_then(&mut storage, |fs| {
+        let mut bytes = [0u8; state_file::HEADER_BYTES + RECORD_LENGTH];
+        let read = fs
+            .open_file_and_then(&PathBuf::from(StorageConstant::SOAK_FILE), |file| file.read(&mut bytes))
+            .unwrap_or(0);
+        Ok(match state_file::decode(&bytes[..read], Some(RECORD_LENGTH)) {
+            Some((StorageConstant::SOAK_VERSION, record)) => decode(record),
+            other => state_file::unsupported(StorageConstant::SOAK_FILE, other),
+        })
+    })
+    .map_err(soak_error)
+}
+
+fn store(stats: &SoakStatsDTO) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    Filesystem::mount_and_then(&mut storage, |fs| {
+        fs.write(
+            &PathBuf::from(StorageConstant::SOAK_FILE),
+            &state_file::encode(StorageConstant::SOAK_VERSION, &encode(stats)),
+        )
+    })
+    .map_err(soak_error)
+}
+
+fn soak_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("Soak error: {:?}", error))
+}
@@ -0,0 +1,35 @@
+use embassy_time::{Duration, Timer};
+use esp_hal::gpio::Output;
+
+use crate::constants::status_led::StatusLedConstant;
+use crate::enums::boot_stage::BootStage;
+use crate::services::boot;
+
+// Blinks the boot stage: n short flashes then a pause, n being the stage
+// number (1 CONFIG_LOADED ... 5 TIME_SYNCED). Fast flicker while the boot
+// is stuck on an error, a single brief flash every few seconds once running
+pub async fn run(mut led: Output<'static>) -> ! {
+    loop {
+        let stage = boot::stage();
+        if boot::has_failed() {
+            led.toggle();
+            Timer::after(Duration::from_millis(StatusLedConstant::ERROR_TOGGLE_MS)).await;
+            continue;
+        }
+        let flashes = match stage {
+            BootStage::Running => 1,
+            stage => stage.index(),
+        };
+        for _ in 0..flashes {
+            led.set_high();
+            Timer::after(Duration::from_millis(StatusLedConstant::FLASH_MS)).await;
+            led.set_low();
+            Timer::after(Duration::from_millis(StatusLedConstant::FLASH_MS)).await;
+        }
+        let pause_ms = match stage {
+            BootStage::Running => StatusLedConstant::RUNNING_PAUSE_MS,
+            _ => StatusLedConstant::STAGE_PAUSE_MS,
+        };
+        Timer::after(Duration::from_millis(pause_ms)).await;
+    }
+}
@@ -0,0 +1,121 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_time::{with_timeout, Duration, Instant, Timer};
+use log::warn;
+
+use crate::abstractions::service::IService;
+use crate::constants::supervisor::SupervisorConstant;
+use crate::dtos::telemetry::heartbeat::HeartbeatDTO;
+use crate::dtos::telemetry::service_health::ServiceHealthDTO;
+use crate::enums::service_state::ServiceState;
+
+// Runs every long-running service as a loop of `IService::run` calls, one
+// call per cycle (a sensing cycle, a heartbeat, a connection check) started
+// every `period`. A run that returns an error counts as crashed and one that outlives its timeout
+// as wedged; it is dropped, which cancels it, and run again after an
+// exponential backoff. Kept in a `StaticCell` and shared with one task per
+// service, each awaiting `supervise`
+pub struct ServiceSupervisor {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    services: Mutex<CriticalSectionRawMutex, RefCell<BTreeMap<String, ServiceHealthDTO>>>,
+}
+
+impl ServiceSupervisor {
+    pub fn new(urn: String, device_urn: String, location_urn: String) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            services: Mutex::new(RefCell::new(BTreeMap::new())),
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // `timeout` bounds a single run, including any waiting it does. A
+    // service whose run waits for its own input, e.g. a reader polling for
+    // a card, can take a `period` of zero; the supervisor still yields
+    // `MIN_PERIOD_MS` between runs so one that returns at once cannot
+    // starve the executor
+    pub async fn supervise<S: IService>(&self, name: &str, service: &mut S, period: Duration, timeout: Duration) -> ! {
+        self.update(name, |health| health.state = ServiceState::Starting);
+        let period = period.max(Duration::from_millis(SupervisorConstant::MIN_PERIOD_MS));
+        let mut backoff_ms = SupervisorConstant::INITIAL_BACKOFF_MS;
+        loop {
+            let started = Instant::now();
+            if self.run_once(name, service, timeout, &mut backoff_ms).await.is_some() {
+                Timer::at(started + period).await;
+            }
+        }
+    }
+
+    // One supervised run, for a service that paces itself instead of running
+    // every `period`, e.g. sensing on the schedule. A failed run is recorded
+    // and the backoff slept out before returning None; `backoff_ms` starts at
+    // `INITIAL_BACKOFF_MS` and is kept by the caller between runs
+    pub async fn run_once<S: IService>(
+        &self,
+        name: &str,
+        service: &mut S,
+        timeout: Duration,
+        backoff_ms: &mut u64,
+    ) -> Option<S::Response> {
+        let (state, error) = match with_timeout(timeout, service.run()).await {
+            Ok(Ok(response)) => {
+                self.update(name, |health| health.state = ServiceState::Running);
+                *backoff_ms = SupervisorConstant::INITIAL_BACKOFF_MS;
+                return Some(response);
+            },
+            Ok(Err(error)) => (ServiceState::Failed, format!("{}", error)),
+            Err(_) => (ServiceState::Wedged, format!("No result within {} ms", timeout.as_millis())),
+        };
+        warn!("Service {} {}: {}, restarting in {} ms", name, state.as_str(), error, backoff_ms);
+        self.update(name, |health| {
+            health.state = state;
+            health.restarts = health.restarts.saturating_add(1);
+            health.last_error = Some(error);
+        });
+        Timer::after(Duration::from_millis(*backoff_ms)).await;
+        *backoff_ms = (*backoff_ms * 2).min(SupervisorConstant::MAX_BACKOFF_MS);
+        None
+    }
+
+    pub fn health(&self) -> Vec<ServiceHealthDTO> {
+        self.services.lock(|services| services.borrow().values().cloned().collect())
+    }
+
+    pub fn report(&self, heartbeat: &mut HeartbeatDTO) {
+        heartbeat.services = self.health();
+    }
+
+    fn update(&self, name: &str, f: impl FnOnce(&mut ServiceHealthDTO)) {
+        self.services.lock(|services| {
+            let mut services = services.borrow_mut();
+            let health = services.entry(String::from(name)).or_insert_with(|| ServiceHealthDTO {
+                name: String::from(name),
+                state: ServiceState::Starting,
+                restarts: 0,
+                last_error: None,
+            });
+            f(health);
+        })
+    }
+}
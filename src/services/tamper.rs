@@ -0,0 +1,268 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::error::Error;
+use core::fmt::Debug;
+
+use embassy_time::Instant;
+use littlefs2::fs::Filesystem;
+use littlefs2::path::PathBuf;
+use log::{info, warn};
+
+use crate::constants::storage::StorageConstant;
+use crate::constants::tamper::TamperConstant;
+use crate::dtos::configurations::tamper::TamperConfigDTO;
+use crate::dtos::event::envelope::EventEnvelopeDTO;
+use crate::dtos::response::server::command::ServerCommandResponseDTO;
+use crate::enums::event_kind::EventKind;
+use crate::enums::transport_error::TransportError;
+use crate::enums::value::Value;
+use crate::services::{clock, message_id};
+use crate::utilities::flash_partition::FlashPartition;
+use crate::utilities::state_file;
+
+struct Armed {
+    // Monotonic seconds; nothing is reported before `from_s`
+    from_s: u64,
+    // End of the window in unix seconds, or in monotonic seconds when armed
+    // before the clock was set
+    until_unix_s: Option<u64>,
+    until_s: Option<u64>,
+    // Gravity direction once settled, the reference for tilt
+    reference: Option<(f32, f32, f32)>,
+    light_baseline: Option<f32>,
+    // The light is over the baseline, the lid is still open
+    light_over: bool,
+}
+
+// Raises a tamper event when the accelerometer sees the enclosure struck or
+// moved, or the BH1750 sees a sudden jump in light inside it (the lid
+// opened). Only while armed through the `arm_tamper` downlink, so service
+// visits can disarm it first. Arming is kept in flash, a reboot or a pulled
+// battery does not disarm the device
+pub struct TamperService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    config: TamperConfigDTO,
+    armed: Option<Armed>,
+    // Monotonic seconds of the last event per cause
+    last_event_s: BTreeMap<&'static str, u64>,
+}
+
+impl TamperService {
+    pub fn new(urn: String, device_urn: String, location_urn: String, config: TamperConfigDTO) -> Self {
+        // Armed again right away, the references are learned anew
+        let armed = match load() {
+            Ok(Some(until_unix_s)) => {
+                info!("Tamper detection still armed");
+                Some(Armed {
+                    from_s: Instant::now().as_secs(),
+                    until_unix_s: (until_unix_s != 0).then_some(until_unix_s),
+                    until_s: None,
+                    reference: None,
+                    light_baseline: None,
+                    light_over: false,
+                })
+            },
+            Ok(None) => None,
+            Err(error) => {
+                warn!("Failed to load tamper arming, starting disarmed: {}", error);
+                None
+            },
+        };
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            config: config,
+            armed: armed,
+            last_event_s: BTreeMap::new(),
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.armed.as_ref().is_some_and(|armed| {
+            let now_s = Instant::now().as_secs();
+            // A window in unix seconds cannot end before the clock is set
+            let before_unix = match (armed.until_unix_s, clock::now()) {
+                (Some(until_unix_s), Some(now_unix_s)) => now_unix_s < until_unix_s,
+                _ => true,
+            };
+            now_s >= armed.from_s && armed.until_s.map_or(true, |until_s| now_s < until_s) && before_unix
+        })
+    }
+
+    // Downlink "arm_tamper" with an optional window in seconds, open-ended
+    // without, and "disarm_tamper"
+    pub fn handle_command(&mut self, command: &ServerCommandResponseDTO) -> Option<Result<(), TransportError>> {
+        if command.command == TamperConstant::DISARM_COMMAND {
+            info!("Tamper detection disarmed");
+            self.armed = None;
+            if let Err(error) = clear() {
+                warn!("Failed to clear tamper arming: {}", error);
+            }
+            return Some(Ok(()));
+        }
+        if command.command != TamperConstant::ARM_COMMAND {
+            return None;
+        }
+        let window_s = match command.argument.as_deref().map(|value| value.trim().parse::<u64>()) {
+            None => None,
+            Some(Ok(window_s)) => Some(window_s),
+            Some(Err(_)) => {
+                return Some(Err(TransportError::Rejected(String::from("Expected the arming window in seconds"))));
+            },
+        };
+        let from_s = Instant::now().as_secs() + TamperConstant::ARM_DELAY_S;
+        match window_s {
+            Some(window_s) => info!("Tamper detection armed for {} s in {} s", window_s, TamperConstant::ARM_DELAY_S),
+            None => info!("Tamper detection armed in {} s", TamperConstant::ARM_DELAY_S),
+        }
+        let until_unix_s = match (window_s, clock::now()) {
+            (Some(window_s), Some(now_unix_s)) => Some(now_unix_s + TamperConstant::ARM_DELAY_S + window_s),
+            _ => None,
+        };
+        self.armed = Some(Armed {
+            from_s: from_s,
+            until_unix_s: until_unix_s,
+            until_s: window_s.filter(|_| until_unix_s.is_none()).map(|window_s| from_s + window_s),
+            reference: None,
+            light_baseline: None,
+            light_over: false,
+        });
+        // A window that only the monotonic clock knows the end of cannot
+        // outlive a reboot, so it is not kept
+        let persisted = match (window_s, until_unix_s) {
+            (None, _) => store(0),
+            (Some(_), Some(until_unix_s)) => store(until_unix_s),
+            (Some(_), None) => Ok(()),
+        };
+        if let Err(error) = persisted {
+            warn!("Failed to persist tamper arming: {}", error);
+        }
+        Some(Ok(()))
+    }
+
+    // Feed every accelerometer sample, in m/s²
+    pub fn update_acceleration(&mut self, x: f32, y: f32, z: f32) -> Option<EventEnvelopeDTO> {
+        if !self.is_armed() {
+            return None;
+        }
+        let magnitude_squared = x * x + y * y + z * z;
+        let gravity = TamperConstant::GRAVITY_MS2;
+        // |a| outside [g - shock, g + shock], compared squared
+        let low = (gravity - self.config.shock_ms2).max(0.0);
+        let high = gravity + self.config.shock_ms2;
+        if magnitude_squared < low * low || magnitude_squared > high * high {
+            return self.event(TamperConstant::STRUCK, format!("({:.1}, {:.1}, {:.1}) m/s²", x, y, z));
+        }
+        let armed = self.armed.as_mut()?;
+        let (rx, ry, rz) = *armed.reference.get_or_insert((x, y, z));
+        let offset_squared = (x - rx) * (x - rx) + (y - ry) * (y - ry) + (z - rz) * (z - rz);
+        if offset_squared > self.config.tilt_ms2 * self.config.tilt_ms2 {
+            // The new position is the reference from now on
+            armed.reference = Some((x, y, z));
+            return self.event(TamperConstant::MOVED, format!("({:.1}, {:.1}, {:.1}) m/s²", x, y, z));
+        }
+        None
+    }
+
+    // Feed every BH1750 reading
+    pub fn update_lux(&mut self, lux: f32) -> Option<EventEnvelopeDTO> {
+        if !self.is_armed() {
+            return None;
+        }
+        let armed = self.armed.as_mut()?;
+        let baseline = *armed.light_baseline.get_or_insert(lux);
+        // The baseline is held while the lid is open, one opening is one
+        // event however long it stays open
+        if lux - baseline > self.config.light_lux {
+            if armed.light_over {
+                return None;
+            }
+            armed.light_over = true;
+            return self.event(TamperConstant::OPENED, format!("{:.0} lux over {:.0}", lux, baseline));
+        }
+        armed.light_over = false;
+        let weight = TamperConstant::LIGHT_BASELINE_WEIGHT;
+        armed.light_baseline = Some(baseline + (lux - baseline) * weight);
+        None
+    }
+
+    fn event(&mut self, cause: &'static str, reading: String) -> Option<EventEnvelopeDTO> {
+        let now_s = Instant::now().as_secs();
+        if self.last_event_s.get(cause).is_some_and(|last_s| now_s - last_s < TamperConstant::HOLDOFF_S) {
+            return None;
+        }
+        self.last_event_s.insert(cause, now_s);
+        warn!("Tamper detected: {} ({})", cause, reading);
+        let mut detail = BTreeMap::new();
+        detail.insert(TamperConstant::CAUSE.to_string(), Value::String(cause.to_string()));
+        detail.insert(TamperConstant::READING.to_string(), Value::String(reading));
+        let (timestamp, relative_ms_since_boot) = clock::stamp();
+        Some(EventEnvelopeDTO {
+            id: message_id::next(),
+            device_urn: self.device_urn.clone(),
+            location_urn: self.location_urn.clone(),
+            sensor_urn: self.urn.clone(),
+            timestamp: timestamp,
+            relative_ms_since_boot: relative_ms_since_boot,
+            kind: EventKind::Tampered,
+            detail: detail,
+        })
+    }
+}
+
+// Unix seconds the arming ends at, 0 for open-ended; None when disarmed
+fn load() -> Result<Option<u64>, Box<dyn Error + Send + Sync>> {
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    Filesystem::mount_and_then(&mut storage, |fs| {
+        let mut bytes = [0u8; state_file::HEADER_BYTES + 8];
+        let read = fs
+            .open_file_and_then(&PathBuf::from(StorageConstant::TAMPER_FILE), |file| file.read(&mut bytes))
+            .unwrap_or(0);
+        Ok(match state_file::decode(&bytes[..read], Some(8)) {
+            Some((StorageConstant::TAMPER_VERSION, body)) => body.try_into().ok().map(u64::from_le_bytes),
+            other => state_file::unsupported(StorageConstant::TAMPER_FILE, other),
+        })
+    })
+    .map_err(tamper_error)
+}
+
+fn store(until_unix_s: u64) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    Filesystem::mount_and_then(&mut storage, |fs| {
+        fs.write(
+            &PathBuf::from(StorageConstant::TAMPER_FILE),
+            &state_file::encode(StorageConstant::TAMPER_VERSION, &until_unix_s.to_le_bytes()),
+        )
+    })
+    .map_err(tamper_error)
+}
+
+fn clear() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut storage = FlashPartition::new(StorageConstant::QUEUE_PARTITION_OFFSET);
+    Filesystem::mount_and_then(&mut storage, |fs| match fs.remove(&PathBuf::from(StorageConstant::TAMPER_FILE)) {
+        Err(littlefs2::io::Error::NoSuchEntry) => Ok(()),
+        result => result,
+    })
+    .map_err(tamper_error)
+}
+
+fn tamper_error<E: Debug>(error: E) -> Box<dyn Error + Send + Sync> {
+    Box::from(format!("Tamper error: {:?}", error))
+}
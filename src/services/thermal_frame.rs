@@ -0,0 +1,105 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use embassy_time::Instant;
+use log::info;
+
+use crate::constants::thermal::ThermalConstant;
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::dtos::measurement::sensor::amg8833::AMG8833SensorMeasurement;
+use crate::dtos::transport::ack::TransportAck;
+use crate::enums::transport_error::TransportError;
+use crate::services::clock;
+use crate::services::envelope;
+use crate::services::http_client::MultipartPart;
+use crate::services::http_transport::HttpTransportService;
+use crate::utilities::thermal;
+
+// Splits a thermal camera into cheap telemetry and the occasional full
+// frame: every reading is reduced to its summary for the regular uplink,
+// while whole frames are only captured when the hottest pixel crosses the
+// alert limit, or every `frame_interval_s` if set, and posted to the
+// uploads endpoint as a multipart file
+pub struct ThermalFrameService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    alert_c: f32,
+    frame_interval_s: u64,
+    // Monotonic ms of the last alert and of the last captured frame
+    last_alert_ms: Option<u64>,
+    last_frame_ms: Option<u64>,
+}
+
+impl ThermalFrameService {
+    pub fn new(urn: String, device_urn: String, location_urn: String, alert_c: f32, frame_interval_s: u64) -> Self {
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            alert_c: alert_c,
+            frame_interval_s: frame_interval_s,
+            last_alert_ms: None,
+            last_frame_ms: None,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // What goes into regular telemetry, the frame itself stays out
+    pub fn summary(&self, measurement: &AMG8833SensorMeasurement) -> MeasurementEnvelopeDTO {
+        envelope::measurement(&self.device_urn, &self.location_urn, BTreeMap::from(measurement))
+    }
+
+    // The encoded frame when this reading should be uploaded
+    pub fn capture(&mut self, measurement: &AMG8833SensorMeasurement) -> Option<Vec<u8>> {
+        let now_ms = Instant::now().as_millis();
+        let alert = measurement.max_c >= self.alert_c
+            && self
+                .last_alert_ms
+                .map_or(true, |last_ms| now_ms - last_ms >= ThermalConstant::ALERT_HOLDOFF_S * 1000);
+        let due = self.frame_interval_s > 0
+            && self
+                .last_frame_ms
+                .map_or(true, |last_ms| now_ms - last_ms >= self.frame_interval_s * 1000);
+        if !alert && !due {
+            return None;
+        }
+        if alert {
+            info!("Thermal alert: {} °C at pixel {}, capturing frame", measurement.max_c, measurement.hotspot);
+            self.last_alert_ms = Some(now_ms);
+        }
+        self.last_frame_ms = Some(now_ms);
+        Some(thermal::encode(ThermalConstant::ROWS, ThermalConstant::COLUMNS, &measurement.frame))
+    }
+
+    pub async fn upload(
+        &self,
+        transport: &mut HttpTransportService,
+        frame: &[u8],
+    ) -> Result<TransportAck, TransportError> {
+        let file_name = format!("thermal-{}.bin", clock::now().unwrap_or(0));
+        let part = MultipartPart::new(
+            ThermalConstant::FRAME_FIELD,
+            &file_name,
+            ThermalConstant::FRAME_CONTENT_TYPE,
+            frame.len(),
+        );
+        let mut source = frame;
+        transport
+            .upload(&mut source, frame.len(), ThermalConstant::FRAME_CONTENT_TYPE, Some(part))
+            .await
+    }
+}
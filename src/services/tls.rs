@@ -0,0 +1,191 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::error::Error;
+use core::ffi::CStr;
+
+use embassy_net::tcp::TcpSocket;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+use esp_mbedtls::asynch::Session;
+use esp_mbedtls::{Certificates, Mode, TlsReference, TlsVersion, X509};
+use log::{info, warn};
+
+use crate::constants::secret::SecretConstant;
+use crate::dtos::response::server::command::ServerCommandResponseDTO;
+use crate::enums::transport_error::TransportError;
+use crate::services::secret_store::SecretStoreService;
+
+// PEM blobs as read from the secret store, NUL terminated for mbedtls
+#[derive(Debug, Clone, Default)]
+pub struct TlsCredentials {
+    pub ca_chain: Option<Vec<u8>>,
+    pub client_cert: Option<Vec<u8>>,
+    pub client_key: Option<Vec<u8>>,
+}
+
+struct TlsState {
+    reference: TlsReference<'static>,
+    credentials: TlsCredentials,
+}
+
+// Shared by every TLS capable transport so a rotation applies to the next
+// connection each of them opens
+static TLS: Mutex<CriticalSectionRawMutex, RefCell<Option<TlsState>>> = Mutex::new(RefCell::new(None));
+
+pub fn init(reference: TlsReference<'static>, secrets: &mut SecretStoreService) {
+    let credentials = load(secrets);
+    if credentials.ca_chain.is_none() {
+        warn!("No CA chain provisioned, TLS connections will be refused");
+    }
+    if credentials.client_cert.is_some() != credentials.client_key.is_some() {
+        warn!("Client certificate and key must be provisioned together, mTLS disabled");
+    }
+    TLS.lock(|state| {
+        *state.borrow_mut() = Some(TlsState {
+            reference: reference,
+            credentials: credentials,
+        })
+    });
+}
+
+pub fn credentials() -> Option<TlsCredentials> {
+    TLS.lock(|state| state.borrow().as_ref().map(|state| state.credentials.clone()))
+}
+
+// Writes the new pair next to the current one and then flips the slot
+// marker, a single write, so a reboot mid-rotation comes back with either
+// the old pair or the new one and never a certificate without its key
+pub fn rotate(secrets: &mut SecretStoreService, cert_pem: &str, key_pem: &str) -> Result<(), TransportError> {
+    let cert = pem(cert_pem.as_bytes());
+    let key = pem(key_pem.as_bytes());
+    // Reject garbage before it replaces a working certificate
+    X509::pem(&cert).map_err(|_| TransportError::Rejected(String::from("Invalid client certificate")))?;
+    X509::pem(&key).map_err(|_| TransportError::Rejected(String::from("Invalid client key")))?;
+
+    // Unknown slot means the pair in use is unknown, nothing is overwritten
+    let next = !slot_b(secrets).map_err(|error| TransportError::Io(error.to_string()))?;
+    let (cert_name, key_name) = client_names(next);
+    secrets
+        .put(cert_name, cert_pem.as_bytes())
+        .and_then(|_| secrets.put(key_name, key_pem.as_bytes()))
+        .and_then(|_| secrets.put(SecretConstant::TLS_CLIENT_SLOT, if next { b"b" } else { b"a" }))
+        .map_err(|error| TransportError::Io(error.to_string()))?;
+    TLS.lock(|state| {
+        if let Some(state) = state.borrow_mut().as_mut() {
+            state.credentials.client_cert = Some(cert);
+            state.credentials.client_key = Some(key);
+        }
+    });
+    info!("Rotated TLS client certificate");
+    Ok(())
+}
+
+// Downlink "rotate_client_cert", the argument carries the certificate, or
+// the certificate chain leaf first, followed by the private key, all PEM
+pub fn handle_command(secrets: &mut SecretStoreService, command: &ServerCommandResponseDTO) -> Option<Result<(), TransportError>> {
+    if command.command != "rotate_client_cert" {
+        return None;
+    }
+    let argument = command.argument.as_deref().unwrap_or("");
+    Some(match private_key_at(argument) {
+        Some(index) if argument[..index].contains("-----BEGIN CERTIFICATE-----") => {
+            rotate(secrets, &argument[..index], &argument[index..])
+        },
+        _ => Err(TransportError::Rejected(String::from("Expected a PEM certificate followed by a PEM key"))),
+    })
+}
+
+// Wraps an already connected socket, presenting the client certificate when
+// one is provisioned. `credentials` comes from `credentials()`
+pub async fn connect<'a>(
+    socket: TcpSocket<'a>,
+    host: &str,
+    credentials: &'a TlsCredentials,
+) -> Result<Session<'a, TcpSocket<'a>>, TransportError> {
+    let reference = TLS
+        .lock(|state| state.borrow().as_ref().map(|state| state.reference))
+        .ok_or_else(|| TransportError::Io(String::from("TLS is not initialised")))?;
+    let server_name = pem(host.as_bytes());
+    let server_name = CStr::from_bytes_with_nul(&server_name).map_err(tls_error)?;
+    let x509 = |value: &'a Option<Vec<u8>>| value.as_deref().and_then(|value| X509::pem(value).ok());
+    // Without a CA mbedtls would accept any server certificate
+    let ca_chain = x509(&credentials.ca_chain)
+        .ok_or_else(|| TransportError::Rejected(String::from("No CA chain provisioned to verify the server")))?;
+    let (certificate, private_key) = match (x509(&credentials.client_cert), x509(&credentials.client_key)) {
+        (Some(certificate), Some(private_key)) => (Some(certificate), Some(private_key)),
+        _ => (None, None),
+    };
+
+    let mut session = Session::new(
+        socket,
+        Mode::Client { servername: server_name },
+        TlsVersion::Tls1_2,
+        Certificates {
+            ca_chain: Some(ca_chain),
+            certificate: certificate,
+            private_key: private_key,
+            password: None,
+        },
+        reference,
+    )
+    .map_err(tls_error)?;
+    session.connect().await.map_err(tls_error)?;
+    Ok(session)
+}
+
+fn load(secrets: &mut SecretStoreService) -> TlsCredentials {
+    let slot_b = slot_b(secrets).unwrap_or_else(|error| {
+        warn!("Could not read the TLS client slot: {:?}", error);
+        false
+    });
+    let (cert_name, key_name) = client_names(slot_b);
+    let mut load = |name: &str| match secrets.get(name) {
+        Ok(value) => value.map(|value| pem(&value)),
+        Err(error) => {
+            warn!("Could not read {} from the secret store: {:?}", name, error);
+            None
+        },
+    };
+    TlsCredentials {
+        ca_chain: load(SecretConstant::TLS_CA_CHAIN),
+        client_cert: load(cert_name),
+        client_key: load(key_name),
+    }
+}
+
+fn slot_b(secrets: &mut SecretStoreService) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    Ok(secrets.get(SecretConstant::TLS_CLIENT_SLOT)?.as_deref() == Some(b"b".as_slice()))
+}
+
+fn client_names(slot_b: bool) -> (&'static str, &'static str) {
+    if slot_b {
+        (SecretConstant::TLS_CLIENT_CERT_B, SecretConstant::TLS_CLIENT_KEY_B)
+    } else {
+        (SecretConstant::TLS_CLIENT_CERT, SecretConstant::TLS_CLIENT_KEY)
+    }
+}
+
+// Start of the first PEM block whose label names a private key ("PRIVATE
+// KEY", "EC PRIVATE KEY", "RSA PRIVATE KEY"..), the certificates before it
+// may be a whole chain
+fn private_key_at(value: &str) -> Option<usize> {
+    value
+        .match_indices("-----BEGIN ")
+        .map(|(index, _)| index)
+        .find(|index| value[*index..].lines().next().is_some_and(|line| line.contains("PRIVATE KEY-----")))
+}
+
+fn pem(value: &[u8]) -> Vec<u8> {
+    let mut bytes = value.to_vec();
+    if bytes.last() != Some(&0) {
+        bytes.push(0);
+    }
+    bytes
+}
+
+fn tls_error<E: core::fmt::Debug>(error: E) -> TransportError {
+    TransportError::Io(format!("TLS error: {:?}", error))
+}
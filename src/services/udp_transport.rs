@@ -0,0 +1,121 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use embassy_net::udp::{PacketMetadata, UdpSocket};
+use embassy_net::{IpEndpoint, Stack};
+use embassy_time::{with_timeout, Duration};
+use static_cell::StaticCell;
+
+use crate::abstractions::transport::ITransport;
+use crate::constants::udp::UdpConstant;
+use crate::dtos::transport::ack::TransportAck;
+use crate::enums::transport_error::TransportError;
+use crate::enums::uplink::Uplink;
+
+// Streams datagrams to a LAN collector for high-rate sensors. Each datagram
+// carries a big-endian u32 sequence number; with `ack` set the collector must
+// echo those four bytes back, otherwise sends are fire-and-forget
+pub struct UdpTransportService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    socket: UdpSocket<'static>,
+    collector: IpEndpoint,
+    ack: bool,
+    sequence: u32,
+}
+
+impl UdpTransportService {
+    // The socket buffers are static, so only one instance can exist
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        stack: Stack<'static>,
+        collector: IpEndpoint,
+        ack: bool,
+    ) -> Result<Self, TransportError> {
+        static RX_META: StaticCell<[PacketMetadata; 4]> = StaticCell::new();
+        static TX_META: StaticCell<[PacketMetadata; 4]> = StaticCell::new();
+        static RX_BUFFER: StaticCell<[u8; UdpConstant::BUFFER_BYTES]> = StaticCell::new();
+        static TX_BUFFER: StaticCell<[u8; UdpConstant::BUFFER_BYTES]> = StaticCell::new();
+
+        let mut socket = UdpSocket::new(
+            stack,
+            RX_META.init([PacketMetadata::EMPTY; 4]),
+            RX_BUFFER.init([0; UdpConstant::BUFFER_BYTES]),
+            TX_META.init([PacketMetadata::EMPTY; 4]),
+            TX_BUFFER.init([0; UdpConstant::BUFFER_BYTES]),
+        );
+        socket
+            .bind(UdpConstant::LOCAL_PORT)
+            .map_err(|_| TransportError::Unavailable)?;
+        Ok(Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            socket: socket,
+            collector: collector,
+            ack: ack,
+            sequence: 0,
+        })
+    }
+
+    async fn await_ack(&mut self, sequence: u32) -> bool {
+        let mut reply = [0u8; UdpConstant::HEADER_BYTES];
+        let timeout = Duration::from_millis(UdpConstant::ACK_TIMEOUT_MS);
+        loop {
+            match with_timeout(timeout, self.socket.recv_from(&mut reply)).await {
+                Ok(Ok((read, _))) if read == UdpConstant::HEADER_BYTES => {
+                    // Late acks for earlier datagrams are skipped
+                    if u32::from_be_bytes(reply) == sequence {
+                        return true;
+                    }
+                },
+                Ok(_) => continue,
+                Err(_) => return false,
+            }
+        }
+    }
+}
+
+impl ITransport for UdpTransportService {
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    async fn send(&mut self, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        if payload.len() + UdpConstant::HEADER_BYTES > UdpConstant::BUFFER_BYTES {
+            return Err(TransportError::PayloadTooLarge(payload.len()));
+        }
+        self.sequence = self.sequence.wrapping_add(1);
+        let mut datagram = Vec::with_capacity(UdpConstant::HEADER_BYTES + payload.len());
+        datagram.extend_from_slice(&self.sequence.to_be_bytes());
+        datagram.extend_from_slice(payload);
+
+        let attempts = if self.ack { 1 + UdpConstant::ACK_RETRIES } else { 1 };
+        for _ in 0..attempts {
+            self.socket
+                .send_to(&datagram, self.collector)
+                .await
+                .map_err(|_| TransportError::Unavailable)?;
+            if !self.ack || self.await_ack(self.sequence).await {
+                return Ok(TransportAck {
+                    uplink: Uplink::Udp,
+                    code: None,
+                    bytes: payload.len(),
+                    cursor: None,
+                });
+            }
+        }
+        Err(TransportError::Timeout)
+    }
+}
@@ -0,0 +1,110 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use log::warn;
+
+use crate::abstractions::transport::ITransport;
+use crate::dtos::transport::ack::TransportAck;
+use crate::enums::transport_error::TransportError;
+use crate::services::http_transport::HttpTransportService;
+use crate::services::mqtt_transport::MqttTransportService;
+
+// The transports `Config.uplinks` can name that only need an IP interface
+pub enum IpTransport {
+    Http(HttpTransportService),
+    Mqtt(MqttTransportService),
+}
+
+impl ITransport for IpTransport {
+    fn urn(&self) -> String {
+        match self {
+            IpTransport::Http(transport) => transport.urn(),
+            IpTransport::Mqtt(transport) => transport.urn(),
+        }
+    }
+
+    fn device_urn(&self) -> String {
+        match self {
+            IpTransport::Http(transport) => transport.device_urn(),
+            IpTransport::Mqtt(transport) => transport.device_urn(),
+        }
+    }
+
+    fn location_urn(&self) -> String {
+        match self {
+            IpTransport::Http(transport) => transport.location_urn(),
+            IpTransport::Mqtt(transport) => transport.location_urn(),
+        }
+    }
+
+    async fn send(&mut self, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        match self {
+            IpTransport::Http(transport) => transport.send(payload).await,
+            IpTransport::Mqtt(transport) => transport.send(payload).await,
+        }
+    }
+
+    async fn send_event(&mut self, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        match self {
+            IpTransport::Http(transport) => transport.send_event(payload).await,
+            IpTransport::Mqtt(transport) => transport.send_event(payload).await,
+        }
+    }
+}
+
+// One upload target reached over the uplinks in the order the config lists
+// them. `FailoverTransport` pairs two transports fixed at compile time; this
+// is the same policy for a chain the config picks at boot
+pub struct UplinkChain {
+    links: Vec<IpTransport>,
+}
+
+impl UplinkChain {
+    // None without any link, a target nothing can reach
+    pub fn new(links: Vec<IpTransport>) -> Option<Self> {
+        if links.is_empty() {
+            return None;
+        }
+        Some(Self { links: links })
+    }
+}
+
+impl ITransport for UplinkChain {
+    fn urn(&self) -> String {
+        self.links[0].urn()
+    }
+
+    fn device_urn(&self) -> String {
+        self.links[0].device_urn()
+    }
+
+    fn location_urn(&self) -> String {
+        self.links[0].location_urn()
+    }
+
+    async fn send(&mut self, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        let last = self.links.len() - 1;
+        for index in 0..last {
+            match self.links[index].send(payload).await {
+                Err(error) if error.is_retryable() => {
+                    warn!("{} failed ({}), falling back to {}", self.links[index].urn(), error, self.links[index + 1].urn());
+                },
+                result => return result,
+            }
+        }
+        self.links[last].send(payload).await
+    }
+
+    async fn send_event(&mut self, payload: &[u8]) -> Result<TransportAck, TransportError> {
+        let last = self.links.len() - 1;
+        for index in 0..last {
+            match self.links[index].send_event(payload).await {
+                Err(error) if error.is_retryable() => {
+                    warn!("{} failed ({}), falling back to {}", self.links[index].urn(), error, self.links[index + 1].urn());
+                },
+                result => return result,
+            }
+        }
+        self.links[last].send_event(payload).await
+    }
+}
@@ -0,0 +1,46 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+use embassy_time::Instant;
+
+use crate::dtos::telemetry::upload_stats::UploadStatsDTO;
+use crate::dtos::telemetry::upload_timing::UploadTimingDTO;
+
+// Timing and size of every successful upload per transport since boot, to
+// tune batching and to tell a slow DNS server from a slow TLS endpoint at a
+// site that uploads sluggishly. Served on /metrics and summed into the
+// device metrics
+static STATS: Mutex<CriticalSectionRawMutex, RefCell<BTreeMap<String, UploadStatsDTO>>> =
+    Mutex::new(RefCell::new(BTreeMap::new()));
+
+pub fn record(name: &str, timing: &UploadTimingDTO) {
+    STATS.lock(|stats| {
+        let mut stats = stats.borrow_mut();
+        let target = stats.entry(String::from(name)).or_insert_with(|| UploadStatsDTO {
+            name: String::from(name),
+            ..UploadStatsDTO::default()
+        });
+        target.uploads = target.uploads.saturating_add(1);
+        target.bytes = target.bytes.saturating_add(timing.bytes as u64);
+        target.total_ms = target.total_ms.saturating_add(timing.total_ms as u64);
+        target.max_total_ms = target.max_total_ms.max(timing.total_ms);
+        target.last = Some(*timing);
+    })
+}
+
+pub fn snapshot() -> Vec<UploadStatsDTO> {
+    STATS.lock(|stats| stats.borrow().values().cloned().collect())
+}
+
+// Milliseconds since `since`, which is then moved up to now so consecutive
+// calls time consecutive phases
+pub fn lap(since: &mut Instant) -> u32 {
+    let now = Instant::now();
+    let elapsed = (now - *since).as_millis();
+    *since = now;
+    elapsed.min(u32::MAX as u64) as u32
+}
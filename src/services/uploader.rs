@@ -0,0 +1,501 @@
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error;
+
+use embassy_time::{Duration, Instant};
+use log::warn;
+
+use crate::abstractions::queue::IQueue;
+use crate::abstractions::transport::ITransport;
+use crate::constants::storage::StorageConstant;
+use crate::constants::upload::UploadConstant;
+use crate::dtos::event::envelope::EventEnvelopeDTO;
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::dtos::telemetry::heartbeat::HeartbeatDTO;
+use crate::dtos::transport::ack::TransportAck;
+use crate::dtos::transport::target_metrics::TargetMetricsDTO;
+use crate::enums::payload_kind::PayloadKind;
+use crate::enums::priority::Priority;
+use crate::enums::transport_error::TransportError;
+use crate::services::{clock, message_id, self_heating, soak};
+use crate::utilities::json;
+
+// Persistent queue records carry their kind in a leading tag byte
+const RECORD_MEASUREMENT: u8 = b'M';
+const RECORD_EVENT: u8 = b'E';
+
+struct QueuedPayload {
+    kind: PayloadKind,
+    priority: Priority,
+    payload: Vec<u8>,
+    // Envelopes recorded before the clock was set, serialized into
+    // `payload` once their timestamps can be made absolute
+    unresolved: Option<UnresolvedEnvelopes>,
+}
+
+#[derive(Clone)]
+struct UnresolvedEnvelopes {
+    envelopes: Vec<MeasurementEnvelopeDTO>,
+    batch: bool,
+}
+
+// One upload destination with its own backlog, so a dead local server never
+// holds back delivery to the cloud and vice versa
+struct UploadTarget<T: ITransport> {
+    transport: T,
+    queue: VecDeque<QueuedPayload>,
+    metrics: TargetMetricsDTO,
+    // Set when the target asked to be left alone for a while
+    throttled_until: Option<Instant>,
+}
+
+// Serializes envelopes and fans them out to every configured transport,
+// keeping the sensing side unaware of the wire protocol
+pub struct UploaderService<T: ITransport> {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    targets: Vec<UploadTarget<T>>,
+    // Per target, how many records from the persistent queue's head it
+    // already acknowledged
+    forwarded: Vec<usize>,
+    forwarded_record: Option<Vec<u8>>,
+}
+
+impl<T: ITransport> UploaderService<T> {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        transports: Vec<T>,
+    ) -> Self {
+        let targets = transports
+            .into_iter()
+            .map(|transport| UploadTarget {
+                transport: transport,
+                queue: VecDeque::with_capacity(UploadConstant::TARGET_QUEUE_DEPTH),
+                metrics: TargetMetricsDTO::default(),
+                throttled_until: None,
+            })
+            .collect::<Vec<_>>();
+        let forwarded = vec![0; targets.len()];
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            targets: targets,
+            forwarded: forwarded,
+            forwarded_record: None,
+        }
+    }
+
+    pub fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    pub fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    pub fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    // Metrics per target, keyed by the transport's URN
+    pub fn metrics(&self) -> Vec<(String, TargetMetricsDTO)> {
+        self.targets
+            .iter()
+            .map(|target| (target.transport.urn(), target.metrics))
+            .collect()
+    }
+
+    // Fills the uplink fields of a heartbeat from every target's queue
+    pub fn report(&self, heartbeat: &mut HeartbeatDTO) {
+        heartbeat.queue_depth = self.targets.iter().map(|target| target.queue.len()).sum();
+        let dropped = |priority: Priority| -> u32 {
+            self.targets.iter().map(|target| target.metrics.dropped[priority.index()]).sum()
+        };
+        heartbeat.dropped_periodic = dropped(Priority::Periodic);
+        heartbeat.dropped_state_change = dropped(Priority::StateChange);
+        heartbeat.dropped_alert = dropped(Priority::Alert);
+    }
+
+    // Returns how many targets are fully caught up after this envelope
+    pub async fn upload(&mut self, envelope: &MeasurementEnvelopeDTO, priority: Priority) -> usize {
+        self.enqueue_envelopes(vec![envelope.clone()], false, priority).await
+    }
+
+    // Sends several envelopes as one JSON array request per target
+    pub async fn upload_batch(&mut self, envelopes: &[MeasurementEnvelopeDTO], priority: Priority) -> usize {
+        self.enqueue_envelopes(envelopes.to_vec(), true, priority).await
+    }
+
+    // Events go to each transport's event endpoint at the priority of their
+    // kind, and stay queued until a target has acknowledged them
+    pub async fn upload_event(&mut self, event: &EventEnvelopeDTO) -> usize {
+        let event = resolve_event(event);
+        let payload = json::event_to_json(&event).into_bytes();
+        self.enqueue(payload, PayloadKind::Event, event.kind.priority(), None, event.timestamp).await
+    }
+
+    // Appends to the persistent queue instead of sending right away, for
+    // data that must survive a reboot until `forward` gets it acknowledged.
+    // Boot-relative stamps are resolved when possible; ones that cannot be
+    // are stored as they are, the boot count in the envelope id tells the
+    // server which boot they are relative to
+    pub fn persist(&self, queue: &mut dyn IQueue, envelope: &MeasurementEnvelopeDTO) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut envelopes = [envelope.clone()];
+        resolve_timestamps(&mut envelopes);
+        queue.push(&record(RECORD_MEASUREMENT, json::envelope_to_json(&envelopes[0]).as_bytes()))
+    }
+
+    pub fn persist_event(&self, queue: &mut dyn IQueue, event: &EventEnvelopeDTO) -> Result<(), Box<dyn Error + Send + Sync>> {
+        queue.push(&record(RECORD_EVENT, json::event_to_json(&resolve_event(event)).as_bytes()))
+    }
+
+    // Delivers the persistent queue oldest first with at-least-once
+    // semantics, one chunk per target and call: consecutive measurements go
+    // up as a single JSON array, events one at a time. Each target keeps its
+    // own cursor into the queue, so one that is unreachable falls behind
+    // without holding back the others. Records are only popped once every
+    // target acknowledged them (2xx, PUBACK) or refused them for good, and
+    // the popped head is the flash queue's persisted cursor, so a dropped
+    // connection costs at most the chunk in flight. A server that answers
+    // with a cursor, the id of the last envelope it stored, acknowledges
+    // only up to that one and the rest of the chunk goes again; uplinks
+    // without a response body acknowledge the whole chunk. A timeout is
+    // ambiguous, the server may have stored the data, so the chunk is resent
+    // and the envelope ids let the server drop the copies. Records stored
+    // before the clock was set are restamped on the way out once it is,
+    // if they are from this boot. Call it again between live uploads until
+    // it returns 0 to drain a long backlog without holding back fresh
+    // readings. Returns the most records a target got through this call
+    pub async fn forward(&mut self, queue: &mut dyn IQueue, now: u64) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let head = match queue.peek()? {
+            Some(head) => head,
+            None => return Ok(0),
+        };
+        // The queue may have dropped its oldest records since the last call
+        if self.forwarded_record.as_ref() != Some(&head) {
+            self.forwarded.iter_mut().for_each(|forwarded| *forwarded = 0);
+            self.forwarded_record = Some(head);
+        }
+
+        let mut progress = 0;
+        for (index, target) in self.targets.iter_mut().enumerate() {
+            if target.is_throttled() {
+                continue;
+            }
+            let from = self.forwarded[index];
+            let records = queue.peek_many(from, UploadConstant::FORWARD_CHUNK_RECORDS, UploadConstant::FORWARD_CHUNK_BYTES)?;
+            let kind = match records.first().and_then(|record| record.first()) {
+                None => continue,
+                Some(&RECORD_MEASUREMENT) => PayloadKind::Measurement,
+                Some(&RECORD_EVENT) => PayloadKind::Event,
+                Some(_) => {
+                    warn!("Skipping malformed queue record of {} bytes", records[0].len());
+                    self.forwarded[index] = from + 1;
+                    progress = progress.max(1);
+                    continue;
+                },
+            };
+            let length = match kind {
+                PayloadKind::Measurement => records
+                    .iter()
+                    .take_while(|record| record.first() == Some(&RECORD_MEASUREMENT))
+                    .count(),
+                PayloadKind::Event => 1,
+            };
+
+            let payload = chunk(kind, &restamp(&records[..length]));
+            match send(&mut target.transport, kind, &payload).await {
+                Ok(ack) => {
+                    target.metrics.successes += 1;
+                    target.metrics.last_success = Some(now);
+                    self.forwarded[index] = from + confirmed(&records[..length], ack.cursor.as_deref());
+                },
+                Err(error) if error.is_retryable() => {
+                    target.metrics.failures += 1;
+                    target.throttle(&error);
+                    warn!("Forwarding to {} failed: {}", target.transport.urn(), error);
+                },
+                Err(error) => {
+                    target.metrics.failures += 1;
+                    warn!("Forwarding to {} rejected: {}", target.transport.urn(), error);
+                    self.forwarded[index] = from + length;
+                },
+            }
+            progress = progress.max(self.forwarded[index] - from);
+        }
+
+        // What the slowest target still needs stays queued
+        let popped = self.forwarded.iter().copied().min().unwrap_or(0);
+        if popped > 0 {
+            queue.pop_many(popped)?;
+            self.forwarded.iter_mut().for_each(|forwarded| *forwarded -= popped);
+            self.forwarded_record = queue.peek()?;
+        }
+        Ok(progress)
+    }
+
+    async fn enqueue_envelopes(&mut self, mut envelopes: Vec<MeasurementEnvelopeDTO>, batch: bool, priority: Priority) -> usize {
+        let now = clock::now().unwrap_or(0);
+        if resolve_timestamps(&mut envelopes) {
+            let payload = serialize(&envelopes, batch);
+            return self.enqueue(payload, PayloadKind::Measurement, priority, None, now).await;
+        }
+        let unresolved = UnresolvedEnvelopes {
+            envelopes: envelopes,
+            batch: batch,
+        };
+        self.enqueue(Vec::new(), PayloadKind::Measurement, priority, Some(unresolved), now).await
+    }
+
+    async fn enqueue(
+        &mut self,
+        payload: Vec<u8>,
+        kind: PayloadKind,
+        priority: Priority,
+        unresolved: Option<UnresolvedEnvelopes>,
+        now: u64,
+    ) -> usize {
+        let mut delivered = 0;
+        for target in self.targets.iter_mut() {
+            if admit(target, kind, priority) {
+                target.queue.push_back(QueuedPayload {
+                    kind: kind,
+                    priority: priority,
+                    payload: payload.clone(),
+                    unresolved: unresolved.clone(),
+                });
+            }
+            if drain(target, now).await {
+                delivered += 1;
+            }
+        }
+        delivered
+    }
+}
+
+// Appends an envelope another node already serialized, one a mesh leaf sent
+// to this gateway, to the persistent queue for `forward` as it is
+pub fn persist_relayed(queue: &mut dyn IQueue, json: &[u8]) -> Result<(), Box<dyn Error + Send + Sync>> {
+    queue.push(&record(RECORD_MEASUREMENT, json))
+}
+
+impl<T: ITransport> UploadTarget<T> {
+    fn is_throttled(&mut self) -> bool {
+        match self.throttled_until {
+            Some(until) if Instant::now() < until => true,
+            _ => {
+                self.throttled_until = None;
+                false
+            },
+        }
+    }
+
+    // Holds the target back for as long as the server asked
+    fn throttle(&mut self, error: &TransportError) {
+        if let TransportError::Throttled(Some(seconds)) = error {
+            let seconds = (*seconds).min(UploadConstant::MAX_RETRY_AFTER_S);
+            self.throttled_until = Some(Instant::now() + Duration::from_secs(seconds));
+        }
+    }
+}
+
+// Thins the oldest records of a full persistent queue, see
+// `FlashQueueService::set_downsampler`: one in `QUEUE_DOWNSAMPLE_FACTOR`
+// measurements is kept and marked with the factor, events are all kept
+pub fn downsample(records: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+    let factor = StorageConstant::QUEUE_DOWNSAMPLE_FACTOR;
+    let mut measurements = 0;
+    let mut kept = Vec::with_capacity(records.len());
+    for record in records {
+        if record.first() != Some(&RECORD_MEASUREMENT) {
+            kept.push(record);
+            continue;
+        }
+        if measurements % factor == 0 {
+            let mut thinned = vec![RECORD_MEASUREMENT];
+            thinned.extend_from_slice(&json::downsampled(&record[1..], factor));
+            kept.push(thinned);
+        }
+        measurements += 1;
+    }
+    kept
+}
+
+// Makes room for an incoming payload when the queue is full by evicting the
+// oldest measurement of the lowest class below or equal to it. Events are
+// never evicted; an incoming event may instead overfill the queue by
+// `EVENT_QUEUE_EXTRA`. Returns false when the incoming payload itself is the
+// one to drop
+fn admit<T: ITransport>(target: &mut UploadTarget<T>, kind: PayloadKind, incoming: Priority) -> bool {
+    if target.queue.len() < UploadConstant::TARGET_QUEUE_DEPTH {
+        return true;
+    }
+    for class in Priority::ALL.iter().filter(|class| **class <= incoming) {
+        let evictable = |queued: &QueuedPayload| queued.kind == PayloadKind::Measurement && queued.priority == *class;
+        if let Some(index) = target.queue.iter().position(evictable) {
+            target.queue.remove(index);
+            target.metrics.dropped[class.index()] += 1;
+            return true;
+        }
+    }
+    if kind == PayloadKind::Event
+        && target.queue.len() < UploadConstant::TARGET_QUEUE_DEPTH + UploadConstant::EVENT_QUEUE_EXTRA
+    {
+        return true;
+    }
+    target.metrics.dropped[incoming.index()] += 1;
+    false
+}
+
+// Highest class first, oldest first within a class. Payloads still waiting
+// for the clock are held back until it is set
+fn next_index(queue: &VecDeque<QueuedPayload>) -> Option<usize> {
+    let synced = clock::is_set();
+    let ready = |queued: &&QueuedPayload| synced || queued.unresolved.is_none();
+    let highest = queue.iter().filter(ready).map(|queued| queued.priority).max()?;
+    queue.iter().position(|queued| ready(&queued) && queued.priority == highest)
+}
+
+// Sends the target's backlog in priority order, stopping at the first
+// retryable failure so ordering is preserved for the next attempt
+async fn drain<T: ITransport>(target: &mut UploadTarget<T>, now: u64) -> bool {
+    while let Some(index) = next_index(&target.queue).filter(|_| !target.is_throttled()) {
+        if let Some(mut unresolved) = target.queue[index].unresolved.take() {
+            resolve_timestamps(&mut unresolved.envelopes);
+            target.queue[index].payload = serialize(&unresolved.envelopes, unresolved.batch);
+        }
+        let queued = &target.queue[index];
+        match send(&mut target.transport, queued.kind, &queued.payload).await {
+            Ok(_) => {
+                target.queue.remove(index);
+                target.metrics.successes += 1;
+                target.metrics.last_success = Some(now);
+            },
+            Err(error) if error.is_retryable() => {
+                target.metrics.failures += 1;
+                target.throttle(&error);
+                warn!("Upload to {} failed: {}", target.transport.urn(), error);
+                break;
+            },
+            Err(error) => {
+                // The target will never accept this payload
+                target.queue.remove(index);
+                target.metrics.failures += 1;
+                warn!("Upload to {} rejected: {}", target.transport.urn(), error);
+            },
+        }
+    }
+    target.metrics.queued = target.queue.len();
+    target.queue.is_empty()
+}
+
+async fn send<T: ITransport>(transport: &mut T, kind: PayloadKind, payload: &[u8]) -> Result<TransportAck, TransportError> {
+    let started = Instant::now();
+    let result = match kind {
+        PayloadKind::Measurement => transport.send(payload).await,
+        PayloadKind::Event => transport.send_event(payload).await,
+    };
+    self_heating::record_radio_active(started.elapsed().as_millis());
+    soak::record_upload(result.is_ok());
+    result
+}
+
+// Rewrites boot-relative stamps into absolute time. Returns false while the
+// clock is unset and any envelope still needs it
+fn resolve_timestamps(envelopes: &mut [MeasurementEnvelopeDTO]) -> bool {
+    for envelope in envelopes.iter_mut() {
+        let relative_ms = match envelope.relative_ms_since_boot {
+            Some(relative_ms) => relative_ms,
+            None => continue,
+        };
+        match clock::at_boot_ms(relative_ms) {
+            Some(unix_ms) => {
+                envelope.timestamp = unix_ms / 1_000;
+                envelope.relative_ms_since_boot = None;
+            },
+            None => return false,
+        }
+    }
+    true
+}
+
+fn resolve_event(event: &EventEnvelopeDTO) -> EventEnvelopeDTO {
+    let mut event = event.clone();
+    if let Some(unix_ms) = event.relative_ms_since_boot.and_then(clock::at_boot_ms) {
+        event.timestamp = unix_ms / 1_000;
+        event.relative_ms_since_boot = None;
+    }
+    event
+}
+
+// Queue records keep the stamp they were stored with; ones from an earlier
+// boot stay relative, the boot count in their id tells the server which
+fn restamp(records: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    records
+        .iter()
+        .map(|queued| {
+            let json = &queued[1..];
+            let this_boot = json::id_of(json).is_some_and(message_id::is_this_boot);
+            json::relative_ms_of(json)
+                .filter(|_| this_boot)
+                .and_then(clock::at_boot_ms)
+                .and_then(|unix_ms| json::restamped(json, unix_ms / 1_000))
+                .map_or_else(|| queued.clone(), |json| record(queued[0], &json))
+        })
+        .collect()
+}
+
+fn serialize(envelopes: &[MeasurementEnvelopeDTO], batch: bool) -> Vec<u8> {
+    if batch {
+        json::envelopes_to_json_array(envelopes).into_bytes()
+    } else {
+        json::envelope_to_json(&envelopes[0]).into_bytes()
+    }
+}
+
+// Strips the tags off queue records, joining measurements into an array
+fn chunk(kind: PayloadKind, records: &[Vec<u8>]) -> Vec<u8> {
+    if kind == PayloadKind::Event || records.len() == 1 {
+        return records[0][1..].to_vec();
+    }
+    let mut payload = Vec::with_capacity(records.iter().map(|record| record.len() + 1).sum::<usize>() + 1);
+    payload.push(b'[');
+    for (index, record) in records.iter().enumerate() {
+        if index > 0 {
+            payload.push(b',');
+        }
+        payload.extend_from_slice(&record[1..]);
+    }
+    payload.push(b']');
+    payload
+}
+
+// How many of the `sent` records the server's cursor acknowledges, all of
+// them without one. A cursor outside the chunk acknowledges none
+fn confirmed(sent: &[Vec<u8>], cursor: Option<&str>) -> usize {
+    let cursor = match cursor {
+        Some(cursor) => cursor,
+        None => return sent.len(),
+    };
+    match sent.iter().position(|record| json::id_of(&record[1..]) == Some(cursor)) {
+        Some(position) => position + 1,
+        None => {
+            warn!("Server cursor {} is not in the chunk sent, resending it", cursor);
+            0
+        },
+    }
+}
+
+fn record(tag: u8, payload: &[u8]) -> Vec<u8> {
+    let mut record = Vec::with_capacity(1 + payload.len());
+    record.push(tag);
+    record.extend_from_slice(payload);
+    record
+}
@@ -0,0 +1,250 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::sync::atomic::{AtomicU16, AtomicU32, Ordering};
+
+use embassy_time::Instant;
+use esp_wifi::wifi::event::{EventExt, StaDisconnected};
+use esp_wifi::wifi::{
+    AccessPointInfo, AuthMethod, ClientConfiguration, Configuration, EapClientConfiguration, WifiController, WifiError,
+};
+use log::{info, warn};
+
+use crate::abstractions::service::IService;
+use crate::constants::secret::SecretConstant;
+use crate::constants::wifi::WifiConstant;
+use crate::dtos::configurations::wifi::{WifiCertificatesDTO, WifiProfileDTO};
+use crate::dtos::telemetry::heartbeat::HeartbeatDTO;
+use crate::dtos::telemetry::wifi_diagnostics::WifiDiagnosticsDTO;
+use crate::enums::eap_method::EapMethod;
+use crate::services::heartbeat;
+use crate::services::secret_store::SecretStoreService;
+
+// Written from the Wi-Fi event handler, 0 means no disconnect seen yet
+static LAST_DISCONNECT_REASON: AtomicU16 = AtomicU16::new(0);
+static DISCONNECTS: AtomicU32 = AtomicU32::new(0);
+
+// Keeps the station connected to the best known network: strongest visible
+// profile first, remaining profiles in configured order, and a roam to a
+// clearly stronger AP once the signal drops below `roam_rssi_dbm`. Run by
+// the supervisor every `WifiConstant::MAINTAIN_INTERVAL_S`
+pub struct WifiManagerService {
+    urn: String,
+    device_urn: String,
+    location_urn: String,
+    controller: WifiController<'static>,
+    profiles: Vec<WifiProfileDTO>,
+    certificates: WifiCertificatesDTO,
+    roam_rssi_dbm: i8,
+    current: Option<usize>,
+    diagnostics: WifiDiagnosticsDTO,
+}
+
+impl IService for WifiManagerService {
+    type Response = ();
+
+    fn urn(&self) -> String {
+        self.urn.clone()
+    }
+
+    fn device_urn(&self) -> String {
+        self.device_urn.clone()
+    }
+
+    fn location_urn(&self) -> String {
+        self.location_urn.clone()
+    }
+
+    async fn run(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let maintained = self.maintain().await;
+        heartbeat::update(|heartbeat| self.report(heartbeat));
+        maintained.map_err(|error| Box::from(format!("Wi-Fi maintenance failed: {:?}", error)))
+    }
+}
+
+impl WifiManagerService {
+    pub fn new(
+        urn: String,
+        device_urn: String,
+        location_urn: String,
+        controller: WifiController<'static>,
+        profiles: Vec<WifiProfileDTO>,
+        certificates: WifiCertificatesDTO,
+        roam_rssi_dbm: i8,
+    ) -> Self {
+        StaDisconnected::update_handler(|event| {
+            LAST_DISCONNECT_REASON.store(event.0.reason as u16, Ordering::Relaxed);
+            DISCONNECTS.fetch_add(1, Ordering::Relaxed);
+        });
+        Self {
+            urn: urn,
+            device_urn: device_urn,
+            location_urn: location_urn,
+            controller: controller,
+            profiles: profiles,
+            certificates: certificates,
+            roam_rssi_dbm: roam_rssi_dbm,
+            current: None,
+            diagnostics: WifiDiagnosticsDTO::default(),
+        }
+    }
+
+    pub fn current_ssid(&self) -> Option<&str> {
+        self.current.map(|index| self.profiles[index].ssid.as_str())
+    }
+
+    pub fn diagnostics(&self) -> WifiDiagnosticsDTO {
+        let reason = LAST_DISCONNECT_REASON.load(Ordering::Relaxed);
+        WifiDiagnosticsDTO {
+            last_disconnect_reason: if reason == 0 { None } else { Some(reason) },
+            disconnects: DISCONNECTS.load(Ordering::Relaxed),
+            ..self.diagnostics.clone()
+        }
+    }
+
+    pub fn report(&self, heartbeat: &mut HeartbeatDTO) {
+        heartbeat.wifi = Some(self.diagnostics());
+    }
+
+    pub async fn maintain(&mut self) -> Result<(), WifiError> {
+        if !matches!(self.controller.is_started(), Ok(true)) {
+            self.controller.start_async().await?;
+        }
+        if !matches!(self.controller.is_connected(), Ok(true)) {
+            self.current = None;
+            self.diagnostics.rssi_dbm = None;
+            return self.connect_best().await;
+        }
+
+        let rssi = self.controller.rssi()? as i8;
+        self.diagnostics.rssi_dbm = Some(rssi);
+        if rssi >= self.roam_rssi_dbm {
+            return Ok(());
+        }
+        let scan = self.scan().await?;
+        let better = self.ranked(&scan).into_iter().find(|(index, candidate_rssi)| {
+            Some(*index) != self.current
+                && candidate_rssi.map_or(false, |candidate| candidate >= rssi.saturating_add(WifiConstant::ROAM_HYSTERESIS_DB))
+        });
+        if let Some((index, candidate_rssi)) = better {
+            info!("Roaming from {:?} ({} dBm) to {} ({:?} dBm)", self.current_ssid(), rssi, self.profiles[index].ssid, candidate_rssi);
+            self.controller.disconnect_async().await?;
+            self.current = None;
+            if self.connect_profile(index, &scan).await.is_err() {
+                return self.connect_best().await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn connect_best(&mut self) -> Result<(), WifiError> {
+        let scan = self.scan().await.unwrap_or_default();
+        let mut last_error = WifiError::NotInitialized;
+        for (index, _) in self.ranked(&scan) {
+            match self.connect_profile(index, &scan).await {
+                Ok(()) => return Ok(()),
+                Err(error) => {
+                    warn!("Wi-Fi profile {} failed: {:?}", self.profiles[index].ssid, error);
+                    last_error = error;
+                },
+            }
+        }
+        Err(last_error)
+    }
+
+    async fn connect_profile(&mut self, index: usize, scan: &[AccessPointInfo]) -> Result<(), WifiError> {
+        let profile = &self.profiles[index];
+        let configuration = match &profile.enterprise {
+            None => Configuration::Client(ClientConfiguration {
+                ssid: profile.ssid.clone(),
+                password: profile.password.clone(),
+                ..Default::default()
+            }),
+            Some(enterprise) => Configuration::EapClient(EapClientConfiguration {
+                ssid: profile.ssid.clone(),
+                auth_method: AuthMethod::WPA2Enterprise,
+                identity: Some(enterprise.identity.clone()),
+                username: match enterprise.method {
+                    EapMethod::Peap => Some(enterprise.username.clone()),
+                    EapMethod::Tls => None,
+                },
+                password: match enterprise.method {
+                    EapMethod::Peap => Some(profile.password.clone()),
+                    EapMethod::Tls => None,
+                },
+                ca_cert: self.certificates.ca_cert,
+                certificate_and_key: match (enterprise.method, self.certificates.client_cert, self.certificates.client_key) {
+                    (EapMethod::Tls, Some(cert), Some(key)) => Some((cert, key, None)),
+                    _ => None,
+                },
+                ..Default::default()
+            }),
+        };
+        self.controller.set_configuration(&configuration)?;
+        let started = Instant::now();
+        self.controller.connect_async().await?;
+        let profile = &self.profiles[index];
+        info!("Connected to Wi-Fi {}", profile.ssid);
+
+        // The driver does not report which AP it associated with, assume
+        // the strongest one seen for this SSID in the preceding scan
+        let access_point = scan
+            .iter()
+            .filter(|access_point| access_point.ssid == profile.ssid)
+            .max_by_key(|access_point| access_point.signal_strength);
+        if self.diagnostics.connect_latency_ms.is_some() {
+            self.diagnostics.reconnects += 1;
+        }
+        self.diagnostics.ssid = Some(profile.ssid.clone());
+        self.diagnostics.bssid = access_point.map(|access_point| access_point.bssid);
+        self.diagnostics.channel = access_point.map(|access_point| access_point.channel);
+        self.diagnostics.rssi_dbm = access_point.map(|access_point| access_point.signal_strength);
+        self.diagnostics.connect_latency_ms = Some(started.elapsed().as_millis());
+        self.current = Some(index);
+        Ok(())
+    }
+
+    async fn scan(&mut self) -> Result<Vec<AccessPointInfo>, WifiError> {
+        self.controller.scan_n_async(WifiConstant::SCAN_MAX_RESULTS).await
+    }
+
+    // Visible profiles by signal strength, then hidden or out-of-range ones
+    // in configured priority order
+    fn ranked(&self, scan: &[AccessPointInfo]) -> Vec<(usize, Option<i8>)> {
+        let mut ranked: Vec<(usize, Option<i8>)> = self
+            .profiles
+            .iter()
+            .enumerate()
+            .map(|(index, profile)| {
+                let rssi = scan
+                    .iter()
+                    .filter(|access_point| access_point.ssid == profile.ssid)
+                    .map(|access_point| access_point.signal_strength)
+                    .max();
+                (index, rssi)
+            })
+            .collect();
+        // Stable sort keeps configured order among equals and among hidden
+        ranked.sort_by(|a, b| b.1.is_some().cmp(&a.1.is_some()).then(b.1.cmp(&a.1)));
+        ranked
+    }
+}
+
+// Missing files are not an error here: PEAP without a CA cert still works
+// (unverified server), EAP-TLS simply fails to associate
+pub fn load_certificates(secrets: &mut SecretStoreService) -> WifiCertificatesDTO {
+    let mut load = |name: &str| match secrets.get_static(name) {
+        Ok(value) => value,
+        Err(error) => {
+            warn!("Could not read {} from the secret store: {:?}", name, error);
+            None
+        },
+    };
+    WifiCertificatesDTO {
+        ca_cert: load(SecretConstant::WIFI_CA_CERT),
+        client_cert: load(SecretConstant::WIFI_CLIENT_CERT),
+        client_key: load(SecretConstant::WIFI_CLIENT_KEY),
+    }
+}
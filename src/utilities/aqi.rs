@@ -0,0 +1,66 @@
+use alloc::vec::Vec;
+
+use crate::dtos::configurations::aqi::{AqiBreakpointDTO, AqiConfigDTO};
+use crate::utilities::thresholds;
+
+// "0.0-9.0:0-50,9.1-35.4:51-100": ascending concentration ranges and the
+// index range each maps onto
+pub fn parse_breakpoints(value: &str) -> Option<Vec<AqiBreakpointDTO>> {
+    let mut breakpoints: Vec<AqiBreakpointDTO> = Vec::new();
+    for part in value.split(',').map(|part| part.trim()).filter(|part| !part.is_empty()) {
+        let (concentration, index) = part.split_once(':')?;
+        let (low, high) = concentration.split_once('-')?;
+        let (index_low, index_high) = index.split_once('-')?;
+        let breakpoint = AqiBreakpointDTO {
+            low: low.trim().parse().ok()?,
+            high: high.trim().parse().ok()?,
+            index_low: index_low.trim().parse().ok()?,
+            index_high: index_high.trim().parse().ok()?,
+        };
+        if breakpoint.low > breakpoint.high
+            || breakpoint.index_low > breakpoint.index_high
+            || breakpoints.last().is_some_and(|last| last.high >= breakpoint.low)
+        {
+            return None;
+        }
+        breakpoints.push(breakpoint);
+    }
+    if breakpoints.is_empty() { None } else { Some(breakpoints) }
+}
+
+// EPA linear interpolation within the matching row. Concentrations in the
+// gap between two rows belong to the upper one, those beyond the table get
+// its top index
+pub fn sub_index(breakpoints: &[AqiBreakpointDTO], concentration: f32) -> Option<u16> {
+    if concentration < 0.0 || concentration.is_nan() {
+        return None;
+    }
+    let last = breakpoints.last()?;
+    if concentration > last.high {
+        return Some(last.index_high);
+    }
+    let row = breakpoints.iter().find(|row| concentration <= row.high)?;
+    let concentration = concentration.max(row.low);
+    let span = (row.index_high - row.index_low) as f32;
+    let fraction = if row.high > row.low { (concentration - row.low) / (row.high - row.low) } else { 0.0 };
+    Some(row.index_low + (span * fraction + 0.5) as u16)
+}
+
+// Combined index as the worst of the available sub-indices, as the EPA
+// does across pollutants, and its category. None without any input
+pub fn compute<'a>(
+    config: &'a AqiConfigDTO,
+    pm2_5: Option<f32>,
+    pm10: Option<f32>,
+    co2: Option<f32>,
+) -> Option<(u16, &'a str)> {
+    let index = [
+        pm2_5.and_then(|value| sub_index(&config.pm2_5, value)),
+        pm10.and_then(|value| sub_index(&config.pm10, value)),
+        co2.and_then(|value| sub_index(&config.co2, value)),
+    ]
+    .into_iter()
+    .flatten()
+    .max()?;
+    Some((index, thresholds::classify(&config.categories, index as f64)))
+}
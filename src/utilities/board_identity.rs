@@ -0,0 +1,104 @@
+use alloc::string::String;
+
+use crate::constants::hardware::HardwareConstant;
+use crate::dtos::configurations::board_identity::BoardIdentityDTO;
+use crate::enums::board_revision::BoardRevision;
+use crate::utilities::modbus;
+
+const CRC_OFFSET: usize = HardwareConstant::EEPROM_IDENTITY_LENGTH - 2;
+
+pub fn encode(identity: &BoardIdentityDTO) -> [u8; HardwareConstant::EEPROM_IDENTITY_LENGTH] {
+    let mut block = [0u8; HardwareConstant::EEPROM_IDENTITY_LENGTH];
+    block[HardwareConstant::EEPROM_REVISION_OFFSET as usize] = identity.revision.as_str().as_bytes()[0];
+    let serial = identity.serial.as_bytes();
+    let length = serial.len().min(HardwareConstant::EEPROM_SERIAL_LENGTH);
+    block[HardwareConstant::EEPROM_SERIAL_OFFSET..HardwareConstant::EEPROM_SERIAL_OFFSET + length]
+        .copy_from_slice(&serial[..length]);
+    let calibration = HardwareConstant::EEPROM_CALIBRATION_OFFSET;
+    block[calibration] = HardwareConstant::EEPROM_CALIBRATION_VERSION;
+    let constants = [identity.temperature_offset_c, identity.cpu_heating_c, identity.radio_heating_c];
+    for (index, value) in constants.iter().enumerate() {
+        let start = calibration + 1 + index * 2;
+        block[start..start + 2].copy_from_slice(&hundredths(*value).to_le_bytes());
+    }
+    let crc = modbus::crc16(&block[..CRC_OFFSET]);
+    block[CRC_OFFSET..].copy_from_slice(&crc.to_le_bytes());
+    block
+}
+
+// None for a blank or partly programmed EEPROM, or one from a newer
+// calibration layout
+pub fn decode(block: &[u8]) -> Option<BoardIdentityDTO> {
+    if block.len() < HardwareConstant::EEPROM_IDENTITY_LENGTH {
+        return None;
+    }
+    let crc = u16::from_le_bytes([block[CRC_OFFSET], block[CRC_OFFSET + 1]]);
+    if modbus::crc16(&block[..CRC_OFFSET]) != crc {
+        return None;
+    }
+    let letter = [block[HardwareConstant::EEPROM_REVISION_OFFSET as usize]];
+    let revision = core::str::from_utf8(&letter).ok().and_then(BoardRevision::parse)?;
+    let serial = &block[HardwareConstant::EEPROM_SERIAL_OFFSET
+        ..HardwareConstant::EEPROM_SERIAL_OFFSET + HardwareConstant::EEPROM_SERIAL_LENGTH];
+    let serial = core::str::from_utf8(serial).ok()?.trim_end_matches('\0');
+    let calibration = HardwareConstant::EEPROM_CALIBRATION_OFFSET;
+    if block[calibration] != HardwareConstant::EEPROM_CALIBRATION_VERSION {
+        return None;
+    }
+    let constant = |index: usize| {
+        let start = calibration + 1 + index * 2;
+        i16::from_le_bytes([block[start], block[start + 1]]) as f32 / 100.0
+    };
+    Some(BoardIdentityDTO {
+        serial: String::from(serial),
+        revision: revision,
+        temperature_offset_c: constant(0),
+        cpu_heating_c: constant(1),
+        radio_heating_c: constant(2),
+    })
+}
+
+// "serial=SP-0001234,revision=D,temperature_offset_c=1.25", the constants
+// optional and 0 when left out; serial and revision are required
+pub fn parse(value: &str) -> Option<BoardIdentityDTO> {
+    let mut serial = None;
+    let mut revision = None;
+    let mut identity = BoardIdentityDTO {
+        serial: String::new(),
+        revision: BoardRevision::B,
+        temperature_offset_c: 0.0,
+        cpu_heating_c: 0.0,
+        radio_heating_c: 0.0,
+    };
+    for part in value.split(',').map(|part| part.trim()).filter(|part| !part.is_empty()) {
+        let (key, field) = part.split_once('=')?;
+        let field = field.trim();
+        match key.trim() {
+            "serial" => {
+                if field.is_empty() || field.len() > HardwareConstant::EEPROM_SERIAL_LENGTH || !field.is_ascii() {
+                    return None;
+                }
+                serial = Some(String::from(field));
+            },
+            "revision" => revision = Some(BoardRevision::parse(field)?),
+            "temperature_offset_c" => identity.temperature_offset_c = constant(field)?,
+            "cpu_heating_c" => identity.cpu_heating_c = constant(field)?,
+            "radio_heating_c" => identity.radio_heating_c = constant(field)?,
+            _ => return None,
+        }
+    }
+    identity.serial = serial?;
+    identity.revision = revision?;
+    Some(identity)
+}
+
+// Stored in hundredths as an i16
+fn constant(value: &str) -> Option<f32> {
+    let value: f32 = value.parse().ok()?;
+    (value.is_finite() && (value * 100.0).abs() <= i16::MAX as f32).then_some(value)
+}
+
+fn hundredths(value: f32) -> i16 {
+    let scaled = value * 100.0;
+    (if scaled < 0.0 { scaled - 0.5 } else { scaled + 0.5 }) as i16
+}
@@ -0,0 +1,63 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::constants::aqi::AqiConstant;
+use crate::enums::value::Value;
+
+// BTHome v2, unencrypted: https://bthome.io/format/
+const SERVICE_UUID: [u8; 2] = [0xd2, 0xfc];
+const DEVICE_INFO: u8 = 0x40;
+const AD_FLAGS: [u8; 3] = [0x02, 0x01, 0x06];
+const AD_SERVICE_DATA_16: u8 = 0x16;
+// Legacy advertising data, flags and service data header included
+pub const MAX_BYTES: usize = 31;
+
+const PACKET_ID: u8 = 0x00;
+
+// Field, object id, length in bytes and factor, in ascending id order as
+// the format requires. Pressure is stored in Pa and BTHome wants 0.01 hPa,
+// which is the same number
+const OBJECTS: [(&str, u8, usize, f32); 9] = [
+    ("battery", 0x01, 1, 1.0),
+    ("temperature", 0x02, 2, 100.0),
+    ("humidity", 0x03, 2, 100.0),
+    ("pressure", 0x04, 3, 1.0),
+    ("lux", 0x05, 3, 100.0),
+    (AqiConstant::PM2_5, 0x0d, 2, 1.0),
+    (AqiConstant::PM10, 0x0e, 2, 1.0),
+    (AqiConstant::CO2, 0x12, 2, 1.0),
+    ("distance_mm", 0x40, 2, 1.0),
+];
+
+// Advertising data for the readings BTHome has an object for. Objects that
+// no longer fit in the 31 bytes are left out, later ids first
+pub fn encode(data: &BTreeMap<String, Value>, packet_id: u8) -> Vec<u8> {
+    let mut service_data = Vec::with_capacity(MAX_BYTES);
+    service_data.extend_from_slice(&SERVICE_UUID);
+    service_data.push(DEVICE_INFO);
+    service_data.extend_from_slice(&[PACKET_ID, packet_id]);
+
+    // Flags plus the length and type bytes of the service data
+    let budget = MAX_BYTES - AD_FLAGS.len() - 2;
+    for (field, id, length, factor) in OBJECTS {
+        let value = match data.get(field).and_then(|value| value.as_f32()) {
+            Some(value) => value,
+            None => continue,
+        };
+        if service_data.len() + 1 + length > budget {
+            break;
+        }
+        // Two's complement little endian, truncated to `length`
+        let raw = (value * factor + if value < 0.0 { -0.5 } else { 0.5 }) as i32;
+        service_data.push(id);
+        service_data.extend_from_slice(&raw.to_le_bytes()[..length]);
+    }
+
+    let mut out = Vec::with_capacity(MAX_BYTES);
+    out.extend_from_slice(&AD_FLAGS);
+    out.push(service_data.len() as u8 + 1);
+    out.push(AD_SERVICE_DATA_16);
+    out.extend_from_slice(&service_data);
+    out
+}
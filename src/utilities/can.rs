@@ -0,0 +1,94 @@
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use crate::constants::can::CanConstant;
+use crate::dtos::configurations::can::CanSignalDTO;
+
+// "rpm=0x0cf00400:24:16:le:0.125:0,coolant_c=0x18feee00:0:8:le:1:-40":
+// field, frame id, start bit, length in bits, byte order (le for Intel, be
+// for Motorola), scale and offset, then ":signed" for two's complement.
+// Ids above 0x7ff are extended
+pub fn parse_signals(value: &str) -> Option<Vec<CanSignalDTO>> {
+    value
+        .split(',')
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (field, spec) = entry.split_once('=')?;
+            let parts: Vec<&str> = spec.split(':').map(|part| part.trim()).collect();
+            let (id, start_bit, length, order, scale, offset, signed) = match parts.as_slice() {
+                [id, start_bit, length, order, scale, offset] => (id, start_bit, length, order, scale, offset, false),
+                [id, start_bit, length, order, scale, offset, "signed"] => {
+                    (id, start_bit, length, order, scale, offset, true)
+                },
+                _ => return None,
+            };
+            let id = match id.strip_prefix("0x") {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => id.parse().ok()?,
+            };
+            let start_bit: u16 = start_bit.parse().ok()?;
+            let length: u8 = length.parse().ok()?;
+            let big_endian = match *order {
+                "le" => false,
+                "be" => true,
+                _ => return None,
+            };
+            if field.trim().is_empty()
+                || id > CanConstant::MAX_EXTENDED_ID
+                || length == 0
+                || length > 64
+                || start_bit >= 64
+            {
+                return None;
+            }
+            Some(CanSignalDTO {
+                field: field.trim().to_string(),
+                id: id,
+                extended: id > CanConstant::MAX_STANDARD_ID,
+                id_mask: CanConstant::MAX_EXTENDED_ID,
+                start_bit: start_bit,
+                length: length,
+                big_endian: big_endian,
+                signed: signed,
+                scale: scale.parse().ok()?,
+                offset: offset.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+// Physical value of `signal` in a frame's payload, None when the payload is
+// too short for it
+pub fn extract(data: &[u8], signal: &CanSignalDTO) -> Option<f32> {
+    let mut raw: u64 = 0;
+    let mut position = signal.start_bit as usize;
+    for index in 0..signal.length as usize {
+        let bit = (data.get(position / 8)? >> (position % 8)) & 1;
+        if signal.big_endian {
+            // Motorola runs from the MSB down, into the next byte's bit 7
+            raw = (raw << 1) | bit as u64;
+            position = if position % 8 == 0 { position + 15 } else { position - 1 };
+        } else {
+            raw |= (bit as u64) << index;
+            position += 1;
+        }
+    }
+    let value = if signal.signed && signal.length < 64 && raw >> (signal.length - 1) & 1 == 1 {
+        (raw as i64 - (1i64 << signal.length)) as f32
+    } else if signal.signed {
+        raw as i64 as f32
+    } else {
+        raw as f32
+    };
+    Some(value * signal.scale + signal.offset)
+}
+
+// Acceptance code and mask letting through exactly the bits all signal ids
+// agree on: one id passes only itself, several pass a superset that the
+// listener narrows down in software
+pub fn acceptance_filter(signals: &[CanSignalDTO]) -> Option<(u32, u32)> {
+    let first = signals.first()?.id;
+    let mask = signals.iter().fold(CanConstant::MAX_EXTENDED_ID, |mask, signal| mask & !(signal.id ^ first));
+    Some((first & mask, mask))
+}
@@ -0,0 +1,255 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::str::CharIndices;
+
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::enums::value::Value;
+
+// RFC 8949 major types
+const UNSIGNED: u8 = 0;
+const NEGATIVE: u8 = 1;
+const TEXT: u8 = 3;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const SIMPLE: u8 = 7;
+// Additional information opening an indefinite length array or map, and the
+// byte closing it
+const INDEFINITE: u8 = 31;
+const BREAK: u8 = 0xff;
+// Deeper JSON is refused rather than recursed into
+const MAX_JSON_DEPTH: usize = 16;
+
+pub fn write_head(out: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    match value {
+        0..=23 => out.push(major | value as u8),
+        24..=0xff => out.extend_from_slice(&[major | 24, value as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend_from_slice(&(value as u16).to_be_bytes());
+        },
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend_from_slice(&(value as u32).to_be_bytes());
+        },
+        _ => {
+            out.push(major | 27);
+            out.extend_from_slice(&value.to_be_bytes());
+        },
+    }
+}
+
+pub fn write_text(out: &mut Vec<u8>, value: &str) {
+    write_head(out, TEXT, value.len() as u64);
+    out.extend_from_slice(value.as_bytes());
+}
+
+pub fn write_integer(out: &mut Vec<u8>, value: i64) {
+    if value >= 0 {
+        write_head(out, UNSIGNED, value as u64);
+    } else {
+        write_head(out, NEGATIVE, (-1 - value) as u64);
+    }
+}
+
+pub fn write_value(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::String(value) => write_text(out, value),
+        Value::Integer(value) => write_integer(out, *value as i64),
+        Value::Boolean(value) => out.push((SIMPLE << 5) | if *value { 21 } else { 20 }),
+        Value::Float(value) => {
+            out.push((SIMPLE << 5) | 26);
+            out.extend_from_slice(&value.to_be_bytes());
+        },
+    }
+}
+
+// Single letter keys keep envelopes small enough for narrow-band uplinks:
+// i = id, t = timestamp, b = relative_ms_since_boot while the clock is
+// unset, d = device_urn, l = location_urn, v = data and, unless every field
+// is GOOD, q = quality as field -> {q = flag, r = raw}.
+// u = units as field -> unit string; the SI scale is implied by the unit
+pub fn envelope_to_cbor(envelope: &MeasurementEnvelopeDTO) -> Vec<u8> {
+    let mut out = Vec::new();
+    let keys = 6 + envelope.relative_ms_since_boot.is_some() as u64 + !envelope.quality.is_empty() as u64;
+    write_head(&mut out, MAP, keys);
+    write_text(&mut out, "i");
+    write_text(&mut out, &envelope.id);
+    write_text(&mut out, "t");
+    write_head(&mut out, UNSIGNED, envelope.timestamp);
+    if let Some(relative_ms) = envelope.relative_ms_since_boot {
+        write_text(&mut out, "b");
+        write_head(&mut out, UNSIGNED, relative_ms);
+    }
+    write_text(&mut out, "d");
+    write_text(&mut out, &envelope.device_urn);
+    write_text(&mut out, "l");
+    write_text(&mut out, &envelope.location_urn);
+    write_text(&mut out, "v");
+    write_head(&mut out, MAP, envelope.data.len() as u64);
+    for (field, value) in envelope.data.iter() {
+        write_text(&mut out, field);
+        write_value(&mut out, value);
+    }
+    write_text(&mut out, "u");
+    write_head(&mut out, MAP, envelope.units.len() as u64);
+    for (field, unit) in envelope.units.iter() {
+        write_text(&mut out, field);
+        write_text(&mut out, unit.unit);
+    }
+    if !envelope.quality.is_empty() {
+        write_text(&mut out, "q");
+        write_head(&mut out, MAP, envelope.quality.len() as u64);
+        for (field, field_quality) in envelope.quality.iter() {
+            write_text(&mut out, field);
+            write_head(&mut out, MAP, if field_quality.raw.is_some() { 2 } else { 1 });
+            write_text(&mut out, "q");
+            write_text(&mut out, field_quality.quality.as_str());
+            if let Some(raw) = &field_quality.raw {
+                write_text(&mut out, "r");
+                write_value(&mut out, raw);
+            }
+        }
+    }
+    out
+}
+
+// The JSON document as CBOR with the same structure and keys, for uplinks
+// that are handed JSON but pay for every byte. Arrays and objects get
+// indefinite lengths so nothing has to be counted ahead; a number with a
+// fraction or exponent becomes a single precision float when that loses
+// nothing of the text, a double otherwise. None unless `json` is a single
+// valid document
+pub fn from_json(json: &[u8]) -> Option<Vec<u8>> {
+    let json = core::str::from_utf8(json).ok()?;
+    let mut out = Vec::with_capacity(json.len());
+    let rest = transcode(json, &mut out, 0)?;
+    rest.trim().is_empty().then_some(out)
+}
+
+// Writes the value at the start of `json`, returns what follows it
+fn transcode<'a>(json: &'a str, out: &mut Vec<u8>, depth: usize) -> Option<&'a str> {
+    if depth > MAX_JSON_DEPTH {
+        return None;
+    }
+    let json = json.trim_start();
+    match json.as_bytes().first()? {
+        b'{' => {
+            out.push((MAP << 5) | INDEFINITE);
+            let mut rest = json[1..].trim_start();
+            if let Some(rest) = rest.strip_prefix('}') {
+                out.push(BREAK);
+                return Some(rest);
+            }
+            loop {
+                let (key, after) = string(rest.trim_start())?;
+                write_text(out, &key);
+                rest = after.trim_start().strip_prefix(':')?;
+                rest = transcode(rest, out, depth + 1)?.trim_start();
+                match rest.strip_prefix(',') {
+                    Some(after) => rest = after,
+                    None => break,
+                }
+            }
+            out.push(BREAK);
+            rest.strip_prefix('}')
+        },
+        b'[' => {
+            out.push((ARRAY << 5) | INDEFINITE);
+            let mut rest = json[1..].trim_start();
+            if let Some(rest) = rest.strip_prefix(']') {
+                out.push(BREAK);
+                return Some(rest);
+            }
+            loop {
+                rest = transcode(rest, out, depth + 1)?.trim_start();
+                match rest.strip_prefix(',') {
+                    Some(after) => rest = after,
+                    None => break,
+                }
+            }
+            out.push(BREAK);
+            rest.strip_prefix(']')
+        },
+        b'"' => {
+            let (value, rest) = string(json)?;
+            write_text(out, &value);
+            Some(rest)
+        },
+        b't' => json.strip_prefix("true").inspect(|_| out.push((SIMPLE << 5) | 21)),
+        b'f' => json.strip_prefix("false").inspect(|_| out.push((SIMPLE << 5) | 20)),
+        b'n' => json.strip_prefix("null").inspect(|_| out.push((SIMPLE << 5) | 22)),
+        _ => number(json, out),
+    }
+}
+
+// The string literal at the start of `json` unescaped, and what follows it
+fn string(json: &str) -> Option<(String, &str)> {
+    let body = json.strip_prefix('"')?;
+    let mut chars = body.char_indices();
+    let mut value = String::new();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &body[index + 1..])),
+            '\\' => value.push(match chars.next()?.1 {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'b' => '\u{8}',
+                'f' => '\u{c}',
+                'u' => {
+                    let code = hex4(&mut chars)?;
+                    // A UTF-16 surrogate pair spelled as two escapes
+                    if (0xd800..0xdc00).contains(&code) {
+                        if chars.next()?.1 != '\\' || chars.next()?.1 != 'u' {
+                            return None;
+                        }
+                        let low = hex4(&mut chars)?;
+                        if !(0xdc00..0xe000).contains(&low) {
+                            return None;
+                        }
+                        char::from_u32(0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00))?
+                    } else {
+                        char::from_u32(code)?
+                    }
+                },
+                escaped @ ('"' | '\\' | '/') => escaped,
+                _ => return None,
+            }),
+            _ => value.push(c),
+        }
+    }
+    None
+}
+
+fn hex4(chars: &mut CharIndices) -> Option<u32> {
+    let mut code = 0;
+    for _ in 0..4 {
+        code = code * 16 + chars.next()?.1.to_digit(16)?;
+    }
+    Some(code)
+}
+
+fn number<'a>(json: &'a str, out: &mut Vec<u8>) -> Option<&'a str> {
+    let end = json
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')))
+        .unwrap_or(json.len());
+    let (text, rest) = json.split_at(end);
+    if !text.contains(['.', 'e', 'E']) {
+        if let Ok(value) = text.parse::<i64>() {
+            write_integer(out, value);
+            return Some(rest);
+        }
+    }
+    let double: f64 = text.parse().ok()?;
+    let single = double as f32;
+    if single as f64 == double || format!("{}", single) == text {
+        out.push((SIMPLE << 5) | 26);
+        out.extend_from_slice(&single.to_be_bytes());
+    } else {
+        out.push((SIMPLE << 5) | 27);
+        out.extend_from_slice(&double.to_be_bytes());
+    }
+    Some(rest)
+}
@@ -0,0 +1,16 @@
+use alloc::borrow::Cow;
+
+use miniz_oxide::deflate::compress_to_vec_zlib;
+
+use crate::enums::compression::Compression;
+
+// Level 6 is miniz's default; higher levels barely help on repetitive JSON
+// but cost noticeably more time on the ESP32
+const DEFLATE_LEVEL: u8 = 6;
+
+pub fn compress(compression: Compression, payload: &[u8]) -> Cow<'_, [u8]> {
+    match compression {
+        Compression::None => Cow::Borrowed(payload),
+        Compression::Deflate => Cow::Owned(compress_to_vec_zlib(payload, DEFLATE_LEVEL)),
+    }
+}
@@ -0,0 +1,74 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::error::Error;
+
+use serde::Deserialize;
+
+use crate::constants::storage::StorageConstant;
+
+// Persisted remote configs start with their schema version as a
+// little-endian u16. Configs written before versioning start with the
+// JSON's own '{' instead and are version 1
+const HEADER_BYTES: usize = 2;
+
+// MIGRATIONS[n - 1] rewrites a version n config into version n + 1, so a
+// device upgraded across several releases runs every step in order. Steps
+// work on the raw JSON because the old layout may no longer deserialize
+const MIGRATIONS: &[fn(&str) -> Result<String, Box<dyn Error + Send + Sync>>] = &[v1_to_v2];
+
+#[derive(Deserialize)]
+struct SchemaVersion {
+    version: Option<u16>,
+}
+
+// The version a config received from the server declares
+pub fn declared_version(json: &[u8]) -> Result<u16, Box<dyn Error + Send + Sync>> {
+    let json = core::str::from_utf8(json).map_err(|error| Box::<dyn Error + Send + Sync>::from(error.to_string()))?;
+    serde_json_core::from_str::<SchemaVersion>(json)
+        .map(|(schema, _)| schema.version.unwrap_or(1))
+        .map_err(|error| Box::from(format!("Config version unreadable: {:?}", error)))
+}
+
+// Splits a persisted config into its version and JSON
+pub fn decode(bytes: &[u8]) -> (u16, &[u8]) {
+    match bytes {
+        [b'{', ..] => (1, bytes),
+        [low, high, json @ ..] => (u16::from_le_bytes([*low, *high]), json),
+        _ => (1, bytes),
+    }
+}
+
+pub fn encode(json: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_BYTES + json.len());
+    bytes.extend_from_slice(&StorageConstant::CONFIG_SCHEMA_VERSION.to_le_bytes());
+    bytes.extend_from_slice(json);
+    bytes
+}
+
+// Brings a version `version` config up to `CONFIG_SCHEMA_VERSION`. A
+// config from newer firmware is refused rather than guessed at, the caller
+// falls back to an older config or the build-time defaults
+pub fn migrate(version: u16, json: &[u8]) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let current = StorageConstant::CONFIG_SCHEMA_VERSION;
+    if version == 0 || version > current {
+        return Err(Box::from(format!(
+            "Config schema {} is not supported, this firmware reads up to {}",
+            version, current
+        )));
+    }
+    let mut json = String::from(
+        core::str::from_utf8(json).map_err(|error| Box::<dyn Error + Send + Sync>::from(error.to_string()))?,
+    );
+    for step in MIGRATIONS[(version - 1) as usize..(current - 1) as usize].iter() {
+        json = step(&json)?;
+    }
+    Ok(json)
+}
+
+// Version 2 only introduced the version header and field, the layout of
+// the fields themselves is unchanged
+fn v1_to_v2(json: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+    Ok(String::from(json))
+}
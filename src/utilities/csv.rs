@@ -0,0 +1,37 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+
+// Quotes a field only when it would otherwise break the row
+pub fn escape(field: &str) -> String {
+    if field.contains(|c| c == ',' || c == '"' || c == '\r' || c == '\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// One row per field so envelopes from different sensor sets share a header
+pub fn envelope_to_rows(envelope: &MeasurementEnvelopeDTO) -> String {
+    let mut rows = String::new();
+    for (field, value) in envelope.data.iter() {
+        rows.push_str(&format!(
+            "{},{},{},{},{}\r\n",
+            envelope.timestamp,
+            escape(&envelope.device_urn),
+            escape(&envelope.location_urn),
+            escape(field),
+            escape(&value.to_string())
+        ));
+    }
+    rows
+}
+
+// Header lines have no numeric timestamp and are always dropped
+pub fn row_in_range(row: &str, from: u64, to: u64) -> bool {
+    row.split(',')
+        .next()
+        .and_then(|timestamp| timestamp.parse::<u64>().ok())
+        .map_or(false, |timestamp| timestamp >= from && timestamp <= to)
+}
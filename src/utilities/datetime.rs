@@ -0,0 +1,55 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CivilDateTime {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+// Converts seconds since the unix epoch into a UTC calendar date
+// (days-from-civil algorithm by Howard Hinnant)
+pub fn from_unix(timestamp: u64) -> CivilDateTime {
+    let days = (timestamp / 86_400) as i64;
+    let seconds_of_day = (timestamp % 86_400) as u32;
+
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = (yoe + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+
+    CivilDateTime {
+        year: year,
+        month: month,
+        day: day,
+        hour: (seconds_of_day / 3_600) as u8,
+        minute: ((seconds_of_day % 3_600) / 60) as u8,
+        second: (seconds_of_day % 60) as u8,
+    }
+}
+
+// Inverse of `from_unix` for a date: days since the unix epoch
+pub fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year } as i64;
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+pub fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
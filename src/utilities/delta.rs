@@ -0,0 +1,167 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use miniz_oxide::inflate::stream::{inflate, InflateState};
+use miniz_oxide::{DataFormat, MZError, MZFlush, MZStatus};
+
+// Delta patch format, zlib compressed as a whole:
+//   "SPDELTA1" | new_size u32
+//   then records until new_size bytes are produced:
+//     diff_len u32 | extra_len u32 | seek i32 | diff bytes | extra bytes
+// Diff bytes are added (wrapping) to the old image at the read cursor, extra
+// bytes are copied verbatim, then the old cursor moves by `seek`. This is
+// bsdiff's control/diff/extra layout interleaved so it can be applied in a
+// single streaming pass. All integers are little-endian.
+const MAGIC: &[u8; 8] = b"SPDELTA1";
+const HEADER_BYTES: usize = 12;
+const CONTROL_BYTES: usize = 12;
+const INFLATE_CHUNK_BYTES: usize = 512;
+
+#[derive(Debug)]
+pub enum DeltaError<E> {
+    BadMagic,
+    Corrupt,
+    // The patch produced more data than the header announced
+    Overrun,
+    Io(E),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Header,
+    Control,
+    Diff(u32),
+    Extra(u32),
+}
+
+pub struct DeltaDecoder {
+    inflate: Box<InflateState>,
+    state: State,
+    // Partially received header or control record
+    pending: Vec<u8>,
+    extra_len: u32,
+    seek: i32,
+    old_offset: i64,
+    new_size: u32,
+    produced: u32,
+}
+
+impl DeltaDecoder {
+    pub fn new() -> Self {
+        Self {
+            inflate: InflateState::new_boxed(DataFormat::Zlib),
+            state: State::Header,
+            pending: Vec::with_capacity(HEADER_BYTES),
+            extra_len: 0,
+            seek: 0,
+            old_offset: 0,
+            new_size: 0,
+            produced: 0,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.state != State::Header && self.produced == self.new_size
+    }
+
+    // `old` fills the buffer from the running image at the given offset,
+    // `output` receives the reconstructed image in order
+    pub fn feed<E>(
+        &mut self,
+        mut compressed: &[u8],
+        old: &mut dyn FnMut(u32, &mut [u8]) -> Result<(), E>,
+        output: &mut dyn FnMut(&[u8]) -> Result<(), E>,
+    ) -> Result<(), DeltaError<E>> {
+        let mut plain = [0u8; INFLATE_CHUNK_BYTES];
+        loop {
+            let result = inflate(&mut self.inflate, compressed, &mut plain, MZFlush::None);
+            match result.status {
+                Ok(MZStatus::Ok) | Ok(MZStatus::StreamEnd) | Err(MZError::Buf) => {},
+                _ => return Err(DeltaError::Corrupt),
+            }
+            compressed = &compressed[result.bytes_consumed..];
+            self.apply(&plain[..result.bytes_written], old, output)?;
+            // A full output buffer may leave decompressed data inside the
+            // inflater even after all input is consumed
+            let drained = compressed.is_empty() && result.bytes_written < plain.len();
+            if drained || (result.bytes_consumed == 0 && result.bytes_written == 0) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn apply<E>(
+        &mut self,
+        mut data: &[u8],
+        old: &mut dyn FnMut(u32, &mut [u8]) -> Result<(), E>,
+        output: &mut dyn FnMut(&[u8]) -> Result<(), E>,
+    ) -> Result<(), DeltaError<E>> {
+        while !data.is_empty() {
+            match self.state {
+                State::Header | State::Control => {
+                    let needed = if self.state == State::Header { HEADER_BYTES } else { CONTROL_BYTES };
+                    let take = data.len().min(needed - self.pending.len());
+                    self.pending.extend_from_slice(&data[..take]);
+                    data = &data[take..];
+                    if self.pending.len() == needed {
+                        self.parse_record()?;
+                    }
+                },
+                State::Diff(remaining) => {
+                    let take = data.len().min(remaining as usize).min(INFLATE_CHUNK_BYTES);
+                    let offset = u32::try_from(self.old_offset).map_err(|_| DeltaError::Corrupt)?;
+                    let mut bytes = [0u8; INFLATE_CHUNK_BYTES];
+                    old(offset, &mut bytes[..take]).map_err(DeltaError::Io)?;
+                    for (byte, diff) in bytes[..take].iter_mut().zip(&data[..take]) {
+                        *byte = byte.wrapping_add(*diff);
+                    }
+                    self.emit(&bytes[..take], output)?;
+                    self.old_offset += take as i64;
+                    data = &data[take..];
+                    self.state = match remaining - take as u32 {
+                        0 => State::Extra(self.extra_len),
+                        left => State::Diff(left),
+                    };
+                },
+                State::Extra(remaining) => {
+                    let take = data.len().min(remaining as usize);
+                    self.emit(&data[..take], output)?;
+                    data = &data[take..];
+                    self.state = State::Extra(remaining - take as u32);
+                },
+            }
+            // Zero-length sections complete immediately
+            if self.state == State::Extra(0) {
+                self.old_offset += self.seek as i64;
+                self.state = State::Control;
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_record<E>(&mut self) -> Result<(), DeltaError<E>> {
+        let word = |bytes: &[u8], i: usize| [bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]];
+        if self.state == State::Header {
+            if &self.pending[..8] != MAGIC {
+                return Err(DeltaError::BadMagic);
+            }
+            self.new_size = u32::from_le_bytes(word(&self.pending, 8));
+            self.state = State::Control;
+        } else {
+            let diff_len = u32::from_le_bytes(word(&self.pending, 0));
+            self.extra_len = u32::from_le_bytes(word(&self.pending, 4));
+            self.seek = i32::from_le_bytes(word(&self.pending, 8));
+            self.state = if diff_len > 0 { State::Diff(diff_len) } else { State::Extra(self.extra_len) };
+        }
+        self.pending.clear();
+        Ok(())
+    }
+
+    fn emit<E>(&mut self, data: &[u8], output: &mut dyn FnMut(&[u8]) -> Result<(), E>) -> Result<(), DeltaError<E>> {
+        if self.produced as usize + data.len() > self.new_size as usize {
+            return Err(DeltaError::Overrun);
+        }
+        self.produced += data.len() as u32;
+        output(data).map_err(DeltaError::Io)
+    }
+}
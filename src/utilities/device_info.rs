@@ -0,0 +1,43 @@
+use embedded_storage::ReadStorage;
+use esp_hal::efuse::{Efuse, CHIP_VER_REV1, CHIP_VER_REV2, WAFER_VERSION_MINOR};
+use esp_storage::FlashStorage;
+
+use crate::constants::http::HttpConstant;
+use crate::dtos::telemetry::device_info::DeviceInfoDTO;
+use crate::services::{board_identity, memory};
+
+// Build values come from build.rs, the rest is read once from eFuse/flash
+pub fn collect() -> DeviceInfoDTO {
+    DeviceInfoDTO {
+        firmware_version: HttpConstant::FIRMWARE_VERSION,
+        git_hash: env!("GIT_HASH"),
+        build_timestamp: env!("BUILD_TIMESTAMP").parse().unwrap_or(0),
+        esp_hal_version: env!("ESP_HAL_VERSION"),
+        chip_model: HttpConstant::CHIP,
+        chip_revision: chip_revision(),
+        flash_size_bytes: FlashStorage::new().capacity() as u32,
+        psram_size_bytes: memory::psram_bytes() as u32,
+        mac: Efuse::mac_address(),
+        serial: board_identity::serial(),
+    }
+}
+
+// APB_CTRL_DATE_REG, whose top bit is the third bit of the ESP32's major revision
+const APB_CTRL_DATE: *const u32 = 0x3ff6_607c as *const u32;
+
+// Silicon revision as ESP-IDF reports it, major * 100 + minor, e.g. 301 for
+// v3.1. esp-hal only decodes it for the newer chips
+fn chip_revision() -> u16 {
+    let date = unsafe { core::ptr::read_volatile(APB_CTRL_DATE) };
+    let eco = Efuse::read_bit(CHIP_VER_REV1) as u8
+        | (Efuse::read_bit(CHIP_VER_REV2) as u8) << 1
+        | ((date >> 31) as u8) << 2;
+    let major = match eco {
+        1 => 1,
+        3 => 2,
+        7 => 3,
+        _ => 0,
+    };
+    let minor: u8 = Efuse::read_field_le(WAFER_VERSION_MINOR);
+    major * 100 + minor as u16
+}
@@ -0,0 +1,72 @@
+use embedded_storage::nor_flash::{NorFlash, ReadNorFlash};
+#[cfg(not(test))]
+use esp_storage::FlashStorage;
+use littlefs2::consts::{U1, U256};
+use littlefs2::driver::Storage;
+use littlefs2::fs::Filesystem;
+use littlefs2::io::{Error, Result};
+
+use crate::constants::storage::StorageConstant;
+#[cfg(test)]
+use crate::host_test::fake_hal::FlashStorage;
+
+// Exposes the `queue` data partition (see partitions.csv) to LittleFS
+pub struct FlashPartition {
+    flash: FlashStorage,
+    offset: u32,
+}
+
+impl FlashPartition {
+    pub fn new(offset: u32) -> Self {
+        Self {
+            flash: FlashStorage::new(),
+            offset: offset,
+        }
+    }
+}
+
+// Call once at boot, before any service mounts the partition. A blank or
+// corrupted partition is formatted here and only here, so a mount that fails
+// later is reported by its service instead of wiping what the others stored.
+// Returns true when it had to format
+pub fn prepare(offset: u32) -> Result<bool> {
+    let mut storage = FlashPartition::new(offset);
+    if Filesystem::is_mountable(&mut storage) {
+        return Ok(false);
+    }
+    Filesystem::format(&mut storage)?;
+    Ok(true)
+}
+
+impl Storage for FlashPartition {
+    const READ_SIZE: usize = 16;
+    const WRITE_SIZE: usize = 16;
+    const BLOCK_SIZE: usize = StorageConstant::FLASH_SECTOR_SIZE;
+    const BLOCK_COUNT: usize = StorageConstant::QUEUE_PARTITION_SIZE / StorageConstant::FLASH_SECTOR_SIZE;
+    // Lets littlefs move hot metadata blocks around instead of wearing out one sector
+    const BLOCK_CYCLES: isize = 500;
+    type CACHE_SIZE = U256;
+    type LOOKAHEAD_SIZE = U1;
+
+    fn read(&mut self, off: usize, buf: &mut [u8]) -> Result<usize> {
+        self.flash
+            .read(self.offset + off as u32, buf)
+            .map_err(|_| Error::Io)?;
+        Ok(buf.len())
+    }
+
+    fn write(&mut self, off: usize, data: &[u8]) -> Result<usize> {
+        self.flash
+            .write(self.offset + off as u32, data)
+            .map_err(|_| Error::Io)?;
+        Ok(data.len())
+    }
+
+    fn erase(&mut self, off: usize, len: usize) -> Result<usize> {
+        let from = self.offset + off as u32;
+        self.flash
+            .erase(from, from + len as u32)
+            .map_err(|_| Error::Io)?;
+        Ok(len)
+    }
+}
@@ -0,0 +1,45 @@
+use core::f32::consts::PI;
+
+// Power of a single frequency over a block of samples, much cheaper than an
+// FFT when only a handful of frequencies matter. A full scale sine at
+// `frequency` yields about (32767 * N / 2)²
+pub fn power(samples: &[i16], sample_rate_hz: u32, frequency_hz: f32) -> f32 {
+    let coefficient = 2.0 * cosine(2.0 * PI * frequency_hz / sample_rate_hz as f32);
+    let mut previous = 0.0;
+    let mut before = 0.0;
+    for sample in samples {
+        let current = *sample as f32 + coefficient * previous - before;
+        before = previous;
+        previous = current;
+    }
+    previous * previous + before * before - coefficient * previous * before
+}
+
+// Mean square of the block, what `power` compares against as
+// N² / 2 * mean_square for a pure tone
+pub fn mean_square(samples: &[i16]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum: f32 = samples.iter().map(|sample| *sample as f32 * *sample as f32).sum();
+    sum / samples.len() as f32
+}
+
+// There is no cos in core; a Taylor series over [-π, π] is plenty for
+// Goertzel coefficients
+fn cosine(angle: f32) -> f32 {
+    let mut x = angle % (2.0 * PI);
+    if x > PI {
+        x -= 2.0 * PI;
+    } else if x < -PI {
+        x += 2.0 * PI;
+    }
+    let x2 = x * x;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for n in 1..10 {
+        term *= -x2 / ((2 * n - 1) * (2 * n)) as f32;
+        sum += term;
+    }
+    sum
+}
@@ -0,0 +1,150 @@
+use alloc::vec::Vec;
+
+// Bluetooth HCI over the H4 transport of the ESP32 controller: each packet
+// starts with its type indicator
+pub const COMMAND: u8 = 0x01;
+pub const EVENT: u8 = 0x04;
+
+pub const LE_SET_ADVERTISING_PARAMETERS: u16 = 0x2006;
+pub const LE_SET_ADVERTISING_DATA: u16 = 0x2008;
+pub const LE_SET_ADVERTISE_ENABLE: u16 = 0x200a;
+pub const LE_SET_SCAN_PARAMETERS: u16 = 0x200b;
+pub const LE_SET_SCAN_ENABLE: u16 = 0x200c;
+pub const LE_META_EVENT: u8 = 0x3e;
+pub const LE_ADVERTISING_REPORT: u8 = 0x02;
+
+// AD types and the prefixes identifying beacon frames
+const AD_SERVICE_DATA_16: u8 = 0x16;
+const AD_MANUFACTURER_DATA: u8 = 0xff;
+const IBEACON_PREFIX: [u8; 4] = [0x4c, 0x00, 0x02, 0x15];
+const EDDYSTONE_UUID: [u8; 2] = [0xaa, 0xfe];
+
+pub struct AdvertisingReport {
+    // Most significant byte first, as MACs are written
+    pub address: [u8; 6],
+    pub rssi: i8,
+    pub data: Vec<u8>,
+}
+
+fn command(opcode: u16, parameters: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(4 + parameters.len());
+    out.push(COMMAND);
+    out.extend_from_slice(&opcode.to_le_bytes());
+    out.push(parameters.len() as u8);
+    out.extend_from_slice(parameters);
+    out
+}
+
+// Non-connectable undirected advertising on all three channels, interval
+// in units of 0.625 ms
+pub fn set_advertising_parameters(interval: u16) -> Vec<u8> {
+    let mut parameters = Vec::with_capacity(15);
+    parameters.extend_from_slice(&interval.to_le_bytes());
+    parameters.extend_from_slice(&interval.to_le_bytes());
+    // ADV_NONCONN_IND, public own address, no peer
+    parameters.extend_from_slice(&[0x03, 0x00, 0x00]);
+    parameters.extend_from_slice(&[0x00; 6]);
+    // All channels, no filter
+    parameters.extend_from_slice(&[0x07, 0x00]);
+    command(LE_SET_ADVERTISING_PARAMETERS, &parameters)
+}
+
+// At most 31 bytes, the controller takes them zero padded
+pub fn set_advertising_data(data: &[u8]) -> Vec<u8> {
+    let length = data.len().min(31);
+    let mut parameters = [0u8; 32];
+    parameters[0] = length as u8;
+    parameters[1..1 + length].copy_from_slice(&data[..length]);
+    command(LE_SET_ADVERTISING_DATA, &parameters)
+}
+
+pub fn set_advertise_enable(enable: bool) -> Vec<u8> {
+    command(LE_SET_ADVERTISE_ENABLE, &[enable as u8])
+}
+
+// Passive scan, interval and window in units of 0.625 ms
+pub fn set_scan_parameters(interval: u16, window: u16) -> Vec<u8> {
+    let mut parameters = Vec::with_capacity(7);
+    parameters.push(0x00);
+    parameters.extend_from_slice(&interval.to_le_bytes());
+    parameters.extend_from_slice(&window.to_le_bytes());
+    // Public own address, accept all advertisers
+    parameters.extend_from_slice(&[0x00, 0x00]);
+    command(LE_SET_SCAN_PARAMETERS, &parameters)
+}
+
+// Duplicates are not filtered, every advertisement carries a fresh RSSI
+pub fn set_scan_enable(enable: bool) -> Vec<u8> {
+    command(LE_SET_SCAN_ENABLE, &[enable as u8, 0x00])
+}
+
+// Reports of every LE Advertising Report event in `buffer`, which holds H4
+// packets back to back as the connector reads them, and how many bytes of
+// whole packets were walked. A packet cut off by the end of the read is left
+// for the next one; anything that is not an event drops the rest
+pub fn parse_advertising_reports(buffer: &[u8]) -> (Vec<AdvertisingReport>, usize) {
+    let mut reports = Vec::new();
+    let mut offset = 0;
+    while offset < buffer.len() {
+        let (code, length) = match &buffer[offset..] {
+            [EVENT, code, length, ..] => (*code, *length as usize),
+            [EVENT, ..] => break,
+            _ => return (reports, buffer.len()),
+        };
+        let parameters = match buffer.get(offset + 3..offset + 3 + length) {
+            Some(parameters) => parameters,
+            None => break,
+        };
+        if code == LE_META_EVENT {
+            if let [LE_ADVERTISING_REPORT, count, events @ ..] = parameters {
+                parse_reports(events, *count as usize, &mut reports);
+            }
+        }
+        offset += 3 + length;
+    }
+    (reports, offset)
+}
+
+// Controllers lay each report out whole, one after the other: event type,
+// address type, address, data length, data, RSSI. A truncated one ends the
+// walk
+fn parse_reports(mut events: &[u8], count: usize, reports: &mut Vec<AdvertisingReport>) {
+    for _ in 0..count {
+        let length = match events.get(8) {
+            Some(length) => *length as usize,
+            None => return,
+        };
+        let rssi = match events.get(9 + length) {
+            Some(rssi) => *rssi as i8,
+            None => return,
+        };
+        let mut address: [u8; 6] = events[2..8].try_into().unwrap_or([0; 6]);
+        address.reverse();
+        reports.push(AdvertisingReport {
+            address: address,
+            rssi: rssi,
+            data: events[9..9 + length].to_vec(),
+        });
+        events = &events[10 + length..];
+    }
+}
+
+// "ibeacon" or "eddystone" when the advertising data is one of those frames
+pub fn beacon_kind(data: &[u8]) -> Option<&'static str> {
+    let mut rest = data;
+    while let [length, ad_type, ..] = rest {
+        let length = *length as usize;
+        if length == 0 || rest.len() < length + 1 {
+            break;
+        }
+        let value = &rest[2..length + 1];
+        if *ad_type == AD_MANUFACTURER_DATA && value.starts_with(&IBEACON_PREFIX) {
+            return Some("ibeacon");
+        }
+        if *ad_type == AD_SERVICE_DATA_16 && value.starts_with(&EDDYSTONE_UUID) {
+            return Some("eddystone");
+        }
+        rest = &rest[length + 1..];
+    }
+    None
+}
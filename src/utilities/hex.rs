@@ -0,0 +1,29 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+pub fn decode(value: &str) -> Option<Vec<u8>> {
+    let value = value.trim();
+    if value.len() % 2 != 0 {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+// Accepts "aa:bb:cc:dd:ee:ff" or "aabbccddeeff"
+pub fn parse_mac(value: &str) -> Option<[u8; 6]> {
+    let bytes = decode(&value.replace(':', ""))?;
+    bytes.try_into().ok()
+}
+
+// 128-bit ESP-NOW keys written as 32 hex characters
+pub fn parse_key(value: &str) -> Option<[u8; 16]> {
+    decode(value)?.try_into().ok()
+}
+
+pub fn mac_to_string(mac: &[u8; 6]) -> String {
+    format!("{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}", mac[0], mac[1], mac[2], mac[3], mac[4], mac[5])
+}
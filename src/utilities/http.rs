@@ -0,0 +1,96 @@
+use alloc::format;
+use alloc::string::String;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequestLine<'a> {
+    pub method: &'a str,
+    pub path: &'a str,
+    pub query: &'a str,
+}
+
+// Parses "GET /export?from=1&to=2 HTTP/1.1" out of a raw request
+pub fn parse_request_line(request: &str) -> Option<HttpRequestLine<'_>> {
+    let line = request.lines().next()?;
+    let mut parts = line.split(' ');
+    let method = parts.next()?;
+    let target = parts.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    Some(HttpRequestLine {
+        method: method,
+        path: path,
+        query: query,
+    })
+}
+
+pub fn query_param<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(name, _)| *name == key)
+        .map(|(_, value)| value)
+}
+
+pub fn status_response(status: u16, reason: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, reason, body.len(), body
+    )
+}
+
+pub fn json_response(body: &str) -> String {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(), body
+    )
+}
+
+// Response head for a body streamed with chunked transfer encoding
+pub fn chunked_header(content_type: &str) -> String {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n",
+        content_type
+    )
+}
+
+pub fn chunk(data: &str) -> String {
+    format!("{:x}\r\n{}\r\n", data.len(), data)
+}
+
+pub const LAST_CHUNK: &str = "0\r\n\r\n";
+
+// Status code from "HTTP/1.1 201 Created"
+pub fn parse_status(response: &[u8]) -> Option<u16> {
+    let line = response.split(|byte| *byte == b'\r').next()?;
+    let line = core::str::from_utf8(line).ok()?;
+    line.split(' ').nth(1)?.parse().ok()
+}
+
+// What follows the headers, None until they are complete
+pub fn body_of(response: &[u8]) -> Option<&[u8]> {
+    let end = response.windows(4).position(|window| window == b"\r\n\r\n")?;
+    Some(&response[end + 4..])
+}
+
+// Seconds from a Retry-After header; the HTTP-date form is not understood
+pub fn retry_after(response: &[u8]) -> Option<u64> {
+    let body = body_of(response).unwrap_or(&[]);
+    let headers = core::str::from_utf8(&response[..response.len() - body.len()]).ok()?;
+    headers.split("\r\n").find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.eq_ignore_ascii_case("retry-after").then(|| value.trim().parse::<u64>().ok())?
+    })
+}
+
+// The headers are in, and as much of the body as Content-Length announces
+pub fn response_complete(response: &[u8]) -> bool {
+    let body = match body_of(response) {
+        Some(body) => body,
+        None => return false,
+    };
+    let headers = core::str::from_utf8(&response[..response.len() - body.len()]).unwrap_or("");
+    let length = headers.split("\r\n").find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.eq_ignore_ascii_case("content-length").then(|| value.trim().parse::<usize>().ok())?
+    });
+    body.len() >= length.unwrap_or(0)
+}
@@ -0,0 +1,67 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::net::Ipv4Addr;
+
+use crate::dtos::configurations::network::StaticIpv4ConfigDTO;
+
+// Parses "192.168.10.50/24" (or a bare address with a separate netmask)
+// and checks that the gateway is reachable on-link
+pub fn parse_static(
+    address: &str,
+    netmask: Option<&str>,
+    gateway: &str,
+    dns_servers: &str,
+) -> Result<StaticIpv4ConfigDTO, String> {
+    let (address, prefix_length) = match (address.split_once('/'), netmask) {
+        (Some((address, prefix)), _) => (
+            parse_address(address)?,
+            prefix.parse::<u8>().map_err(|_| format!("Invalid prefix length {}", prefix))?,
+        ),
+        (None, Some(netmask)) => (parse_address(address)?, prefix_from_netmask(parse_address(netmask)?)?),
+        (None, None) => return Err(String::from("Static IP needs a /prefix or a netmask")),
+    };
+    if !(1..=30).contains(&prefix_length) {
+        return Err(format!("Prefix length /{} leaves no usable hosts", prefix_length));
+    }
+    let gateway = parse_address(gateway)?;
+    let dns_servers = dns_servers
+        .split(',')
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .map(parse_address)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mask = u32::MAX << (32 - prefix_length);
+    let network = u32::from(address) & mask;
+    let host = u32::from(address) & !mask;
+    if host == 0 || host == !mask {
+        return Err(format!("{} is the network or broadcast address", address));
+    }
+    if u32::from(gateway) & mask != network {
+        return Err(format!("Gateway {} is outside {}/{}", gateway, address, prefix_length));
+    }
+    if gateway == address {
+        return Err(String::from("Gateway must differ from the device address"));
+    }
+    Ok(StaticIpv4ConfigDTO {
+        address: address,
+        prefix_length: prefix_length,
+        gateway: gateway,
+        dns_servers: dns_servers,
+    })
+}
+
+fn parse_address(value: &str) -> Result<Ipv4Addr, String> {
+    value.trim().parse().map_err(|_| format!("Invalid IPv4 address {}", value))
+}
+
+// Netmasks must be contiguous ones, e.g. 255.255.255.0 -> 24
+fn prefix_from_netmask(netmask: Ipv4Addr) -> Result<u8, String> {
+    let bits = u32::from(netmask);
+    let prefix = bits.leading_ones();
+    if bits.checked_shl(prefix).unwrap_or(0) != 0 {
+        return Err(format!("Netmask {} is not contiguous", netmask));
+    }
+    Ok(prefix as u8)
+}
@@ -0,0 +1,34 @@
+use alloc::format;
+use alloc::string::String;
+use core::net::Ipv6Addr;
+
+use crate::dtos::configurations::network::StaticIpv6ConfigDTO;
+
+// Parses "2001:db8::20/64" and an optional default router; a global
+// address without a router only reaches its own prefix
+pub fn parse_static(address: &str, gateway: Option<&str>) -> Result<StaticIpv6ConfigDTO, String> {
+    let (address, prefix) = address
+        .split_once('/')
+        .ok_or_else(|| String::from("Static IPv6 needs a /prefix"))?;
+    let address = parse_address(address)?;
+    let prefix_length = prefix.parse::<u8>().map_err(|_| format!("Invalid prefix length {}", prefix))?;
+    if !(1..=128).contains(&prefix_length) {
+        return Err(format!("Invalid prefix length /{}", prefix_length));
+    }
+    if address.is_unspecified() || address.is_multicast() {
+        return Err(format!("{} cannot be a host address", address));
+    }
+    let gateway = gateway.map(parse_address).transpose()?;
+    if gateway == Some(address) {
+        return Err(String::from("Gateway must differ from the device address"));
+    }
+    Ok(StaticIpv6ConfigDTO {
+        address: address,
+        prefix_length: prefix_length,
+        gateway: gateway,
+    })
+}
+
+fn parse_address(value: &str) -> Result<Ipv6Addr, String> {
+    value.trim().parse().map_err(|_| format!("Invalid IPv6 address {}", value))
+}
@@ -0,0 +1,196 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::constants::ir::IrConstant;
+use crate::dtos::configurations::ir::IrCodeDTO;
+use crate::enums::ir_protocol::IrProtocol;
+
+// Frames are handled as (mark, duration in µs) pulses, mark meaning the
+// carrier is on; converting to and from RMT pulse codes is the caller's
+
+fn near(actual: u32, nominal: u32) -> bool {
+    let tolerance = nominal * IrConstant::TOLERANCE_PERCENT / 100;
+    actual.abs_diff(nominal) <= tolerance
+}
+
+pub fn encode(code: &IrCodeDTO, toggle: bool) -> Vec<(bool, u32)> {
+    match code.protocol {
+        IrProtocol::Nec => encode_nec(code.address, code.command as u8),
+        IrProtocol::Rc5 => encode_rc5(code.address as u8, code.command as u8, toggle),
+    }
+}
+
+// None for noise, unknown protocols and NEC repeat frames
+pub fn decode(pulses: &[(bool, u32)]) -> Option<IrCodeDTO> {
+    decode_nec(pulses).or_else(|| decode_rc5(pulses))
+}
+
+fn encode_nec(address: u16, command: u8) -> Vec<(bool, u32)> {
+    // Addresses above 0xff are extended NEC, without the inverted byte
+    let address_bytes = if address > 0xff {
+        address.to_le_bytes()
+    } else {
+        [address as u8, !(address as u8)]
+    };
+    let bytes = [address_bytes[0], address_bytes[1], command, !command];
+    let mut pulses = Vec::with_capacity(68);
+    pulses.push((true, IrConstant::NEC_LEADER_MARK_US));
+    pulses.push((false, IrConstant::NEC_LEADER_SPACE_US));
+    for byte in bytes {
+        for bit in 0..8 {
+            pulses.push((true, IrConstant::NEC_BIT_MARK_US));
+            let one = byte >> bit & 1 == 1;
+            pulses.push((false, if one { IrConstant::NEC_ONE_SPACE_US } else { IrConstant::NEC_ZERO_SPACE_US }));
+        }
+    }
+    pulses.push((true, IrConstant::NEC_BIT_MARK_US));
+    pulses
+}
+
+fn decode_nec(pulses: &[(bool, u32)]) -> Option<IrCodeDTO> {
+    match pulses {
+        [(true, mark), (false, space), rest @ ..]
+            if near(*mark, IrConstant::NEC_LEADER_MARK_US) && near(*space, IrConstant::NEC_LEADER_SPACE_US) =>
+        {
+            // 32 bits, a mark and a space each
+            if rest.len() < 64 {
+                return None;
+            }
+            let mut bits: u32 = 0;
+            for (index, pair) in rest.chunks(2).take(32).enumerate() {
+                match pair {
+                    [(true, mark), (false, space)] if near(*mark, IrConstant::NEC_BIT_MARK_US) => {
+                        if near(*space, IrConstant::NEC_ONE_SPACE_US) {
+                            bits |= 1 << index;
+                        } else if !near(*space, IrConstant::NEC_ZERO_SPACE_US) {
+                            return None;
+                        }
+                    },
+                    _ => return None,
+                }
+            }
+            let [low, high, command, inverted] = bits.to_le_bytes();
+            if command != !inverted {
+                return None;
+            }
+            let address = if high == !low { low as u16 } else { u16::from_le_bytes([low, high]) };
+            Some(IrCodeDTO {
+                protocol: IrProtocol::Nec,
+                address: address,
+                command: command as u16,
+            })
+        },
+        _ => None,
+    }
+}
+
+// 14 Manchester bits: two start bits, the second doubling as the inverted
+// bit 6 of the command, the toggle bit, 5 address and 6 command bits. A
+// one is a space then a mark, a zero the opposite
+fn encode_rc5(address: u8, command: u8, toggle: bool) -> Vec<(bool, u32)> {
+    let mut bits: Vec<bool> = Vec::with_capacity(14);
+    bits.push(true);
+    bits.push(command & 0x40 == 0);
+    bits.push(toggle);
+    bits.extend((0..5).rev().map(|bit| address >> bit & 1 == 1));
+    bits.extend((0..6).rev().map(|bit| command >> bit & 1 == 1));
+
+    let mut pulses: Vec<(bool, u32)> = Vec::with_capacity(28);
+    for bit in bits {
+        for mark in [!bit, bit] {
+            match pulses.last_mut() {
+                Some((last, duration)) if *last == mark => *duration += IrConstant::RC5_HALF_BIT_US,
+                // The leading space of the first start bit is just idle
+                None if !mark => {},
+                _ => pulses.push((mark, IrConstant::RC5_HALF_BIT_US)),
+            }
+        }
+    }
+    pulses
+}
+
+fn decode_rc5(pulses: &[(bool, u32)]) -> Option<IrCodeDTO> {
+    // Back to half bits, the idle space before the first mark included
+    let mut halves: Vec<bool> = Vec::with_capacity(28);
+    halves.push(false);
+    for (mark, duration) in pulses {
+        let count = if near(*duration, IrConstant::RC5_HALF_BIT_US) {
+            1
+        } else if near(*duration, 2 * IrConstant::RC5_HALF_BIT_US) {
+            2
+        } else {
+            return None;
+        };
+        halves.extend((0..count).map(|_| *mark));
+    }
+    // A trailing zero ends in a space that merges into idle
+    if halves.len() == 27 {
+        halves.push(false);
+    }
+    if halves.len() != 28 {
+        return None;
+    }
+    let mut bits: u16 = 0;
+    for pair in halves.chunks(2) {
+        let bit = match pair {
+            [false, true] => 1,
+            [true, false] => 0,
+            _ => return None,
+        };
+        bits = bits << 1 | bit;
+    }
+    if bits >> 13 != 1 {
+        return None;
+    }
+    let field = bits >> 12 & 1;
+    Some(IrCodeDTO {
+        protocol: IrProtocol::Rc5,
+        address: bits >> 6 & 0x1f,
+        command: (bits & 0x3f) | if field == 0 { 0x40 } else { 0 },
+    })
+}
+
+// "nec:0x04:0x08": protocol, address and command, decimal or hex
+pub fn parse_code(value: &str) -> Option<IrCodeDTO> {
+    let mut parts = value.split(':').map(|part| part.trim());
+    let protocol = IrProtocol::parse(parts.next()?)?;
+    let address = parse_number(parts.next()?)?;
+    let command = parse_number(parts.next()?)?;
+    let fits = match protocol {
+        IrProtocol::Nec => command <= 0xff,
+        IrProtocol::Rc5 => address <= 0x1f && command <= 0x7f,
+    };
+    if parts.next().is_some() || !fits {
+        return None;
+    }
+    Some(IrCodeDTO {
+        protocol: protocol,
+        address: address,
+        command: command,
+    })
+}
+
+// "ac_off=nec:0x04:0x08,projector_on=rc5:0:12"
+pub fn parse_codes(value: &str) -> Option<BTreeMap<String, IrCodeDTO>> {
+    value
+        .split(',')
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (name, code) = entry.split_once('=')?;
+            let name = name.trim();
+            if name.is_empty() {
+                return None;
+            }
+            Some((name.to_string(), parse_code(code)?))
+        })
+        .collect()
+}
+
+fn parse_number(value: &str) -> Option<u16> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u16::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
@@ -0,0 +1,379 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::dtos::event::envelope::EventEnvelopeDTO;
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::dtos::measurement::field_quality::FieldQualityDTO;
+use crate::dtos::measurement::unit::UnitDTO;
+use crate::dtos::telemetry::clock_diagnostics::ClockDiagnosticsDTO;
+use crate::dtos::telemetry::device_info::DeviceInfoDTO;
+use crate::dtos::telemetry::heartbeat::HeartbeatDTO;
+use crate::dtos::telemetry::sensor_stats::SensorStatsDTO;
+use crate::dtos::telemetry::service_health::ServiceHealthDTO;
+use crate::dtos::telemetry::soak_stats::SoakStatsDTO;
+use crate::dtos::telemetry::upload_stats::UploadStatsDTO;
+use crate::dtos::telemetry::wifi_diagnostics::WifiDiagnosticsDTO;
+use crate::enums::value::Value;
+use crate::utilities::hex;
+
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+pub fn value_to_json(value: &Value) -> String {
+    match value {
+        Value::String(value) => escape(value),
+        Value::Float(value) if value.is_finite() => format!("{}", value),
+        Value::Float(_) => String::from("null"),
+        Value::Integer(value) => format!("{}", value),
+        Value::Boolean(value) => format!("{}", value),
+    }
+}
+
+fn values_to_json(values: &BTreeMap<String, Value>) -> String {
+    let mut object = String::from("{");
+    for (index, (field, value)) in values.iter().enumerate() {
+        if index > 0 {
+            object.push(',');
+        }
+        object.push_str(&format!("{}:{}", escape(field), value_to_json(value)));
+    }
+    object.push('}');
+    object
+}
+
+pub fn envelope_to_json(envelope: &MeasurementEnvelopeDTO) -> String {
+    format!(
+        "{{\"id\":{},\"timestamp\":{},{}\"device_urn\":{},\"location_urn\":{},\"data\":{},\"units\":{}{}{}}}",
+        escape(&envelope.id),
+        envelope.timestamp,
+        envelope
+            .relative_ms_since_boot
+            .map_or(String::new(), |relative| format!("\"relative_ms_since_boot\":{},", relative)),
+        escape(&envelope.device_urn),
+        escape(&envelope.location_urn),
+        values_to_json(&envelope.data),
+        units_to_json(&envelope.units),
+        si_scales_to_json(&envelope.units),
+        // Left out while every field is GOOD, as most envelopes are
+        if envelope.quality.is_empty() {
+            String::new()
+        } else {
+            format!(",\"quality\":{}", quality_to_json(&envelope.quality))
+        }
+    )
+}
+
+// Marks a serialized envelope as standing in for `factor` envelopes that
+// were thinned out of the persistent queue, as a leading "downsample" key.
+// An envelope thinned again gets the product of both factors
+pub fn downsampled(envelope: &[u8], factor: u32) -> Vec<u8> {
+    const KEY: &[u8] = b"{\"downsample\":";
+    let (previous, rest) = match envelope.strip_prefix(KEY) {
+        Some(rest) => {
+            let digits = rest.iter().take_while(|byte| byte.is_ascii_digit()).count();
+            let previous = core::str::from_utf8(&rest[..digits])
+                .ok()
+                .and_then(|digits| digits.parse::<u32>().ok())
+                .unwrap_or(1);
+            // Past the digits and their trailing comma
+            (previous, &rest[(digits + 1).min(rest.len())..])
+        },
+        None => (1, envelope.get(1..).unwrap_or_default()),
+    };
+    let mut out = Vec::with_capacity(envelope.len() + 16);
+    out.extend_from_slice(KEY);
+    out.extend_from_slice(format!("{},", previous.saturating_mul(factor)).as_bytes());
+    out.extend_from_slice(rest);
+    out
+}
+
+// The "timestamp" of a serialized envelope or event without parsing the
+// rest, which comes before any user data that could contain the key
+pub fn timestamp_of(json: &[u8]) -> Option<u64> {
+    const KEY: &[u8] = b"\"timestamp\":";
+    let start = json.windows(KEY.len()).position(|window| window == KEY)? + KEY.len();
+    let digits = json[start..].iter().take_while(|byte| byte.is_ascii_digit()).count();
+    core::str::from_utf8(&json[start..start + digits]).ok()?.parse().ok()
+}
+
+// The "id" of a serialized envelope or event, the first string key
+pub fn id_of(json: &[u8]) -> Option<&str> {
+    const KEY: &[u8] = b"\"id\":\"";
+    let start = json.windows(KEY.len()).position(|window| window == KEY)? + KEY.len();
+    let length = json[start..].iter().position(|&byte| byte == b'"')?;
+    core::str::from_utf8(&json[start..start + length]).ok()
+}
+
+// The "cursor" of an upload response: the id of the last envelope the
+// server has stored
+pub fn cursor_of(json: &[u8]) -> Option<&str> {
+    const KEY: &[u8] = b"\"cursor\":\"";
+    let start = json.windows(KEY.len()).position(|window| window == KEY)? + KEY.len();
+    let length = json[start..].iter().position(|&byte| byte == b'"')?;
+    core::str::from_utf8(&json[start..start + length]).ok()
+}
+
+// The "relative_ms_since_boot" of a serialized envelope or event, which
+// directly follows its "timestamp"
+pub fn relative_ms_of(json: &[u8]) -> Option<u64> {
+    const KEY: &[u8] = b"\"timestamp\":0,\"relative_ms_since_boot\":";
+    let start = json.windows(KEY.len()).position(|window| window == KEY)? + KEY.len();
+    let digits = json[start..].iter().take_while(|byte| byte.is_ascii_digit()).count();
+    core::str::from_utf8(&json[start..start + digits]).ok()?.parse().ok()
+}
+
+// Replaces the boot-relative stamp of a serialized envelope or event with
+// `timestamp`, as `resolve_timestamps` does before serializing
+pub fn restamped(json: &[u8], timestamp: u64) -> Option<Vec<u8>> {
+    const KEY: &[u8] = b"\"timestamp\":0,\"relative_ms_since_boot\":";
+    let start = json.windows(KEY.len()).position(|window| window == KEY)?;
+    let digits = json[start + KEY.len()..].iter().take_while(|byte| byte.is_ascii_digit()).count();
+    // Past the digits and their trailing comma
+    let rest = &json[(start + KEY.len() + digits + 1).min(json.len())..];
+    let mut out = Vec::with_capacity(json.len());
+    out.extend_from_slice(&json[..start]);
+    out.extend_from_slice(format!("\"timestamp\":{},", timestamp).as_bytes());
+    out.extend_from_slice(rest);
+    Some(out)
+}
+
+fn units_to_json(units: &BTreeMap<String, UnitDTO>) -> String {
+    let mut object = String::from("{");
+    for (index, (field, unit)) in units.iter().enumerate() {
+        if index > 0 {
+            object.push(',');
+        }
+        object.push_str(&format!("{}:{}", escape(field), escape(unit.unit)));
+    }
+    object.push('}');
+    object
+}
+
+// Only the fields not already in an SI unit, the key is left out otherwise
+fn si_scales_to_json(units: &BTreeMap<String, UnitDTO>) -> String {
+    let mut object = String::new();
+    for (field, si_scale) in units.iter().filter_map(|(field, unit)| unit.si_scale.map(|si_scale| (field, si_scale))) {
+        object.push(if object.is_empty() { '{' } else { ',' });
+        object.push_str(&format!("{}:{}", escape(field), si_scale));
+    }
+    if object.is_empty() {
+        return object;
+    }
+    object.push('}');
+    format!(",\"si_scale\":{}", object)
+}
+
+fn quality_to_json(quality: &BTreeMap<String, FieldQualityDTO>) -> String {
+    let mut object = String::from("{");
+    for (index, (field, field_quality)) in quality.iter().enumerate() {
+        if index > 0 {
+            object.push(',');
+        }
+        object.push_str(&format!(
+            "{}:{{\"quality\":{},\"raw\":{}}}",
+            escape(field),
+            escape(field_quality.quality.as_str()),
+            field_quality.raw.as_ref().map_or(String::from("null"), value_to_json)
+        ));
+    }
+    object.push('}');
+    object
+}
+
+pub fn event_to_json(event: &EventEnvelopeDTO) -> String {
+    format!(
+        "{{\"id\":{},\"event\":{},\"timestamp\":{},{}\"device_urn\":{},\"location_urn\":{},\"sensor_urn\":{},\"detail\":{}}}",
+        escape(&event.id),
+        escape(event.kind.as_str()),
+        event.timestamp,
+        event
+            .relative_ms_since_boot
+            .map_or(String::new(), |relative| format!("\"relative_ms_since_boot\":{},", relative)),
+        escape(&event.device_urn),
+        escape(&event.location_urn),
+        escape(&event.sensor_urn),
+        values_to_json(&event.detail)
+    )
+}
+
+// Batched uploads are a plain JSON array of envelopes
+pub fn envelopes_to_json_array(envelopes: &[MeasurementEnvelopeDTO]) -> String {
+    let mut array = String::from("[");
+    for (index, envelope) in envelopes.iter().enumerate() {
+        if index > 0 {
+            array.push(',');
+        }
+        array.push_str(&envelope_to_json(envelope));
+    }
+    array.push(']');
+    array
+}
+
+pub fn heartbeat_to_json(heartbeat: &HeartbeatDTO) -> String {
+    format!(
+        "{{\"timestamp\":{},\"device_urn\":{},\"location_urn\":{},\"uptime_s\":{},\"queue_depth\":{},\"dropped\":{{\"periodic\":{},\"state_change\":{},\"alert\":{}}},\"device\":{},\"clock\":{},\"wifi\":{},\"services\":{},\"sensors\":{},\"boot\":{{\"stage\":{},\"error\":{},\"previous_stage\":{}}}}}",
+        heartbeat.timestamp,
+        escape(&heartbeat.device_urn),
+        escape(&heartbeat.location_urn),
+        heartbeat.uptime_s,
+        heartbeat.queue_depth,
+        heartbeat.dropped_periodic,
+        heartbeat.dropped_state_change,
+        heartbeat.dropped_alert,
+        device_info_to_json(&heartbeat.device),
+        clock_diagnostics_to_json(&heartbeat.clock),
+        heartbeat.wifi.as_ref().map_or(String::from("null"), wifi_diagnostics_to_json),
+        services_to_json(&heartbeat.services),
+        sensor_stats_to_json(&heartbeat.sensors),
+        escape(heartbeat.boot_stage.as_str()),
+        heartbeat.boot_error.as_deref().map_or(String::from("null"), escape),
+        heartbeat.previous_boot_stage.map_or(String::from("null"), |stage| escape(stage.as_str()))
+    )
+}
+
+pub fn device_info_to_json(device: &DeviceInfoDTO) -> String {
+    format!(
+        "{{\"firmware_version\":{},\"git_hash\":{},\"build_timestamp\":{},\"esp_hal_version\":{},\"chip_model\":{},\"chip_revision\":{},\"flash_size_bytes\":{},\"psram_size_bytes\":{},\"mac\":{},\"serial\":{}}}",
+        escape(device.firmware_version),
+        escape(device.git_hash),
+        device.build_timestamp,
+        escape(device.esp_hal_version),
+        escape(device.chip_model),
+        device.chip_revision,
+        device.flash_size_bytes,
+        device.psram_size_bytes,
+        escape(&hex::mac_to_string(&device.mac)),
+        device.serial.as_deref().map_or(String::from("null"), escape)
+    )
+}
+
+pub fn services_to_json(services: &[ServiceHealthDTO]) -> String {
+    let mut array = String::from("[");
+    for (index, service) in services.iter().enumerate() {
+        if index > 0 {
+            array.push(',');
+        }
+        array.push_str(&format!(
+            "{{\"name\":{},\"state\":{},\"restarts\":{},\"last_error\":{}}}",
+            escape(&service.name),
+            escape(service.state.as_str()),
+            service.restarts,
+            service.last_error.as_deref().map_or(String::from("null"), escape)
+        ));
+    }
+    array.push(']');
+    array
+}
+
+pub fn sensor_stats_to_json(sensors: &[SensorStatsDTO]) -> String {
+    let optional = |value: Option<String>| value.unwrap_or_else(|| String::from("null"));
+    let mut array = String::from("[");
+    for (index, sensor) in sensors.iter().enumerate() {
+        if index > 0 {
+            array.push(',');
+        }
+        array.push_str(&format!(
+            "{{\"name\":{},\"successes\":{},\"failures\":{{\"error\":{},\"timeout\":{}}},\"last_success\":{},\"latency_ms\":{{\"min\":{},\"max\":{}}}}}",
+            escape(&sensor.name),
+            sensor.successes,
+            sensor.errors,
+            sensor.timeouts,
+            optional(sensor.last_success.map(|value| format!("{}", value))),
+            optional(sensor.min_latency_ms.map(|value| format!("{}", value))),
+            optional(sensor.max_latency_ms.map(|value| format!("{}", value)))
+        ));
+    }
+    array.push(']');
+    array
+}
+
+pub fn upload_stats_to_json(targets: &[UploadStatsDTO]) -> String {
+    let optional = |value: Option<u32>| value.map_or(String::from("null"), |value| format!("{}", value));
+    let mut array = String::from("[");
+    for (index, target) in targets.iter().enumerate() {
+        if index > 0 {
+            array.push(',');
+        }
+        let last = target.last.unwrap_or_default();
+        array.push_str(&format!(
+            "{{\"name\":{},\"uploads\":{},\"bytes\":{},\"total_ms\":{{\"mean\":{},\"max\":{}}},\"bytes_per_s\":{},\"last\":{{\"dns_ms\":{},\"connect_ms\":{},\"tls_ms\":{},\"request_ms\":{},\"total_ms\":{},\"bytes\":{}}}}}",
+            escape(&target.name),
+            target.uploads,
+            target.bytes,
+            target.total_ms / target.uploads.max(1) as u64,
+            target.max_total_ms,
+            target.bytes * 1000 / target.total_ms.max(1),
+            last.dns_ms,
+            last.connect_ms,
+            optional(last.tls_ms),
+            last.request_ms,
+            last.total_ms,
+            last.bytes
+        ));
+    }
+    array.push(']');
+    array
+}
+
+pub fn soak_stats_to_json(soak: &SoakStatsDTO, duration_s: u64) -> String {
+    format!(
+        "{{\"active\":{},\"completed\":{},\"started_at\":{},\"elapsed_s\":{},\"duration_s\":{},\"reads\":{},\"read_errors\":{},\"uploads\":{},\"upload_errors\":{},\"resets\":{},\"watchdog_resets\":{},\"brownout_resets\":{}}}",
+        soak.active,
+        soak.completed,
+        soak.started_at,
+        soak.elapsed_s,
+        duration_s,
+        soak.reads,
+        soak.read_errors,
+        soak.uploads,
+        soak.upload_errors,
+        soak.resets,
+        soak.watchdog_resets,
+        soak.brownout_resets
+    )
+}
+
+pub fn clock_diagnostics_to_json(clock: &ClockDiagnosticsDTO) -> String {
+    let optional = |value: Option<String>| value.unwrap_or_else(|| String::from("null"));
+    format!(
+        "{{\"source\":{},\"last_sync\":{},\"rtc_offset_s\":{},\"drift_ppm\":{},\"rtc_aging_offset\":{},\"rtc_temperature\":{}}}",
+        optional(clock.source.map(|source| escape(source.as_str()))),
+        optional(clock.last_sync.map(|timestamp| format!("{}", timestamp))),
+        optional(clock.rtc_offset_s.map(|offset| format!("{}", offset))),
+        optional(clock.drift_ppm.map(|drift| format!("{:.2}", drift))),
+        optional(clock.rtc_aging_offset.map(|aging| format!("{}", aging))),
+        optional(clock.rtc_temperature.map(|temperature| format!("{:.2}", temperature)))
+    )
+}
+
+pub fn wifi_diagnostics_to_json(wifi: &WifiDiagnosticsDTO) -> String {
+    let optional = |value: Option<String>| value.unwrap_or_else(|| String::from("null"));
+    format!(
+        "{{\"ssid\":{},\"bssid\":{},\"channel\":{},\"rssi_dbm\":{},\"connect_latency_ms\":{},\"last_disconnect_reason\":{},\"disconnects\":{},\"reconnects\":{}}}",
+        optional(wifi.ssid.as_deref().map(escape)),
+        optional(wifi.bssid.map(|bssid| escape(&hex::mac_to_string(&bssid)))),
+        optional(wifi.channel.map(|channel| format!("{}", channel))),
+        optional(wifi.rssi_dbm.map(|rssi| format!("{}", rssi))),
+        optional(wifi.connect_latency_ms.map(|latency| format!("{}", latency))),
+        optional(wifi.last_disconnect_reason.map(|reason| format!("{}", reason))),
+        wifi.disconnects,
+        wifi.reconnects
+    )
+}
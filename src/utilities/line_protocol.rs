@@ -0,0 +1,66 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::enums::value::Value;
+
+// The InfluxDB measurement every envelope is written to, its URNs as tags
+const MEASUREMENT: &str = "measurements";
+
+// Measurement names only need commas and spaces escaped
+fn escape_measurement(value: &str) -> String {
+    value.replace(',', "\\,").replace(' ', "\\ ")
+}
+
+// Tag keys, tag values and field keys also need equals signs escaped
+fn escape_key(value: &str) -> String {
+    escape_measurement(value).replace('=', "\\=")
+}
+
+fn escape_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Line protocol has no NaN or infinity, those fields are left out
+fn value_to_field(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(escape_string(value)),
+        Value::Float(value) if value.is_finite() => Some(format!("{}", value)),
+        Value::Float(_) => None,
+        Value::Integer(value) => Some(format!("{}i", value)),
+        Value::Boolean(value) => Some(format!("{}", value)),
+    }
+}
+
+// One line per envelope, written with `precision=s`:
+// measurements,device_urn=..,location_urn=.. <data>,id="..",<field>_quality=".." <timestamp>
+// Flagged fields keep their value and gain a `<field>_quality` string. An
+// envelope stamped before the clock was set goes without a timestamp, so the
+// server stamps it on arrival, and carries `relative_ms_since_boot` instead
+pub fn envelope_to_line(envelope: &MeasurementEnvelopeDTO) -> String {
+    let mut fields: Vec<String> = envelope
+        .data
+        .iter()
+        .filter_map(|(field, value)| value_to_field(value).map(|value| format!("{}={}", escape_key(field), value)))
+        .collect();
+    fields.push(format!("id={}", escape_string(&envelope.id)));
+    for (field, field_quality) in envelope.quality.iter() {
+        fields.push(format!("{}_quality={}", escape_key(field), escape_string(field_quality.quality.as_str())));
+    }
+    if let Some(relative_ms) = envelope.relative_ms_since_boot {
+        fields.push(format!("relative_ms_since_boot={}i", relative_ms));
+    }
+
+    let mut line = format!(
+        "{},device_urn={},location_urn={} {}",
+        escape_measurement(MEASUREMENT),
+        escape_key(&envelope.device_urn),
+        escape_key(&envelope.location_urn),
+        fields.join(",")
+    );
+    if envelope.relative_ms_since_boot.is_none() {
+        line.push_str(&format!(" {}", envelope.timestamp));
+    }
+    line
+}
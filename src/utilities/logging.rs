@@ -0,0 +1,14 @@
+// Log macros for code that should cost next to nothing in a defmt build.
+// With the `defmt` feature the format strings stay on the host and only
+// indices and raw arguments go out over RTT; without it these are the
+// regular `log` macros printed through esp-println. Arguments must then
+// implement both `Display` and `defmt::Format`, which primitives and
+// strings do. Code on the `log` macros is forwarded to RTT as well, see
+// services/log_filter.rs
+#[cfg(feature = "defmt")]
+pub use defmt::{debug, error, info, warn};
+#[cfg(not(feature = "defmt"))]
+pub use log::{debug, error, info, warn};
+
+#[cfg(feature = "defmt")]
+defmt::timestamp!("{=u64:ms}", embassy_time::Instant::now().as_millis());
@@ -0,0 +1,39 @@
+pub mod aqi;
+pub mod board_identity;
+pub mod bthome;
+pub mod can;
+pub mod cbor;
+pub mod compression;
+pub mod config_migration;
+pub mod csv;
+pub mod datetime;
+pub mod delta;
+#[cfg(not(test))]
+pub mod device_info;
+pub mod flash_partition;
+pub mod goertzel;
+pub mod hci;
+pub mod hex;
+pub mod i2c;
+pub mod http;
+pub mod ipv4;
+pub mod ipv6;
+pub mod ir;
+pub mod join;
+pub mod json;
+pub mod line_protocol;
+pub mod logging;
+pub mod modbus;
+pub mod mqtt;
+pub mod retention;
+pub mod schedule;
+pub mod sdi12;
+pub mod senml;
+pub mod stack;
+pub mod state_file;
+pub mod thermal;
+pub mod thresholds;
+pub mod timezone;
+pub mod units;
+pub mod url;
+pub mod websocket;
\ No newline at end of file
@@ -0,0 +1,126 @@
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use crate::dtos::configurations::modbus::ModbusRegisterDTO;
+use crate::enums::modbus_data_type::ModbusDataType;
+use crate::enums::modbus_register_kind::ModbusRegisterKind;
+
+// Set on the function code of an exception response
+const EXCEPTION_FLAG: u8 = 0x80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseError {
+    Crc,
+    // The exception code the slave answered with
+    Exception(u8),
+    Malformed,
+}
+
+// CRC-16/MODBUS, sent low byte first
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xffff;
+    for byte in data {
+        crc ^= *byte as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xa001 } else { crc >> 1 };
+        }
+    }
+    crc
+}
+
+pub fn read_request(slave: u8, function: u8, address: u16, count: u16) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(8);
+    frame.extend_from_slice(&[slave, function]);
+    frame.extend_from_slice(&address.to_be_bytes());
+    frame.extend_from_slice(&count.to_be_bytes());
+    let crc = crc16(&frame);
+    frame.extend_from_slice(&crc.to_le_bytes());
+    frame
+}
+
+// Length of the complete response once its first three bytes are in, so
+// the reader knows when to stop without waiting for the frame gap
+pub fn expected_length(header: &[u8]) -> Option<usize> {
+    match header {
+        [_, function, ..] if function & EXCEPTION_FLAG != 0 => Some(5),
+        [_, _, byte_count, ..] => Some(3 + *byte_count as usize + 2),
+        _ => None,
+    }
+}
+
+pub fn parse_read_response(frame: &[u8], slave: u8, function: u8, count: u16) -> Result<Vec<u16>, ResponseError> {
+    if frame.len() < 5 {
+        return Err(ResponseError::Malformed);
+    }
+    let (body, crc) = frame.split_at(frame.len() - 2);
+    if crc16(body).to_le_bytes() != [crc[0], crc[1]] {
+        return Err(ResponseError::Crc);
+    }
+    match body {
+        [answered, code, exception] if *answered == slave && *code == function | EXCEPTION_FLAG => {
+            Err(ResponseError::Exception(*exception))
+        },
+        [answered, code, byte_count, data @ ..]
+            if *answered == slave
+                && *code == function
+                && *byte_count as usize == data.len()
+                && data.len() == 2 * count as usize =>
+        {
+            Ok(data.chunks(2).map(|word| u16::from_be_bytes([word[0], word[1]])).collect())
+        },
+        _ => Err(ResponseError::Malformed),
+    }
+}
+
+pub fn decode(registers: &[u16], data_type: ModbusDataType) -> Option<f32> {
+    let word = |index: usize| registers.get(index).copied();
+    let double = || Some(((word(0)? as u32) << 16) | word(1)? as u32);
+    Some(match data_type {
+        ModbusDataType::U16 => word(0)? as f32,
+        ModbusDataType::I16 => word(0)? as i16 as f32,
+        ModbusDataType::U32 => double()? as f32,
+        ModbusDataType::I32 => double()? as i32 as f32,
+        ModbusDataType::F32 => f32::from_bits(double()?),
+    })
+}
+
+// "power_w=1:input:12:f32*0.001,voltage=1:holding:0:u16*0.1": field, then
+// slave, register kind, zero-based address, data type and optional scale
+pub fn parse_registers(value: &str) -> Option<Vec<ModbusRegisterDTO>> {
+    value
+        .split(',')
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (field, spec) = entry.split_once('=')?;
+            let (spec, scale) = match spec.split_once('*') {
+                Some((spec, scale)) => (spec, scale.trim().parse().ok()?),
+                None => (spec, 1.0),
+            };
+            let mut parts = spec.split(':').map(|part| part.trim());
+            let slave = parts.next()?.parse().ok()?;
+            let kind = ModbusRegisterKind::parse(parts.next()?)?;
+            let address = parse_address(parts.next()?)?;
+            let data_type = ModbusDataType::parse(parts.next()?)?;
+            if parts.next().is_some() || field.trim().is_empty() {
+                return None;
+            }
+            Some(ModbusRegisterDTO {
+                field: field.trim().to_string(),
+                slave: slave,
+                kind: kind,
+                address: address,
+                data_type: data_type,
+                scale: scale,
+            })
+        })
+        .collect()
+}
+
+// Decimal, or hex as meter manuals usually list them
+fn parse_address(value: &str) -> Option<u16> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u16::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
@@ -0,0 +1,87 @@
+use alloc::vec::Vec;
+
+// MQTT 3.1.1 control packet types (upper nibble of the fixed header)
+pub const CONNECT: u8 = 0x10;
+pub const CONNACK: u8 = 0x20;
+pub const PUBLISH: u8 = 0x30;
+pub const PUBACK: u8 = 0x40;
+pub const DISCONNECT: u8 = 0xe0;
+
+fn write_remaining_length(out: &mut Vec<u8>, mut length: usize) {
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+}
+
+fn write_string(out: &mut Vec<u8>, value: &str) {
+    out.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    out.extend_from_slice(value.as_bytes());
+}
+
+pub fn connect(client_id: &str, username: Option<&str>, password: Option<&str>, keep_alive_s: u16) -> Vec<u8> {
+    let mut body = Vec::new();
+    write_string(&mut body, "MQTT");
+    body.push(4);
+    // Clean session, plus the credential flags when present
+    let mut flags = 0x02;
+    if username.is_some() {
+        flags |= 0x80;
+    }
+    if password.is_some() {
+        flags |= 0x40;
+    }
+    body.push(flags);
+    body.extend_from_slice(&keep_alive_s.to_be_bytes());
+    write_string(&mut body, client_id);
+    if let Some(username) = username {
+        write_string(&mut body, username);
+    }
+    if let Some(password) = password {
+        write_string(&mut body, password);
+    }
+
+    let mut packet = Vec::with_capacity(body.len() + 5);
+    packet.push(CONNECT);
+    write_remaining_length(&mut packet, body.len());
+    packet.extend_from_slice(&body);
+    packet
+}
+
+// QoS 1 publish, the broker confirms with a PUBACK carrying `packet_id`
+pub fn publish(topic: &str, payload: &[u8], packet_id: u16) -> Vec<u8> {
+    let remaining = 2 + topic.len() + 2 + payload.len();
+    let mut packet = Vec::with_capacity(remaining + 5);
+    packet.push(PUBLISH | 0x02);
+    write_remaining_length(&mut packet, remaining);
+    write_string(&mut packet, topic);
+    packet.extend_from_slice(&packet_id.to_be_bytes());
+    packet.extend_from_slice(payload);
+    packet
+}
+
+pub fn disconnect() -> [u8; 2] {
+    [DISCONNECT, 0]
+}
+
+// Returns the CONNACK return code, 0 means accepted
+pub fn parse_connack(packet: &[u8]) -> Option<u8> {
+    match packet {
+        [CONNACK, 2, _, code, ..] => Some(*code),
+        _ => None,
+    }
+}
+
+pub fn parse_puback(packet: &[u8]) -> Option<u16> {
+    match packet {
+        [PUBACK, 2, high, low, ..] => Some(u16::from_be_bytes([*high, *low])),
+        _ => None,
+    }
+}
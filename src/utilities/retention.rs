@@ -0,0 +1,17 @@
+use crate::dtos::configurations::retention::RetentionConfigDTO;
+
+// "records=5000,age_s=604800,bytes=524288", any subset; empty for no limits
+pub fn parse(value: &str) -> Option<RetentionConfigDTO> {
+    let mut retention = RetentionConfigDTO::default();
+    for part in value.split(',').map(|part| part.trim()).filter(|part| !part.is_empty()) {
+        let (key, limit) = part.split_once('=')?;
+        let limit = limit.trim();
+        match key.trim() {
+            "records" => retention.max_records = Some(limit.parse().ok()?),
+            "age_s" => retention.max_age_s = Some(limit.parse().ok()?),
+            "bytes" => retention.max_bytes = Some(limit.parse().ok()?),
+            _ => return None,
+        }
+    }
+    Some(retention)
+}
@@ -0,0 +1,146 @@
+use alloc::vec::Vec;
+
+use crate::dtos::configurations::schedule::ScheduleWindowDTO;
+
+const DAY_S: i64 = 86_400;
+const DAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+// Parses ';' separated rules such as
+//   "mon-fri 07:00-19:00 every 15m; sat,sun 10:00-12:00; * 22:00-06:00"
+// An empty expression means no restriction and yields no windows
+pub fn parse(expression: &str) -> Option<Vec<ScheduleWindowDTO>> {
+    expression
+        .split(';')
+        .map(str::trim)
+        .filter(|rule| !rule.is_empty())
+        .map(parse_rule)
+        .collect()
+}
+
+fn parse_rule(rule: &str) -> Option<ScheduleWindowDTO> {
+    let mut tokens = rule.split_whitespace();
+    let weekdays = parse_days(tokens.next()?)?;
+    let (start, end) = tokens.next()?.split_once('-')?;
+    let every_s = match (tokens.next(), tokens.next()) {
+        (None, _) => None,
+        (Some("every"), Some(interval)) => Some(parse_interval(interval)?),
+        _ => return None,
+    };
+    if tokens.next().is_some() {
+        return None;
+    }
+    Some(ScheduleWindowDTO {
+        weekdays: weekdays,
+        start_minute: parse_time(start)?,
+        end_minute: parse_time(end)?,
+        every_s: every_s,
+    })
+}
+
+// "*", "mon", "mon-fri", "sat,sun" or "fri-mon"
+fn parse_days(value: &str) -> Option<u8> {
+    if value == "*" {
+        return Some(0x7f);
+    }
+    let day = |name: &str| DAY_NAMES.iter().position(|day| name.eq_ignore_ascii_case(day));
+    let mut mask = 0u8;
+    for part in value.split(',') {
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let (mut day_index, to) = (day(from)?, day(to)?);
+                loop {
+                    mask |= 1 << day_index;
+                    if day_index == to {
+                        break;
+                    }
+                    day_index = (day_index + 1) % 7;
+                }
+            },
+            None => mask |= 1 << day(part)?,
+        }
+    }
+    Some(mask)
+}
+
+// "HH:MM", 24:00 is accepted as the end of the day
+fn parse_time(value: &str) -> Option<u16> {
+    let (hours, minutes) = value.split_once(':')?;
+    let (hours, minutes): (u16, u16) = (hours.parse().ok()?, minutes.parse().ok()?);
+    match (hours, minutes) {
+        (24, 0) => Some(0),
+        (0..=23, 0..=59) => Some(hours * 60 + minutes),
+        _ => None,
+    }
+}
+
+// "30s", "15m" or "2h"
+fn parse_interval(value: &str) -> Option<u32> {
+    let (number, unit) = value.split_at(value.len().checked_sub(1)?);
+    let number: u32 = number.parse().ok().filter(|number| *number > 0)?;
+    match unit {
+        "s" => Some(number),
+        "m" => number.checked_mul(60),
+        "h" => number.checked_mul(3_600),
+        _ => None,
+    }
+}
+
+// 1970-01-01 was a Thursday
+fn weekday(day: i64) -> u8 {
+    (day + 4).rem_euclid(7) as u8
+}
+
+fn window_length_s(window: &ScheduleWindowDTO) -> i64 {
+    match (window.end_minute as i64 - window.start_minute as i64).rem_euclid(1_440) {
+        0 => DAY_S,
+        minutes => minutes * 60,
+    }
+}
+
+pub fn is_active(windows: &[ScheduleWindowDTO], local: i64) -> bool {
+    windows.is_empty() || active_window(windows, local).is_some()
+}
+
+pub fn active_window(windows: &[ScheduleWindowDTO], local: i64) -> Option<&ScheduleWindowDTO> {
+    let today = local.div_euclid(DAY_S);
+    // A window that started yesterday may still be open past midnight
+    windows.iter().find(|window| {
+        [today - 1, today].into_iter().any(|day| {
+            let start = day * DAY_S + window.start_minute as i64 * 60;
+            window.weekdays & (1 << weekday(day)) != 0 && local >= start && local < start + window_length_s(window)
+        })
+    })
+}
+
+// Seconds from `local` until the next moment sensing is due, 0 when it is
+// due right now: the next tick aligned to the window start while inside a
+// window, "every" apart or `interval_s` apart for a window without its own
+// cadence, and the next window start otherwise. None when no window is
+// configured
+pub fn next_due_in(windows: &[ScheduleWindowDTO], local: i64, interval_s: i64) -> Option<i64> {
+    let today = local.div_euclid(DAY_S);
+    let mut next: Option<i64> = None;
+    for window in windows {
+        for day in today - 1..=today + 7 {
+            if window.weekdays & (1 << weekday(day)) == 0 {
+                continue;
+            }
+            let start = day * DAY_S + window.start_minute as i64 * 60;
+            let end = start + window_length_s(window);
+            let due = if local < start {
+                start
+            } else if local < end {
+                let every = window.every_s.map_or(interval_s, |every| every as i64).max(1);
+                let tick = start + (local - start + every - 1) / every * every;
+                if tick >= end {
+                    continue;
+                }
+                tick
+            } else {
+                continue;
+            };
+            next = Some(next.map_or(due, |next| next.min(due)));
+        }
+    }
+    next.map(|due| due - local)
+}
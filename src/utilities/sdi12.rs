@@ -0,0 +1,73 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::constants::sdi12::Sdi12Constant;
+use crate::dtos::configurations::sdi12::Sdi12ProbeDTO;
+
+// "atttn" answering a start measurement command: seconds until the values
+// are ready and how many there will be
+pub fn parse_measurement_reply(reply: &str, address: char) -> Option<(u16, u8)> {
+    let rest = reply.strip_prefix(address)?;
+    if rest.len() < 4 || !rest.is_ascii() {
+        return None;
+    }
+    let seconds = rest[..3].parse().ok()?;
+    let count = rest[3..].parse().ok()?;
+    Some((seconds, count))
+}
+
+// "a+1.23-0.5+12" answering a data or continuous command. Every value
+// carries its sign, which is what separates them
+pub fn parse_values(reply: &str, address: char) -> Option<Vec<f32>> {
+    let rest = reply.strip_prefix(address)?;
+    let mut values = Vec::new();
+    let mut start = None;
+    for (index, character) in rest.char_indices() {
+        if character == '+' || character == '-' {
+            if let Some(start) = start {
+                values.push(rest[start..index].parse().ok()?);
+            }
+            start = Some(index);
+        }
+    }
+    if let Some(start) = start {
+        values.push(rest[start..].parse().ok()?);
+    }
+    Some(values)
+}
+
+// "soil=0:M:moisture/temperature/ec,rain=1:R0:rain_mm": virtual sensor
+// name, address, command and the fields of the returned values
+pub fn parse_probes(value: &str) -> Option<Vec<Sdi12ProbeDTO>> {
+    value
+        .split(',')
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (name, spec) = entry.split_once('=')?;
+            let mut parts = spec.split(':').map(|part| part.trim());
+            let address = parts.next().filter(|address| address.len() == 1)?.chars().next()?;
+            let command = parts.next().filter(|command| !command.is_empty()).unwrap_or(Sdi12Constant::DEFAULT_COMMAND);
+            let fields: Vec<String> = parts
+                .next()?
+                .split('/')
+                .map(|field| field.trim().to_string())
+                .collect();
+            let valid_command = matches!(command.as_bytes(), [b'M'] | [b'M', b'1'..=b'9'] | [b'R', b'0'..=b'9']);
+            if parts.next().is_some()
+                || name.trim().is_empty()
+                || !address.is_ascii_alphanumeric()
+                || !valid_command
+                || fields.iter().any(|field| field.is_empty())
+            {
+                return None;
+            }
+            Some(Sdi12ProbeDTO {
+                name: name.trim().to_lowercase(),
+                address: address,
+                command: command.to_string(),
+                fields: fields,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,75 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::constants::unit::UnitConstant;
+use crate::dtos::measurement::envelope::MeasurementEnvelopeDTO;
+use crate::dtos::measurement::unit::UnitDTO;
+use crate::enums::quality::Quality;
+use crate::enums::value::Value;
+use crate::utilities::json;
+
+// RFC 8428 registers SI symbols, so units with an SI scale go out converted.
+// Units SenML has no symbol for are left out, the field name still says it
+fn senml_unit(unit: &UnitDTO) -> Option<&'static str> {
+    match unit.unit {
+        UnitConstant::TEMPERATURE => Some("Cel"),
+        UnitConstant::HUMIDITY => Some("%RH"),
+        UnitConstant::PERCENT => Some("%"),
+        UnitConstant::PRESSURE | UnitConstant::INCH_OF_MERCURY => Some("Pa"),
+        UnitConstant::LUMINOSITY => Some("lx"),
+        UnitConstant::DISTANCE | UnitConstant::INCH => Some("m"),
+        UnitConstant::ACCELERATION => Some("m/s2"),
+        UnitConstant::MAGNETIC_FIELD => Some("T"),
+        UnitConstant::CO2 => Some("ppm"),
+        UnitConstant::DURATION => Some("s"),
+        UnitConstant::SOUND_LEVEL => Some("dB"),
+        UnitConstant::COUNT => Some("count"),
+        _ => None,
+    }
+}
+
+fn record(field: &str, value: &Value, unit: Option<&UnitDTO>) -> Option<String> {
+    let symbol = unit.and_then(senml_unit);
+    let si_scale = unit.and_then(|unit| unit.si_scale).filter(|_| symbol.is_some());
+    let value = match (value, si_scale) {
+        (Value::String(value), _) => format!("\"vs\":{}", json::escape(value)),
+        (Value::Boolean(value), _) => format!("\"vb\":{}", value),
+        (Value::Integer(value), None) => format!("\"v\":{}", value),
+        (Value::Integer(value), Some(si_scale)) => format!("\"v\":{}", *value as f32 * si_scale),
+        (Value::Float(value), _) if !value.is_finite() => return None,
+        (Value::Float(value), si_scale) => format!("\"v\":{}", value * si_scale.unwrap_or(1.0)),
+    };
+    Some(match symbol {
+        Some(symbol) => format!("{{\"n\":{},\"u\":{},{}}}", json::escape(field), json::escape(symbol), value),
+        None => format!("{{\"n\":{},{}}}", json::escape(field), value),
+    })
+}
+
+// A SenML pack (RFC 8428, JSON) per envelope. The base record names the
+// device, `bn` ending in a colon so names resolve to `<device_urn>:<field>`,
+// and carries the location as a string record. SenML has no quality flag,
+// so only GOOD fields go out rather than passing flagged ones off as
+// readings. Without a set clock `bt` is left out and the server takes the
+// time of arrival, as it would for a record without any time
+pub fn envelope_to_senml(envelope: &MeasurementEnvelopeDTO) -> String {
+    let base = match envelope.relative_ms_since_boot {
+        Some(_) => format!("\"bn\":{}", json::escape(&format!("{}:", envelope.device_urn))),
+        None => format!(
+            "\"bn\":{},\"bt\":{}",
+            json::escape(&format!("{}:", envelope.device_urn)),
+            envelope.timestamp
+        ),
+    };
+    let mut records: Vec<String> = Vec::new();
+    records.push(format!("{{{},\"n\":\"location_urn\",\"vs\":{}}}", base, json::escape(&envelope.location_urn)));
+    for (field, value) in envelope.data.iter() {
+        if envelope.quality(field) != Quality::Good {
+            continue;
+        }
+        if let Some(record) = record(field, value, envelope.units.get(field)) {
+            records.push(record);
+        }
+    }
+    format!("[{}]", records.join(","))
+}
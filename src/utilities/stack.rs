@@ -0,0 +1,70 @@
+use core::cell::Cell;
+
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
+
+use crate::constants::metrics::MetricsConstant;
+
+static PAINTED: Mutex<CriticalSectionRawMutex, Cell<bool>> = Mutex::new(Cell::new(false));
+
+// Bounds of the main stack of the first core from the esp-hal linker
+// script; it grows down from the start towards the end. The stack protector
+// canary sits `ESP_HAL_CONFIG_STACK_GUARD_OFFSET` bytes above the end
+extern "C" {
+    static _stack_start_cpu0: u32;
+    static _stack_end_cpu0: u32;
+    static __stack_chk_guard: u32;
+}
+
+fn bounds() -> (usize, usize) {
+    let start = core::ptr::addr_of!(_stack_start_cpu0) as usize;
+    let end = core::ptr::addr_of!(_stack_end_cpu0) as usize;
+    (start, end)
+}
+
+// Lowest word `paint` and `high_water` touch: past the canary, which
+// overwritten would fail the next stack check
+fn floor() -> usize {
+    let (start, end) = bounds();
+    let guard = core::ptr::addr_of!(__stack_chk_guard) as usize;
+    if guard >= end && guard < start {
+        guard + 4
+    } else {
+        end
+    }
+}
+
+pub fn size() -> usize {
+    let (start, end) = bounds();
+    start - end
+}
+
+// Fills the unused part of the stack with a pattern, so `high_water` can
+// later tell how deep it ever got. Call once, early in main
+pub fn paint() {
+    let marker = 0u32;
+    let top = (core::ptr::addr_of!(marker) as usize).saturating_sub(MetricsConstant::STACK_PAINT_MARGIN_BYTES) & !3;
+    let mut address = floor();
+    while address < top {
+        // Below the stack pointer, nothing lives there yet
+        unsafe { core::ptr::write_volatile(address as *mut u32, MetricsConstant::STACK_PAINT) };
+        address += 4;
+    }
+    PAINTED.lock(|painted| painted.set(true));
+}
+
+// Deepest use since `paint`, in bytes; None when it was never painted
+pub fn high_water() -> Option<usize> {
+    if !PAINTED.lock(|painted| painted.get()) {
+        return None;
+    }
+    let (start, _) = bounds();
+    let mut address = floor();
+    while address < start {
+        if unsafe { core::ptr::read_volatile(address as *const u32) } != MetricsConstant::STACK_PAINT {
+            break;
+        }
+        address += 4;
+    }
+    Some(start - address)
+}
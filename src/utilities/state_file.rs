@@ -0,0 +1,40 @@
+use alloc::vec::Vec;
+
+use log::warn;
+
+// Small state files on the data partition (queue cursor, counters, the
+// selected profile..) start with a marker byte and their layout version, so
+// a later release can convert an older layout instead of misreading it:
+// callers match on the version, one arm per layout they still read. Files
+// written before versioning have neither and are version 1, told apart by
+// their length, which a later layout must therefore not share
+const MARKER: u8 = 0xfe;
+pub const HEADER_BYTES: usize = 2;
+
+pub fn encode(version: u8, body: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_BYTES + body.len());
+    bytes.extend_from_slice(&[MARKER, version]);
+    bytes.extend_from_slice(body);
+    bytes
+}
+
+// The layout version and body, None for an empty or unrecognised file.
+// `legacy_bytes` is the length of the file before versioning, if there was
+// one
+pub fn decode(bytes: &[u8], legacy_bytes: Option<usize>) -> Option<(u8, &[u8])> {
+    match bytes {
+        _ if legacy_bytes == Some(bytes.len()) => Some((1, bytes)),
+        [MARKER, version, body @ ..] => Some((*version, body)),
+        _ => None,
+    }
+}
+
+// For the arm no layout matched: a version this firmware does not know,
+// most likely written by a newer one before a downgrade, is named and
+// treated like a missing file
+pub fn unsupported<T>(name: &str, decoded: Option<(u8, &[u8])>) -> Option<T> {
+    if let Some((version, _)) = decoded {
+        warn!("{} has layout {}, which this firmware does not read", name, version);
+    }
+    None
+}
@@ -0,0 +1,47 @@
+use alloc::vec::Vec;
+
+// Frames travel as a two byte header, rows then columns, followed by every
+// pixel row-major as a little-endian i16 in hundredths of a °C: 130 bytes
+// for an 8x8 array against more than 500 as JSON
+pub fn encode(rows: usize, columns: usize, frame: &[f32]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(2 + frame.len() * 2);
+    bytes.push(rows as u8);
+    bytes.push(columns as u8);
+    for pixel in frame {
+        let centi = (pixel * 100.0).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+        bytes.extend_from_slice(&centi.to_le_bytes());
+    }
+    bytes
+}
+
+// Minimum, maximum, mean and index of the hottest pixel; None for an empty frame
+pub fn summary(frame: &[f32]) -> Option<(f32, f32, f32, usize)> {
+    let first = *frame.first()?;
+    let mut min = first;
+    let mut max = first;
+    let mut hotspot = 0;
+    let mut sum = 0.0;
+    for (index, pixel) in frame.iter().enumerate() {
+        if *pixel < min {
+            min = *pixel;
+        }
+        if *pixel > max {
+            max = *pixel;
+            hotspot = index;
+        }
+        sum += pixel;
+    }
+    Some((min, max, sum / frame.len() as f32, hotspot))
+}
+
+// 12-bit two's complement pixel register
+pub fn pixel(low: u8, high: u8) -> f32 {
+    let raw = ((((high as u16) << 8) | low as u16) << 4) as i16 >> 4;
+    raw as f32
+}
+
+// 12-bit sign and magnitude thermistor register
+pub fn thermistor(low: u8, high: u8) -> f32 {
+    let magnitude = (((high & 0x07) as u16) << 8) | low as u16;
+    if high & 0x08 != 0 { -(magnitude as f32) } else { magnitude as f32 }
+}
@@ -0,0 +1,36 @@
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use crate::dtos::configurations::thresholds::{ThresholdStepDTO, ThresholdsDTO};
+
+// "10:VERY_DARK,50:DARK,200:DIM,EXTREME": ascending "upper:LABEL" steps,
+// the trailing bare label applies above the last one
+pub fn parse(value: &str) -> Option<ThresholdsDTO> {
+    let mut parts: Vec<&str> = value.split(',').map(|part| part.trim()).collect();
+    let above = parts.pop().filter(|above| !above.is_empty() && !above.contains(':'))?;
+    let mut steps: Vec<ThresholdStepDTO> = Vec::with_capacity(parts.len());
+    for part in parts {
+        let (upper, label) = part.split_once(':')?;
+        let upper: f64 = upper.trim().parse().ok()?;
+        let label = label.trim();
+        if label.is_empty() || steps.last().is_some_and(|last| last.upper >= upper) {
+            return None;
+        }
+        steps.push(ThresholdStepDTO {
+            upper: upper,
+            label: label.to_string(),
+        });
+    }
+    Some(ThresholdsDTO {
+        steps: steps,
+        above: above.to_string(),
+    })
+}
+
+pub fn classify(thresholds: &ThresholdsDTO, value: f64) -> &str {
+    thresholds
+        .steps
+        .iter()
+        .find(|step| value <= step.upper)
+        .map_or(thresholds.above.as_str(), |step| step.label.as_str())
+}
@@ -0,0 +1,159 @@
+use alloc::format;
+use alloc::string::String;
+
+use crate::dtos::configurations::timezone::{DstRuleDTO, TimeZoneDTO, TransitionDTO};
+use crate::utilities::datetime;
+
+pub const UTC: TimeZoneDTO = TimeZoneDTO {
+    std_offset_s: 0,
+    dst: None,
+};
+
+// Parses the POSIX TZ forms found in practice, e.g. "UTC0", "IST-5:30",
+// "<+0545>-5:45", "CET-1CEST,M3.5.0,M10.5.0/3", "EST5EDT,M3.2.0,M11.1.0".
+// Julian day rules (Jn / n) are not supported
+pub fn parse(value: &str) -> Option<TimeZoneDTO> {
+    let (rules, dst_rules) = match value.split_once(',') {
+        Some((head, rules)) => (head, Some(rules)),
+        None => (value, None),
+    };
+    let rest = skip_name(rules)?;
+    let (std_offset, rest) = parse_offset(rest)?;
+    let std_offset_s = -std_offset;
+    if rest.is_empty() {
+        return match dst_rules {
+            None => Some(TimeZoneDTO { std_offset_s: std_offset_s, dst: None }),
+            Some(_) => None,
+        };
+    }
+
+    // DST name, optionally followed by its own offset (default one hour ahead)
+    let rest = skip_name(rest)?;
+    let dst_offset_s = if rest.is_empty() {
+        std_offset_s + 3_600
+    } else {
+        -parse_offset(rest).filter(|(_, rest)| rest.is_empty())?.0
+    };
+    let (start, end) = dst_rules?.split_once(',')?;
+    Some(TimeZoneDTO {
+        std_offset_s: std_offset_s,
+        dst: Some(DstRuleDTO {
+            offset_s: dst_offset_s,
+            start: parse_transition(start)?,
+            end: parse_transition(end)?,
+        }),
+    })
+}
+
+// Zone abbreviations are 3+ letters or anything inside <>
+fn skip_name(value: &str) -> Option<&str> {
+    if let Some(rest) = value.strip_prefix('<') {
+        return rest.split_once('>').map(|(_, rest)| rest);
+    }
+    let length = value.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(value.len());
+    if length < 3 {
+        return None;
+    }
+    Some(&value[length..])
+}
+
+// "[+-]hh[:mm[:ss]]" in seconds, as written (west positive)
+fn parse_offset(value: &str) -> Option<(i32, &str)> {
+    let length = value
+        .find(|c: char| !(c.is_ascii_digit() || c == ':' || c == '+' || c == '-'))
+        .unwrap_or(value.len());
+    Some((parse_time(&value[..length])?, &value[length..]))
+}
+
+fn parse_time(value: &str) -> Option<i32> {
+    let (sign, value) = match value.as_bytes().first()? {
+        b'-' => (-1, &value[1..]),
+        b'+' => (1, &value[1..]),
+        _ => (1, value),
+    };
+    let mut seconds = 0;
+    let mut scale = 3_600;
+    for part in value.split(':') {
+        if scale == 0 {
+            return None;
+        }
+        seconds += part.parse::<i32>().ok()? * scale;
+        scale /= 60;
+    }
+    Some(sign * seconds)
+}
+
+// "Mm.w.d[/time]", the default transition time is 02:00
+fn parse_transition(value: &str) -> Option<TransitionDTO> {
+    let (date, time) = match value.split_once('/') {
+        Some((date, time)) => (date, parse_time(time)?),
+        None => (value, 7_200),
+    };
+    let mut fields = date.strip_prefix('M')?.split('.');
+    let month: u8 = fields.next()?.parse().ok()?;
+    let week: u8 = fields.next()?.parse().ok()?;
+    let weekday: u8 = fields.next()?.parse().ok()?;
+    if fields.next().is_some() || !(1..=12).contains(&month) || !(1..=5).contains(&week) || weekday > 6 {
+        return None;
+    }
+    Some(TransitionDTO {
+        month: month,
+        week: week,
+        weekday: weekday,
+        time_s: time,
+    })
+}
+
+// Local wall-clock seconds of the transition in `year`
+fn transition_local(transition: &TransitionDTO, year: i32) -> i64 {
+    let first = datetime::days_from_civil(year, transition.month, 1);
+    // 1970-01-01 was a Thursday
+    let first_weekday = (first + 4).rem_euclid(7) as u8;
+    let mut day = 1 + (transition.weekday + 7 - first_weekday) % 7 + (transition.week - 1) * 7;
+    while day > datetime::days_in_month(year, transition.month) {
+        day -= 7;
+    }
+    (first + day as i64 - 1) * 86_400 + transition.time_s as i64
+}
+
+// Offset from UTC in effect at `utc`
+pub fn offset_s(timezone: &TimeZoneDTO, utc: i64) -> i32 {
+    let dst = match &timezone.dst {
+        Some(dst) => dst,
+        None => return timezone.std_offset_s,
+    };
+    let year = datetime::from_unix((utc + timezone.std_offset_s as i64).max(0) as u64).year;
+    // The start is written in standard time, the end in daylight time
+    let start = transition_local(&dst.start, year) - timezone.std_offset_s as i64;
+    let end = transition_local(&dst.end, year) - dst.offset_s as i64;
+    let in_dst = if start < end {
+        utc >= start && utc < end
+    } else {
+        // Southern hemisphere, DST spans the new year
+        utc >= start || utc < end
+    };
+    if in_dst { dst.offset_s } else { timezone.std_offset_s }
+}
+
+pub fn to_local(timezone: &TimeZoneDTO, utc: u64) -> i64 {
+    utc as i64 + offset_s(timezone, utc as i64) as i64
+}
+
+// "2025-10-17 14:05:09+02:00", for anything a person reads
+pub fn format_local(timezone: &TimeZoneDTO, utc: u64) -> String {
+    let offset = offset_s(timezone, utc as i64);
+    let local = datetime::from_unix((utc as i64 + offset as i64).max(0) as u64);
+    let sign = if offset < 0 { '-' } else { '+' };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}{}{:02}:{:02}",
+        local.year,
+        local.month,
+        local.day,
+        local.hour,
+        local.minute,
+        local.second,
+        sign,
+        offset.abs() / 3_600,
+        offset.abs() % 3_600 / 60
+    )
+}
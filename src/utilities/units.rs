@@ -0,0 +1,37 @@
+use crate::constants::aqi::AqiConstant;
+use crate::constants::people_counter::PeopleCounterConstant;
+use crate::constants::sensor::SensorConstant;
+use crate::constants::thermal::ThermalConstant;
+use crate::constants::unit::UnitConstant;
+use crate::dtos::measurement::unit::UnitDTO;
+
+// Unit of a measurement field by its name, so the backend no longer has to
+// know which sensor reports what in which unit. Unknown fields get none
+pub fn of(field: &str) -> Option<UnitDTO> {
+    let (unit, si_scale) = match field {
+        "temperature" | SensorConstant::DS3231_TEMP => (UnitConstant::TEMPERATURE, None),
+        ThermalConstant::MIN_C | ThermalConstant::MAX_C | ThermalConstant::MEAN_C | ThermalConstant::THERMISTOR_C_FIELD => {
+            (UnitConstant::TEMPERATURE, None)
+        },
+        "humidity" => (UnitConstant::HUMIDITY, None),
+        "pressure" => (UnitConstant::PRESSURE, None),
+        "lux" => (UnitConstant::LUMINOSITY, None),
+        "distance_mm" => (UnitConstant::DISTANCE, Some(UnitConstant::DISTANCE_SI_SCALE)),
+        "temperature_f" => (UnitConstant::FAHRENHEIT, None),
+        field if field.strip_suffix("_f") == Some(SensorConstant::DS3231_TEMP) => (UnitConstant::FAHRENHEIT, None),
+        "distance_in" => (UnitConstant::INCH, Some(UnitConstant::INCH_SI_SCALE)),
+        "pressure_inhg" => (UnitConstant::INCH_OF_MERCURY, Some(UnitConstant::INCH_OF_MERCURY_SI_SCALE)),
+        AqiConstant::CO2 => (UnitConstant::CO2, None),
+        AqiConstant::PM2_5 | AqiConstant::PM10 => (UnitConstant::PARTICULATE, None),
+        AqiConstant::AQI => (UnitConstant::AQI, None),
+        "sound_level" => (UnitConstant::SOUND_LEVEL, None),
+        "voc_index" => (UnitConstant::VOC_INDEX, None),
+        PeopleCounterConstant::OCCUPANCY | PeopleCounterConstant::ENTRIES | PeopleCounterConstant::EXITS => {
+            (UnitConstant::COUNT, None)
+        },
+        // `<sensor>_age_s` from `BaseMeasurementDTO::data_or_last`
+        field if field.ends_with(SensorConstant::AGE_S) => (UnitConstant::DURATION, None),
+        _ => return None,
+    };
+    Some(UnitDTO { unit: unit, si_scale: si_scale })
+}
@@ -0,0 +1,76 @@
+use alloc::string::String;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlParts {
+    pub scheme: String,
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+}
+
+fn default_port(scheme: &str) -> Option<u16> {
+    match scheme {
+        "http" => Some(80),
+        "https" => Some(443),
+        "mqtt" => Some(1883),
+        "mqtts" => Some(8883),
+        _ => None,
+    }
+}
+
+// Splits "scheme://host[:port][/path]", the path defaults to "/"
+pub fn split(url: &str) -> Option<UrlParts> {
+    let (scheme, rest) = url.split_once("://")?;
+    let (authority, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+    // IPv6 literals are bracketed: "[2001:db8::1]:8080"
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']')?;
+        match rest.strip_prefix(':') {
+            Some(port) => (host, port.parse().ok()?),
+            None if rest.is_empty() => (host, default_port(scheme)?),
+            None => return None,
+        }
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().ok()?),
+            None => (authority, default_port(scheme)?),
+        }
+    };
+    if host.is_empty() {
+        return None;
+    }
+    Some(UrlParts {
+        scheme: String::from(scheme),
+        host: String::from(host),
+        port: port,
+        path: String::from(path),
+    })
+}
+
+// Host header form of a host, re-adding the brackets around IPv6 literals
+pub fn host_header(host: &str) -> String {
+    if host.contains(':') {
+        alloc::format!("[{}]", host)
+    } else {
+        String::from(host)
+    }
+}
+
+// RFC 3986 unreserved characters pass through, everything else is %XX
+pub fn encode_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => {
+                encoded.push('%');
+                encoded.push(char::from_digit((byte >> 4) as u32, 16).unwrap().to_ascii_uppercase());
+                encoded.push(char::from_digit((byte & 0xf) as u32, 16).unwrap().to_ascii_uppercase());
+            },
+        }
+    }
+    encoded
+}
@@ -0,0 +1,74 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use sha1::{Digest, Sha1};
+
+// RFC 6455 handshake GUID
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+const FIN: u8 = 0x80;
+
+pub fn base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let word = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(BASE64[(word >> (18 - 6 * index) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+// Value for the Sec-WebSocket-Accept response header
+pub fn accept_key(client_key: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(client_key.trim().as_bytes());
+    hasher.update(GUID.as_bytes());
+    base64(&hasher.finalize())
+}
+
+// Header lookup on a raw request, names compared case-insensitively
+pub fn header<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+    request
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim())
+}
+
+fn frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    // Server to client frames are never masked
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(FIN | opcode);
+    match payload.len() {
+        0..=125 => frame.push(payload.len() as u8),
+        126..=0xffff => {
+            frame.push(126);
+            frame.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+        },
+        _ => {
+            frame.push(127);
+            frame.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+        },
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+pub fn text_frame(text: &str) -> Vec<u8> {
+    frame(OPCODE_TEXT, text.as_bytes())
+}
+
+pub fn close_frame() -> Vec<u8> {
+    frame(OPCODE_CLOSE, &[])
+}